@@ -0,0 +1,128 @@
+// core/blockchain-core/src/address.rs
+//! Address derivation and checksummed text encoding.
+//!
+//! The text form is `0x` followed by 40 hex digits whose letter case encodes a
+//! checksum, EIP-55 style but keyed by SHA-256: a letter is uppercase when the
+//! matching nibble of `hash_data(lowercase_hex)` is 8 or above.
+use crate::{hash_data, Address, BlockchainError, Result};
+
+/// Uncompressed secp256k1 public key, including the 0x04 prefix
+const SECP256K1_PUBLIC_KEY_LEN: usize = 65;
+const ED25519_PUBLIC_KEY_LEN: usize = 32;
+
+/// Derivation and encoding for `Address`
+pub trait AddressExt: Sized {
+    /// Derive an address from a public key: the last 20 bytes of its hash.
+    ///
+    /// Accepts 65-byte uncompressed secp256k1 keys (hashed without the prefix)
+    /// and 32-byte ed25519 keys, as returned by `KeyPair::public_key`.
+    fn from_public_key(public_key: &[u8]) -> Result<Self>;
+
+    /// Checksummed `0x`-prefixed hex
+    fn to_checksum_hex(&self) -> String;
+
+    /// Parse checksummed hex, rejecting addresses whose letter case does not match
+    fn from_checksum_hex(s: &str) -> Result<Self>;
+}
+
+impl AddressExt for Address {
+    fn from_public_key(public_key: &[u8]) -> Result<Self> {
+        let key_material = match public_key.len() {
+            SECP256K1_PUBLIC_KEY_LEN if public_key[0] == 0x04 => &public_key[1..],
+            ED25519_PUBLIC_KEY_LEN => public_key,
+            len => return Err(invalid(format!("Unsupported public key length {}", len))),
+        };
+
+        let digest = hash_data(key_material);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&digest[12..]);
+        Ok(address)
+    }
+
+    fn to_checksum_hex(&self) -> String {
+        format!("0x{}", checksum_case(&hex::encode(self)))
+    }
+
+    fn from_checksum_hex(s: &str) -> Result<Self> {
+        let digits = s
+            .strip_prefix("0x")
+            .ok_or_else(|| invalid("Address must start with 0x".to_string()))?;
+        if digits.len() != 40 {
+            return Err(invalid(format!("Address must have 40 hex digits, got {}", digits.len())));
+        }
+
+        let bytes = hex::decode(digits).map_err(|e| invalid(format!("Invalid address hex: {}", e)))?;
+        if checksum_case(&digits.to_ascii_lowercase()) != digits {
+            return Err(invalid("Address checksum mismatch".to_string()));
+        }
+
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&bytes);
+        Ok(address)
+    }
+}
+
+/// Apply the checksum casing to lowercase hex digits
+fn checksum_case(lower: &str) -> String {
+    let digest = hash_data(lower.as_bytes());
+
+    lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (digest[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if c.is_ascii_alphabetic() && nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn invalid(reason: String) -> BlockchainError {
+    BlockchainError::InvalidAddress { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyPair, SignatureScheme};
+
+    #[test]
+    fn test_checksum_roundtrip() {
+        let address: Address = [0xab; 20];
+        let encoded = address.to_checksum_hex();
+
+        assert_eq!(encoded.len(), 42);
+        assert_eq!(encoded.to_ascii_lowercase(), format!("0x{}", "ab".repeat(20)));
+        assert_eq!(Address::from_checksum_hex(&encoded).unwrap(), address);
+    }
+
+    #[test]
+    fn test_rejects_bad_checksum() {
+        let encoded = [0xab; 20].to_checksum_hex();
+
+        // Flip the case of one letter
+        let position = encoded.find(|c: char| c.is_ascii_alphabetic() && c != 'x').unwrap();
+        let mut flipped = encoded.clone().into_bytes();
+        flipped[position] ^= 0x20;
+        let flipped = String::from_utf8(flipped).unwrap();
+
+        assert!(Address::from_checksum_hex(&flipped).is_err());
+        assert!(Address::from_checksum_hex(&encoded[2..]).is_err());
+        assert!(Address::from_checksum_hex("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_from_public_key() {
+        for scheme in [SignatureScheme::Secp256k1, SignatureScheme::Ed25519] {
+            let key = KeyPair::generate(scheme);
+            let address = Address::from_public_key(&key.public_key()).unwrap();
+            assert_eq!(address, Address::from_public_key(&key.public_key()).unwrap());
+            assert!(crate::validate_address(&address));
+        }
+
+        assert!(Address::from_public_key(&[0x04; 33]).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+// core/blockchain-core/src/admission.rs
+//! Transaction admission under relayer back-pressure.
+//!
+//! The relayer drains committed transactions to the target chain; when its
+//! queue backs up, admitting more transactions only deepens the backlog. The
+//! policy steps through levels as the queue grows: `Elevated` multiplies the
+//! minimum gas price and `Shedding` additionally rejects transactions paying
+//! less than `shed_min_fee` in total. Each level is left only once the queue
+//! falls `release_margin` below the depth that entered it, so a queue
+//! hovering at a threshold does not flap between levels.
+use crate::{Amount, BlockchainError, Result, Transaction};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackpressureConfig {
+    /// Queue depth entering `Elevated`
+    pub elevate_at: u64,
+    /// Queue depth entering `Shedding`
+    pub shed_at: u64,
+    /// How far below a threshold the queue must fall to leave its level
+    pub release_margin: u64,
+    /// Minimum gas price multiplier while `Elevated` or `Shedding`
+    pub fee_multiplier: u64,
+    /// Smallest total fee admitted while `Shedding`
+    pub shed_min_fee: Amount,
+}
+
+impl Default for BackpressureConfig {
+    fn default() -> Self {
+        Self {
+            elevate_at: 1_000,
+            shed_at: 5_000,
+            release_margin: 200,
+            fee_multiplier: 4,
+            shed_min_fee: 1_000_000,
+        }
+    }
+}
+
+impl BackpressureConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.elevate_at == 0 || self.shed_at <= self.elevate_at {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: format!(
+                    "Back-pressure thresholds must satisfy 0 < elevate_at < shed_at, got {} and {}",
+                    self.elevate_at, self.shed_at
+                ),
+            });
+        }
+        if self.release_margin >= self.elevate_at {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "Release margin must be below the elevate threshold".to_string(),
+            });
+        }
+        if self.fee_multiplier == 0 {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "Fee multiplier must be greater than 0".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdmissionLevel {
+    #[default]
+    Normal,
+    Elevated,
+    Shedding,
+}
+
+impl fmt::Display for AdmissionLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdmissionLevel::Normal => write!(f, "normal"),
+            AdmissionLevel::Elevated => write!(f, "elevated"),
+            AdmissionLevel::Shedding => write!(f, "shedding"),
+        }
+    }
+}
+
+/// What operators see of the policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AdmissionState {
+    pub level: AdmissionLevel,
+    /// Relayer queue depth at the last observation
+    pub queue_depth: u64,
+    pub min_gas_price: u64,
+    /// Smallest total fee admitted, if low-value transactions are being shed
+    pub shed_min_fee: Option<Amount>,
+    pub level_since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdmissionPolicy {
+    base_min_gas_price: u64,
+    config: BackpressureConfig,
+    level: AdmissionLevel,
+    queue_depth: u64,
+    level_since: DateTime<Utc>,
+}
+
+impl AdmissionPolicy {
+    pub fn new(base_min_gas_price: u64, config: BackpressureConfig) -> Self {
+        Self {
+            base_min_gas_price,
+            config,
+            level: AdmissionLevel::Normal,
+            queue_depth: 0,
+            level_since: Utc::now(),
+        }
+    }
+
+    pub fn level(&self) -> AdmissionLevel {
+        self.level
+    }
+
+    /// Feed the current relayer queue depth, returning the new level if it changed
+    pub fn observe(&mut self, queue_depth: u64, now: DateTime<Utc>) -> Option<AdmissionLevel> {
+        self.queue_depth = queue_depth;
+        let config = &self.config;
+
+        let mut level = self.level;
+        if queue_depth >= config.shed_at {
+            level = AdmissionLevel::Shedding;
+        } else if queue_depth >= config.elevate_at {
+            level = level.max(AdmissionLevel::Elevated);
+        }
+        if level == AdmissionLevel::Shedding && queue_depth + config.release_margin < config.shed_at {
+            level = AdmissionLevel::Elevated;
+        }
+        if level == AdmissionLevel::Elevated && queue_depth + config.release_margin < config.elevate_at {
+            level = AdmissionLevel::Normal;
+        }
+
+        if level == self.level {
+            return None;
+        }
+        self.level = level;
+        self.level_since = now;
+        Some(level)
+    }
+
+    pub fn min_gas_price(&self) -> u64 {
+        match self.level {
+            AdmissionLevel::Normal => self.base_min_gas_price,
+            AdmissionLevel::Elevated | AdmissionLevel::Shedding => {
+                self.base_min_gas_price.saturating_mul(self.config.fee_multiplier)
+            }
+        }
+    }
+
+    /// Reject `tx` if it does not meet the current admission bar
+    pub fn check(&self, tx: &Transaction) -> Result<()> {
+        if tx.is_coinbase() {
+            return Ok(());
+        }
+        let min_gas_price = self.min_gas_price();
+        if tx.gas_price < min_gas_price {
+            return Err(BlockchainError::InvalidTransaction {
+                reason: format!(
+                    "Gas price {} below the {} admission minimum {}",
+                    tx.gas_price, self.level, min_gas_price
+                ),
+            });
+        }
+        if self.level == AdmissionLevel::Shedding && tx.total_fee() < self.config.shed_min_fee {
+            return Err(BlockchainError::InvalidTransaction {
+                reason: format!(
+                    "Relayer backlog of {} batches: transactions paying under {} are not admitted",
+                    self.queue_depth, self.config.shed_min_fee
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn state(&self) -> AdmissionState {
+        AdmissionState {
+            level: self.level,
+            queue_depth: self.queue_depth,
+            min_gas_price: self.min_gas_price(),
+            shed_min_fee: (self.level == AdmissionLevel::Shedding).then_some(self.config.shed_min_fee),
+            level_since: self.level_since,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> AdmissionPolicy {
+        let config = BackpressureConfig {
+            elevate_at: 100,
+            shed_at: 500,
+            release_margin: 20,
+            fee_multiplier: 3,
+            shed_min_fee: 100_000,
+        };
+        config.validate().unwrap();
+        AdmissionPolicy::new(1, config)
+    }
+
+    #[test]
+    fn test_levels_with_hysteresis() {
+        let mut policy = policy();
+        let now = Utc::now();
+
+        assert_eq!(policy.observe(99, now), None);
+        assert_eq!(policy.observe(100, now), Some(AdmissionLevel::Elevated));
+        // Within the margin below the threshold the level holds
+        assert_eq!(policy.observe(85, now), None);
+        assert_eq!(policy.observe(600, now), Some(AdmissionLevel::Shedding));
+        assert_eq!(policy.observe(490, now), None);
+        assert_eq!(policy.observe(479, now), Some(AdmissionLevel::Elevated));
+        assert_eq!(policy.observe(10, now), Some(AdmissionLevel::Normal));
+        assert_eq!(policy.state().queue_depth, 10);
+    }
+
+    #[test]
+    fn test_check_follows_level() {
+        let mut policy = policy();
+        let cheap = Transaction::new_transfer([1; 20], [2; 20], 10, 0, 21_000, 2).unwrap();
+        let generous = Transaction::new_transfer([1; 20], [2; 20], 10, 1, 21_000, 5).unwrap();
+        policy.check(&cheap).unwrap();
+
+        policy.observe(200, Utc::now());
+        assert_eq!(policy.min_gas_price(), 3);
+        assert!(policy.check(&cheap).is_err());
+        policy.check(&generous).unwrap();
+
+        // 21_000 * 5 in fees clears the shedding bar, 21_000 * 3 does not
+        policy.observe(1_000, Utc::now());
+        policy.check(&generous).unwrap();
+        let small = Transaction::new_transfer([1; 20], [2; 20], 10, 2, 21_000, 3).unwrap();
+        assert!(policy.check(&small).is_err());
+        assert_eq!(policy.state().shed_min_fee, Some(100_000));
+    }
+}
@@ -0,0 +1,176 @@
+// core/blockchain-core/src/batch_verify.rs
+//! Verifying the signatures of a block or batch of transactions at once.
+//!
+//! Signatures are checked on `workers` threads, each taking a contiguous
+//! share of the transactions; once one fails the others stop at their next
+//! transaction, and the failure with the lowest index found is returned.
+//! Lists shorter than `min_parallel` are checked on the calling thread.
+//!
+//! Ed25519 signatures first go through a single batched verification. When
+//! it passes they are done; when it fails they are checked one by one with
+//! the rest to find the culprit. Batched verification can judge signatures
+//! with small-order components differently from single verification, so
+//! nodes that must agree on such edge cases can turn it off.
+//!
+//! Coinbases are minted, not signed, and are skipped.
+use crate::signature::{self, verify_ed25519_batch};
+use crate::{Address, AddressExt, Block, BlockchainError, Result, SignatureScheme, Transaction};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyConfig {
+    /// Threads verifying in parallel
+    pub workers: usize,
+    /// Fewest signatures worth spreading over threads
+    pub min_parallel: usize,
+    /// Try one batched verification of all ed25519 signatures first
+    pub batch_ed25519: bool,
+}
+
+impl Default for VerifyConfig {
+    fn default() -> Self {
+        Self {
+            workers: std::thread::available_parallelism().map_or(1, |workers| workers.get()),
+            min_parallel: 32,
+            batch_ed25519: true,
+        }
+    }
+}
+
+impl VerifyConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.workers == 0 {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "Signature verification needs at least 1 worker".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Check every non-coinbase transaction is signed by its sender, failing on
+/// the first bad signature found
+pub fn verify_transactions(transactions: &[Transaction], config: &VerifyConfig) -> Result<()> {
+    config.validate()?;
+    let signed: Vec<(usize, &Transaction)> =
+        transactions.iter().enumerate().filter(|(_, tx)| !tx.is_coinbase()).collect();
+
+    let (ed25519, rest): (Vec<_>, Vec<_>) = signed
+        .iter()
+        .copied()
+        .partition(|(_, tx)| config.batch_ed25519 && tx.signature_scheme() == Some(SignatureScheme::Ed25519));
+    if ed25519.len() > 1 && batch_verified(&ed25519) {
+        return verify_each(&rest, config);
+    }
+    verify_each(&signed, config)
+}
+
+impl Block {
+    /// Check the signatures of every transaction but the coinbase
+    pub fn verify_signatures(&self, config: &VerifyConfig) -> Result<()> {
+        verify_transactions(&self.transactions, config)
+    }
+}
+
+/// Whether every ed25519 signature in `transactions` verifies and is by the
+/// transaction's sender
+fn batch_verified(transactions: &[(usize, &Transaction)]) -> bool {
+    let digests: Vec<&[u8; 32]> = transactions.iter().map(|(_, tx)| &tx.hash).collect();
+    let signatures: Vec<&[u8]> = transactions.iter().map(|(_, tx)| tx.signature.as_slice()).collect();
+    verify_ed25519_batch(&digests, &signatures).is_ok_and(|keys| {
+        keys.iter()
+            .zip(transactions)
+            .all(|(key, (_, tx))| Address::from_public_key(key).is_ok_and(|signer| signer == tx.sender()))
+    })
+}
+
+fn verify_each(transactions: &[(usize, &Transaction)], config: &VerifyConfig) -> Result<()> {
+    if config.workers == 1 || transactions.len() < config.min_parallel {
+        return transactions.iter().try_for_each(|(index, tx)| verify_one(*index, tx));
+    }
+
+    let failed = AtomicBool::new(false);
+    let first: Mutex<Option<(usize, BlockchainError)>> = Mutex::new(None);
+    // Rounded up, and never 0, which `chunks` rejects
+    let share = transactions.len().saturating_sub(1) / config.workers + 1;
+    std::thread::scope(|scope| {
+        for part in transactions.chunks(share) {
+            let (failed, first) = (&failed, &first);
+            scope.spawn(move || {
+                for (index, tx) in part {
+                    if failed.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    if let Err(e) = verify_one(*index, tx) {
+                        failed.store(true, Ordering::Relaxed);
+                        let mut first = first.lock().unwrap();
+                        let earliest = match first.as_ref() {
+                            Some((earliest, _)) => index < earliest,
+                            None => true,
+                        };
+                        if earliest {
+                            *first = Some((*index, e));
+                        }
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    first.into_inner().unwrap().map_or(Ok(()), |(_, e)| Err(e))
+}
+
+fn verify_one(index: usize, tx: &Transaction) -> Result<()> {
+    let fail = |reason: String| BlockchainError::InvalidSignature {
+        reason: format!("Transaction {} (0x{}): {}", index, hex::encode(tx.hash), reason),
+    };
+    let scheme = tx.signature_scheme().ok_or_else(|| fail("Transaction is not signed".to_string()))?;
+    let public_key = signature::verify_signature(scheme, &tx.hash, &tx.signature).map_err(|e| match e {
+        BlockchainError::InvalidSignature { reason } => fail(reason),
+        other => fail(other.to_string()),
+    })?;
+    if Address::from_public_key(&public_key)? != tx.sender() {
+        return Err(fail("Signer does not match sender address".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyPair;
+
+    #[test]
+    fn test_parallel_and_batched_verification() {
+        let signed = |scheme, nonce| {
+            let key = KeyPair::generate(scheme);
+            let sender = Address::from_public_key(&key.public_key()).unwrap();
+            let mut tx = Transaction::new_transfer(sender, [9; 20], 1, nonce, 21_000, 1).unwrap();
+            tx.sign(&key);
+            tx
+        };
+        let mut transactions = vec![Transaction::new_coinbase([1; 20], 50, 1).unwrap()];
+        for nonce in 0..40 {
+            let scheme = if nonce % 2 == 0 { SignatureScheme::Ed25519 } else { SignatureScheme::Secp256k1 };
+            transactions.push(signed(scheme, nonce));
+        }
+        let configs = [
+            VerifyConfig { workers: 4, min_parallel: 8, batch_ed25519: true },
+            VerifyConfig { workers: 1, min_parallel: 8, batch_ed25519: false },
+        ];
+        for config in &configs {
+            verify_transactions(&transactions, config).unwrap();
+        }
+
+        // An ed25519 signature moved onto another transaction fails the batch, and
+        // the per-transaction pass names the earliest bad one
+        transactions[31].signature = transactions[11].signature.clone();
+        transactions[35].signature.clear();
+        for config in &configs {
+            let err = verify_transactions(&transactions, config).unwrap_err().to_string();
+            assert!(err.starts_with("Invalid signature: Transaction 31 "), "{}", err);
+        }
+        assert!(VerifyConfig { workers: 0, ..configs[0] }.validate().is_err());
+    }
+}
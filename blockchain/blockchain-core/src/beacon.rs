@@ -0,0 +1,134 @@
+// core/blockchain-core/src/beacon.rs
+//! Randomness beacon: a pseudo-random seed per block, derived from the chain.
+//!
+//! The seed of a block hashes a domain tag, the block's height, its parent's
+//! seed and its parent's hash, followed by any validator VRF outputs for the
+//! block; a proof-of-stake header carries its proposer's. Chaining through the
+//! parent's seed mixes every earlier block hash in at the cost of one hash per
+//! block. A parent from before `RANDOM_SEED_HEADER_VERSION` stands in with
+//! its hash as its seed. Every node derives the same seed, and a block whose
+//! header carries any other is rejected.
+//!
+//! The seed is deterministic, not unbiasable:
+//! - without a VRF output it is known as soon as the parent is, so the
+//!   parent's proposer learns it first and can try many parent contents,
+//!   publishing the one whose hash gives a seed it prefers; a VRF output is
+//!   fixed by the proposer's key and slot, so it leaves nothing to try;
+//! - a proposer can withhold its block, trading the seed it would have led to
+//!   for whatever the next proposer's block gives.
+//!
+//! Applications should only rely on seeds revealed after their inputs are
+//! fixed, i.e. the seed of block `h + MIN_REVEAL_DELAY` or later for inputs
+//! committed in block `h`, and should not stake more on a single seed than a
+//! proposer would forgo by withholding or grinding a block.
+use crate::{hash_data, vrf, Block, BlockHash, BlockHeight, BlockchainError, Result};
+
+/// Blocks between committing to an input and the first seed that may decide
+/// it. The seed of block `h + 1` follows from block `h`, so whoever proposes
+/// the block holding a commitment already knows it.
+pub const MIN_REVEAL_DELAY: BlockHeight = 2;
+
+/// Tags the hashed preimage so seeds never collide with other hashes
+const BEACON_DOMAIN: &[u8] = b"randomness-beacon/v1";
+
+/// Output of a validator's verifiable random function for a block
+pub type VrfOutput = [u8; 32];
+
+/// Seed of the block at `height` whose parent has `parent_seed` and
+/// `parent_hash`, mixing in `vrf_outputs` in order
+pub fn derive_seed(
+    height: BlockHeight,
+    parent_seed: &BlockHash,
+    parent_hash: &BlockHash,
+    vrf_outputs: &[VrfOutput],
+) -> BlockHash {
+    let mut preimage = Vec::with_capacity(BEACON_DOMAIN.len() + 8 + 64 + 32 * vrf_outputs.len());
+    preimage.extend_from_slice(BEACON_DOMAIN);
+    preimage.extend_from_slice(&height.to_be_bytes());
+    preimage.extend_from_slice(parent_seed);
+    preimage.extend_from_slice(parent_hash);
+    for output in vrf_outputs {
+        preimage.extend_from_slice(output);
+    }
+    hash_data(&preimage)
+}
+
+/// Seed a block may use, its own or, before `RANDOM_SEED_HEADER_VERSION`,
+/// its hash
+pub fn effective_seed(block: &Block) -> BlockHash {
+    block.header.random_seed.unwrap_or(block.hash)
+}
+
+/// Seed a child of `parent` without a VRF proof must carry
+pub fn next_seed(parent: &Block) -> BlockHash {
+    derive_seed(parent.header.height + 1, &effective_seed(parent), &parent.hash, &[])
+}
+
+/// VRF outputs `block` mixes into its seed: its proposer's, if the header
+/// carries a proof. Consensus checks the proof itself.
+pub fn vrf_outputs(block: &Block) -> Result<Vec<VrfOutput>> {
+    match block.header.vrf_proof.as_deref() {
+        Some(proof) if !proof.is_empty() => Ok(vec![vrf::proof_to_output(proof)?]),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Seed `block` must carry on top of `parent`
+pub fn seed_for(block: &Block, parent: &Block) -> Result<BlockHash> {
+    Ok(derive_seed(parent.header.height + 1, &effective_seed(parent), &parent.hash, &vrf_outputs(block)?))
+}
+
+/// Check `block`'s seed against `parent`; headers without one pass
+pub fn verify_seed(block: &Block, parent: &Block) -> Result<()> {
+    match block.header.random_seed {
+        Some(seed) if seed != seed_for(block, parent)? => Err(BlockchainError::BlockValidationFailed {
+            reason: format!("Random seed of block {} does not follow its parent", block.header.height),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RANDOM_SEED_HEADER_VERSION;
+
+    fn child(parent: &Block) -> Block {
+        Block::new(parent.header.height + 1, parent.hash, vec![], 1)
+            .unwrap()
+            .with_state_root([7; 32])
+            .unwrap()
+            .with_random_seed(next_seed(parent))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_seeds_chain_through_parents() {
+        let genesis = Block::genesis().unwrap();
+        let first = child(&genesis);
+        let second = child(&first);
+        assert_eq!(first.header.version, RANDOM_SEED_HEADER_VERSION);
+        assert_eq!(first.header.random_seed, Some(derive_seed(1, &genesis.hash, &genesis.hash, &[])));
+        assert_ne!(first.header.random_seed, second.header.random_seed);
+        verify_seed(&second, &first).unwrap();
+
+        // The seed is part of the encoded, hashed header
+        let decoded: Block = bincode::deserialize(&bincode::serialize(&second).unwrap()).unwrap();
+        assert_eq!(decoded, second);
+        let forged = second.clone().with_random_seed([1; 32]).unwrap();
+        assert_ne!(forged.hash, second.hash);
+        assert!(verify_seed(&forged, &first).is_err());
+        // Built on a different parent, the same seed no longer follows
+        assert!(verify_seed(&second, &genesis).is_err());
+
+        // VRF outputs change the seed, and their order matters
+        let (a, b) = ([1; 32], [2; 32]);
+        let base = derive_seed(3, &[4; 32], &[5; 32], &[]);
+        assert_ne!(derive_seed(3, &[4; 32], &[5; 32], &[a, b]), base);
+        assert_ne!(derive_seed(3, &[4; 32], &[5; 32], &[a, b]), derive_seed(3, &[4; 32], &[5; 32], &[b, a]));
+
+        // Headers without a seed are left alone
+        assert!(Block::genesis().unwrap().with_random_seed([0; 32]).is_err());
+        verify_seed(&Block::new(1, genesis.hash, vec![], 1).unwrap(), &first).unwrap();
+    }
+}
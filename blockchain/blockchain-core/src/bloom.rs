@@ -0,0 +1,168 @@
+// core/blockchain-core/src/bloom.rs
+use crate::{hash_data, Address, BlockchainError, Result, Transaction};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Size of a block bloom filter in bytes (2048 bits)
+pub const BLOOM_BYTES: usize = 256;
+
+/// Bits set per inserted item
+const BLOOM_HASHES: usize = 3;
+
+/// 2048-bit bloom filter over the addresses a block touches.
+///
+/// False positives are possible, false negatives are not: `contains`
+/// returning false means the item was never added.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Bloom([u8; BLOOM_BYTES]);
+
+impl Bloom {
+    /// Bloom of every sender and recipient in `transactions`
+    pub fn from_transactions(transactions: &[Transaction]) -> Self {
+        let mut bloom = Self::default();
+        for tx in transactions {
+            if !tx.is_coinbase() {
+                bloom.accrue_address(&tx.sender());
+            }
+            if let Some(recipient) = tx.recipient() {
+                bloom.accrue_address(&recipient);
+            }
+        }
+        bloom
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let bytes: [u8; BLOOM_BYTES] = bytes.try_into().map_err(|_| {
+            BlockchainError::BlockValidationFailed {
+                reason: format!("Bloom must be {} bytes, got {}", BLOOM_BYTES, bytes.len()),
+            }
+        })?;
+        Ok(Bloom(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Add arbitrary data, such as an address or a log topic
+    pub fn accrue(&mut self, item: &[u8]) {
+        for bit in bit_indexes(item) {
+            self.0[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    pub fn accrue_address(&mut self, address: &Address) {
+        self.accrue(address);
+    }
+
+    /// Whether `item` may have been added
+    pub fn contains(&self, item: &[u8]) -> bool {
+        bit_indexes(item).iter().all(|&bit| self.0[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    pub fn contains_address(&self, address: &Address) -> bool {
+        self.contains(address)
+    }
+
+    /// Merge another bloom into this one, e.g. to filter a range of blocks
+    pub fn union(&mut self, other: &Bloom) {
+        for (byte, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *byte |= other;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&b| b == 0)
+    }
+}
+
+/// Bit positions for an item: three 11-bit slices of its hash
+fn bit_indexes(item: &[u8]) -> [usize; BLOOM_HASHES] {
+    let hash = hash_data(item);
+    let mut bits = [0usize; BLOOM_HASHES];
+    for (i, bit) in bits.iter_mut().enumerate() {
+        *bit = (((hash[2 * i] as usize) << 8) | hash[2 * i + 1] as usize) % (BLOOM_BYTES * 8);
+    }
+    bits
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom([0u8; BLOOM_BYTES])
+    }
+}
+
+impl fmt::Debug for Bloom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bloom({})", hex::encode(self.0))
+    }
+}
+
+impl Serialize for Bloom {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bloom {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct BloomVisitor;
+
+        impl<'de> Visitor<'de> for BloomVisitor {
+            type Value = Bloom;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{} bloom bytes", BLOOM_BYTES)
+            }
+
+            fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> std::result::Result<Bloom, E> {
+                Bloom::from_slice(bytes).map_err(|_| E::invalid_length(bytes.len(), &self))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Bloom, A::Error> {
+                let mut bytes = Vec::with_capacity(BLOOM_BYTES);
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(BloomVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_membership() {
+        let tx = Transaction::new_transfer([1; 20], [2; 20], 10, 0, 21_000, 1).unwrap();
+        let bloom = Bloom::from_transactions(&[tx]);
+
+        assert!(bloom.contains_address(&[1; 20]));
+        assert!(bloom.contains_address(&[2; 20]));
+        assert!(!bloom.contains_address(&[3; 20]));
+        assert!(Bloom::default().is_empty());
+
+        let mut merged = Bloom::default();
+        merged.union(&bloom);
+        assert_eq!(merged, bloom);
+    }
+
+    #[test]
+    fn test_bloom_serialization() {
+        let mut bloom = Bloom::default();
+        bloom.accrue(b"topic");
+
+        let encoded = bincode::serialize(&bloom).unwrap();
+        assert_eq!(encoded.len(), 8 + BLOOM_BYTES);
+        assert_eq!(bincode::deserialize::<Bloom>(&encoded).unwrap(), bloom);
+
+        let json = serde_json::to_string(&bloom).unwrap();
+        assert_eq!(serde_json::from_str::<Bloom>(&json).unwrap(), bloom);
+        assert!(Bloom::from_slice(&[0u8; 10]).is_err());
+    }
+}
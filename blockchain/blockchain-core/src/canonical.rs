@@ -0,0 +1,251 @@
+// core/blockchain-core/src/canonical.rs
+//! Canonical byte encoding of signed transactions, for exchange with tooling
+//! outside the node.
+//!
+//! Unlike the bincode wire format, the layout is fixed and documented here so
+//! other implementations can produce it byte for byte:
+//!
+//! ```text
+//! version   u8          CANONICAL_VERSION
+//! chain_id  u64
+//! type      u8          0 transfer, 1 deploy, 2 call, 3 coinbase
+//! body                  per type, fields in declaration order
+//! nonce     u64
+//! gas_limit u64
+//! gas_price u64
+//! timestamp i64 secs, u32 nanos
+//! signature bytes
+//! ```
+//!
+//! Integers are big-endian, addresses are their 20 raw bytes and `bytes` is a
+//! u32 length followed by the data. The hash and status are not encoded: the
+//! hash is recomputed on decode and a decoded transaction is always pending.
+use crate::{Address, BlockchainError, Result, Transaction, TransactionStatus, TransactionType};
+use chrono::{DateTime, Utc};
+
+/// Current layout version, the first byte of every encoding
+pub const CANONICAL_VERSION: u8 = 1;
+
+/// Largest byte string accepted on decode
+const MAX_FIELD_LEN: usize = 1024 * 1024;
+
+const TAG_TRANSFER: u8 = 0;
+const TAG_DEPLOY: u8 = 1;
+const TAG_CALL: u8 = 2;
+const TAG_COINBASE: u8 = 3;
+
+impl Transaction {
+    /// Encode under the canonical layout
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(128 + self.signature.len());
+        out.push(CANONICAL_VERSION);
+        out.extend_from_slice(&self.chain_id.to_be_bytes());
+
+        match &self.tx_type {
+            TransactionType::Transfer { from, to, amount } => {
+                out.push(TAG_TRANSFER);
+                out.extend_from_slice(from);
+                out.extend_from_slice(to);
+                out.extend_from_slice(&amount.to_be_bytes());
+            }
+            TransactionType::Deploy { from, code, init_data } => {
+                out.push(TAG_DEPLOY);
+                out.extend_from_slice(from);
+                put_bytes(&mut out, code);
+                put_bytes(&mut out, init_data);
+            }
+            TransactionType::Call { from, to, data, amount } => {
+                out.push(TAG_CALL);
+                out.extend_from_slice(from);
+                out.extend_from_slice(to);
+                put_bytes(&mut out, data);
+                out.extend_from_slice(&amount.to_be_bytes());
+            }
+            TransactionType::Coinbase { to, amount, height } => {
+                out.push(TAG_COINBASE);
+                out.extend_from_slice(to);
+                out.extend_from_slice(&amount.to_be_bytes());
+                out.extend_from_slice(&height.to_be_bytes());
+            }
+        }
+
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out.extend_from_slice(&self.gas_limit.to_be_bytes());
+        out.extend_from_slice(&self.gas_price.to_be_bytes());
+        out.extend_from_slice(&self.timestamp.timestamp().to_be_bytes());
+        out.extend_from_slice(&self.timestamp.timestamp_subsec_nanos().to_be_bytes());
+        put_bytes(&mut out, &self.signature);
+        out
+    }
+
+    /// Decode the canonical layout, rejecting unknown versions and trailing bytes.
+    ///
+    /// Only the encoding is checked; callers still run `validate_structure`
+    /// and signature verification.
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        let version = reader.u8()?;
+        if version != CANONICAL_VERSION {
+            return Err(malformed(format!("unsupported version {}", version)));
+        }
+        let chain_id = reader.u64()?;
+
+        let tx_type = match reader.u8()? {
+            TAG_TRANSFER => TransactionType::Transfer {
+                from: reader.address()?,
+                to: reader.address()?,
+                amount: reader.u64()?,
+            },
+            TAG_DEPLOY => TransactionType::Deploy {
+                from: reader.address()?,
+                code: reader.bytes()?,
+                init_data: reader.bytes()?,
+            },
+            TAG_CALL => TransactionType::Call {
+                from: reader.address()?,
+                to: reader.address()?,
+                data: reader.bytes()?,
+                amount: reader.u64()?,
+            },
+            TAG_COINBASE => TransactionType::Coinbase {
+                to: reader.address()?,
+                amount: reader.u64()?,
+                height: reader.u64()?,
+            },
+            tag => return Err(malformed(format!("unknown transaction type {}", tag))),
+        };
+
+        let nonce = reader.u64()?;
+        let gas_limit = reader.u64()?;
+        let gas_price = reader.u64()?;
+        let secs = reader.u64()? as i64;
+        let nanos = reader.u32()?;
+        let timestamp = DateTime::<Utc>::from_timestamp(secs, nanos)
+            .ok_or_else(|| malformed(format!("timestamp {}.{} out of range", secs, nanos)))?;
+        let signature = reader.bytes()?;
+
+        if reader.pos != bytes.len() {
+            return Err(malformed(format!("{} trailing bytes", bytes.len() - reader.pos)));
+        }
+
+        let mut tx = Transaction {
+            hash: [0u8; 32],
+            tx_type,
+            nonce,
+            gas_limit,
+            gas_price,
+            timestamp,
+            signature,
+            status: TransactionStatus::Pending,
+            chain_id,
+        };
+        tx.hash = tx.calculate_hash()?;
+        Ok(tx)
+    }
+}
+
+fn put_bytes(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(data);
+}
+
+fn malformed(reason: String) -> BlockchainError {
+    BlockchainError::InvalidTransaction {
+        reason: format!("Malformed canonical encoding: {}", reason),
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| malformed(format!("truncated at byte {}", self.pos)))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(buf))
+    }
+
+    fn address(&mut self) -> Result<Address> {
+        let mut address = [0u8; 20];
+        address.copy_from_slice(self.take(20)?);
+        Ok(address)
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>> {
+        let len = self.u32()? as usize;
+        if len > MAX_FIELD_LEN {
+            return Err(malformed(format!("field of {} bytes exceeds {}", len, MAX_FIELD_LEN)));
+        }
+        Ok(self.take(len)?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AddressExt, KeyPair, SignatureScheme};
+
+    #[test]
+    fn test_roundtrip_preserves_hash_and_signature() {
+        let key = KeyPair::generate(SignatureScheme::Ed25519);
+        let from = Address::from_public_key(&key.public_key()).unwrap();
+        let mut tx = Transaction::new_call(from, [2; 20], vec![1, 2, 3], 50, 3, 30_000, 7)
+            .unwrap()
+            .with_chain_id(9)
+            .unwrap();
+        tx.sign(&key);
+
+        let bytes = tx.to_canonical_bytes();
+        assert_eq!(bytes[0], CANONICAL_VERSION);
+        let decoded = Transaction::from_canonical_bytes(&bytes).unwrap();
+        assert_eq!(decoded, tx);
+        decoded.verify_sender(SignatureScheme::Ed25519).unwrap();
+
+        let coinbase = Transaction::new_coinbase([4; 20], 100, 12).unwrap();
+        assert_eq!(Transaction::from_canonical_bytes(&coinbase.to_canonical_bytes()).unwrap(), coinbase);
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let tx = Transaction::new_transfer([1; 20], [2; 20], 10, 0, 21_000, 1).unwrap();
+        let bytes = tx.to_canonical_bytes();
+
+        assert!(Transaction::from_canonical_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Transaction::from_canonical_bytes(&trailing).is_err());
+
+        let mut future = bytes.clone();
+        future[0] = CANONICAL_VERSION + 1;
+        assert!(Transaction::from_canonical_bytes(&future).is_err());
+
+        let mut unknown_type = bytes;
+        unknown_type[9] = 9;
+        assert!(Transaction::from_canonical_bytes(&unknown_type).is_err());
+    }
+}
@@ -0,0 +1,629 @@
+// core/blockchain-core/src/chain.rs
+use crate::{Block, BlockHash, BlockHeight, BlockOutcome, BlockchainError, ChainSpec, Checkpoint, Ledger, OrphanPool, Result};
+use crate::beacon;
+use crate::clock::{Clock, SystemClock};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Deepest reorg the chain accepts unless configured otherwise
+pub const DEFAULT_MAX_REORG_DEPTH: u64 = 100;
+
+/// Result of offering a block to the chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainUpdate {
+    /// The block was already known
+    Duplicate,
+    /// The block extended the main chain
+    Extended(BlockOutcome),
+    /// The block was kept on a side chain with no more work than the main chain
+    SideChain,
+    /// The block's parent is unknown; it waits in the orphan pool until
+    /// `missing_parent` arrives
+    Orphaned { missing_parent: BlockHash },
+    /// A side chain overtook the main chain.
+    ///
+    /// `rolled_back` lists the former main-chain blocks from the old tip down,
+    /// so their transactions can be returned to the mempool; `applied` lists
+    /// the new main-chain blocks from the fork point up.
+    Reorged {
+        rolled_back: Vec<Block>,
+        applied: Vec<(Block, BlockOutcome)>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct ChainEntry {
+    block: Block,
+    /// Work of this block and all of its ancestors
+    total_work: u128,
+}
+
+/// Expected hashing work to produce a block at `difficulty`
+pub fn block_work(difficulty: u32) -> u128 {
+    1u128 << difficulty.min(127)
+}
+
+/// Block tree rooted at genesis, following the branch with the most cumulative work.
+///
+/// Side-chain blocks are validated on arrival but only executed once their
+/// branch becomes the main chain. Ledger checkpoints are kept for the last
+/// `max_reorg_depth` blocks, which bounds how far a reorg may reach back.
+/// With finality configured, blocks at or below the last finalized
+/// checkpoint are never rolled back either.
+#[derive(Debug, Clone)]
+pub struct Chain {
+    spec: ChainSpec,
+    entries: HashMap<BlockHash, ChainEntry>,
+    /// Main-chain block hash at each height
+    main: Vec<BlockHash>,
+    ledger: Ledger,
+    /// Ledger after each recent main-chain block, oldest first
+    checkpoints: VecDeque<(BlockHeight, Ledger)>,
+    max_reorg_depth: u64,
+    orphans: OrphanPool,
+    finalized: Option<Checkpoint>,
+    /// Judges whether block timestamps are too far in the future
+    clock: Arc<dyn Clock>,
+}
+
+impl Chain {
+    /// Start a chain from `genesis`, with `allocations` as its initial balances
+    pub fn new(spec: ChainSpec, genesis: Block, allocations: Ledger) -> Result<Self> {
+        spec.validate()?;
+        if genesis.header.height != 0 {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: format!("Genesis must be at height 0, got {}", genesis.header.height),
+            });
+        }
+        genesis.validate()?;
+        spec.validate_block(&genesis)?;
+
+        let hash = genesis.hash;
+        let total_work = block_work(genesis.header.difficulty);
+        let mut entries = HashMap::new();
+        entries.insert(hash, ChainEntry { block: genesis, total_work });
+
+        Ok(Self {
+            spec,
+            entries,
+            main: vec![hash],
+            checkpoints: VecDeque::from([(0, allocations.clone())]),
+            ledger: allocations,
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+            orphans: OrphanPool::default(),
+            finalized: None,
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    pub fn with_max_reorg_depth(mut self, depth: u64) -> Self {
+        self.max_reorg_depth = depth;
+        self.trim_checkpoints();
+        self
+    }
+
+    pub fn with_orphan_pool(mut self, orphans: OrphanPool) -> Self {
+        self.orphans = orphans;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn orphans(&self) -> &OrphanPool {
+        &self.orphans
+    }
+
+    /// For evicting orphans under memory pressure; orphans are not part of the chain
+    pub fn orphans_mut(&mut self) -> &mut OrphanPool {
+        &mut self.orphans
+    }
+
+    /// Last checkpoint accepted by `finalize`
+    pub fn finalized(&self) -> Option<&Checkpoint> {
+        self.finalized.as_ref()
+    }
+
+    /// Height below which the main chain can no longer change; genesis is
+    /// always final
+    pub fn finalized_height(&self) -> BlockHeight {
+        self.finalized.as_ref().map_or(0, |checkpoint| checkpoint.height)
+    }
+
+    /// Make the main-chain block named by `checkpoint` irreversible.
+    ///
+    /// The checkpoint must carry a quorum of the spec's finality validators,
+    /// name the current main-chain block at its height and be above the last
+    /// finalized height. Also used to restore the persisted checkpoint after
+    /// a restart, once the chain has been replayed past it.
+    pub fn finalize(&mut self, checkpoint: Checkpoint) -> Result<()> {
+        let Some(finality) = &self.spec.finality else {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: "Chain spec has no finality validators".to_string(),
+            });
+        };
+        finality.verify(self.spec.params.chain_id, &checkpoint)?;
+
+        if checkpoint.height <= self.finalized_height() {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: format!(
+                    "Checkpoint at height {} is not above finalized height {}",
+                    checkpoint.height,
+                    self.finalized_height()
+                ),
+            });
+        }
+        if self.main.get(checkpoint.height as usize) != Some(&checkpoint.block_hash) {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: format!(
+                    "Checkpoint block {} is not on the main chain at height {}",
+                    hex::encode(checkpoint.block_hash),
+                    checkpoint.height
+                ),
+            });
+        }
+
+        self.finalized = Some(checkpoint);
+        self.prune_side_chains();
+        Ok(())
+    }
+
+    pub fn spec(&self) -> &ChainSpec {
+        &self.spec
+    }
+
+    /// Account state at the tip of the main chain
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    pub fn height(&self) -> BlockHeight {
+        (self.main.len() - 1) as BlockHeight
+    }
+
+    pub fn tip(&self) -> &Block {
+        &self.entries[&self.main[self.main.len() - 1]].block
+    }
+
+    /// Commit `block`, produced on the current tip, to the account state it
+    /// leaves. Call before sealing, so the seal covers the root.
+    pub fn commit_state_root(&self, block: Block) -> Result<Block> {
+        if block.header.previous_hash != self.tip().hash {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: format!("Block at height {} does not build on the tip", block.header.height),
+            });
+        }
+        let state_root = self.ledger.state_root_after(&block, &self.spec.fees)?;
+        block.with_state_root(state_root)
+    }
+
+    /// Give `block`, produced on the current tip, the beacon seed that follows
+    /// the tip. Call after `commit_state_root` and before sealing.
+    pub fn commit_random_seed(&self, block: Block) -> Result<Block> {
+        if block.header.previous_hash != self.tip().hash {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: format!("Block at height {} does not build on the tip", block.header.height),
+            });
+        }
+        let seed = beacon::seed_for(&block, self.tip())?;
+        block.with_random_seed(seed)
+    }
+
+        /// Cumulative work of the main chain
+    pub fn total_work(&self) -> u128 {
+        self.entries[&self.tip().hash].total_work
+    }
+
+    /// Any known block, on the main chain or a side chain
+    pub fn get_block(&self, hash: &BlockHash) -> Option<&Block> {
+        self.entries.get(hash).map(|entry| &entry.block)
+    }
+
+    /// Main-chain block at `height`
+    pub fn block_at(&self, height: BlockHeight) -> Option<&Block> {
+        let hash = self.main.get(height as usize)?;
+        self.get_block(hash)
+    }
+
+    pub fn is_main_chain(&self, hash: &BlockHash) -> bool {
+        self.entries
+            .get(hash)
+            .is_some_and(|entry| self.main.get(entry.block.header.height as usize) == Some(hash))
+    }
+
+    /// Add a block received from a peer or produced locally.
+    ///
+    /// The parent must already be known; `accept_block` buffers blocks that
+    /// arrive early. If the block gives its branch more
+    /// cumulative work than the main chain, the chain reorganizes onto it;
+    /// ties keep the branch seen first. A block that fails execution during a
+    /// reorg is rejected and the chain is left unchanged.
+    pub fn apply_block(&mut self, block: Block) -> Result<ChainUpdate> {
+        if self.entries.contains_key(&block.hash) {
+            return Ok(ChainUpdate::Duplicate);
+        }
+
+        block.validate_with_clock(self.clock.as_ref())?;
+        self.spec.validate_block(&block)?;
+        if block.header.height <= self.finalized_height() {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: format!(
+                    "Block at height {} conflicts with finalized height {}",
+                    block.header.height,
+                    self.finalized_height()
+                ),
+            });
+        }
+
+        let parent = self.entries.get(&block.header.previous_hash).ok_or_else(|| {
+            BlockchainError::ChainValidationFailed {
+                reason: format!("Unknown parent block {}", hex::encode(block.header.previous_hash)),
+            }
+        })?;
+        block.can_follow(&parent.block)?;
+        beacon::verify_seed(&block, &parent.block)?;
+        self.spec.consensus.validate_successor(&block, &parent.block)?;
+        self.spec.empty_blocks.check(&block, &parent.block)?;
+
+        let total_work = parent.total_work + block_work(block.header.difficulty);
+
+        if block.header.previous_hash == self.tip().hash {
+            let outcome = self.ledger.apply_block(&block, &self.spec)?;
+            self.push_main(block, total_work);
+            return Ok(ChainUpdate::Extended(outcome));
+        }
+
+        if total_work <= self.total_work() {
+            self.entries.insert(block.hash, ChainEntry { block, total_work });
+            return Ok(ChainUpdate::SideChain);
+        }
+
+        self.reorganize(block, total_work)
+    }
+
+    /// Add a block that may arrive before its parent, as during sync.
+    ///
+    /// A block with an unknown parent is structurally checked and held in the
+    /// orphan pool. Once a block connects, orphans descending from it are
+    /// applied too; the update for `block` comes first, followed by one per
+    /// re-attached orphan. Orphans that fail to apply are dropped.
+    pub fn accept_block(&mut self, block: Block, now: Instant) -> Result<Vec<ChainUpdate>> {
+        self.orphans.expire(now);
+
+        if !self.entries.contains_key(&block.header.previous_hash) {
+            if self.entries.contains_key(&block.hash) {
+                return Ok(vec![ChainUpdate::Duplicate]);
+            }
+            block.validate_with_clock(self.clock.as_ref())?;
+
+            let hash = block.hash;
+            let missing_parent = block.header.previous_hash;
+            self.orphans.insert(block, now);
+            let missing_parent = self.orphans.missing_ancestor(&hash).unwrap_or(missing_parent);
+            return Ok(vec![ChainUpdate::Orphaned { missing_parent }]);
+        }
+
+        let mut connected = vec![block.hash];
+        let mut updates = vec![self.apply_block(block)?];
+
+        while let Some(parent) = connected.pop() {
+            for child in self.orphans.take_children(&parent) {
+                let hash = child.hash;
+                if let Ok(update) = self.apply_block(child) {
+                    updates.push(update);
+                    connected.push(hash);
+                }
+            }
+        }
+
+        Ok(updates)
+    }
+
+    /// Switch the main chain to the branch ending in `block`
+    fn reorganize(&mut self, block: Block, total_work: u128) -> Result<ChainUpdate> {
+        // Walk back to the main chain, collecting the branch tip first
+        let mut branch = vec![block.hash];
+        let mut cursor = block.header.previous_hash;
+        while !self.is_main_chain(&cursor) {
+            branch.push(cursor);
+            cursor = self.entries[&cursor].block.header.previous_hash;
+        }
+        branch.reverse();
+
+        let fork_height = self.entries[&cursor].block.header.height;
+        let depth = self.height() - fork_height;
+        if depth > self.max_reorg_depth {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: format!("Reorg of depth {} exceeds limit {}", depth, self.max_reorg_depth),
+            });
+        }
+        if fork_height < self.finalized_height() {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: format!(
+                    "Reorg from height {} would roll back finalized height {}",
+                    fork_height,
+                    self.finalized_height()
+                ),
+            });
+        }
+
+        let Some(mut ledger) = self
+            .checkpoints
+            .iter()
+            .find(|(height, _)| *height == fork_height)
+            .map(|(_, ledger)| ledger.clone())
+        else {
+            return Err(BlockchainError::ChainValidationFailed {
+                reason: format!("No ledger checkpoint at fork height {}", fork_height),
+            });
+        };
+
+        // Execute the whole branch before touching any state
+        let mut checkpoints = Vec::with_capacity(branch.len());
+        let mut applied = Vec::with_capacity(branch.len());
+        for hash in &branch {
+            let branch_block = if *hash == block.hash { &block } else { &self.entries[hash].block };
+            let outcome = ledger.apply_block(branch_block, &self.spec)?;
+            checkpoints.push((branch_block.header.height, ledger.clone()));
+            applied.push((branch_block.clone(), outcome));
+        }
+
+        let rolled_back = self.main[fork_height as usize + 1..]
+            .iter()
+            .rev()
+            .map(|hash| self.entries[hash].block.clone())
+            .collect();
+
+        self.entries.insert(block.hash, ChainEntry { block, total_work });
+        self.main.truncate(fork_height as usize + 1);
+        self.main.extend(branch);
+        self.checkpoints.retain(|(height, _)| *height <= fork_height);
+        self.checkpoints.extend(checkpoints);
+        self.ledger = ledger;
+        self.trim_checkpoints();
+        self.prune_side_chains();
+
+        Ok(ChainUpdate::Reorged { rolled_back, applied })
+    }
+
+    fn push_main(&mut self, block: Block, total_work: u128) {
+        let (hash, height) = (block.hash, block.header.height);
+        self.entries.insert(hash, ChainEntry { block, total_work });
+        self.main.push(hash);
+        self.checkpoints.push_back((height, self.ledger.clone()));
+        self.trim_checkpoints();
+        self.prune_side_chains();
+    }
+
+    fn trim_checkpoints(&mut self) {
+        while self.checkpoints.len() as u64 > self.max_reorg_depth + 1 {
+            self.checkpoints.pop_front();
+        }
+    }
+
+    /// Forget side-chain blocks too deep or too old to ever be reorganized onto
+    fn prune_side_chains(&mut self) {
+        let horizon = self.height().saturating_sub(self.max_reorg_depth).max(self.finalized_height());
+        if horizon == 0 {
+            return;
+        }
+        let main = &self.main;
+        self.entries.retain(|hash, entry| {
+            let height = entry.block.header.height;
+            height >= horizon || main[height as usize] == *hash
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::TESTNET_CHAIN_ID;
+    use crate::{Address, AddressExt, ChainParams, EmissionSchedule, FeeDistribution, FinalityConfig, KeyPair, SignatureScheme, Transaction};
+    use crate::{DriftConfig, DriftMonitor, MockClock, MAX_FUTURE_BLOCK_SECS};
+    use chrono::Duration;
+
+    const ALICE: Address = [1; 20];
+    const BOB: Address = [2; 20];
+    const MINER_A: Address = [5; 20];
+    const MINER_B: Address = [6; 20];
+
+    fn spec() -> ChainSpec {
+        ChainSpec {
+            params: ChainParams::testnet(),
+            fees: FeeDistribution { burn_bps: 0, treasury_bps: 0, treasury: None, base_gas_price: None },
+            emission: EmissionSchedule::Fixed { reward: 50 },
+            ..ChainSpec::default()
+        }
+    }
+
+    fn chain() -> Chain {
+        let mut allocations = Ledger::new();
+        allocations.credit(&ALICE, 1_000_000).unwrap();
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        Chain::new(spec(), genesis, allocations).unwrap()
+    }
+
+    fn child(parent: &Block, producer: Address, transactions: Vec<Transaction>, difficulty: u32) -> Block {
+        let mut block = spec()
+            .produce_block(producer, parent.header.height + 1, parent.hash, transactions, difficulty)
+            .unwrap();
+        block.header.timestamp = parent.header.timestamp + chrono::Duration::seconds(12);
+        block.set_nonce(0).unwrap();
+        block
+    }
+
+    fn transfer(nonce: u64) -> Transaction {
+        Transaction::new_transfer(ALICE, BOB, 100, nonce, 21_000, 1)
+            .unwrap()
+            .with_chain_id(TESTNET_CHAIN_ID)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_linear_growth_and_side_chain() {
+        let mut chain = chain();
+        let genesis = chain.tip().clone();
+
+        let a1 = child(&genesis, MINER_A, vec![transfer(0)], 1);
+        assert!(matches!(chain.apply_block(a1.clone()).unwrap(), ChainUpdate::Extended(_)));
+        assert_eq!(chain.apply_block(a1.clone()).unwrap(), ChainUpdate::Duplicate);
+
+        // Equal work does not displace the block seen first
+        let b1 = child(&genesis, MINER_B, vec![], 1);
+        assert_eq!(chain.apply_block(b1.clone()).unwrap(), ChainUpdate::SideChain);
+        assert_eq!(chain.tip().hash, a1.hash);
+        assert!(!chain.is_main_chain(&b1.hash));
+        assert!(chain.get_block(&b1.hash).is_some());
+        assert_eq!(chain.ledger().balance(&BOB), 100);
+
+        let orphan = child(&child(&b1, MINER_B, vec![], 1), MINER_B, vec![], 1);
+        assert!(chain.apply_block(orphan).is_err());
+    }
+
+    #[test]
+    fn test_heavier_side_chain_reorgs() {
+        let mut chain = chain();
+        let genesis = chain.tip().clone();
+
+        let a1 = child(&genesis, MINER_A, vec![transfer(0)], 1);
+        let a2 = child(&a1, MINER_A, vec![transfer(1)], 1);
+        chain.apply_block(a1.clone()).unwrap();
+        chain.apply_block(a2.clone()).unwrap();
+        assert_eq!(chain.ledger().balance(&BOB), 200);
+
+        let b1 = child(&genesis, MINER_B, vec![], 1);
+        let b2 = child(&b1, MINER_B, vec![], 3);
+        assert_eq!(chain.apply_block(b1.clone()).unwrap(), ChainUpdate::SideChain);
+
+        let ChainUpdate::Reorged { rolled_back, applied } = chain.apply_block(b2.clone()).unwrap() else {
+            panic!("expected a reorg");
+        };
+        assert_eq!(rolled_back.iter().map(|b| b.hash).collect::<Vec<_>>(), vec![a2.hash, a1.hash]);
+        assert_eq!(applied.iter().map(|(b, _)| b.hash).collect::<Vec<_>>(), vec![b1.hash, b2.hash]);
+
+        assert_eq!(chain.tip().hash, b2.hash);
+        assert_eq!(chain.block_at(1).unwrap().hash, b1.hash);
+        assert_eq!(chain.total_work(), block_work(1) * 2 + block_work(3));
+        assert_eq!(chain.ledger().balance(&BOB), 0);
+        assert_eq!(chain.ledger().balance(&MINER_A), 0);
+        assert_eq!(chain.ledger().balance(&MINER_B), 100);
+
+        // The old branch can take the lead back
+        let a3 = child(&a2, MINER_A, vec![], 4);
+        assert!(matches!(chain.apply_block(a3.clone()).unwrap(), ChainUpdate::Reorged { .. }));
+        assert_eq!(chain.tip().hash, a3.hash);
+        assert_eq!(chain.ledger().balance(&BOB), 200);
+    }
+
+    #[test]
+    fn test_out_of_order_blocks_reattach() {
+        let mut chain = chain();
+        let genesis = chain.tip().clone();
+        let now = Instant::now();
+
+        let a1 = child(&genesis, MINER_A, vec![transfer(0)], 1);
+        let a2 = child(&a1, MINER_A, vec![transfer(1)], 1);
+        let a3 = child(&a2, MINER_A, vec![], 1);
+
+        assert_eq!(chain.accept_block(a3.clone(), now).unwrap(), vec![ChainUpdate::Orphaned { missing_parent: a2.hash }]);
+        assert_eq!(chain.accept_block(a2.clone(), now).unwrap(), vec![ChainUpdate::Orphaned { missing_parent: a1.hash }]);
+        assert_eq!(chain.orphans().len(), 2);
+
+        let updates = chain.accept_block(a1, now).unwrap();
+        assert_eq!(updates.len(), 3);
+        assert!(updates.iter().all(|update| matches!(update, ChainUpdate::Extended(_))));
+        assert_eq!(chain.tip().hash, a3.hash);
+        assert_eq!(chain.ledger().balance(&BOB), 200);
+        assert!(chain.orphans().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_branch_and_deep_reorg_rejected() {
+        let mut chain = chain().with_max_reorg_depth(1);
+        let genesis = chain.tip().clone();
+
+        let a1 = child(&genesis, MINER_A, vec![], 1);
+        let a2 = child(&a1, MINER_A, vec![], 1);
+        chain.apply_block(a1.clone()).unwrap();
+        chain.apply_block(a2.clone()).unwrap();
+
+        // Replays a nonce, so the branch fails to execute once it would win
+        let b2 = child(&a1, MINER_B, vec![transfer(1)], 1);
+        chain.apply_block(b2.clone()).unwrap();
+        let b3 = child(&b2, MINER_B, vec![], 1);
+        assert!(chain.apply_block(b3).is_err());
+        assert_eq!(chain.tip().hash, a2.hash);
+
+        let c1 = child(&genesis, MINER_B, vec![], 8);
+        assert!(chain.apply_block(c1).is_err());
+        assert_eq!(chain.tip().hash, a2.hash);
+        assert_eq!(chain.height(), 2);
+    }
+
+    #[test]
+    fn test_no_reorg_below_finalized_checkpoint() {
+        let keys: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate(SignatureScheme::Ed25519)).collect();
+        let finality = FinalityConfig {
+            validators: keys.iter().map(|k| Address::from_public_key(&k.public_key()).unwrap()).collect(),
+            interval: 2,
+        };
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        let spec = ChainSpec { finality: Some(finality), ..spec() };
+        let mut chain = Chain::new(spec, genesis.clone(), Ledger::new()).unwrap();
+
+        let a1 = child(&genesis, MINER_A, vec![], 1);
+        let a2 = child(&a1, MINER_A, vec![], 1);
+        let b1 = child(&genesis, MINER_B, vec![], 1);
+        chain.apply_block(a1).unwrap();
+        chain.apply_block(a2.clone()).unwrap();
+        chain.apply_block(b1.clone()).unwrap();
+
+        let mut checkpoint = Checkpoint::new(2, a2.hash);
+        checkpoint.sign(TESTNET_CHAIN_ID, &keys[0]).unwrap();
+        checkpoint.sign(TESTNET_CHAIN_ID, &keys[2]).unwrap();
+        assert!(chain.finalize(checkpoint.clone()).is_err());
+        checkpoint.sign(TESTNET_CHAIN_ID, &keys[1]).unwrap();
+        chain.finalize(checkpoint.clone()).unwrap();
+        assert_eq!(chain.finalized_height(), 2);
+        assert!(chain.get_block(&b1.hash).is_none());
+        assert!(chain.finalize(checkpoint).is_err());
+
+        // A heavier branch from before the checkpoint can no longer win
+        let b2 = child(&b1, MINER_B, vec![], 9);
+        assert!(chain.apply_block(b2).is_err());
+        assert_eq!(chain.tip().hash, a2.hash);
+
+        let a3 = child(&a2, MINER_A, vec![], 1);
+        assert!(matches!(chain.apply_block(a3).unwrap(), ChainUpdate::Extended(_)));
+    }
+
+    #[test]
+    fn test_future_blocks_judged_by_injected_clock() {
+        let chain = chain();
+        let genesis = chain.tip().clone();
+        let clock = MockClock::new(genesis.header.timestamp);
+        let monitor = Arc::new(DriftMonitor::new(Arc::new(clock.clone()), DriftConfig::default()));
+        let mut chain = chain.with_clock(monitor.clone());
+
+        let mut early = child(&genesis, MINER_A, vec![], 1);
+        early.header.timestamp = genesis.header.timestamp + Duration::seconds(MAX_FUTURE_BLOCK_SECS + 5);
+        early.set_nonce(0).unwrap();
+        assert!(chain.apply_block(early.clone()).is_err());
+
+        // Measured drift widens the window
+        monitor.record(Duration::seconds(8));
+        assert!(matches!(chain.apply_block(early.clone()).unwrap(), ChainUpdate::Extended(_)));
+
+        // and it narrows again once the clock is back in sync
+        monitor.record(Duration::zero());
+        monitor.record(Duration::zero());
+        let next = child(&early, MINER_A, vec![], 1);
+        assert!(chain.apply_block(next.clone()).is_err());
+        clock.advance(Duration::seconds(20));
+        assert!(matches!(chain.apply_block(next).unwrap(), ChainUpdate::Extended(_)));
+    }
+}
@@ -0,0 +1,277 @@
+// core/blockchain-core/src/classify.rs
+//! Tagging confirmed transactions with a category for analytics and policy.
+//!
+//! A `ClassifierPipeline` runs its classifiers over each stored block in
+//! order; a transaction gets the category of the first classifier that
+//! returns one and stays untagged if none does. The built-in heuristics
+//! recognise dust spam, exchange payout batches and bridge operations;
+//! operators add their own by implementing `TxClassifier`.
+//!
+//! Dust spam is scored rather than matched: a transfer of at most
+//! `dust_max_amount` scores 50, its sender making `dust_fanout` or more such
+//! transfers in the block adds 30, and paying the block's lowest gas price
+//! adds 20. Transfers scoring `spam_threshold` or more are tagged.
+use crate::{Address, Amount, Block, BlockchainError, Result, Transaction, TransactionType};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+pub const DUST_SPAM: &str = "dust_spam";
+pub const EXCHANGE_BATCH: &str = "exchange_batch";
+pub const BRIDGE_OP: &str = "bridge_op";
+
+/// Assigns categories to a block's transactions
+pub trait TxClassifier: Send + Sync {
+    /// Category of each of `block`'s transactions, in block order; `None`
+    /// leaves the transaction to the next classifier
+    fn classify(&self, block: &Block) -> Vec<Option<String>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassificationConfig {
+    /// Largest transfer that counts as dust
+    pub dust_max_amount: Amount,
+    /// Dust transfers from one sender in a block that count as fanning out
+    pub dust_fanout: usize,
+    /// Spam score, out of 100, from which a transfer is tagged as dust spam
+    pub spam_threshold: u8,
+    /// Distinct recipients of one sender's transfers in a block that make
+    /// them an exchange batch
+    pub exchange_batch_min_recipients: usize,
+    /// Bridge contracts and custody accounts
+    pub bridge_addresses: Vec<Address>,
+}
+
+impl Default for ClassificationConfig {
+    fn default() -> Self {
+        Self {
+            dust_max_amount: 1_000,
+            dust_fanout: 5,
+            spam_threshold: 70,
+            exchange_batch_min_recipients: 20,
+            bridge_addresses: Vec::new(),
+        }
+    }
+}
+
+impl ClassificationConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.dust_fanout == 0 {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "Dust fan-out must be greater than 0".to_string(),
+            });
+        }
+        if !(1..=100).contains(&self.spam_threshold) {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: format!("Spam threshold must be between 1 and 100, got {}", self.spam_threshold),
+            });
+        }
+        if self.exchange_batch_min_recipients < 2 {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "An exchange batch needs at least 2 recipients".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn transfer(tx: &Transaction) -> Option<(Address, Address, Amount)> {
+    match tx.tx_type {
+        TransactionType::Transfer { from, to, amount } => Some((from, to, amount)),
+        _ => None,
+    }
+}
+
+/// Scores transfers for dust spam
+#[derive(Debug, Clone)]
+pub struct DustSpamClassifier {
+    pub max_amount: Amount,
+    pub fanout: usize,
+    pub threshold: u8,
+}
+
+impl DustSpamClassifier {
+    /// Spam score, out of 100, of each of `block`'s transactions
+    pub fn scores(&self, block: &Block) -> Vec<u8> {
+        let is_dust = |tx: &Transaction| transfer(tx).is_some_and(|(_, _, amount)| amount <= self.max_amount);
+        let mut dust_by_sender: HashMap<Address, usize> = HashMap::new();
+        for tx in block.transactions.iter().filter(|tx| is_dust(tx)) {
+            *dust_by_sender.entry(tx.sender()).or_default() += 1;
+        }
+        let lowest_price = block.transactions.iter().filter(|tx| !tx.is_coinbase()).map(|tx| tx.gas_price).min();
+
+        block
+            .transactions
+            .iter()
+            .map(|tx| {
+                if !is_dust(tx) {
+                    return 0;
+                }
+                let mut score = 50;
+                if dust_by_sender[&tx.sender()] >= self.fanout {
+                    score += 30;
+                }
+                if lowest_price == Some(tx.gas_price) {
+                    score += 20;
+                }
+                score
+            })
+            .collect()
+    }
+}
+
+impl TxClassifier for DustSpamClassifier {
+    fn classify(&self, block: &Block) -> Vec<Option<String>> {
+        self.scores(block).into_iter().map(|score| (score >= self.threshold).then(|| DUST_SPAM.to_string())).collect()
+    }
+}
+
+/// Tags a sender's transfers as an exchange batch when they pay out to many
+/// recipients in one block
+#[derive(Debug, Clone)]
+pub struct ExchangeBatchClassifier {
+    pub min_recipients: usize,
+}
+
+impl TxClassifier for ExchangeBatchClassifier {
+    fn classify(&self, block: &Block) -> Vec<Option<String>> {
+        let mut recipients: HashMap<Address, HashSet<Address>> = HashMap::new();
+        for (from, to, _) in block.transactions.iter().filter_map(transfer) {
+            recipients.entry(from).or_default().insert(to);
+        }
+        block
+            .transactions
+            .iter()
+            .map(|tx| {
+                let (from, _, _) = transfer(tx)?;
+                (recipients[&from].len() >= self.min_recipients).then(|| EXCHANGE_BATCH.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Tags transactions sending to or calling a bridge
+#[derive(Debug, Clone)]
+pub struct BridgeClassifier {
+    pub addresses: HashSet<Address>,
+}
+
+impl TxClassifier for BridgeClassifier {
+    fn classify(&self, block: &Block) -> Vec<Option<String>> {
+        block
+            .transactions
+            .iter()
+            .map(|tx| {
+                let bridged = !tx.is_coinbase() && tx.recipient().is_some_and(|to| self.addresses.contains(&to));
+                bridged.then(|| BRIDGE_OP.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Classifiers run in order over every stored block
+#[derive(Clone, Default)]
+pub struct ClassifierPipeline {
+    classifiers: Vec<Arc<dyn TxClassifier>>,
+}
+
+impl ClassifierPipeline {
+    pub fn new(classifiers: Vec<Arc<dyn TxClassifier>>) -> Self {
+        Self { classifiers }
+    }
+
+    /// The built-in heuristics: bridge operations first, since a bridge
+    /// deposit is worth knowing about however small, then dust spam, then
+    /// exchange batches
+    pub fn builtin(config: &ClassificationConfig) -> Result<Self> {
+        config.validate()?;
+        let mut classifiers: Vec<Arc<dyn TxClassifier>> = Vec::new();
+        if !config.bridge_addresses.is_empty() {
+            let addresses = config.bridge_addresses.iter().copied().collect();
+            classifiers.push(Arc::new(BridgeClassifier { addresses }));
+        }
+        classifiers.push(Arc::new(DustSpamClassifier {
+            max_amount: config.dust_max_amount,
+            fanout: config.dust_fanout,
+            threshold: config.spam_threshold,
+        }));
+        classifiers.push(Arc::new(ExchangeBatchClassifier { min_recipients: config.exchange_batch_min_recipients }));
+        Ok(Self::new(classifiers))
+    }
+
+    /// Run `classifier` before the ones already in the pipeline
+    pub fn prepend(&mut self, classifier: Arc<dyn TxClassifier>) {
+        self.classifiers.insert(0, classifier);
+    }
+
+    /// Run `classifier` after the ones already in the pipeline
+    pub fn push(&mut self, classifier: Arc<dyn TxClassifier>) {
+        self.classifiers.push(classifier);
+    }
+
+    /// Category of each of `block`'s transactions, in block order
+    pub fn classify(&self, block: &Block) -> Vec<Option<String>> {
+        let mut categories = vec![None; block.transactions.len()];
+        for classifier in &self.classifiers {
+            if categories.iter().all(Option::is_some) {
+                break;
+            }
+            let tagged = classifier.classify(block);
+            for (category, tag) in categories.iter_mut().zip(tagged) {
+                if category.is_none() {
+                    *category = tag;
+                }
+            }
+        }
+        categories
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct LargeTransfers;
+
+    impl TxClassifier for LargeTransfers {
+        fn classify(&self, block: &Block) -> Vec<Option<String>> {
+            block.transactions.iter().map(|tx| (tx.amount() >= 1_000_000).then(|| "whale".to_string())).collect()
+        }
+    }
+
+    #[test]
+    fn test_builtin_and_custom_categories() {
+        let (spammer, exchange, user, bridge) = ([1; 20], [2; 20], [3; 20], [9; 20]);
+        let mut transactions = Vec::new();
+        for nonce in 0..5 {
+            transactions.push(Transaction::new_transfer(spammer, [10 + nonce as u8; 20], 1, nonce, 21_000, 1).unwrap());
+        }
+        for nonce in 0..3 {
+            let payout = Transaction::new_transfer(exchange, [20 + nonce as u8; 20], 5_000, nonce, 21_000, 4);
+            transactions.push(payout.unwrap());
+        }
+        transactions.push(Transaction::new_transfer(user, bridge, 10, 0, 21_000, 4).unwrap());
+        transactions.push(Transaction::new_transfer(user, [30; 20], 2_000_000, 1, 21_000, 4).unwrap());
+        transactions.push(Transaction::new_transfer(user, [30; 20], 500, 2, 21_000, 4).unwrap());
+        let block = Block::new(1, [0; 32], transactions, 1).unwrap();
+
+        let config = ClassificationConfig {
+            exchange_batch_min_recipients: 3,
+            bridge_addresses: vec![bridge],
+            ..Default::default()
+        };
+        let mut pipeline = ClassifierPipeline::builtin(&config).unwrap();
+        pipeline.push(Arc::new(LargeTransfers));
+        let categories = pipeline.classify(&block);
+        let categories: Vec<Option<&str>> = categories.iter().map(Option::as_deref).collect();
+
+        // A lone dust transfer at a normal price scores 50 and stays untagged
+        assert_eq!(categories[..5], [Some(DUST_SPAM); 5]);
+        assert_eq!(categories[5..8], [Some(EXCHANGE_BATCH); 3]);
+        assert_eq!(categories[8..], [Some(BRIDGE_OP), Some("whale"), None]);
+        let scores = DustSpamClassifier { max_amount: 1_000, fanout: 5, threshold: 70 }.scores(&block);
+        assert_eq!((scores[0], scores[10]), (100, 50));
+
+        assert!(ClassificationConfig { spam_threshold: 0, ..Default::default() }.validate().is_err());
+    }
+}
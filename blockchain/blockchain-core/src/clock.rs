@@ -0,0 +1,245 @@
+// core/blockchain-core/src/clock.rs
+//! Time source for timestamp checks.
+//!
+//! Chain code asks a `Clock` for the time instead of calling `Utc::now()`,
+//! so tests can pin it with `MockClock`. A node whose clock may drift wraps
+//! the system clock in a `DriftMonitor` fed with NTP offsets: the wider the
+//! measured drift, the wider the window in which future block timestamps are
+//! still accepted, and the window narrows again once the clock is back in sync.
+use crate::{BlockchainError, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// How far `now` may be off from true time; timestamp checks widen
+    /// their acceptance window by this much
+    fn uncertainty(&self) -> Duration {
+        Duration::zero()
+    }
+}
+
+/// The host's wall clock, trusted as is
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that only moves when told to. Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Arc::new(Mutex::new(now)) }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *now += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DriftConfig {
+    /// Recent NTP offsets kept; their median is the drift estimate, so one
+    /// bad sample does not move it
+    pub samples: usize,
+    /// Drift at which the clock counts as `Drifting`
+    pub warn_offset_ms: i64,
+    /// Drift at which the clock counts as `Unsynchronized`; the acceptance
+    /// window never widens by more than this
+    pub max_offset_ms: i64,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self { samples: 5, warn_offset_ms: 500, max_offset_ms: 10_000 }
+    }
+}
+
+impl DriftConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.samples == 0 {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "Drift monitor needs at least one sample".to_string(),
+            });
+        }
+        if self.warn_offset_ms <= 0 || self.max_offset_ms <= self.warn_offset_ms {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: format!(
+                    "Drift thresholds must satisfy 0 < warn_offset_ms < max_offset_ms, got {} and {}",
+                    self.warn_offset_ms, self.max_offset_ms
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftStatus {
+    /// No offset measured yet
+    #[default]
+    Unknown,
+    InSync,
+    Drifting,
+    Unsynchronized,
+}
+
+impl fmt::Display for DriftStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriftStatus::Unknown => write!(f, "unknown"),
+            DriftStatus::InSync => write!(f, "in_sync"),
+            DriftStatus::Drifting => write!(f, "drifting"),
+            DriftStatus::Unsynchronized => write!(f, "unsynchronized"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct DriftState {
+    /// Offsets in milliseconds, oldest first
+    samples: VecDeque<i64>,
+    status: DriftStatus,
+}
+
+/// Wraps a clock with the drift measured against NTP.
+///
+/// The time itself is not corrected, since peers judge our timestamps by
+/// their own clocks; only `uncertainty` follows the drift.
+#[derive(Debug)]
+pub struct DriftMonitor {
+    clock: Arc<dyn Clock>,
+    config: DriftConfig,
+    state: Mutex<DriftState>,
+}
+
+impl DriftMonitor {
+    pub fn new(clock: Arc<dyn Clock>, config: DriftConfig) -> Self {
+        Self { clock, config, state: Mutex::new(DriftState::default()) }
+    }
+
+    /// Record an NTP offset (true time minus local time), returning the new
+    /// status if it changed
+    pub fn record(&self, offset: Duration) -> Option<DriftStatus> {
+        let mut state = self.state();
+        state.samples.push_back(offset.num_milliseconds());
+        while state.samples.len() > self.config.samples {
+            state.samples.pop_front();
+        }
+
+        let drift = median(&state.samples).unsigned_abs();
+        let status = if drift >= self.config.max_offset_ms as u64 {
+            DriftStatus::Unsynchronized
+        } else if drift >= self.config.warn_offset_ms as u64 {
+            DriftStatus::Drifting
+        } else {
+            DriftStatus::InSync
+        };
+
+        let changed = status != state.status;
+        state.status = status;
+        changed.then_some(status)
+    }
+
+    /// Median of the recent offsets, `None` before the first sample
+    pub fn offset(&self) -> Option<Duration> {
+        let state = self.state();
+        (!state.samples.is_empty()).then(|| Duration::milliseconds(median(&state.samples)))
+    }
+
+    pub fn status(&self) -> DriftStatus {
+        self.state().status
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, DriftState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Clock for DriftMonitor {
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    fn uncertainty(&self) -> Duration {
+        let drift = self.offset().map_or(0, |offset| offset.num_milliseconds().abs());
+        Duration::milliseconds(drift.min(self.config.max_offset_ms))
+    }
+}
+
+fn median(samples: &VecDeque<i64>) -> i64 {
+    let mut sorted: Vec<i64> = samples.iter().copied().collect();
+    sorted.sort_unstable();
+    match sorted.len() {
+        0 => 0,
+        len if len % 2 == 0 => (sorted[len / 2 - 1] + sorted[len / 2]) / 2,
+        len => sorted[len / 2],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_mock_clock_is_shared() {
+        let clock = MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+        let copy = clock.clone();
+        clock.advance(Duration::seconds(30));
+        assert_eq!(copy.now(), Utc.timestamp_opt(1_700_000_030, 0).unwrap());
+        assert_eq!(copy.uncertainty(), Duration::zero());
+    }
+
+    #[test]
+    fn test_drift_widens_and_narrows() {
+        let config = DriftConfig { samples: 3, ..Default::default() };
+        config.validate().unwrap();
+        let monitor = DriftMonitor::new(Arc::new(SystemClock), config);
+        assert_eq!(monitor.status(), DriftStatus::Unknown);
+        assert_eq!(monitor.uncertainty(), Duration::zero());
+
+        assert_eq!(monitor.record(Duration::milliseconds(-20)), Some(DriftStatus::InSync));
+        assert_eq!(monitor.record(Duration::milliseconds(-30)), None);
+        assert_eq!(monitor.uncertainty(), Duration::milliseconds(25));
+        // One bad sample does not move the median
+        assert_eq!(monitor.record(Duration::milliseconds(2_000)), None);
+        assert_eq!(monitor.record(Duration::milliseconds(-1_500)), None);
+        assert_eq!(monitor.record(Duration::milliseconds(-1_800)), Some(DriftStatus::Drifting));
+        assert_eq!(monitor.uncertainty(), Duration::milliseconds(1_500));
+
+        assert_eq!(monitor.record(Duration::seconds(60)), None);
+        assert_eq!(monitor.record(Duration::seconds(60)), Some(DriftStatus::Unsynchronized));
+        assert_eq!(monitor.uncertainty(), Duration::seconds(10));
+
+        for _ in 0..3 {
+            monitor.record(Duration::milliseconds(5));
+        }
+        assert_eq!(monitor.status(), DriftStatus::InSync);
+        assert_eq!(monitor.uncertainty(), Duration::milliseconds(5));
+    }
+}
@@ -0,0 +1,126 @@
+// core/blockchain-core/src/config_check.rs
+//! Startup configuration checks that report every problem at once.
+//!
+//! Each config section records its problems under the path of the offending
+//! field, e.g. `scylla.retry_policy.base_delay_ms`, instead of stopping at
+//! the first one, so an operator can fix a whole config file in one pass
+//! before the node connects to anything.
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Dotted path of the field, e.g. `p2p.outbound.low_max_delay_ms`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Collects issues while walking a config tree
+#[derive(Debug, Default)]
+pub struct ConfigReport {
+    scope: String,
+    issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn path(&self, field: &str) -> String {
+        match (self.scope.is_empty(), field.is_empty()) {
+            (true, _) => field.to_string(),
+            (false, true) => self.scope.clone(),
+            (false, false) => format!("{}.{}", self.scope, field),
+        }
+    }
+
+    /// Record a problem with `field` of the current section
+    pub fn error(&mut self, field: &str, message: impl Into<String>) {
+        let path = self.path(field);
+        self.issues.push(ConfigIssue { path, message: message.into() });
+    }
+
+    /// Record `message` against `field` if `failed`
+    pub fn check(&mut self, failed: bool, field: &str, message: impl Into<String>) {
+        if failed {
+            self.error(field, message);
+        }
+    }
+
+    /// Record the outcome of a section's own `validate`, which stops at its first problem
+    pub fn adopt<E: fmt::Display>(&mut self, field: &str, outcome: std::result::Result<(), E>) {
+        if let Err(e) = outcome {
+            self.error(field, e.to_string());
+        }
+    }
+
+    /// Check a nested section, recording its issues under `field`
+    pub fn section(&mut self, field: &str, check: impl FnOnce(&mut ConfigReport)) {
+        let mut nested = ConfigReport { scope: self.path(field), issues: Vec::new() };
+        check(&mut nested);
+        self.issues.append(&mut nested.issues);
+    }
+
+    pub fn issues(&self) -> &[ConfigIssue] {
+        &self.issues
+    }
+
+    pub fn finish(self) -> std::result::Result<(), ConfigErrors> {
+        if self.issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigErrors(self.issues))
+        }
+    }
+}
+
+/// Every problem found in a configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<ConfigIssue>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.as_slice() {
+            [issue] => write!(f, "Invalid configuration: {}", issue),
+            issues => {
+                write!(f, "Invalid configuration, {} problems:", issues.len())?;
+                for issue in issues {
+                    write!(f, "\n  - {}", issue)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collects_every_issue_with_paths() {
+        let mut report = ConfigReport::new();
+        report.check(false, "listen_addr", "never recorded");
+        report.section("scylla", |scylla| {
+            scylla.error("keyspace", "Keyspace name cannot be empty");
+            scylla.section("retry_policy", |retry| {
+                retry.check(true, "base_delay_ms", "must not exceed max_delay_ms");
+            });
+        });
+        report.adopt("p2p.nat", Err::<(), _>("NAT confirmations must be greater than 0"));
+
+        let errors = report.finish().unwrap_err();
+        let paths: Vec<&str> = errors.0.iter().map(|issue| issue.path.as_str()).collect();
+        assert_eq!(paths, vec!["scylla.keyspace", "scylla.retry_policy.base_delay_ms", "p2p.nat"]);
+        assert!(errors.to_string().starts_with("Invalid configuration, 3 problems:"));
+        assert!(ConfigReport::new().finish().is_ok());
+    }
+}
@@ -0,0 +1,128 @@
+// core/blockchain-core/src/emission.rs
+use crate::{Amount, BlockHeight, BlockchainError, Result};
+use crate::fees::BPS_DENOMINATOR;
+use serde::{Deserialize, Serialize};
+
+/// New coins minted per block, before fees
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EmissionSchedule {
+    /// Same reward at every height
+    Fixed { reward: Amount },
+    /// Reward halves every `interval_blocks`
+    Halving {
+        initial_reward: Amount,
+        interval_blocks: u64,
+    },
+    /// Reward shrinks by `decay_bps` basis points every `interval_blocks`
+    Decay {
+        initial_reward: Amount,
+        interval_blocks: u64,
+        decay_bps: u16,
+    },
+}
+
+impl EmissionSchedule {
+    /// Reward for the block at `height`; genesis mints nothing
+    pub fn reward_at(&self, height: BlockHeight) -> Amount {
+        if height == 0 {
+            return 0;
+        }
+
+        match *self {
+            EmissionSchedule::Fixed { reward } => reward,
+            EmissionSchedule::Halving { initial_reward, interval_blocks } => {
+                let halvings = (height - 1) / interval_blocks;
+                if halvings >= Amount::BITS as u64 {
+                    0
+                } else {
+                    initial_reward >> halvings
+                }
+            }
+            EmissionSchedule::Decay { initial_reward, interval_blocks, decay_bps } => {
+                if decay_bps == 0 {
+                    return initial_reward;
+                }
+                let keep = BPS_DENOMINATOR.saturating_sub(decay_bps as u64);
+                let mut reward = initial_reward;
+                for _ in 0..(height - 1) / interval_blocks {
+                    if reward == 0 {
+                        break;
+                    }
+                    reward = (reward as u128 * keep as u128 / BPS_DENOMINATOR as u128) as Amount;
+                }
+                reward
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| {
+            Err(BlockchainError::InvalidChainParams {
+                reason: reason.to_string(),
+            })
+        };
+
+        match *self {
+            EmissionSchedule::Halving { interval_blocks: 0, .. } => {
+                invalid("Halving interval must be greater than 0")
+            }
+            EmissionSchedule::Decay { interval_blocks: 0, .. } => {
+                invalid("Decay interval must be greater than 0")
+            }
+            EmissionSchedule::Decay { decay_bps, .. } if decay_bps as u64 > BPS_DENOMINATOR => {
+                invalid("Decay cannot exceed 100%")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for EmissionSchedule {
+    fn default() -> Self {
+        EmissionSchedule::Halving {
+            initial_reward: 5_000_000_000,
+            interval_blocks: 2_100_000,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_halving_schedule() {
+        let schedule = EmissionSchedule::Halving { initial_reward: 1_000, interval_blocks: 10 };
+        assert_eq!(schedule.reward_at(0), 0);
+        assert_eq!(schedule.reward_at(1), 1_000);
+        assert_eq!(schedule.reward_at(10), 1_000);
+        assert_eq!(schedule.reward_at(11), 500);
+        assert_eq!(schedule.reward_at(31), 125);
+        assert_eq!(schedule.reward_at(u64::MAX), 0);
+    }
+
+    #[test]
+    fn test_fixed_schedule() {
+        let schedule = EmissionSchedule::Fixed { reward: 250 };
+        assert_eq!(schedule.reward_at(0), 0);
+        assert_eq!(schedule.reward_at(1), 250);
+        assert_eq!(schedule.reward_at(u64::MAX), 250);
+
+        let json = serde_json::to_string(&schedule).unwrap();
+        assert_eq!(json, r#"{"kind":"fixed","reward":250}"#);
+        assert_eq!(serde_json::from_str::<EmissionSchedule>(&json).unwrap(), schedule);
+    }
+
+    #[test]
+    fn test_decay_schedule() {
+        let schedule = EmissionSchedule::Decay { initial_reward: 1_000, interval_blocks: 5, decay_bps: 1_000 };
+        assert_eq!(schedule.reward_at(5), 1_000);
+        assert_eq!(schedule.reward_at(6), 900);
+        assert_eq!(schedule.reward_at(11), 810);
+
+        assert!(EmissionSchedule::Decay { initial_reward: 1, interval_blocks: 0, decay_bps: 1 }.validate().is_err());
+        assert!(EmissionSchedule::Decay { initial_reward: 1, interval_blocks: 1, decay_bps: 10_001 }.validate().is_err());
+        EmissionSchedule::default().validate().unwrap();
+    }
+}
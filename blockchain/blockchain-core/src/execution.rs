@@ -0,0 +1,528 @@
+// core/blockchain-core/src/execution.rs
+use crate::gas_estimate::intrinsic_gas;
+use crate::trace::{AccessKind, ExecutionTrace, ExecutionTracer, StepKind, TraceLimits};
+use crate::smt::{SparseMerkleProof, SparseMerkleTree};
+use crate::{
+    hash_data, Address, Amount, Block, BlockHash, BlockHeight, BlockchainError, ChainSpec, FeeDistribution, FeeSplit,
+    Nonce, Result, Transaction, TxHash,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Balance and next expected nonce of an account
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountState {
+    pub balance: Amount,
+    pub nonce: Nonce,
+}
+
+/// Result of applying one transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionReceipt {
+    pub tx_hash: TxHash,
+    pub block_height: BlockHeight,
+    /// Position of the transaction within its block
+    pub index: u32,
+    /// Fee charged to the sender and how it was divided
+    pub fee: FeeSplit,
+}
+
+/// Receipts, fee totals and new issuance produced by applying a block
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockOutcome {
+    pub receipts: Vec<TransactionReceipt>,
+    pub fees: FeeSplit,
+    /// Newly minted block reward, excluding the fees and tips paid out through the coinbase
+    pub reward: Amount,
+}
+
+/// Evidence of an account's state, or of its absence, in a ledger with a
+/// given state root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountProof {
+    pub address: Address,
+    /// `None` proves that no account exists at `address`
+    pub state: Option<AccountState>,
+    pub proof: SparseMerkleProof,
+}
+
+impl AccountProof {
+    /// Whether the proof holds for `state_root`
+    pub fn verify(&self, state_root: &BlockHash) -> Result<bool> {
+        let leaf = self.state.map(|state| account_leaf(&self.address, &state));
+        Ok(self.proof.verify(state_root, &self.address, leaf))
+    }
+}
+
+/// Leaf committing to `address` holding `state` in the state tree
+fn account_leaf(address: &Address, state: &AccountState) -> BlockHash {
+    let mut bytes = Vec::with_capacity(36);
+    bytes.extend_from_slice(address);
+    bytes.extend_from_slice(&state.balance.to_be_bytes());
+    bytes.extend_from_slice(&state.nonce.to_be_bytes());
+    hash_data(&bytes)
+}
+
+/// Account state the chain's transactions are applied to
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    accounts: HashMap<Address, AccountState>,
+    /// Sparse merkle tree over `accounts`, kept current on every write
+    state: SparseMerkleTree,
+    /// Fees destroyed over the ledger's lifetime
+    burned: Amount,
+    /// Block rewards minted over the ledger's lifetime
+    issued: Amount,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn account(&self, address: &Address) -> AccountState {
+        self.accounts.get(address).copied().unwrap_or_default()
+    }
+
+    pub fn balance(&self, address: &Address) -> Amount {
+        self.account(address).balance
+    }
+
+    /// Accounts holding a balance or a used nonce, ordered by address
+    pub fn accounts(&self) -> Vec<(Address, AccountState)> {
+        let mut accounts: Vec<_> = self
+            .accounts
+            .iter()
+            .filter(|(_, state)| **state != AccountState::default())
+            .map(|(address, state)| (*address, *state))
+            .collect();
+        accounts.sort_unstable_by_key(|(address, _)| *address);
+        accounts
+    }
+
+    /// Root of the sparse merkle tree over `accounts`, keyed by address;
+    /// equal on two ledgers exactly when they hold the same accounts in the
+    /// same state. Kept up to date as accounts change, so this is cheap.
+    pub fn state_root(&self) -> Result<BlockHash> {
+        Ok(self.state.root())
+    }
+
+    /// Proof of `address`'s state against `state_root`; for an address not
+    /// in `accounts`, proof that it has no account
+    pub fn account_proof(&self, address: &Address) -> Result<AccountProof> {
+        let state = Some(self.account(address)).filter(|state| *state != AccountState::default());
+        Ok(AccountProof { address: *address, state, proof: self.state.proof(address) })
+    }
+
+    pub fn total_burned(&self) -> Amount {
+        self.burned
+    }
+
+    pub fn total_issued(&self) -> Amount {
+        self.issued
+    }
+
+    /// Mint funds into an account, e.g. from genesis allocations
+    pub fn credit(&mut self, address: &Address, amount: Amount) -> Result<()> {
+        let account = self.accounts.entry(*address).or_default();
+        account.balance = account.balance.checked_add(amount).ok_or_else(|| {
+            BlockchainError::InvalidTransaction {
+                reason: "Balance overflow".to_string(),
+            }
+        })?;
+        self.refresh_leaf(address);
+        Ok(())
+    }
+
+    /// Load an account's state as stored elsewhere, e.g. to check pending
+    /// transactions against the chain's current accounts
+    pub fn set_account(&mut self, address: Address, state: AccountState) {
+        self.accounts.insert(address, state);
+        self.refresh_leaf(&address);
+    }
+
+    /// Bring `address`'s state tree leaf in line with its account; an
+    /// account back at the default state leaves the tree
+    fn refresh_leaf(&mut self, address: &Address) {
+        let state = self.account(address);
+        let leaf = (state != AccountState::default()).then(|| account_leaf(address, &state));
+        self.state.update(address, leaf);
+    }
+
+    /// Apply a single transaction outside any block, returning how its fee
+    /// was divided. Either it applies or the ledger is left unchanged.
+    pub fn apply_transaction(&mut self, tx: &Transaction, fees: &FeeDistribution) -> Result<FeeSplit> {
+        let mut next = self.clone();
+        let split = next.execute(tx, fees, None)?;
+        *self = next;
+        Ok(split)
+    }
+
+    fn debit(&mut self, address: &Address, amount: Amount) -> Result<()> {
+        let account = self.accounts.entry(*address).or_default();
+        if account.balance < amount {
+            return Err(BlockchainError::InsufficientBalance {
+                have: account.balance,
+                need: amount,
+            });
+        }
+        account.balance -= amount;
+        self.refresh_leaf(address);
+        Ok(())
+    }
+
+    /// Apply every transaction in `block` under `spec`.
+    ///
+    /// Treasury shares are credited directly; the producer's share of fees
+    /// and any tips reach it through the coinbase, together with the block
+    /// reward.
+    /// A header committing to a state root must match the resulting state.
+    /// Either the whole block applies or the ledger is left unchanged.
+    pub fn apply_block(&mut self, block: &Block, spec: &ChainSpec) -> Result<BlockOutcome> {
+        spec.validate()?;
+        spec.validate_block(block)?;
+        let (next, outcome) = self.applied(block, &spec.fees)?;
+        if let Some(expected) = block.header.state_root {
+            if next.state_root()? != expected {
+                return Err(BlockchainError::BlockValidationFailed {
+                    reason: format!("State root mismatch at height {}", block.header.height),
+                });
+            }
+        }
+        *self = next;
+        Ok(outcome)
+    }
+
+    /// Apply a block that was validated when the chain accepted it, e.g. one
+    /// read back from storage to rebuild historical state. Its state root is
+    /// not checked again.
+    pub fn replay_block(&mut self, block: &Block, fees: &FeeDistribution) -> Result<BlockOutcome> {
+        let (next, outcome) = self.applied(block, fees)?;
+        *self = next;
+        Ok(outcome)
+    }
+
+    /// `state_root` once `block` is applied, for a producer to commit to;
+    /// the ledger is left unchanged
+    pub fn state_root_after(&self, block: &Block, fees: &FeeDistribution) -> Result<BlockHash> {
+        self.applied(block, fees)?.0.state_root()
+    }
+
+    /// The ledger after `block`, and what applying it produced
+    fn applied(&self, block: &Block, fees: &FeeDistribution) -> Result<(Ledger, BlockOutcome)> {
+        let mut next = self.clone();
+        let mut outcome = BlockOutcome::default();
+
+        for (index, tx) in block.transactions.iter().enumerate() {
+            let split = next.execute(tx, fees, None)?;
+            outcome.fees += split;
+            outcome.receipts.push(TransactionReceipt {
+                tx_hash: tx.hash,
+                block_height: block.header.height,
+                index: index as u32,
+                fee: split,
+            });
+        }
+
+        outcome.reward = block
+            .coinbase()
+            .map(|coinbase| coinbase.amount().saturating_sub(outcome.fees.to_proposer()))
+            .unwrap_or(0);
+        next.issued += outcome.reward;
+        Ok((next, outcome))
+    }
+
+    /// Execute `tx` at position `index` of the block at `block_height`, as
+    /// if this ledger were the state before it, and record what it did.
+    ///
+    /// The ledger is left unchanged. A transaction that fails still yields
+    /// a trace, up to the failing step, with the error.
+    pub fn trace_transaction(
+        &self,
+        tx: &Transaction,
+        fees: &FeeDistribution,
+        block_height: BlockHeight,
+        index: u32,
+        limits: TraceLimits,
+    ) -> ExecutionTrace {
+        let mut tracer = ExecutionTracer::new(tx, block_height, index, limits);
+        let error = self.clone().execute(tx, fees, Some(&mut tracer)).err();
+        tracer.finish(error.map(|e| e.to_string()))
+    }
+
+    /// Gas `tx` needs, from a dry run against this ledger that charges the
+    /// estimate rather than the transaction's own limit. Fails as applying
+    /// it would, e.g. on a stale nonce or a sender unable to pay; the ledger
+    /// is left unchanged.
+    pub fn estimate_gas(&self, tx: &Transaction, fees: &FeeDistribution) -> Result<u64> {
+        if tx.is_coinbase() {
+            return Err(BlockchainError::InvalidTransaction {
+                reason: "Coinbase transactions pay no gas".to_string(),
+            });
+        }
+        let gas = intrinsic_gas(tx);
+        let probe = Transaction { gas_limit: gas, ..tx.clone() };
+        self.clone().execute(&probe, fees, None)?;
+        Ok(gas)
+    }
+
+    /// Apply one transaction, returning how its fee was divided. On error
+    /// the ledger may be partly updated; callers execute on a copy.
+    fn execute(
+        &mut self,
+        tx: &Transaction,
+        fees: &FeeDistribution,
+        mut tracer: Option<&mut ExecutionTracer>,
+    ) -> Result<FeeSplit> {
+        if tx.is_coinbase() {
+            // Minted rather than transferred; `validate_block` checked the amount
+            if let Some(producer) = tx.recipient() {
+                self.credit(&producer, tx.amount())?;
+                record_step(tracer.as_deref_mut(), StepKind::Mint, 0);
+                self.record_access(tracer, AccessKind::Write, &producer);
+            }
+            return Ok(FeeSplit::default());
+        }
+
+        let sender = tx.sender();
+        let expected = self.account(&sender).nonce;
+        self.record_access(tracer.as_deref_mut(), AccessKind::Read, &sender);
+        record_step(tracer.as_deref_mut(), StepKind::CheckNonce, 0);
+        if tx.nonce != expected {
+            return Err(BlockchainError::InvalidNonce {
+                expected,
+                actual: tx.nonce,
+            });
+        }
+
+        let fee = tx.gas_limit.checked_mul(tx.gas_price).ok_or_else(|| {
+            BlockchainError::InvalidTransaction {
+                reason: "Fee overflow".to_string(),
+            }
+        })?;
+        let cost = tx.amount().checked_add(fee).ok_or_else(|| {
+            BlockchainError::InvalidTransaction {
+                reason: "Cost overflow".to_string(),
+            }
+        })?;
+
+        self.debit(&sender, cost)?;
+        self.accounts.entry(sender).or_default().nonce += 1;
+        self.refresh_leaf(&sender);
+        // The whole gas limit is charged; there is no metering to refund unused gas
+        record_step(tracer.as_deref_mut(), StepKind::ChargeFee, tx.gas_limit);
+        self.record_access(tracer.as_deref_mut(), AccessKind::Write, &sender);
+
+        if let Some(recipient) = tx.recipient() {
+            self.credit(&recipient, tx.amount())?;
+            record_step(tracer.as_deref_mut(), StepKind::Transfer, 0);
+            self.record_access(tracer.as_deref_mut(), AccessKind::Write, &recipient);
+        }
+
+        let split = fees.split_transaction(tx);
+        record_step(tracer.as_deref_mut(), StepKind::DistributeFee, 0);
+        if let Some(treasury) = &fees.treasury {
+            self.credit(treasury, split.treasury)?;
+            self.record_access(tracer, AccessKind::Write, treasury);
+        }
+        self.burned += split.burned;
+        Ok(split)
+    }
+
+    fn record_access(&self, tracer: Option<&mut ExecutionTracer>, kind: AccessKind, address: &Address) {
+        if let Some(tracer) = tracer {
+            let account = self.account(address);
+            tracer.access(kind, *address, account.balance, account.nonce);
+        }
+    }
+}
+
+fn record_step(tracer: Option<&mut ExecutionTracer>, kind: StepKind, gas: u64) {
+    if let Some(tracer) = tracer {
+        tracer.step(kind, gas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::TESTNET_CHAIN_ID;
+    use crate::{ChainParams, EmissionSchedule, FeeDistribution, Transaction};
+
+    const ALICE: Address = [1; 20];
+    const BOB: Address = [2; 20];
+    const TREASURY: Address = [3; 20];
+    const PROPOSER: Address = [4; 20];
+
+    fn spec() -> ChainSpec {
+        ChainSpec {
+            params: ChainParams::testnet(),
+            fees: FeeDistribution {
+                burn_bps: 2_000,
+                treasury_bps: 1_000,
+                treasury: Some(TREASURY),
+                base_gas_price: None,
+            },
+            emission: EmissionSchedule::Halving { initial_reward: 1_000, interval_blocks: 100 },
+            ..ChainSpec::default()
+        }
+    }
+
+    fn transfer(amount: u64, nonce: u64, gas_price: u64) -> Transaction {
+        Transaction::new_transfer(ALICE, BOB, amount, nonce, 21_000, gas_price)
+            .unwrap()
+            .with_chain_id(TESTNET_CHAIN_ID)
+            .unwrap()
+    }
+
+    fn produce(spec: &ChainSpec, transactions: Vec<Transaction>) -> Block {
+        spec.produce_block(PROPOSER, 1, [0; 32], transactions, 1).unwrap()
+    }
+
+    #[test]
+    fn test_apply_block_splits_fees() {
+        let spec = spec();
+        let mut ledger = Ledger::new();
+        ledger.credit(&ALICE, 1_000_000).unwrap();
+
+        let block = produce(&spec, vec![transfer(100, 0, 1), transfer(50, 1, 2)]);
+
+        let outcome = ledger.apply_block(&block, &spec).unwrap();
+        assert_eq!(outcome.receipts.len(), 3);
+        assert_eq!(outcome.receipts[2].index, 2);
+        assert_eq!(outcome.receipts[1].fee, FeeSplit { burned: 4_200, treasury: 2_100, proposer: 14_700, tip: 0 });
+        assert_eq!(outcome.fees.total(), 63_000);
+        assert_eq!(outcome.reward, 1_000);
+
+        assert_eq!(ledger.balance(&ALICE), 1_000_000 - 150 - 63_000);
+        assert_eq!(ledger.balance(&BOB), 150);
+        assert_eq!(ledger.balance(&TREASURY), outcome.fees.treasury);
+        assert_eq!(ledger.balance(&PROPOSER), outcome.fees.to_proposer() + 1_000);
+        assert_eq!(ledger.total_burned(), outcome.fees.burned);
+        assert_eq!(ledger.total_issued(), 1_000);
+        assert_eq!(ledger.account(&ALICE).nonce, 2);
+    }
+
+    #[test]
+    fn test_failed_block_leaves_ledger_unchanged() {
+        let spec = spec();
+        let mut ledger = Ledger::new();
+        ledger.credit(&ALICE, 30_000).unwrap();
+
+        // The second transfer cannot cover its fee
+        let block = produce(&spec, vec![transfer(100, 0, 1), transfer(100, 1, 1)]);
+
+        let err = ledger.apply_block(&block, &spec).unwrap_err();
+        assert!(matches!(err, BlockchainError::InsufficientBalance { .. }));
+        assert_eq!(ledger.balance(&ALICE), 30_000);
+        assert_eq!(ledger.account(&ALICE).nonce, 0);
+        assert_eq!(ledger.balance(&PROPOSER), 0);
+        assert_eq!(ledger.total_issued(), 0);
+    }
+
+    #[test]
+    fn test_state_root_covers_every_account() {
+        let mut ledger = Ledger::new();
+        ledger.credit(&BOB, 5).unwrap();
+        ledger.credit(&ALICE, 10).unwrap();
+        ledger.credit(&TREASURY, 0).unwrap();
+        let addresses: Vec<Address> = ledger.accounts().iter().map(|(address, _)| *address).collect();
+        assert_eq!(addresses, vec![ALICE, BOB]);
+
+        let mut other = Ledger::new();
+        other.credit(&ALICE, 10).unwrap();
+        other.credit(&BOB, 5).unwrap();
+        assert_eq!(ledger.state_root().unwrap(), other.state_root().unwrap());
+        other.credit(&BOB, 1).unwrap();
+        assert_ne!(ledger.state_root().unwrap(), other.state_root().unwrap());
+
+        let proof = ledger.account_proof(&BOB).unwrap();
+        assert_eq!(proof.state.unwrap().balance, 5);
+        assert!(proof.verify(&ledger.state_root().unwrap()).unwrap());
+        assert!(!proof.verify(&other.state_root().unwrap()).unwrap());
+
+        // An account left at its default state is absent, and provably so
+        let absent = ledger.account_proof(&TREASURY).unwrap();
+        assert_eq!(absent.state, None);
+        assert!(absent.verify(&ledger.state_root().unwrap()).unwrap());
+        let claimed = AccountProof { state: Some(AccountState { balance: 1, nonce: 0 }), ..absent };
+        assert!(!claimed.verify(&ledger.state_root().unwrap()).unwrap());
+
+        // Emptying an account takes its leaf back out of the tree
+        other.set_account(BOB, AccountState::default());
+        let mut alice_only = Ledger::new();
+        alice_only.credit(&ALICE, 10).unwrap();
+        assert_eq!(other.state_root().unwrap(), alice_only.state_root().unwrap());
+    }
+
+    #[test]
+    fn test_tips_reach_proposer_through_coinbase() {
+        let spec = ChainSpec { fees: FeeDistribution { base_gas_price: Some(1), ..spec().fees }, ..spec() };
+        let mut ledger = Ledger::new();
+        ledger.credit(&ALICE, 1_000_000).unwrap();
+
+        // Paying 3 per unit of gas tips 2 of it: 42_000 to the proposer alone
+        let block = produce(&spec, vec![transfer(100, 0, 3)]);
+        assert_eq!(block.total_tips(&spec.fees), 42_000);
+        let outcome = ledger.apply_block(&block, &spec).unwrap();
+        assert_eq!(outcome.fees, FeeSplit { burned: 4_200, treasury: 2_100, proposer: 14_700, tip: 42_000 });
+        assert_eq!(outcome.receipts[1].fee.tip, 42_000);
+        assert_eq!(outcome.reward, 1_000);
+        assert_eq!(ledger.balance(&PROPOSER), 14_700 + 42_000 + 1_000);
+        assert_eq!(ledger.total_burned(), 4_200);
+    }
+
+    #[test]
+    fn test_apply_block_checks_committed_state_root() {
+        let spec = spec();
+        let mut ledger = Ledger::new();
+        ledger.credit(&ALICE, 1_000_000).unwrap();
+
+        let block = produce(&spec, vec![transfer(100, 0, 1)]);
+        let root = ledger.state_root_after(&block, &spec.fees).unwrap();
+        let committed = block.clone().with_state_root(root).unwrap();
+        committed.validate().unwrap();
+        let decoded: Block = bincode::deserialize(&bincode::serialize(&committed).unwrap()).unwrap();
+        assert_eq!(decoded, committed);
+
+        let wrong = block.with_state_root([9; 32]).unwrap();
+        let err = ledger.clone().apply_block(&wrong, &spec).unwrap_err();
+        assert!(err.to_string().contains("State root mismatch"), "{}", err);
+
+        ledger.apply_block(&committed, &spec).unwrap();
+        assert_eq!(ledger.state_root().unwrap(), root);
+        let proof = ledger.account_proof(&BOB).unwrap();
+        assert!(committed.header.verify_account(&proof).unwrap());
+        assert!(!wrong.header.verify_account(&proof).unwrap());
+    }
+
+    #[test]
+    fn test_trace_records_steps_until_failure() {
+        let spec = spec();
+        let mut ledger = Ledger::new();
+        ledger.credit(&ALICE, 30_000).unwrap();
+
+        let trace = ledger.trace_transaction(&transfer(100, 0, 1), &spec.fees, 1, 1, TraceLimits::default());
+        assert_eq!(trace.error, None);
+        let steps: Vec<StepKind> = trace.steps.iter().map(|step| step.kind).collect();
+        assert_eq!(steps, vec![StepKind::CheckNonce, StepKind::ChargeFee, StepKind::Transfer, StepKind::DistributeFee]);
+        assert_eq!(trace.gas_used, 21_000);
+        assert_eq!(trace.frames[0].gas_used, 21_000);
+        assert_eq!(trace.accesses.last().unwrap().address, TREASURY);
+        assert_eq!(trace.accesses[1].balance, 30_000 - 100 - 21_000);
+        // Tracing never changes the ledger
+        assert_eq!(ledger.balance(&ALICE), 30_000);
+
+        // A failing transaction is traced up to the step that failed
+        let trace = ledger.trace_transaction(&transfer(100, 0, 2), &spec.fees, 1, 1, TraceLimits::default());
+        assert!(trace.error.unwrap().contains("Insufficient balance"));
+        assert_eq!(trace.steps.len(), 1);
+        assert_eq!(trace.gas_used, 0);
+
+        let limits = TraceLimits { max_entries: 3, ..Default::default() };
+        let trace = ledger.trace_transaction(&transfer(100, 0, 1), &spec.fees, 1, 1, limits);
+        assert!(trace.truncated);
+        assert_eq!(trace.steps.len() + trace.accesses.len(), 3);
+        assert_eq!(trace.gas_used, 21_000);
+    }
+}
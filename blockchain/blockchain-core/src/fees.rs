@@ -0,0 +1,176 @@
+// core/blockchain-core/src/fees.rs
+use crate::{Address, Amount, BlockchainError, Result, Transaction};
+use serde::{Deserialize, Serialize};
+use std::ops::AddAssign;
+
+/// Shares are expressed in basis points of the fee
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Percent a replacement must raise the gas price of the pending transaction
+/// it replaces by, unless configured otherwise
+pub const DEFAULT_REPLACEMENT_BUMP_PERCENT: u64 = 10;
+
+/// Lowest gas price a transaction may replace a pending one paying `current`
+/// with: `bump_percent` more, rounded up, and always at least 1 more, so a
+/// sender cannot churn the pool with replacements that pay nothing extra
+pub fn min_replacement_gas_price(current: Amount, bump_percent: u64) -> Amount {
+    // Rounded up, and at least 1
+    let bump = (current as u128 * bump_percent as u128).saturating_sub(1) / 100 + 1;
+    current.saturating_add(bump.min(Amount::MAX as u128) as Amount)
+}
+
+/// How transaction fees are divided between burning, the treasury and the block proposer.
+///
+/// With a `base_gas_price`, only the fee up to that price per unit of gas is
+/// divided; whatever a transaction pays above it is a priority tip, paid to
+/// the proposer in full so producers are rewarded for including it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeDistribution {
+    /// Share destroyed, in basis points
+    pub burn_bps: u16,
+    /// Share paid to `treasury`, in basis points
+    pub treasury_bps: u16,
+    /// Treasury account, required when `treasury_bps` is non-zero
+    pub treasury: Option<Address>,
+    /// Gas price above which a transaction tips the proposer; `None` divides the whole fee
+    #[serde(default)]
+    pub base_gas_price: Option<Amount>,
+}
+
+impl FeeDistribution {
+    pub fn validate(&self) -> Result<()> {
+        if self.burn_bps as u64 + self.treasury_bps as u64 > BPS_DENOMINATOR {
+            return Err(BlockchainError::InvalidFeeDistribution {
+                reason: "Burn and treasury shares exceed 100%".to_string(),
+            });
+        }
+        if self.treasury_bps > 0 && self.treasury.is_none() {
+            return Err(BlockchainError::InvalidFeeDistribution {
+                reason: "Treasury share configured without a treasury address".to_string(),
+            });
+        }
+        if self.base_gas_price == Some(0) {
+            return Err(BlockchainError::InvalidFeeDistribution {
+                reason: "Base gas price must be greater than 0".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Split a fee; rounding always favours the proposer so no fee is lost
+    pub fn split(&self, fee: Amount) -> FeeSplit {
+        let share = |bps: u16| (fee as u128 * bps as u128 / BPS_DENOMINATOR as u128) as Amount;
+        let burned = share(self.burn_bps);
+        let treasury = share(self.treasury_bps);
+
+        FeeSplit {
+            burned,
+            treasury,
+            proposer: fee - burned - treasury,
+            tip: 0,
+        }
+    }
+
+    /// Split the fee `tx` pays: up to `base_gas_price` per unit of gas as
+    /// `split` does, the rest to the proposer as a tip
+    pub fn split_transaction(&self, tx: &Transaction) -> FeeSplit {
+        let base_price = self.base_gas_price.map_or(tx.gas_price, |base| base.min(tx.gas_price));
+        let mut split = self.split(tx.gas_limit.saturating_mul(base_price));
+        split.tip = tx.gas_limit.saturating_mul(tx.gas_price - base_price);
+        split
+    }
+}
+
+/// Where one fee, or the fees of a whole block, went
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSplit {
+    pub burned: Amount,
+    pub treasury: Amount,
+    pub proposer: Amount,
+    /// Paid to the proposer on top of its share, from gas prices above the base
+    #[serde(default)]
+    pub tip: Amount,
+}
+
+impl FeeSplit {
+    pub fn total(&self) -> Amount {
+        self.burned + self.treasury + self.proposer + self.tip
+    }
+
+    /// Everything the proposer receives: its share plus tips
+    pub fn to_proposer(&self) -> Amount {
+        self.proposer + self.tip
+    }
+}
+
+impl AddAssign for FeeSplit {
+    fn add_assign(&mut self, other: FeeSplit) {
+        self.burned += other.burned;
+        self.treasury += other.treasury;
+        self.proposer += other.proposer;
+        self.tip += other.tip;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_split() {
+        let distribution = FeeDistribution {
+            burn_bps: 5_000,
+            treasury_bps: 1_000,
+            treasury: Some([9; 20]),
+            base_gas_price: None,
+        };
+        distribution.validate().unwrap();
+
+        let split = distribution.split(1_001);
+        assert_eq!(split, FeeSplit { burned: 500, treasury: 100, proposer: 401, tip: 0 });
+        assert_eq!(split.total(), 1_001);
+
+        // Default keeps the whole fee with the proposer
+        assert_eq!(FeeDistribution::default().split(42).proposer, 42);
+        assert_eq!(distribution.split(u64::MAX).total(), u64::MAX);
+    }
+
+    #[test]
+    fn test_min_replacement_gas_price() {
+        assert_eq!(min_replacement_gas_price(100, 10), 110);
+        // Rounded up, and never the same price
+        assert_eq!(min_replacement_gas_price(15, 10), 17);
+        assert_eq!(min_replacement_gas_price(5, 0), 6);
+        assert_eq!(min_replacement_gas_price(0, 10), 1);
+        assert_eq!(min_replacement_gas_price(Amount::MAX, 10), Amount::MAX);
+    }
+
+    #[test]
+    fn test_tips_above_base_gas_price() {
+        let distribution = FeeDistribution { burn_bps: 5_000, base_gas_price: Some(10), ..FeeDistribution::default() };
+        distribution.validate().unwrap();
+        let tx = |gas_price| Transaction::new_transfer([1; 20], [2; 20], 1, 0, 100, gas_price).unwrap();
+
+        // 1_000 of base fee divided, 500 tipped in full
+        let split = distribution.split_transaction(&tx(15));
+        assert_eq!(split, FeeSplit { burned: 500, treasury: 0, proposer: 500, tip: 500 });
+        assert_eq!((split.total(), split.to_proposer()), (1_500, 1_000));
+        assert_eq!(distribution.split_transaction(&tx(8)).tip, 0);
+        assert_eq!(FeeDistribution::default().split_transaction(&tx(15)).tip, 0);
+        assert!(FeeDistribution { base_gas_price: Some(0), ..FeeDistribution::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_distributions() {
+        let over = FeeDistribution {
+            burn_bps: 9_000,
+            treasury_bps: 2_000,
+            treasury: Some([9; 20]),
+            ..Default::default()
+        };
+        assert!(over.validate().is_err());
+
+        let no_treasury = FeeDistribution { burn_bps: 0, treasury_bps: 100, treasury: None, ..Default::default() };
+        assert!(no_treasury.validate().is_err());
+    }
+}
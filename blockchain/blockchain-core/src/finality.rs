@@ -0,0 +1,154 @@
+// core/blockchain-core/src/finality.rs
+//! Checkpoint finality on top of the fork-choice rule.
+//!
+//! Every `interval`-th main-chain block can be finalized by a checkpoint
+//! carrying signatures from a quorum of the finality validators. A finalized
+//! block is never rolled back: the chain refuses any reorg that forks below
+//! the last finalized height.
+use crate::signature::{self, KeyPair, SignatureScheme};
+use crate::{hash_serializable, Address, AddressExt, BlockHash, BlockHeight, BlockchainError, ChainId, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Domain separator so a checkpoint vote is never a valid signature elsewhere
+const CHECKPOINT_DOMAIN: &str = "checkpoint";
+
+/// Validators that finalize checkpoints and how often they do so
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalityConfig {
+    pub validators: Vec<Address>,
+    /// Only heights that are a multiple of this can be finalized
+    pub interval: u64,
+}
+
+impl FinalityConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.validators.is_empty() {
+            return Err(invalid_params("Finality needs at least one validator".to_string()));
+        }
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = self.validators.iter().find(|validator| !seen.insert(*validator)) {
+            return Err(invalid_params(format!("Duplicate finality validator {}", duplicate.to_checksum_hex())));
+        }
+        if self.interval == 0 {
+            return Err(invalid_params("Checkpoint interval must be greater than 0".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Signatures needed to finalize: more than two thirds of the validators
+    pub fn quorum(&self) -> usize {
+        self.validators.len() * 2 / 3 + 1
+    }
+
+    pub fn is_checkpoint_height(&self, height: BlockHeight) -> bool {
+        height > 0 && height.checked_rem(self.interval) == Some(0)
+    }
+
+    /// Check that `checkpoint` is at a checkpoint height and signed by a
+    /// quorum, returning the distinct validators that signed it
+    pub fn verify(&self, chain_id: ChainId, checkpoint: &Checkpoint) -> Result<Vec<Address>> {
+        if !self.is_checkpoint_height(checkpoint.height) {
+            return Err(rejected(format!(
+                "Height {} is not a multiple of the checkpoint interval {}",
+                checkpoint.height, self.interval
+            )));
+        }
+
+        let digest = checkpoint.vote_digest(chain_id)?;
+        let mut signers = Vec::new();
+        for vote in &checkpoint.signatures {
+            let scheme = SignatureScheme::of_signature(vote)
+                .ok_or_else(|| rejected(format!("Unrecognized checkpoint signature of {} bytes", vote.len())))?;
+            let signer = Address::from_public_key(&signature::verify_signature(scheme, &digest, vote)?)?;
+            if !self.validators.contains(&signer) {
+                return Err(rejected(format!("{} is not a finality validator", signer.to_checksum_hex())));
+            }
+            if !signers.contains(&signer) {
+                signers.push(signer);
+            }
+        }
+
+        if signers.len() < self.quorum() {
+            return Err(rejected(format!(
+                "Checkpoint at height {} has {} of {} required signatures",
+                checkpoint.height,
+                signers.len(),
+                self.quorum()
+            )));
+        }
+        Ok(signers)
+    }
+}
+
+/// A block the finality validators vote to make irreversible
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub height: BlockHeight,
+    pub block_hash: BlockHash,
+    pub signatures: Vec<Vec<u8>>,
+}
+
+impl Checkpoint {
+    pub fn new(height: BlockHeight, block_hash: BlockHash) -> Self {
+        Self { height, block_hash, signatures: Vec::new() }
+    }
+
+    /// Digest validators sign, bound to the chain so votes cannot be replayed
+    /// on another network
+    pub fn vote_digest(&self, chain_id: ChainId) -> Result<[u8; 32]> {
+        hash_serializable(&(CHECKPOINT_DOMAIN, chain_id, self.height, self.block_hash))
+    }
+
+    /// Add `key`'s vote
+    pub fn sign(&mut self, chain_id: ChainId, key: &KeyPair) -> Result<()> {
+        let vote = key.sign(&self.vote_digest(chain_id)?);
+        self.signatures.push(vote);
+        Ok(())
+    }
+}
+
+fn invalid_params(reason: String) -> BlockchainError {
+    BlockchainError::InvalidChainParams { reason }
+}
+
+fn rejected(reason: String) -> BlockchainError {
+    BlockchainError::ChainValidationFailed { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quorum_of_distinct_validators() {
+        let keys: Vec<KeyPair> = (0..4).map(|_| KeyPair::generate(SignatureScheme::Ed25519)).collect();
+        let config = FinalityConfig {
+            validators: keys.iter().map(|k| Address::from_public_key(&k.public_key()).unwrap()).collect(),
+            interval: 10,
+        };
+        config.validate().unwrap();
+        assert_eq!(config.quorum(), 3);
+
+        let mut checkpoint = Checkpoint::new(20, [7; 32]);
+        checkpoint.sign(1, &keys[0]).unwrap();
+        checkpoint.sign(1, &keys[0]).unwrap();
+        checkpoint.sign(1, &keys[1]).unwrap();
+        assert!(config.verify(1, &checkpoint).is_err());
+
+        checkpoint.sign(1, &keys[3]).unwrap();
+        assert_eq!(config.verify(1, &checkpoint).unwrap().len(), 3);
+        // Votes are bound to the chain id
+        assert!(config.verify(2, &checkpoint).is_err());
+
+        let mut off_interval = Checkpoint::new(15, [7; 32]);
+        for key in &keys {
+            off_interval.sign(1, key).unwrap();
+        }
+        assert!(config.verify(1, &off_interval).is_err());
+
+        let mut outsider = checkpoint.clone();
+        outsider.sign(1, &KeyPair::generate(SignatureScheme::Secp256k1)).unwrap();
+        assert!(config.verify(1, &outsider).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+// core/blockchain-core/src/gas_estimate.rs
+//! Gas a transaction needs, for senders choosing a gas limit.
+//!
+//! The ledger has no contract interpreter, so for now the estimate is the
+//! transaction's intrinsic gas: a base charge per transaction, a charge per
+//! byte of call data or of code and init data, and a creation charge for a
+//! deployment. Once execution is metered, an estimate will come from running
+//! the transaction instead. Either way a sender should not set the bare
+//! estimate as its limit; `with_margin` adds headroom for the state changing
+//! between estimation and inclusion.
+use crate::{Transaction, TransactionType};
+
+/// Charged to every non-coinbase transaction
+pub const TRANSACTION_GAS: u64 = 21_000;
+
+/// Charged on top for deploying a contract
+pub const CREATE_GAS: u64 = 32_000;
+
+pub const ZERO_BYTE_GAS: u64 = 4;
+
+pub const NONZERO_BYTE_GAS: u64 = 16;
+
+/// Headroom added to an estimate when suggesting a gas limit
+pub const DEFAULT_GAS_MARGIN_PERCENT: u64 = 20;
+
+/// Gas `tx` uses before any contract code runs; coinbase transactions pay none
+pub fn intrinsic_gas(tx: &Transaction) -> u64 {
+    match &tx.tx_type {
+        TransactionType::Transfer { .. } => TRANSACTION_GAS,
+        TransactionType::Call { data, .. } => TRANSACTION_GAS.saturating_add(data_gas(data)),
+        TransactionType::Deploy { code, init_data, .. } => TRANSACTION_GAS
+            .saturating_add(CREATE_GAS)
+            .saturating_add(data_gas(code))
+            .saturating_add(data_gas(init_data)),
+        TransactionType::Coinbase { .. } => 0,
+    }
+}
+
+/// `gas` raised by `margin_percent`, at most `max`, e.g. the block gas limit
+pub fn with_margin(gas: u64, margin_percent: u64, max: u64) -> u64 {
+    let margin = gas.saturating_mul(margin_percent) / 100;
+    gas.saturating_add(margin).min(max)
+}
+
+fn data_gas(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .map(|&byte| if byte == 0 { ZERO_BYTE_GAS } else { NONZERO_BYTE_GAS })
+        .fold(0, u64::saturating_add)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AccountState, BlockchainError, FeeDistribution, Ledger};
+
+    #[test]
+    fn test_estimates_intrinsic_gas_against_state() {
+        let (alice, bob) = ([1; 20], [2; 20]);
+        let transfer = Transaction::new_transfer(alice, bob, 100, 0, 1, 1).unwrap();
+        let call = Transaction::new_call(alice, bob, vec![0, 7, 7], 0, 0, 1, 1).unwrap();
+        let deploy = Transaction::new_deploy(alice, vec![7; 10], vec![0; 5], 0, 1, 1).unwrap();
+        assert_eq!(intrinsic_gas(&transfer), 21_000);
+        assert_eq!(intrinsic_gas(&call), 21_000 + 4 + 2 * 16);
+        assert_eq!(intrinsic_gas(&deploy), 21_000 + 32_000 + 10 * 16 + 5 * 4);
+        assert_eq!(with_margin(21_000, DEFAULT_GAS_MARGIN_PERCENT, 30_000_000), 25_200);
+        assert_eq!(with_margin(21_000, DEFAULT_GAS_MARGIN_PERCENT, 22_000), 22_000);
+
+        // The dry run charges the estimate, not the transaction's own limit,
+        // and leaves the ledger unchanged
+        let fees = FeeDistribution::default();
+        let mut ledger = Ledger::new();
+        ledger.set_account(alice, AccountState { balance: 21_100, nonce: 0 });
+        assert_eq!(ledger.estimate_gas(&transfer, &fees).unwrap(), 21_000);
+        assert_eq!(ledger.balance(&alice), 21_100);
+        let short = ledger.estimate_gas(&Transaction::new_transfer(alice, bob, 101, 0, 1, 1).unwrap(), &fees);
+        assert!(matches!(short, Err(BlockchainError::InsufficientBalance { have: 21_100, need: 21_101 })));
+        let reused = ledger.estimate_gas(&Transaction::new_transfer(alice, bob, 1, 1, 1, 1).unwrap(), &fees);
+        assert!(matches!(reused, Err(BlockchainError::InvalidNonce { expected: 0, actual: 1 })));
+    }
+}
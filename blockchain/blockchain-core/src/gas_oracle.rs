@@ -0,0 +1,127 @@
+// core/blockchain-core/src/gas_oracle.rs
+//! Gas price suggestions from recently included transactions.
+//!
+//! A suggestion is the gas prices paid in a sample of recent blocks, read at
+//! three percentiles: `slow` usually gets in within a few blocks, `standard`
+//! within the next one or two, and `fast` outbids almost every recent sender.
+//! Coinbase transactions pay no gas and are left out. Every suggestion is at
+//! least the floor, so a quiet chain never suggests a price the node refuses.
+use crate::{Amount, Block, BlockchainError, Result};
+use serde::{Deserialize, Serialize};
+
+/// How much to pay for inclusion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeSpeed {
+    Slow,
+    #[default]
+    Standard,
+    Fast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasOracleConfig {
+    /// Most recent blocks sampled per suggestion
+    pub sample_blocks: u64,
+    pub slow_percentile: u8,
+    pub standard_percentile: u8,
+    pub fast_percentile: u8,
+}
+
+impl Default for GasOracleConfig {
+    fn default() -> Self {
+        Self { sample_blocks: 20, slow_percentile: 25, standard_percentile: 50, fast_percentile: 90 }
+    }
+}
+
+impl GasOracleConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.sample_blocks == 0 {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "Gas oracle must sample at least one block".to_string(),
+            });
+        }
+        let percentiles = [self.slow_percentile, self.standard_percentile, self.fast_percentile];
+        if percentiles.iter().any(|&p| p > 100) || !percentiles.windows(2).all(|pair| pair[0] <= pair[1]) {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: format!("Gas oracle percentiles must rise from slow to fast within 100: {:?}", percentiles),
+            });
+        }
+        Ok(())
+    }
+
+    /// Suggestion from the gas prices paid in a sample, at least `floor`
+    pub fn suggest(&self, mut prices: Vec<Amount>, floor: Amount) -> FeeSuggestion {
+        prices.sort_unstable();
+        let at = |percentile: u8| {
+            let price = match prices.len() {
+                0 => floor,
+                len => prices[(len - 1) * percentile as usize / 100],
+            };
+            price.max(floor)
+        };
+        FeeSuggestion {
+            slow: at(self.slow_percentile),
+            standard: at(self.standard_percentile),
+            fast: at(self.fast_percentile),
+            sampled_transactions: prices.len(),
+        }
+    }
+
+    /// Suggestion from the transactions in `blocks`, at least `floor`
+    pub fn suggest_from_blocks<'a>(&self, blocks: impl IntoIterator<Item = &'a Block>, floor: Amount) -> FeeSuggestion {
+        self.suggest(blocks.into_iter().flat_map(block_gas_prices).collect(), floor)
+    }
+}
+
+/// Gas prices paid by `block`'s non-coinbase transactions
+pub fn block_gas_prices(block: &Block) -> impl Iterator<Item = Amount> + '_ {
+    block.transactions.iter().filter(|tx| !tx.is_coinbase()).map(|tx| tx.gas_price)
+}
+
+/// Gas prices to offer at each speed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSuggestion {
+    pub slow: Amount,
+    pub standard: Amount,
+    pub fast: Amount,
+    /// Transactions the suggestion was read from; 0 means only the floor
+    pub sampled_transactions: usize,
+}
+
+impl FeeSuggestion {
+    pub fn price(&self, speed: FeeSpeed) -> Amount {
+        match speed {
+            FeeSpeed::Slow => self.slow,
+            FeeSpeed::Standard => self.standard,
+            FeeSpeed::Fast => self.fast,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Transaction;
+
+    #[test]
+    fn test_suggestions_from_recent_blocks() {
+        let config = GasOracleConfig::default();
+        config.validate().unwrap();
+        assert!(GasOracleConfig { fast_percentile: 10, ..config }.validate().is_err());
+        assert!(GasOracleConfig { sample_blocks: 0, ..config }.validate().is_err());
+
+        let transfers = (1..=10)
+            .map(|price| Transaction::new_transfer([1; 20], [2; 20], 5, price, 21_000, price * 10).unwrap())
+            .collect();
+        let block = Block::new(1, [0; 32], transfers, 1).unwrap();
+        let suggestion = config.suggest_from_blocks([&block], 1);
+        assert_eq!(suggestion, FeeSuggestion { slow: 30, standard: 50, fast: 90, sampled_transactions: 10 });
+        assert_eq!(suggestion.price(FeeSpeed::Fast), 90);
+
+        // Prices below the floor, or no transactions at all, suggest the floor
+        assert_eq!(config.suggest_from_blocks([&block], 40).slow, 40);
+        let idle = config.suggest(Vec::new(), 7);
+        assert_eq!((idle.slow, idle.standard, idle.fast, idle.sampled_transactions), (7, 7, 7, 0));
+    }
+}
@@ -0,0 +1,178 @@
+// core/blockchain-core/src/golden_vectors.rs
+//! Fixed blocks and transactions with their expected hashes and encodings.
+//!
+//! A failure here means a hash or serialization change that would orphan
+//! data already written by earlier releases. Only update a vector together
+//! with a migration for the affected data.
+use crate::{Block, BlockHeader, Bloom, Transaction, TransactionStatus, TransactionType, LEGACY_CHAIN_ID};
+use chrono::{DateTime, TimeZone, Utc};
+
+/// `block_data` blob written by crate version 0.1.0
+const BLOCK_DATA_V0_1_0: &str = include_str!("../testdata/block_data_v0_1_0.hex");
+
+/// `tx_data` blob of `transfer_vector()` written by crate version 0.1.0
+const TRANSFER_TX_V0_1_0: &str = include_str!("../testdata/transfer_tx_v0_1_0.hex");
+
+fn fixed_time(offset_secs: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(1_700_000_000 + offset_secs, 0).unwrap()
+}
+
+fn decode_hex(data: &str) -> Vec<u8> {
+    hex::decode(data.split_whitespace().collect::<String>()).unwrap()
+}
+
+fn with_hash(mut tx: Transaction) -> Transaction {
+    tx.hash = tx.calculate_hash().unwrap();
+    tx
+}
+
+fn transfer_vector() -> Transaction {
+    with_hash(Transaction {
+        hash: [0u8; 32],
+        tx_type: TransactionType::Transfer {
+            from: [0x11; 20],
+            to: [0x22; 20],
+            amount: 1_000,
+        },
+        nonce: 7,
+        gas_limit: 21_000,
+        gas_price: 20,
+        timestamp: fixed_time(0),
+        signature: vec![0xab; 65],
+        status: TransactionStatus::Pending,
+        chain_id: LEGACY_CHAIN_ID,
+    })
+}
+
+fn call_vector() -> Transaction {
+    with_hash(Transaction {
+        hash: [0u8; 32],
+        tx_type: TransactionType::Call {
+            from: [0x33; 20],
+            to: [0x44; 20],
+            data: vec![0xde, 0xad, 0xbe, 0xef],
+            amount: 5,
+        },
+        nonce: 1,
+        gas_limit: 100_000,
+        gas_price: 30,
+        timestamp: fixed_time(1),
+        signature: vec![0xcd; 65],
+        status: TransactionStatus::Pending,
+        chain_id: LEGACY_CHAIN_ID,
+    })
+}
+
+fn deploy_vector() -> Transaction {
+    with_hash(Transaction {
+        hash: [0u8; 32],
+        tx_type: TransactionType::Deploy {
+            from: [0x55; 20],
+            code: vec![0x60, 0x80, 0x60, 0x40],
+            init_data: vec![0x01],
+        },
+        nonce: 0,
+        gas_limit: 500_000,
+        gas_price: 25,
+        timestamp: fixed_time(2),
+        signature: vec![0xef; 65],
+        status: TransactionStatus::Pending,
+        chain_id: LEGACY_CHAIN_ID,
+    })
+}
+
+fn block_vector() -> Block {
+    let transactions = vec![transfer_vector(), call_vector(), deploy_vector()];
+    let header = BlockHeader {
+        height: 42,
+        previous_hash: [0x66; 32],
+        merkle_root: hex32("28860bc126dbcd5945c67cca6f924ed3b8d9cf8a1bd0e7a1cef58746104952c8"),
+        timestamp: fixed_time(10),
+        nonce: 12_345,
+        difficulty: 1_000,
+        version: 1,
+        bloom: None,
+        proposer_signature: None,
+        state_root: None,
+        random_seed: None,
+        vrf_proof: None,
+    };
+
+    let mut block = Block {
+        hash: [0u8; 32],
+        header,
+        transaction_count: transactions.len() as u32,
+        transactions,
+        size: 812,
+    };
+    block.hash = block.calculate_hash().unwrap();
+    block
+}
+
+/// `block_vector()` as a version 2 header, committing to the address bloom
+fn block_vector_v2() -> Block {
+    let mut block = block_vector();
+    block.header.version = 2;
+    block.header.bloom = Some(Bloom::from_transactions(&block.transactions));
+    block.size = bincode::serialized_size(&block).unwrap();
+    block.hash = block.calculate_hash().unwrap();
+    block
+}
+
+fn hex32(data: &str) -> [u8; 32] {
+    decode_hex(data).try_into().unwrap()
+}
+
+#[test]
+fn test_transaction_hash_vectors() {
+    assert_eq!(hex::encode(transfer_vector().hash), "73b73ec7d9301a169b78881b2ac871ad2d554ad6b643aaf8bec33d9f31fe14fd");
+    assert_eq!(hex::encode(call_vector().hash), "58a2de55a72fbbbbee0bfd8f325fc844f90a5d66e5ba2c1d284d90179f183100");
+    assert_eq!(hex::encode(deploy_vector().hash), "5af8467e6fbf20b5b5b9d73064790bd66fcd5fbbb24351bfc7f6ec0d85a508b8");
+}
+
+#[test]
+fn test_transaction_hash_excludes_signature_and_status() {
+    let mut tx = transfer_vector();
+    tx.signature = Vec::new();
+    tx.status = TransactionStatus::Failed { reason: "vector".to_string() };
+    assert_eq!(tx.calculate_hash().unwrap(), transfer_vector().hash);
+}
+
+#[test]
+fn test_transaction_encoding_vector() {
+    // 0.1.0 left the chain id out of legacy transactions
+    let blob = decode_hex(TRANSFER_TX_V0_1_0);
+    assert_eq!(Transaction::decode_legacy(&blob).unwrap(), transfer_vector());
+
+    // Current builds always append it
+    let encoded = bincode::serialize(&transfer_vector()).unwrap();
+    assert_eq!(encoded, [blob, LEGACY_CHAIN_ID.to_le_bytes().to_vec()].concat());
+    assert_eq!(bincode::deserialize::<Transaction>(&encoded).unwrap(), transfer_vector());
+}
+
+#[test]
+fn test_block_vector() {
+    let block = block_vector();
+    block.validate().unwrap();
+    assert_eq!(hex::encode(block.hash), "29d9c44ee668d1f67c9b57bc29b93b244623a44b557889bff1f6eeb17681164e");
+}
+
+#[test]
+fn test_block_vector_v2() {
+    let block = block_vector_v2();
+    block.validate().unwrap();
+    assert_eq!(block.size, 812 + 8 + crate::bloom::BLOOM_BYTES as u64);
+    assert_eq!(hex::encode(block.hash), "c8e879c63eece6c419aff030c1bc8f45e6b565fe8352a2419210c3e23f8a6840");
+    assert_eq!(bincode::deserialize::<Block>(&bincode::serialize(&block).unwrap()).unwrap(), block);
+}
+
+#[test]
+fn test_block_data_compatibility() {
+    let blob = decode_hex(BLOCK_DATA_V0_1_0);
+    let decoded: Block = bincode::deserialize(&blob).unwrap();
+    assert_eq!(decoded, block_vector());
+    decoded.validate().unwrap();
+
+    // Current builds still write the same bytes
+    assert_eq!(bincode::serialize(&block_vector()).unwrap(), blob);
+}
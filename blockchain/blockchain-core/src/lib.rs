@@ -8,12 +8,66 @@ pub mod transaction_block;
 pub mod transaction;
 pub mod chain;
 pub mod merkle;
+pub mod signature;
+pub mod address;
+pub mod bloom;
+pub mod fees;
+pub mod execution;
+pub mod params;
+pub mod emission;
+pub mod orphans;
+pub mod canonical;
+pub mod poa;
+pub mod finality;
+pub mod admission;
+pub mod clock;
+pub mod config_check;
+pub mod memory;
+pub mod trace;
+pub mod production;
+pub mod gas_oracle;
+pub mod gas_estimate;
+pub mod classify;
+pub mod readiness;
+pub mod batch_verify;
+pub mod beacon;
+pub mod vrf;
+pub mod pos;
+pub mod smt;
+
+#[cfg(test)]
+mod golden_vectors;
 
 // Re-export main types
 pub use transaction_block::*;
 pub use transaction::*;
 pub use chain::*;
 pub use merkle::*;
+pub use signature::{KeyPair, SignatureScheme};
+pub use address::AddressExt;
+pub use bloom::Bloom;
+pub use fees::{min_replacement_gas_price, FeeDistribution, FeeSplit, DEFAULT_REPLACEMENT_BUMP_PERCENT};
+pub use execution::{AccountProof, AccountState, BlockOutcome, Ledger, TransactionReceipt};
+pub use params::{ChainParams, ChainSpec};
+pub use emission::EmissionSchedule;
+pub use orphans::OrphanPool;
+pub use poa::{Consensus, PoaConfig, SlotOutcome};
+pub use finality::{Checkpoint, FinalityConfig};
+pub use admission::{AdmissionLevel, AdmissionPolicy, AdmissionState, BackpressureConfig};
+pub use config_check::{ConfigErrors, ConfigIssue, ConfigReport};
+pub use clock::{Clock, DriftConfig, DriftMonitor, DriftStatus, MockClock, SystemClock};
+pub use memory::{MemoryAccountant, MemoryConfig, MemoryStats, MemorySubsystem, SubsystemMemory};
+pub use trace::{ExecutionTrace, TraceLimits};
+pub use production::{EmptyBlockRules, ProducerConfig};
+pub use smt::{SparseMerkleProof, SparseMerkleTree};
+pub use gas_oracle::{FeeSpeed, FeeSuggestion, GasOracleConfig};
+pub use gas_estimate::{intrinsic_gas, DEFAULT_GAS_MARGIN_PERCENT};
+pub use classify::{ClassificationConfig, ClassifierPipeline, TxClassifier};
+pub use readiness::{ReadinessConfig, ReadinessGate, ReadinessStatus};
+pub use batch_verify::{verify_transactions, VerifyConfig};
+pub use beacon::VrfOutput;
+pub use vrf::{VrfPublicKey, VrfSecretKey};
+pub use pos::{PosConfig, StakedValidator};
 
 /// Block hash type
 pub type BlockHash = [u8; 32];
@@ -33,6 +87,12 @@ pub type BlockHeight = u64;
 /// Nonce for transactions
 pub type Nonce = u64;
 
+/// Identifier of the network a transaction is valid on
+pub type ChainId = u64;
+
+/// Chain id of transactions created before replay protection
+pub const LEGACY_CHAIN_ID: ChainId = 0;
+
 /// Core blockchain errors
 #[derive(Debug, thiserror::Error)]
 pub enum BlockchainError {
@@ -54,6 +114,18 @@ pub enum BlockchainError {
     #[error("Invalid nonce: expected {expected}, got {actual}")]
     InvalidNonce { expected: Nonce, actual: Nonce },
     
+    #[error("Invalid signature: {reason}")]
+    InvalidSignature { reason: String },
+    
+    #[error("Invalid address: {reason}")]
+    InvalidAddress { reason: String },
+    
+    #[error("Invalid fee distribution: {reason}")]
+    InvalidFeeDistribution { reason: String },
+    
+    #[error("Invalid chain parameters: {reason}")]
+    InvalidChainParams { reason: String },
+    
     #[error("Serialization error: {0}")]
     SerializationError(#[from] bincode::Error),
     
@@ -76,9 +148,10 @@ pub fn hash_serializable<T: Serialize>(data: &T) -> Result<[u8; 32]> {
     Ok(hash_data(&bytes))
 }
 
-/// Validate an address format
+/// Validate an address format.
+///
+/// Raw bytes carry no checksum; text input is checked by `AddressExt::from_checksum_hex`.
 pub fn validate_address(address: &Address) -> bool {
-    // Basic validation - in production you'd check checksum, etc.
     !address.iter().all(|&b| b == 0)
 }
 
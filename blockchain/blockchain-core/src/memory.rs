@@ -0,0 +1,280 @@
+// core/blockchain-core/src/memory.rs
+//! Memory budgets shared by the in-memory pools, caches and buffers.
+//!
+//! Each subsystem charges the accountant for what it holds and releases it
+//! when entries go away. Charging never blocks or evicts: it only reports
+//! whether the subsystem is still within its budget. `enforce`, run by a
+//! background monitor or by a caller holding no subsystem locks, invokes the
+//! eviction callback of every subsystem over budget with the excess; the
+//! callback evicts through the subsystem, which releases the memory as usual.
+use crate::{BlockchainError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemorySubsystem {
+    Mempool,
+    OrphanPool,
+    SeenCache,
+    /// Per-peer outbound queues and read buffers
+    PeerBuffers,
+    /// Block, header and state caches
+    Caches,
+}
+
+impl MemorySubsystem {
+    pub const ALL: [MemorySubsystem; 5] = [
+        MemorySubsystem::Mempool,
+        MemorySubsystem::OrphanPool,
+        MemorySubsystem::SeenCache,
+        MemorySubsystem::PeerBuffers,
+        MemorySubsystem::Caches,
+    ];
+}
+
+impl fmt::Display for MemorySubsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemorySubsystem::Mempool => write!(f, "mempool"),
+            MemorySubsystem::OrphanPool => write!(f, "orphan_pool"),
+            MemorySubsystem::SeenCache => write!(f, "seen_cache"),
+            MemorySubsystem::PeerBuffers => write!(f, "peer_buffers"),
+            MemorySubsystem::Caches => write!(f, "caches"),
+        }
+    }
+}
+
+const MIB: u64 = 1024 * 1024;
+
+/// Budget per subsystem, in bytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    pub mempool_bytes: u64,
+    pub orphan_pool_bytes: u64,
+    pub seen_cache_bytes: u64,
+    pub peer_buffer_bytes: u64,
+    pub cache_bytes: u64,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            mempool_bytes: 256 * MIB,
+            orphan_pool_bytes: 64 * MIB,
+            seen_cache_bytes: 32 * MIB,
+            peer_buffer_bytes: 64 * MIB,
+            cache_bytes: 128 * MIB,
+        }
+    }
+}
+
+impl MemoryConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(subsystem) = MemorySubsystem::ALL.into_iter().find(|s| self.budget(*s) == 0) {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: format!("Memory budget for {} must be greater than 0", subsystem),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn budget(&self, subsystem: MemorySubsystem) -> u64 {
+        match subsystem {
+            MemorySubsystem::Mempool => self.mempool_bytes,
+            MemorySubsystem::OrphanPool => self.orphan_pool_bytes,
+            MemorySubsystem::SeenCache => self.seen_cache_bytes,
+            MemorySubsystem::PeerBuffers => self.peer_buffer_bytes,
+            MemorySubsystem::Caches => self.cache_bytes,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        MemorySubsystem::ALL.into_iter().map(|s| self.budget(s)).sum()
+    }
+}
+
+/// Usage of one subsystem, as reported by `debug_memoryStats`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubsystemMemory {
+    pub subsystem: MemorySubsystem,
+    pub used_bytes: u64,
+    pub budget_bytes: u64,
+    /// Highest usage since start
+    pub peak_bytes: u64,
+    /// Eviction callbacks run because the budget was exceeded
+    pub evictions: u64,
+    /// Bytes those callbacks reported freeing
+    pub evicted_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MemoryStats {
+    pub used_bytes: u64,
+    pub budget_bytes: u64,
+    pub subsystems: Vec<SubsystemMemory>,
+}
+
+/// Called with the bytes to free; returns how many it freed
+type Evictor = Arc<dyn Fn(u64) -> u64 + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Usage {
+    used: u64,
+    peak: u64,
+    evictions: u64,
+    evicted: u64,
+}
+
+pub struct MemoryAccountant {
+    config: MemoryConfig,
+    usage: Mutex<BTreeMap<MemorySubsystem, Usage>>,
+    evictors: Mutex<HashMap<MemorySubsystem, Evictor>>,
+}
+
+impl fmt::Debug for MemoryAccountant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryAccountant").field("config", &self.config).finish_non_exhaustive()
+    }
+}
+
+impl MemoryAccountant {
+    pub fn new(config: MemoryConfig) -> Self {
+        Self {
+            config,
+            usage: Mutex::new(BTreeMap::new()),
+            evictors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &MemoryConfig {
+        &self.config
+    }
+
+    /// Evict through `evictor` whenever `enforce` finds `subsystem` over
+    /// budget, replacing any earlier callback
+    pub fn on_over_budget(&self, subsystem: MemorySubsystem, evictor: impl Fn(u64) -> u64 + Send + Sync + 'static) {
+        lock(&self.evictors).insert(subsystem, Arc::new(evictor));
+    }
+
+    /// Record `bytes` more held by `subsystem`, returning whether it is still within budget
+    pub fn charge(&self, subsystem: MemorySubsystem, bytes: u64) -> bool {
+        let mut usage = lock(&self.usage);
+        let entry = usage.entry(subsystem).or_default();
+        entry.used = entry.used.saturating_add(bytes);
+        entry.peak = entry.peak.max(entry.used);
+        entry.used <= self.config.budget(subsystem)
+    }
+
+    pub fn release(&self, subsystem: MemorySubsystem, bytes: u64) {
+        let mut usage = lock(&self.usage);
+        let entry = usage.entry(subsystem).or_default();
+        entry.used = entry.used.saturating_sub(bytes);
+    }
+
+    /// Replace the usage of a subsystem that measures itself rather than
+    /// charging per entry
+    pub fn set_usage(&self, subsystem: MemorySubsystem, bytes: u64) {
+        let mut usage = lock(&self.usage);
+        let entry = usage.entry(subsystem).or_default();
+        entry.used = bytes;
+        entry.peak = entry.peak.max(bytes);
+    }
+
+    pub fn used(&self, subsystem: MemorySubsystem) -> u64 {
+        lock(&self.usage).get(&subsystem).map_or(0, |usage| usage.used)
+    }
+
+    /// Bytes `subsystem` holds beyond its budget
+    pub fn excess(&self, subsystem: MemorySubsystem) -> u64 {
+        self.used(subsystem).saturating_sub(self.config.budget(subsystem))
+    }
+
+    /// Run the eviction callback of every subsystem over budget, returning
+    /// the subsystems that were, with the bytes their callbacks freed.
+    ///
+    /// No lock is held while a callback runs, so it may lock its subsystem
+    /// and release memory.
+    pub fn enforce(&self) -> Vec<(MemorySubsystem, u64)> {
+        let mut enforced = Vec::new();
+        for subsystem in MemorySubsystem::ALL {
+            let excess = self.excess(subsystem);
+            if excess == 0 {
+                continue;
+            }
+            let evictor = lock(&self.evictors).get(&subsystem).cloned();
+            let freed = evictor.map_or(0, |evict| evict(excess));
+
+            let mut usage = lock(&self.usage);
+            let entry = usage.entry(subsystem).or_default();
+            entry.evictions += 1;
+            entry.evicted = entry.evicted.saturating_add(freed);
+            enforced.push((subsystem, freed));
+        }
+        enforced
+    }
+
+    pub fn stats(&self) -> MemoryStats {
+        let usage = lock(&self.usage);
+        let subsystems: Vec<SubsystemMemory> = MemorySubsystem::ALL
+            .into_iter()
+            .map(|subsystem| {
+                let entry = usage.get(&subsystem).copied().unwrap_or_default();
+                SubsystemMemory {
+                    subsystem,
+                    used_bytes: entry.used,
+                    budget_bytes: self.config.budget(subsystem),
+                    peak_bytes: entry.peak,
+                    evictions: entry.evictions,
+                    evicted_bytes: entry.evicted,
+                }
+            })
+            .collect();
+
+        MemoryStats {
+            used_bytes: subsystems.iter().map(|s| s.used_bytes).sum(),
+            budget_bytes: self.config.total(),
+            subsystems,
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enforce_runs_evictors_over_budget() {
+        let config = MemoryConfig { seen_cache_bytes: 100, ..Default::default() };
+        config.validate().unwrap();
+        let accountant = Arc::new(MemoryAccountant::new(config));
+
+        // The callback evicts through the subsystem, which releases what it dropped
+        let handle = accountant.clone();
+        accountant.on_over_budget(MemorySubsystem::SeenCache, move |excess| {
+            handle.release(MemorySubsystem::SeenCache, excess);
+            excess
+        });
+
+        assert!(accountant.charge(MemorySubsystem::SeenCache, 80));
+        assert!(!accountant.charge(MemorySubsystem::SeenCache, 50));
+        assert_eq!(accountant.excess(MemorySubsystem::SeenCache), 30);
+
+        assert_eq!(accountant.enforce(), vec![(MemorySubsystem::SeenCache, 30)]);
+        assert_eq!(accountant.used(MemorySubsystem::SeenCache), 100);
+        assert!(accountant.enforce().is_empty());
+
+        let stats = accountant.stats();
+        let seen = stats.subsystems.iter().find(|s| s.subsystem == MemorySubsystem::SeenCache).unwrap();
+        assert_eq!((seen.peak_bytes, seen.evictions, seen.evicted_bytes), (130, 1, 30));
+        assert_eq!(stats.used_bytes, 100);
+
+        assert!(MemoryConfig { cache_bytes: 0, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,89 @@
+// core/blockchain-core/src/merkle.rs
+use crate::{hash_data, TxHash};
+
+/// Root of the binary merkle tree over `leaves`, in order.
+///
+/// Levels with an odd number of nodes pair the last node with itself, and
+/// an empty tree has the all-zero root. Block headers and relayer
+/// commitments both commit to transactions this way.
+pub fn merkle_root(leaves: &[TxHash]) -> TxHash {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Sibling hashes from the leaf at `index` up to the root, or `None` if there
+/// is no such leaf
+pub fn merkle_proof(leaves: &[TxHash], index: usize) -> Option<Vec<TxHash>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut position = index;
+    while level.len() > 1 {
+        let sibling = position ^ 1;
+        proof.push(*level.get(sibling).unwrap_or(&level[position]));
+        level = next_level(&level);
+        position /= 2;
+    }
+    Some(proof)
+}
+
+/// Whether `proof` places `leaf` at `index` in the tree with root `root`
+pub fn verify_merkle_proof(leaf: &TxHash, index: usize, proof: &[TxHash], root: &TxHash) -> bool {
+    let mut position = index;
+    let mut node = *leaf;
+    for sibling in proof {
+        node = if position & 1 == 0 { hash_pair(&node, sibling) } else { hash_pair(sibling, &node) };
+        position /= 2;
+    }
+    position == 0 && node == *root
+}
+
+fn next_level(level: &[TxHash]) -> Vec<TxHash> {
+    level.chunks(2).map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0]))).collect()
+}
+
+pub(crate) fn hash_pair(left: &TxHash, right: &TxHash) -> TxHash {
+    let mut combined = [0u8; 64];
+    combined[..32].copy_from_slice(left);
+    combined[32..].copy_from_slice(right);
+    hash_data(&combined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_odd_levels_duplicate_last_node() {
+        let (a, b, c) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+        let pair = |l: &TxHash, r: &TxHash| hash_data(&[l.as_slice(), r.as_slice()].concat());
+
+        assert_eq!(merkle_root(&[]), [0u8; 32]);
+        assert_eq!(merkle_root(&[a]), a);
+        assert_eq!(merkle_root(&[a, b, c]), pair(&pair(&a, &b), &pair(&c, &c)));
+    }
+
+    #[test]
+    fn test_proofs_verify_against_root() {
+        let leaves: Vec<TxHash> = (0..5u8).map(|i| [i; 32]).collect();
+        let root = merkle_root(&leaves);
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = merkle_proof(&leaves, index).unwrap();
+            assert!(verify_merkle_proof(leaf, index, &proof, &root));
+            assert!(!verify_merkle_proof(&[9; 32], index, &proof, &root));
+        }
+        let first = merkle_proof(&leaves, 0).unwrap();
+        assert!(!verify_merkle_proof(&leaves[0], 1, &first, &root));
+        assert!(merkle_proof(&leaves, 5).is_none());
+        assert_eq!(merkle_proof(&leaves[..1], 0).unwrap(), Vec::<TxHash>::new());
+    }
+}
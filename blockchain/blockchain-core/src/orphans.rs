@@ -0,0 +1,252 @@
+// core/blockchain-core/src/orphans.rs
+use crate::memory::{MemoryAccountant, MemorySubsystem};
+use crate::{Block, BlockHash};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Orphans held before the oldest is evicted
+pub const DEFAULT_MAX_ORPHANS: usize = 256;
+
+/// How long an orphan waits for its parent
+pub const DEFAULT_ORPHAN_TTL: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone)]
+struct Orphan {
+    block: Block,
+    received_at: Instant,
+    /// Encoded size, charged to the memory accountant
+    size: u64,
+}
+
+/// Blocks whose parent is not known yet, indexed by the parent they wait for
+#[derive(Debug, Clone)]
+pub struct OrphanPool {
+    orphans: HashMap<BlockHash, Orphan>,
+    by_parent: HashMap<BlockHash, Vec<BlockHash>>,
+    max_orphans: usize,
+    ttl: Duration,
+    size_bytes: u64,
+    accountant: Option<Arc<MemoryAccountant>>,
+}
+
+impl OrphanPool {
+    pub fn new(max_orphans: usize, ttl: Duration) -> Self {
+        Self {
+            orphans: HashMap::new(),
+            by_parent: HashMap::new(),
+            max_orphans: max_orphans.max(1),
+            ttl,
+            size_bytes: 0,
+            accountant: None,
+        }
+    }
+
+    /// Charge held orphans to `accountant` under `MemorySubsystem::OrphanPool`
+    pub fn with_accountant(mut self, accountant: Arc<MemoryAccountant>) -> Self {
+        accountant.charge(MemorySubsystem::OrphanPool, self.size_bytes);
+        self.accountant = Some(accountant);
+        self
+    }
+
+    /// Encoded size of the held blocks
+    pub fn size_bytes(&self) -> u64 {
+        self.size_bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.orphans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orphans.is_empty()
+    }
+
+    pub fn contains(&self, hash: &BlockHash) -> bool {
+        self.orphans.contains_key(hash)
+    }
+
+    /// Hold `block` until its parent arrives; evicts the oldest orphan when full.
+    ///
+    /// Returns false if the block was already held.
+    pub fn insert(&mut self, block: Block, now: Instant) -> bool {
+        self.expire(now);
+        if self.orphans.contains_key(&block.hash) {
+            return false;
+        }
+
+        while self.orphans.len() >= self.max_orphans {
+            if self.remove_oldest() == 0 {
+                break;
+            }
+        }
+
+        let size = block.calculate_size().unwrap_or(0);
+        self.size_bytes += size;
+        if let Some(accountant) = &self.accountant {
+            accountant.charge(MemorySubsystem::OrphanPool, size);
+        }
+        self.by_parent.entry(block.header.previous_hash).or_default().push(block.hash);
+        self.orphans.insert(block.hash, Orphan { block, received_at: now, size });
+        true
+    }
+
+    /// Evict the oldest orphans until at least `bytes` are freed or the pool
+    /// is empty, returning the bytes freed
+    pub fn evict_bytes(&mut self, bytes: u64) -> u64 {
+        let mut freed = 0;
+        while freed < bytes && !self.orphans.is_empty() {
+            freed += self.remove_oldest();
+        }
+        freed
+    }
+
+    /// Remove and return the orphans waiting on `parent`, in arrival order
+    pub fn take_children(&mut self, parent: &BlockHash) -> Vec<Block> {
+        let children: Vec<Orphan> = self
+            .by_parent
+            .remove(parent)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|hash| self.orphans.remove(&hash))
+            .collect();
+        for orphan in &children {
+            self.released(orphan.size);
+        }
+        children.into_iter().map(|orphan| orphan.block).collect()
+    }
+
+    /// Parent hash at the bottom of the orphan branch containing `hash`,
+    /// i.e. the block sync has to fetch next
+    pub fn missing_ancestor(&self, hash: &BlockHash) -> Option<BlockHash> {
+        let mut parent = self.orphans.get(hash)?.block.header.previous_hash;
+        // Each step moves one height down, so the walk is bounded by the pool size
+        while let Some(orphan) = self.orphans.get(&parent) {
+            parent = orphan.block.header.previous_hash;
+        }
+        Some(parent)
+    }
+
+    /// Drop orphans older than the TTL, returning how many were dropped
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let stale: Vec<BlockHash> = self
+            .orphans
+            .iter()
+            .filter(|(_, orphan)| now.saturating_duration_since(orphan.received_at) > self.ttl)
+            .map(|(hash, _)| *hash)
+            .collect();
+
+        for hash in &stale {
+            self.remove(hash);
+        }
+        stale.len()
+    }
+
+    /// Remove the orphan received first, returning its size
+    fn remove_oldest(&mut self) -> u64 {
+        let Some(oldest) = self
+            .orphans
+            .iter()
+            .min_by_key(|(_, orphan)| orphan.received_at)
+            .map(|(hash, _)| *hash)
+        else {
+            return 0;
+        };
+        self.remove(&oldest)
+    }
+
+    fn remove(&mut self, hash: &BlockHash) -> u64 {
+        let Some(orphan) = self.orphans.remove(hash) else {
+            return 0;
+        };
+        self.released(orphan.size);
+        let parent = orphan.block.header.previous_hash;
+        if let Some(siblings) = self.by_parent.get_mut(&parent) {
+            siblings.retain(|sibling| sibling != hash);
+            if siblings.is_empty() {
+                self.by_parent.remove(&parent);
+            }
+        }
+        orphan.size
+    }
+
+    fn released(&mut self, size: u64) {
+        self.size_bytes -= size;
+        if let Some(accountant) = &self.accountant {
+            accountant.release(MemorySubsystem::OrphanPool, size);
+        }
+    }
+}
+
+impl Default for OrphanPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ORPHANS, DEFAULT_ORPHAN_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(height: u64, previous_hash: BlockHash) -> Block {
+        Block::new(height, previous_hash, vec![], height as u32).unwrap()
+    }
+
+    #[test]
+    fn test_children_and_missing_ancestor() {
+        let mut pool = OrphanPool::default();
+        let now = Instant::now();
+
+        let b5 = block(5, [4; 32]);
+        let b6 = block(6, b5.hash);
+        assert!(pool.insert(b6.clone(), now));
+        assert!(pool.insert(b5.clone(), now));
+        assert!(!pool.insert(b5.clone(), now));
+        assert_eq!(pool.missing_ancestor(&b6.hash), Some([4; 32]));
+
+        assert_eq!(pool.take_children(&[4; 32]), vec![b5.clone()]);
+        assert_eq!(pool.take_children(&b5.hash), vec![b6]);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn test_expiry_and_eviction() {
+        let mut pool = OrphanPool::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+
+        let a = block(1, [1; 32]);
+        let b = block(2, [2; 32]);
+        let c = block(3, [3; 32]);
+        pool.insert(a.clone(), start);
+        pool.insert(b.clone(), start + Duration::from_secs(10));
+        pool.insert(c.clone(), start + Duration::from_secs(20));
+        assert!(!pool.contains(&a.hash));
+        assert_eq!(pool.len(), 2);
+
+        assert_eq!(pool.expire(start + Duration::from_secs(75)), 1);
+        assert!(pool.contains(&c.hash));
+        assert!(pool.take_children(&[2; 32]).is_empty());
+    }
+
+    #[test]
+    fn test_memory_accounting() {
+        let accountant = Arc::new(MemoryAccountant::new(Default::default()));
+        let mut pool = OrphanPool::default().with_accountant(accountant.clone());
+        let start = Instant::now();
+
+        let a = block(1, [1; 32]);
+        let b = block(2, [2; 32]);
+        pool.insert(a.clone(), start);
+        pool.insert(b.clone(), start + Duration::from_secs(1));
+        let size = a.calculate_size().unwrap();
+        assert!(size > 0);
+        assert_eq!(accountant.used(MemorySubsystem::OrphanPool), pool.size_bytes());
+
+        // The oldest goes first, and only as many as needed
+        assert_eq!(pool.evict_bytes(1), size);
+        assert!(!pool.contains(&a.hash));
+        assert_eq!(pool.take_children(&[2; 32]), vec![b]);
+        assert_eq!(pool.size_bytes(), 0);
+        assert_eq!(accountant.used(MemorySubsystem::OrphanPool), 0);
+    }
+}
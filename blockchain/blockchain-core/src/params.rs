@@ -0,0 +1,294 @@
+// core/blockchain-core/src/params.rs
+use crate::{
+    Address, Amount, Block, BlockHash, BlockHeight, BlockchainError, ChainId, Consensus, EmissionSchedule,
+    EmptyBlockRules, FeeDistribution, FinalityConfig, Result, Transaction, LEGACY_CHAIN_ID,
+};
+use serde::{Deserialize, Serialize};
+
+pub const MAINNET_CHAIN_ID: ChainId = 1;
+pub const TESTNET_CHAIN_ID: ChainId = 2;
+
+/// Consensus parameters that differ between networks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainParams {
+    /// Transactions must be bound to this chain to be accepted
+    pub chain_id: ChainId,
+    /// Upper bound on the summed gas limits of a block's transactions
+    pub block_gas_limit: u64,
+    /// Intended interval between blocks
+    pub target_block_time_secs: u64,
+}
+
+impl ChainParams {
+    pub fn mainnet() -> Self {
+        Self {
+            chain_id: MAINNET_CHAIN_ID,
+            block_gas_limit: 30_000_000,
+            target_block_time_secs: 12,
+        }
+    }
+
+    pub fn testnet() -> Self {
+        Self {
+            chain_id: TESTNET_CHAIN_ID,
+            ..Self::mainnet()
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.chain_id == LEGACY_CHAIN_ID {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: format!("Chain id {} is reserved for legacy transactions", LEGACY_CHAIN_ID),
+            });
+        }
+        if self.block_gas_limit == 0 {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "Block gas limit must be greater than 0".to_string(),
+            });
+        }
+        if self.target_block_time_secs == 0 {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "Target block time must be greater than 0".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Reject transactions signed for another chain, or for no chain at all
+    pub fn validate_transaction(&self, tx: &Transaction) -> Result<()> {
+        if tx.chain_id != self.chain_id {
+            return Err(BlockchainError::InvalidTransaction {
+                reason: format!("Transaction is for chain {}, expected {}", tx.chain_id, self.chain_id),
+            });
+        }
+        if tx.gas_limit > self.block_gas_limit {
+            return Err(BlockchainError::InvalidTransaction {
+                reason: format!("Gas limit {} exceeds block gas limit {}", tx.gas_limit, self.block_gas_limit),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check every transaction's chain and the block's total gas
+    pub fn validate_block(&self, block: &Block) -> Result<()> {
+        let mut gas: u64 = 0;
+        for tx in &block.transactions {
+            self.validate_transaction(tx)?;
+            gas = gas.saturating_add(tx.gas_limit);
+        }
+
+        if gas > self.block_gas_limit {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: format!("Block gas {} exceeds limit {}", gas, self.block_gas_limit),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for ChainParams {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+/// Complete rule set of a network: consensus parameters, fee distribution,
+/// emission and block production
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainSpec {
+    pub params: ChainParams,
+    pub fees: FeeDistribution,
+    pub emission: EmissionSchedule,
+    /// Specs written before proof-of-authority default to proof of work
+    #[serde(default)]
+    pub consensus: Consensus,
+    /// Checkpoint finality; without it any reorg within the depth limit is allowed
+    #[serde(default)]
+    pub finality: Option<FinalityConfig>,
+    /// When blocks without user transactions are valid
+    #[serde(default)]
+    pub empty_blocks: EmptyBlockRules,
+}
+
+impl ChainSpec {
+    pub fn validate(&self) -> Result<()> {
+        self.params.validate()?;
+        self.fees.validate()?;
+        self.emission.validate()?;
+        self.consensus.validate()?;
+        self.empty_blocks.validate(&self.params)?;
+        self.finality.as_ref().map_or(Ok(()), FinalityConfig::validate)
+    }
+
+    /// Share of the fees paid by `transactions` that goes to the block producer, tips included
+    pub fn producer_fees(&self, transactions: &[Transaction]) -> Amount {
+        transactions
+            .iter()
+            .filter(|tx| !tx.is_coinbase())
+            .map(|tx| self.fees.split_transaction(tx).to_proposer())
+            .fold(0, Amount::saturating_add)
+    }
+
+    /// Amount the coinbase of block `height` must mint: the scheduled reward plus producer fees
+    pub fn expected_coinbase(&self, height: BlockHeight, transactions: &[Transaction]) -> Amount {
+        self.emission.reward_at(height).saturating_add(self.producer_fees(transactions))
+    }
+
+    /// Coinbase a producer places first in block `height`, ahead of `transactions`
+    pub fn coinbase_for(&self, producer: Address, height: BlockHeight, transactions: &[Transaction]) -> Result<Transaction> {
+        Transaction::new_coinbase(producer, self.expected_coinbase(height, transactions), height)?
+            .with_chain_id(self.params.chain_id)
+    }
+
+    /// Build block `height` for `producer`, leading with the coinbase that collects its reward and fees
+    pub fn produce_block(
+        &self,
+        producer: Address,
+        height: BlockHeight,
+        previous_hash: BlockHash,
+        transactions: Vec<Transaction>,
+        difficulty: u32,
+    ) -> Result<Block> {
+        let mut all = Vec::with_capacity(transactions.len() + 1);
+        all.push(self.coinbase_for(producer, height, &transactions)?);
+        all.extend(transactions);
+        Block::new(height, previous_hash, all, difficulty)
+    }
+
+    /// Chain parameter checks plus the coinbase and consensus rules.
+    ///
+    /// Every block above genesis carries exactly one coinbase minting the
+    /// expected amount; genesis balances come from allocations, not transactions.
+    /// Genesis is fixed by configuration and needs no proposer seal.
+    pub fn validate_block(&self, block: &Block) -> Result<()> {
+        self.params.validate_block(block)?;
+
+        let height = block.header.height;
+        if height == 0 {
+            if !block.transactions.is_empty() {
+                return Err(BlockchainError::BlockValidationFailed {
+                    reason: "Genesis block cannot contain transactions".to_string(),
+                });
+            }
+            return Ok(());
+        }
+
+        let coinbases = block.transactions.iter().filter(|tx| tx.is_coinbase()).count();
+        let Some(coinbase) = block.coinbase().filter(|_| coinbases == 1) else {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: format!("Block must lead with exactly one coinbase, found {}", coinbases),
+            });
+        };
+
+        let expected = self.expected_coinbase(height, &block.transactions);
+        if coinbase.amount() != expected {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: format!("Coinbase mints {}, expected {}", coinbase.amount(), expected),
+            });
+        }
+        self.consensus.validate_block(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AddressExt, KeyPair, PoaConfig, SignatureScheme};
+    use chrono::Utc;
+
+    #[test]
+    fn test_chain_params_validation() {
+        ChainParams::mainnet().validate().unwrap();
+        ChainParams::testnet().validate().unwrap();
+        assert!(ChainParams { chain_id: LEGACY_CHAIN_ID, ..ChainParams::mainnet() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_transactions_bound_to_chain() {
+        let params = ChainParams { block_gas_limit: 50_000, ..ChainParams::testnet() };
+        let tx = Transaction::new_transfer([1; 20], [2; 20], 100, 0, 21_000, 1).unwrap();
+
+        assert!(params.validate_transaction(&tx).is_err());
+        let bound = tx.clone().with_chain_id(TESTNET_CHAIN_ID).unwrap();
+        params.validate_transaction(&bound).unwrap();
+        assert!(ChainParams::mainnet().validate_transaction(&bound).is_err());
+
+        let second = Transaction::new_transfer([1; 20], [2; 20], 100, 1, 21_000, 1)
+            .unwrap()
+            .with_chain_id(TESTNET_CHAIN_ID)
+            .unwrap();
+        let block = Block::new(1, [0; 32], vec![bound.clone(), second.clone()], 1).unwrap();
+        params.validate_block(&block).unwrap();
+
+        let third = second.clone();
+        let over = Block::new(1, [0; 32], vec![bound, second, third], 1).unwrap();
+        assert!(params.validate_block(&over).is_err());
+    }
+
+    #[test]
+    fn test_coinbase_validation() {
+        let spec = ChainSpec {
+            params: ChainParams::testnet(),
+            fees: FeeDistribution { burn_bps: 5_000, treasury_bps: 0, treasury: None, base_gas_price: None },
+            emission: EmissionSchedule::Halving { initial_reward: 1_000, interval_blocks: 100 },
+            ..ChainSpec::default()
+        };
+        spec.validate().unwrap();
+
+        let tx = Transaction::new_transfer([1; 20], [2; 20], 100, 0, 21_000, 1)
+            .unwrap()
+            .with_chain_id(TESTNET_CHAIN_ID)
+            .unwrap();
+        let coinbase = spec.coinbase_for([7; 20], 1, std::slice::from_ref(&tx)).unwrap();
+        assert_eq!(coinbase.amount(), 1_000 + 10_500);
+
+        let block = Block::new(1, [0; 32], vec![coinbase.clone(), tx.clone()], 1).unwrap();
+        block.validate().unwrap();
+        spec.validate_block(&block).unwrap();
+
+        // Missing, overpaying and misplaced coinbases are rejected
+        let missing = Block::new(1, [0; 32], vec![tx.clone()], 1).unwrap();
+        assert!(spec.validate_block(&missing).is_err());
+
+        let greedy = spec.coinbase_for([7; 20], 1, &[tx.clone(), tx.clone()]).unwrap();
+        let overpaid = Block::new(1, [0; 32], vec![greedy, tx.clone()], 1).unwrap();
+        assert!(spec.validate_block(&overpaid).is_err());
+
+        let misplaced = Block::new(1, [0; 32], vec![tx.clone(), coinbase.clone()], 1).unwrap();
+        assert!(misplaced.validate().is_err());
+
+        let twice = Block::new(1, [0; 32], vec![coinbase.clone(), coinbase, tx.clone()], 1).unwrap();
+        assert!(spec.validate_block(&twice).is_err());
+
+        let produced = spec.produce_block([7; 20], 1, [0; 32], vec![tx], 1).unwrap();
+        produced.validate().unwrap();
+        spec.validate_block(&produced).unwrap();
+    }
+
+    #[test]
+    fn test_authority_chain_requires_seal() {
+        let key = KeyPair::generate(SignatureScheme::Secp256k1);
+        let validator = Address::from_public_key(&key.public_key()).unwrap();
+        let poa = PoaConfig {
+            validators: vec![validator],
+            slot_duration_secs: 2,
+            genesis_time: Utc::now() - chrono::Duration::hours(1),
+        };
+        let spec = ChainSpec {
+            params: ChainParams::testnet(),
+            consensus: Consensus::ProofOfAuthority(poa.clone()),
+            ..ChainSpec::default()
+        };
+        spec.validate().unwrap();
+
+        let unsealed = spec.produce_block(validator, 1, [0; 32], vec![], 1).unwrap();
+        assert!(spec.validate_block(&unsealed).is_err());
+
+        let slot = poa.slot_at(Utc::now()).unwrap();
+        let sealed = poa.seal(unsealed, slot, &key).unwrap();
+        spec.validate_block(&sealed).unwrap();
+
+        let empty = ChainSpec { consensus: Consensus::ProofOfAuthority(PoaConfig { validators: vec![], ..poa }), ..spec };
+        assert!(empty.validate().is_err());
+    }
+}
@@ -0,0 +1,279 @@
+// core/blockchain-core/src/poa.rs
+//! Proof-of-authority block production.
+//!
+//! Time is divided into fixed slots counted from `genesis_time`, and slot `n`
+//! belongs to `validators[n % len]`. A block is valid only if its timestamp is
+//! the start of a slot and its header is sealed by that slot's proposer, so
+//! every honest node agrees on who may produce each block without any work.
+use crate::pos::PosConfig;
+use crate::signature::{self, KeyPair, SignatureScheme};
+use crate::{Address, AddressExt, Block, BlockchainError, Result, SEALED_HEADER_VERSION};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Difficulty of every sealed block; fork choice degenerates to longest chain
+pub const POA_DIFFICULTY: u32 = 1;
+
+/// Whether a slot's proposer produced its block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotOutcome {
+    pub slot: u64,
+    pub proposer: Address,
+    /// `false` when the slot passed without a block
+    pub proposed: bool,
+}
+
+/// Validator set and slot schedule of a proof-of-authority chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoaConfig {
+    /// Authorized proposers, in rotation order
+    pub validators: Vec<Address>,
+    pub slot_duration_secs: u64,
+    /// Start of slot 0
+    pub genesis_time: DateTime<Utc>,
+}
+
+impl PoaConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.validators.is_empty() {
+            return Err(invalid_params("Proof-of-authority needs at least one validator".to_string()));
+        }
+        let mut seen = HashSet::new();
+        for validator in &self.validators {
+            if !crate::validate_address(validator) {
+                return Err(invalid_params("Validator address cannot be zero".to_string()));
+            }
+            if !seen.insert(validator) {
+                return Err(invalid_params(format!("Duplicate validator {}", validator.to_checksum_hex())));
+            }
+        }
+        if self.slot_duration_secs == 0 {
+            return Err(invalid_params("Slot duration must be greater than 0".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Slot containing `time`, `None` before genesis
+    pub fn slot_at(&self, time: DateTime<Utc>) -> Option<u64> {
+        let elapsed = (time - self.genesis_time).num_seconds();
+        u64::try_from(elapsed).ok().map(|secs| secs / self.slot_duration_secs)
+    }
+
+    pub fn slot_start(&self, slot: u64) -> DateTime<Utc> {
+        let offset = slot.saturating_mul(self.slot_duration_secs).min(i64::MAX as u64) as i64;
+        self.genesis_time + Duration::seconds(offset)
+    }
+
+    /// Validator allowed to propose in `slot`
+    pub fn proposer_for_slot(&self, slot: u64) -> Address {
+        self.validators[(slot % self.validators.len() as u64) as usize]
+    }
+
+    /// Outcome of every slot after `parent_timestamp` up to the slot of
+    /// `timestamp`: the slots skipped in between were missed, the last one
+    /// produced the block.
+    pub fn slot_outcomes(&self, parent_timestamp: DateTime<Utc>, timestamp: DateTime<Utc>) -> Vec<SlotOutcome> {
+        let Some(slot) = self.slot_at(timestamp) else {
+            return Vec::new();
+        };
+        let first = self.slot_at(parent_timestamp).map_or(0, |parent| parent + 1);
+
+        (first..=slot)
+            .map(|n| SlotOutcome { slot: n, proposer: self.proposer_for_slot(n), proposed: n == slot })
+            .collect()
+    }
+
+    /// Turn `block` into the sealed block for `slot`: the timestamp moves to
+    /// the slot start and the header is signed by `key`.
+    ///
+    /// Fails if `key` is not the slot's proposer, so a node never produces a
+    /// block its peers would reject.
+    pub fn seal(&self, mut block: Block, slot: u64, key: &KeyPair) -> Result<Block> {
+        let proposer = Address::from_public_key(&key.public_key())?;
+        let expected = self.proposer_for_slot(slot);
+        if proposer != expected {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: format!(
+                    "Slot {} belongs to {}, not {}",
+                    slot,
+                    expected.to_checksum_hex(),
+                    proposer.to_checksum_hex()
+                ),
+            });
+        }
+
+        block.header.timestamp = self.slot_start(slot);
+        block.header.version = block.header.version.max(SEALED_HEADER_VERSION);
+        block.header.difficulty = POA_DIFFICULTY;
+        block.header.nonce = 0;
+        block.header.proposer_signature = Some(Vec::new());
+        block.header.proposer_signature = Some(key.sign(&block.header.seal_hash()?));
+        block.hash = block.calculate_hash()?;
+        block.size = block.calculate_size()?;
+        Ok(block)
+    }
+
+    /// Check that `block` sits on a slot boundary and is sealed by that slot's
+    /// proposer, returning the proposer
+    pub fn verify(&self, block: &Block) -> Result<Address> {
+        let header = &block.header;
+        if header.version < SEALED_HEADER_VERSION {
+            return Err(rejected(format!("Version {} headers carry no proposer signature", header.version)));
+        }
+        if header.difficulty != POA_DIFFICULTY {
+            return Err(rejected(format!("Difficulty must be {}, got {}", POA_DIFFICULTY, header.difficulty)));
+        }
+
+        let slot = self
+            .slot_at(header.timestamp)
+            .filter(|&slot| self.slot_start(slot) == header.timestamp)
+            .ok_or_else(|| rejected("Block timestamp is not the start of a slot".to_string()))?;
+
+        let seal = header.proposer_signature.as_deref().unwrap_or_default();
+        let scheme = SignatureScheme::of_signature(seal)
+            .ok_or_else(|| rejected(format!("Unrecognized proposer signature of {} bytes", seal.len())))?;
+        let public_key = signature::verify_signature(scheme, &header.seal_hash()?, seal)?;
+        let signer = Address::from_public_key(&public_key)?;
+
+        let expected = self.proposer_for_slot(slot);
+        if signer != expected {
+            return Err(rejected(format!(
+                "Slot {} belongs to {}, sealed by {}",
+                slot,
+                expected.to_checksum_hex(),
+                signer.to_checksum_hex()
+            )));
+        }
+        Ok(signer)
+    }
+}
+
+/// How a chain decides who may produce the next block
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Consensus {
+    /// Heaviest cumulative difficulty wins; headers are unsigned
+    #[default]
+    ProofOfWork,
+    /// Round-robin slots over a fixed validator set
+    ProofOfAuthority(PoaConfig),
+    /// Slots won by stake-weighted VRF election
+    ProofOfStake(PosConfig),
+}
+
+impl Consensus {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Consensus::ProofOfWork => Ok(()),
+            Consensus::ProofOfAuthority(poa) => poa.validate(),
+            Consensus::ProofOfStake(pos) => pos.validate(),
+        }
+    }
+
+    /// Consensus checks for a block above genesis
+    pub fn validate_block(&self, block: &Block) -> Result<()> {
+        let has_vrf_proof = block.header.vrf_proof.as_ref().is_some_and(|proof| !proof.is_empty());
+        match self {
+            Consensus::ProofOfWork | Consensus::ProofOfAuthority(_) if has_vrf_proof => {
+                Err(rejected("Only proof-of-stake blocks carry a VRF proof".to_string()))
+            }
+            Consensus::ProofOfWork => Ok(()),
+            Consensus::ProofOfAuthority(poa) => poa.verify(block).map(|_| ()),
+            Consensus::ProofOfStake(pos) => pos.verify_seal(block).map(|_| ()),
+        }
+    }
+
+    /// Consensus checks for a block that need its parent, after `validate_block`
+    pub fn validate_successor(&self, block: &Block, parent: &Block) -> Result<()> {
+        match self {
+            Consensus::ProofOfWork | Consensus::ProofOfAuthority(_) => Ok(()),
+            Consensus::ProofOfStake(pos) => pos.verify(block, parent).map(|_| ()),
+        }
+    }
+}
+
+fn invalid_params(reason: String) -> BlockchainError {
+    BlockchainError::InvalidChainParams { reason }
+}
+
+fn rejected(reason: String) -> BlockchainError {
+    BlockchainError::BlockValidationFailed { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn validators() -> (Vec<KeyPair>, PoaConfig) {
+        let keys = vec![
+            KeyPair::generate(SignatureScheme::Secp256k1),
+            KeyPair::generate(SignatureScheme::Ed25519),
+            KeyPair::generate(SignatureScheme::Secp256k1),
+        ];
+        let config = PoaConfig {
+            validators: keys.iter().map(|k| Address::from_public_key(&k.public_key()).unwrap()).collect(),
+            slot_duration_secs: 5,
+            genesis_time: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+        };
+        (keys, config)
+    }
+
+    #[test]
+    fn test_round_robin_slots() {
+        let (_, config) = validators();
+        config.validate().unwrap();
+
+        assert_eq!(config.slot_at(config.genesis_time - Duration::seconds(1)), None);
+        assert_eq!(config.slot_at(config.genesis_time + Duration::seconds(14)), Some(2));
+        assert_eq!(config.slot_start(2), config.genesis_time + Duration::seconds(10));
+        assert_eq!(config.proposer_for_slot(4), config.validators[1]);
+
+        let duplicate = PoaConfig { validators: vec![[1; 20], [1; 20]], ..config };
+        assert!(duplicate.validate().is_err());
+    }
+
+    #[test]
+    fn test_seal_and_verify() {
+        let (keys, config) = validators();
+        let block = Block::new(1, [0; 32], vec![], 10).unwrap();
+
+        // Slot 4 rotates to the ed25519 validator
+        assert!(config.seal(block.clone(), 4, &keys[0]).is_err());
+        let sealed = config.seal(block, 4, &keys[1]).unwrap();
+        sealed.validate().unwrap();
+        assert_eq!(sealed.header.timestamp, config.slot_start(4));
+        assert_eq!(config.verify(&sealed).unwrap(), config.validators[1]);
+
+        let decoded: Block = bincode::deserialize(&bincode::serialize(&sealed).unwrap()).unwrap();
+        assert_eq!(decoded, sealed);
+
+        // Moving the block to another slot breaks the seal and the rotation
+        let mut moved = sealed.clone();
+        moved.header.timestamp = config.slot_start(5);
+        moved.hash = moved.calculate_hash().unwrap();
+        assert!(config.verify(&moved).is_err());
+
+        let mut offset = sealed;
+        offset.header.timestamp += Duration::seconds(1);
+        assert!(config.verify(&offset).is_err());
+    }
+
+    #[test]
+    fn test_slot_outcomes() {
+        let (_, config) = validators();
+
+        let outcomes = config.slot_outcomes(config.slot_start(1), config.slot_start(4));
+        let slots: Vec<(u64, bool)> = outcomes.iter().map(|o| (o.slot, o.proposed)).collect();
+        assert_eq!(slots, vec![(2, false), (3, false), (4, true)]);
+        assert_eq!(outcomes[0].proposer, config.validators[2]);
+        assert_eq!(outcomes[2].proposer, config.validators[1]);
+
+        assert_eq!(config.slot_outcomes(config.slot_start(4), config.slot_start(5)).len(), 1);
+        // The first block after genesis accounts for slot 0 too
+        let before_genesis = config.genesis_time - Duration::seconds(1);
+        assert_eq!(config.slot_outcomes(before_genesis, config.slot_start(1)).len(), 2);
+        assert!(config.slot_outcomes(config.slot_start(4), before_genesis).is_empty());
+    }
+}
@@ -0,0 +1,320 @@
+// core/blockchain-core/src/pos.rs
+//! Proof-of-stake block production with VRF proposer election.
+//!
+//! Slots are laid out as in proof of authority, but instead of a public
+//! rotation each validator evaluates its VRF on the parent's beacon seed and
+//! the slot, and may propose when the output falls below a threshold
+//! proportional to its share of the stake. The header carries the VRF proof
+//! and is sealed with the validator's signing key, so peers check who signed
+//! the block and that the signer won the slot. Nobody can predict another
+//! validator's turns or grind their own, and the output feeds the block's
+//! beacon seed.
+//!
+//! Election is per validator, so a slot may have no winner or several: about
+//! `leader_rate_percent / 100` validators win each slot, and competing blocks
+//! resolve like any fork.
+use crate::signature::{self, KeyPair, SignatureScheme};
+use crate::vrf::{self, VrfPublicKey, VrfSecretKey};
+use crate::{
+    beacon, Address, AddressExt, Block, BlockchainError, Result, VrfOutput, STATE_ROOT_HEADER_VERSION,
+    VRF_HEADER_VERSION,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Difficulty of every sealed block; fork choice degenerates to longest chain
+pub const POS_DIFFICULTY: u32 = 1;
+
+/// Tags the VRF input so slot proofs never double as other proofs
+const SLOT_DOMAIN: &[u8] = b"pos-slot/v1";
+
+/// A validator and the stake it is elected by
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakedValidator {
+    /// Address of the key sealing its blocks
+    pub address: Address,
+    /// Key its slot proofs verify against
+    pub vrf_key: VrfPublicKey,
+    pub stake: u64,
+}
+
+/// Validator set, stakes and slot schedule of a proof-of-stake chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PosConfig {
+    pub validators: Vec<StakedValidator>,
+    pub slot_duration_secs: u64,
+    /// Start of slot 0
+    pub genesis_time: DateTime<Utc>,
+    /// Expected winners per slot, in percent; at most 100
+    pub leader_rate_percent: u8,
+}
+
+impl PosConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.validators.is_empty() {
+            return Err(invalid_params("Proof-of-stake needs at least one validator".to_string()));
+        }
+        let (mut addresses, mut vrf_keys) = (HashSet::new(), HashSet::new());
+        let mut total: u64 = 0;
+        for validator in &self.validators {
+            let name = validator.address.to_checksum_hex();
+            if !crate::validate_address(&validator.address) {
+                return Err(invalid_params("Validator address cannot be zero".to_string()));
+            }
+            if !addresses.insert(validator.address) {
+                return Err(invalid_params(format!("Duplicate validator {}", name)));
+            }
+            if !vrf_keys.insert(validator.vrf_key) {
+                return Err(invalid_params(format!("Validator {} reuses another validator's VRF key", name)));
+            }
+            if validator.stake == 0 {
+                return Err(invalid_params(format!("Validator {} has no stake", name)));
+            }
+            total = total
+                .checked_add(validator.stake)
+                .ok_or_else(|| invalid_params("Total stake overflows".to_string()))?;
+        }
+        if self.slot_duration_secs == 0 {
+            return Err(invalid_params("Slot duration must be greater than 0".to_string()));
+        }
+        if !(1..=100).contains(&self.leader_rate_percent) {
+            return Err(invalid_params(format!(
+                "Leader rate must be between 1 and 100 percent, got {}",
+                self.leader_rate_percent
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn total_stake(&self) -> u64 {
+        self.validators.iter().map(|v| v.stake).sum()
+    }
+
+    pub fn validator(&self, address: &Address) -> Option<&StakedValidator> {
+        self.validators.iter().find(|v| &v.address == address)
+    }
+
+    /// Slot containing `time`, `None` before genesis
+    pub fn slot_at(&self, time: DateTime<Utc>) -> Option<u64> {
+        let elapsed = (time - self.genesis_time).num_seconds();
+        u64::try_from(elapsed).ok().map(|secs| secs / self.slot_duration_secs)
+    }
+
+    pub fn slot_start(&self, slot: u64) -> DateTime<Utc> {
+        let offset = slot.saturating_mul(self.slot_duration_secs).min(i64::MAX as u64) as i64;
+        self.genesis_time + Duration::seconds(offset)
+    }
+
+    /// VRF input electing the proposers of `slot` on top of `parent`
+    pub fn slot_input(parent: &Block, slot: u64) -> Vec<u8> {
+        let mut input = Vec::with_capacity(SLOT_DOMAIN.len() + 32 + 8);
+        input.extend_from_slice(SLOT_DOMAIN);
+        input.extend_from_slice(&beacon::effective_seed(parent));
+        input.extend_from_slice(&slot.to_be_bytes());
+        input
+    }
+
+    /// Whether `output` elects `validator`: its leading 64 bits, read as a
+    /// fraction, fall below its stake share scaled by the leader rate
+    pub fn is_elected(&self, validator: &StakedValidator, output: &VrfOutput) -> bool {
+        let draw = u64::from_be_bytes(output[..8].try_into().expect("output has 8 leading bytes"));
+        let scaled = (1u128 << 64) * self.leader_rate_percent as u128 / 100;
+        let threshold = scaled * validator.stake as u128 / self.total_stake().max(1) as u128;
+        (draw as u128) < threshold
+    }
+
+    /// Proof that `vrf_key`'s validator won `slot` on top of `parent`, `None`
+    /// when it did not
+    pub fn slot_proof(&self, parent: &Block, slot: u64, vrf_key: &VrfSecretKey) -> Result<Option<Vec<u8>>> {
+        let public_key = vrf_key.public_key();
+        let validator = self
+            .validators
+            .iter()
+            .find(|v| v.vrf_key == public_key)
+            .ok_or_else(|| rejected(format!("VRF key {} belongs to no validator", hex::encode(public_key))))?;
+
+        let proof = vrf_key.prove(&Self::slot_input(parent, slot))?;
+        let output = vrf::proof_to_output(&proof)?;
+        Ok(self.is_elected(validator, &output).then_some(proof))
+    }
+
+    /// Turn `block`, built on `parent` with its state root committed, into the
+    /// sealed block for `slot`: the header takes the slot proof, the seed it
+    /// leads to and a signature by `key`.
+    ///
+    /// Fails unless `key` and `vrf_key` belong to the same validator and that
+    /// validator won the slot, so a node never produces a block its peers
+    /// would reject.
+    pub fn seal(
+        &self,
+        mut block: Block,
+        parent: &Block,
+        slot: u64,
+        key: &KeyPair,
+        vrf_key: &VrfSecretKey,
+    ) -> Result<Block> {
+        let proposer = Address::from_public_key(&key.public_key())?;
+        let validator = self
+            .validator(&proposer)
+            .filter(|v| v.vrf_key == vrf_key.public_key())
+            .ok_or_else(|| rejected(format!("{} is not a validator with this VRF key", proposer.to_checksum_hex())))?;
+        if block.header.previous_hash != parent.hash {
+            return Err(rejected(format!("Block at height {} does not build on the parent", block.header.height)));
+        }
+        if block.header.version < STATE_ROOT_HEADER_VERSION {
+            return Err(rejected("A slot proof needs the state root committed first".to_string()));
+        }
+        let proof = self.slot_proof(parent, slot, vrf_key)?.ok_or_else(|| {
+            rejected(format!("Slot {} did not elect {}", slot, validator.address.to_checksum_hex()))
+        })?;
+
+        block.header.timestamp = self.slot_start(slot);
+        block.header.version = block.header.version.max(VRF_HEADER_VERSION);
+        block.header.difficulty = POS_DIFFICULTY;
+        block.header.nonce = 0;
+        block.header.vrf_proof = Some(proof);
+        block.header.random_seed = Some(beacon::seed_for(&block, parent)?);
+        block.header.proposer_signature = Some(Vec::new());
+        block.header.proposer_signature = Some(key.sign(&block.header.seal_hash()?));
+        block.hash = block.calculate_hash()?;
+        block.size = block.calculate_size()?;
+        Ok(block)
+    }
+
+    /// Check that `block` sits on a slot boundary and is sealed by a
+    /// validator, returning the slot and the validator. The election needs
+    /// the parent; see `verify`.
+    pub fn verify_seal(&self, block: &Block) -> Result<(u64, &StakedValidator)> {
+        let header = &block.header;
+        if header.version < VRF_HEADER_VERSION {
+            return Err(rejected(format!("Version {} headers carry no VRF proof", header.version)));
+        }
+        if header.difficulty != POS_DIFFICULTY {
+            return Err(rejected(format!("Difficulty must be {}, got {}", POS_DIFFICULTY, header.difficulty)));
+        }
+
+        let slot = self
+            .slot_at(header.timestamp)
+            .filter(|&slot| self.slot_start(slot) == header.timestamp)
+            .ok_or_else(|| rejected("Block timestamp is not the start of a slot".to_string()))?;
+
+        let seal = header.proposer_signature.as_deref().unwrap_or_default();
+        let scheme = SignatureScheme::of_signature(seal)
+            .ok_or_else(|| rejected(format!("Unrecognized proposer signature of {} bytes", seal.len())))?;
+        let public_key = signature::verify_signature(scheme, &header.seal_hash()?, seal)?;
+        let signer = Address::from_public_key(&public_key)?;
+        let validator = self
+            .validator(&signer)
+            .ok_or_else(|| rejected(format!("Block sealed by {}, not a validator", signer.to_checksum_hex())))?;
+        Ok((slot, validator))
+    }
+
+    /// Check `block`'s seal and that its VRF proof elects the signer for its
+    /// slot on top of `parent`, returning the proposer
+    pub fn verify(&self, block: &Block, parent: &Block) -> Result<Address> {
+        let (slot, validator) = self.verify_seal(block)?;
+        let proof = block.header.vrf_proof.as_deref().unwrap_or_default();
+        let output = vrf::verify(&validator.vrf_key, &Self::slot_input(parent, slot), proof)?;
+        if !self.is_elected(validator, &output) {
+            return Err(rejected(format!(
+                "Slot {} did not elect {}",
+                slot,
+                validator.address.to_checksum_hex()
+            )));
+        }
+        Ok(validator.address)
+    }
+}
+
+fn invalid_params(reason: String) -> BlockchainError {
+    BlockchainError::InvalidChainParams { reason }
+}
+
+fn rejected(reason: String) -> BlockchainError {
+    BlockchainError::BlockValidationFailed { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn validators(stakes: &[u64]) -> (Vec<(KeyPair, VrfSecretKey)>, PosConfig) {
+        let keys: Vec<_> = stakes
+            .iter()
+            .map(|_| (KeyPair::generate(SignatureScheme::Ed25519), VrfSecretKey::generate()))
+            .collect();
+        let config = PosConfig {
+            validators: keys
+                .iter()
+                .zip(stakes)
+                .map(|((key, vrf_key), &stake)| StakedValidator {
+                    address: Address::from_public_key(&key.public_key()).unwrap(),
+                    vrf_key: vrf_key.public_key(),
+                    stake,
+                })
+                .collect(),
+            slot_duration_secs: 5,
+            genesis_time: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            leader_rate_percent: 100,
+        };
+        (keys, config)
+    }
+
+    fn unsealed(parent: &Block) -> Block {
+        Block::new(parent.header.height + 1, parent.hash, vec![], 10).unwrap().with_state_root([7; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_vrf_election_seal_and_verify() {
+        let (keys, config) = validators(&[50, 30, 20]);
+        config.validate().unwrap();
+        let parent = Block::genesis().unwrap();
+        let (key, vrf_key) = &keys[2];
+
+        // The smallest stake still wins some slot, and nobody else can use its proof
+        let slot = (0..500).find(|&slot| config.slot_proof(&parent, slot, vrf_key).unwrap().is_some()).unwrap();
+        let sealed = config.seal(unsealed(&parent), &parent, slot, key, vrf_key).unwrap();
+        sealed.validate().unwrap();
+        assert_eq!(sealed.header.version, VRF_HEADER_VERSION);
+        assert_eq!(config.verify(&sealed, &parent).unwrap(), config.validators[2].address);
+        beacon::verify_seed(&sealed, &parent).unwrap();
+        assert_ne!(sealed.header.random_seed, Some(beacon::next_seed(&parent)));
+        assert!(config.seal(unsealed(&parent), &parent, slot, &keys[0].0, vrf_key).is_err());
+
+        let decoded: Block = bincode::deserialize(&bincode::serialize(&sealed).unwrap()).unwrap();
+        assert_eq!(decoded, sealed);
+
+        // On another parent the proof is for the wrong input
+        let other_parent = unsealed(&parent);
+        assert!(config.verify(&sealed, &other_parent).is_err());
+
+        // A slot it lost cannot be sealed, and a forged proof does not verify
+        let lost = (0..500).find(|&slot| config.slot_proof(&parent, slot, vrf_key).unwrap().is_none()).unwrap();
+        assert!(config.seal(unsealed(&parent), &parent, lost, key, vrf_key).is_err());
+        let mut forged = sealed.clone();
+        forged.header.vrf_proof = Some(keys[0].1.prove(&PosConfig::slot_input(&parent, slot)).unwrap());
+        forged.header.proposer_signature = Some(Vec::new());
+        forged.header.proposer_signature = Some(key.sign(&forged.header.seal_hash().unwrap()));
+        assert!(config.verify(&forged, &parent).is_err());
+    }
+
+    #[test]
+    fn test_stake_weights_election() {
+        // All the stake at the full leader rate wins every slot
+        let (keys, config) = validators(&[10]);
+        let parent = Block::genesis().unwrap();
+        assert!((0..20).all(|slot| config.slot_proof(&parent, slot, &keys[0].1).unwrap().is_some()));
+
+        let (keys, config) = validators(&[900, 100]);
+        let wins = |i: usize| {
+            (0..400).filter(|&slot| config.slot_proof(&parent, slot, &keys[i].1).unwrap().is_some()).count()
+        };
+        assert!(wins(0) > 2 * wins(1));
+
+        let broke = StakedValidator { stake: 0, ..config.validators[0].clone() };
+        assert!(PosConfig { validators: vec![broke], ..config.clone() }.validate().is_err());
+        assert!(PosConfig { leader_rate_percent: 0, ..config }.validate().is_err());
+    }
+}
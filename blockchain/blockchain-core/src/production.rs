@@ -0,0 +1,153 @@
+// core/blockchain-core/src/production.rs
+//! When a block may be produced without user transactions.
+//!
+//! `EmptyBlockRules` belongs to the chain spec: every node checks every block
+//! against it, so it must be the same network-wide. `ProducerConfig` is a
+//! node's own choice of when to produce, checked against the rules at startup
+//! so a node never produces blocks its peers reject. A chain that allows empty
+//! blocks accepts both nodes that wait for transactions and nodes producing
+//! steady heartbeats; one that only allows heartbeats still accepts nodes
+//! that wait for transactions.
+use crate::{Block, BlockchainError, ChainParams, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Which blocks carrying only a coinbase a chain accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmptyBlockRules {
+    /// Empty blocks are valid whenever any block is
+    pub allow_empty_blocks: bool,
+    /// With `allow_empty_blocks` off, an empty block is still valid once this
+    /// long has passed since its parent, as a heartbeat through idle periods
+    #[serde(default)]
+    pub max_idle_interval_secs: Option<u64>,
+}
+
+impl Default for EmptyBlockRules {
+    /// Specs written before these rules accepted every empty block
+    fn default() -> Self {
+        Self { allow_empty_blocks: true, max_idle_interval_secs: None }
+    }
+}
+
+impl EmptyBlockRules {
+    pub fn validate(&self, params: &ChainParams) -> Result<()> {
+        if let Some(idle) = self.max_idle_interval_secs {
+            if idle < params.target_block_time_secs {
+                return Err(BlockchainError::InvalidChainParams {
+                    reason: format!(
+                        "Max idle interval {}s is shorter than the target block time {}s",
+                        idle, params.target_block_time_secs
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject `block` if it is empty and the rules do not allow it to follow
+    /// `parent`
+    pub fn check(&self, block: &Block, parent: &Block) -> Result<()> {
+        if !is_empty_block(block) || self.allow_empty_blocks {
+            return Ok(());
+        }
+        let idle = (block.header.timestamp - parent.header.timestamp).num_seconds();
+        match self.max_idle_interval_secs {
+            Some(max_idle) if idle >= 0 && idle as u64 >= max_idle => Ok(()),
+            Some(max_idle) => Err(BlockchainError::BlockValidationFailed {
+                reason: format!("Empty block {}s after its parent, heartbeats need {}s", idle, max_idle),
+            }),
+            None => Err(BlockchainError::BlockValidationFailed {
+                reason: "Chain does not accept empty blocks".to_string(),
+            }),
+        }
+    }
+}
+
+/// Whether `block` carries no transactions besides its coinbase
+pub fn is_empty_block(block: &Block) -> bool {
+    block.transactions.iter().all(|tx| tx.is_coinbase())
+}
+
+/// When this node produces a block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProducerConfig {
+    /// Produce at every opportunity, with or without transactions
+    pub allow_empty_blocks: bool,
+    /// Otherwise produce an empty heartbeat block once the chain has been idle
+    /// this long; without it, produce only when transactions are pending
+    pub max_idle_interval: Option<Duration>,
+}
+
+impl ProducerConfig {
+    /// Check that every block this config produces is valid under `rules`
+    pub fn validate(&self, rules: &EmptyBlockRules) -> Result<()> {
+        if rules.allow_empty_blocks {
+            return Ok(());
+        }
+        if self.allow_empty_blocks {
+            return Err(BlockchainError::InvalidChainParams {
+                reason: "Chain does not accept empty blocks; disable allow_empty_blocks".to_string(),
+            });
+        }
+        match (self.max_idle_interval, rules.max_idle_interval_secs) {
+            (None, _) => Ok(()),
+            (Some(_), None) => Err(BlockchainError::InvalidChainParams {
+                reason: "Chain does not accept heartbeat blocks; unset max_idle_interval".to_string(),
+            }),
+            (Some(idle), Some(min)) if idle.as_secs() < min => Err(BlockchainError::InvalidChainParams {
+                reason: format!("Max idle interval {}s is below the chain's {}s", idle.as_secs(), min),
+            }),
+            (Some(_), Some(_)) => Ok(()),
+        }
+    }
+
+    /// Whether to produce now, with `pending` transactions waiting and `idle`
+    /// elapsed since the parent block
+    pub fn should_produce(&self, pending: usize, idle: Duration) -> bool {
+        pending > 0 || self.allow_empty_blocks || self.max_idle_interval.is_some_and(|max| idle >= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChainSpec, Transaction};
+    use chrono::Duration as ChronoDuration;
+
+    fn child(parent: &Block, secs: i64, transactions: Vec<Transaction>) -> Block {
+        let mut block = ChainSpec::default().produce_block([7; 20], 1, parent.hash, transactions, 1).unwrap();
+        block.header.timestamp = parent.header.timestamp + ChronoDuration::seconds(secs);
+        block.hash = block.calculate_hash().unwrap();
+        block
+    }
+
+    #[test]
+    fn test_heartbeat_rules_and_producers() {
+        let params = ChainParams::mainnet();
+        let rules = EmptyBlockRules { allow_empty_blocks: false, max_idle_interval_secs: Some(60) };
+        rules.validate(&params).unwrap();
+        assert!(EmptyBlockRules { max_idle_interval_secs: Some(5), ..rules }.validate(&params).is_err());
+
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        let tx = Transaction::new_transfer([1; 20], [2; 20], 100, 0, 21_000, 1).unwrap();
+        rules.check(&child(&genesis, 12, vec![tx]), &genesis).unwrap();
+        assert!(rules.check(&child(&genesis, 12, vec![]), &genesis).is_err());
+        rules.check(&child(&genesis, 60, vec![]), &genesis).unwrap();
+        EmptyBlockRules::default().check(&child(&genesis, 12, vec![]), &genesis).unwrap();
+
+        // Transaction-only and slower heartbeat producers both fit the rules
+        let on_demand = ProducerConfig::default();
+        let heartbeat = ProducerConfig { max_idle_interval: Some(Duration::from_secs(90)), ..on_demand };
+        on_demand.validate(&rules).unwrap();
+        heartbeat.validate(&rules).unwrap();
+        assert!(ProducerConfig { allow_empty_blocks: true, ..on_demand }.validate(&rules).is_err());
+        let eager = ProducerConfig { max_idle_interval: Some(Duration::from_secs(30)), ..on_demand };
+        assert!(eager.validate(&rules).is_err());
+
+        assert!(!on_demand.should_produce(0, Duration::from_secs(600)));
+        assert!(on_demand.should_produce(1, Duration::ZERO));
+        assert!(!heartbeat.should_produce(0, Duration::from_secs(89)));
+        assert!(heartbeat.should_produce(0, Duration::from_secs(90)));
+    }
+}
@@ -0,0 +1,135 @@
+// core/blockchain-core/src/readiness.rs
+//! Readiness gate for accepting transactions and producing blocks.
+//!
+//! A node with no peers can't propagate what it accepts, and a node still
+//! catching up would build on a stale head. The gate stays closed until at
+//! least `min_peers` peers are connected and the local head is no more than
+//! `max_sync_lag` blocks behind the best one they report. Until then
+//! `sendRawTransaction` is refused with the current status and block
+//! producers should not call `ProducerConfig::should_produce`.
+use crate::BlockHeight;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadinessConfig {
+    /// Connected peers needed before the node is ready; 0 disables the check
+    pub min_peers: usize,
+    /// Blocks the local head may trail the best peer by
+    pub max_sync_lag: u64,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self { min_peers: 1, max_sync_lag: 10 }
+    }
+}
+
+impl ReadinessConfig {
+    /// A gate that opens straight away, for single-node and test setups
+    pub fn disabled() -> Self {
+        Self { min_peers: 0, max_sync_lag: u64::MAX }
+    }
+}
+
+/// What operators and refused clients see of the gate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReadinessStatus {
+    pub ready: bool,
+    pub peers: usize,
+    pub min_peers: usize,
+    pub local_height: Option<BlockHeight>,
+    /// Highest head reported by a connected peer
+    pub best_peer_height: Option<BlockHeight>,
+    pub sync_lag: u64,
+    pub max_sync_lag: u64,
+    /// Why the node is not ready; empty once it is
+    pub reasons: Vec<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReadinessGate {
+    config: ReadinessConfig,
+    status: ReadinessStatus,
+}
+
+impl ReadinessGate {
+    /// A gate that has seen no peers and no blocks yet
+    pub fn new(config: ReadinessConfig) -> Self {
+        let status = Self::evaluate(&config, 0, None, None, Utc::now());
+        Self { config, status }
+    }
+
+    fn evaluate(
+        config: &ReadinessConfig,
+        peers: usize,
+        local_height: Option<BlockHeight>,
+        best_peer_height: Option<BlockHeight>,
+        now: DateTime<Utc>,
+    ) -> ReadinessStatus {
+        let sync_lag = best_peer_height.map_or(0, |best| best.saturating_sub(local_height.unwrap_or(0)));
+        let mut reasons = Vec::new();
+        if peers < config.min_peers {
+            reasons.push(format!("{} of {} required peers connected", peers, config.min_peers));
+        }
+        if sync_lag > config.max_sync_lag {
+            reasons.push(format!("{} blocks behind the best peer, at most {} allowed", sync_lag, config.max_sync_lag));
+        }
+        ReadinessStatus {
+            ready: reasons.is_empty(),
+            peers,
+            min_peers: config.min_peers,
+            local_height,
+            best_peer_height,
+            sync_lag,
+            max_sync_lag: config.max_sync_lag,
+            reasons,
+            updated_at: now,
+        }
+    }
+
+    /// Feed the connected peer count and heads, returning the new readiness
+    /// if it changed
+    pub fn observe(
+        &mut self,
+        peers: usize,
+        local_height: Option<BlockHeight>,
+        best_peer_height: Option<BlockHeight>,
+        now: DateTime<Utc>,
+    ) -> Option<bool> {
+        let was_ready = self.status.ready;
+        self.status = Self::evaluate(&self.config, peers, local_height, best_peer_height, now);
+        (self.status.ready != was_ready).then_some(self.status.ready)
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.status.ready
+    }
+
+    pub fn status(&self) -> ReadinessStatus {
+        self.status.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waits_for_peers_and_sync() {
+        let mut gate = ReadinessGate::new(ReadinessConfig { min_peers: 2, max_sync_lag: 5 });
+        assert!(!gate.is_ready());
+        assert_eq!(gate.status().reasons, ["0 of 2 required peers connected"]);
+
+        assert_eq!(gate.observe(2, Some(10), Some(100), Utc::now()), None);
+        let status = gate.status();
+        assert_eq!((status.sync_lag, status.reasons.len()), (90, 1));
+        assert_eq!(status.reasons[0], "90 blocks behind the best peer, at most 5 allowed");
+
+        assert_eq!(gate.observe(3, Some(96), Some(100), Utc::now()), Some(true));
+        assert_eq!(gate.observe(1, Some(100), Some(100), Utc::now()), Some(false));
+
+        assert!(ReadinessGate::new(ReadinessConfig::disabled()).is_ready());
+    }
+}
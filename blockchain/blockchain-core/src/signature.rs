@@ -0,0 +1,269 @@
+// core/blockchain-core/src/signature.rs
+//! Transaction signing backends.
+//!
+//! The scheme is carried by the signature encoding itself, so transactions
+//! written before ed25519 support decode and verify unchanged:
+//!
+//! - secp256k1: 64-byte compact signature followed by the recovery id
+//! - ed25519: 32-byte public key followed by the 64-byte signature, since
+//!   ed25519 keys cannot be recovered from a signature
+use crate::{BlockchainError, Result};
+use ed25519_dalek::{Signer, Verifier};
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const SECP256K1_SIGNATURE_LEN: usize = 65;
+const ED25519_PUBLIC_KEY_LEN: usize = 32;
+const ED25519_SIGNATURE_LEN: usize = ED25519_PUBLIC_KEY_LEN + 64;
+
+/// Signature algorithm used by a chain
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureScheme {
+    #[default]
+    Secp256k1,
+    Ed25519,
+}
+
+impl SignatureScheme {
+    /// Length of a transaction signature under this scheme
+    pub fn signature_len(&self) -> usize {
+        match self {
+            SignatureScheme::Secp256k1 => SECP256K1_SIGNATURE_LEN,
+            SignatureScheme::Ed25519 => ED25519_SIGNATURE_LEN,
+        }
+    }
+
+    /// Identify the scheme of an encoded signature
+    pub fn of_signature(signature: &[u8]) -> Option<Self> {
+        match signature.len() {
+            SECP256K1_SIGNATURE_LEN => Some(SignatureScheme::Secp256k1),
+            ED25519_SIGNATURE_LEN => Some(SignatureScheme::Ed25519),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SignatureScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureScheme::Secp256k1 => write!(f, "secp256k1"),
+            SignatureScheme::Ed25519 => write!(f, "ed25519"),
+        }
+    }
+}
+
+impl FromStr for SignatureScheme {
+    type Err = BlockchainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "secp256k1" => Ok(SignatureScheme::Secp256k1),
+            "ed25519" => Ok(SignatureScheme::Ed25519),
+            other => Err(invalid(format!("Unknown signature scheme: {}", other))),
+        }
+    }
+}
+
+/// A secret key for one of the supported schemes
+#[derive(Clone)]
+pub enum KeyPair {
+    Secp256k1(secp256k1::SecretKey),
+    Ed25519(ed25519_dalek::SigningKey),
+}
+
+impl KeyPair {
+    /// Generate a random key
+    pub fn generate(scheme: SignatureScheme) -> Self {
+        match scheme {
+            SignatureScheme::Secp256k1 => {
+                KeyPair::Secp256k1(secp256k1::SecretKey::new(&mut rand::thread_rng()))
+            }
+            SignatureScheme::Ed25519 => {
+                KeyPair::Ed25519(ed25519_dalek::SigningKey::from_bytes(&rand::random()))
+            }
+        }
+    }
+
+    /// Restore a key from its 32 secret bytes
+    pub fn from_secret_bytes(scheme: SignatureScheme, bytes: &[u8]) -> Result<Self> {
+        match scheme {
+            SignatureScheme::Secp256k1 => secp256k1::SecretKey::from_slice(bytes)
+                .map(KeyPair::Secp256k1)
+                .map_err(|e| invalid(format!("Invalid secp256k1 secret key: {}", e))),
+            SignatureScheme::Ed25519 => {
+                let bytes: [u8; 32] = bytes
+                    .try_into()
+                    .map_err(|_| invalid("Ed25519 secret key must be 32 bytes".to_string()))?;
+                Ok(KeyPair::Ed25519(ed25519_dalek::SigningKey::from_bytes(&bytes)))
+            }
+        }
+    }
+
+    pub fn scheme(&self) -> SignatureScheme {
+        match self {
+            KeyPair::Secp256k1(_) => SignatureScheme::Secp256k1,
+            KeyPair::Ed25519(_) => SignatureScheme::Ed25519,
+        }
+    }
+
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        match self {
+            KeyPair::Secp256k1(key) => key.secret_bytes(),
+            KeyPair::Ed25519(key) => key.to_bytes(),
+        }
+    }
+
+    /// Public key bytes: 65-byte uncompressed secp256k1 or 32-byte ed25519
+    pub fn public_key(&self) -> Vec<u8> {
+        match self {
+            KeyPair::Secp256k1(key) => key
+                .public_key(&Secp256k1::signing_only())
+                .serialize_uncompressed()
+                .to_vec(),
+            KeyPair::Ed25519(key) => key.verifying_key().to_bytes().to_vec(),
+        }
+    }
+
+    /// Sign a 32-byte digest, producing the scheme's transaction signature encoding
+    pub fn sign(&self, digest: &[u8; 32]) -> Vec<u8> {
+        match self {
+            KeyPair::Secp256k1(key) => {
+                let message = Message::from_digest_slice(digest).expect("digest is 32 bytes");
+                let (recovery_id, compact) = Secp256k1::signing_only()
+                    .sign_ecdsa_recoverable(&message, key)
+                    .serialize_compact();
+
+                let mut signature = compact.to_vec();
+                signature.push(recovery_id.to_i32() as u8);
+                signature
+            }
+            KeyPair::Ed25519(key) => {
+                let mut signature = key.verifying_key().to_bytes().to_vec();
+                signature.extend_from_slice(&key.sign(digest).to_bytes());
+                signature
+            }
+        }
+    }
+}
+
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret material
+        write!(f, "KeyPair({}, {})", self.scheme(), hex::encode(self.public_key()))
+    }
+}
+
+/// Verify a signature over `digest` and return the signer's public key
+pub fn verify_signature(scheme: SignatureScheme, digest: &[u8; 32], signature: &[u8]) -> Result<Vec<u8>> {
+    if signature.len() != scheme.signature_len() {
+        return Err(invalid(format!(
+            "Expected a {}-byte {} signature, got {} bytes",
+            scheme.signature_len(),
+            scheme,
+            signature.len()
+        )));
+    }
+
+    match scheme {
+        SignatureScheme::Secp256k1 => {
+            let recovery_id = RecoveryId::from_i32(signature[64] as i32)
+                .map_err(|e| invalid(format!("Invalid recovery id: {}", e)))?;
+            let recoverable = RecoverableSignature::from_compact(&signature[..64], recovery_id)
+                .map_err(|e| invalid(format!("Malformed secp256k1 signature: {}", e)))?;
+            let message = Message::from_digest_slice(digest).expect("digest is 32 bytes");
+
+            let public_key = Secp256k1::verification_only()
+                .recover_ecdsa(&message, &recoverable)
+                .map_err(|e| invalid(format!("Signature verification failed: {}", e)))?;
+            Ok(public_key.serialize_uncompressed().to_vec())
+        }
+        SignatureScheme::Ed25519 => {
+            let (key_bytes, sig_bytes) = signature.split_at(ED25519_PUBLIC_KEY_LEN);
+            let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(key_bytes.try_into().unwrap())
+                .map_err(|e| invalid(format!("Malformed ed25519 public key: {}", e)))?;
+            let sig = ed25519_dalek::Signature::from_bytes(sig_bytes.try_into().unwrap());
+
+            verifying_key
+                .verify(digest, &sig)
+                .map_err(|e| invalid(format!("Signature verification failed: {}", e)))?;
+            Ok(key_bytes.to_vec())
+        }
+    }
+}
+
+/// Verify many ed25519 signatures in one multi-scalar multiplication,
+/// returning the signers' public keys. An error says some signature is bad,
+/// not which; callers fall back to `verify_signature` to find it.
+pub fn verify_ed25519_batch(digests: &[&[u8; 32]], signatures: &[&[u8]]) -> Result<Vec<Vec<u8>>> {
+    if digests.len() != signatures.len() {
+        return Err(invalid(format!("{} digests for {} signatures", digests.len(), signatures.len())));
+    }
+    let mut keys = Vec::with_capacity(signatures.len());
+    let mut sigs = Vec::with_capacity(signatures.len());
+    for signature in signatures {
+        if signature.len() != ED25519_SIGNATURE_LEN {
+            return Err(invalid(format!("Expected a {}-byte ed25519 signature", ED25519_SIGNATURE_LEN)));
+        }
+        let (key_bytes, sig_bytes) = signature.split_at(ED25519_PUBLIC_KEY_LEN);
+        keys.push(
+            ed25519_dalek::VerifyingKey::from_bytes(key_bytes.try_into().unwrap())
+                .map_err(|e| invalid(format!("Malformed ed25519 public key: {}", e)))?,
+        );
+        sigs.push(ed25519_dalek::Signature::from_bytes(sig_bytes.try_into().unwrap()));
+    }
+    let messages: Vec<&[u8]> = digests.iter().map(|digest| &digest[..]).collect();
+    ed25519_dalek::verify_batch(&messages, &sigs, &keys)
+        .map_err(|e| invalid(format!("Batch verification failed: {}", e)))?;
+    Ok(keys.iter().map(|key| key.to_bytes().to_vec()).collect())
+}
+
+fn invalid(reason: String) -> BlockchainError {
+    BlockchainError::InvalidSignature { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify_each_scheme() {
+        let digest = crate::hash_data(b"payload");
+
+        for scheme in [SignatureScheme::Secp256k1, SignatureScheme::Ed25519] {
+            let key = KeyPair::generate(scheme);
+            let signature = key.sign(&digest);
+
+            assert_eq!(signature.len(), scheme.signature_len());
+            assert_eq!(SignatureScheme::of_signature(&signature), Some(scheme));
+            assert_eq!(verify_signature(scheme, &digest, &signature).unwrap(), key.public_key());
+
+            let restored = KeyPair::from_secret_bytes(scheme, &key.secret_bytes()).unwrap();
+            assert_eq!(restored.public_key(), key.public_key());
+        }
+    }
+
+    #[test]
+    fn test_rejects_tampered_signatures() {
+        let digest = crate::hash_data(b"payload");
+        let other = crate::hash_data(b"other");
+
+        let ed_key = KeyPair::generate(SignatureScheme::Ed25519);
+        let mut signature = ed_key.sign(&digest);
+        assert!(verify_signature(SignatureScheme::Ed25519, &other, &signature).is_err());
+        signature[40] ^= 1;
+        assert!(verify_signature(SignatureScheme::Ed25519, &digest, &signature).is_err());
+
+        // A secp256k1 signature over another digest recovers a different key
+        let secp_key = KeyPair::generate(SignatureScheme::Secp256k1);
+        let signature = secp_key.sign(&digest);
+        let recovered = verify_signature(SignatureScheme::Secp256k1, &other, &signature);
+        assert!(recovered.map_or(true, |key| key != secp_key.public_key()));
+
+        assert!(verify_signature(SignatureScheme::Ed25519, &digest, &signature).is_err());
+        assert_eq!("ed25519".parse::<SignatureScheme>().unwrap(), SignatureScheme::Ed25519);
+    }
+}
@@ -0,0 +1,305 @@
+// core/blockchain-core/src/smt.rs
+//! Sparse merkle tree keyed by account address.
+//!
+//! Conceptually every one of the 2^160 addresses has a leaf, at the position
+//! its bits spell out from the root down, and an absent account has the
+//! all-zero empty leaf. Empty subtrees hash to precomputed defaults, and a
+//! subtree holding a single account is stored as that one leaf, so the tree
+//! keeps about two nodes per account. Updates copy only the path they
+//! change and clones share the rest, which keeps cloning a ledger cheap.
+//!
+//! Since an address's position is fixed, a proof that its leaf is empty shows
+//! that the account does not exist.
+use crate::merkle::hash_pair;
+use crate::{Address, BlockHash};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+
+/// Levels between the root and the leaves, one per address bit
+pub const SMT_DEPTH: usize = 160;
+
+/// Leaf of an address without an account
+pub const EMPTY_LEAF: BlockHash = [0; 32];
+
+#[derive(Debug)]
+enum Node {
+    /// Subtree holding exactly one account
+    Leaf { address: Address, leaf: BlockHash, hash: BlockHash },
+    Branch { left: Option<Arc<Node>>, right: Option<Arc<Node>>, hash: BlockHash },
+}
+
+impl Node {
+    fn hash(&self) -> BlockHash {
+        match self {
+            Node::Leaf { hash, .. } | Node::Branch { hash, .. } => *hash,
+        }
+    }
+
+    /// `address`'s leaf as the only one in a subtree rooted at `depth`
+    fn leaf(depth: usize, address: Address, leaf: BlockHash) -> Arc<Node> {
+        let hash = fold(leaf, &address, depth, |_| None);
+        Arc::new(Node::Leaf { address, leaf, hash })
+    }
+
+    fn branch(depth: usize, left: Option<Arc<Node>>, right: Option<Arc<Node>>) -> Arc<Node> {
+        let hash = hash_pair(&subtree_hash(left.as_ref(), depth + 1), &subtree_hash(right.as_ref(), depth + 1));
+        Arc::new(Node::Branch { left, right, hash })
+    }
+
+    /// Subtree at `depth` over two children, collapsed to a lone leaf or to
+    /// nothing so every set of accounts has one shape
+    fn join(depth: usize, left: Option<Arc<Node>>, right: Option<Arc<Node>>) -> Option<Arc<Node>> {
+        if let (Some(only), None) | (None, Some(only)) = (&left, &right) {
+            if let Node::Leaf { address, leaf, .. } = only.as_ref() {
+                return Some(Node::leaf(depth, *address, *leaf));
+            }
+        }
+        if left.is_none() && right.is_none() {
+            return None;
+        }
+        Some(Node::branch(depth, left, right))
+    }
+}
+
+/// Sparse merkle tree over account leaves. Clones share unchanged subtrees.
+#[derive(Debug, Clone, Default)]
+pub struct SparseMerkleTree {
+    root: Option<Arc<Node>>,
+}
+
+impl SparseMerkleTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn root(&self) -> BlockHash {
+        subtree_hash(self.root.as_ref(), 0)
+    }
+
+    /// Set `address`'s leaf, or clear it with `None`, rehashing its path
+    pub fn update(&mut self, address: &Address, leaf: Option<BlockHash>) {
+        self.root = update(self.root.as_ref(), 0, address, leaf);
+    }
+
+    /// Proof of `address`'s current leaf, whether it holds an account or not
+    pub fn proof(&self, address: &Address) -> SparseMerkleProof {
+        let mut proof = SparseMerkleProof::default();
+        // Top-down, as the tree is walked; reversed to leaf-first at the end
+        let mut siblings = Vec::new();
+        let mut node = self.root.as_ref();
+        let mut depth = 0;
+
+        while let Some(current) = node {
+            match current.as_ref() {
+                Node::Branch { left, right, .. } => {
+                    let (next, sibling) = if bit(address, depth) { (right, left) } else { (left, right) };
+                    if let Some(sibling) = sibling {
+                        set_bit(&mut proof.non_empty, depth);
+                        siblings.push(sibling.hash());
+                    }
+                    node = next.as_ref();
+                    depth += 1;
+                }
+                Node::Leaf { address: other, leaf, .. } => {
+                    // Another account alone in this subtree is the one
+                    // non-empty sibling, where the two paths part
+                    if other != address {
+                        let split = (depth..SMT_DEPTH).find(|d| bit(address, *d) != bit(other, *d)).unwrap_or(depth);
+                        set_bit(&mut proof.non_empty, split);
+                        siblings.push(fold(*leaf, other, split + 1, |_| None));
+                    }
+                    break;
+                }
+            }
+        }
+
+        siblings.reverse();
+        proof.siblings = siblings;
+        proof
+    }
+}
+
+/// Path from a leaf to the root, listing only non-empty siblings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SparseMerkleProof {
+    /// Bit `d`, in address bit order, is set when the sibling joining the
+    /// path at depth `d` is non-empty and so listed in `siblings`
+    pub non_empty: [u8; 20],
+    /// Non-empty sibling hashes from the leaf up to the root
+    pub siblings: Vec<BlockHash>,
+}
+
+impl SparseMerkleProof {
+    /// Root of the tree this proof places `leaf` at `address` in, with `None`
+    /// standing for the empty leaf; `None` if the proof lists more or fewer
+    /// siblings than it flags
+    pub fn root(&self, address: &Address, leaf: Option<BlockHash>) -> Option<BlockHash> {
+        let expected = self.non_empty.iter().map(|byte| byte.count_ones() as usize).sum::<usize>();
+        if expected != self.siblings.len() {
+            return None;
+        }
+        let mut listed = self.siblings.iter();
+        let leaf = leaf.unwrap_or(EMPTY_LEAF);
+        Some(fold(leaf, address, 0, |depth| {
+            bit(&self.non_empty, depth).then(|| *listed.next().expect("sibling count checked"))
+        }))
+    }
+
+    pub fn verify(&self, root: &BlockHash, address: &Address, leaf: Option<BlockHash>) -> bool {
+        self.root(address, leaf).as_ref() == Some(root)
+    }
+}
+
+fn update(node: Option<&Arc<Node>>, depth: usize, address: &Address, leaf: Option<BlockHash>) -> Option<Arc<Node>> {
+    let Some(current) = node else {
+        return leaf.map(|leaf| Node::leaf(depth, *address, leaf));
+    };
+    match current.as_ref() {
+        Node::Leaf { address: other, .. } if other == address => leaf.map(|leaf| Node::leaf(depth, *address, leaf)),
+        // Clearing an address without an account changes nothing
+        Node::Leaf { .. } if leaf.is_none() => Some(current.clone()),
+        Node::Leaf { address: other, leaf: other_leaf, .. } => {
+            // Push the resident account down a level and insert beside it
+            let moved = Some(Node::leaf(depth + 1, *other, *other_leaf));
+            let branch = if bit(other, depth) {
+                Node::branch(depth, None, moved)
+            } else {
+                Node::branch(depth, moved, None)
+            };
+            update(Some(&branch), depth, address, leaf)
+        }
+        Node::Branch { left, right, .. } => {
+            if bit(address, depth) {
+                Node::join(depth, left.clone(), update(right.as_ref(), depth + 1, address, leaf))
+            } else {
+                Node::join(depth, update(left.as_ref(), depth + 1, address, leaf), right.clone())
+            }
+        }
+    }
+}
+
+fn subtree_hash(node: Option<&Arc<Node>>, depth: usize) -> BlockHash {
+    node.map_or(empty_hashes()[depth], |node| node.hash())
+}
+
+/// Hash `leaf` up along `address`'s path from the leaves to `depth`, taking
+/// the sibling joining at each level from `sibling` or the empty subtree
+fn fold(
+    leaf: BlockHash,
+    address: &Address,
+    depth: usize,
+    mut sibling: impl FnMut(usize) -> Option<BlockHash>,
+) -> BlockHash {
+    let empty = empty_hashes();
+    let mut hash = leaf;
+    for level in (depth..SMT_DEPTH).rev() {
+        let other = sibling(level).unwrap_or(empty[level + 1]);
+        hash = if bit(address, level) { hash_pair(&other, &hash) } else { hash_pair(&hash, &other) };
+    }
+    hash
+}
+
+/// Hash of an empty subtree rooted at each depth, the leaves last
+fn empty_hashes() -> &'static [BlockHash] {
+    static EMPTY: OnceLock<Vec<BlockHash>> = OnceLock::new();
+    EMPTY.get_or_init(|| {
+        let mut hashes = vec![EMPTY_LEAF; SMT_DEPTH + 1];
+        for depth in (0..SMT_DEPTH).rev() {
+            hashes[depth] = hash_pair(&hashes[depth + 1], &hashes[depth + 1]);
+        }
+        hashes
+    })
+}
+
+/// Bit `index` of an address, most significant first; for an address, whether
+/// its path turns right below depth `index`
+fn bit(bits: &[u8; 20], index: usize) -> bool {
+    bits[index / 8] & (0x80 >> (index % 8)) != 0
+}
+
+fn set_bit(bits: &mut [u8; 20], index: usize) {
+    bits[index / 8] |= 0x80 >> (index % 8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash_data;
+
+    fn leaf(byte: u8) -> Option<BlockHash> {
+        Some(hash_data(&[byte]))
+    }
+
+    /// Root computed level by level over every non-empty path, without
+    /// collapsing lone leaves
+    fn naive_root(leaves: &[(Address, BlockHash)], depth: usize) -> BlockHash {
+        if leaves.is_empty() {
+            return empty_hashes()[depth];
+        }
+        if depth == SMT_DEPTH {
+            return leaves[0].1;
+        }
+        let (right, left): (Vec<_>, Vec<_>) = leaves.iter().copied().partition(|(address, _)| bit(address, depth));
+        hash_pair(&naive_root(&left, depth + 1), &naive_root(&right, depth + 1))
+    }
+
+    #[test]
+    fn test_root_matches_full_tree_and_ignores_order() {
+        let (a, b, c) = ([0x00; 20], [0x01; 20], [0x80; 20]);
+        let mut tree = SparseMerkleTree::new();
+        assert_eq!(tree.root(), empty_hashes()[0]);
+
+        tree.update(&a, leaf(1));
+        tree.update(&b, leaf(2));
+        tree.update(&c, leaf(3));
+        let expected = naive_root(&[(a, leaf(1).unwrap()), (b, leaf(2).unwrap()), (c, leaf(3).unwrap())], 0);
+        assert_eq!(tree.root(), expected);
+
+        let mut reordered = SparseMerkleTree::new();
+        reordered.update(&c, leaf(3));
+        reordered.update(&a, leaf(9));
+        reordered.update(&b, leaf(2));
+        reordered.update(&a, leaf(1));
+        assert_eq!(reordered.root(), expected);
+
+        // Removing accounts collapses back to the same shapes
+        let snapshot = tree.clone();
+        tree.update(&b, None);
+        tree.update(&[0x42; 20], None);
+        assert_eq!(tree.root(), naive_root(&[(a, leaf(1).unwrap()), (c, leaf(3).unwrap())], 0));
+        tree.update(&a, None);
+        tree.update(&c, None);
+        assert_eq!(tree.root(), empty_hashes()[0]);
+        assert_eq!(snapshot.root(), expected);
+    }
+
+    #[test]
+    fn test_membership_and_non_membership_proofs() {
+        let mut tree = SparseMerkleTree::new();
+        let present = [[0x10; 20], [0x11; 20], [0xF0; 20]];
+        for (index, address) in present.iter().enumerate() {
+            tree.update(address, leaf(index as u8));
+        }
+        let root = tree.root();
+
+        for (index, address) in present.iter().enumerate() {
+            let proof = tree.proof(address);
+            assert!(proof.verify(&root, address, leaf(index as u8)));
+            assert!(!proof.verify(&root, address, leaf(99)));
+            assert!(!proof.verify(&root, address, None));
+        }
+
+        // Absent addresses, beside a lone leaf and under an empty subtree
+        let mut beside = [0x10; 20];
+        beside[19] = 0x11;
+        for absent in [beside, [0x70; 20], [0xF1; 20]] {
+            let proof = tree.proof(&absent);
+            assert!(proof.verify(&root, &absent, None));
+            assert!(!proof.verify(&root, &absent, leaf(1)));
+        }
+
+        let mut tampered = tree.proof(&present[0]);
+        tampered.siblings.pop();
+        assert!(tampered.root(&present[0], leaf(0)).is_none());
+    }
+}
@@ -0,0 +1,186 @@
+// core/blockchain-core/src/trace.rs
+//! Step traces of executing a single transaction, for debugging failures.
+//!
+//! A trace records the call frame the transaction opened, each execution
+//! step with the gas it charged, and every account read and write in order.
+//! The ledger charges the whole gas limit up front and has no contract
+//! interpreter, so a trace holds one frame and the gas is charged by the fee
+//! step. Traces are capped by `TraceLimits`; entries past the cap are
+//! dropped and the trace is marked truncated.
+use crate::{Address, Amount, BlockHeight, Nonce, Transaction, TransactionType, TxHash};
+use serde::{Deserialize, Serialize};
+
+/// Steps and state accesses kept per trace when no limit is given
+pub const DEFAULT_MAX_TRACE_ENTRIES: usize = 1_024;
+
+/// Call data bytes kept per frame when no limit is given
+pub const DEFAULT_MAX_TRACE_INPUT_BYTES: usize = 4_096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceLimits {
+    /// Steps plus state accesses kept
+    pub max_entries: usize,
+    /// Call data bytes kept per frame
+    pub max_input_bytes: usize,
+}
+
+impl Default for TraceLimits {
+    fn default() -> Self {
+        Self { max_entries: DEFAULT_MAX_TRACE_ENTRIES, max_input_bytes: DEFAULT_MAX_TRACE_INPUT_BYTES }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallKind {
+    Transfer,
+    Call,
+    Deploy,
+    /// Block reward minted to the producer
+    Mint,
+}
+
+/// A call opened while executing a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallFrame {
+    pub kind: CallKind,
+    /// Nesting depth; the transaction's own frame is 0
+    pub depth: u32,
+    pub from: Address,
+    /// `None` for deployments
+    pub to: Option<Address>,
+    pub value: Amount,
+    pub gas_limit: u64,
+    pub gas_used: u64,
+    /// Call data, or code followed by init data for a deployment, cut to
+    /// `max_input_bytes`
+    pub input: Vec<u8>,
+    /// Length of the full input
+    pub input_size: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepKind {
+    CheckNonce,
+    ChargeFee,
+    Transfer,
+    Mint,
+    DistributeFee,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub kind: StepKind,
+    /// Depth of the frame the step ran in
+    pub depth: u32,
+    /// Gas charged by this step
+    pub gas: u64,
+    /// Gas used by the transaction after this step
+    pub gas_used: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessKind {
+    Read,
+    Write,
+}
+
+/// An account read or written, with its state after the access
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateAccess {
+    pub kind: AccessKind,
+    pub address: Address,
+    pub balance: Amount,
+    pub nonce: Nonce,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionTrace {
+    pub tx_hash: TxHash,
+    pub block_height: BlockHeight,
+    /// Position of the transaction within its block
+    pub index: u32,
+    pub frames: Vec<CallFrame>,
+    pub steps: Vec<TraceStep>,
+    pub accesses: Vec<StateAccess>,
+    pub gas_used: u64,
+    /// Why execution failed; the ledger keeps none of the transaction's changes
+    pub error: Option<String>,
+    /// Whether entries or input bytes were dropped to stay within the limits
+    pub truncated: bool,
+}
+
+/// Collects the trace of one transaction as the ledger executes it
+#[derive(Debug, Clone)]
+pub struct ExecutionTracer {
+    limits: TraceLimits,
+    trace: ExecutionTrace,
+}
+
+impl ExecutionTracer {
+    pub fn new(tx: &Transaction, block_height: BlockHeight, index: u32, limits: TraceLimits) -> Self {
+        let (kind, input) = match &tx.tx_type {
+            TransactionType::Transfer { .. } => (CallKind::Transfer, Vec::new()),
+            TransactionType::Call { data, .. } => (CallKind::Call, data.clone()),
+            TransactionType::Deploy { code, init_data, .. } => (CallKind::Deploy, [&code[..], init_data].concat()),
+            TransactionType::Coinbase { .. } => (CallKind::Mint, Vec::new()),
+        };
+        let input_size = input.len();
+        let truncated = input_size > limits.max_input_bytes;
+        let frame = CallFrame {
+            kind,
+            depth: 0,
+            from: tx.sender(),
+            to: tx.recipient(),
+            value: tx.amount(),
+            gas_limit: tx.gas_limit,
+            gas_used: 0,
+            input: input.into_iter().take(limits.max_input_bytes).collect(),
+            input_size,
+        };
+        Self {
+            limits,
+            trace: ExecutionTrace {
+                tx_hash: tx.hash,
+                block_height,
+                index,
+                frames: vec![frame],
+                steps: Vec::new(),
+                accesses: Vec::new(),
+                gas_used: 0,
+                error: None,
+                truncated,
+            },
+        }
+    }
+
+    /// Record a step of the top-level frame charging `gas`
+    pub fn step(&mut self, kind: StepKind, gas: u64) {
+        self.trace.gas_used = self.trace.gas_used.saturating_add(gas);
+        self.trace.frames[0].gas_used = self.trace.gas_used;
+        let step = TraceStep { kind, depth: 0, gas, gas_used: self.trace.gas_used };
+        if self.has_room() {
+            self.trace.steps.push(step);
+        }
+    }
+
+    pub fn access(&mut self, kind: AccessKind, address: Address, balance: Amount, nonce: Nonce) {
+        if self.has_room() {
+            self.trace.accesses.push(StateAccess { kind, address, balance, nonce });
+        }
+    }
+
+    /// Finish the trace with the outcome of execution
+    pub fn finish(mut self, error: Option<String>) -> ExecutionTrace {
+        self.trace.error = error;
+        self.trace
+    }
+
+    fn has_room(&mut self) -> bool {
+        let room = self.trace.steps.len() + self.trace.accesses.len() < self.limits.max_entries;
+        self.trace.truncated |= !room;
+        room
+    }
+}
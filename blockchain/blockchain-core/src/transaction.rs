@@ -1,7 +1,13 @@
 // core/blockchain-core/src/transaction.rs
-use crate::{Address, Amount, Nonce, TxHash, Result, hash_serializable, validate_address, BlockchainError};
+use crate::{Address, Amount, BlockHeight, ChainId, Nonce, TxHash, Result, hash_serializable, validate_address, BlockchainError, LEGACY_CHAIN_ID};
+use crate::signature::{self, KeyPair, SignatureScheme};
+use crate::AddressExt;
+use bincode::Options;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 
 /// Transaction types supported by the blockchain
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -25,6 +31,13 @@ pub enum TransactionType {
         data: Vec<u8>,
         amount: Amount,
     },
+    /// Block reward plus collected fees, minted to the block producer
+    Coinbase {
+        to: Address,
+        amount: Amount,
+        /// Height of the rewarded block, keeping coinbase hashes unique
+        height: BlockHeight,
+    },
 }
 
 /// Transaction status for tracking
@@ -40,8 +53,14 @@ pub enum TransactionStatus {
     Rejected { reason: String },
 }
 
-/// Core transaction structure
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Core transaction structure.
+///
+/// A standalone transaction always encodes `chain_id`, so transactions
+/// decode back to back in any container. Inside blocks it is only encoded
+/// from `CHAIN_ID_BLOCK_VERSION` on, so older blocks keep their bytes and
+/// hashes. Standalone blobs from before it was always encoded are read with
+/// `Transaction::decode_legacy`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Transaction {
     /// Unique transaction hash
     pub hash: TxHash,
@@ -59,6 +78,8 @@ pub struct Transaction {
     pub signature: Vec<u8>,
     /// Current status
     pub status: TransactionStatus,
+    /// Chain the transaction is valid on; `LEGACY_CHAIN_ID` for transactions that predate replay protection
+    pub chain_id: ChainId,
 }
 
 impl Transaction {
@@ -102,6 +123,12 @@ impl Transaction {
         Self::new(tx_type, nonce, gas_limit, gas_price)
     }
 
+    /// Create the coinbase transaction paying `amount` to the producer of block `height`
+    pub fn new_coinbase(to: Address, amount: Amount, height: BlockHeight) -> Result<Self> {
+        let tx_type = TransactionType::Coinbase { to, amount, height };
+        Self::new(tx_type, 0, 0, 0)
+    }
+
     /// Internal constructor
     fn new(
         tx_type: TransactionType,
@@ -122,6 +149,7 @@ impl Transaction {
             timestamp,
             signature,
             status,
+            chain_id: LEGACY_CHAIN_ID,
         };
 
         // Calculate actual hash
@@ -129,7 +157,10 @@ impl Transaction {
         Ok(tx)
     }
 
-    /// Calculate transaction hash (excludes signature and status)
+    /// Calculate transaction hash (excludes signature and status).
+    ///
+    /// The chain id is committed to unless it is `LEGACY_CHAIN_ID`, so a
+    /// signature over the hash is only valid on one chain.
     pub fn calculate_hash(&self) -> Result<TxHash> {
         #[derive(Serialize)]
         struct HashableTransaction<'a> {
@@ -140,6 +171,12 @@ impl Transaction {
             timestamp: DateTime<Utc>,
         }
 
+        #[derive(Serialize)]
+        struct ChainHashableTransaction<'a> {
+            chain_id: ChainId,
+            transaction: HashableTransaction<'a>,
+        }
+
         let hashable = HashableTransaction {
             tx_type: &self.tx_type,
             nonce: self.nonce,
@@ -148,36 +185,82 @@ impl Transaction {
             timestamp: self.timestamp,
         };
 
-        hash_serializable(&hashable)
+        if self.chain_id == LEGACY_CHAIN_ID {
+            return hash_serializable(&hashable);
+        }
+        hash_serializable(&ChainHashableTransaction {
+            chain_id: self.chain_id,
+            transaction: hashable,
+        })
+    }
+
+    /// Stamp the transaction with a time taken from a `Clock`, recomputing its hash
+    pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Result<Self> {
+        self.timestamp = timestamp;
+        self.hash = self.calculate_hash()?;
+        Ok(self)
+    }
+
+    /// Bind the transaction to a chain, recomputing its hash
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Result<Self> {
+        self.chain_id = chain_id;
+        self.hash = self.calculate_hash()?;
+        Ok(self)
     }
 
-    /// Get the sender address from the transaction
+    /// Get the sender address from the transaction; the zero address for coinbase transactions
     pub fn sender(&self) -> Address {
         match &self.tx_type {
             TransactionType::Transfer { from, .. } => *from,
             TransactionType::Deploy { from, .. } => *from,
             TransactionType::Call { from, .. } => *from,
+            TransactionType::Coinbase { .. } => [0u8; 20],
         }
     }
 
+    pub fn is_coinbase(&self) -> bool {
+        matches!(self.tx_type, TransactionType::Coinbase { .. })
+    }
+
     /// Get the recipient address (if applicable)
     pub fn recipient(&self) -> Option<Address> {
         match &self.tx_type {
             TransactionType::Transfer { to, .. } => Some(*to),
             TransactionType::Call { to, .. } => Some(*to),
+            TransactionType::Coinbase { to, .. } => Some(*to),
             TransactionType::Deploy { .. } => None,
         }
     }
 
+    /// Address of the contract a deployment creates, taken from the hash of
+    /// its sender and nonce; `None` for other transactions
+    pub fn contract_address(&self) -> Option<Address> {
+        let TransactionType::Deploy { from, .. } = &self.tx_type else {
+            return None;
+        };
+        let mut preimage = from.to_vec();
+        preimage.extend_from_slice(&self.nonce.to_be_bytes());
+        let hash = crate::hash_data(&preimage);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hash[12..]);
+        Some(address)
+    }
+
     /// Get the amount being transferred
     pub fn amount(&self) -> Amount {
         match &self.tx_type {
             TransactionType::Transfer { amount, .. } => *amount,
             TransactionType::Call { amount, .. } => *amount,
+            TransactionType::Coinbase { amount, .. } => *amount,
             TransactionType::Deploy { .. } => 0,
         }
     }
 
+    /// Size of the transaction in its serialized (`tx_data`) form
+    pub fn encoded_size(&self) -> Result<u64> {
+        Ok(bincode::serialized_size(self)?)
+    }
+
     /// Calculate total transaction fee
     pub fn total_fee(&self) -> Amount {
         self.gas_limit * self.gas_price
@@ -233,16 +316,29 @@ impl Transaction {
                     });
                 }
             }
+            TransactionType::Coinbase { to, .. } => {
+                if !validate_address(to) {
+                    return Err(BlockchainError::InvalidTransaction {
+                        reason: "Invalid coinbase recipient".to_string(),
+                    });
+                }
+                // Minted by the producer, so there is no fee to pay and no sender to sign
+                if self.gas_limit != 0 || self.gas_price != 0 || !self.signature.is_empty() {
+                    return Err(BlockchainError::InvalidTransaction {
+                        reason: "Coinbase transactions carry no gas or signature".to_string(),
+                    });
+                }
+            }
         }
 
         // Validate gas parameters
-        if self.gas_limit == 0 {
+        if self.gas_limit == 0 && !self.is_coinbase() {
             return Err(BlockchainError::InvalidTransaction {
                 reason: "Gas limit cannot be zero".to_string(),
             });
         }
 
-        if self.gas_price == 0 {
+        if self.gas_price == 0 && !self.is_coinbase() {
             return Err(BlockchainError::InvalidTransaction {
                 reason: "Gas price cannot be zero".to_string(),
             });
@@ -259,12 +355,188 @@ impl Transaction {
         Ok(())
     }
 
+    /// Scheme of the attached signature, if it is signed
+    pub fn signature_scheme(&self) -> Option<SignatureScheme> {
+        SignatureScheme::of_signature(&self.signature)
+    }
+
+    /// Sign the transaction hash with `key`
+    pub fn sign(&mut self, key: &KeyPair) {
+        self.signature = key.sign(&self.hash);
+    }
+
+    /// Verify the signature under the chain's scheme and return the signer's public key
+    pub fn verify_signature(&self, scheme: SignatureScheme) -> Result<Vec<u8>> {
+        signature::verify_signature(scheme, &self.hash, &self.signature)
+    }
+
+    /// Verify the signature and that the signing key derives the sender address
+    pub fn verify_sender(&self, scheme: SignatureScheme) -> Result<()> {
+        let public_key = self.verify_signature(scheme)?;
+        if Address::from_public_key(&public_key)? != self.sender() {
+            return Err(BlockchainError::InvalidSignature {
+                reason: "Signer does not match sender address".to_string(),
+            });
+        }
+        Ok(())
+    }
+
     /// Update transaction status
     pub fn update_status(&mut self, status: TransactionStatus) {
         self.status = status;
     }
 }
 
+const TRANSACTION_FIELDS: [&str; 9] = [
+    "hash",
+    "tx_type",
+    "nonce",
+    "gas_limit",
+    "gas_price",
+    "timestamp",
+    "signature",
+    "status",
+    "chain_id",
+];
+
+/// Whether a positional encoding carries the trailing `chain_id` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChainIdField {
+    /// Only for chain-bound transactions, as standalone transactions were
+    /// once encoded; only decodable where the transaction ends the input
+    Optional,
+    /// Always, as inside chain-id blocks
+    Required,
+    /// Never, as inside blocks that predate chain ids
+    Absent,
+}
+
+/// A transaction serialized under a given `ChainIdField` rule
+pub(crate) struct TransactionEncoding<'a>(pub &'a Transaction, pub ChainIdField);
+
+impl Serialize for TransactionEncoding<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let tx = self.0;
+        let with_chain_id = match self.1 {
+            ChainIdField::Optional => tx.chain_id != LEGACY_CHAIN_ID,
+            ChainIdField::Required => true,
+            ChainIdField::Absent => false,
+        };
+
+        let mut state = serializer.serialize_struct("Transaction", if with_chain_id { 9 } else { 8 })?;
+        state.serialize_field("hash", &tx.hash)?;
+        state.serialize_field("tx_type", &tx.tx_type)?;
+        state.serialize_field("nonce", &tx.nonce)?;
+        state.serialize_field("gas_limit", &tx.gas_limit)?;
+        state.serialize_field("gas_price", &tx.gas_price)?;
+        state.serialize_field("timestamp", &tx.timestamp)?;
+        state.serialize_field("signature", &tx.signature)?;
+        state.serialize_field("status", &tx.status)?;
+        if with_chain_id {
+            state.serialize_field("chain_id", &tx.chain_id)?;
+        }
+        state.end()
+    }
+}
+
+impl Serialize for Transaction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        TransactionEncoding(self, ChainIdField::Required).serialize(serializer)
+    }
+}
+
+/// Decodes a transaction written under the given `ChainIdField` rule
+#[derive(Clone, Copy)]
+pub(crate) struct TransactionSeed(pub ChainIdField);
+
+impl<'de> DeserializeSeed<'de> for TransactionSeed {
+    type Value = Transaction;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Transaction, D::Error> {
+        deserializer.deserialize_struct("Transaction", &TRANSACTION_FIELDS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for TransactionSeed {
+    type Value = Transaction;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a transaction")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Transaction, A::Error> {
+        let missing = |i: usize| <A::Error as de::Error>::invalid_length(i, &self);
+        let hash = seq.next_element()?.ok_or_else(|| missing(0))?;
+        let tx_type = seq.next_element()?.ok_or_else(|| missing(1))?;
+        let nonce = seq.next_element()?.ok_or_else(|| missing(2))?;
+        let gas_limit = seq.next_element()?.ok_or_else(|| missing(3))?;
+        let gas_price = seq.next_element()?.ok_or_else(|| missing(4))?;
+        let timestamp = seq.next_element()?.ok_or_else(|| missing(5))?;
+        let signature = seq.next_element()?.ok_or_else(|| missing(6))?;
+        let status = seq.next_element()?.ok_or_else(|| missing(7))?;
+        let chain_id = match self.0 {
+            ChainIdField::Required => seq.next_element()?.ok_or_else(|| missing(8))?,
+            ChainIdField::Absent => LEGACY_CHAIN_ID,
+            // bincode reports running out of input as an error rather than a missing element
+            ChainIdField::Optional => seq.next_element().ok().flatten().unwrap_or(LEGACY_CHAIN_ID),
+        };
+
+        Ok(Transaction { hash, tx_type, nonce, gas_limit, gas_price, timestamp, signature, status, chain_id })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Transaction, A::Error> {
+        let (mut hash, mut tx_type, mut nonce, mut gas_limit) = (None, None, None, None);
+        let (mut gas_price, mut timestamp, mut signature, mut status) = (None, None, None, None);
+        let mut chain_id = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "hash" => hash = Some(map.next_value()?),
+                "tx_type" => tx_type = Some(map.next_value()?),
+                "nonce" => nonce = Some(map.next_value()?),
+                "gas_limit" => gas_limit = Some(map.next_value()?),
+                "gas_price" => gas_price = Some(map.next_value()?),
+                "timestamp" => timestamp = Some(map.next_value()?),
+                "signature" => signature = Some(map.next_value()?),
+                "status" => status = Some(map.next_value()?),
+                "chain_id" => chain_id = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(Transaction {
+            hash: hash.ok_or_else(|| de::Error::missing_field("hash"))?,
+            tx_type: tx_type.ok_or_else(|| de::Error::missing_field("tx_type"))?,
+            nonce: nonce.ok_or_else(|| de::Error::missing_field("nonce"))?,
+            gas_limit: gas_limit.ok_or_else(|| de::Error::missing_field("gas_limit"))?,
+            gas_price: gas_price.ok_or_else(|| de::Error::missing_field("gas_price"))?,
+            timestamp: timestamp.ok_or_else(|| de::Error::missing_field("timestamp"))?,
+            signature: signature.ok_or_else(|| de::Error::missing_field("signature"))?,
+            status: status.ok_or_else(|| de::Error::missing_field("status"))?,
+            chain_id: chain_id.unwrap_or(LEGACY_CHAIN_ID),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Transaction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        TransactionSeed(ChainIdField::Required).deserialize(deserializer)
+    }
+}
+
+impl Transaction {
+    /// Decode a standalone bincode transaction written before `chain_id` was
+    /// always encoded, when legacy transactions left it out. `bytes` must hold
+    /// this one transaction and nothing after it.
+    pub fn decode_legacy(bytes: &[u8]) -> Result<Transaction> {
+        let options = bincode::DefaultOptions::new().with_fixint_encoding().allow_trailing_bytes();
+        let mut deserializer = bincode::Deserializer::from_slice(bytes, options);
+        Ok(TransactionSeed(ChainIdField::Optional).deserialize(&mut deserializer)?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,6 +562,19 @@ mod tests {
         assert_eq!(tx.nonce, nonce);
         assert_eq!(tx.total_fee(), gas_limit * gas_price);
         assert_eq!(tx.status, TransactionStatus::Pending);
+        assert_eq!(tx.contract_address(), None);
+    }
+
+    #[test]
+    fn test_contract_address() {
+        let deploy = Transaction::new_deploy(dummy_address(1), vec![1, 2, 3], vec![], 0, 100_000, 1).unwrap();
+        let again = Transaction::new_deploy(dummy_address(1), vec![4], vec![], 0, 100_000, 1).unwrap();
+        let next = Transaction::new_deploy(dummy_address(1), vec![1, 2, 3], vec![], 1, 100_000, 1).unwrap();
+
+        // Sender and nonce decide the address, not the code
+        assert_eq!(deploy.contract_address(), again.contract_address());
+        assert_ne!(deploy.contract_address(), next.contract_address());
+        assert_ne!(deploy.contract_address(), Some(dummy_address(1)));
     }
 
     #[test]
@@ -323,4 +608,98 @@ mod tests {
         assert_eq!(hash1, hash2);
         assert_eq!(tx.hash, hash1);
     }
+
+    #[test]
+    fn test_encoded_size() {
+        let tx = Transaction::new_transfer(
+            dummy_address(1),
+            dummy_address(2),
+            1000,
+            1,
+            21000,
+            20,
+        ).unwrap();
+
+        let size = tx.encoded_size().unwrap();
+        assert_eq!(size, bincode::serialize(&tx).unwrap().len() as u64);
+
+        let mut signed = tx.clone();
+        signed.signature = vec![0u8; 65];
+        assert_eq!(signed.encoded_size().unwrap(), size + 65);
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut tx = Transaction::new_transfer(dummy_address(1), dummy_address(2), 1000, 1, 21000, 20).unwrap();
+        assert_eq!(tx.signature_scheme(), None);
+
+        let key = KeyPair::generate(SignatureScheme::Ed25519);
+        tx.sign(&key);
+        assert_eq!(tx.signature_scheme(), Some(SignatureScheme::Ed25519));
+        assert_eq!(tx.verify_signature(SignatureScheme::Ed25519).unwrap(), key.public_key());
+
+        // A chain using secp256k1 does not accept ed25519 signatures
+        assert!(tx.verify_signature(SignatureScheme::Secp256k1).is_err());
+
+        tx.nonce += 1;
+        tx.hash = tx.calculate_hash().unwrap();
+        assert!(tx.verify_signature(SignatureScheme::Ed25519).is_err());
+    }
+
+    #[test]
+    fn test_verify_sender() {
+        let key = KeyPair::generate(SignatureScheme::Secp256k1);
+        let from = Address::from_public_key(&key.public_key()).unwrap();
+
+        let mut tx = Transaction::new_transfer(from, dummy_address(2), 1000, 1, 21000, 20).unwrap();
+        tx.sign(&key);
+        assert!(tx.verify_sender(SignatureScheme::Secp256k1).is_ok());
+
+        // Valid signature, wrong key for the sender
+        tx.sign(&KeyPair::generate(SignatureScheme::Secp256k1));
+        assert!(tx.verify_signature(SignatureScheme::Secp256k1).is_ok());
+        assert!(tx.verify_sender(SignatureScheme::Secp256k1).is_err());
+    }
+
+    #[test]
+    fn test_chain_id_replay_protection() {
+        let key = KeyPair::generate(SignatureScheme::Secp256k1);
+        let legacy = Transaction::new_transfer(dummy_address(1), dummy_address(2), 1000, 1, 21000, 20).unwrap();
+        let mut testnet = legacy.clone().with_chain_id(2).unwrap();
+        testnet.sign(&key);
+
+        // The signature is over a hash that commits to the chain
+        let mut replayed = testnet.clone();
+        replayed.chain_id = 1;
+        assert_ne!(testnet.hash, legacy.hash);
+        assert_ne!(replayed.calculate_hash().unwrap(), testnet.hash);
+        assert!(replayed.validate_structure().is_err());
+
+        // Both encode their chain id and round trip it
+        let legacy_bytes = bincode::serialize(&legacy).unwrap();
+        assert_eq!(bincode::serialize(&testnet).unwrap().len(), legacy_bytes.len() + testnet.signature.len());
+        assert_eq!(bincode::deserialize::<Transaction>(&legacy_bytes).unwrap(), legacy);
+        assert_eq!(bincode::deserialize::<Transaction>(&bincode::serialize(&testnet).unwrap()).unwrap(), testnet);
+
+        // Blobs from before chain ids were always encoded left them out of legacy transactions
+        let old_legacy = &legacy_bytes[..legacy_bytes.len() - 8];
+        assert_eq!(Transaction::decode_legacy(old_legacy).unwrap(), legacy);
+        let old_testnet = bincode::serialize(&testnet).unwrap();
+        assert_eq!(Transaction::decode_legacy(&old_testnet).unwrap(), testnet);
+
+        let json = serde_json::to_string(&testnet).unwrap();
+        assert_eq!(serde_json::from_str::<Transaction>(&json).unwrap(), testnet);
+    }
+
+    #[test]
+    fn test_transaction_list_roundtrip() {
+        let legacy = Transaction::new_transfer(dummy_address(1), dummy_address(2), 1000, 1, 21000, 20).unwrap();
+        let testnet = legacy.clone().with_chain_id(2).unwrap();
+        let transactions = vec![legacy.clone(), testnet.clone(), legacy, testnet];
+
+        let encoded = bincode::serialize(&transactions).unwrap();
+        assert_eq!(bincode::deserialize::<Vec<Transaction>>(&encoded).unwrap(), transactions);
+        let json = serde_json::to_string(&transactions).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Transaction>>(&json).unwrap(), transactions);
+    }
 }
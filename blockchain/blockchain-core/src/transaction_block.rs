@@ -1,10 +1,51 @@
 // core/blockchain-core/src/block.rs
-use crate::{Transaction, BlockHash, TxHash, BlockHeight, Result, hash_serializable, BlockchainError};
+use crate::{Address, Bloom, Transaction, TransactionType, BlockHash, TxHash, BlockHeight, Result, hash_serializable, BlockchainError, LEGACY_CHAIN_ID};
+use crate::clock::{Clock, SystemClock};
+use crate::execution::AccountProof;
+use crate::fees::FeeDistribution;
+use crate::transaction::{ChainIdField, TransactionEncoding, TransactionSeed};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 
-/// Block header containing metadata
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Default upper bound on a serialized block, matching `max_block_size` in system_config
+pub const MAX_BLOCK_SIZE: u64 = 1_048_576;
+
+/// How far ahead of the local clock a block timestamp may be
+pub const MAX_FUTURE_BLOCK_SECS: i64 = 600;
+
+/// Header version written by this build
+pub const BLOCK_VERSION: u32 = 3;
+
+/// First header version carrying an address bloom
+pub const BLOOM_HEADER_VERSION: u32 = 2;
+
+/// First block version whose transactions carry a chain id
+pub const CHAIN_ID_BLOCK_VERSION: u32 = 3;
+
+/// First header version carrying a proposer signature, written by proof-of-authority producers
+pub const SEALED_HEADER_VERSION: u32 = 4;
+
+/// First header version committing to the account state after the block.
+/// Such headers also carry the proposer signature field, empty when unsealed.
+pub const STATE_ROOT_HEADER_VERSION: u32 = 5;
+
+/// First header version carrying the randomness beacon seed; such headers
+/// also carry every field of version 5.
+pub const RANDOM_SEED_HEADER_VERSION: u32 = 6;
+
+/// First header version carrying the proposer's VRF proof, written by
+/// proof-of-stake producers; such headers also carry every field of version 6.
+pub const VRF_HEADER_VERSION: u32 = 7;
+
+/// Block header containing metadata.
+///
+/// Fields added after version 1 are only encoded (and hashed) for headers
+/// of the version that introduced them, so older blocks keep their bytes
+/// and hashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BlockHeader {
     /// Block height/index in the chain
     pub height: BlockHeight,
@@ -20,10 +61,229 @@ pub struct BlockHeader {
     pub difficulty: u32,
     /// Version of the block format
     pub version: u32,
+    /// Bloom of addresses touched by the block's transactions; `None` before version 2
+    pub bloom: Option<Bloom>,
+    /// Proposer's signature over `seal_hash`; `None` before version 4
+    pub proposer_signature: Option<Vec<u8>>,
+    /// `Ledger::state_root` after applying the block; required from version 5,
+    /// `None` before
+    pub state_root: Option<BlockHash>,
+    /// `beacon::seed_for` the block and its parent; `None` before version 6
+    pub random_seed: Option<BlockHash>,
+    /// Proposer's `vrf::prove` output for its slot, empty when not proof of
+    /// stake; `None` before version 7
+    pub vrf_proof: Option<Vec<u8>>,
+}
+
+impl BlockHeader {
+    /// Hash the proposer signs: the header with an empty signature
+    pub fn seal_hash(&self) -> Result<BlockHash> {
+        let mut unsealed = self.clone();
+        unsealed.proposer_signature = Some(Vec::new());
+        hash_serializable(&unsealed)
+    }
+
+    /// Whether the block may involve `address`; always true for headers without a bloom
+    pub fn may_contain_address(&self, address: &Address) -> bool {
+        match &self.bloom {
+            Some(bloom) => bloom.contains_address(address),
+            None => true,
+        }
+    }
+
+    /// Whether `proof` shows an account's state after this block; always
+    /// false for headers without a state root
+    pub fn verify_account(&self, proof: &AccountProof) -> Result<bool> {
+        match &self.state_root {
+            Some(state_root) => proof.verify(state_root),
+            None => Ok(false),
+        }
+    }
+}
+
+const HEADER_FIELDS: [&str; 12] = [
+    "height",
+    "previous_hash",
+    "merkle_root",
+    "timestamp",
+    "nonce",
+    "difficulty",
+    "version",
+    "bloom",
+    "proposer_signature",
+    "state_root",
+    "random_seed",
+    "vrf_proof",
+];
+
+impl Serialize for BlockHeader {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let has_bloom = self.version >= BLOOM_HEADER_VERSION;
+        let sealed = self.version >= SEALED_HEADER_VERSION;
+        let has_state_root = self.version >= STATE_ROOT_HEADER_VERSION;
+        let has_random_seed = self.version >= RANDOM_SEED_HEADER_VERSION;
+        let has_vrf_proof = self.version >= VRF_HEADER_VERSION;
+        let len = 7
+            + has_bloom as usize
+            + sealed as usize
+            + has_state_root as usize
+            + has_random_seed as usize
+            + has_vrf_proof as usize;
+        let mut state = serializer.serialize_struct("BlockHeader", len)?;
+        state.serialize_field("height", &self.height)?;
+        state.serialize_field("previous_hash", &self.previous_hash)?;
+        state.serialize_field("merkle_root", &self.merkle_root)?;
+        state.serialize_field("timestamp", &self.timestamp)?;
+        state.serialize_field("nonce", &self.nonce)?;
+        state.serialize_field("difficulty", &self.difficulty)?;
+        state.serialize_field("version", &self.version)?;
+        if has_bloom {
+            state.serialize_field("bloom", &self.bloom.clone().unwrap_or_default())?;
+        }
+        if sealed {
+            state.serialize_field("proposer_signature", self.proposer_signature.as_deref().unwrap_or_default())?;
+        }
+        if has_state_root {
+            state.serialize_field("state_root", &self.state_root.unwrap_or_default())?;
+        }
+        if has_random_seed {
+            state.serialize_field("random_seed", &self.random_seed.unwrap_or_default())?;
+        }
+        if has_vrf_proof {
+            state.serialize_field("vrf_proof", self.vrf_proof.as_deref().unwrap_or_default())?;
+        }
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockHeader {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct HeaderVisitor;
+
+        impl<'de> Visitor<'de> for HeaderVisitor {
+            type Value = BlockHeader;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a block header")
+            }
+
+            // Positional formats (bincode): the version decides which later fields follow
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<BlockHeader, A::Error> {
+                let missing = |i: usize| <A::Error as de::Error>::invalid_length(i, &self);
+                let height = seq.next_element()?.ok_or_else(|| missing(0))?;
+                let previous_hash = seq.next_element()?.ok_or_else(|| missing(1))?;
+                let merkle_root = seq.next_element()?.ok_or_else(|| missing(2))?;
+                let timestamp = seq.next_element()?.ok_or_else(|| missing(3))?;
+                let nonce = seq.next_element()?.ok_or_else(|| missing(4))?;
+                let difficulty = seq.next_element()?.ok_or_else(|| missing(5))?;
+                let version: u32 = seq.next_element()?.ok_or_else(|| missing(6))?;
+                let bloom = if version >= BLOOM_HEADER_VERSION {
+                    Some(seq.next_element()?.ok_or_else(|| missing(7))?)
+                } else {
+                    None
+                };
+                let proposer_signature = if version >= SEALED_HEADER_VERSION {
+                    Some(seq.next_element()?.ok_or_else(|| missing(8))?)
+                } else {
+                    None
+                };
+                let state_root = if version >= STATE_ROOT_HEADER_VERSION {
+                    Some(seq.next_element()?.ok_or_else(|| missing(9))?)
+                } else {
+                    None
+                };
+                let random_seed = if version >= RANDOM_SEED_HEADER_VERSION {
+                    Some(seq.next_element()?.ok_or_else(|| missing(10))?)
+                } else {
+                    None
+                };
+                let vrf_proof = if version >= VRF_HEADER_VERSION {
+                    Some(seq.next_element()?.ok_or_else(|| missing(11))?)
+                } else {
+                    None
+                };
+
+                Ok(BlockHeader {
+                    height,
+                    previous_hash,
+                    merkle_root,
+                    timestamp,
+                    nonce,
+                    difficulty,
+                    version,
+                    bloom,
+                    proposer_signature,
+                    state_root,
+                    random_seed,
+                    vrf_proof,
+                })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<BlockHeader, A::Error> {
+                let (mut height, mut previous_hash, mut merkle_root, mut timestamp) = (None, None, None, None);
+                let (mut nonce, mut difficulty, mut version, mut bloom) = (None, None, None, None);
+                let (mut proposer_signature, mut state_root, mut random_seed, mut vrf_proof) = (None, None, None, None);
+
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "height" => height = Some(map.next_value()?),
+                        "previous_hash" => previous_hash = Some(map.next_value()?),
+                        "merkle_root" => merkle_root = Some(map.next_value()?),
+                        "timestamp" => timestamp = Some(map.next_value()?),
+                        "nonce" => nonce = Some(map.next_value()?),
+                        "difficulty" => difficulty = Some(map.next_value()?),
+                        "version" => version = Some(map.next_value()?),
+                        "bloom" => bloom = map.next_value()?,
+                        "proposer_signature" => proposer_signature = map.next_value()?,
+                        "state_root" => state_root = map.next_value()?,
+                        "random_seed" => random_seed = map.next_value()?,
+                        "vrf_proof" => vrf_proof = map.next_value()?,
+                        _ => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                let version: u32 = version.ok_or_else(|| de::Error::missing_field("version"))?;
+                if version >= BLOOM_HEADER_VERSION && bloom.is_none() {
+                    return Err(de::Error::missing_field("bloom"));
+                }
+                if version >= SEALED_HEADER_VERSION && proposer_signature.is_none() {
+                    return Err(de::Error::missing_field("proposer_signature"));
+                }
+                if version >= STATE_ROOT_HEADER_VERSION && state_root.is_none() {
+                    return Err(de::Error::missing_field("state_root"));
+                }
+                if version >= RANDOM_SEED_HEADER_VERSION && random_seed.is_none() {
+                    return Err(de::Error::missing_field("random_seed"));
+                }
+                if version >= VRF_HEADER_VERSION && vrf_proof.is_none() {
+                    return Err(de::Error::missing_field("vrf_proof"));
+                }
+
+                Ok(BlockHeader {
+                    height: height.ok_or_else(|| de::Error::missing_field("height"))?,
+                    previous_hash: previous_hash.ok_or_else(|| de::Error::missing_field("previous_hash"))?,
+                    merkle_root: merkle_root.ok_or_else(|| de::Error::missing_field("merkle_root"))?,
+                    timestamp: timestamp.ok_or_else(|| de::Error::missing_field("timestamp"))?,
+                    nonce: nonce.ok_or_else(|| de::Error::missing_field("nonce"))?,
+                    difficulty: difficulty.ok_or_else(|| de::Error::missing_field("difficulty"))?,
+                    version,
+                    bloom: if version >= BLOOM_HEADER_VERSION { bloom } else { None },
+                    proposer_signature: if version >= SEALED_HEADER_VERSION { proposer_signature } else { None },
+                    state_root: if version >= STATE_ROOT_HEADER_VERSION { state_root } else { None },
+                    random_seed: if version >= RANDOM_SEED_HEADER_VERSION { random_seed } else { None },
+                    vrf_proof: if version >= VRF_HEADER_VERSION { vrf_proof } else { None },
+                })
+            }
+        }
+
+        deserializer.deserialize_struct("BlockHeader", &HEADER_FIELDS, HeaderVisitor)
+    }
 }
 
 /// Complete block with header and transactions
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Block {
     /// Block hash (calculated from header)
     pub hash: BlockHash,
@@ -37,6 +297,125 @@ pub struct Block {
     pub size: u64,
 }
 
+/// Chain id rule for the transactions of a block of `version`
+fn chain_id_field(version: u32) -> ChainIdField {
+    if version >= CHAIN_ID_BLOCK_VERSION {
+        ChainIdField::Required
+    } else {
+        ChainIdField::Absent
+    }
+}
+
+struct TransactionList<'a>(&'a [Transaction], ChainIdField);
+
+impl Serialize for TransactionList<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|tx| TransactionEncoding(tx, self.1)))
+    }
+}
+
+struct TransactionListSeed(ChainIdField);
+
+impl<'de> DeserializeSeed<'de> for TransactionListSeed {
+    type Value = Vec<Transaction>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for TransactionListSeed {
+    type Value = Vec<Transaction>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of transactions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+        let mut transactions = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(tx) = seq.next_element_seed(TransactionSeed(self.0))? {
+            transactions.push(tx);
+        }
+        Ok(transactions)
+    }
+}
+
+const BLOCK_FIELDS: [&str; 5] = ["hash", "header", "transactions", "transaction_count", "size"];
+
+impl Serialize for Block {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Block", 5)?;
+        state.serialize_field("hash", &self.hash)?;
+        state.serialize_field("header", &self.header)?;
+        state.serialize_field(
+            "transactions",
+            &TransactionList(&self.transactions, chain_id_field(self.header.version)),
+        )?;
+        state.serialize_field("transaction_count", &self.transaction_count)?;
+        state.serialize_field("size", &self.size)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Block {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct BlockVisitor;
+
+        impl<'de> Visitor<'de> for BlockVisitor {
+            type Value = Block;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a block")
+            }
+
+            // Positional formats: the header version decides how transactions are laid out
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Block, A::Error> {
+                let missing = |i: usize| <A::Error as de::Error>::invalid_length(i, &self);
+                let hash = seq.next_element()?.ok_or_else(|| missing(0))?;
+                let header: BlockHeader = seq.next_element()?.ok_or_else(|| missing(1))?;
+                let transactions = seq
+                    .next_element_seed(TransactionListSeed(chain_id_field(header.version)))?
+                    .ok_or_else(|| missing(2))?;
+                let transaction_count = seq.next_element()?.ok_or_else(|| missing(3))?;
+                let size = seq.next_element()?.ok_or_else(|| missing(4))?;
+
+                Ok(Block { hash, header, transactions, transaction_count, size })
+            }
+
+            // Self-describing formats name the chain id field, so every layout decodes
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Block, A::Error> {
+                let (mut hash, mut header, mut transactions, mut transaction_count, mut size) =
+                    (None, None, None, None, None);
+
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "hash" => hash = Some(map.next_value()?),
+                        "header" => header = Some(map.next_value()?),
+                        "transactions" => {
+                            transactions = Some(map.next_value_seed(TransactionListSeed(ChainIdField::Optional))?)
+                        }
+                        "transaction_count" => transaction_count = Some(map.next_value()?),
+                        "size" => size = Some(map.next_value()?),
+                        _ => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                Ok(Block {
+                    hash: hash.ok_or_else(|| de::Error::missing_field("hash"))?,
+                    header: header.ok_or_else(|| de::Error::missing_field("header"))?,
+                    transactions: transactions.ok_or_else(|| de::Error::missing_field("transactions"))?,
+                    transaction_count: transaction_count.ok_or_else(|| de::Error::missing_field("transaction_count"))?,
+                    size: size.ok_or_else(|| de::Error::missing_field("size"))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_struct("Block", &BLOCK_FIELDS, BlockVisitor)
+    }
+}
+
 impl Block {
     /// Create a new block
     pub fn new(
@@ -50,6 +429,7 @@ impl Block {
         
         // Calculate merkle root from transactions
         let merkle_root = Self::calculate_merkle_root(&transactions)?;
+        let bloom = Bloom::from_transactions(&transactions);
         
         let header = BlockHeader {
             height,
@@ -58,7 +438,12 @@ impl Block {
             timestamp,
             nonce: 0, // Will be set during mining
             difficulty,
-            version: 1,
+            version: BLOCK_VERSION,
+            bloom: Some(bloom),
+            proposer_signature: None,
+            state_root: None,
+            random_seed: None,
+            vrf_proof: None,
         };
 
         let mut block = Block {
@@ -92,52 +477,43 @@ impl Block {
 
     /// Calculate merkle root of transactions
     fn calculate_merkle_root(transactions: &[Transaction]) -> Result<TxHash> {
-        if transactions.is_empty() {
-            return Ok([0u8; 32]); // Empty merkle root
-        }
+        let hashes: Vec<TxHash> = transactions.iter().map(|tx| tx.hash).collect();
+        Ok(crate::merkle_root(&hashes))
+    }
 
-        let mut hashes: Vec<TxHash> = transactions
-            .iter()
-            .map(|tx| tx.hash)
-            .collect();
-
-        // Build merkle tree bottom-up
-        while hashes.len() > 1 {
-            let mut next_level = Vec::new();
-            
-            for chunk in hashes.chunks(2) {
-                let combined = if chunk.len() == 2 {
-                    // Combine two hashes
-                    let mut combined_data = Vec::new();
-                    combined_data.extend_from_slice(&chunk[0]);
-                    combined_data.extend_from_slice(&chunk[1]);
-                    combined_data
-                } else {
-                    // Odd number - duplicate the last hash
-                    let mut combined_data = Vec::new();
-                    combined_data.extend_from_slice(&chunk[0]);
-                    combined_data.extend_from_slice(&chunk[0]);
-                    combined_data
-                };
-                
-                let parent_hash = crate::hash_data(&combined_data);
-                next_level.push(parent_hash);
-            }
-            
-            hashes = next_level;
-        }
+    /// Calculate the size of the block in bytes
+    pub(crate) fn calculate_size(&self) -> Result<u64> {
+        Ok(bincode::serialized_size(self)?)
+    }
 
-        Ok(hashes[0])
+    /// Check the recorded size against the encoding and a size limit
+    pub fn validate_size(&self, max_size: u64) -> Result<()> {
+        if self.calculate_size()? != self.size {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: "Block size mismatch".to_string(),
+            });
+        }
+        if self.size > max_size {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: format!("Block size {} exceeds limit {}", self.size, max_size),
+            });
+        }
+        Ok(())
     }
 
-    /// Calculate the size of the block in bytes
-    fn calculate_size(&self) -> Result<u64> {
-        let serialized = bincode::serialize(self)?;
-        Ok(serialized.len() as u64)
+    /// Whether a transaction of `tx_size` bytes still fits under `max_size`
+    pub fn has_room_for(&self, tx_size: u64, max_size: u64) -> bool {
+        self.size.saturating_add(tx_size) <= max_size
     }
 
-    /// Validate the block structure and contents
+    /// Validate the block structure and contents against the system clock
     pub fn validate(&self) -> Result<()> {
+        self.validate_with_clock(&SystemClock)
+    }
+
+    /// `validate`, judging how far in the future the timestamp may be by
+    /// `clock`, widened by the clock's uncertainty
+    pub fn validate_with_clock(&self, clock: &dyn Clock) -> Result<()> {
         // Validate header hash
         let calculated_hash = self.calculate_hash()?;
         if calculated_hash != self.hash {
@@ -154,6 +530,33 @@ impl Block {
             });
         }
 
+        if self.header.version >= BLOOM_HEADER_VERSION
+            && self.header.bloom.as_ref() != Some(&Bloom::from_transactions(&self.transactions))
+        {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: "Bloom mismatch".to_string(),
+            });
+        }
+
+        // The header would encode a missing root as all zeros, which no
+        // ledger has; the block would commit to nothing
+        if self.header.version >= STATE_ROOT_HEADER_VERSION && self.header.state_root.is_none() {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: format!("Version {} headers must carry a state root", self.header.version),
+            });
+        }
+
+        // Older layouts have nowhere to encode a chain id
+        if self.header.version < CHAIN_ID_BLOCK_VERSION
+            && self.transactions.iter().any(|tx| tx.chain_id != LEGACY_CHAIN_ID)
+        {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: format!("Version {} blocks cannot carry chain-bound transactions", self.header.version),
+            });
+        }
+
+        self.validate_size(MAX_BLOCK_SIZE)?;
+
         // Validate transaction count
         if self.transaction_count != self.transactions.len() as u32 {
             return Err(BlockchainError::BlockValidationFailed {
@@ -161,14 +564,24 @@ impl Block {
             });
         }
 
+        // A coinbase may only lead the block, and only for its own height
+        for (index, tx) in self.transactions.iter().enumerate() {
+            if let TransactionType::Coinbase { height, .. } = tx.tx_type {
+                if index != 0 || height != self.header.height {
+                    return Err(BlockchainError::BlockValidationFailed {
+                        reason: "Coinbase must be the first transaction and match the block height".to_string(),
+                    });
+                }
+            }
+        }
+
         // Validate each transaction
         for tx in &self.transactions {
             tx.validate_structure()?;
         }
 
         // Validate timestamp (should not be too far in the future)
-        let now = Utc::now();
-        let max_future = now + chrono::Duration::minutes(10);
+        let max_future = clock.now() + chrono::Duration::seconds(MAX_FUTURE_BLOCK_SECS) + clock.uncertainty();
         if self.header.timestamp > max_future {
             return Err(BlockchainError::BlockValidationFailed {
                 reason: "Block timestamp too far in future".to_string(),
@@ -218,6 +631,21 @@ impl Block {
         self.transactions.iter().map(|tx| tx.total_fee()).sum()
     }
 
+    /// Priority tips this block's transactions pay its proposer under `fees`
+    pub fn total_tips(&self, fees: &FeeDistribution) -> u64 {
+        self.transactions.iter().map(|tx| fees.split_transaction(tx).tip).sum()
+    }
+
+    /// The leading coinbase transaction, if the block has one
+    pub fn coinbase(&self) -> Option<&Transaction> {
+        self.transactions.first().filter(|tx| tx.is_coinbase())
+    }
+
+    /// Whether any transaction may involve `address`, without scanning them
+    pub fn may_contain_address(&self, address: &Address) -> bool {
+        self.header.may_contain_address(address)
+    }
+
     /// Check if block contains a specific transaction
     pub fn contains_transaction(&self, tx_hash: &TxHash) -> bool {
         self.transactions.iter().any(|tx| &tx.hash == tx_hash)
@@ -228,6 +656,43 @@ impl Block {
         self.transactions.iter().find(|tx| &tx.hash == tx_hash)
     }
 
+    /// Commit the block to `state_root`, the account state after applying it,
+    /// raising the header to `STATE_ROOT_HEADER_VERSION`. Producers call this
+    /// before sealing, since the seal covers the root.
+    pub fn with_state_root(mut self, state_root: BlockHash) -> Result<Self> {
+        self.header.version = self.header.version.max(STATE_ROOT_HEADER_VERSION);
+        self.header.proposer_signature.get_or_insert_with(Vec::new);
+        self.header.state_root = Some(state_root);
+        self.hash = self.calculate_hash()?;
+        self.size = self.calculate_size()?;
+        Ok(self)
+    }
+
+    /// Carry `random_seed`, the beacon seed for this block, raising the header
+    /// to `RANDOM_SEED_HEADER_VERSION`. Such headers encode a state root, so
+    /// `with_state_root` must come first; both precede sealing.
+    pub fn with_random_seed(mut self, random_seed: BlockHash) -> Result<Self> {
+        if self.header.state_root.is_none() {
+            return Err(BlockchainError::BlockValidationFailed {
+                reason: "A random seed needs the state root committed first".to_string(),
+            });
+        }
+        self.header.version = self.header.version.max(RANDOM_SEED_HEADER_VERSION);
+        self.header.random_seed = Some(random_seed);
+        self.hash = self.calculate_hash()?;
+        self.size = self.calculate_size()?;
+        Ok(self)
+    }
+
+    /// Stamp the block with a time taken from a `Clock`, recomputing its hash
+    /// and size, since the encoded timestamp's length varies
+    pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Result<Self> {
+        self.header.timestamp = timestamp;
+        self.hash = self.calculate_hash()?;
+        self.size = self.calculate_size()?;
+        Ok(self)
+    }
+
     /// Set nonce (typically used during mining)
     pub fn set_nonce(&mut self, nonce: u64) -> Result<()> {
         self.header.nonce = nonce;
@@ -324,4 +789,85 @@ mod tests {
         let merkle_root = Block::calculate_merkle_root(&[]).unwrap();
         assert_eq!(merkle_root, [0u8; 32]);
     }
+
+    #[test]
+    fn test_block_size_limit() {
+        let tx = Transaction::new_transfer(
+            dummy_address(1),
+            dummy_address(2),
+            1000,
+            1,
+            21000,
+            20,
+        ).unwrap();
+        let tx_size = tx.encoded_size().unwrap();
+
+        let block = Block::new(1, [1u8; 32], vec![tx], 1000).unwrap();
+        assert!(block.validate_size(MAX_BLOCK_SIZE).is_ok());
+        assert!(block.validate_size(block.size - 1).is_err());
+        assert!(block.has_room_for(tx_size, block.size + tx_size));
+        assert!(!block.has_room_for(tx_size, block.size + tx_size - 1));
+
+        let mut tampered = block.clone();
+        tampered.size += 1;
+        assert!(tampered.validate_size(MAX_BLOCK_SIZE).is_err());
+    }
+
+    #[test]
+    fn test_block_bloom() {
+        let tx = Transaction::new_transfer(dummy_address(1), dummy_address(2), 100, 1, 21000, 20).unwrap();
+        let block = Block::new(1, [0u8; 32], vec![tx], 1).unwrap();
+
+        assert_eq!(block.header.version, BLOCK_VERSION);
+        assert!(block.may_contain_address(&dummy_address(1)));
+        assert!(!block.may_contain_address(&dummy_address(9)));
+        block.validate().unwrap();
+
+        let decoded: Block = bincode::deserialize(&bincode::serialize(&block).unwrap()).unwrap();
+        assert_eq!(decoded, block);
+        let json: Block = serde_json::from_str(&serde_json::to_string(&block).unwrap()).unwrap();
+        assert_eq!(json, block);
+
+        let mut tampered = block.clone();
+        tampered.header.bloom = Some(Bloom::default());
+        tampered.hash = tampered.calculate_hash().unwrap();
+        assert!(tampered.validate().is_err());
+
+        // Version 1 headers carry no bloom and match every address
+        let mut legacy = block.header.clone();
+        legacy.version = 1;
+        legacy.bloom = None;
+        assert!(legacy.may_contain_address(&dummy_address(9)));
+    }
+
+    #[test]
+    fn test_block_chain_ids() {
+        let tx = Transaction::new_transfer(dummy_address(1), dummy_address(2), 100, 1, 21000, 20).unwrap();
+        let bound = tx.clone().with_chain_id(2).unwrap();
+
+        // Version 3 blocks encode a chain id for every transaction, legacy or not
+        let block = Block::new(1, [0u8; 32], vec![tx, bound], 1).unwrap();
+        assert_eq!(block.header.version, CHAIN_ID_BLOCK_VERSION);
+        block.validate().unwrap();
+        let decoded: Block = bincode::deserialize(&bincode::serialize(&block).unwrap()).unwrap();
+        assert_eq!(decoded, block);
+
+        let mut old = block.clone();
+        old.header.version = BLOOM_HEADER_VERSION;
+        old.size = bincode::serialized_size(&old).unwrap();
+        old.hash = old.calculate_hash().unwrap();
+        assert!(old.validate().is_err());
+    }
+
+    #[test]
+    fn test_state_root_required_from_version_5() {
+        let block = Block::new(1, [0u8; 32], vec![], 1).unwrap().with_state_root([1; 32]).unwrap();
+        block.validate().unwrap();
+
+        let mut missing = block;
+        missing.header.state_root = None;
+        missing.hash = missing.calculate_hash().unwrap();
+        let err = missing.validate().unwrap_err();
+        assert!(err.to_string().contains("must carry a state root"), "{}", err);
+    }
 }
@@ -0,0 +1,229 @@
+// core/blockchain-core/src/vrf.rs
+//! Verifiable random function: ECVRF-EDWARDS25519-SHA512-TAI (RFC 9381).
+//!
+//! A VRF key is an ed25519 secret seed and its public key is the matching
+//! ed25519 public key, so a validator can reuse its ed25519 seed. Proving an
+//! input gives an 80-byte proof; anyone with the public key can check it and
+//! derive the output, which is unique per key and input, so the prover can
+//! neither choose it nor try several. `VrfOutput` is the first half of the
+//! RFC's 64-byte output.
+use crate::beacon::VrfOutput;
+use crate::{BlockchainError, Result};
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha512};
+use std::fmt;
+
+/// Length of an encoded proof: Gamma, the challenge and the response
+pub const PROOF_LEN: usize = 80;
+
+/// Encoded ed25519 public key proofs verify against
+pub type VrfPublicKey = [u8; 32];
+
+const SUITE: u8 = 0x03;
+const CHALLENGE_LEN: usize = 16;
+
+/// Secret key proving VRF outputs
+#[derive(Clone)]
+pub struct VrfSecretKey {
+    seed: [u8; 32],
+    scalar: Scalar,
+    public_key: VrfPublicKey,
+}
+
+impl VrfSecretKey {
+    /// Generate a random key
+    pub fn generate() -> Self {
+        Self::from_bytes(&rand::random())
+    }
+
+    /// Restore a key from its 32-byte ed25519 seed
+    pub fn from_bytes(seed: &[u8; 32]) -> Self {
+        let hashed = Sha512::digest(seed);
+        let mut scalar_bytes = [0u8; 32];
+        scalar_bytes.copy_from_slice(&hashed[..32]);
+        scalar_bytes[0] &= 248;
+        scalar_bytes[31] &= 127;
+        scalar_bytes[31] |= 64;
+        let scalar = Scalar::from_bytes_mod_order(scalar_bytes);
+        let public_key = (ED25519_BASEPOINT_POINT * scalar).compress().to_bytes();
+        VrfSecretKey { seed: *seed, scalar, public_key }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    pub fn public_key(&self) -> VrfPublicKey {
+        self.public_key
+    }
+
+    /// Prove the output for `alpha`
+    pub fn prove(&self, alpha: &[u8]) -> Result<Vec<u8>> {
+        let h = encode_to_curve(&self.public_key, alpha)?;
+        let gamma = h * self.scalar;
+        // Deterministic nonce, as ed25519 derives it
+        let mut nonce = Sha512::new();
+        nonce.update(&Sha512::digest(self.seed)[32..]);
+        nonce.update(h.compress().as_bytes());
+        let k = wide_scalar(&nonce.finalize());
+        let c = challenge(&self.public_key, [&h, &gamma, &(ED25519_BASEPOINT_POINT * k), &(h * k)]);
+        let s = k + c * self.scalar;
+
+        let mut proof = Vec::with_capacity(PROOF_LEN);
+        proof.extend_from_slice(gamma.compress().as_bytes());
+        proof.extend_from_slice(&c.as_bytes()[..CHALLENGE_LEN]);
+        proof.extend_from_slice(s.as_bytes());
+        Ok(proof)
+    }
+}
+
+impl fmt::Debug for VrfSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret material
+        write!(f, "VrfSecretKey({})", hex::encode(self.public_key))
+    }
+}
+
+/// Check `proof` for `alpha` under `public_key` and return its output
+pub fn verify(public_key: &VrfPublicKey, alpha: &[u8], proof: &[u8]) -> Result<VrfOutput> {
+    let y = CompressedEdwardsY(*public_key)
+        .decompress()
+        .filter(|y| !y.is_small_order())
+        .ok_or_else(|| invalid("Malformed VRF public key".to_string()))?;
+    let (gamma, c, s) = decode_proof(proof)?;
+    let h = encode_to_curve(public_key, alpha)?;
+
+    // U = s*B - c*Y and V = s*H - c*Gamma recompute the prover's commitments
+    let u = EdwardsPoint::vartime_double_scalar_mul_basepoint(&-c, &y, &s);
+    let v = h * s - gamma * c;
+    if challenge(public_key, [&h, &gamma, &u, &v]) != c {
+        return Err(invalid("VRF proof does not verify".to_string()));
+    }
+    Ok(output(&gamma))
+}
+
+/// Output of `proof` without checking it; only meaningful once `verify` has
+/// passed for the same proof
+pub fn proof_to_output(proof: &[u8]) -> Result<VrfOutput> {
+    decode_proof(proof).map(|(gamma, _, _)| output(&gamma))
+}
+
+/// Hash `alpha` onto the curve by try-and-increment
+fn encode_to_curve(public_key: &VrfPublicKey, alpha: &[u8]) -> Result<EdwardsPoint> {
+    for counter in 0..=u8::MAX {
+        let mut hasher = Sha512::new();
+        hasher.update([SUITE, 0x01]);
+        hasher.update(public_key);
+        hasher.update(alpha);
+        hasher.update([counter, 0x00]);
+        let mut candidate = [0u8; 32];
+        candidate.copy_from_slice(&hasher.finalize()[..32]);
+        if let Some(point) = CompressedEdwardsY(candidate).decompress() {
+            return Ok(point.mul_by_cofactor());
+        }
+    }
+    Err(invalid("No curve point for VRF input".to_string()))
+}
+
+fn challenge(public_key: &VrfPublicKey, points: [&EdwardsPoint; 4]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update([SUITE, 0x02]);
+    hasher.update(public_key);
+    for point in points {
+        hasher.update(point.compress().as_bytes());
+    }
+    hasher.update([0x00]);
+    let mut c = [0u8; 32];
+    c[..CHALLENGE_LEN].copy_from_slice(&hasher.finalize()[..CHALLENGE_LEN]);
+    Scalar::from_bytes_mod_order(c)
+}
+
+fn decode_proof(proof: &[u8]) -> Result<(EdwardsPoint, Scalar, Scalar)> {
+    if proof.len() != PROOF_LEN {
+        return Err(invalid(format!("Expected a {}-byte VRF proof, got {} bytes", PROOF_LEN, proof.len())));
+    }
+    let mut gamma = [0u8; 32];
+    gamma.copy_from_slice(&proof[..32]);
+    let gamma = CompressedEdwardsY(gamma)
+        .decompress()
+        .ok_or_else(|| invalid("Malformed VRF proof point".to_string()))?;
+
+    let mut c = [0u8; 32];
+    c[..CHALLENGE_LEN].copy_from_slice(&proof[32..32 + CHALLENGE_LEN]);
+    let mut s = [0u8; 32];
+    s.copy_from_slice(&proof[32 + CHALLENGE_LEN..]);
+    let s = Option::<Scalar>::from(Scalar::from_canonical_bytes(s))
+        .ok_or_else(|| invalid("Non-canonical VRF proof scalar".to_string()))?;
+    Ok((gamma, Scalar::from_bytes_mod_order(c), s))
+}
+
+fn output(gamma: &EdwardsPoint) -> VrfOutput {
+    let mut hasher = Sha512::new();
+    hasher.update([SUITE, 0x03]);
+    hasher.update(gamma.mul_by_cofactor().compress().as_bytes());
+    hasher.update([0x00]);
+    let mut output = [0u8; 32];
+    output.copy_from_slice(&hasher.finalize()[..32]);
+    output
+}
+
+fn wide_scalar(hash: &[u8]) -> Scalar {
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(hash);
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+fn invalid(reason: String) -> BlockchainError {
+    BlockchainError::InvalidSignature { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyPair, SignatureScheme};
+
+    fn hex_bytes(s: &str) -> Vec<u8> {
+        hex::decode(s).unwrap()
+    }
+
+    #[test]
+    fn test_rfc9381_vector_and_tampering() {
+        // RFC 9381, appendix B.3, example 16
+        let seed = hex_bytes("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60");
+        let key = VrfSecretKey::from_bytes(&seed.clone().try_into().unwrap());
+        assert_eq!(
+            key.public_key().to_vec(),
+            hex_bytes("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+        );
+        let ed25519 = KeyPair::from_secret_bytes(SignatureScheme::Ed25519, &seed).unwrap();
+        assert_eq!(ed25519.public_key(), key.public_key().to_vec());
+
+        let proof = key.prove(b"").unwrap();
+        assert_eq!(
+            proof,
+            hex_bytes(concat!(
+                "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f",
+                "26f8a57ccaed74ee1b190bed1f479d97",
+                "27d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805"
+            ))
+        );
+        let output = verify(&key.public_key(), b"", &proof).unwrap();
+        assert_eq!(output.to_vec(), hex_bytes("90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff"));
+        assert_eq!(proof_to_output(&proof).unwrap(), output);
+
+        // Another input, another key or a flipped bit no longer verify
+        assert!(verify(&key.public_key(), b"other", &proof).is_err());
+        assert!(verify(&VrfSecretKey::generate().public_key(), b"", &proof).is_err());
+        for byte in [0, 40, 60] {
+            let mut tampered = proof.clone();
+            tampered[byte] ^= 1;
+            assert!(verify(&key.public_key(), b"", &tampered).is_err());
+        }
+        assert!(verify(&key.public_key(), b"", &proof[..PROOF_LEN - 1]).is_err());
+
+        let other = key.prove(b"other").unwrap();
+        assert_ne!(verify(&key.public_key(), b"other", &other).unwrap(), output);
+    }
+}
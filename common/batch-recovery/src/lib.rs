@@ -0,0 +1,168 @@
+// common/batch-recovery/src/lib.rs
+//! Returning batches stranded by a crashed worker to their queue.
+//!
+//! Validators and relayers both claim a queued batch, move it to
+//! `Processing` and work on it. A worker that dies or hangs mid-batch
+//! leaves it there, and workers only claim queued batches. Each pass of
+//! `BatchRecovery` asks the stage's `StuckBatchStore` for batches that have
+//! been processing longer than `stuck_after` and has it put each one back,
+//! which the store does only while the batch is still where it was found.
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Storage of one stage's batches, as recovery sees it
+#[async_trait]
+pub trait StuckBatchStore: Send + Sync {
+    type Batch: Send;
+
+    /// Stage name used in logs
+    const STAGE: &'static str;
+
+    /// Up to `limit` batches processing since before `stuck_since`
+    async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<Self::Batch>>;
+
+    /// Return a batch `stuck_batches` found to the queue, counting the
+    /// recovery, unless it moved on since; the batch as now stored, or
+    /// `None` if it was left alone
+    async fn recover_batch(&self, batch: Self::Batch) -> Result<Option<Self::Batch>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryConfig {
+    /// How long a batch may stay processing before it counts as stuck
+    pub stuck_after: Duration,
+    /// Stuck batches considered per pass
+    pub scan_limit: i32,
+    pub interval: Duration,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self { stuck_after: Duration::from_secs(600), scan_limit: 100, interval: Duration::from_secs(60) }
+    }
+}
+
+impl RecoveryConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.stuck_after.is_zero() {
+            bail!("Stuck batch timeout must be greater than 0");
+        }
+        if self.scan_limit <= 0 {
+            bail!("Scan limit must be greater than 0");
+        }
+        if self.interval.is_zero() {
+            bail!("Recovery interval must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+pub struct BatchRecovery<S> {
+    store: Arc<S>,
+    config: RecoveryConfig,
+}
+
+impl<S: StuckBatchStore> BatchRecovery<S> {
+    pub fn new(store: Arc<S>, config: RecoveryConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self { store, config })
+    }
+
+    pub fn config(&self) -> &RecoveryConfig {
+        &self.config
+    }
+
+    /// Run one pass, returning the batches put back in the queue
+    pub async fn recover_once(&self, now: DateTime<Utc>) -> Result<Vec<S::Batch>> {
+        let stuck_since = now - chrono::Duration::from_std(self.config.stuck_after)?;
+        let mut recovered = Vec::new();
+        for batch in self.store.stuck_batches(stuck_since, self.config.scan_limit).await? {
+            if let Some(batch) = self.store.recover_batch(batch).await? {
+                recovered.push(batch);
+            }
+        }
+        Ok(recovered)
+    }
+}
+
+/// Recover stuck batches every `interval` of the recovery's config
+pub fn spawn_batch_recovery<S: StuckBatchStore + 'static>(
+    recovery: Arc<BatchRecovery<S>>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(recovery.config.interval);
+        loop {
+            ticker.tick().await;
+            match recovery.recover_once(Utc::now()).await {
+                Ok(batches) if !batches.is_empty() => {
+                    tracing::warn!(stage = S::STAGE, batches = batches.len(), "requeued batches stuck in processing");
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(stage = S::STAGE, error = %e, "stuck batch recovery pass failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    /// Batches as `(id, processing since)`, with the ids a worker finished meanwhile
+    struct MemoryStore {
+        processing: Mutex<Vec<(u32, DateTime<Utc>)>>,
+        finished: Vec<u32>,
+    }
+
+    #[async_trait]
+    impl StuckBatchStore for MemoryStore {
+        type Batch = u32;
+        const STAGE: &'static str = "test";
+
+        async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<u32>> {
+            let processing = self.processing.lock();
+            Ok(processing
+                .iter()
+                .filter(|(_, since)| *since < stuck_since)
+                .map(|(id, _)| *id)
+                .take(limit as usize)
+                .collect())
+        }
+
+        async fn recover_batch(&self, batch: u32) -> Result<Option<u32>> {
+            if self.finished.contains(&batch) {
+                return Ok(None);
+            }
+            self.processing.lock().retain(|(id, _)| *id != batch);
+            Ok(Some(batch))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recovers_batches_stuck_past_the_timeout() {
+        let now = Utc::now();
+        let minutes_ago = |minutes| now - chrono::Duration::minutes(minutes);
+        let store = Arc::new(MemoryStore {
+            processing: Mutex::new(vec![
+                (1, minutes_ago(30)),
+                (2, minutes_ago(30)),
+                (3, minutes_ago(1)),
+                (4, minutes_ago(20)),
+            ]),
+            finished: vec![2],
+        });
+        let config = RecoveryConfig { scan_limit: 2, ..Default::default() };
+        let recovery = BatchRecovery::new(store.clone(), config).unwrap();
+
+        // The finished batch is left alone, and the scan limit holds the rest back
+        assert_eq!(recovery.recover_once(now).await.unwrap(), vec![1]);
+        assert_eq!(recovery.recover_once(now).await.unwrap(), vec![4]);
+        assert!(recovery.recover_once(now).await.unwrap().is_empty());
+        assert!(RecoveryConfig { scan_limit: 0, ..Default::default() }.validate().is_err());
+        assert!(RecoveryConfig { stuck_after: Duration::ZERO, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,70 @@
+// common/retry/src/budget.rs
+//! Capping retries to a share of calls.
+//!
+//! When a dependency is down, every caller retrying multiplies the load on
+//! it just as it tries to recover. A budget shared by the callers of one
+//! dependency earns `ratio` of a retry for each call made and spends one per
+//! retry, holding at most `reserve` retries (and at least one), so retries
+//! stay near `ratio` of calls under a sustained outage while isolated
+//! failures are still retried at once.
+use std::sync::Mutex;
+
+#[derive(Debug)]
+pub struct RetryBudget {
+    ratio: f64,
+    capacity: f64,
+    tokens: Mutex<f64>,
+}
+
+impl RetryBudget {
+    /// Budget allowing `ratio` retries per call, with `reserve` available up front
+    pub fn new(ratio: f64, reserve: u32) -> Self {
+        let capacity = f64::from(reserve).max(1.0);
+        Self { ratio: ratio.max(0.0), capacity, tokens: Mutex::new(capacity) }
+    }
+
+    /// Record a call
+    pub fn deposit(&self) {
+        let mut tokens = self.tokens.lock().unwrap();
+        *tokens = (*tokens + self.ratio).min(self.capacity);
+    }
+
+    /// Take a retry; `false` when the budget is spent
+    pub fn withdraw(&self) -> bool {
+        let mut tokens = self.tokens.lock().unwrap();
+        if *tokens < 1.0 {
+            return false;
+        }
+        *tokens -= 1.0;
+        true
+    }
+
+    /// Whole retries left
+    pub fn available(&self) -> u32 {
+        *self.tokens.lock().unwrap() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_refills_with_calls() {
+        let budget = RetryBudget::new(0.25, 2);
+        assert!(budget.withdraw() && budget.withdraw());
+        assert!(!budget.withdraw());
+
+        // Four calls earn one retry
+        for _ in 0..4 {
+            budget.deposit();
+        }
+        assert_eq!(budget.available(), 1);
+        assert!(budget.withdraw() && !budget.withdraw());
+
+        for _ in 0..100 {
+            budget.deposit();
+        }
+        assert_eq!(budget.available(), 2);
+    }
+}
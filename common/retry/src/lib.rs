@@ -0,0 +1,13 @@
+// common/retry/src/lib.rs
+//! Retrying failed operations: backoff policies with optional jitter, a
+//! budget capping retries to a share of calls, and a runner that only
+//! repeats what is safe to repeat, stops when cancelled and reports each
+//! step to an observer for metrics.
+pub mod budget;
+pub mod policy;
+pub mod run;
+
+pub use budget::RetryBudget;
+pub use policy::{Backoff, Jitter, RetryPolicy};
+pub use run::{Cancelled, GiveUp, Idempotency, Outcome, Retry, RetryCounts, RetryObserver, RetryStats};
+pub use tokio_util::sync::CancellationToken;
@@ -0,0 +1,115 @@
+// common/retry/src/policy.rs
+//! How many attempts an operation gets and how long to wait between them.
+use anyhow::{bail, Result};
+use rand::Rng;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// The same wait after every failure
+    Fixed(Duration),
+    /// `base * 2^(n-1)` after the n-th failure, capped at `max`
+    Exponential { base: Duration, max: Duration },
+}
+
+impl Backoff {
+    /// Wait after `failures` failures in a row
+    pub fn delay(&self, failures: u32) -> Duration {
+        match *self {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { base, max } => {
+                let doublings = failures.saturating_sub(1).min(31);
+                base.saturating_mul(1u32 << doublings).min(max)
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Backoff::Exponential { base, max } = self {
+            if base.is_zero() || base > max {
+                bail!("Base backoff must be greater than 0 and at most the maximum backoff");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Randomness added to waits, so callers that failed together do not all
+/// retry at the same moment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Jitter {
+    #[default]
+    None,
+    /// Anywhere between zero and the full wait
+    Full,
+    /// At least half the wait, plus up to the other half
+    Equal,
+}
+
+impl Jitter {
+    pub fn apply(&self, delay: Duration, rng: &mut impl Rng) -> Duration {
+        match self {
+            Jitter::None => delay,
+            Jitter::Full => delay.mul_f64(rng.gen::<f64>()),
+            Jitter::Equal => delay / 2 + (delay / 2).mul_f64(rng.gen::<f64>()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, counting the first
+    pub max_attempts: u32,
+    pub backoff: Backoff,
+    pub jitter: Jitter,
+}
+
+impl RetryPolicy {
+    /// Up to `max_attempts` attempts with exponential backoff and no jitter
+    pub fn exponential(max_attempts: u32, base: Duration, max: Duration) -> Self {
+        Self { max_attempts, backoff: Backoff::Exponential { base, max }, jitter: Jitter::None }
+    }
+
+    /// A single attempt
+    pub fn never() -> Self {
+        Self { max_attempts: 1, backoff: Backoff::Fixed(Duration::ZERO), jitter: Jitter::None }
+    }
+
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.max_attempts == 0 {
+            bail!("Max attempts must be greater than 0");
+        }
+        self.backoff.validate()
+    }
+
+    /// Wait after `failures` failures in a row, jittered
+    pub fn delay(&self, failures: u32) -> Duration {
+        self.jitter.apply(self.backoff.delay(failures), &mut rand::thread_rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_and_jitter_bounds() {
+        let policy = RetryPolicy::exponential(5, Duration::from_millis(100), Duration::from_millis(350));
+        let delays: Vec<u128> = (1..=4).map(|failures| policy.delay(failures).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 350, 350]);
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let equal = Jitter::Equal.apply(Duration::from_millis(200), &mut rng);
+            assert!(equal >= Duration::from_millis(100) && equal <= Duration::from_millis(200));
+            assert!(Jitter::Full.apply(Duration::from_millis(200), &mut rng) <= Duration::from_millis(200));
+        }
+        assert!(RetryPolicy { max_attempts: 0, ..policy }.validate().is_err());
+        assert!(RetryPolicy::exponential(3, Duration::from_secs(2), Duration::from_secs(1)).validate().is_err());
+    }
+}
@@ -0,0 +1,305 @@
+// common/retry/src/run.rs
+//! Running an operation until it succeeds, fails for good or runs out of
+//! attempts.
+//!
+//! Each failure is classified by the caller. A failure that certainly did
+//! not take effect, e.g. refused before it reached the server, may always be
+//! repeated; one that may have taken effect, e.g. a timeout, is repeated only
+//! for operations marked idempotent; a permanent one is returned at once.
+//! Waiting between attempts follows the policy and, with a budget, each
+//! retry must be paid for. A cancelled run stops before its next attempt or
+//! mid-wait with `Cancelled`.
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::budget::RetryBudget;
+use crate::policy::RetryPolicy;
+
+/// Whether repeating an operation that may already have taken effect is safe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Idempotency {
+    Idempotent,
+    NotIdempotent,
+}
+
+/// What a failed attempt means for the next one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The attempt certainly did not take effect
+    NotApplied,
+    /// The attempt may have taken effect
+    Unknown,
+    /// Another attempt would fail the same way
+    Permanent,
+}
+
+impl Outcome {
+    fn retryable(self, idempotency: Idempotency) -> bool {
+        match self {
+            Outcome::NotApplied => true,
+            Outcome::Unknown => idempotency == Idempotency::Idempotent,
+            Outcome::Permanent => false,
+        }
+    }
+}
+
+/// Why a run stopped without succeeding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GiveUp {
+    /// The failure was permanent, or could have taken effect on an operation
+    /// that must not be repeated
+    NotRetryable,
+    Exhausted,
+    BudgetSpent,
+    Cancelled,
+}
+
+/// Returned when a run is cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Operation cancelled before it succeeded")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Told about every step of a run, e.g. to count retries
+pub trait RetryObserver: Send + Sync {
+    fn retrying(&self, _operation: &str, _failures: u32, _delay: Duration) {}
+
+    fn succeeded(&self, _operation: &str, _attempts: u32) {}
+
+    fn gave_up(&self, _operation: &str, _attempts: u32, _reason: GiveUp) {}
+}
+
+/// Counters over every run it observes
+#[derive(Debug, Default)]
+pub struct RetryStats {
+    retries: AtomicU64,
+    succeeded: AtomicU64,
+    succeeded_after_retry: AtomicU64,
+    gave_up: AtomicU64,
+    budget_spent: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryCounts {
+    pub retries: u64,
+    pub succeeded: u64,
+    /// Runs that needed more than one attempt
+    pub succeeded_after_retry: u64,
+    pub gave_up: u64,
+    /// Runs that gave up because the budget was spent
+    pub budget_spent: u64,
+}
+
+impl RetryStats {
+    pub fn counts(&self) -> RetryCounts {
+        RetryCounts {
+            retries: self.retries.load(Ordering::Relaxed),
+            succeeded: self.succeeded.load(Ordering::Relaxed),
+            succeeded_after_retry: self.succeeded_after_retry.load(Ordering::Relaxed),
+            gave_up: self.gave_up.load(Ordering::Relaxed),
+            budget_spent: self.budget_spent.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl RetryObserver for RetryStats {
+    fn retrying(&self, _operation: &str, _failures: u32, _delay: Duration) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn succeeded(&self, _operation: &str, attempts: u32) {
+        self.succeeded.fetch_add(1, Ordering::Relaxed);
+        if attempts > 1 {
+            self.succeeded_after_retry.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn gave_up(&self, _operation: &str, _attempts: u32, reason: GiveUp) {
+        self.gave_up.fetch_add(1, Ordering::Relaxed);
+        if reason == GiveUp::BudgetSpent {
+            self.budget_spent.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// One way of running operations; build it with the policy, then add what
+/// applies
+#[derive(Clone, Copy)]
+pub struct Retry<'a> {
+    policy: &'a RetryPolicy,
+    idempotency: Idempotency,
+    budget: Option<&'a RetryBudget>,
+    cancel: Option<&'a CancellationToken>,
+    observer: Option<&'a dyn RetryObserver>,
+}
+
+impl<'a> Retry<'a> {
+    /// Runs under `policy`, treating operations as not idempotent
+    pub fn new(policy: &'a RetryPolicy) -> Self {
+        Self { policy, idempotency: Idempotency::NotIdempotent, budget: None, cancel: None, observer: None }
+    }
+
+    pub fn idempotency(mut self, idempotency: Idempotency) -> Self {
+        self.idempotency = idempotency;
+        self
+    }
+
+    /// Pay for every retry from `budget`
+    pub fn budget(mut self, budget: &'a RetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn cancel_on(mut self, token: &'a CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    pub fn observe(mut self, observer: &'a dyn RetryObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Run `attempt` until it succeeds or retrying stops, returning the
+    /// last failure. `operation` names it to the observer.
+    pub async fn run<T, E, F, Fut>(
+        &self,
+        operation: &str,
+        classify: impl Fn(&E) -> Outcome,
+        mut attempt: F,
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<Cancelled>,
+    {
+        if let Some(budget) = self.budget {
+            budget.deposit();
+        }
+        let mut failures = 0;
+        loop {
+            if self.cancel.is_some_and(|token| token.is_cancelled()) {
+                return Err(self.give_up(operation, failures, GiveUp::Cancelled, Cancelled.into()));
+            }
+            let error = match attempt().await {
+                Ok(value) => {
+                    if let Some(observer) = self.observer {
+                        observer.succeeded(operation, failures + 1);
+                    }
+                    return Ok(value);
+                }
+                Err(error) => error,
+            };
+            failures += 1;
+
+            let stop = if !classify(&error).retryable(self.idempotency) {
+                Some(GiveUp::NotRetryable)
+            } else if failures >= self.policy.max_attempts {
+                Some(GiveUp::Exhausted)
+            } else if self.budget.is_some_and(|budget| !budget.withdraw()) {
+                Some(GiveUp::BudgetSpent)
+            } else {
+                None
+            };
+            if let Some(reason) = stop {
+                return Err(self.give_up(operation, failures, reason, error));
+            }
+
+            let delay = self.policy.delay(failures);
+            if let Some(observer) = self.observer {
+                observer.retrying(operation, failures, delay);
+            }
+            match self.cancel {
+                Some(token) => tokio::select! {
+                    _ = token.cancelled() => {
+                        return Err(self.give_up(operation, failures, GiveUp::Cancelled, Cancelled.into()));
+                    }
+                    _ = tokio::time::sleep(delay) => {}
+                },
+                None => tokio::time::sleep(delay).await,
+            }
+        }
+    }
+
+    fn give_up<E>(&self, operation: &str, attempts: u32, reason: GiveUp, error: E) -> E {
+        if let Some(observer) = self.observer {
+            observer.gave_up(operation, attempts, reason);
+        }
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[derive(Debug, PartialEq)]
+    enum TestError {
+        Timeout,
+        Refused,
+        Cancelled,
+    }
+
+    impl From<Cancelled> for TestError {
+        fn from(_: Cancelled) -> Self {
+            TestError::Cancelled
+        }
+    }
+
+    fn classify(error: &TestError) -> Outcome {
+        match error {
+            TestError::Refused => Outcome::NotApplied,
+            TestError::Timeout => Outcome::Unknown,
+            TestError::Cancelled => Outcome::Permanent,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_by_idempotency_budget_and_cancellation() {
+        let policy = RetryPolicy::exponential(4, Duration::from_millis(10), Duration::from_millis(100));
+        let stats = RetryStats::default();
+        let calls = AtomicU32::new(0);
+        let failing = |error: fn() -> TestError, failures: u32| {
+            calls.store(0, Ordering::Relaxed);
+            let calls = &calls;
+            move || async move {
+                match calls.fetch_add(1, Ordering::Relaxed) < failures {
+                    true => Err(error()),
+                    false => Ok(()),
+                }
+            }
+        };
+
+        // Timeouts are only repeated for idempotent operations
+        let retry = Retry::new(&policy).observe(&stats);
+        assert_eq!(retry.run("write", classify, failing(|| TestError::Timeout, 1)).await, Err(TestError::Timeout));
+        let idempotent = retry.idempotency(Idempotency::Idempotent);
+        assert_eq!(idempotent.run("read", classify, failing(|| TestError::Timeout, 2)).await, Ok(()));
+        assert_eq!(retry.run("write", classify, failing(|| TestError::Refused, 9)).await, Err(TestError::Refused));
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+
+        // One retry in reserve, and a call earns none back
+        let budget = RetryBudget::new(0.0, 1);
+        let budgeted = idempotent.budget(&budget);
+        assert_eq!(budgeted.run("read", classify, failing(|| TestError::Refused, 3)).await, Err(TestError::Refused));
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cancelled = idempotent.cancel_on(&token);
+        assert_eq!(cancelled.run("read", classify, failing(|| TestError::Refused, 0)).await, Err(TestError::Cancelled));
+
+        let counts = stats.counts();
+        assert_eq!((counts.retries, counts.succeeded, counts.succeeded_after_retry), (6, 1, 1));
+        assert_eq!((counts.gave_up, counts.budget_spent), (4, 1));
+    }
+}
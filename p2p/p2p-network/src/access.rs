@@ -0,0 +1,314 @@
+// p2p/p2p-network/src/access.rs
+use ipnet::IpNet;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use crate::{NetworkError, PeerId, Result};
+
+/// Allow and deny rules as written in configuration: CIDR ranges, bare IPs or peer ids
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessList {
+    /// When non-empty, only matching peers may connect
+    pub allow: Vec<String>,
+    /// Always refused, even if also allowed
+    pub deny: Vec<String>,
+}
+
+impl AccessList {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        for rule in self.allow.iter().chain(&self.deny) {
+            AccessRule::parse(rule).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// A single parsed rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessRule {
+    Cidr(IpNet),
+    Peer(PeerId),
+}
+
+impl AccessRule {
+    pub fn parse(rule: &str) -> Result<Self> {
+        let rule = rule.trim();
+        if let Ok(net) = rule.parse::<IpNet>() {
+            return Ok(AccessRule::Cidr(net.trunc()));
+        }
+        if let Ok(ip) = rule.parse::<IpAddr>() {
+            return Ok(AccessRule::Cidr(IpNet::from(ip)));
+        }
+        if !rule.is_empty() && rule.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Ok(AccessRule::Peer(rule.to_string()));
+        }
+        Err(NetworkError::InvalidRule(format!(
+            "'{}' is not a CIDR range, IP address or peer id",
+            rule
+        )))
+    }
+
+    fn matches_ip(&self, ip: IpAddr) -> bool {
+        match self {
+            AccessRule::Cidr(net) => net.contains(&ip),
+            AccessRule::Peer(_) => false,
+        }
+    }
+
+    fn matches_peer(&self, peer_id: &str) -> bool {
+        matches!(self, AccessRule::Peer(id) if id == peer_id)
+    }
+}
+
+impl fmt::Display for AccessRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccessRule::Cidr(net) => write!(f, "{}", net),
+            AccessRule::Peer(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Rules {
+    allow: Vec<AccessRule>,
+    deny: Vec<AccessRule>,
+}
+
+impl Rules {
+    fn extend(&mut self, list: &AccessList) -> Result<()> {
+        for rule in &list.allow {
+            self.allow.push(AccessRule::parse(rule)?);
+        }
+        for rule in &list.deny {
+            self.deny.push(AccessRule::parse(rule)?);
+        }
+        Ok(())
+    }
+}
+
+/// Connection admission based on configured and runtime allow/deny rules.
+///
+/// Rules from configuration are fixed for the life of the process; rules
+/// added through the admin API are written to `access_list_path` and
+/// reloaded on the next start.
+pub struct AccessControl {
+    configured: AccessList,
+    runtime: RwLock<AccessList>,
+    rules: RwLock<Rules>,
+    path: Option<PathBuf>,
+}
+
+impl AccessControl {
+    /// Build from configuration, merging rules persisted by a previous run
+    pub fn new(configured: AccessList, path: Option<PathBuf>) -> Result<Self> {
+        let runtime = match &path {
+            Some(path) if path.exists() => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            _ => AccessList::default(),
+        };
+
+        let control = Self {
+            configured,
+            runtime: RwLock::new(runtime),
+            rules: RwLock::new(Rules::default()),
+            path,
+        };
+        control.rebuild()?;
+        Ok(control)
+    }
+
+    /// Check a connection at accept time, before the peer id is known
+    pub fn admit_connection(&self, addr: SocketAddr) -> Result<()> {
+        self.check(None, addr)
+    }
+
+    /// Check a peer once the handshake has revealed its id
+    pub fn admit_peer(&self, peer_id: &str, addr: SocketAddr) -> Result<()> {
+        self.check(Some(peer_id), addr)
+    }
+
+    fn check(&self, peer_id: Option<&str>, addr: SocketAddr) -> Result<()> {
+        let refuse = |reason: String| Err(NetworkError::ConnectionRefused { addr, reason });
+        let rules = self.rules.read();
+        let ip = addr.ip();
+        let matches = |rule: &AccessRule| {
+            rule.matches_ip(ip) || peer_id.is_some_and(|id| rule.matches_peer(id))
+        };
+
+        if let Some(rule) = rules.deny.iter().find(|rule| matches(rule)) {
+            return refuse(format!("denied by rule {}", rule));
+        }
+        if rules.allow.is_empty() || rules.allow.iter().any(matches) {
+            return Ok(());
+        }
+
+        // Peer id rules can only be decided after the handshake
+        let awaiting_id = peer_id.is_none()
+            && rules.allow.iter().any(|rule| matches!(rule, AccessRule::Peer(_)));
+        if awaiting_id {
+            return Ok(());
+        }
+        refuse("not on allowlist".to_string())
+    }
+
+    /// Add a runtime allow rule
+    pub fn allow(&self, rule: &str) -> Result<()> {
+        self.add(rule, |list| &mut list.allow)
+    }
+
+    /// Add a runtime deny rule
+    pub fn deny(&self, rule: &str) -> Result<()> {
+        self.add(rule, |list| &mut list.deny)
+    }
+
+    /// Remove a rule added at runtime; configured rules cannot be removed
+    pub fn remove(&self, rule: &str) -> Result<bool> {
+        let parsed = AccessRule::parse(rule)?;
+        let is_configured = self
+            .configured
+            .allow
+            .iter()
+            .chain(&self.configured.deny)
+            .any(|r| AccessRule::parse(r).ok().as_ref() == Some(&parsed));
+        if is_configured {
+            return Err(NetworkError::InvalidRule(format!("{} is defined in configuration", rule)));
+        }
+
+        let removed = {
+            let mut runtime = self.runtime.write();
+            let before = runtime.allow.len() + runtime.deny.len();
+            let keep = |r: &String| AccessRule::parse(r).ok().as_ref() != Some(&parsed);
+            runtime.allow.retain(keep);
+            runtime.deny.retain(keep);
+            before != runtime.allow.len() + runtime.deny.len()
+        };
+
+        if removed {
+            self.rebuild()?;
+            self.persist()?;
+        }
+        Ok(removed)
+    }
+
+    /// Effective rules: configured followed by runtime
+    pub fn list(&self) -> AccessList {
+        let runtime = self.runtime.read();
+        AccessList {
+            allow: self.configured.allow.iter().chain(&runtime.allow).cloned().collect(),
+            deny: self.configured.deny.iter().chain(&runtime.deny).cloned().collect(),
+        }
+    }
+
+    fn add(&self, rule: &str, target: impl Fn(&mut AccessList) -> &mut Vec<String>) -> Result<()> {
+        let normalized = AccessRule::parse(rule)?.to_string();
+        {
+            let mut runtime = self.runtime.write();
+            let rules = target(&mut runtime);
+            if rules.contains(&normalized) {
+                return Ok(());
+            }
+            rules.push(normalized);
+        }
+        self.rebuild()?;
+        self.persist()
+    }
+
+    fn rebuild(&self) -> Result<()> {
+        let mut rules = Rules::default();
+        rules.extend(&self.configured)?;
+        rules.extend(&self.runtime.read())?;
+        *self.rules.write() = rules;
+        Ok(())
+    }
+
+    fn persist(&self) -> Result<()> {
+        match &self.path {
+            Some(path) => write_atomic(path, &serde_json::to_vec_pretty(&*self.runtime.read())?),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Write via a temporary file so a crash never leaves a truncated list
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    fn list(allow: &[&str], deny: &[&str]) -> AccessList {
+        AccessList {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(
+            AccessRule::parse("10.1.2.3/8").unwrap(),
+            AccessRule::Cidr("10.0.0.0/8".parse().unwrap())
+        );
+        assert_eq!(
+            AccessRule::parse("::1").unwrap(),
+            AccessRule::Cidr("::1/128".parse().unwrap())
+        );
+        assert_eq!(
+            AccessRule::parse("12D3KooWPeer").unwrap(),
+            AccessRule::Peer("12D3KooWPeer".to_string())
+        );
+        assert!(AccessRule::parse("10.0.0.0/33").is_err());
+        assert!(AccessRule::parse("").is_err());
+    }
+
+    #[test]
+    fn test_open_node_with_denylist() {
+        let control = AccessControl::new(list(&[], &["192.168.0.0/16", "BadPeer"]), None).unwrap();
+        assert!(control.admit_connection(addr("8.8.8.8:30303")).is_ok());
+        assert!(control.admit_connection(addr("192.168.4.2:30303")).is_err());
+        assert!(control.admit_peer("BadPeer", addr("8.8.8.8:30303")).is_err());
+    }
+
+    #[test]
+    fn test_permissioned_allowlist() {
+        let control = AccessControl::new(list(&["10.0.0.0/8", "TrustedPeer"], &["10.0.0.66"]), None).unwrap();
+        assert!(control.admit_connection(addr("10.2.3.4:1")).is_ok());
+        assert!(control.admit_connection(addr("10.0.0.66:1")).is_err());
+
+        // Unknown IPs wait for the handshake because a peer id rule may match
+        assert!(control.admit_connection(addr("1.2.3.4:1")).is_ok());
+        assert!(control.admit_peer("TrustedPeer", addr("1.2.3.4:1")).is_ok());
+        assert!(control.admit_peer("OtherPeer", addr("1.2.3.4:1")).is_err());
+    }
+
+    #[test]
+    fn test_runtime_rules_persist() {
+        let path = std::env::temp_dir().join(format!("p2p-access-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let control = AccessControl::new(list(&[], &["10.0.0.1"]), Some(path.clone())).unwrap();
+        control.deny("203.0.113.0/24").unwrap();
+        assert!(control.admit_connection(addr("203.0.113.9:1")).is_err());
+        assert!(control.remove("10.0.0.1").is_err());
+
+        let restarted = AccessControl::new(list(&[], &["10.0.0.1"]), Some(path.clone())).unwrap();
+        assert!(restarted.admit_connection(addr("203.0.113.9:1")).is_err());
+        assert_eq!(restarted.list().deny, vec!["10.0.0.1", "203.0.113.0/24"]);
+
+        assert!(restarted.remove("203.0.113.0/24").unwrap());
+        assert!(restarted.admit_connection(addr("203.0.113.9:1")).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
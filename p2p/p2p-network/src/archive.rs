@@ -0,0 +1,376 @@
+// p2p/p2p-network/src/archive.rs
+//! Serving history that pruned nodes no longer hold.
+//!
+//! Archive nodes advertise `ARCHIVE_SERVING` and answer `ArchiveRequest`s
+//! for old block bodies, transaction receipts and account state proofs.
+//! Every item has a price in units. The units served to each peer are
+//! counted so an operator can bill for them. With a `PaymentHook`
+//! installed, a request is served only once the hook accepts the payment
+//! voucher sent with it. Without a hook, history is served for free, still
+//! under the per-peer rate limit, which also counts units.
+use async_trait::async_trait;
+use blockchain_core::{AccountProof, Address, Block, BlockHash, BlockHeight, TransactionReceipt, TxHash};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::headers::PeerRateLimiter;
+use crate::{NetworkError, PeerId, Result};
+
+/// History a peer asks for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryQuery {
+    BlockBodies { heights: Vec<BlockHeight> },
+    Receipts { tx_hashes: Vec<TxHash> },
+    /// State of `addresses` after the block at `height`
+    StateProofs { height: BlockHeight, addresses: Vec<Address> },
+}
+
+impl HistoryQuery {
+    fn len(&self) -> usize {
+        match self {
+            HistoryQuery::BlockBodies { heights } => heights.len(),
+            HistoryQuery::Receipts { tx_hashes } => tx_hashes.len(),
+            HistoryQuery::StateProofs { addresses, .. } => addresses.len(),
+        }
+    }
+
+    fn truncate(&mut self, max_items: usize) {
+        match self {
+            HistoryQuery::BlockBodies { heights } => heights.truncate(max_items),
+            HistoryQuery::Receipts { tx_hashes } => tx_hashes.truncate(max_items),
+            HistoryQuery::StateProofs { addresses, .. } => addresses.truncate(max_items),
+        }
+    }
+}
+
+/// Payment for served history, checked by the operator's `PaymentHook`;
+/// opaque to the protocol
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentVoucher {
+    pub channel_id: String,
+    /// Total paid into the channel so far
+    pub cumulative_amount: u64,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveRequest {
+    /// Echoed in the response so requests can be pipelined
+    pub request_id: u64,
+    pub query: HistoryQuery,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voucher: Option<PaymentVoucher>,
+}
+
+/// Served history; items the node does not hold are left out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryItems {
+    BlockBodies { blocks: Vec<Block> },
+    Receipts { receipts: Vec<TransactionReceipt> },
+    /// Proofs against the state root after the block at `height`; empty if
+    /// that state cannot be rebuilt
+    StateProofs { height: BlockHeight, state_root: Option<BlockHash>, proofs: Vec<AccountProof> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveResponse {
+    pub request_id: u64,
+    pub items: HistoryItems,
+    /// Units counted against the peer for the items served
+    pub units: u64,
+}
+
+/// Where an archive node reads history from
+#[async_trait]
+pub trait HistorySource: Send + Sync {
+    async fn block_body(&self, height: BlockHeight) -> Result<Option<Block>>;
+
+    async fn receipt(&self, tx_hash: &TxHash) -> Result<Option<TransactionReceipt>>;
+
+    /// State root after the block at `height` and a proof for each of
+    /// `addresses`, of its state or of its absence, or `None` if the state is
+    /// not available
+    async fn state_proofs(
+        &self,
+        height: BlockHeight,
+        addresses: &[Address],
+    ) -> Result<Option<(BlockHash, Vec<AccountProof>)>>;
+}
+
+/// Operator hook deciding whether a peer has paid for a request
+pub trait PaymentHook: Send + Sync {
+    /// Accept `voucher` as payment for `units` more from `peer_id`, or fail
+    /// with `PaymentRequired`
+    fn authorize(&self, peer_id: &str, units: u64, voucher: Option<&PaymentVoucher>) -> Result<()>;
+}
+
+/// Units charged per served item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivePricing {
+    pub block_body: u64,
+    pub receipt: u64,
+    pub state_proof: u64,
+}
+
+impl Default for ArchivePricing {
+    fn default() -> Self {
+        Self { block_body: 10, receipt: 1, state_proof: 5 }
+    }
+}
+
+impl ArchivePricing {
+    fn unit_price(&self, query: &HistoryQuery) -> u64 {
+        match query {
+            HistoryQuery::BlockBodies { .. } => self.block_body,
+            HistoryQuery::Receipts { .. } => self.receipt,
+            HistoryQuery::StateProofs { .. } => self.state_proof,
+        }
+    }
+}
+
+/// Limits and prices for serving history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveServingConfig {
+    /// Requests asking for more items are truncated
+    pub max_items_per_request: u32,
+    /// Sustained units per second served to one peer
+    pub units_per_second: u32,
+    /// Units a peer may use in a burst before the rate applies
+    pub burst_units: u32,
+    pub pricing: ArchivePricing,
+}
+
+impl Default for ArchiveServingConfig {
+    fn default() -> Self {
+        Self {
+            max_items_per_request: 64,
+            units_per_second: 500,
+            burst_units: 1_000,
+            pricing: ArchivePricing::default(),
+        }
+    }
+}
+
+impl ArchiveServingConfig {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.max_items_per_request == 0 || self.units_per_second == 0 {
+            return Err("Archive serving limits must be greater than 0".to_string());
+        }
+        // A burst smaller than one full request would reject every full request
+        let pricing = &self.pricing;
+        let largest = pricing.block_body.max(pricing.receipt).max(pricing.state_proof);
+        if (self.burst_units as u64) < largest * self.max_items_per_request as u64 {
+            return Err("Archive serving burst must cover a full request of the most expensive item".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// History served to one peer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerUsage {
+    pub requests: u64,
+    pub items: u64,
+    pub units: u64,
+}
+
+/// Answers `ArchiveRequest`s from a `HistorySource`
+pub struct ArchiveServer {
+    source: Arc<dyn HistorySource>,
+    config: ArchiveServingConfig,
+    payments: Option<Arc<dyn PaymentHook>>,
+    limiter: Mutex<PeerRateLimiter>,
+    usage: Mutex<HashMap<PeerId, PeerUsage>>,
+}
+
+impl ArchiveServer {
+    pub fn new(source: Arc<dyn HistorySource>, config: ArchiveServingConfig) -> Self {
+        Self {
+            source,
+            limiter: Mutex::new(PeerRateLimiter::new(config.units_per_second, config.burst_units)),
+            config,
+            payments: None,
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Serve history only to peers `hook` accepts payment from
+    pub fn with_payments(mut self, hook: Arc<dyn PaymentHook>) -> Self {
+        self.payments = Some(hook);
+        self
+    }
+
+    pub async fn serve(&self, peer_id: &str, request: &ArchiveRequest, now: Instant) -> Result<ArchiveResponse> {
+        let mut query = request.query.clone();
+        query.truncate(self.config.max_items_per_request as usize);
+        let unit_price = self.config.pricing.unit_price(&query);
+        let requested_units = unit_price * query.len() as u64;
+
+        if let Some(payments) = &self.payments {
+            payments.authorize(peer_id, requested_units, request.voucher.as_ref())?;
+        }
+        let cost = u32::try_from(requested_units).unwrap_or(u32::MAX);
+        if !self.limiter.lock().try_acquire(peer_id, cost, now) {
+            return Err(NetworkError::RateLimited { peer_id: peer_id.to_string() });
+        }
+
+        let (items, served) = self.fetch(&query).await?;
+        let units = unit_price * served as u64;
+        let mut usage = self.usage.lock();
+        let peer = usage.entry(peer_id.to_string()).or_default();
+        peer.requests += 1;
+        peer.items += served as u64;
+        peer.units += units;
+        Ok(ArchiveResponse { request_id: request.request_id, items, units })
+    }
+
+    async fn fetch(&self, query: &HistoryQuery) -> Result<(HistoryItems, usize)> {
+        match query {
+            HistoryQuery::BlockBodies { heights } => {
+                let mut blocks = Vec::new();
+                for height in heights {
+                    blocks.extend(self.source.block_body(*height).await?);
+                }
+                let served = blocks.len();
+                Ok((HistoryItems::BlockBodies { blocks }, served))
+            }
+            HistoryQuery::Receipts { tx_hashes } => {
+                let mut receipts = Vec::new();
+                for tx_hash in tx_hashes {
+                    receipts.extend(self.source.receipt(tx_hash).await?);
+                }
+                let served = receipts.len();
+                Ok((HistoryItems::Receipts { receipts }, served))
+            }
+            HistoryQuery::StateProofs { height, addresses } => {
+                let (state_root, proofs) = match self.source.state_proofs(*height, addresses).await? {
+                    Some((root, proofs)) => (Some(root), proofs),
+                    None => (None, Vec::new()),
+                };
+                let served = proofs.len();
+                Ok((HistoryItems::StateProofs { height: *height, state_root, proofs }, served))
+            }
+        }
+    }
+
+    /// History served to `peer_id` since the last `take_usage`
+    pub fn usage(&self, peer_id: &str) -> PeerUsage {
+        self.usage.lock().get(peer_id).copied().unwrap_or_default()
+    }
+
+    /// Per-peer usage since the last call, for billing; resets the counters
+    pub fn take_usage(&self) -> HashMap<PeerId, PeerUsage> {
+        std::mem::take(&mut *self.usage.lock())
+    }
+
+    pub fn on_disconnected(&self, peer_id: &str) {
+        self.limiter.lock().forget(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blockchain_core::Ledger;
+
+    struct MemoryHistory {
+        blocks: Vec<Block>,
+        ledger: Ledger,
+    }
+
+    #[async_trait]
+    impl HistorySource for MemoryHistory {
+        async fn block_body(&self, height: BlockHeight) -> Result<Option<Block>> {
+            Ok(self.blocks.get(height as usize).cloned())
+        }
+
+        async fn receipt(&self, _tx_hash: &TxHash) -> Result<Option<TransactionReceipt>> {
+            Ok(None)
+        }
+
+        async fn state_proofs(
+            &self,
+            _height: BlockHeight,
+            addresses: &[Address],
+        ) -> Result<Option<(BlockHash, Vec<AccountProof>)>> {
+            let root = self.ledger.state_root().map_err(|e| NetworkError::Storage(e.to_string()))?;
+            let mut proofs = Vec::new();
+            for address in addresses {
+                proofs.push(self.ledger.account_proof(address).map_err(|e| NetworkError::Storage(e.to_string()))?);
+            }
+            Ok(Some((root, proofs)))
+        }
+    }
+
+    /// Accepts vouchers from the channel named after the peer
+    struct ChannelPerPeer;
+
+    impl PaymentHook for ChannelPerPeer {
+        fn authorize(&self, peer_id: &str, units: u64, voucher: Option<&PaymentVoucher>) -> Result<()> {
+            match voucher {
+                Some(voucher) if voucher.channel_id == peer_id => Ok(()),
+                _ => Err(NetworkError::PaymentRequired { peer_id: peer_id.to_string(), units }),
+            }
+        }
+    }
+
+    fn request(request_id: u64, query: HistoryQuery, voucher: Option<PaymentVoucher>) -> ArchiveRequest {
+        ArchiveRequest { request_id, query, voucher }
+    }
+
+    #[tokio::test]
+    async fn test_serves_and_accounts_history() {
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        let mut ledger = Ledger::new();
+        ledger.credit(&[1; 20], 50).unwrap();
+        let source = Arc::new(MemoryHistory { blocks: vec![genesis.clone()], ledger });
+        let server = ArchiveServer::new(source, ArchiveServingConfig::default());
+        let now = Instant::now();
+
+        // The missing block is left out and not charged for
+        let bodies = request(1, HistoryQuery::BlockBodies { heights: vec![0, 7] }, None);
+        let response = server.serve("peer-a", &bodies, now).await.unwrap();
+        assert_eq!(response.items, HistoryItems::BlockBodies { blocks: vec![genesis] });
+        assert_eq!(response.units, 10);
+
+        let query = HistoryQuery::StateProofs { height: 0, addresses: vec![[1; 20], [2; 20]] };
+        let response = server.serve("peer-a", &request(2, query, None), now).await.unwrap();
+        let HistoryItems::StateProofs { state_root, proofs, .. } = response.items else {
+            panic!("expected state proofs");
+        };
+        // The unfunded address gets a proof of absence
+        assert_eq!(proofs.len(), 2);
+        assert_eq!(proofs[1].state, None);
+        assert!(proofs.iter().all(|proof| proof.verify(&state_root.unwrap()).unwrap()));
+        assert_eq!(server.usage("peer-a"), PeerUsage { requests: 2, items: 3, units: 20 });
+        assert_eq!(server.take_usage().len(), 1);
+        assert_eq!(server.usage("peer-a"), PeerUsage::default());
+
+        // Over the burst, the peer waits for its budget to refill
+        let heights = (0..64).collect();
+        let large = request(3, HistoryQuery::BlockBodies { heights }, None);
+        server.serve("peer-b", &large, now).await.unwrap();
+        assert!(matches!(server.serve("peer-b", &large, now).await, Err(NetworkError::RateLimited { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_payment_hook_gates_requests() {
+        let source = Arc::new(MemoryHistory { blocks: Vec::new(), ledger: Ledger::new() });
+        let server =
+            ArchiveServer::new(source, ArchiveServingConfig::default()).with_payments(Arc::new(ChannelPerPeer));
+        let query = HistoryQuery::Receipts { tx_hashes: vec![[4; 32]] };
+
+        let unpaid = server.serve("peer-a", &request(1, query.clone(), None), Instant::now()).await;
+        assert!(matches!(unpaid, Err(NetworkError::PaymentRequired { units: 1, .. })));
+        let voucher =
+            PaymentVoucher { channel_id: "peer-a".to_string(), cumulative_amount: 1, signature: String::new() };
+        let paid = server.serve("peer-a", &request(2, query, Some(voucher)), Instant::now()).await.unwrap();
+        assert_eq!(paid.items, HistoryItems::Receipts { receipts: Vec::new() });
+        assert!(ArchiveServingConfig { burst_units: 10, ..Default::default() }.validate().is_err());
+    }
+}
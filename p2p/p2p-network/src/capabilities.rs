@@ -0,0 +1,135 @@
+// p2p/p2p-network/src/capabilities.rs
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
+
+/// Optional sub-protocols a peer supports, exchanged as a bitmap in the handshake.
+///
+/// Unknown bits from newer peers are kept so they survive re-advertisement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Capabilities(u64);
+
+impl Capabilities {
+    pub const NONE: Capabilities = Capabilities(0);
+    /// Full block and transaction relay
+    pub const BLOCK_RELAY: Capabilities = Capabilities(1 << 0);
+    /// Transaction gossip
+    pub const TX_GOSSIP: Capabilities = Capabilities(1 << 1);
+    /// Compact block announcements
+    pub const COMPACT_BLOCKS: Capabilities = Capabilities(1 << 2);
+    /// Serves state snapshots for fast sync
+    pub const SNAPSHOT_SERVING: Capabilities = Capabilities(1 << 3);
+    /// Serves headers and proofs to light clients
+    pub const LIGHT_CLIENT_SERVING: Capabilities = Capabilities(1 << 4);
+    /// Serves old block bodies, receipts and state proofs that pruned nodes dropped
+    pub const ARCHIVE_SERVING: Capabilities = Capabilities(1 << 5);
+
+    const NAMED: [(Capabilities, &'static str); 6] = [
+        (Self::BLOCK_RELAY, "block_relay"),
+        (Self::TX_GOSSIP, "tx_gossip"),
+        (Self::COMPACT_BLOCKS, "compact_blocks"),
+        (Self::SNAPSHOT_SERVING, "snapshot_serving"),
+        (Self::LIGHT_CLIENT_SERVING, "light_client_serving"),
+        (Self::ARCHIVE_SERVING, "archive_serving"),
+    ];
+
+    pub const fn from_bits(bits: u64) -> Self {
+        Capabilities(bits)
+    }
+
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub const fn contains(self, other: Capabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Capabilities both sides support; only these may be used on the connection
+    pub const fn negotiate(self, remote: Capabilities) -> Capabilities {
+        Capabilities(self.0 & remote.0)
+    }
+
+    /// Number of capabilities from `wanted` that are present
+    pub const fn overlap(self, wanted: Capabilities) -> u32 {
+        (self.0 & wanted.0).count_ones()
+    }
+
+    /// Parse a comma-separated list of capability names
+    pub fn parse_list(list: &str) -> Result<Self, String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .try_fold(Self::NONE, |caps, name| {
+                Self::NAMED
+                    .iter()
+                    .find(|(_, n)| *n == name)
+                    .map(|(cap, _)| caps | *cap)
+                    .ok_or_else(|| format!("Unknown capability: {}", name))
+            })
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Capabilities;
+
+    fn bitor(self, rhs: Capabilities) -> Capabilities {
+        Capabilities(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for Capabilities {
+    type Output = Capabilities;
+
+    fn bitand(self, rhs: Capabilities) -> Capabilities {
+        Capabilities(self.0 & rhs.0)
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut names: Vec<String> = Self::NAMED
+            .iter()
+            .filter(|(cap, _)| self.contains(*cap))
+            .map(|(_, name)| name.to_string())
+            .collect();
+
+        let known = Self::NAMED.iter().fold(0, |bits, (cap, _)| bits | cap.0);
+        if self.0 & !known != 0 {
+            names.push(format!("unknown({:#x})", self.0 & !known));
+        }
+        write!(f, "{}", names.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_keeps_common_bits() {
+        let local = Capabilities::BLOCK_RELAY | Capabilities::COMPACT_BLOCKS;
+        let remote = Capabilities::BLOCK_RELAY | Capabilities::SNAPSHOT_SERVING;
+        assert_eq!(local.negotiate(remote), Capabilities::BLOCK_RELAY);
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let caps = Capabilities::parse_list("block_relay, snapshot_serving").unwrap();
+        assert!(caps.contains(Capabilities::SNAPSHOT_SERVING));
+        assert!(!caps.contains(Capabilities::TX_GOSSIP));
+        assert_eq!(caps.to_string(), "block_relay,snapshot_serving");
+        assert!(Capabilities::parse_list("warp_sync").is_err());
+
+        // Bits from newer protocol versions round-trip untouched
+        let future = Capabilities::from_bits(1 << 40) | Capabilities::TX_GOSSIP;
+        assert_eq!(future.to_string(), "tx_gossip,unknown(0x10000000000)");
+        let json = serde_json::to_string(&future).unwrap();
+        assert_eq!(serde_json::from_str::<Capabilities>(&json).unwrap(), future);
+    }
+}
@@ -0,0 +1,335 @@
+// p2p/p2p-network/src/config.rs
+use blockchain_core::ConfigReport;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::access::AccessList;
+use crate::capabilities::Capabilities;
+use crate::connections::ConnectionConfig;
+use crate::discovery::DiscoveryConfig;
+use crate::archive::ArchiveServingConfig;
+use crate::headers::HeaderServingConfig;
+use crate::identity::NodeIdentity;
+use crate::nat::NatConfig;
+use crate::outbound::OutboundQueueConfig;
+use crate::peer_manager::StaticNode;
+use crate::role::NodeRole;
+use crate::scoring::ScoringConfig;
+use crate::seen::SeenCacheConfig;
+use crate::tx_gossip::TxGossipConfig;
+
+/// Where the node key is kept unless `P2P_NODE_KEY_PATH` says otherwise
+pub const DEFAULT_NODE_KEY_PATH: &str = "data/p2p/node.key";
+
+/// P2P network configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Topology role; sets the defaults below and is advertised in the handshake
+    pub role: NodeRole,
+    /// Address to accept peer connections on
+    pub listen_addr: String,
+    /// Maximum number of connected regular peers
+    pub max_peers: usize,
+    /// Inbound and outbound limits within `max_peers`, and dial backoff
+    pub connections: ConnectionConfig,
+    /// Peers kept connected at all times, as `peer_id@ip:port`
+    pub static_nodes: Vec<String>,
+    /// Peers that may always connect and are never banned by scoring
+    pub trusted_peers: Vec<String>,
+    /// Sub-protocols this node advertises in the handshake
+    pub capabilities: Capabilities,
+    /// Per-peer outbound priority queues
+    pub outbound: OutboundQueueConfig,
+    /// Header download limits for light clients
+    pub header_serving: HeaderServingConfig,
+    /// Item limits, rate and prices for serving history to other nodes
+    pub archive_serving: ArchiveServingConfig,
+    /// First-seen tracking of announced block and transaction hashes
+    pub seen_cache: SeenCacheConfig,
+    /// Message size and per-peer rate limits on transaction gossip
+    pub tx_gossip: TxGossipConfig,
+    /// Delay between redial attempts after an immediate reconnect fails
+    pub reconnect_interval_ms: u64,
+    /// Allow and deny rules applied at connection accept time
+    pub access: AccessList,
+    /// File holding rules added at runtime through the admin API
+    pub access_list_path: Option<PathBuf>,
+    /// Node key file, created on first start; without it the node id changes on every restart
+    /// and allowlist entries for this node stop matching
+    pub node_key_path: Option<PathBuf>,
+    /// Peer records older than this are not dialed
+    pub peer_record_max_age_secs: i64,
+    /// Kademlia bootnodes, bucket size and random-walk interval
+    pub discovery: DiscoveryConfig,
+    /// Offence penalties and the score at which regular peers are banned
+    pub scoring: ScoringConfig,
+    /// Gateway port mapping and external address discovery
+    pub nat: NatConfig,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self::for_role(NodeRole::default())
+    }
+}
+
+impl NetworkConfig {
+    /// Defaults for a node role
+    pub fn for_role(role: NodeRole) -> Self {
+        Self {
+            role,
+            listen_addr: "0.0.0.0:30303".to_string(),
+            max_peers: role.default_max_peers(),
+            connections: ConnectionConfig::for_max_peers(role.default_max_peers()),
+            static_nodes: Vec::new(),
+            trusted_peers: Vec::new(),
+            capabilities: role.default_capabilities(),
+            outbound: OutboundQueueConfig::default(),
+            header_serving: HeaderServingConfig::default(),
+            archive_serving: ArchiveServingConfig::default(),
+            seen_cache: SeenCacheConfig {
+                capacity: role.default_seen_cache_capacity(),
+                ..SeenCacheConfig::default()
+            },
+            tx_gossip: TxGossipConfig::default(),
+            reconnect_interval_ms: 5_000,
+            access: AccessList::default(),
+            access_list_path: None,
+            node_key_path: Some(PathBuf::from(DEFAULT_NODE_KEY_PATH)),
+            peer_record_max_age_secs: 86_400,
+            discovery: DiscoveryConfig::default(),
+            scoring: ScoringConfig::default(),
+            nat: NatConfig::default(),
+        }
+    }
+
+    /// Load configuration from environment variables
+    pub fn from_env() -> Self {
+        // The role is read first so the remaining variables override its defaults
+        let role = std::env::var("NODE_ROLE")
+            .ok()
+            .and_then(|role| role.parse().ok())
+            .unwrap_or_default();
+        let mut config = Self::for_role(role);
+
+        if let Ok(addr) = std::env::var("P2P_LISTEN_ADDR") {
+            config.listen_addr = addr;
+        }
+
+        if let Ok(max_peers) = std::env::var("P2P_MAX_PEERS") {
+            config.max_peers = max_peers.parse().unwrap_or(config.max_peers);
+            config.connections = ConnectionConfig::for_max_peers(config.max_peers);
+        }
+
+        if let Ok(max_inbound) = std::env::var("P2P_MAX_INBOUND") {
+            config.connections.max_inbound = max_inbound.parse().unwrap_or(config.connections.max_inbound);
+        }
+
+        if let Ok(max_outbound) = std::env::var("P2P_MAX_OUTBOUND") {
+            config.connections.max_outbound = max_outbound.parse().unwrap_or(config.connections.max_outbound);
+        }
+
+        if let Ok(backoff) = std::env::var("P2P_DIAL_BACKOFF_MAX_MS") {
+            config.connections.dial_backoff_max_ms = backoff.parse().unwrap_or(config.connections.dial_backoff_max_ms);
+        }
+
+        if let Ok(nodes) = std::env::var("P2P_STATIC_NODES") {
+            config.static_nodes = split_list(&nodes);
+        }
+
+        if let Ok(peers) = std::env::var("P2P_TRUSTED_PEERS") {
+            config.trusted_peers = split_list(&peers);
+        }
+
+        // An unparseable list keeps the default capabilities
+        if let Ok(caps) = std::env::var("P2P_CAPABILITIES") {
+            config.capabilities = Capabilities::parse_list(&caps).unwrap_or(config.capabilities);
+        }
+
+        if let Ok(interval) = std::env::var("P2P_RECONNECT_INTERVAL_MS") {
+            config.reconnect_interval_ms = interval.parse().unwrap_or(config.reconnect_interval_ms);
+        }
+
+        // Comma-separated CIDR ranges or peer ids; unparseable entries are left to validate()
+        if let Ok(allow) = std::env::var("P2P_ALLOWLIST") {
+            config.access.allow = split_list(&allow);
+        }
+
+        if let Ok(deny) = std::env::var("P2P_DENYLIST") {
+            config.access.deny = split_list(&deny);
+        }
+
+        if let Ok(path) = std::env::var("P2P_ACCESS_LIST_PATH") {
+            config.access_list_path = Some(PathBuf::from(path));
+        }
+
+        if let Ok(path) = std::env::var("P2P_NODE_KEY_PATH") {
+            config.node_key_path = Some(PathBuf::from(path));
+        }
+
+        if let Ok(max_age) = std::env::var("P2P_PEER_RECORD_MAX_AGE_SECS") {
+            config.peer_record_max_age_secs = max_age.parse().unwrap_or(config.peer_record_max_age_secs);
+        }
+
+        if let Ok(nodes) = std::env::var("P2P_BOOTNODES") {
+            config.discovery.bootnodes = split_list(&nodes);
+        }
+
+        if let Ok(interval) = std::env::var("P2P_RANDOM_WALK_INTERVAL_SECS") {
+            config.discovery.random_walk_interval_secs =
+                interval.parse().unwrap_or(config.discovery.random_walk_interval_secs);
+        }
+
+        if let Ok(enabled) = std::env::var("P2P_NAT") {
+            config.nat.enabled = enabled.parse().unwrap_or(config.nat.enabled);
+        }
+
+        // An unparseable address is ignored and discovery runs as usual
+        if let Ok(addr) = std::env::var("P2P_EXTERNAL_ADDR") {
+            config.nat.external_addr = addr.parse().ok();
+        }
+
+        if let Ok(threshold) = std::env::var("P2P_BAN_THRESHOLD") {
+            config.scoring.ban_threshold = threshold.parse().unwrap_or(config.scoring.ban_threshold);
+        }
+
+        if let Ok(duration) = std::env::var("P2P_BAN_DURATION_SECS") {
+            config.scoring.ban_duration_secs = duration.parse().unwrap_or(config.scoring.ban_duration_secs);
+        }
+
+        config
+    }
+
+    /// Validate configuration, stopping at the first problem
+    pub fn validate(&self) -> Result<(), String> {
+        let mut report = ConfigReport::new();
+        self.check(&mut report);
+        match report.issues().first() {
+            Some(issue) => Err(issue.message.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Record every problem in `report`, under field paths relative to this section
+    pub fn check(&self, report: &mut ConfigReport) {
+        report.check(
+            self.listen_addr.parse::<std::net::SocketAddr>().is_err(),
+            "listen_addr",
+            format!("Invalid listen address: {}", self.listen_addr),
+        );
+
+        report.check(self.max_peers == 0, "max_peers", "max_peers must be greater than 0");
+
+        report.adopt("connections", self.connections.validate());
+
+        // The role switches subsystems on and off; the advertised capabilities must agree with it
+        let subsystems = self.role.subsystems();
+        report.check(
+            self.role == NodeRole::RelayOnly && !self.capabilities.contains(Capabilities::BLOCK_RELAY),
+            "capabilities",
+            "relay_only nodes must advertise block_relay",
+        );
+        report.check(
+            subsystems.archival_serving && !self.capabilities.contains(Capabilities::SNAPSHOT_SERVING),
+            "capabilities",
+            format!("{} nodes must advertise snapshot_serving", self.role),
+        );
+        report.check(
+            subsystems.archival_serving && !self.capabilities.contains(Capabilities::ARCHIVE_SERVING),
+            "capabilities",
+            format!("{} nodes must advertise archive_serving", self.role),
+        );
+        report.check(
+            !subsystems.relayer && !subsystems.consensus && self.capabilities.contains(Capabilities::BLOCK_RELAY),
+            "capabilities",
+            format!("{} nodes do not relay blocks and must not advertise block_relay", self.role),
+        );
+
+        for (index, node) in self.static_nodes.iter().enumerate() {
+            report.adopt(&format!("static_nodes[{}]", index), node.parse::<StaticNode>().map(|_| ()));
+        }
+
+        report.section("outbound", |outbound| {
+            outbound.check(
+                self.outbound.capacity_per_class == 0,
+                "capacity_per_class",
+                "Outbound queue capacity must be greater than 0",
+            );
+            // Normal traffic is promoted ahead of low-priority traffic, never after it
+            outbound.check(
+                self.outbound.normal_max_delay_ms > self.outbound.low_max_delay_ms,
+                "normal_max_delay_ms",
+                format!(
+                    "Normal priority delay {}ms exceeds the low priority delay {}ms",
+                    self.outbound.normal_max_delay_ms, self.outbound.low_max_delay_ms
+                ),
+            );
+        });
+
+        report.section("header_serving", |headers| {
+            let serving = &self.header_serving;
+            headers.check(
+                serving.max_headers_per_request == 0 || serving.headers_per_second == 0,
+                "",
+                "Header serving limits must be greater than 0",
+            );
+            // A burst smaller than one full request would reject every full request
+            headers.check(
+                serving.burst < serving.max_headers_per_request,
+                "burst",
+                "Header serving burst must cover max_headers_per_request",
+            );
+        });
+
+        report.adopt("archive_serving", self.archive_serving.validate());
+
+        report.check(
+            self.seen_cache.capacity == 0 || self.seen_cache.ttl_secs == 0,
+            "seen_cache",
+            "Seen cache capacity and ttl must be greater than 0",
+        );
+
+        report.adopt("tx_gossip", self.tx_gossip.validate());
+
+        report.check(
+            self.peer_record_max_age_secs <= 0,
+            "peer_record_max_age_secs",
+            "peer_record_max_age_secs must be greater than 0",
+        );
+
+        report.check(
+            self.reconnect_interval_ms == 0,
+            "reconnect_interval_ms",
+            "reconnect_interval_ms must be greater than 0",
+        );
+
+        report.adopt("discovery", self.discovery.validate());
+
+        report.adopt("scoring", self.scoring.validate());
+
+        report.adopt("nat", self.nat.validate());
+
+        report.adopt("access", self.access.validate());
+    }
+
+    /// The node identity, loaded from `node_key_path` or created there
+    pub fn identity(&self) -> crate::Result<NodeIdentity> {
+        match &self.node_key_path {
+            Some(path) => NodeIdentity::load_or_generate(path),
+            None => Ok(NodeIdentity::generate()),
+        }
+    }
+
+    /// Parsed `static_nodes`; call after `validate()`
+    pub fn static_nodes(&self) -> Vec<StaticNode> {
+        self.static_nodes.iter().filter_map(|node| node.parse().ok()).collect()
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
@@ -0,0 +1,315 @@
+// p2p/p2p-network/src/connections.rs
+//! Inbound and outbound connection slots, eviction and dial backoff.
+//!
+//! Regular peers are split by who opened the connection: outbound slots are
+//! filled by our own dials, inbound slots by peers dialing us, so a flood of
+//! inbound connections cannot crowd out the peers we chose. When the inbound
+//! slots are full, a newcomer replaces the lowest-scored inbound peer if it
+//! scores better; otherwise it is turned away. Static and trusted peers use
+//! the dedicated slots of the `PeerManager` and are never evicted.
+//!
+//! Addresses that fail to connect are retried with exponential backoff,
+//! starting at `dial_backoff_base_ms` and doubling up to `dial_backoff_max_ms`.
+use retry::Backoff;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crate::peer_manager::{PeerKind, PeerManager};
+use crate::{NetworkError, PeerId, Result};
+
+/// Who opened a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Direction::Inbound => write!(f, "inbound"),
+            Direction::Outbound => write!(f, "outbound"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionConfig {
+    /// Regular peers that dialed us
+    pub max_inbound: usize,
+    /// Regular peers we dialed
+    pub max_outbound: usize,
+    /// Wait after the first failed dial of an address
+    pub dial_backoff_base_ms: u64,
+    /// Longest wait between dials of an unreachable address
+    pub dial_backoff_max_ms: u64,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self::for_max_peers(50)
+    }
+}
+
+impl ConnectionConfig {
+    /// Split `max_peers` into a few outbound slots and the rest inbound
+    pub fn for_max_peers(max_peers: usize) -> Self {
+        let max_outbound = (max_peers / 4).clamp(1, 8);
+        Self {
+            max_inbound: max_peers.saturating_sub(max_outbound),
+            max_outbound,
+            dial_backoff_base_ms: 1_000,
+            dial_backoff_max_ms: 300_000,
+        }
+    }
+
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.max_outbound == 0 {
+            return Err("max_outbound must be greater than 0".to_string());
+        }
+        if self.dial_backoff_base_ms == 0 {
+            return Err("dial_backoff_base_ms must be greater than 0".to_string());
+        }
+        if self.dial_backoff_base_ms > self.dial_backoff_max_ms {
+            return Err("dial_backoff_base_ms must not exceed dial_backoff_max_ms".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn limit(&self, direction: Direction) -> usize {
+        match direction {
+            Direction::Inbound => self.max_inbound,
+            Direction::Outbound => self.max_outbound,
+        }
+    }
+
+    /// Wait before the next dial after `failures` consecutive failures
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let base = Duration::from_millis(self.dial_backoff_base_ms);
+        Backoff::Exponential { base, max: Duration::from_millis(self.dial_backoff_max_ms) }.delay(failures)
+    }
+}
+
+/// Outcome of a successful `ConnectionManager::admit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Admission {
+    pub kind: PeerKind,
+    /// Peer to disconnect first to free the slot
+    pub evict: Option<PeerId>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DialBackoff {
+    failures: u32,
+    next_at: Instant,
+}
+
+/// Tracks the direction of each connection and the backoff of failed dials
+pub struct ConnectionManager {
+    config: ConnectionConfig,
+    directions: HashMap<PeerId, Direction>,
+    backoff: HashMap<SocketAddr, DialBackoff>,
+}
+
+impl ConnectionManager {
+    pub fn new(config: &ConnectionConfig) -> Self {
+        Self { config: config.clone(), directions: HashMap::new(), backoff: HashMap::new() }
+    }
+
+    /// Connected regular peers in `direction`
+    pub fn count(&self, peers: &PeerManager, direction: Direction) -> usize {
+        self.regular_peers(peers, direction).count()
+    }
+
+    /// Outbound slots our dialer may still fill
+    pub fn free_outbound_slots(&self, peers: &PeerManager) -> usize {
+        let total_free = peers.max_peers().saturating_sub(peers.regular_peer_count());
+        self.config.max_outbound.saturating_sub(self.count(peers, Direction::Outbound)).min(total_free)
+    }
+
+    /// Decide whether a peer may connect in `direction`, picking the peer to
+    /// evict when the inbound slots are full. The caller disconnects the
+    /// evicted peer before completing the handshake.
+    pub fn admit(
+        &self,
+        peers: &PeerManager,
+        peer_id: &str,
+        direction: Direction,
+        now: Instant,
+    ) -> Result<Admission> {
+        let kind = peers.kind_of(peer_id);
+        let reject = |reason: String| {
+            Err(NetworkError::PeerRejected { peer_id: peer_id.to_string(), reason })
+        };
+
+        if peers.is_connected(peer_id) {
+            return reject("already connected".to_string());
+        }
+        if kind.is_protected() {
+            return Ok(Admission { kind, evict: None });
+        }
+        if peers.is_banned(peer_id, now) {
+            return reject("banned".to_string());
+        }
+
+        let full = self.count(peers, direction) >= self.config.limit(direction)
+            || peers.regular_peer_count() >= peers.max_peers();
+        if !full {
+            return Ok(Admission { kind, evict: None });
+        }
+        if direction == Direction::Outbound {
+            return reject("no free outbound slots".to_string());
+        }
+
+        match self.eviction_candidate(peers, now) {
+            Some((victim, score)) if score < peers.score(peer_id, now) => {
+                Ok(Admission { kind, evict: Some(victim) })
+            }
+            _ => reject(format!("no free {} slots", direction)),
+        }
+    }
+
+    /// Lowest-scored inbound regular peer, the most recently connected on a
+    /// tie so long-standing peers are kept
+    pub fn eviction_candidate(&self, peers: &PeerManager, now: Instant) -> Option<(PeerId, i64)> {
+        self.regular_peers(peers, Direction::Inbound)
+            .map(|(id, connected_at)| (id, peers.score(id, now), connected_at))
+            .min_by(|(a_id, a_score, a_at), (b_id, b_score, b_at)| {
+                a_score.cmp(b_score).then_with(|| b_at.cmp(a_at)).then_with(|| a_id.cmp(b_id))
+            })
+            .map(|(id, score, _)| (id.clone(), score))
+    }
+
+    pub fn on_connected(&mut self, peer_id: &str, addr: SocketAddr, direction: Direction) {
+        self.directions.insert(peer_id.to_string(), direction);
+        if direction == Direction::Outbound {
+            self.backoff.remove(&addr);
+        }
+    }
+
+    pub fn on_disconnected(&mut self, peer_id: &str) {
+        self.directions.remove(peer_id);
+    }
+
+    pub fn direction(&self, peer_id: &str) -> Option<Direction> {
+        self.directions.get(peer_id).copied()
+    }
+
+    /// Whether `addr` is out of backoff
+    pub fn can_dial(&self, addr: &SocketAddr, now: Instant) -> bool {
+        !self.backoff.get(addr).is_some_and(|backoff| backoff.next_at > now)
+    }
+
+    /// Record a failed dial, returning how long to wait before the next one
+    pub fn on_dial_failed(&mut self, addr: SocketAddr, now: Instant) -> Duration {
+        let entry = self.backoff.entry(addr).or_insert(DialBackoff { failures: 0, next_at: now });
+        entry.failures = entry.failures.saturating_add(1);
+        let delay = self.config.backoff(entry.failures);
+        entry.next_at = now + delay;
+        delay
+    }
+
+    /// Forget addresses that have been out of backoff for a full maximum
+    /// backoff period, bounding the table to recently failing addresses
+    pub fn prune(&mut self, now: Instant) {
+        let idle = Duration::from_millis(self.config.dial_backoff_max_ms);
+        self.backoff.retain(|_, backoff| now.saturating_duration_since(backoff.next_at) < idle);
+    }
+
+    fn regular_peers<'a>(
+        &'a self,
+        peers: &'a PeerManager,
+        direction: Direction,
+    ) -> impl Iterator<Item = (&'a PeerId, Instant)> + 'a {
+        peers
+            .connected_peers()
+            .filter(|(_, peer)| peer.kind == PeerKind::Regular)
+            .filter(move |(id, _)| self.directions.get(*id) == Some(&direction))
+            .map(|(id, peer)| (id, peer.connected_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capabilities::Capabilities;
+    use crate::config::NetworkConfig;
+    use crate::protocol::PROTOCOL_VERSION;
+    use crate::scoring::Offence;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, 1], port))
+    }
+
+    fn connect(
+        peers: &mut PeerManager,
+        connections: &mut ConnectionManager,
+        id: &str,
+        port: u16,
+        direction: Direction,
+    ) {
+        let now = Instant::now();
+        connections.admit(peers, id, direction, now).unwrap();
+        peers.on_connected(id, addr(port), Capabilities::BLOCK_RELAY, PROTOCOL_VERSION, now);
+        connections.on_connected(id, addr(port), direction);
+    }
+
+    #[test]
+    fn test_inbound_eviction_prefers_lowest_score() {
+        let config = NetworkConfig {
+            max_peers: 10,
+            trusted_peers: vec!["Trusted".to_string()],
+            connections: ConnectionConfig { max_inbound: 2, max_outbound: 1, ..Default::default() },
+            ..Default::default()
+        };
+        config.validate().unwrap();
+        let now = Instant::now();
+        let mut peers = PeerManager::new(&config, now);
+        let mut connections = ConnectionManager::new(&config.connections);
+
+        connect(&mut peers, &mut connections, "Out", 1, Direction::Outbound);
+        assert!(connections.admit(&peers, "Out2", Direction::Outbound, now).is_err());
+        assert_eq!(connections.free_outbound_slots(&peers), 0);
+
+        connect(&mut peers, &mut connections, "Good", 2, Direction::Inbound);
+        connect(&mut peers, &mut connections, "Flaky", 3, Direction::Inbound);
+        peers.report("Flaky", Offence::Timeout, now);
+
+        // A clean newcomer displaces the worst inbound peer, never an outbound one
+        let admission = connections.admit(&peers, "New", Direction::Inbound, now).unwrap();
+        assert_eq!(admission.evict, Some("Flaky".to_string()));
+
+        // With every inbound peer clean, newcomers are turned away
+        peers.on_disconnected("Flaky", now);
+        connections.on_disconnected("Flaky");
+        connect(&mut peers, &mut connections, "New", 3, Direction::Inbound);
+        assert!(connections.admit(&peers, "Another", Direction::Inbound, now).is_err());
+
+        // Protected peers bypass the limits
+        assert_eq!(connections.admit(&peers, "Trusted", Direction::Inbound, now).unwrap().evict, None);
+        assert_eq!(connections.count(&peers, Direction::Inbound), 2);
+    }
+
+    #[test]
+    fn test_dial_backoff_doubles_up_to_the_cap() {
+        let config = ConnectionConfig { dial_backoff_base_ms: 1_000, dial_backoff_max_ms: 5_000, ..Default::default() };
+        let mut connections = ConnectionManager::new(&config);
+        let now = Instant::now();
+        let target = addr(30303);
+
+        let delays: Vec<u64> = (0..4).map(|_| connections.on_dial_failed(target, now).as_millis() as u64).collect();
+        assert_eq!(delays, vec![1_000, 2_000, 4_000, 5_000]);
+        assert!(!connections.can_dial(&target, now));
+        assert!(connections.can_dial(&target, now + Duration::from_secs(5)));
+
+        // A successful outbound connection clears the backoff
+        connections.on_connected("Peer", target, Direction::Outbound);
+        assert!(connections.can_dial(&target, now));
+
+        assert!(ConnectionConfig { dial_backoff_base_ms: 10, dial_backoff_max_ms: 5, ..Default::default() }
+            .validate()
+            .is_err());
+    }
+}
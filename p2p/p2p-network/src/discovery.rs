@@ -0,0 +1,588 @@
+// p2p/p2p-network/src/discovery.rs
+//! Kademlia peer discovery.
+//!
+//! Nodes are placed in a 256-bit key space by hashing their peer id, and the
+//! routing table keeps up to `bucket_size` peers per power-of-two distance
+//! from the local key. Lookups walk towards a target by asking the closest
+//! known peers for their closest peers, `lookup_parallelism` at a time.
+//! Bootnodes seed the first lookup; random walks towards random keys keep the
+//! table filled as peers come and go.
+//!
+//! Nothing here does I/O: callers send `FindNode`, feed back the `Neighbors`
+//! replies and persist the table through a `PeerStore`.
+use blockchain_core::hash_data;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use storage_traits::{KnownPeer, PeerStore};
+
+use crate::identity::{PeerRecord, PeerRecordBook};
+use crate::peer_manager::StaticNode;
+use crate::{NetworkError, PeerId, Result};
+
+/// Position of a node in the key space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NodeKey(pub [u8; 32]);
+
+impl NodeKey {
+    pub fn of(peer_id: &str) -> Self {
+        NodeKey(hash_data(peer_id.as_bytes()))
+    }
+
+    pub fn random() -> Self {
+        NodeKey(rand::random())
+    }
+
+    /// XOR distance, compared as a big-endian integer
+    pub fn distance(&self, other: &NodeKey) -> [u8; 32] {
+        let mut distance = [0u8; 32];
+        for (i, byte) in distance.iter_mut().enumerate() {
+            *byte = self.0[i] ^ other.0[i];
+        }
+        distance
+    }
+
+    /// Bucket `other` falls in: the index of the highest differing bit,
+    /// `None` for the key itself
+    fn bucket_index(&self, other: &NodeKey) -> Option<usize> {
+        let distance = self.distance(other);
+        let first = distance.iter().position(|byte| *byte != 0)?;
+        Some(255 - (first * 8 + distance[first].leading_zeros() as usize))
+    }
+}
+
+/// Discovery settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryConfig {
+    /// Peers the first lookup starts from, as `peer_id@ip:port`
+    pub bootnodes: Vec<String>,
+    /// Peers kept per bucket, and the size of a lookup result
+    pub bucket_size: usize,
+    /// `FindNode` requests a lookup keeps in flight
+    pub lookup_parallelism: usize,
+    /// Delay between random walks
+    pub random_walk_interval_secs: u64,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            bootnodes: Vec::new(),
+            bucket_size: 16,
+            lookup_parallelism: 3,
+            random_walk_interval_secs: 300,
+        }
+    }
+}
+
+impl DiscoveryConfig {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        for node in &self.bootnodes {
+            node.parse::<StaticNode>()?;
+        }
+        if self.bucket_size == 0 || self.lookup_parallelism == 0 {
+            return Err("Discovery bucket_size and lookup_parallelism must be greater than 0".to_string());
+        }
+        if self.random_walk_interval_secs == 0 {
+            return Err("random_walk_interval_secs must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Ask a peer for the peers it knows closest to `target`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FindNode {
+    pub target: NodeKey,
+}
+
+/// Reply to `FindNode`, as signed records so addresses cannot be forged
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighbors {
+    pub records: Vec<PeerRecord>,
+}
+
+/// Routing table entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeEntry {
+    pub peer_id: PeerId,
+    pub addr: SocketAddr,
+    /// From the peer's signed record; `None` for bootnodes not yet heard from
+    pub protocol_version: Option<u32>,
+    /// Unix seconds
+    pub last_seen: i64,
+}
+
+impl NodeEntry {
+    fn key(&self) -> NodeKey {
+        NodeKey::of(&self.peer_id)
+    }
+
+    /// Entry for a verified record, dialed at its first advertised address
+    fn from_record(record: &PeerRecord) -> Option<Self> {
+        Some(NodeEntry {
+            peer_id: record.peer_id.clone(),
+            addr: *record.addresses.first()?,
+            protocol_version: Some(record.protocol_version),
+            last_seen: record.timestamp,
+        })
+    }
+}
+
+impl From<KnownPeer> for NodeEntry {
+    fn from(peer: KnownPeer) -> Self {
+        NodeEntry {
+            peer_id: peer.peer_id,
+            addr: peer.addr,
+            protocol_version: peer.protocol_version,
+            last_seen: peer.last_seen,
+        }
+    }
+}
+
+impl From<&NodeEntry> for KnownPeer {
+    fn from(entry: &NodeEntry) -> Self {
+        KnownPeer {
+            peer_id: entry.peer_id.clone(),
+            addr: entry.addr,
+            protocol_version: entry.protocol_version,
+            last_seen: entry.last_seen,
+        }
+    }
+}
+
+/// What `RoutingTable::insert` did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertOutcome {
+    Added,
+    /// Already known; moved to the most recently seen end of its bucket
+    Refreshed,
+    /// The bucket is full. Long-lived peers are preferred, so the newcomer is
+    /// dropped unless `oldest` fails to answer and is evicted.
+    BucketFull { oldest: PeerId },
+    /// The local node
+    Ignored,
+}
+
+/// k-buckets ordered least recently seen first
+#[derive(Debug)]
+pub struct RoutingTable {
+    local: NodeKey,
+    bucket_size: usize,
+    buckets: Vec<Vec<NodeEntry>>,
+}
+
+impl RoutingTable {
+    pub fn new(local_peer_id: &str, bucket_size: usize) -> Self {
+        Self {
+            local: NodeKey::of(local_peer_id),
+            bucket_size,
+            buckets: vec![Vec::new(); 256],
+        }
+    }
+
+    pub fn insert(&mut self, entry: NodeEntry) -> InsertOutcome {
+        let Some(index) = self.local.bucket_index(&entry.key()) else {
+            return InsertOutcome::Ignored;
+        };
+        let bucket = &mut self.buckets[index];
+
+        if let Some(position) = bucket.iter().position(|known| known.peer_id == entry.peer_id) {
+            let mut known = bucket.remove(position);
+            // A bootnode keeps the version learned from its record
+            known.protocol_version = entry.protocol_version.or(known.protocol_version);
+            known.addr = entry.addr;
+            known.last_seen = known.last_seen.max(entry.last_seen);
+            bucket.push(known);
+            return InsertOutcome::Refreshed;
+        }
+        if bucket.len() >= self.bucket_size {
+            return InsertOutcome::BucketFull { oldest: bucket[0].peer_id.clone() };
+        }
+        bucket.push(entry);
+        InsertOutcome::Added
+    }
+
+    /// Drop a peer that stopped answering; returns whether it was present
+    pub fn evict(&mut self, peer_id: &str) -> bool {
+        let Some(index) = self.local.bucket_index(&NodeKey::of(peer_id)) else {
+            return false;
+        };
+        let bucket = &mut self.buckets[index];
+        let before = bucket.len();
+        bucket.retain(|entry| entry.peer_id != peer_id);
+        bucket.len() != before
+    }
+
+    /// Up to `count` known peers closest to `target`, closest first
+    pub fn closest(&self, target: &NodeKey, count: usize) -> Vec<NodeEntry> {
+        let mut entries: Vec<&NodeEntry> = self.entries().collect();
+        entries.sort_by_key(|entry| target.distance(&entry.key()));
+        entries.into_iter().take(count).cloned().collect()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &NodeEntry> {
+        self.buckets.iter().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueryState {
+    NotQueried,
+    InFlight,
+    Responded,
+    Failed,
+}
+
+/// One iterative lookup towards `target`
+#[derive(Debug)]
+pub struct Lookup {
+    target: NodeKey,
+    local: NodeKey,
+    parallelism: usize,
+    result_size: usize,
+    candidates: BTreeMap<[u8; 32], (NodeEntry, QueryState)>,
+}
+
+impl Lookup {
+    fn new(target: NodeKey, local: NodeKey, config: &DiscoveryConfig, seeds: Vec<NodeEntry>) -> Self {
+        let mut lookup = Self {
+            target,
+            local,
+            parallelism: config.lookup_parallelism,
+            result_size: config.bucket_size,
+            candidates: BTreeMap::new(),
+        };
+        for seed in seeds {
+            lookup.add_candidate(seed);
+        }
+        lookup
+    }
+
+    pub fn target(&self) -> NodeKey {
+        self.target
+    }
+
+    fn add_candidate(&mut self, entry: NodeEntry) {
+        let key = entry.key();
+        if key != self.local {
+            self.candidates
+                .entry(self.target.distance(&key))
+                .or_insert((entry, QueryState::NotQueried));
+        }
+    }
+
+    /// The `result_size` closest candidates that have not failed
+    fn frontier(&self) -> impl Iterator<Item = &(NodeEntry, QueryState)> {
+        self.candidates
+            .values()
+            .filter(|(_, state)| *state != QueryState::Failed)
+            .take(self.result_size)
+    }
+
+    /// Peers to send `FindNode` to now, marked in flight
+    pub fn next_queries(&mut self) -> Vec<NodeEntry> {
+        let in_flight = self.frontier().filter(|(_, state)| *state == QueryState::InFlight).count();
+        let wanted: Vec<[u8; 32]> = self
+            .candidates
+            .iter()
+            .filter(|(_, (_, state))| *state != QueryState::Failed)
+            .take(self.result_size)
+            .filter(|(_, (_, state))| *state == QueryState::NotQueried)
+            .take(self.parallelism.saturating_sub(in_flight))
+            .map(|(distance, _)| *distance)
+            .collect();
+
+        let mut queries = Vec::new();
+        for distance in wanted {
+            if let Some((entry, state)) = self.candidates.get_mut(&distance) {
+                *state = QueryState::InFlight;
+                queries.push(entry.clone());
+            }
+        }
+        queries
+    }
+
+    fn set_state(&mut self, peer_id: &str, state: QueryState) {
+        let distance = self.target.distance(&NodeKey::of(peer_id));
+        if let Some((_, current)) = self.candidates.get_mut(&distance) {
+            *current = state;
+        }
+    }
+
+    /// Record `from`'s answer and the peers it named
+    pub fn on_response(&mut self, from: &str, entries: Vec<NodeEntry>) {
+        self.set_state(from, QueryState::Responded);
+        for entry in entries {
+            self.add_candidate(entry);
+        }
+    }
+
+    /// `from` timed out or returned garbage; it no longer counts towards the result
+    pub fn on_failure(&mut self, from: &str) {
+        self.set_state(from, QueryState::Failed);
+    }
+
+    /// Done once every peer among the closest `result_size` has answered or failed
+    pub fn is_finished(&self) -> bool {
+        self.frontier().all(|(_, state)| *state == QueryState::Responded)
+    }
+
+    /// Closest peers that answered
+    pub fn result(&self) -> Vec<NodeEntry> {
+        self.frontier()
+            .filter(|(_, state)| *state == QueryState::Responded)
+            .map(|(entry, _)| entry.clone())
+            .collect()
+    }
+}
+
+/// Routing table, bootnodes and the random-walk schedule
+pub struct Discovery {
+    local_peer_id: PeerId,
+    config: DiscoveryConfig,
+    table: RoutingTable,
+    bootnodes: Vec<StaticNode>,
+    random_walk_interval: Duration,
+    next_walk: Instant,
+}
+
+impl Discovery {
+    /// Call after `DiscoveryConfig::validate()`
+    pub fn new(local_peer_id: PeerId, config: &DiscoveryConfig, now: Instant) -> Self {
+        let random_walk_interval = Duration::from_secs(config.random_walk_interval_secs);
+        Self {
+            table: RoutingTable::new(&local_peer_id, config.bucket_size),
+            local_peer_id,
+            config: config.clone(),
+            bootnodes: config.bootnodes.iter().filter_map(|node| node.parse().ok()).collect(),
+            random_walk_interval,
+            next_walk: now + random_walk_interval,
+        }
+    }
+
+    pub fn table(&self) -> &RoutingTable {
+        &self.table
+    }
+
+    pub fn table_mut(&mut self) -> &mut RoutingTable {
+        &mut self.table
+    }
+
+    /// Lookup of our own key, which fills the buckets nearest to us.
+    ///
+    /// Seeded from the table and the bootnodes, so it also works after a
+    /// restore left the bootnodes unreachable.
+    pub fn bootstrap(&self) -> Lookup {
+        self.lookup(NodeKey::of(&self.local_peer_id))
+    }
+
+    /// Lookup towards `target`, starting from the closest known peers
+    pub fn lookup(&self, target: NodeKey) -> Lookup {
+        let mut seeds = self.table.closest(&target, self.config.bucket_size);
+        seeds.extend(self.bootnodes.iter().map(|node| NodeEntry {
+            peer_id: node.peer_id.clone(),
+            addr: node.addr,
+            protocol_version: None,
+            last_seen: 0,
+        }));
+        Lookup::new(target, self.table.local, &self.config, seeds)
+    }
+
+    /// A random walk if one is due
+    pub fn due_random_walk(&mut self, now: Instant) -> Option<Lookup> {
+        if now < self.next_walk {
+            return None;
+        }
+        self.next_walk = now + self.random_walk_interval;
+        Some(self.lookup(NodeKey::random()))
+    }
+
+    /// Answer a `FindNode` with the records we hold for the closest peers
+    pub fn answer(&self, request: &FindNode, records: &PeerRecordBook) -> Neighbors {
+        let records = self
+            .table
+            .closest(&request.target, self.config.bucket_size)
+            .iter()
+            .filter_map(|entry| records.get(&entry.peer_id).cloned())
+            .collect();
+        Neighbors { records }
+    }
+
+    /// Verify `from`'s reply, learn the peers in it and advance `lookup`.
+    ///
+    /// Records that fail verification are dropped. Returns the oldest peers
+    /// of buckets that had no room; the caller pings them and evicts those
+    /// that do not answer.
+    pub fn on_neighbors(
+        &mut self,
+        lookup: &mut Lookup,
+        from: &str,
+        reply: Neighbors,
+        records: &mut PeerRecordBook,
+        now: i64,
+    ) -> Vec<PeerId> {
+        let mut entries = Vec::new();
+        for record in reply.records {
+            if records.insert(record.clone(), now).is_err() {
+                continue;
+            }
+            if let Some(entry) = NodeEntry::from_record(&record) {
+                entries.push(entry);
+            }
+        }
+
+        let mut to_ping = HashSet::new();
+        for entry in &entries {
+            if let InsertOutcome::BucketFull { oldest } = self.table.insert(entry.clone()) {
+                to_ping.insert(oldest);
+            }
+        }
+        lookup.on_response(from, entries);
+        to_ping.into_iter().collect()
+    }
+
+    /// Save the routing table
+    pub async fn persist(&self, store: &dyn PeerStore) -> Result<()> {
+        let peers: Vec<KnownPeer> = self.table.entries().map(KnownPeer::from).collect();
+        store
+            .save_known_peers(&peers)
+            .await
+            .map_err(|e| NetworkError::Storage(e.to_string()))
+    }
+
+    /// Refill the routing table from `store`, returning how many peers were added
+    pub async fn restore(&mut self, store: &dyn PeerStore, limit: usize) -> Result<usize> {
+        let peers = store
+            .load_known_peers(limit)
+            .await
+            .map_err(|e| NetworkError::Storage(e.to_string()))?;
+
+        Ok(peers
+            .into_iter()
+            .map(|peer| self.table.insert(peer.into()))
+            .filter(|outcome| *outcome == InsertOutcome::Added)
+            .count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capabilities::Capabilities;
+    use crate::identity::NodeIdentity;
+    use crate::protocol::PROTOCOL_VERSION;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn entry(peer_id: &str, port: u16) -> NodeEntry {
+        NodeEntry {
+            peer_id: peer_id.to_string(),
+            addr: SocketAddr::from(([10, 0, 0, 1], port)),
+            protocol_version: Some(PROTOCOL_VERSION),
+            last_seen: NOW,
+        }
+    }
+
+    fn config() -> DiscoveryConfig {
+        DiscoveryConfig {
+            bootnodes: vec!["boot@10.0.0.9:30303".to_string()],
+            bucket_size: 2,
+            lookup_parallelism: 2,
+            random_walk_interval_secs: 60,
+        }
+    }
+
+    #[test]
+    fn test_buckets_keep_long_lived_peers() {
+        let mut table = RoutingTable::new("local", 2);
+        assert_eq!(table.insert(entry("local", 1)), InsertOutcome::Ignored);
+
+        // Three peers that fall in the same bucket as peer-0
+        let local = NodeKey::of("local");
+        let index = local.bucket_index(&NodeKey::of("peer-0")).unwrap();
+        let same_bucket: Vec<String> = (0..)
+            .map(|i| format!("peer-{}", i))
+            .filter(|id| local.bucket_index(&NodeKey::of(id)) == Some(index))
+            .take(3)
+            .collect();
+
+        assert_eq!(table.insert(entry(&same_bucket[0], 1)), InsertOutcome::Added);
+        assert_eq!(table.insert(entry(&same_bucket[1], 2)), InsertOutcome::Added);
+        assert_eq!(table.insert(entry(&same_bucket[0], 3)), InsertOutcome::Refreshed);
+        assert_eq!(
+            table.insert(entry(&same_bucket[2], 4)),
+            InsertOutcome::BucketFull { oldest: same_bucket[1].clone() }
+        );
+
+        assert!(table.evict(&same_bucket[1]));
+        assert_eq!(table.insert(entry(&same_bucket[2], 4)), InsertOutcome::Added);
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn test_lookup_converges_on_closest() {
+        let local = NodeKey::of("local");
+        let target = NodeKey::of("target");
+        let mut lookup = Lookup::new(target, local, &config(), vec![entry("a", 1), entry("b", 2), entry("c", 3)]);
+
+        let first = lookup.next_queries();
+        assert_eq!(first.len(), 2);
+        // Nothing more goes out while both requests are in flight
+        assert!(lookup.next_queries().is_empty());
+        assert!(!lookup.is_finished());
+
+        lookup.on_failure(&first[0].peer_id);
+        lookup.on_response(&first[1].peer_id, vec![entry("local", 9)]);
+        for peer in lookup.next_queries() {
+            lookup.on_response(&peer.peer_id, Vec::new());
+        }
+
+        assert!(lookup.is_finished());
+        let result = lookup.result();
+        assert_eq!(result.len(), 2);
+        assert!(result.iter().all(|peer| peer.peer_id != first[0].peer_id && peer.peer_id != "local"));
+        assert!(target.distance(&result[0].key()) < target.distance(&result[1].key()));
+    }
+
+    #[test]
+    fn test_neighbors_are_verified_before_use() {
+        let mut discovery = Discovery::new("local".to_string(), &config(), Instant::now());
+        let mut book = PeerRecordBook::new(3600);
+        let mut lookup = discovery.bootstrap();
+        assert_eq!(lookup.next_queries()[0].peer_id, "boot");
+
+        let identity = NodeIdentity::generate();
+        let record = identity
+            .sign_record(vec![SocketAddr::from(([10, 0, 0, 2], 30303))], PROTOCOL_VERSION, Capabilities::NONE, NOW);
+        let mut forged = record.clone();
+        forged.addresses = vec![SocketAddr::from(([10, 6, 6, 6], 30303))];
+        forged.peer_id = "forged".to_string();
+
+        let reply = Neighbors { records: vec![record, forged] };
+        discovery.on_neighbors(&mut lookup, "boot", reply, &mut book, NOW);
+        assert_eq!(discovery.table().len(), 1);
+        assert_eq!(discovery.table().entries().next().unwrap().peer_id, identity.peer_id());
+
+        let answer = discovery.answer(&FindNode { target: NodeKey::random() }, &book);
+        assert_eq!(answer.records.len(), 1);
+    }
+
+    #[test]
+    fn test_random_walk_schedule() {
+        let start = Instant::now();
+        let mut discovery = Discovery::new("local".to_string(), &config(), start);
+        assert!(discovery.due_random_walk(start).is_none());
+        assert!(discovery.due_random_walk(start + Duration::from_secs(60)).is_some());
+        assert!(discovery.due_random_walk(start + Duration::from_secs(61)).is_none());
+    }
+}
@@ -0,0 +1,86 @@
+// p2p/p2p-network/src/handshake.rs
+//! Accepting or refusing a peer from its handshake.
+//!
+//! Both sides send a `Handshake` first. A peer is kept only if it shares a
+//! protocol version with us and follows the same chain, identified by chain
+//! id and genesis hash; anything else is disconnected before a single block
+//! or transaction is exchanged.
+use std::net::SocketAddr;
+use storage_traits::{KnownPeer, PeerStore};
+
+use crate::protocol::{ChainIdentity, Handshake};
+use crate::versioning::negotiate_version;
+use crate::{NetworkError, Result};
+
+/// Check `remote` against the local chain, returning the negotiated protocol version
+pub fn check_handshake(local: &ChainIdentity, remote: &Handshake) -> Result<u32> {
+    let version = negotiate_version(remote)?;
+    let reject = |reason: String| NetworkError::PeerRejected { peer_id: remote.peer_id.clone(), reason };
+
+    let Some(chain) = remote.chain else {
+        if version >= 2 {
+            return Err(reject("handshake does not name a chain".to_string()));
+        }
+        // Version 1 peers cannot say; a wrong chain shows up in the first block they send
+        return Ok(version);
+    };
+    if chain.chain_id != local.chain_id {
+        return Err(reject(format!("peer follows chain {}, we follow {}", chain.chain_id, local.chain_id)));
+    }
+    if chain.genesis_hash != local.genesis_hash {
+        return Err(reject(format!(
+            "genesis {} differs from ours {}",
+            hex::encode(chain.genesis_hash),
+            hex::encode(local.genesis_hash)
+        )));
+    }
+    Ok(version)
+}
+
+/// Record an accepted peer and the height it reported
+pub async fn record_handshake(
+    store: &dyn PeerStore,
+    remote: &Handshake,
+    addr: SocketAddr,
+    protocol_version: u32,
+    now: i64,
+) -> Result<()> {
+    let peer = KnownPeer {
+        peer_id: remote.peer_id.clone(),
+        addr,
+        protocol_version: Some(protocol_version),
+        last_seen: now,
+    };
+    store
+        .record_connected_peer(&peer, remote.best_height)
+        .await
+        .map_err(|e| NetworkError::Storage(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capabilities::Capabilities;
+    use crate::role::NodeRole;
+
+    const CHAIN: ChainIdentity = ChainIdentity { chain_id: 7, genesis_hash: [1; 32] };
+
+    fn remote(chain: Option<ChainIdentity>) -> Handshake {
+        Handshake { chain, ..Handshake::new("peer".to_string(), 42, Capabilities::BLOCK_RELAY, NodeRole::Full, CHAIN) }
+    }
+
+    #[test]
+    fn test_incompatible_chains_rejected() {
+        assert_eq!(check_handshake(&CHAIN, &remote(Some(CHAIN))).unwrap(), 2);
+
+        let other_chain = ChainIdentity { chain_id: 8, ..CHAIN };
+        assert!(check_handshake(&CHAIN, &remote(Some(other_chain))).is_err());
+        let other_genesis = ChainIdentity { genesis_hash: [2; 32], ..CHAIN };
+        assert!(check_handshake(&CHAIN, &remote(Some(other_genesis))).is_err());
+        assert!(check_handshake(&CHAIN, &remote(None)).is_err());
+
+        // Version 1 handshakes carry no chain and are let through
+        let v1 = Handshake { protocol_version: 1, min_protocol_version: None, ..remote(None) };
+        assert_eq!(check_handshake(&CHAIN, &v1).unwrap(), 1);
+    }
+}
@@ -0,0 +1,175 @@
+// p2p/p2p-network/src/headers.rs
+use blockchain_core::{BlockHeader, BlockHeight};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use storage_traits::BlockchainStorage;
+
+use crate::{NetworkError, PeerId, Result};
+
+/// Request for `count` headers from `start`, leaving `skip` heights between each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GetHeaders {
+    pub start: BlockHeight,
+    pub count: u32,
+    pub skip: u32,
+}
+
+impl GetHeaders {
+    /// Requested heights, capped at `max_count` entries
+    pub fn heights(&self, max_count: u32) -> Vec<BlockHeight> {
+        let step = self.skip as u64 + 1;
+        let count = self.count.min(max_count) as u64;
+
+        (0..count)
+            .map_while(|i| i.checked_mul(step).and_then(|offset| self.start.checked_add(offset)))
+            .collect()
+    }
+}
+
+/// Reply to `GetHeaders`; shorter than requested when the chain ends first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockHeaders {
+    pub headers: Vec<BlockHeader>,
+}
+
+/// Limits on header serving to light clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeaderServingConfig {
+    /// Requests asking for more are truncated
+    pub max_headers_per_request: u32,
+    /// Sustained headers per second served to one peer
+    pub headers_per_second: u32,
+    /// Headers a peer may fetch in a burst before the rate applies
+    pub burst: u32,
+}
+
+impl Default for HeaderServingConfig {
+    fn default() -> Self {
+        Self {
+            max_headers_per_request: 192,
+            headers_per_second: 1_000,
+            burst: 2_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Per-peer token buckets, one token per header
+#[derive(Debug)]
+pub struct PeerRateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets: HashMap<PeerId, Bucket>,
+}
+
+impl PeerRateLimiter {
+    pub fn new(per_second: u32, burst: u32) -> Self {
+        Self {
+            rate: per_second as f64,
+            burst: burst as f64,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Take `cost` tokens from the peer's bucket, or none if it holds fewer
+    pub fn try_acquire(&mut self, peer_id: &str, cost: u32, now: Instant) -> bool {
+        let bucket = self.buckets.entry(peer_id.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated_at: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated_at = now;
+
+        if bucket.tokens < cost as f64 {
+            return false;
+        }
+        bucket.tokens -= cost as f64;
+        true
+    }
+
+    /// Drop a disconnected peer's bucket
+    pub fn forget(&mut self, peer_id: &str) {
+        self.buckets.remove(peer_id);
+    }
+}
+
+/// Answers `GetHeaders` from stored headers, never loading block bodies
+pub struct HeaderServer {
+    storage: Arc<dyn BlockchainStorage>,
+    max_headers_per_request: u32,
+    limiter: Mutex<PeerRateLimiter>,
+}
+
+impl HeaderServer {
+    pub fn new(storage: Arc<dyn BlockchainStorage>, config: &HeaderServingConfig) -> Self {
+        Self {
+            storage,
+            max_headers_per_request: config.max_headers_per_request,
+            limiter: Mutex::new(PeerRateLimiter::new(config.headers_per_second, config.burst)),
+        }
+    }
+
+    pub async fn serve(&self, peer_id: &str, request: &GetHeaders, now: Instant) -> Result<BlockHeaders> {
+        let heights = request.heights(self.max_headers_per_request);
+        if !self.limiter.lock().try_acquire(peer_id, heights.len() as u32, now) {
+            return Err(NetworkError::RateLimited { peer_id: peer_id.to_string() });
+        }
+
+        let headers = self
+            .storage
+            .get_block_headers(&heights)
+            .await
+            .map_err(|e| NetworkError::Storage(e.to_string()))?;
+        Ok(BlockHeaders { headers })
+    }
+
+    pub fn on_disconnected(&self, peer_id: &str) {
+        self.limiter.lock().forget(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_requested_heights() {
+        let request = GetHeaders { start: 10, count: 4, skip: 2 };
+        assert_eq!(request.heights(192), vec![10, 13, 16, 19]);
+        assert_eq!(request.heights(2), vec![10, 13]);
+
+        let contiguous = GetHeaders { start: 0, count: 3, skip: 0 };
+        assert_eq!(contiguous.heights(192), vec![0, 1, 2]);
+
+        // Never wraps past the maximum height
+        let overflowing = GetHeaders { start: u64::MAX - 1, count: 5, skip: 0 };
+        assert_eq!(overflowing.heights(192), vec![u64::MAX - 1, u64::MAX]);
+    }
+
+    #[test]
+    fn test_rate_limit_per_peer() {
+        let start = Instant::now();
+        let mut limiter = PeerRateLimiter::new(100, 200);
+
+        assert!(limiter.try_acquire("peer-a", 192, start));
+        assert!(!limiter.try_acquire("peer-a", 192, start));
+        // Other peers have their own budget
+        assert!(limiter.try_acquire("peer-b", 192, start));
+
+        // Refills at the configured rate, up to the burst size
+        assert!(limiter.try_acquire("peer-a", 192, start + Duration::from_secs(2)));
+        assert!(!limiter.try_acquire("peer-a", 192, start + Duration::from_secs(2)));
+        assert!(!limiter.try_acquire("peer-c", 201, start));
+    }
+}
@@ -0,0 +1,241 @@
+// p2p/p2p-network/src/identity.rs
+//! Node keys and signed peer records.
+//!
+//! A node's peer id is the hex-encoded ed25519 public key of its node key,
+//! so a record signed by that key proves the advertised addresses, version
+//! and capabilities came from the peer itself.
+use blockchain_core::signature::verify_signature;
+use blockchain_core::{hash_serializable, KeyPair, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+
+use crate::capabilities::Capabilities;
+use crate::{NetworkError, PeerId, Result};
+
+/// Accepted clock difference for record timestamps from the future
+const MAX_CLOCK_SKEW_SECS: i64 = 60;
+
+/// Long-lived node key
+#[derive(Debug, Clone)]
+pub struct NodeIdentity {
+    key: KeyPair,
+}
+
+impl NodeIdentity {
+    pub fn generate() -> Self {
+        Self { key: KeyPair::generate(SignatureScheme::Ed25519) }
+    }
+
+    /// Load the hex secret key at `path`, creating one on first start
+    pub fn load_or_generate(path: &Path) -> Result<Self> {
+        if path.exists() {
+            let bytes = hex::decode(std::fs::read_to_string(path)?.trim())
+                .map_err(|e| NetworkError::Config(format!("Invalid node key file: {}", e)))?;
+            let key = KeyPair::from_secret_bytes(SignatureScheme::Ed25519, &bytes)
+                .map_err(|e| NetworkError::Config(e.to_string()))?;
+            return Ok(Self { key });
+        }
+
+        let identity = Self::generate();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, hex::encode(identity.key.secret_bytes()))?;
+        Ok(identity)
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        peer_id_from_public_key(&self.key.public_key())
+    }
+
+    /// Sign a digest with the node key; callers domain-separate what they sign
+    pub fn sign_digest(&self, digest: &[u8; 32]) -> Vec<u8> {
+        self.key.sign(digest)
+    }
+
+    /// Sign the addresses, protocol version and capabilities this node advertises
+    pub fn sign_record(
+        &self,
+        addresses: Vec<SocketAddr>,
+        protocol_version: u32,
+        capabilities: Capabilities,
+        timestamp: i64,
+    ) -> PeerRecord {
+        let mut record = PeerRecord {
+            peer_id: self.peer_id(),
+            addresses,
+            protocol_version,
+            capabilities,
+            timestamp,
+            signature: Vec::new(),
+        };
+        record.signature = self.key.sign(&record.signing_hash());
+        record
+    }
+}
+
+pub fn peer_id_from_public_key(public_key: &[u8]) -> PeerId {
+    hex::encode(public_key)
+}
+
+/// Self-signed advertisement exchanged during discovery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerRecord {
+    pub peer_id: PeerId,
+    pub addresses: Vec<SocketAddr>,
+    pub protocol_version: u32,
+    pub capabilities: Capabilities,
+    /// Unix seconds at signing; newer records replace older ones
+    pub timestamp: i64,
+    /// Ed25519 signature, including the signer's public key
+    pub signature: Vec<u8>,
+}
+
+impl PeerRecord {
+    fn signing_hash(&self) -> [u8; 32] {
+        #[derive(Serialize)]
+        struct Unsigned<'a> {
+            peer_id: &'a str,
+            addresses: &'a [SocketAddr],
+            protocol_version: u32,
+            capabilities: Capabilities,
+            timestamp: i64,
+        }
+
+        hash_serializable(&Unsigned {
+            peer_id: &self.peer_id,
+            addresses: &self.addresses,
+            protocol_version: self.protocol_version,
+            capabilities: self.capabilities,
+            timestamp: self.timestamp,
+        })
+        .expect("peer record fields always serialize")
+    }
+
+    /// Check the signature, that the signing key matches `peer_id`, and the record's age
+    pub fn verify(&self, now: i64, max_age_secs: i64) -> Result<()> {
+        let public_key = verify_signature(SignatureScheme::Ed25519, &self.signing_hash(), &self.signature)
+            .map_err(|e| self.invalid(e.to_string()))?;
+
+        if peer_id_from_public_key(&public_key) != self.peer_id {
+            return Err(self.invalid("Signed by a key other than the peer's".to_string()));
+        }
+        if self.timestamp > now + MAX_CLOCK_SKEW_SECS {
+            return Err(self.invalid("Timestamp is in the future".to_string()));
+        }
+        if now - self.timestamp > max_age_secs {
+            return Err(self.invalid("Record has expired".to_string()));
+        }
+        Ok(())
+    }
+
+    fn invalid(&self, reason: String) -> NetworkError {
+        NetworkError::InvalidPeerRecord { peer_id: self.peer_id.clone(), reason }
+    }
+}
+
+/// Latest verified record per peer; the only source of dialable addresses
+pub struct PeerRecordBook {
+    max_age_secs: i64,
+    records: HashMap<PeerId, PeerRecord>,
+}
+
+impl PeerRecordBook {
+    pub fn new(max_age_secs: i64) -> Self {
+        Self {
+            max_age_secs,
+            records: HashMap::new(),
+        }
+    }
+
+    /// Verify and keep a record; returns false when a newer one is already held
+    pub fn insert(&mut self, record: PeerRecord, now: i64) -> Result<bool> {
+        record.verify(now, self.max_age_secs)?;
+
+        if let Some(existing) = self.records.get(&record.peer_id) {
+            if existing.timestamp >= record.timestamp {
+                return Ok(false);
+            }
+        }
+        self.records.insert(record.peer_id.clone(), record);
+        Ok(true)
+    }
+
+    pub fn get(&self, peer_id: &str) -> Option<&PeerRecord> {
+        self.records.get(peer_id)
+    }
+
+    /// Addresses from an unexpired verified record, empty if there is none
+    pub fn dial_addresses(&self, peer_id: &str, now: i64) -> Vec<SocketAddr> {
+        match self.records.get(peer_id) {
+            Some(record) if now - record.timestamp <= self.max_age_secs => record.addresses.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn record(identity: &NodeIdentity, timestamp: i64) -> PeerRecord {
+        identity.sign_record(
+            vec!["10.0.0.1:30303".parse().unwrap()],
+            1,
+            Capabilities::BLOCK_RELAY,
+            timestamp,
+        )
+    }
+
+    #[test]
+    fn test_record_verification() {
+        let identity = NodeIdentity::generate();
+        let signed = record(&identity, NOW);
+        assert!(signed.verify(NOW, 3600).is_ok());
+
+        let mut tampered = signed.clone();
+        tampered.addresses = vec!["192.0.2.1:30303".parse().unwrap()];
+        assert!(tampered.verify(NOW, 3600).is_err());
+
+        // Valid signature, but claiming another node's id
+        let mut impersonated = signed.clone();
+        impersonated.peer_id = NodeIdentity::generate().peer_id();
+        assert!(impersonated.verify(NOW, 3600).is_err());
+
+        assert!(signed.verify(NOW + 3601, 3600).is_err());
+        assert!(record(&identity, NOW + 120).verify(NOW, 3600).is_err());
+    }
+
+    #[test]
+    fn test_book_keeps_newest_verified_record() {
+        let identity = NodeIdentity::generate();
+        let mut book = PeerRecordBook::new(3600);
+
+        assert!(book.insert(record(&identity, NOW - 10), NOW).unwrap());
+        assert!(!book.insert(record(&identity, NOW - 20), NOW).unwrap());
+        assert_eq!(book.get(&identity.peer_id()).unwrap().timestamp, NOW - 10);
+
+        let mut forged = record(&identity, NOW);
+        forged.signature[40] ^= 1;
+        assert!(book.insert(forged, NOW).is_err());
+
+        assert_eq!(book.dial_addresses(&identity.peer_id(), NOW).len(), 1);
+        assert!(book.dial_addresses(&identity.peer_id(), NOW + 3600).is_empty());
+        assert!(book.dial_addresses("unknown", NOW).is_empty());
+    }
+
+    #[test]
+    fn test_node_key_persists() {
+        let path = std::env::temp_dir().join(format!("node-key-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let first = NodeIdentity::load_or_generate(&path).unwrap();
+        let second = NodeIdentity::load_or_generate(&path).unwrap();
+        assert_eq!(first.peer_id(), second.peer_id());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,14 +1,94 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+// p2p/p2p-network/src/lib.rs
+use std::net::SocketAddr;
+
+pub mod access;
+pub mod archive;
+pub mod capabilities;
+pub mod config;
+pub mod connections;
+pub mod discovery;
+pub mod handshake;
+pub mod headers;
+pub mod identity;
+pub mod nat;
+pub mod noise;
+pub mod outbound;
+pub mod peer_manager;
+pub mod protocol;
+pub mod role;
+pub mod scoring;
+pub mod seen;
+pub mod tx_gossip;
+pub mod versioning;
+
+pub use access::{AccessControl, AccessList, AccessRule};
+pub use archive::{
+    ArchivePricing, ArchiveRequest, ArchiveResponse, ArchiveServer, ArchiveServingConfig, HistoryItems, HistoryQuery,
+    HistorySource, PaymentHook, PaymentVoucher, PeerUsage,
+};
+pub use capabilities::Capabilities;
+pub use config::NetworkConfig;
+pub use connections::{Admission, ConnectionConfig, ConnectionManager, Direction};
+pub use discovery::{Discovery, DiscoveryConfig, FindNode, InsertOutcome, Lookup, Neighbors, NodeEntry, NodeKey, RoutingTable};
+pub use handshake::{check_handshake, record_handshake};
+pub use headers::{BlockHeaders, GetHeaders, HeaderServer, HeaderServingConfig};
+pub use identity::{NodeIdentity, PeerRecord, PeerRecordBook};
+pub use nat::{ExternalAddresses, Identify, NatConfig, PortMapping};
+pub use noise::{NoiseHandshake, NoiseKeys, NoiseTransport, MAX_FRAME_PLAINTEXT, NOISE_PARAMS};
+pub use outbound::{MessagePriority, OutboundQueue, OutboundQueueConfig};
+pub use peer_manager::{PeerKind, PeerManager, StaticNode};
+pub use protocol::{ChainIdentity, Handshake, MessageKind, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+pub use role::{NodeRole, Subsystems};
+pub use scoring::{record_ban, restore_bans, Ban, Offence, PeerScores, ScoringConfig};
+pub use seen::{AnnouncementStats, InventoryKind, Observation, SeenCache, SeenCacheConfig};
+pub use tx_gossip::{IngestReport, TransactionsMessage, TxGossip, TxGossipConfig, TX_TOPIC};
+pub use versioning::{decode_handshake, encode_handshake, negotiate_version};
+
+/// Message delay and peer-drop decisions, applied to outbound queues and received gossip in test builds
+#[cfg(feature = "fault-injection")]
+pub use dev_tools::{FaultInjector, MessageFate};
+
+/// Peer identifier as advertised on the wire
+pub type PeerId = String;
+
+/// Networking errors
+#[derive(Debug, thiserror::Error)]
+pub enum NetworkError {
+    #[error("Connection from {addr} refused: {reason}")]
+    ConnectionRefused { addr: SocketAddr, reason: String },
+
+    #[error("Peer {peer_id} rejected: {reason}")]
+    PeerRejected { peer_id: PeerId, reason: String },
+
+    #[error("Invalid peer record for {peer_id}: {reason}")]
+    InvalidPeerRecord { peer_id: PeerId, reason: String },
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[error("Peer {peer_id} exceeded its request rate")]
+    RateLimited { peer_id: PeerId },
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
+    #[error("Peer {peer_id} has not paid for {units} units of history")]
+    PaymentRequired { peer_id: PeerId, units: u64 },
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("NAT traversal failed: {0}")]
+    Nat(String),
+
+    #[error("Noise handshake or frame failed: {0}")]
+    Noise(String),
+
+    #[error("Invalid rule: {0}")]
+    InvalidRule(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
 }
+
+pub type Result<T> = std::result::Result<T, NetworkError>;
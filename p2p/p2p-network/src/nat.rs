@@ -0,0 +1,366 @@
+// p2p/p2p-network/src/nat.rs
+//! NAT traversal and external address discovery.
+//!
+//! A node behind NAT learns its public address two ways. It asks the gateway
+//! for a port mapping, over NAT-PMP (RFC 6886) or UPnP IGD, whose reply names
+//! the external address. And after each handshake peers send an `Identify`
+//! message saying which address they see the connection coming from; an
+//! address reported by `confirmations` distinct peers is taken as ours and
+//! advertised in peer records. Reports of private or loopback addresses are
+//! ignored, since they only describe the peer's side of a LAN.
+//!
+//! Nothing here does I/O: callers send the encoded NAT-PMP datagrams to the
+//! gateway on `NATPMP_PORT`, the SSDP search to `SSDP_ADDR` and the SOAP
+//! requests to the control URL from the device description.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use crate::{NetworkError, PeerId, Result};
+
+/// Port NAT-PMP gateways listen on
+pub const NATPMP_PORT: u16 = 5351;
+
+/// Multicast address UPnP gateways answer searches on
+pub const SSDP_ADDR: &str = "239.255.255.250:1900";
+
+const UPNP_SERVICE: &str = "urn:schemas-upnp-org:service:WANIPConnection:1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NatConfig {
+    /// Map the listen port on the gateway and discover the external address
+    pub enabled: bool,
+    /// Requested lifetime of a port mapping; renewed halfway through
+    pub mapping_lifetime_secs: u32,
+    /// Distinct peers that must report the same address before it is advertised
+    pub confirmations: usize,
+    /// Advertise this address and skip discovery, e.g. behind a cloud load balancer
+    pub external_addr: Option<SocketAddr>,
+}
+
+impl Default for NatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mapping_lifetime_secs: 7_200,
+            confirmations: 3,
+            external_addr: None,
+        }
+    }
+}
+
+impl NatConfig {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.mapping_lifetime_secs < 120 {
+            return Err("mapping_lifetime_secs must be at least 120".to_string());
+        }
+        if self.confirmations == 0 {
+            return Err("NAT confirmations must be greater than 0".to_string());
+        }
+        if self.external_addr.is_some_and(|addr| !is_public(addr.ip())) {
+            return Err("external_addr must be a public address".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Body of a `MessageKind::Identify` message, sent once after the handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identify {
+    /// Address the sender sees this connection coming from
+    pub observed_addr: SocketAddr,
+}
+
+/// Whether `ip` can be reached from the internet
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => {
+            let unique_local = ip.segments()[0] & 0xfe00 == 0xfc00;
+            let link_local = ip.segments()[0] & 0xffc0 == 0xfe80;
+            !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local)
+        }
+    }
+}
+
+/// Where the node believes it can be reached from outside
+pub struct ExternalAddresses {
+    confirmations: usize,
+    manual: Option<SocketAddr>,
+    mapped: Option<SocketAddr>,
+    /// Latest report per peer, so one peer cannot confirm an address alone
+    observed: HashMap<PeerId, SocketAddr>,
+    confirmed: Option<SocketAddr>,
+}
+
+impl ExternalAddresses {
+    pub fn new(config: &NatConfig) -> Self {
+        Self {
+            confirmations: config.confirmations,
+            manual: config.external_addr,
+            mapped: None,
+            observed: HashMap::new(),
+            confirmed: None,
+        }
+    }
+
+    /// Record the address `peer_id` sees us at, returning it if this report
+    /// confirmed a new address
+    pub fn observe(&mut self, peer_id: &str, observed: &Identify) -> Option<SocketAddr> {
+        let addr = observed.observed_addr;
+        if !is_public(addr.ip()) {
+            return None;
+        }
+        self.observed.insert(peer_id.to_string(), addr);
+
+        let reports = self.observed.values().filter(|a| **a == addr).count();
+        if reports < self.confirmations || self.confirmed == Some(addr) {
+            return None;
+        }
+        self.confirmed = Some(addr);
+        Some(addr)
+    }
+
+    /// A disconnected peer's report no longer counts
+    pub fn forget(&mut self, peer_id: &str) {
+        self.observed.remove(peer_id);
+    }
+
+    /// Record the address a gateway mapped for us, or `None` once the mapping lapsed
+    pub fn set_mapped(&mut self, mapped: Option<SocketAddr>) {
+        self.mapped = mapped;
+    }
+
+    /// Addresses to advertise in peer records, most trusted first
+    pub fn advertised(&self) -> Vec<SocketAddr> {
+        if let Some(manual) = self.manual {
+            return vec![manual];
+        }
+        let mut addrs = Vec::new();
+        for addr in [self.mapped, self.confirmed].into_iter().flatten() {
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
+        addrs
+    }
+}
+
+/// Port mapping granted by a gateway
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortMapping {
+    pub internal_port: u16,
+    pub external_port: u16,
+    pub lifetime_secs: u32,
+}
+
+impl PortMapping {
+    /// Seconds until the mapping should be renewed
+    pub fn renew_after_secs(&self) -> u32 {
+        self.lifetime_secs / 2
+    }
+}
+
+fn nat_error(reason: impl Into<String>) -> NetworkError {
+    NetworkError::Nat(reason.into())
+}
+
+/// NAT-PMP request for the gateway's external address
+pub fn natpmp_external_address_request() -> [u8; 2] {
+    [0, 0]
+}
+
+/// NAT-PMP request mapping TCP `internal_port` to the same external port;
+/// a lifetime of 0 deletes the mapping
+pub fn natpmp_mapping_request(internal_port: u16, lifetime_secs: u32) -> [u8; 12] {
+    let mut request = [0u8; 12];
+    request[1] = 2; // map TCP
+    request[4..6].copy_from_slice(&internal_port.to_be_bytes());
+    request[6..8].copy_from_slice(&internal_port.to_be_bytes());
+    request[8..12].copy_from_slice(&lifetime_secs.to_be_bytes());
+    request
+}
+
+fn natpmp_header(response: &[u8], opcode: u8, len: usize) -> Result<()> {
+    if response.len() < len {
+        return Err(nat_error(format!("NAT-PMP reply of {} bytes is too short", response.len())));
+    }
+    if response[0] != 0 || response[1] != opcode {
+        return Err(nat_error(format!("Unexpected NAT-PMP reply {}/{}", response[0], response[1])));
+    }
+    match u16::from_be_bytes([response[2], response[3]]) {
+        0 => Ok(()),
+        code => Err(nat_error(format!("NAT-PMP gateway refused with result code {}", code))),
+    }
+}
+
+pub fn natpmp_parse_external_address(response: &[u8]) -> Result<Ipv4Addr> {
+    natpmp_header(response, 128, 12)?;
+    Ok(Ipv4Addr::new(response[8], response[9], response[10], response[11]))
+}
+
+pub fn natpmp_parse_mapping(response: &[u8]) -> Result<PortMapping> {
+    natpmp_header(response, 130, 16)?;
+    Ok(PortMapping {
+        internal_port: u16::from_be_bytes([response[8], response[9]]),
+        external_port: u16::from_be_bytes([response[10], response[11]]),
+        lifetime_secs: u32::from_be_bytes([response[12], response[13], response[14], response[15]]),
+    })
+}
+
+/// SSDP search for UPnP internet gateways
+pub fn ssdp_search_request() -> String {
+    format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: {}\r\nMAN: \"ssdp:discover\"\r\nMX: 2\r\nST: {}\r\n\r\n",
+        SSDP_ADDR, UPNP_SERVICE
+    )
+}
+
+/// Device description URL from an SSDP search reply
+pub fn ssdp_location(response: &str) -> Option<&str> {
+    response.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case("location").then(|| value.trim())
+    })
+}
+
+/// SOAP action header and body for a UPnP request
+pub struct SoapRequest {
+    pub action: String,
+    pub body: String,
+}
+
+fn soap_request(action: &str, arguments: &str) -> SoapRequest {
+    SoapRequest {
+        action: format!("\"{}#{}\"", UPNP_SERVICE, action),
+        body: format!(
+            concat!(
+                "<?xml version=\"1.0\"?>",
+                "<s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" ",
+                "s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">",
+                "<s:Body><u:{action} xmlns:u=\"{service}\">{arguments}</u:{action}></s:Body></s:Envelope>"
+            ),
+            action = action,
+            service = UPNP_SERVICE,
+            arguments = arguments
+        ),
+    }
+}
+
+/// UPnP request mapping TCP `internal.port()` on the gateway to `internal`
+pub fn upnp_add_port_mapping(internal: SocketAddr, lifetime_secs: u32) -> SoapRequest {
+    let arguments = format!(
+        concat!(
+            "<NewRemoteHost></NewRemoteHost>",
+            "<NewExternalPort>{port}</NewExternalPort>",
+            "<NewProtocol>TCP</NewProtocol>",
+            "<NewInternalPort>{port}</NewInternalPort>",
+            "<NewInternalClient>{client}</NewInternalClient>",
+            "<NewEnabled>1</NewEnabled>",
+            "<NewPortMappingDescription>p2p-blockchain</NewPortMappingDescription>",
+            "<NewLeaseDuration>{lifetime}</NewLeaseDuration>"
+        ),
+        port = internal.port(),
+        client = internal.ip(),
+        lifetime = lifetime_secs
+    );
+    soap_request("AddPortMapping", &arguments)
+}
+
+pub fn upnp_get_external_ip() -> SoapRequest {
+    soap_request("GetExternalIPAddress", "")
+}
+
+/// External address from a `GetExternalIPAddress` reply
+pub fn upnp_parse_external_ip(body: &str) -> Result<IpAddr> {
+    let start = body
+        .find("<NewExternalIPAddress>")
+        .map(|i| i + "<NewExternalIPAddress>".len())
+        .ok_or_else(|| nat_error("UPnP reply has no external address"))?;
+    let end = body[start..]
+        .find('<')
+        .map(|i| start + i)
+        .ok_or_else(|| nat_error("Truncated UPnP reply"))?;
+    body[start..end]
+        .trim()
+        .parse()
+        .map_err(|_| nat_error(format!("Invalid UPnP external address: {}", &body[start..end])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identify(addr: &str) -> Identify {
+        Identify { observed_addr: addr.parse().unwrap() }
+    }
+
+    #[test]
+    fn test_address_confirmed_by_distinct_peers() {
+        let config = NatConfig { confirmations: 2, ..Default::default() };
+        config.validate().unwrap();
+        let mut external = ExternalAddresses::new(&config);
+
+        // Private reports and repeats from one peer do not count
+        assert_eq!(external.observe("peer-a", &identify("192.168.1.20:30303")), None);
+        assert_eq!(external.observe("peer-a", &identify("81.2.69.142:30303")), None);
+        assert_eq!(external.observe("peer-a", &identify("81.2.69.142:30303")), None);
+        assert!(external.advertised().is_empty());
+
+        let confirmed = external.observe("peer-b", &identify("81.2.69.142:30303"));
+        assert_eq!(confirmed, Some("81.2.69.142:30303".parse().unwrap()));
+        assert_eq!(external.observe("peer-c", &identify("81.2.69.142:30303")), None);
+
+        external.set_mapped(Some("81.2.69.160:30303".parse().unwrap()));
+        assert_eq!(external.advertised().len(), 2);
+
+        let manual = NatConfig { external_addr: Some("81.2.69.200:30303".parse().unwrap()), ..config };
+        assert_eq!(ExternalAddresses::new(&manual).advertised(), vec![manual.external_addr.unwrap()]);
+        let private = NatConfig { external_addr: Some("10.0.0.1:30303".parse().unwrap()), ..manual };
+        assert!(private.validate().is_err());
+    }
+
+    #[test]
+    fn test_natpmp_codec() {
+        let request = natpmp_mapping_request(30303, 7_200);
+        assert_eq!(&request[..2], &[0, 2]);
+        assert_eq!(u16::from_be_bytes([request[4], request[5]]), 30303);
+
+        let mut reply = vec![0, 130, 0, 0, 0, 0, 0, 9];
+        reply.extend_from_slice(&30303u16.to_be_bytes());
+        reply.extend_from_slice(&40404u16.to_be_bytes());
+        reply.extend_from_slice(&3_600u32.to_be_bytes());
+        let mapping = natpmp_parse_mapping(&reply).unwrap();
+        assert_eq!((mapping.external_port, mapping.renew_after_secs()), (40404, 1_800));
+
+        reply[3] = 3; // network failure
+        assert!(natpmp_parse_mapping(&reply).is_err());
+        assert_eq!(
+            natpmp_parse_external_address(&[0, 128, 0, 0, 0, 0, 0, 1, 81, 2, 69, 142]).unwrap(),
+            Ipv4Addr::new(81, 2, 69, 142)
+        );
+    }
+
+    #[test]
+    fn test_upnp_messages() {
+        let reply = "HTTP/1.1 200 OK\r\nLOCATION: http://192.168.1.1:5000/rootDesc.xml\r\n\r\n";
+        assert_eq!(ssdp_location(reply), Some("http://192.168.1.1:5000/rootDesc.xml"));
+
+        let request = upnp_add_port_mapping("192.168.1.20:30303".parse().unwrap(), 7_200);
+        assert!(request.action.ends_with("#AddPortMapping\""));
+        assert!(request.body.contains("<NewInternalClient>192.168.1.20</NewInternalClient>"));
+
+        let body = "<u:GetExternalIPAddressResponse><NewExternalIPAddress>81.2.69.142</NewExternalIPAddress>";
+        assert_eq!(upnp_parse_external_ip(body).unwrap(), "81.2.69.142".parse::<IpAddr>().unwrap());
+        assert!(upnp_parse_external_ip("<NewExternalIPAddress>nope</NewExternalIPAddress>").is_err());
+    }
+}
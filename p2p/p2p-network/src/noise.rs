@@ -0,0 +1,264 @@
+// p2p/p2p-network/src/noise.rs
+//! Noise transport encryption for peer connections.
+//!
+//! Connections run the `XX` pattern, in which both sides learn each other's
+//! static X25519 key. That key is generated at startup and signed with the
+//! node key, and the signature travels in the encrypted handshake payload, so
+//! completing a handshake proves the remote holds the node key behind its
+//! peer id. There is no plaintext fallback: a connection that cannot finish
+//! the handshake is closed.
+//!
+//! As elsewhere in this crate there is no I/O here; callers move the
+//! handshake and transport messages over the socket, one length-prefixed
+//! frame each.
+use blockchain_core::signature::verify_signature;
+use blockchain_core::{hash_serializable, SignatureScheme};
+use snow::{HandshakeState, TransportState};
+
+use crate::identity::{peer_id_from_public_key, NodeIdentity};
+use crate::{NetworkError, PeerId, Result};
+
+/// Handshake pattern, DH function, cipher and hash used on every connection
+pub const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_SHA256";
+
+/// Largest Noise message, handshake or transport
+pub const MAX_NOISE_MESSAGE: usize = 65_535;
+
+const TAG_LEN: usize = 16;
+
+/// Largest plaintext sealed into one transport message
+pub const MAX_FRAME_PLAINTEXT: usize = MAX_NOISE_MESSAGE - TAG_LEN;
+
+/// Domain separating the static-key proof from every other signature made
+/// with the node key
+const STATIC_KEY_DOMAIN: &str = "p2p-noise-static-key";
+
+fn noise_error(e: snow::Error) -> NetworkError {
+    NetworkError::Noise(e.to_string())
+}
+
+fn static_key_digest(static_key: &[u8]) -> [u8; 32] {
+    hash_serializable(&(STATIC_KEY_DOMAIN, static_key)).expect("static key proof always serializes")
+}
+
+/// Static Noise key of this node, with the node key's signature over it
+pub struct NoiseKeys {
+    private: Vec<u8>,
+    public: Vec<u8>,
+    proof: Vec<u8>,
+    peer_id: PeerId,
+}
+
+impl NoiseKeys {
+    /// Generate a static key for this run; it needs no persistence, since
+    /// peers identify us by the node key that signs it
+    pub fn generate(identity: &NodeIdentity) -> Result<Self> {
+        let keypair = snow::Builder::new(params()?).generate_keypair().map_err(noise_error)?;
+        let proof = identity.sign_digest(&static_key_digest(&keypair.public));
+        Ok(Self {
+            private: keypair.private,
+            public: keypair.public,
+            proof,
+            peer_id: identity.peer_id(),
+        })
+    }
+
+    pub fn peer_id(&self) -> &PeerId {
+        &self.peer_id
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public
+    }
+}
+
+fn params() -> Result<snow::params::NoiseParams> {
+    NOISE_PARAMS.parse().map_err(noise_error)
+}
+
+/// One side of a handshake in progress
+pub struct NoiseHandshake {
+    state: HandshakeState,
+    proof: Vec<u8>,
+    /// Peer id the dialed address was advertised for, if any
+    expected_peer: Option<PeerId>,
+    remote_peer: Option<PeerId>,
+    sent: usize,
+}
+
+impl NoiseHandshake {
+    /// Dialing side; `expected_peer` rejects a remote with another identity,
+    /// e.g. when dialing a static node or a peer record's address
+    pub fn initiator(keys: &NoiseKeys, expected_peer: Option<PeerId>) -> Result<Self> {
+        let state = snow::Builder::new(params()?)
+            .local_private_key(&keys.private)
+            .build_initiator()
+            .map_err(noise_error)?;
+        Ok(Self::new(state, keys, expected_peer))
+    }
+
+    /// Accepting side
+    pub fn responder(keys: &NoiseKeys) -> Result<Self> {
+        let state = snow::Builder::new(params()?)
+            .local_private_key(&keys.private)
+            .build_responder()
+            .map_err(noise_error)?;
+        Ok(Self::new(state, keys, None))
+    }
+
+    fn new(state: HandshakeState, keys: &NoiseKeys, expected_peer: Option<PeerId>) -> Self {
+        Self { state, proof: keys.proof.clone(), expected_peer, remote_peer: None, sent: 0 }
+    }
+
+    /// Whether the next handshake message is ours to send
+    pub fn is_my_turn(&self) -> bool {
+        !self.state.is_handshake_finished() && self.state.is_my_turn()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.is_handshake_finished()
+    }
+
+    /// Next handshake message to send
+    pub fn write_message(&mut self) -> Result<Vec<u8>> {
+        // The initiator's first message is sent in the clear, so the proof
+        // waits for the message carrying our static key
+        let payload: &[u8] = if self.state.is_initiator() && self.sent == 0 { &[] } else { &self.proof };
+        let mut message = vec![0u8; MAX_NOISE_MESSAGE];
+        let len = self.state.write_message(payload, &mut message).map_err(noise_error)?;
+        message.truncate(len);
+        self.sent += 1;
+        Ok(message)
+    }
+
+    /// Process a handshake message from the remote, checking its identity
+    /// proof once its static key is known
+    pub fn read_message(&mut self, message: &[u8]) -> Result<()> {
+        let mut payload = vec![0u8; MAX_NOISE_MESSAGE];
+        let len = self.state.read_message(message, &mut payload).map_err(noise_error)?;
+
+        let Some(remote_static) = self.state.get_remote_static() else {
+            return Ok(());
+        };
+        let digest = static_key_digest(remote_static);
+        let public_key = verify_signature(SignatureScheme::Ed25519, &digest, &payload[..len])
+            .map_err(|e| NetworkError::Noise(format!("Invalid static key proof: {}", e)))?;
+        let peer_id = peer_id_from_public_key(&public_key);
+
+        if let Some(expected) = &self.expected_peer {
+            if *expected != peer_id {
+                return Err(NetworkError::PeerRejected {
+                    peer_id,
+                    reason: format!("dialed {} but a different node answered", expected),
+                });
+            }
+        }
+        self.remote_peer = Some(peer_id);
+        Ok(())
+    }
+
+    /// Switch to transport mode once the handshake has finished
+    pub fn into_transport(self) -> Result<NoiseTransport> {
+        let remote_peer = match (self.state.is_handshake_finished(), self.remote_peer) {
+            (true, Some(peer_id)) => peer_id,
+            _ => return Err(NetworkError::Noise("Handshake has not finished".to_string())),
+        };
+        let state = self.state.into_transport_mode().map_err(noise_error)?;
+        Ok(NoiseTransport { state, remote_peer })
+    }
+}
+
+/// Encrypted channel to an authenticated peer
+pub struct NoiseTransport {
+    state: TransportState,
+    remote_peer: PeerId,
+}
+
+impl NoiseTransport {
+    /// Peer id proven during the handshake
+    pub fn remote_peer(&self) -> &PeerId {
+        &self.remote_peer
+    }
+
+    /// Seal one frame; callers split payloads above `MAX_FRAME_PLAINTEXT`
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        if plaintext.len() > MAX_FRAME_PLAINTEXT {
+            return Err(NetworkError::Noise(format!(
+                "Frame of {} bytes exceeds {}",
+                plaintext.len(),
+                MAX_FRAME_PLAINTEXT
+            )));
+        }
+        let mut message = vec![0u8; plaintext.len() + TAG_LEN];
+        let len = self.state.write_message(plaintext, &mut message).map_err(noise_error)?;
+        message.truncate(len);
+        Ok(message)
+    }
+
+    /// Open one frame; any failure means the connection is to be closed
+    pub fn decrypt(&mut self, message: &[u8]) -> Result<Vec<u8>> {
+        let mut plaintext = vec![0u8; message.len()];
+        let len = self.state.read_message(message, &mut plaintext).map_err(noise_error)?;
+        plaintext.truncate(len);
+        Ok(plaintext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(initiator: &mut NoiseHandshake, responder: &mut NoiseHandshake) -> Result<()> {
+        responder.read_message(&initiator.write_message()?)?;
+        initiator.read_message(&responder.write_message()?)?;
+        responder.read_message(&initiator.write_message()?)
+    }
+
+    #[test]
+    fn test_handshake_authenticates_and_encrypts() {
+        let alice = NoiseKeys::generate(&NodeIdentity::generate()).unwrap();
+        let bob = NoiseKeys::generate(&NodeIdentity::generate()).unwrap();
+
+        let mut dialer = NoiseHandshake::initiator(&alice, Some(bob.peer_id().clone())).unwrap();
+        let mut listener = NoiseHandshake::responder(&bob).unwrap();
+        assert!(dialer.is_my_turn());
+        handshake(&mut dialer, &mut listener).unwrap();
+        assert!(dialer.is_finished() && listener.is_finished());
+
+        let mut dialer = dialer.into_transport().unwrap();
+        let mut listener = listener.into_transport().unwrap();
+        assert_eq!(dialer.remote_peer(), bob.peer_id());
+        assert_eq!(listener.remote_peer(), alice.peer_id());
+
+        let sealed = dialer.encrypt(b"block announcement").unwrap();
+        assert!(!sealed.windows(5).any(|w| w == b"block"));
+        assert_eq!(listener.decrypt(&sealed).unwrap(), b"block announcement");
+
+        // Tampered frames are rejected
+        let mut tampered = listener.encrypt(b"headers").unwrap();
+        tampered[0] ^= 1;
+        assert!(dialer.decrypt(&tampered).is_err());
+        assert!(dialer.encrypt(&vec![0; MAX_FRAME_PLAINTEXT + 1]).is_err());
+    }
+
+    #[test]
+    fn test_dialing_the_wrong_identity_fails() {
+        let alice = NoiseKeys::generate(&NodeIdentity::generate()).unwrap();
+        let bob = NoiseKeys::generate(&NodeIdentity::generate()).unwrap();
+        let expected = NodeIdentity::generate().peer_id();
+
+        let mut dialer = NoiseHandshake::initiator(&alice, Some(expected)).unwrap();
+        let mut listener = NoiseHandshake::responder(&bob).unwrap();
+        assert!(matches!(
+            handshake(&mut dialer, &mut listener),
+            Err(NetworkError::PeerRejected { .. })
+        ));
+
+        // A static key signed by someone else's node key does not verify
+        let mut forged = NoiseKeys::generate(&NodeIdentity::generate()).unwrap();
+        forged.proof = alice.proof.clone();
+        let mut dialer = NoiseHandshake::initiator(&forged, None).unwrap();
+        let mut listener = NoiseHandshake::responder(&bob).unwrap();
+        assert!(matches!(handshake(&mut dialer, &mut listener), Err(NetworkError::Noise(_))));
+    }
+}
@@ -0,0 +1,331 @@
+// p2p/p2p-network/src/outbound.rs
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::protocol::MessageKind;
+#[cfg(feature = "fault-injection")]
+use crate::{FaultInjector, MessageFate};
+
+/// Scheduling class of an outbound message, highest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MessagePriority {
+    /// Block propagation; preempts everything else
+    High = 0,
+    /// Requests that a peer is actively waiting on
+    Normal = 1,
+    /// Transaction gossip and bulk transfers
+    Low = 2,
+}
+
+impl MessagePriority {
+    pub const ALL: [MessagePriority; 3] = [MessagePriority::High, MessagePriority::Normal, MessagePriority::Low];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl std::fmt::Display for MessagePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MessagePriority::High => write!(f, "high"),
+            MessagePriority::Normal => write!(f, "normal"),
+            MessagePriority::Low => write!(f, "low"),
+        }
+    }
+}
+
+impl MessageKind {
+    pub const fn priority(self) -> MessagePriority {
+        match self {
+            MessageKind::BlockAnnouncement | MessageKind::CompactBlock => MessagePriority::High,
+            MessageKind::SnapshotRequest
+            | MessageKind::LightClientRequest
+            | MessageKind::GetHeaders
+            | MessageKind::BlockHeaders
+            | MessageKind::FindNode
+            | MessageKind::Neighbors
+            | MessageKind::Identify
+            | MessageKind::ArchiveRequest => MessagePriority::Normal,
+            MessageKind::Transactions | MessageKind::SnapshotChunk | MessageKind::ArchiveResponse => {
+                MessagePriority::Low
+            }
+        }
+    }
+}
+
+/// Per-peer write queue limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboundQueueConfig {
+    /// Messages held per class before the oldest is dropped
+    pub capacity_per_class: usize,
+    /// Longest a normal-priority message waits before it is sent ahead of higher classes
+    pub normal_max_delay_ms: u64,
+    /// Longest a low-priority message waits before it is sent ahead of higher classes
+    pub low_max_delay_ms: u64,
+}
+
+impl Default for OutboundQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity_per_class: 1_024,
+            normal_max_delay_ms: 500,
+            low_max_delay_ms: 2_000,
+        }
+    }
+}
+
+/// Queueing statistics for one class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClassMetrics {
+    pub enqueued: u64,
+    pub sent: u64,
+    pub dropped: u64,
+    /// Sent ahead of higher classes because they waited too long
+    pub promoted: u64,
+    pub total_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl ClassMetrics {
+    pub fn avg_delay(&self) -> Duration {
+        match self.sent {
+            0 => Duration::ZERO,
+            sent => self.total_delay / sent as u32,
+        }
+    }
+}
+
+/// Priority write queue for a single peer connection.
+///
+/// Higher classes are always sent first, except that a lower-class message
+/// which has waited past its class's maximum delay goes next, so sustained
+/// block traffic cannot starve gossip indefinitely.
+pub struct OutboundQueue<T> {
+    config: OutboundQueueConfig,
+    queues: [VecDeque<(Instant, MessageKind, T)>; 3],
+    metrics: [ClassMetrics; 3],
+    #[cfg(feature = "fault-injection")]
+    faults: FaultInjector,
+    /// Messages held back by an injected delay, with the time they are released
+    #[cfg(feature = "fault-injection")]
+    held: VecDeque<(Instant, MessageKind, T)>,
+    /// Set once an injected fault dropped the peer
+    #[cfg(feature = "fault-injection")]
+    closed: bool,
+}
+
+impl<T> OutboundQueue<T> {
+    pub fn new(config: OutboundQueueConfig) -> Self {
+        Self {
+            config,
+            queues: [VecDeque::new(), VecDeque::new(), VecDeque::new()],
+            metrics: [ClassMetrics::default(); 3],
+            #[cfg(feature = "fault-injection")]
+            faults: FaultInjector::default(),
+            #[cfg(feature = "fault-injection")]
+            held: VecDeque::new(),
+            #[cfg(feature = "fault-injection")]
+            closed: false,
+        }
+    }
+
+    /// Delay or drop popped messages as `faults` decides
+    #[cfg(feature = "fault-injection")]
+    pub fn with_faults(mut self, faults: FaultInjector) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Queue a message; returns false if the class was full and its oldest message was dropped
+    pub fn push(&mut self, kind: MessageKind, message: T, now: Instant) -> bool {
+        let class = kind.priority().index();
+        let queue = &mut self.queues[class];
+        let mut kept_all = true;
+
+        if queue.len() >= self.config.capacity_per_class {
+            queue.pop_front();
+            self.metrics[class].dropped += 1;
+            kept_all = false;
+        }
+        queue.push_back((now, kind, message));
+        self.metrics[class].enqueued += 1;
+        kept_all
+    }
+
+    /// Next message to write to the peer
+    #[cfg(not(feature = "fault-injection"))]
+    pub fn pop(&mut self, now: Instant) -> Option<(MessageKind, T)> {
+        self.next_queued(now)
+    }
+
+    /// Next message to write to the peer. A message the injector delays is
+    /// held until its release time; one that drops the peer closes the queue,
+    /// after which the writer should disconnect
+    #[cfg(feature = "fault-injection")]
+    pub fn pop(&mut self, now: Instant) -> Option<(MessageKind, T)> {
+        if self.closed {
+            return None;
+        }
+        if let Some(index) = self.held.iter().position(|(release_at, _, _)| *release_at <= now) {
+            return self.held.remove(index).map(|(_, kind, message)| (kind, message));
+        }
+        while let Some((kind, message)) = self.next_queued(now) {
+            match self.faults.message_fate() {
+                MessageFate::Deliver => return Some((kind, message)),
+                MessageFate::Delay(delay) => self.held.push_back((now + delay, kind, message)),
+                MessageFate::DropPeer => {
+                    self.closed = true;
+                    self.held.clear();
+                    self.queues.iter_mut().for_each(VecDeque::clear);
+                    return None;
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether an injected fault dropped the peer
+    #[cfg(feature = "fault-injection")]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn next_queued(&mut self, now: Instant) -> Option<(MessageKind, T)> {
+        let overdue = [MessagePriority::Low, MessagePriority::Normal]
+            .into_iter()
+            .find(|class| self.is_overdue(*class, now));
+        let next = MessagePriority::ALL
+            .into_iter()
+            .find(|class| !self.queues[class.index()].is_empty());
+
+        let class = overdue.or(next)?;
+        let (enqueued_at, kind, message) = self.queues[class.index()].pop_front()?;
+
+        let delay = now.saturating_duration_since(enqueued_at);
+        let metrics = &mut self.metrics[class.index()];
+        metrics.sent += 1;
+        metrics.total_delay += delay;
+        metrics.max_delay = metrics.max_delay.max(delay);
+        if overdue.is_some() && next != Some(class) {
+            metrics.promoted += 1;
+        }
+
+        Some((kind, message))
+    }
+
+    pub fn len(&self) -> usize {
+        #[cfg(feature = "fault-injection")]
+        let held = self.held.len();
+        #[cfg(not(feature = "fault-injection"))]
+        let held = 0;
+        self.queues.iter().map(VecDeque::len).sum::<usize>() + held
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn metrics(&self, class: MessagePriority) -> ClassMetrics {
+        self.metrics[class.index()]
+    }
+
+    fn is_overdue(&self, class: MessagePriority, now: Instant) -> bool {
+        let max_delay = match class {
+            MessagePriority::High => return false,
+            MessagePriority::Normal => self.config.normal_max_delay_ms,
+            MessagePriority::Low => self.config.low_max_delay_ms,
+        };
+        self.queues[class.index()]
+            .front()
+            .is_some_and(|(at, _, _)| now.saturating_duration_since(*at) >= Duration::from_millis(max_delay))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_blocks_preempt_gossip() {
+        let now = Instant::now();
+        let mut queue = OutboundQueue::new(OutboundQueueConfig::default());
+        queue.push(MessageKind::Transactions, 1, now);
+        queue.push(MessageKind::ArchiveResponse, 4, now);
+        queue.push(MessageKind::SnapshotRequest, 2, now);
+        queue.push(MessageKind::ArchiveRequest, 5, now);
+        queue.push(MessageKind::BlockAnnouncement, 3, now);
+
+        // Archive responses are bulk history, sent behind requests like snapshot chunks
+        let order: Vec<i32> = std::iter::from_fn(|| queue.pop(now).map(|(_, m)| m)).collect();
+        assert_eq!(order, vec![3, 2, 5, 1, 4]);
+    }
+
+    #[test]
+    fn test_overdue_gossip_is_not_starved() {
+        let start = Instant::now();
+        let mut queue = OutboundQueue::new(OutboundQueueConfig::default());
+        queue.push(MessageKind::Transactions, 0, start);
+
+        let later = start + ms(2_000);
+        queue.push(MessageKind::BlockAnnouncement, 1, later);
+        queue.push(MessageKind::BlockAnnouncement, 2, later);
+
+        assert_eq!(queue.pop(later), Some((MessageKind::Transactions, 0)));
+        assert_eq!(queue.pop(later), Some((MessageKind::BlockAnnouncement, 1)));
+
+        let low = queue.metrics(MessagePriority::Low);
+        assert_eq!(low.promoted, 1);
+        assert_eq!(low.max_delay, ms(2_000));
+        assert_eq!(queue.metrics(MessagePriority::High).avg_delay(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_full_class_drops_oldest() {
+        let now = Instant::now();
+        let mut queue = OutboundQueue::new(OutboundQueueConfig {
+            capacity_per_class: 2,
+            ..Default::default()
+        });
+        assert!(queue.push(MessageKind::Transactions, 1, now));
+        assert!(queue.push(MessageKind::Transactions, 2, now));
+        assert!(!queue.push(MessageKind::Transactions, 3, now));
+        assert!(queue.push(MessageKind::BlockAnnouncement, 4, now));
+
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.metrics(MessagePriority::Low).dropped, 1);
+        assert_eq!(queue.pop(now), Some((MessageKind::BlockAnnouncement, 4)));
+        assert_eq!(queue.pop(now), Some((MessageKind::Transactions, 2)));
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[test]
+    fn test_injected_delay_and_peer_drop() {
+        use dev_tools::FaultConfig;
+
+        let now = Instant::now();
+        let faults = FaultInjector::new(FaultConfig { message_delay_ms: 100, ..Default::default() });
+        let mut queue = OutboundQueue::new(OutboundQueueConfig::default()).with_faults(faults.clone());
+        queue.push(MessageKind::BlockAnnouncement, 1, now);
+        queue.push(MessageKind::Transactions, 2, now);
+
+        // Both are held, and released once their delay has passed
+        assert_eq!(queue.pop(now), None);
+        assert_eq!(queue.len(), 2);
+        faults.clear();
+        assert_eq!(queue.pop(now + ms(50)), None);
+        assert_eq!(queue.pop(now + ms(100)), Some((MessageKind::BlockAnnouncement, 1)));
+        assert_eq!(queue.pop(now + ms(100)), Some((MessageKind::Transactions, 2)));
+
+        faults.set(FaultConfig { peer_drop_rate: 1.0, ..Default::default() }).unwrap();
+        queue.push(MessageKind::BlockAnnouncement, 3, now);
+        assert_eq!(queue.pop(now), None);
+        assert!(queue.is_closed());
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,464 @@
+// p2p/p2p-network/src/peer_manager.rs
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::capabilities::Capabilities;
+use crate::config::NetworkConfig;
+use crate::handshake::check_handshake;
+use crate::protocol::{ChainIdentity, Handshake, MessageKind};
+use crate::scoring::{Ban, Offence, PeerScores};
+use crate::{NetworkError, PeerId, Result};
+
+/// Peer always kept connected, configured as `peer_id@ip:port`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StaticNode {
+    pub peer_id: PeerId,
+    pub addr: SocketAddr,
+}
+
+impl FromStr for StaticNode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (peer_id, addr) = s
+            .split_once('@')
+            .ok_or_else(|| format!("Static node must be peer_id@ip:port: {}", s))?;
+        if peer_id.is_empty() {
+            return Err(format!("Static node is missing a peer id: {}", s));
+        }
+        let addr = addr
+            .parse()
+            .map_err(|_| format!("Invalid static node address: {}", addr))?;
+        Ok(StaticNode {
+            peer_id: peer_id.to_string(),
+            addr,
+        })
+    }
+}
+
+/// How a peer is treated for slots and bans
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerKind {
+    /// Dialed and redialed by us; dedicated slot, never banned
+    Static,
+    /// Always admitted into a dedicated slot, never banned
+    Trusted,
+    /// Subject to `max_peers` and scoring
+    Regular,
+}
+
+impl PeerKind {
+    /// Whether this peer bypasses slot limits and bans
+    pub fn is_protected(self) -> bool {
+        !matches!(self, PeerKind::Regular)
+    }
+}
+
+impl std::fmt::Display for PeerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PeerKind::Static => write!(f, "static"),
+            PeerKind::Trusted => write!(f, "trusted"),
+            PeerKind::Regular => write!(f, "regular"),
+        }
+    }
+}
+
+/// A connected peer
+#[derive(Debug, Clone)]
+pub struct ConnectedPeer {
+    pub addr: SocketAddr,
+    pub kind: PeerKind,
+    /// Capabilities negotiated in the handshake
+    pub capabilities: Capabilities,
+    /// Wire protocol version negotiated in the handshake
+    pub protocol_version: u32,
+    pub connected_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+struct Redial {
+    attempts: u32,
+    next_at: Instant,
+}
+
+/// Tracks connected peers, connection slots, bans and static-node redials
+pub struct PeerManager {
+    max_peers: usize,
+    local_capabilities: Capabilities,
+    reconnect_interval: Duration,
+    static_nodes: HashMap<PeerId, StaticNode>,
+    trusted: HashSet<PeerId>,
+    connected: HashMap<PeerId, ConnectedPeer>,
+    banned: HashMap<PeerId, Instant>,
+    scores: PeerScores,
+    redials: HashMap<PeerId, Redial>,
+}
+
+impl PeerManager {
+    pub fn new(config: &NetworkConfig, now: Instant) -> Self {
+        let static_nodes: HashMap<PeerId, StaticNode> = config
+            .static_nodes()
+            .into_iter()
+            .map(|node| (node.peer_id.clone(), node))
+            .collect();
+
+        // Static nodes are dialed as soon as the network starts
+        let redials = static_nodes
+            .keys()
+            .map(|id| (id.clone(), Redial { attempts: 0, next_at: now }))
+            .collect();
+
+        Self {
+            max_peers: config.max_peers,
+            local_capabilities: config.capabilities,
+            reconnect_interval: Duration::from_millis(config.reconnect_interval_ms),
+            static_nodes,
+            trusted: config.trusted_peers.iter().cloned().collect(),
+            connected: HashMap::new(),
+            banned: HashMap::new(),
+            scores: PeerScores::new(&config.scoring),
+            redials,
+        }
+    }
+
+    pub fn kind_of(&self, peer_id: &str) -> PeerKind {
+        if self.static_nodes.contains_key(peer_id) {
+            PeerKind::Static
+        } else if self.trusted.contains(peer_id) {
+            PeerKind::Trusted
+        } else {
+            PeerKind::Regular
+        }
+    }
+
+    /// Slots shared by regular peers
+    pub fn max_peers(&self) -> usize {
+        self.max_peers
+    }
+
+    pub fn is_connected(&self, peer_id: &str) -> bool {
+        self.connected.contains_key(peer_id)
+    }
+
+    /// Regular peers currently using a slot
+    pub fn regular_peer_count(&self) -> usize {
+        self.connected.values().filter(|p| p.kind == PeerKind::Regular).count()
+    }
+
+    pub fn connected_peers(&self) -> impl Iterator<Item = (&PeerId, &ConnectedPeer)> {
+        self.connected.iter()
+    }
+
+    /// Decide whether a peer may take a connection slot
+    pub fn admit(&self, peer_id: &str, now: Instant) -> Result<PeerKind> {
+        let kind = self.kind_of(peer_id);
+        let reject = |reason: &str| {
+            Err(NetworkError::PeerRejected {
+                peer_id: peer_id.to_string(),
+                reason: reason.to_string(),
+            })
+        };
+
+        if self.connected.contains_key(peer_id) {
+            return reject("already connected");
+        }
+        if kind.is_protected() {
+            return Ok(kind);
+        }
+        if self.is_banned(peer_id, now) {
+            return reject("banned");
+        }
+        if self.regular_peer_count() >= self.max_peers {
+            return reject("no free peer slots");
+        }
+        Ok(kind)
+    }
+
+    /// Admit the sender of `remote` if it follows `chain` and shares a
+    /// protocol version with us; on error the connection is to be closed
+    pub fn on_handshake(
+        &mut self,
+        chain: &ChainIdentity,
+        remote: &Handshake,
+        addr: SocketAddr,
+        now: Instant,
+    ) -> Result<(PeerKind, u32)> {
+        self.admit(&remote.peer_id, now)?;
+        let version = check_handshake(chain, remote)?;
+        let kind = self.on_connected(&remote.peer_id, addr, remote.capabilities, version, now);
+        Ok((kind, version))
+    }
+
+    /// Record a completed handshake, keeping only capabilities both sides support
+    pub fn on_connected(
+        &mut self,
+        peer_id: &str,
+        addr: SocketAddr,
+        remote_capabilities: Capabilities,
+        protocol_version: u32,
+        now: Instant,
+    ) -> PeerKind {
+        let kind = self.kind_of(peer_id);
+        let capabilities = self.local_capabilities.negotiate(remote_capabilities);
+        self.redials.remove(peer_id);
+        self.connected.insert(
+            peer_id.to_string(),
+            ConnectedPeer { addr, kind, capabilities, protocol_version, connected_at: now },
+        );
+        kind
+    }
+
+    /// Connected peers that negotiated the capability and protocol version a message needs
+    pub fn route(&self, kind: MessageKind) -> Vec<PeerId> {
+        let required = kind.required_capability();
+        self.connected
+            .iter()
+            .filter(|(_, peer)| peer.capabilities.contains(required))
+            .filter(|(_, peer)| peer.protocol_version >= kind.min_protocol_version())
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Connected peer count per negotiated protocol version, for deciding when to drop the oldest
+    pub fn peers_by_version(&self) -> BTreeMap<u32, usize> {
+        let mut counts = BTreeMap::new();
+        for peer in self.connected.values() {
+            *counts.entry(peer.protocol_version).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Pick up to `count` peers, preferring those advertising the most of `wanted`.
+    ///
+    /// Peers lacking every wanted capability are still returned when there
+    /// are not enough better candidates; protected peers win ties.
+    pub fn select_peers(&self, wanted: Capabilities, count: usize) -> Vec<PeerId> {
+        let mut candidates: Vec<(&PeerId, &ConnectedPeer)> = self.connected.iter().collect();
+        candidates.sort_by(|(a_id, a), (b_id, b)| {
+            b.capabilities
+                .overlap(wanted)
+                .cmp(&a.capabilities.overlap(wanted))
+                .then_with(|| b.kind.is_protected().cmp(&a.kind.is_protected()))
+                .then_with(|| a_id.cmp(b_id))
+        });
+        candidates.into_iter().take(count).map(|(id, _)| id.clone()).collect()
+    }
+
+    /// Record a disconnect; static nodes are scheduled for an immediate redial
+    pub fn on_disconnected(&mut self, peer_id: &str, now: Instant) {
+        self.connected.remove(peer_id);
+        if self.static_nodes.contains_key(peer_id) {
+            self.redials.insert(peer_id.to_string(), Redial { attempts: 0, next_at: now });
+        }
+    }
+
+    /// Record a failed dial; later attempts wait `reconnect_interval` each time
+    pub fn on_dial_failed(&mut self, peer_id: &str, now: Instant) {
+        if let Some(redial) = self.redials.get_mut(peer_id) {
+            redial.attempts += 1;
+            redial.next_at = now + self.reconnect_interval;
+        }
+    }
+
+    /// Static nodes that should be dialed now
+    pub fn due_static_dials(&self, now: Instant) -> Vec<StaticNode> {
+        self.redials
+            .iter()
+            .filter(|(id, redial)| redial.next_at <= now && !self.connected.contains_key(*id))
+            .filter_map(|(id, _)| self.static_nodes.get(id).cloned())
+            .collect()
+    }
+
+    /// Ban a peer until `until`; returns false for static and trusted peers, which are exempt
+    pub fn ban(&mut self, peer_id: &str, until: Instant) -> bool {
+        if self.kind_of(peer_id).is_protected() {
+            return false;
+        }
+        self.banned.insert(peer_id.to_string(), until);
+        true
+    }
+
+    pub fn is_banned(&self, peer_id: &str, now: Instant) -> bool {
+        self.banned.get(peer_id).is_some_and(|until| *until > now)
+    }
+
+    /// Score of a peer at `now`; 0 is a clean record
+    pub fn score(&self, peer_id: &str, now: Instant) -> i64 {
+        self.scores.score(peer_id, now)
+    }
+
+    /// Penalize a peer for `offence`, banning it once its score reaches the
+    /// threshold. The returned ban is in effect already and still has to be
+    /// persisted; the caller also disconnects the peer.
+    pub fn report(&mut self, peer_id: &str, offence: Offence, now: Instant) -> Option<Ban> {
+        let score = self.scores.penalize(peer_id, offence, now);
+        if !self.scores.should_ban(score) {
+            return None;
+        }
+
+        let duration = self.scores.ban_duration();
+        self.banned.retain(|_, until| *until > now);
+        self.scores.prune(now);
+        if !self.ban(peer_id, now + duration) {
+            return None;
+        }
+        // The ban is the punishment; the peer comes back with a clean record
+        self.scores.forget(peer_id);
+        Some(Ban { peer_id: peer_id.to_string(), duration, reason: offence.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::PROTOCOL_VERSION;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, 1], port))
+    }
+
+    const RELAY: Capabilities = Capabilities::BLOCK_RELAY;
+
+    fn manager(now: Instant) -> PeerManager {
+        let config = NetworkConfig {
+            max_peers: 1,
+            static_nodes: vec!["RelayerA@10.0.0.1:30303".to_string()],
+            trusted_peers: vec!["RelayerB".to_string()],
+            ..Default::default()
+        };
+        config.validate().unwrap();
+        PeerManager::new(&config, now)
+    }
+
+    #[test]
+    fn test_parse_static_node() {
+        let node: StaticNode = "Peer1@127.0.0.1:30303".parse().unwrap();
+        assert_eq!(node.peer_id, "Peer1");
+        assert_eq!(node.addr.port(), 30303);
+        assert!("127.0.0.1:30303".parse::<StaticNode>().is_err());
+        assert!("Peer1@nowhere".parse::<StaticNode>().is_err());
+    }
+
+    #[test]
+    fn test_protected_peers_use_dedicated_slots() {
+        let now = Instant::now();
+        let mut peers = manager(now);
+
+        peers.on_connected("Regular1", addr(1), RELAY, PROTOCOL_VERSION, now);
+        assert!(peers.admit("Regular2", now).is_err());
+        assert_eq!(peers.admit("RelayerA", now).unwrap(), PeerKind::Static);
+        assert_eq!(peers.admit("RelayerB", now).unwrap(), PeerKind::Trusted);
+
+        peers.on_connected("RelayerB", addr(2), RELAY, PROTOCOL_VERSION, now);
+        assert_eq!(peers.regular_peer_count(), 1);
+    }
+
+    #[test]
+    fn test_protected_peers_are_never_banned() {
+        let now = Instant::now();
+        let mut peers = manager(now);
+        let later = now + Duration::from_secs(60);
+
+        assert!(!peers.ban("RelayerA", later));
+        assert!(!peers.ban("RelayerB", later));
+        assert!(peers.ban("Regular1", later));
+
+        assert!(peers.admit("Regular1", now).is_err());
+        assert!(peers.admit("Regular1", later).is_ok());
+        assert!(peers.admit("RelayerB", now).is_ok());
+    }
+
+    #[test]
+    fn test_reported_peers_are_banned_at_threshold() {
+        let now = Instant::now();
+        let mut peers = manager(now);
+
+        assert_eq!(peers.report("Regular1", Offence::ProtocolViolation, now), None);
+        assert_eq!(peers.score("Regular1", now), -50);
+        let ban = peers.report("Regular1", Offence::ProtocolViolation, now).unwrap();
+        assert_eq!(ban.duration, Duration::from_secs(3_600));
+        assert_eq!(ban.reason, "protocol violation");
+        assert!(peers.admit("Regular1", now).is_err());
+        assert!(peers.admit("Regular1", now + ban.duration).is_ok());
+        assert_eq!(peers.score("Regular1", now), 0);
+
+        // Protected peers sink below the threshold but stay admitted
+        assert_eq!(peers.report("RelayerB", Offence::InvalidBlock, now), None);
+        assert!(peers.admit("RelayerB", now).is_ok());
+        assert_eq!(peers.score("RelayerB", now), -100);
+    }
+
+    #[test]
+    fn test_static_node_redial() {
+        let now = Instant::now();
+        let mut peers = manager(now);
+        assert_eq!(peers.due_static_dials(now).len(), 1);
+
+        peers.on_connected("RelayerA", addr(30303), RELAY, PROTOCOL_VERSION, now);
+        assert!(peers.due_static_dials(now).is_empty());
+
+        // Dropped static nodes are redialed immediately, then after the interval
+        peers.on_disconnected("RelayerA", now);
+        assert_eq!(peers.due_static_dials(now)[0].peer_id, "RelayerA");
+
+        peers.on_dial_failed("RelayerA", now);
+        assert!(peers.due_static_dials(now).is_empty());
+        assert_eq!(peers.due_static_dials(now + Duration::from_secs(5)).len(), 1);
+
+        // Regular peers are not redialed
+        peers.on_connected("Regular1", addr(1), RELAY, PROTOCOL_VERSION, now);
+        peers.on_disconnected("Regular1", now);
+        assert_eq!(peers.due_static_dials(now + Duration::from_secs(5)).len(), 1);
+    }
+
+    #[test]
+    fn test_capability_routing_and_selection() {
+        let now = Instant::now();
+        let config = NetworkConfig {
+            max_peers: 10,
+            capabilities: Capabilities::BLOCK_RELAY | Capabilities::SNAPSHOT_SERVING,
+            ..Default::default()
+        };
+        let mut peers = PeerManager::new(&config, now);
+
+        peers.on_connected("Plain", addr(1), Capabilities::BLOCK_RELAY, PROTOCOL_VERSION, now);
+        peers.on_connected(
+            "Snapshots",
+            addr(2),
+            Capabilities::BLOCK_RELAY | Capabilities::SNAPSHOT_SERVING,
+            PROTOCOL_VERSION,
+            now,
+        );
+        // Advertised but not supported locally, so never negotiated
+        peers.on_connected("LightOnly", addr(3), Capabilities::LIGHT_CLIENT_SERVING, PROTOCOL_VERSION, now);
+
+        assert_eq!(peers.route(MessageKind::SnapshotRequest), vec!["Snapshots".to_string()]);
+        assert!(peers.route(MessageKind::LightClientRequest).is_empty());
+        assert_eq!(peers.route(MessageKind::BlockAnnouncement).len(), 2);
+
+        let selected = peers.select_peers(Capabilities::SNAPSHOT_SERVING, 2);
+        assert_eq!(selected, vec!["Snapshots".to_string(), "LightOnly".to_string()]);
+    }
+
+    #[test]
+    fn test_mixed_version_routing() {
+        let now = Instant::now();
+        let config = NetworkConfig {
+            capabilities: Capabilities::BLOCK_RELAY | Capabilities::LIGHT_CLIENT_SERVING,
+            ..Default::default()
+        };
+        let mut peers = PeerManager::new(&config, now);
+        let light = Capabilities::BLOCK_RELAY | Capabilities::LIGHT_CLIENT_SERVING;
+
+        peers.on_connected("Old", addr(1), light, 1, now);
+        peers.on_connected("New", addr(2), light, PROTOCOL_VERSION, now);
+
+        // Header sync only exists in version 2
+        assert_eq!(peers.route(MessageKind::GetHeaders), vec!["New".to_string()]);
+        assert_eq!(peers.route(MessageKind::BlockAnnouncement).len(), 2);
+        assert_eq!(peers.peers_by_version(), BTreeMap::from([(1, 1), (PROTOCOL_VERSION, 1)]));
+    }
+}
@@ -0,0 +1,98 @@
+// p2p/p2p-network/src/protocol.rs
+use blockchain_core::{BlockHash, ChainId};
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::Capabilities;
+use crate::role::NodeRole;
+use crate::PeerId;
+
+/// Newest wire protocol version spoken by this build
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest wire protocol version still accepted from peers
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Which chain a node follows; peers on another chain are disconnected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainIdentity {
+    pub chain_id: ChainId,
+    pub genesis_hash: BlockHash,
+}
+
+/// First message on every connection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Handshake {
+    /// Newest version the sender speaks
+    pub protocol_version: u32,
+    /// Oldest version the sender speaks; absent from version 1 peers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_protocol_version: Option<u32>,
+    pub peer_id: PeerId,
+    /// Height of the sender's best block
+    pub best_height: u64,
+    pub capabilities: Capabilities,
+    /// Missing from peers that predate roles, which are treated as full nodes
+    #[serde(default)]
+    pub role: NodeRole,
+    /// Required from version 2 on; version 1 handshakes cannot carry it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<ChainIdentity>,
+}
+
+impl Handshake {
+    /// Handshake advertising the full version range of this build
+    pub fn new(
+        peer_id: PeerId,
+        best_height: u64,
+        capabilities: Capabilities,
+        role: NodeRole,
+        chain: ChainIdentity,
+    ) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: Some(MIN_PROTOCOL_VERSION),
+            peer_id,
+            best_height,
+            capabilities,
+            role,
+            chain: Some(chain),
+        }
+    }
+}
+
+/// Message classes carried over peer connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    BlockAnnouncement,
+    CompactBlock,
+    Transactions,
+    SnapshotRequest,
+    SnapshotChunk,
+    LightClientRequest,
+    GetHeaders,
+    BlockHeaders,
+    FindNode,
+    Neighbors,
+    /// The address a peer sees us at, for external address discovery
+    Identify,
+    ArchiveRequest,
+    ArchiveResponse,
+}
+
+impl MessageKind {
+    /// Capability a peer must have negotiated to be sent this message
+    pub const fn required_capability(self) -> Capabilities {
+        match self {
+            MessageKind::BlockAnnouncement => Capabilities::BLOCK_RELAY,
+            MessageKind::CompactBlock => Capabilities::COMPACT_BLOCKS,
+            MessageKind::Transactions => Capabilities::TX_GOSSIP,
+            MessageKind::SnapshotRequest | MessageKind::SnapshotChunk => Capabilities::SNAPSHOT_SERVING,
+            MessageKind::LightClientRequest | MessageKind::GetHeaders | MessageKind::BlockHeaders => {
+                Capabilities::LIGHT_CLIENT_SERVING
+            }
+            MessageKind::ArchiveRequest | MessageKind::ArchiveResponse => Capabilities::ARCHIVE_SERVING,
+            // Every node takes part in discovery
+            MessageKind::FindNode | MessageKind::Neighbors | MessageKind::Identify => Capabilities::NONE,
+        }
+    }
+}
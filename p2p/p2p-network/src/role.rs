@@ -0,0 +1,146 @@
+// p2p/p2p-network/src/role.rs
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::capabilities::Capabilities;
+
+/// Part a node plays in the network topology
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeRole {
+    /// Validates, relays and serves everything
+    #[default]
+    Full,
+    /// Forwards blocks and transactions without running consensus or serving history
+    RelayOnly,
+    /// Full node that also serves snapshots and historical data
+    Archive,
+    /// Public RPC frontend; gossips transactions but does not relay blocks
+    RpcOnly,
+}
+
+/// Node subsystems switched on by a role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subsystems {
+    pub consensus: bool,
+    pub relayer: bool,
+    pub archival_serving: bool,
+    pub public_rpc: bool,
+}
+
+impl NodeRole {
+    pub fn subsystems(self) -> Subsystems {
+        match self {
+            NodeRole::Full => Subsystems {
+                consensus: true,
+                relayer: true,
+                archival_serving: false,
+                public_rpc: true,
+            },
+            NodeRole::RelayOnly => Subsystems {
+                consensus: false,
+                relayer: true,
+                archival_serving: false,
+                public_rpc: false,
+            },
+            NodeRole::Archive => Subsystems {
+                consensus: true,
+                relayer: false,
+                archival_serving: true,
+                public_rpc: true,
+            },
+            NodeRole::RpcOnly => Subsystems {
+                consensus: false,
+                relayer: false,
+                archival_serving: false,
+                public_rpc: true,
+            },
+        }
+    }
+
+    /// Sub-protocols advertised by default
+    pub fn default_capabilities(self) -> Capabilities {
+        match self {
+            NodeRole::Full => Capabilities::BLOCK_RELAY | Capabilities::TX_GOSSIP,
+            NodeRole::RelayOnly => {
+                Capabilities::BLOCK_RELAY | Capabilities::TX_GOSSIP | Capabilities::COMPACT_BLOCKS
+            }
+            NodeRole::Archive => {
+                Capabilities::BLOCK_RELAY
+                    | Capabilities::TX_GOSSIP
+                    | Capabilities::SNAPSHOT_SERVING
+                    | Capabilities::LIGHT_CLIENT_SERVING
+                    | Capabilities::ARCHIVE_SERVING
+            }
+            NodeRole::RpcOnly => Capabilities::TX_GOSSIP,
+        }
+    }
+
+    /// Default peer limit; relays fan out widely, RPC frontends need few peers
+    pub fn default_max_peers(self) -> usize {
+        match self {
+            NodeRole::Full | NodeRole::Archive => 50,
+            NodeRole::RelayOnly => 200,
+            NodeRole::RpcOnly => 16,
+        }
+    }
+
+    /// Default first-seen cache size, scaled with expected announcement volume
+    pub fn default_seen_cache_capacity(self) -> usize {
+        match self {
+            NodeRole::RelayOnly => 500_000,
+            NodeRole::RpcOnly => 20_000,
+            NodeRole::Full | NodeRole::Archive => 100_000,
+        }
+    }
+}
+
+impl fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeRole::Full => write!(f, "full"),
+            NodeRole::RelayOnly => write!(f, "relay_only"),
+            NodeRole::Archive => write!(f, "archive"),
+            NodeRole::RpcOnly => write!(f, "rpc_only"),
+        }
+    }
+}
+
+impl FromStr for NodeRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "full" => Ok(NodeRole::Full),
+            "relay_only" | "relay" => Ok(NodeRole::RelayOnly),
+            "archive" => Ok(NodeRole::Archive),
+            "rpc_only" | "rpc" => Ok(NodeRole::RpcOnly),
+            _ => Err(format!("Invalid node role: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_parsing() {
+        assert_eq!("relay-only".parse::<NodeRole>().unwrap(), NodeRole::RelayOnly);
+        assert_eq!("RPC_ONLY".parse::<NodeRole>().unwrap(), NodeRole::RpcOnly);
+        for role in [NodeRole::Full, NodeRole::RelayOnly, NodeRole::Archive, NodeRole::RpcOnly] {
+            assert_eq!(role.to_string().parse::<NodeRole>().unwrap(), role);
+        }
+        assert!("validator".parse::<NodeRole>().is_err());
+    }
+
+    #[test]
+    fn test_role_subsystems() {
+        assert!(!NodeRole::RelayOnly.subsystems().consensus);
+        assert!(NodeRole::Archive.subsystems().archival_serving);
+        assert!(NodeRole::Archive.default_capabilities().contains(Capabilities::SNAPSHOT_SERVING));
+        assert!(NodeRole::Archive.default_capabilities().contains(Capabilities::ARCHIVE_SERVING));
+        assert!(!NodeRole::RpcOnly.default_capabilities().contains(Capabilities::BLOCK_RELAY));
+    }
+}
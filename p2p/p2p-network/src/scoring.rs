@@ -0,0 +1,240 @@
+// p2p/p2p-network/src/scoring.rs
+//! Peer reputation.
+//!
+//! Every peer starts at a score of 0. Each offence subtracts its penalty, and
+//! the score recovers toward 0 by `recovery_per_minute` while the peer behaves.
+//! Once it falls to `ban_threshold` the peer manager bans it for
+//! `ban_duration_secs` and its score starts over. Static and trusted peers are
+//! scored like anyone else but never banned.
+//!
+//! Bans are kept in the peer store with their expiry so a restart does not
+//! readmit a peer early; `restore_bans` reapplies the ones still in force.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use storage_traits::{PeerBan, PeerStore};
+
+use crate::peer_manager::PeerManager;
+use crate::{NetworkError, PeerId, Result};
+
+/// Misbehaviour that costs a peer reputation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offence {
+    InvalidBlock,
+    InvalidTransaction,
+    /// Malformed or unexpected message
+    ProtocolViolation,
+    /// No reply to a request in time
+    Timeout,
+}
+
+impl std::fmt::Display for Offence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Offence::InvalidBlock => write!(f, "invalid block"),
+            Offence::InvalidTransaction => write!(f, "invalid transaction"),
+            Offence::ProtocolViolation => write!(f, "protocol violation"),
+            Offence::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringConfig {
+    pub invalid_block_penalty: i64,
+    pub invalid_transaction_penalty: i64,
+    pub protocol_violation_penalty: i64,
+    pub timeout_penalty: i64,
+    /// Score at or below which a regular peer is banned
+    pub ban_threshold: i64,
+    pub ban_duration_secs: u64,
+    /// Points regained per minute without offences, up to 0
+    pub recovery_per_minute: i64,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            invalid_block_penalty: 100,
+            invalid_transaction_penalty: 10,
+            protocol_violation_penalty: 50,
+            timeout_penalty: 5,
+            ban_threshold: -100,
+            ban_duration_secs: 3_600,
+            recovery_per_minute: 1,
+        }
+    }
+}
+
+impl ScoringConfig {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        let penalties = [
+            self.invalid_block_penalty,
+            self.invalid_transaction_penalty,
+            self.protocol_violation_penalty,
+            self.timeout_penalty,
+        ];
+        if penalties.iter().any(|penalty| *penalty <= 0) {
+            return Err("Scoring penalties must be greater than 0".to_string());
+        }
+        if self.ban_threshold >= 0 {
+            return Err("ban_threshold must be below 0".to_string());
+        }
+        if self.ban_duration_secs == 0 {
+            return Err("ban_duration_secs must be greater than 0".to_string());
+        }
+        if self.recovery_per_minute < 0 {
+            return Err("recovery_per_minute must not be negative".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn penalty(&self, offence: Offence) -> i64 {
+        match offence {
+            Offence::InvalidBlock => self.invalid_block_penalty,
+            Offence::InvalidTransaction => self.invalid_transaction_penalty,
+            Offence::ProtocolViolation => self.protocol_violation_penalty,
+            Offence::Timeout => self.timeout_penalty,
+        }
+    }
+}
+
+/// A ban issued by scoring, to be persisted with `record_ban`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ban {
+    pub peer_id: PeerId,
+    pub duration: Duration,
+    /// The offence that crossed the threshold
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Score {
+    value: i64,
+    updated_at: Instant,
+}
+
+/// Current score of every peer that has offended and not fully recovered
+pub struct PeerScores {
+    config: ScoringConfig,
+    scores: HashMap<PeerId, Score>,
+}
+
+impl PeerScores {
+    pub fn new(config: &ScoringConfig) -> Self {
+        Self { config: config.clone(), scores: HashMap::new() }
+    }
+
+    /// Score at `now` with recovery applied; 0 for unknown peers
+    pub fn score(&self, peer_id: &str, now: Instant) -> i64 {
+        self.scores.get(peer_id).map_or(0, |score| self.recovered(score, now))
+    }
+
+    /// Subtract the offence's penalty, returning the new score
+    pub fn penalize(&mut self, peer_id: &str, offence: Offence, now: Instant) -> i64 {
+        let value = self.score(peer_id, now) - self.config.penalty(offence);
+        self.scores.insert(peer_id.to_string(), Score { value, updated_at: now });
+        value
+    }
+
+    /// Whether `score` is low enough for a ban
+    pub fn should_ban(&self, score: i64) -> bool {
+        score <= self.config.ban_threshold
+    }
+
+    pub fn ban_duration(&self) -> Duration {
+        Duration::from_secs(self.config.ban_duration_secs)
+    }
+
+    pub fn forget(&mut self, peer_id: &str) {
+        self.scores.remove(peer_id);
+    }
+
+    /// Drop peers that have recovered to 0
+    pub fn prune(&mut self, now: Instant) {
+        let recovery = self.config.recovery_per_minute;
+        self.scores.retain(|_, score| recovered(score, recovery, now) < 0);
+    }
+
+    fn recovered(&self, score: &Score, now: Instant) -> i64 {
+        recovered(score, self.config.recovery_per_minute, now)
+    }
+}
+
+fn recovered(score: &Score, recovery_per_minute: i64, now: Instant) -> i64 {
+    let minutes = now.saturating_duration_since(score.updated_at).as_secs() / 60;
+    let regained = recovery_per_minute.saturating_mul(minutes as i64);
+    score.value.saturating_add(regained).min(0)
+}
+
+/// Persist `ban`, issued at `now` (Unix seconds), so it survives a restart
+pub async fn record_ban(store: &dyn PeerStore, ban: &Ban, now: i64) -> Result<()> {
+    let ban = PeerBan {
+        peer_id: ban.peer_id.clone(),
+        until: now.saturating_add(ban.duration.as_secs() as i64),
+        reason: ban.reason.clone(),
+    };
+    store
+        .record_peer_ban(&ban)
+        .await
+        .map_err(|e| NetworkError::Storage(e.to_string()))
+}
+
+/// Reapply stored bans still in force, returning how many were restored.
+///
+/// `now` and `now_unix` are the same moment on the two clocks.
+pub async fn restore_bans(
+    store: &dyn PeerStore,
+    peers: &mut PeerManager,
+    now: Instant,
+    now_unix: i64,
+) -> Result<usize> {
+    let bans = store
+        .load_peer_bans(now_unix)
+        .await
+        .map_err(|e| NetworkError::Storage(e.to_string()))?;
+
+    let mut restored = 0;
+    for ban in bans {
+        let remaining = Duration::from_secs(ban.until.saturating_sub(now_unix).max(0) as u64);
+        if !remaining.is_zero() && peers.ban(&ban.peer_id, now + remaining) {
+            restored += 1;
+        }
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_penalties_accumulate_and_recover() {
+        let config = ScoringConfig::default();
+        config.validate().unwrap();
+        let mut scores = PeerScores::new(&config);
+        let now = Instant::now();
+
+        assert_eq!(scores.penalize("peer-a", Offence::InvalidTransaction, now), -10);
+        assert_eq!(scores.penalize("peer-a", Offence::ProtocolViolation, now), -60);
+        assert!(!scores.should_ban(-60));
+
+        // One point back per minute, never above 0
+        assert_eq!(scores.score("peer-a", now + Duration::from_secs(600)), -50);
+        assert_eq!(scores.score("peer-a", now + Duration::from_secs(7_200)), 0);
+        assert_eq!(scores.score("peer-b", now), 0);
+
+        let score = scores.penalize("peer-a", Offence::InvalidBlock, now);
+        assert_eq!(score, -160);
+        assert!(scores.should_ban(score));
+
+        // Pruning forgets a peer only once it has fully recovered
+        scores.prune(now + Duration::from_secs(7_200));
+        assert_eq!(scores.score("peer-a", now), -160);
+        scores.prune(now + Duration::from_secs(9_600));
+        assert_eq!(scores.score("peer-a", now), 0);
+
+        assert!(ScoringConfig { ban_threshold: 0, ..config.clone() }.validate().is_err());
+        assert!(ScoringConfig { timeout_penalty: 0, ..config }.validate().is_err());
+    }
+}
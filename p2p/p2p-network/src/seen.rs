@@ -0,0 +1,197 @@
+// p2p/p2p-network/src/seen.rs
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::PeerId;
+
+/// Kind of announced object; block and transaction hashes are tracked separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InventoryKind {
+    Block,
+    Transaction,
+}
+
+/// Limits on first-seen tracking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeenCacheConfig {
+    /// Hashes remembered before the oldest are forgotten
+    pub capacity: usize,
+    /// How long a hash is remembered
+    pub ttl_secs: u64,
+}
+
+impl Default for SeenCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 100_000,
+            ttl_secs: 600,
+        }
+    }
+}
+
+/// Where and when a hash was first announced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirstSeen {
+    pub peer_id: PeerId,
+    pub at: Instant,
+    /// Later announcements of the same hash
+    pub duplicates: u32,
+}
+
+/// Result of recording an announcement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Observation {
+    /// New hash; process it
+    First,
+    /// Already seen; skip processing
+    Duplicate { first_peer: PeerId, first_seen: Instant },
+}
+
+impl Observation {
+    pub fn is_first(&self) -> bool {
+        matches!(self, Observation::First)
+    }
+}
+
+/// Announcement counters for one peer, fed into peer scoring
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnnouncementStats {
+    /// Announcements this peer delivered before anyone else
+    pub first: u64,
+    /// Announcements of hashes already seen from another peer
+    pub duplicate: u64,
+}
+
+/// Bounded first-seen record of block and transaction hashes
+pub struct SeenCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: HashMap<(InventoryKind, [u8; 32]), FirstSeen>,
+    /// Insertion order, for capacity and age eviction
+    order: VecDeque<(InventoryKind, [u8; 32])>,
+    stats: HashMap<PeerId, AnnouncementStats>,
+}
+
+impl SeenCache {
+    pub fn new(config: &SeenCacheConfig) -> Self {
+        Self {
+            capacity: config.capacity,
+            ttl: Duration::from_secs(config.ttl_secs),
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            stats: HashMap::new(),
+        }
+    }
+
+    /// Record that `peer_id` announced `hash`, crediting it if it was first
+    pub fn observe(&mut self, kind: InventoryKind, hash: [u8; 32], peer_id: &str, now: Instant) -> Observation {
+        self.expire(now);
+
+        if let Some(seen) = self.entries.get_mut(&(kind, hash)) {
+            seen.duplicates += 1;
+            // A peer repeating its own announcement is not a late duplicate
+            if seen.peer_id != peer_id {
+                self.stats.entry(peer_id.to_string()).or_default().duplicate += 1;
+            }
+            return Observation::Duplicate {
+                first_peer: seen.peer_id.clone(),
+                first_seen: seen.at,
+            };
+        }
+
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.entries.insert(
+            (kind, hash),
+            FirstSeen {
+                peer_id: peer_id.to_string(),
+                at: now,
+                duplicates: 0,
+            },
+        );
+        self.order.push_back((kind, hash));
+        self.stats.entry(peer_id.to_string()).or_default().first += 1;
+        Observation::First
+    }
+
+    pub fn first_seen(&self, kind: InventoryKind, hash: &[u8; 32]) -> Option<&FirstSeen> {
+        self.entries.get(&(kind, *hash))
+    }
+
+    pub fn stats(&self, peer_id: &str) -> AnnouncementStats {
+        self.stats.get(peer_id).copied().unwrap_or_default()
+    }
+
+    /// Drop a disconnected peer's counters, returning them for scoring
+    pub fn take_stats(&mut self, peer_id: &str) -> AnnouncementStats {
+        self.stats.remove(peer_id).unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some(key) = self.order.front() {
+            match self.entries.get(key) {
+                Some(seen) if now.saturating_duration_since(seen.at) < self.ttl => break,
+                _ => {
+                    self.entries.remove(key);
+                    self.order.pop_front();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(capacity: usize) -> SeenCache {
+        SeenCache::new(&SeenCacheConfig { capacity, ttl_secs: 60 })
+    }
+
+    #[test]
+    fn test_duplicates_suppressed_and_first_peer_credited() {
+        let now = Instant::now();
+        let mut seen = cache(10);
+
+        assert!(seen.observe(InventoryKind::Block, [1; 32], "peer-a", now).is_first());
+        assert_eq!(
+            seen.observe(InventoryKind::Block, [1; 32], "peer-b", now),
+            Observation::Duplicate { first_peer: "peer-a".to_string(), first_seen: now }
+        );
+        // Same hash as a different kind is tracked separately
+        assert!(seen.observe(InventoryKind::Transaction, [1; 32], "peer-b", now).is_first());
+
+        assert_eq!(seen.stats("peer-a"), AnnouncementStats { first: 1, duplicate: 0 });
+        assert_eq!(seen.stats("peer-b"), AnnouncementStats { first: 1, duplicate: 1 });
+        assert_eq!(seen.first_seen(InventoryKind::Block, &[1; 32]).unwrap().duplicates, 1);
+    }
+
+    #[test]
+    fn test_eviction_by_capacity_and_age() {
+        let now = Instant::now();
+        let mut seen = cache(2);
+
+        seen.observe(InventoryKind::Block, [1; 32], "peer-a", now);
+        seen.observe(InventoryKind::Block, [2; 32], "peer-a", now);
+        seen.observe(InventoryKind::Block, [3; 32], "peer-a", now);
+        assert_eq!(seen.len(), 2);
+        assert!(seen.first_seen(InventoryKind::Block, &[1; 32]).is_none());
+
+        let later = now + Duration::from_secs(61);
+        assert!(seen.observe(InventoryKind::Block, [2; 32], "peer-b", later).is_first());
+        assert_eq!(seen.len(), 1);
+    }
+}
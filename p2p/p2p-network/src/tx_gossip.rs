@@ -0,0 +1,352 @@
+// p2p/p2p-network/src/tx_gossip.rs
+//! Transaction gossip.
+//!
+//! Transactions travel on the `TX_TOPIC` gossipsub topic in `Transactions`
+//! messages. Every hash goes through the seen-cache, so a transaction is
+//! queued and forwarded once however many peers relay it. Remote messages are
+//! charged one token per transaction against the sender's rate limit, checked
+//! with `validate_structure` and added to the pending queue; signatures,
+//! nonces and balances are left to admission and validation.
+//!
+//! Nothing here does network I/O: callers publish the returned messages and
+//! feed received ones to `ingest` along with the mempool to queue them in.
+use blockchain_core::{Transaction, TxHash};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use storage_traits::BlockchainStorage;
+
+use crate::headers::PeerRateLimiter;
+use crate::seen::{AnnouncementStats, InventoryKind, Observation, SeenCache, SeenCacheConfig};
+use crate::{NetworkError, PeerId, Result};
+#[cfg(feature = "fault-injection")]
+use crate::{FaultInjector, MessageFate};
+
+/// Gossipsub topic transactions are published on
+pub const TX_TOPIC: &str = "/blockchain/txs/1";
+
+/// Body of a `MessageKind::Transactions` message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionsMessage {
+    pub transactions: Vec<Transaction>,
+}
+
+/// Limits on gossiped transactions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxGossipConfig {
+    /// Larger messages are rejected outright and never sent
+    pub max_transactions_per_message: usize,
+    /// Sustained transactions per second accepted from one peer
+    pub transactions_per_second: u32,
+    /// Transactions a peer may send in a burst before the rate applies
+    pub burst: u32,
+    /// Mempool transactions re-announced per `announce_pending` call
+    pub pending_announce_limit: i32,
+}
+
+impl Default for TxGossipConfig {
+    fn default() -> Self {
+        Self {
+            max_transactions_per_message: 256,
+            transactions_per_second: 500,
+            burst: 2_000,
+            pending_announce_limit: 1_000,
+        }
+    }
+}
+
+impl TxGossipConfig {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.max_transactions_per_message == 0 || self.transactions_per_second == 0 {
+            return Err("Transaction gossip limits must be greater than 0".to_string());
+        }
+        // A burst smaller than one full message would reject every full message
+        if (self.burst as usize) < self.max_transactions_per_message {
+            return Err("Transaction gossip burst must cover max_transactions_per_message".to_string());
+        }
+        if self.pending_announce_limit <= 0 {
+            return Err("pending_announce_limit must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// What became of a received message
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IngestReport {
+    /// New, well-formed transactions now in the pending queue, in message order
+    pub accepted: Vec<Transaction>,
+    /// Already seen from this or another peer
+    pub duplicates: usize,
+    /// Failed structure validation, with the reason
+    pub invalid: Vec<(TxHash, String)>,
+}
+
+impl IngestReport {
+    /// Message re-gossiping the accepted transactions, if there are any
+    pub fn forward(&self) -> Option<TransactionsMessage> {
+        (!self.accepted.is_empty()).then(|| TransactionsMessage { transactions: self.accepted.clone() })
+    }
+}
+
+/// Publishes local transactions and ingests remote ones, each at most once
+pub struct TxGossip {
+    local_peer_id: PeerId,
+    max_transactions_per_message: usize,
+    pending_announce_limit: i32,
+    seen: Mutex<SeenCache>,
+    limiter: Mutex<PeerRateLimiter>,
+    #[cfg(feature = "fault-injection")]
+    faults: FaultInjector,
+}
+
+impl TxGossip {
+    pub fn new(local_peer_id: PeerId, config: &TxGossipConfig, seen: &SeenCacheConfig) -> Self {
+        Self {
+            local_peer_id,
+            max_transactions_per_message: config.max_transactions_per_message,
+            pending_announce_limit: config.pending_announce_limit,
+            seen: Mutex::new(SeenCache::new(seen)),
+            limiter: Mutex::new(PeerRateLimiter::new(config.transactions_per_second, config.burst)),
+            #[cfg(feature = "fault-injection")]
+            faults: FaultInjector::default(),
+        }
+    }
+
+    /// Delay received messages or drop their sender as `faults` decides
+    #[cfg(feature = "fault-injection")]
+    pub fn with_faults(mut self, faults: FaultInjector) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Messages carrying the transactions not gossiped yet, split to the
+    /// per-message limit; empty when every transaction was already seen
+    pub fn announce(&self, transactions: &[Transaction], now: Instant) -> Vec<TransactionsMessage> {
+        let fresh: Vec<Transaction> = {
+            let mut seen = self.seen.lock();
+            transactions
+                .iter()
+                .filter(|tx| seen.observe(InventoryKind::Transaction, tx.hash, &self.local_peer_id, now).is_first())
+                .cloned()
+                .collect()
+        };
+
+        fresh
+            .chunks(self.max_transactions_per_message)
+            .map(|chunk| TransactionsMessage { transactions: chunk.to_vec() })
+            .collect()
+    }
+
+    /// Announce mempool transactions that have not been gossiped yet, e.g.
+    /// after startup or once new peers connect
+    pub async fn announce_pending(
+        &self,
+        storage: &dyn BlockchainStorage,
+        now: Instant,
+    ) -> Result<Vec<TransactionsMessage>> {
+        let pending = storage
+            .get_pending_transactions(self.pending_announce_limit)
+            .await
+            .map_err(|e| NetworkError::Storage(e.to_string()))?;
+        Ok(self.announce(&pending, now))
+    }
+
+    /// Screen a message from `peer_id` and queue its new, well-formed transactions
+    pub async fn ingest(
+        &self,
+        storage: &dyn BlockchainStorage,
+        peer_id: &str,
+        message: &TransactionsMessage,
+        now: Instant,
+    ) -> Result<IngestReport> {
+        #[cfg(feature = "fault-injection")]
+        if let Some(delay) = self.injected_delay(peer_id)? {
+            tokio::time::sleep(delay).await;
+        }
+        let report = self.screen(peer_id, message, now)?;
+        for tx in &report.accepted {
+            storage
+                .add_pending_transaction(tx)
+                .await
+                .map_err(|e| NetworkError::Storage(e.to_string()))?;
+        }
+        Ok(report)
+    }
+
+    /// Rate limit, validate and deduplicate a message without touching storage
+    fn screen(&self, peer_id: &str, message: &TransactionsMessage, now: Instant) -> Result<IngestReport> {
+        let count = message.transactions.len();
+        if count > self.max_transactions_per_message {
+            return Err(NetworkError::PeerRejected {
+                peer_id: peer_id.to_string(),
+                reason: format!(
+                    "{} transactions in one message, limit {}",
+                    count, self.max_transactions_per_message
+                ),
+            });
+        }
+        if !self.limiter.lock().try_acquire(peer_id, count as u32, now) {
+            return Err(NetworkError::RateLimited { peer_id: peer_id.to_string() });
+        }
+
+        let mut report = IngestReport::default();
+        let mut seen = self.seen.lock();
+        for tx in &message.transactions {
+            // Checked before the seen-cache, whose key is the claimed hash, so a
+            // malformed copy cannot shadow the real transaction
+            if let Err(e) = tx.validate_structure() {
+                report.invalid.push((tx.hash, e.to_string()));
+                continue;
+            }
+            match seen.observe(InventoryKind::Transaction, tx.hash, peer_id, now) {
+                Observation::First => report.accepted.push(tx.clone()),
+                Observation::Duplicate { .. } => report.duplicates += 1,
+            }
+        }
+        Ok(report)
+    }
+
+    /// Delay before handling a message from `peer_id`, or an error dropping the peer
+    #[cfg(feature = "fault-injection")]
+    fn injected_delay(&self, peer_id: &str) -> Result<Option<std::time::Duration>> {
+        match self.faults.message_fate() {
+            MessageFate::Deliver => Ok(None),
+            MessageFate::Delay(delay) => Ok(Some(delay)),
+            MessageFate::DropPeer => Err(NetworkError::PeerRejected {
+                peer_id: peer_id.to_string(),
+                reason: "Injected peer drop".to_string(),
+            }),
+        }
+    }
+
+    /// Drop a disconnected peer's rate limit, returning its announcement counters for scoring
+    pub fn on_disconnected(&self, peer_id: &str) -> AnnouncementStats {
+        self.limiter.lock().forget(peer_id);
+        self.seen.lock().take_stats(peer_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blockchain_core::{Address, AddressExt, KeyPair, SignatureScheme};
+    use std::time::Duration;
+
+    fn gossip(config: &TxGossipConfig) -> TxGossip {
+        let seen = SeenCacheConfig { capacity: 1_000, ttl_secs: 60 };
+        TxGossip::new("local".to_string(), config, &seen)
+    }
+
+    fn transfers(count: u64) -> Vec<Transaction> {
+        let key = KeyPair::generate(SignatureScheme::Ed25519);
+        let from = Address::from_public_key(&key.public_key()).unwrap();
+        (0..count)
+            .map(|nonce| {
+                let mut tx = Transaction::new_transfer(from, [2; 20], 10, nonce, 21_000, 1).unwrap();
+                tx.sign(&key);
+                tx
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_message_roundtrip_with_legacy_transactions() {
+        // A legacy transaction followed by chain-bound ones must not read into its neighbour
+        let mut transactions = transfers(3);
+        transactions[1] = transactions[1].clone().with_chain_id(2).unwrap();
+        transactions[2] = transactions[2].clone().with_chain_id(2).unwrap();
+        let message = TransactionsMessage { transactions };
+
+        let encoded = bincode::serialize(&message).unwrap();
+        assert_eq!(bincode::deserialize::<TransactionsMessage>(&encoded).unwrap(), message);
+        let json = serde_json::to_vec(&message).unwrap();
+        assert_eq!(serde_json::from_slice::<TransactionsMessage>(&json).unwrap(), message);
+    }
+
+    #[test]
+    fn test_announce_skips_gossiped_and_splits() {
+        let gossip = gossip(&TxGossipConfig { max_transactions_per_message: 2, ..Default::default() });
+        let now = Instant::now();
+        let txs = transfers(3);
+
+        let messages = gossip.announce(&txs, now);
+        assert_eq!(messages.iter().map(|m| m.transactions.len()).collect::<Vec<_>>(), vec![2, 1]);
+        assert!(gossip.announce(&txs, now).is_empty());
+
+        // A transaction that arrived from a peer is not announced again
+        let remote = transfers(1);
+        let message = TransactionsMessage { transactions: remote.clone() };
+        assert_eq!(gossip.screen("peer-a", &message, now).unwrap().accepted, remote);
+        assert!(gossip.announce(&remote, now).is_empty());
+    }
+
+    #[test]
+    fn test_screen_deduplicates_and_validates() {
+        let gossip = gossip(&TxGossipConfig::default());
+        let now = Instant::now();
+        let txs = transfers(2);
+
+        let mut forged = txs[1].clone();
+        forged.gas_price = 1_000;
+        let message = TransactionsMessage { transactions: vec![txs[0].clone(), forged, txs[0].clone()] };
+        let report = gossip.screen("peer-a", &message, now).unwrap();
+        assert_eq!(report.accepted, vec![txs[0].clone()]);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.invalid.len(), 1);
+        assert_eq!(report.forward().unwrap().transactions, vec![txs[0].clone()]);
+
+        // The forged copy did not claim the hash, so the real one still gets in
+        let message = TransactionsMessage { transactions: txs.clone() };
+        let report = gossip.screen("peer-b", &message, now).unwrap();
+        assert_eq!(report.accepted, vec![txs[1].clone()]);
+        assert_eq!(report.duplicates, 1);
+        assert!(report.forward().is_some());
+
+        assert_eq!(gossip.on_disconnected("peer-b"), AnnouncementStats { first: 1, duplicate: 1 });
+    }
+
+    #[test]
+    fn test_per_peer_rate_limit_and_message_size() {
+        let config = TxGossipConfig {
+            max_transactions_per_message: 4,
+            transactions_per_second: 2,
+            burst: 4,
+            ..Default::default()
+        };
+        let gossip = gossip(&config);
+        let now = Instant::now();
+        let txs = transfers(9);
+        let message = |range: std::ops::Range<usize>| TransactionsMessage { transactions: txs[range].to_vec() };
+
+        assert!(matches!(
+            gossip.screen("peer-a", &message(0..5), now),
+            Err(NetworkError::PeerRejected { .. })
+        ));
+        assert!(gossip.screen("peer-a", &message(0..4), now).is_ok());
+        assert!(matches!(
+            gossip.screen("peer-a", &message(4..5), now),
+            Err(NetworkError::RateLimited { .. })
+        ));
+        // Other peers have their own budget, and it refills over time
+        assert!(gossip.screen("peer-b", &message(4..8), now).is_ok());
+        assert!(gossip.screen("peer-a", &message(8..9), now + Duration::from_secs(1)).is_ok());
+        assert!(TxGossipConfig { burst: 3, ..config }.validate().is_err());
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[test]
+    fn test_injected_receive_faults() {
+        use dev_tools::FaultConfig;
+
+        let faults = FaultInjector::default();
+        let gossip = gossip(&TxGossipConfig::default()).with_faults(faults.clone());
+        assert_eq!(gossip.injected_delay("peer-a").unwrap(), None);
+
+        faults.set(FaultConfig { message_delay_ms: 250, ..Default::default() }).unwrap();
+        assert_eq!(gossip.injected_delay("peer-a").unwrap(), Some(Duration::from_millis(250)));
+
+        faults.set(FaultConfig { peer_drop_rate: 1.0, ..Default::default() }).unwrap();
+        assert!(matches!(gossip.injected_delay("peer-a"), Err(NetworkError::PeerRejected { .. })));
+    }
+}
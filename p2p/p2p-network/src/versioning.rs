@@ -0,0 +1,127 @@
+// p2p/p2p-network/src/versioning.rs
+//! Running two wire protocol versions side by side.
+//!
+//! Each connection settles on the highest version both ends speak. Messages
+//! are built in the current form and translated to the older form at the
+//! connection boundary; message kinds the older version lacks are never
+//! routed to its peers.
+//!
+//! Version history:
+//! - 1: initial protocol
+//! - 2: node role, supported version range and chain identity in the
+//!   handshake, header sync, Kademlia discovery
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::Capabilities;
+use crate::protocol::{Handshake, MessageKind, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use crate::{NetworkError, PeerId, Result};
+
+/// Handshake as sent by version 1 peers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HandshakeV1 {
+    protocol_version: u32,
+    peer_id: PeerId,
+    best_height: u64,
+    capabilities: Capabilities,
+}
+
+/// Highest version both sides support
+pub fn negotiate_version(remote: &Handshake) -> Result<u32> {
+    let remote_min = remote.min_protocol_version.unwrap_or(remote.protocol_version);
+    let version = remote.protocol_version.min(PROTOCOL_VERSION);
+
+    if version < MIN_PROTOCOL_VERSION.max(remote_min) {
+        return Err(NetworkError::PeerRejected {
+            peer_id: remote.peer_id.clone(),
+            reason: format!(
+                "no common protocol version: peer speaks {}-{}, we speak {}-{}",
+                remote_min, remote.protocol_version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+            ),
+        });
+    }
+    Ok(version)
+}
+
+/// Encode our handshake in the form a peer of `version` expects
+pub fn encode_handshake(handshake: &Handshake, version: u32) -> Result<Vec<u8>> {
+    if version >= 2 {
+        return Ok(serde_json::to_vec(handshake)?);
+    }
+
+    Ok(serde_json::to_vec(&HandshakeV1 {
+        protocol_version: 1,
+        peer_id: handshake.peer_id.clone(),
+        best_height: handshake.best_height,
+        capabilities: handshake.capabilities,
+    })?)
+}
+
+/// Decode a handshake of any supported version into the current form
+pub fn decode_handshake(bytes: &[u8]) -> Result<Handshake> {
+    // Fields added in version 2 default when absent
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+impl MessageKind {
+    /// Oldest protocol version that carries this message
+    pub const fn min_protocol_version(self) -> u32 {
+        match self {
+            MessageKind::GetHeaders
+            | MessageKind::BlockHeaders
+            | MessageKind::FindNode
+            | MessageKind::Neighbors
+            | MessageKind::Identify => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ChainIdentity;
+    use crate::role::NodeRole;
+
+    fn handshake(protocol_version: u32, min_protocol_version: Option<u32>) -> Handshake {
+        Handshake {
+            protocol_version,
+            min_protocol_version,
+            peer_id: "peer".to_string(),
+            best_height: 10,
+            capabilities: Capabilities::BLOCK_RELAY,
+            role: NodeRole::Archive,
+            chain: None,
+        }
+    }
+
+    #[test]
+    fn test_version_negotiation() {
+        assert_eq!(negotiate_version(&handshake(1, None)).unwrap(), 1);
+        assert_eq!(negotiate_version(&handshake(2, Some(1))).unwrap(), 2);
+        // A newer peer that still speaks our version
+        assert_eq!(negotiate_version(&handshake(3, Some(2))).unwrap(), 2);
+        // A newer peer that has dropped it
+        assert!(negotiate_version(&handshake(4, Some(3))).is_err());
+        assert!(negotiate_version(&handshake(0, None)).is_err());
+    }
+
+    #[test]
+    fn test_handshake_translation() {
+        let chain = ChainIdentity { chain_id: 1, genesis_hash: [3; 32] };
+        let ours = Handshake::new("peer".to_string(), 10, Capabilities::BLOCK_RELAY, NodeRole::Archive, chain);
+
+        let v1 = encode_handshake(&ours, 1).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&v1).unwrap();
+        assert_eq!(json["protocol_version"], 1);
+        assert!(json.get("role").is_none());
+        assert!(json.get("chain").is_none());
+
+        // Old peers decode as full nodes speaking only version 1
+        let decoded = decode_handshake(&v1).unwrap();
+        assert_eq!(decoded.role, NodeRole::Full);
+        assert_eq!(negotiate_version(&decoded).unwrap(), 1);
+
+        let v2 = decode_handshake(&encode_handshake(&ours, 2).unwrap()).unwrap();
+        assert_eq!(v2, ours);
+    }
+}
@@ -0,0 +1,49 @@
+// p2p/rpc-server/src/backpressure.rs
+//! Feedback loop from relayer queue depth to transaction admission.
+use anyhow::Result;
+use blockchain_core::{AdmissionLevel, AdmissionPolicy};
+use chrono::Utc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use storage_traits::RelayerBacklog;
+
+/// How often the relayer queue is sampled
+pub const DEFAULT_BACKLOG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Sample the queue once and update `admission`, returning the new level on a change
+pub async fn observe_backlog(
+    admission: &RwLock<AdmissionPolicy>,
+    backlog: &dyn RelayerBacklog,
+) -> Result<Option<AdmissionLevel>> {
+    let depth = backlog.relayer_queue_depth().await?;
+    let mut policy = admission.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let changed = policy.observe(depth, Utc::now());
+    if let Some(level) = changed {
+        tracing::warn!(
+            level = %level,
+            queue_depth = depth,
+            min_gas_price = policy.min_gas_price(),
+            "transaction admission level changed"
+        );
+    }
+    Ok(changed)
+}
+
+/// Keep `admission` following the relayer backlog until the task is aborted.
+///
+/// A failed sample leaves the level where it was.
+pub fn spawn_backlog_monitor(
+    admission: Arc<RwLock<AdmissionPolicy>>,
+    backlog: Arc<dyn RelayerBacklog>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = observe_backlog(&admission, backlog.as_ref()).await {
+                tracing::warn!(error = %e, "failed to sample relayer queue depth");
+            }
+        }
+    })
+}
@@ -0,0 +1,299 @@
+// p2p/rpc-server/src/backup.rs
+//! Signed chain exports, for `rpc-server backup`.
+//!
+//! An export is a directory of chunk files, each holding a run of blocks as
+//! one JSON block per line, and a `manifest.json` listing the chunks with
+//! their SHA-256, the height range and the chain they belong to. The
+//! manifest is signed with the node key, so a copy that was edited after
+//! export no longer verifies.
+//!
+//! A restore only trusts manifests signed by the given peers. It checks the
+//! signature and every chunk's hash before writing anything, refuses an
+//! export of another chain than the target keyspace's, and requires each
+//! block to validate and link to the one below it.
+use anyhow::{anyhow, bail, Context, Result};
+use blockchain_core::signature::verify_signature;
+use blockchain_core::{hash_serializable, Block, BlockHeight, ChainId, SignatureScheme};
+use chrono::{DateTime, Utc};
+use p2p_network::identity::peer_id_from_public_key;
+use p2p_network::{ChainIdentity, NodeIdentity, PeerId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::ffi::OsStr;
+use std::path::Path;
+use storage_traits::BlockchainStorage;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Manifest layout written by this build
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Blocks per chunk file when none is given
+pub const DEFAULT_CHUNK_BLOCKS: u64 = 1_000;
+
+/// Keeps a manifest signature from being replayed as any other signed message
+const MANIFEST_DOMAIN: &str = "backup-manifest-v1";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkEntry {
+    /// File name within the export directory
+    pub file: String,
+    pub from_height: BlockHeight,
+    pub to_height: BlockHeight,
+    /// Hex SHA-256 of the file
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub version: u32,
+    pub chain: ChainIdentity,
+    pub from_height: BlockHeight,
+    pub to_height: BlockHeight,
+    /// In height order, covering the range without gaps
+    pub chunks: Vec<ChunkEntry>,
+    pub created_at: DateTime<Utc>,
+    /// Peer id of the node that signed the manifest
+    pub signer: PeerId,
+    /// Hex Ed25519 signature, including the signer's public key
+    pub signature: String,
+}
+
+impl BackupManifest {
+    fn signing_hash(&self) -> [u8; 32] {
+        hash_serializable(&(
+            MANIFEST_DOMAIN,
+            self.version,
+            self.chain,
+            self.from_height,
+            self.to_height,
+            &self.chunks,
+            self.created_at,
+        ))
+        .expect("manifest fields always serialize")
+    }
+
+    /// Check the signature is by `signer` and that `signer` is one of `trusted`
+    pub fn verify_signature(&self, trusted: &[PeerId]) -> Result<()> {
+        let signature = hex::decode(&self.signature).map_err(|e| anyhow!("Invalid manifest signature: {}", e))?;
+        let public_key = verify_signature(SignatureScheme::Ed25519, &self.signing_hash(), &signature)
+            .map_err(|e| anyhow!("Manifest signature does not verify: {}", e))?;
+        if peer_id_from_public_key(&public_key) != self.signer {
+            bail!("Manifest was signed by a key other than its signer's");
+        }
+        if !trusted.contains(&self.signer) {
+            bail!("Manifest signer {} is not trusted", self.signer);
+        }
+        Ok(())
+    }
+}
+
+fn chunk_file_name(from: BlockHeight, to: BlockHeight) -> String {
+    format!("blocks-{:012}-{:012}.jsonl", from, to)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Write blocks `from..=to` to `dir` in chunks of `chunk_blocks`, with a
+/// manifest signed by `identity`
+pub async fn export(
+    storage: &dyn BlockchainStorage,
+    identity: &NodeIdentity,
+    chain_id: ChainId,
+    from: BlockHeight,
+    to: BlockHeight,
+    chunk_blocks: u64,
+    dir: &Path,
+) -> Result<BackupManifest> {
+    if from > to {
+        bail!("Export range is empty: {}..={}", from, to);
+    }
+    if chunk_blocks == 0 {
+        bail!("Chunk size must be greater than 0");
+    }
+    let genesis_hash = storage
+        .get_block_by_height(0)
+        .await?
+        .ok_or_else(|| anyhow!("No genesis block stored"))?
+        .hash;
+    std::fs::create_dir_all(dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+
+    let mut chunks = Vec::new();
+    let mut chunk_from = from;
+    while chunk_from <= to {
+        let chunk_to = chunk_from.saturating_add(chunk_blocks - 1).min(to);
+        let mut contents = String::new();
+        for height in chunk_from..=chunk_to {
+            let block = storage
+                .get_block_by_height(height)
+                .await?
+                .ok_or_else(|| anyhow!("Block {} is not stored", height))?;
+            contents.push_str(&serde_json::to_string(&block)?);
+            contents.push('\n');
+        }
+        let file = chunk_file_name(chunk_from, chunk_to);
+        std::fs::write(dir.join(&file), &contents)?;
+        chunks.push(ChunkEntry {
+            file,
+            from_height: chunk_from,
+            to_height: chunk_to,
+            sha256: sha256_hex(contents.as_bytes()),
+        });
+        if chunk_to == to {
+            break;
+        }
+        chunk_from = chunk_to + 1;
+    }
+
+    let mut manifest = BackupManifest {
+        version: MANIFEST_VERSION,
+        chain: ChainIdentity { chain_id, genesis_hash },
+        from_height: from,
+        to_height: to,
+        chunks,
+        created_at: Utc::now(),
+        signer: identity.peer_id(),
+        signature: String::new(),
+    };
+    manifest.signature = hex::encode(identity.sign_digest(&manifest.signing_hash()));
+    std::fs::write(dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?)?;
+    Ok(manifest)
+}
+
+/// Read the manifest in `dir` and check its signature, the chunk layout and
+/// every chunk's hash
+pub fn verify(dir: &Path, trusted: &[PeerId]) -> Result<BackupManifest> {
+    let path = dir.join(MANIFEST_FILE);
+    let manifest: BackupManifest = serde_json::from_slice(
+        &std::fs::read(&path).with_context(|| format!("Cannot read {}", path.display()))?,
+    )
+    .with_context(|| format!("Invalid manifest {}", path.display()))?;
+    if manifest.version > MANIFEST_VERSION {
+        bail!("Manifest version {} is newer than this build supports", manifest.version);
+    }
+    manifest.verify_signature(trusted)?;
+
+    let mut next = manifest.from_height;
+    for chunk in &manifest.chunks {
+        if chunk.from_height != next || chunk.to_height < chunk.from_height {
+            bail!("Chunk {} does not continue the export at height {}", chunk.file, next);
+        }
+        if Path::new(&chunk.file).file_name() != Some(OsStr::new(&chunk.file)) {
+            bail!("Chunk {} is outside the export directory", chunk.file);
+        }
+        let contents = std::fs::read(dir.join(&chunk.file)).with_context(|| format!("Cannot read {}", chunk.file))?;
+        if sha256_hex(&contents) != chunk.sha256 {
+            bail!("Chunk {} does not match its manifest hash", chunk.file);
+        }
+        next = chunk.to_height + 1;
+    }
+    if manifest.chunks.is_empty() || next != manifest.to_height + 1 {
+        bail!("Chunks do not cover heights {}..={}", manifest.from_height, manifest.to_height);
+    }
+    Ok(manifest)
+}
+
+/// Verify the export in `dir` and store its blocks in `storage`, which must
+/// hold `target`'s chain up to the export's first height
+pub async fn restore(
+    storage: &dyn BlockchainStorage,
+    dir: &Path,
+    target: ChainIdentity,
+    trusted: &[PeerId],
+) -> Result<BackupManifest> {
+    let manifest = verify(dir, trusted)?;
+    if manifest.chain.chain_id != target.chain_id {
+        bail!("Export is of chain {}, the target keyspace holds chain {}", manifest.chain.chain_id, target.chain_id);
+    }
+    if manifest.chain.genesis_hash != target.genesis_hash {
+        bail!(
+            "Export genesis 0x{} does not match the target's 0x{}",
+            hex::encode(manifest.chain.genesis_hash),
+            hex::encode(target.genesis_hash)
+        );
+    }
+
+    // Parse and check everything before the first write
+    let mut previous = match manifest.from_height {
+        0 => None,
+        from => {
+            let parent = storage.get_block_by_height(from - 1).await?;
+            Some(parent.ok_or_else(|| anyhow!("Target is missing block {} below the export", from - 1))?.hash)
+        }
+    };
+    let mut blocks = Vec::new();
+    for chunk in &manifest.chunks {
+        let contents = std::fs::read_to_string(dir.join(&chunk.file))?;
+        let mut height = chunk.from_height;
+        for line in contents.lines() {
+            let block: Block = serde_json::from_str(line).with_context(|| format!("Invalid block in {}", chunk.file))?;
+            if block.header.height != height || height > chunk.to_height {
+                bail!("Chunk {} holds block {} out of order", chunk.file, block.header.height);
+            }
+            block.validate().with_context(|| format!("Block {} is invalid", height))?;
+            match previous {
+                Some(parent) if block.header.previous_hash != parent => {
+                    bail!("Block {} does not link to the block below it", height)
+                }
+                None if block.hash != target.genesis_hash => bail!("Genesis block does not match the target's"),
+                _ => {}
+            }
+            previous = Some(block.hash);
+            blocks.push(block);
+            height += 1;
+        }
+        if height != chunk.to_height + 1 {
+            bail!("Chunk {} ends at block {}, not {}", chunk.file, height.saturating_sub(1), chunk.to_height);
+        }
+    }
+
+    for block in &blocks {
+        storage.store_block(block).await?;
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MemoryStorage;
+
+    #[tokio::test]
+    async fn test_export_verify_and_restore() {
+        let dir = std::env::temp_dir().join(format!("node-backup-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let source = MemoryStorage::default();
+        let mut previous = [0; 32];
+        for height in 0..5 {
+            let block = Block::new(height, previous, Vec::new(), 1).unwrap();
+            previous = block.hash;
+            source.store_block(&block).await.unwrap();
+        }
+        let genesis_hash = source.get_block_by_height(0).await.unwrap().unwrap().hash;
+        let identity = NodeIdentity::generate();
+        let trusted = [identity.peer_id()];
+
+        let manifest = export(&source, &identity, 1, 2, 4, 2, &dir).await.unwrap();
+        assert_eq!(manifest.chunks.len(), 2);
+        assert!(verify(&dir, &[NodeIdentity::generate().peer_id()]).unwrap_err().to_string().contains("not trusted"));
+
+        // The target needs the chain below the export, and the same chain
+        let target = MemoryStorage::default();
+        let chain = ChainIdentity { chain_id: 1, genesis_hash };
+        for height in 0..2 {
+            target.store_block(&source.get_block_by_height(height).await.unwrap().unwrap()).await.unwrap();
+        }
+        let other_chain = ChainIdentity { chain_id: 2, ..chain };
+        assert!(restore(&target, &dir, other_chain, &trusted).await.is_err());
+        restore(&target, &dir, chain, &trusted).await.unwrap();
+        assert_eq!(target.get_latest_block_height().await.unwrap(), Some(4));
+
+        // A tampered chunk fails its hash
+        let chunk = dir.join(&manifest.chunks[1].file);
+        std::fs::write(&chunk, std::fs::read_to_string(&chunk).unwrap().replace('1', "2")).unwrap();
+        assert!(verify(&dir, &trusted).unwrap_err().to_string().contains("manifest hash"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,230 @@
+// p2p/rpc-server/src/capacity.rs
+//! Capacity planning, for `rpc-server report capacity`.
+//!
+//! A sample reads table size estimates, chain totals, the stats rows of a
+//! recent window, mempool usage, the connected peer count and the driver's
+//! counters. Projections assume every table grows in step with transaction
+//! volume, so tables with a TTL (the mempool, hourly stats) are
+//! overestimated. Bandwidth counts each block, and each transaction while
+//! pending, relayed once to every connected peer.
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use scylla_adapter::model::{DriverMetrics, MempoolUsage, TableSize};
+use scylla_adapter::ScyllaAdapter;
+use serde::{Deserialize, Serialize};
+use storage_traits::ConnectedPeers;
+
+pub const DEFAULT_CAPACITY_WINDOW_DAYS: u32 = 7;
+
+pub const DEFAULT_CAPACITY_HORIZONS_DAYS: [u32; 3] = [30, 90, 365];
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Current sizes and the chain's activity over the sampled window
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapacitySample {
+    pub sampled_at: DateTime<Utc>,
+    pub window_days: u32,
+    pub tables: Vec<TableSize>,
+    pub total_blocks: u64,
+    pub total_transactions: u64,
+    /// Blocks and transactions in the window's stats rows
+    pub window_blocks: u64,
+    pub window_transactions: u64,
+    pub mempool: MempoolUsage,
+    pub connected_peers: u64,
+    /// Includes the sampling queries themselves
+    pub driver: DriverMetrics,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableGrowth {
+    pub table: String,
+    pub bytes: u64,
+    pub bytes_per_day: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolChurn {
+    pub pending_transactions: u64,
+    pub pending_bytes: u64,
+    /// Transactions leaving the mempool into blocks
+    pub transactions_per_second: f64,
+    /// Time to replace the whole mempool at that rate; `None` when nothing is included
+    pub turnover_seconds: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BandwidthEstimate {
+    pub block_bytes_per_second: f64,
+    pub transaction_bytes_per_second: f64,
+    pub connected_peers: u64,
+    /// Outbound gossip to all connected peers
+    pub total_bytes_per_second: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Projection {
+    pub horizon_days: u32,
+    pub storage_bytes: u64,
+    /// Gossip sent over the whole horizon
+    pub bandwidth_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityReport {
+    pub sample: CapacitySample,
+    pub blocks_per_day: f64,
+    pub transactions_per_day: f64,
+    pub storage_bytes: u64,
+    pub storage_bytes_per_day: u64,
+    pub tables: Vec<TableGrowth>,
+    pub mempool: MempoolChurn,
+    pub bandwidth: BandwidthEstimate,
+    pub projections: Vec<Projection>,
+}
+
+impl CapacitySample {
+    /// Sample `storage` as of `now`, taking rates from the last `window_days`
+    pub async fn collect(storage: &ScyllaAdapter, window_days: u32, now: DateTime<Utc>) -> Result<Self> {
+        if window_days == 0 {
+            bail!("Capacity window must be at least one day");
+        }
+        let stats = storage.get_chain_stats().await?;
+        let from = now - chrono::Duration::days(i64::from(window_days));
+        let periods = storage.get_chain_stats_range(from, now, now).await?;
+        let tables = storage.get_table_sizes().await?;
+        let connected_peers = storage.connected_peer_heights().await?.len() as u64;
+
+        Ok(Self {
+            sampled_at: now,
+            window_days,
+            tables,
+            total_blocks: stats.total_blocks,
+            total_transactions: stats.total_transactions,
+            window_blocks: periods.iter().map(|period| period.total_blocks).sum(),
+            window_transactions: periods.iter().map(|period| period.total_transactions).sum(),
+            mempool: MempoolUsage { transaction_count: stats.pending_transactions, total_bytes: stats.pending_bytes },
+            connected_peers,
+            driver: storage.driver_metrics(),
+        })
+    }
+
+    /// Growth rates from the sample, and storage and bandwidth needs at each of `horizons_days`
+    pub fn project(self, horizons_days: &[u32]) -> CapacityReport {
+        let window = f64::from(self.window_days.max(1));
+        let blocks_per_day = self.window_blocks as f64 / window;
+        let transactions_per_day = self.window_transactions as f64 / window;
+
+        // Share of today's data added per day
+        let daily_fraction = match (self.total_transactions, self.total_blocks) {
+            (0, 0) => 0.0,
+            (0, blocks) => blocks_per_day / blocks as f64,
+            (transactions, _) => transactions_per_day / transactions as f64,
+        };
+        let tables: Vec<TableGrowth> = self
+            .tables
+            .iter()
+            .map(|size| TableGrowth {
+                table: size.table.clone(),
+                bytes: size.estimated_bytes,
+                bytes_per_day: (size.estimated_bytes as f64 * daily_fraction) as u64,
+            })
+            .collect();
+        let storage_bytes = tables.iter().map(|table| table.bytes).sum();
+        let storage_bytes_per_day: u64 = tables.iter().map(|table| table.bytes_per_day).sum();
+
+        let transactions_per_second = transactions_per_day / SECONDS_PER_DAY;
+        let pending = self.mempool.transaction_count;
+        let mempool = MempoolChurn {
+            pending_transactions: pending,
+            pending_bytes: self.mempool.total_bytes,
+            transactions_per_second,
+            turnover_seconds: (transactions_per_second > 0.0).then(|| pending as f64 / transactions_per_second),
+        };
+
+        let blocks_table = self.tables.iter().find(|size| size.table == "blocks");
+        let average_block = average(blocks_table.map_or(0, |size| size.estimated_bytes), self.total_blocks);
+        let average_transaction = average(self.mempool.total_bytes, pending);
+        let block_bytes_per_second = average_block * blocks_per_day / SECONDS_PER_DAY;
+        let transaction_bytes_per_second = average_transaction * transactions_per_second;
+        let bandwidth = BandwidthEstimate {
+            block_bytes_per_second,
+            transaction_bytes_per_second,
+            connected_peers: self.connected_peers,
+            total_bytes_per_second: (block_bytes_per_second + transaction_bytes_per_second)
+                * self.connected_peers as f64,
+        };
+
+        let projections = horizons_days
+            .iter()
+            .map(|&days| Projection {
+                horizon_days: days,
+                storage_bytes: storage_bytes + storage_bytes_per_day.saturating_mul(u64::from(days)),
+                bandwidth_bytes: (bandwidth.total_bytes_per_second * SECONDS_PER_DAY * f64::from(days)) as u64,
+            })
+            .collect();
+
+        CapacityReport {
+            sample: self,
+            blocks_per_day,
+            transactions_per_day,
+            storage_bytes,
+            storage_bytes_per_day,
+            tables,
+            mempool,
+            bandwidth,
+            projections,
+        }
+    }
+}
+
+fn average(total: u64, count: u64) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(name: &str, bytes: u64) -> TableSize {
+        TableSize { table: name.to_string(), estimated_bytes: bytes, estimated_partitions: 1 }
+    }
+
+    #[test]
+    fn test_projects_growth_from_window_rates() {
+        let sample = CapacitySample {
+            window_days: 2,
+            tables: vec![table("blocks", 1_000_000), table("transactions", 4_000_000)],
+            total_blocks: 1_000,
+            total_transactions: 10_000,
+            window_blocks: 200,
+            window_transactions: 2_000,
+            mempool: MempoolUsage { transaction_count: 100, total_bytes: 20_000 },
+            connected_peers: 4,
+            ..Default::default()
+        };
+
+        let report = sample.project(&[10]);
+        assert_eq!((report.blocks_per_day, report.transactions_per_day), (100.0, 1_000.0));
+        // A tenth of the transactions arrived per day, so a tenth of the data
+        assert_eq!((report.storage_bytes, report.storage_bytes_per_day), (5_000_000, 500_000));
+        assert_eq!(report.tables[1].bytes_per_day, 400_000);
+
+        let turnover = report.mempool.turnover_seconds.unwrap();
+        assert!((turnover - 8_640.0).abs() < 1e-6, "{}", turnover);
+        // 100 blocks of 1 kB and 1,000 transactions of 200 bytes a day, to 4 peers
+        let per_day = report.bandwidth.total_bytes_per_second * SECONDS_PER_DAY;
+        assert!((per_day - 1_200_000.0).abs() < 1e-3, "{}", per_day);
+        assert_eq!(report.projections[0].storage_bytes, 10_000_000);
+        assert!(report.projections[0].bandwidth_bytes.abs_diff(12_000_000) <= 1);
+
+        let idle = CapacitySample { window_days: 1, ..Default::default() }.project(&[30]);
+        assert_eq!(idle.mempool.turnover_seconds, None);
+        assert_eq!(idle.projections[0], Projection { horizon_days: 30, storage_bytes: 0, bandwidth_bytes: 0 });
+    }
+}
@@ -0,0 +1,138 @@
+// p2p/rpc-server/src/clock_drift.rs
+//! Local clock drift measured against an NTP server.
+use anyhow::{anyhow, Result};
+use blockchain_core::{Clock, DriftMonitor, DriftStatus};
+use chrono::{DateTime, TimeZone, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+/// How often the NTP server is queried
+pub const DEFAULT_DRIFT_POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Server queried when `NTP_SERVER` is not set
+pub const DEFAULT_NTP_SERVER: &str = "pool.ntp.org:123";
+
+/// A query without a reply after this long is abandoned
+const NTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Seconds from the NTP epoch (1900) to the Unix epoch
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+const NTP_PACKET_LEN: usize = 48;
+
+/// Ask `server` (host:port) for the time once over SNTP, returning true time
+/// minus local time
+pub async fn query_offset(server: &str) -> Result<chrono::Duration> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(server).await?;
+
+    let mut request = [0u8; NTP_PACKET_LEN];
+    request[0] = 0x1b; // no leap warning, version 3, client mode
+    let sent_at = Utc::now();
+    socket.send(&request).await?;
+
+    let mut response = [0u8; NTP_PACKET_LEN];
+    let len = tokio::time::timeout(NTP_TIMEOUT, socket.recv(&mut response)).await??;
+    offset_from_response(&response[..len], sent_at, Utc::now())
+}
+
+/// Offset from an SNTP reply, averaging the request and reply legs so the
+/// network delay cancels out
+pub fn offset_from_response(
+    response: &[u8],
+    sent_at: DateTime<Utc>,
+    received_at: DateTime<Utc>,
+) -> Result<chrono::Duration> {
+    if response.len() < NTP_PACKET_LEN {
+        return Err(anyhow!("NTP reply of {} bytes is too short", response.len()));
+    }
+    if response[0] & 0x07 != 4 {
+        return Err(anyhow!("NTP reply is not in server mode"));
+    }
+    let server_received = ntp_timestamp(&response[32..40])?;
+    let server_sent = ntp_timestamp(&response[40..48])?;
+    Ok(((server_received - sent_at) + (server_sent - received_at)) / 2)
+}
+
+fn ntp_timestamp(bytes: &[u8]) -> Result<DateTime<Utc>> {
+    let seconds = u32::from_be_bytes(bytes[0..4].try_into()?) as i64 - NTP_UNIX_OFFSET;
+    let fraction = u32::from_be_bytes(bytes[4..8].try_into()?) as u64;
+    let nanos = ((fraction * 1_000_000_000) >> 32) as u32;
+    Utc.timestamp_opt(seconds, nanos)
+        .single()
+        .ok_or_else(|| anyhow!("Invalid NTP timestamp"))
+}
+
+/// Record one offset in `monitor`, returning the new status on a change
+pub fn observe_drift(monitor: &DriftMonitor, offset: chrono::Duration) -> Option<DriftStatus> {
+    let changed = monitor.record(offset);
+    match changed {
+        Some(DriftStatus::InSync) => {
+            tracing::info!(offset_ms = offset.num_milliseconds(), "local clock is in sync");
+        }
+        Some(status) => {
+            tracing::warn!(
+                status = %status,
+                offset_ms = offset.num_milliseconds(),
+                window_ms = monitor.uncertainty().num_milliseconds(),
+                "local clock drift, widening the block timestamp window"
+            );
+        }
+        None => {}
+    }
+    changed
+}
+
+/// Keep `monitor` fed with offsets from `server` until the task is aborted.
+///
+/// A failed query leaves the drift estimate where it was.
+pub fn spawn_drift_monitor(
+    monitor: Arc<DriftMonitor>,
+    server: String,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match query_offset(&server).await {
+                Ok(offset) => {
+                    observe_drift(&monitor, offset);
+                }
+                Err(e) => tracing::warn!(error = %e, server = %server, "failed to query NTP server"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(server_received: DateTime<Utc>, server_sent: DateTime<Utc>) -> Vec<u8> {
+        let mut response = vec![0u8; NTP_PACKET_LEN];
+        response[0] = 0x1c; // version 3, server mode
+        for (offset, time) in [(32, server_received), (40, server_sent)] {
+            let seconds = (time.timestamp() + NTP_UNIX_OFFSET) as u32;
+            response[offset..offset + 4].copy_from_slice(&seconds.to_be_bytes());
+        }
+        response
+    }
+
+    #[test]
+    fn test_offset_cancels_network_delay() {
+        let sent_at = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let received_at = sent_at + chrono::Duration::seconds(2);
+        // Server is 2s ahead; each leg takes 1s
+        let server_time = sent_at + chrono::Duration::seconds(3);
+
+        let offset = offset_from_response(&reply(server_time, server_time), sent_at, received_at).unwrap();
+        assert_eq!(offset, chrono::Duration::seconds(2));
+
+        assert!(offset_from_response(&reply(server_time, server_time)[..40], sent_at, received_at).is_err());
+        let mut client_mode = reply(server_time, server_time);
+        client_mode[0] = 0x1b;
+        assert!(offset_from_response(&client_mode, sent_at, received_at).is_err());
+    }
+}
@@ -0,0 +1,389 @@
+// p2p/rpc-server/src/compare.rs
+//! Finding where two nodes' chains part ways, for `rpc-server compare`.
+//!
+//! Each block commits to its parent's hash, so once two nodes store different
+//! blocks at some height they differ at every height above it. The first
+//! divergent height is found by binary search over the heights both store.
+//! The blocks there are then compared header field by header field, and the
+//! state after them account by account. State is rebuilt by replay (see
+//! `trace`), so it is only compared when both nodes have tracing configured.
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use blockchain_core::{AccountState, Address, AddressExt, Block, BlockHash, BlockHeight};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use storage_traits::BlockchainStorage;
+
+use crate::jsonrpc::RESOURCE_NOT_FOUND;
+use crate::trace::{self, TraceConfig};
+
+/// One node's chain, as far as a comparison needs it
+#[async_trait]
+pub trait ChainView: Send + Sync {
+    async fn head_height(&self) -> Result<Option<BlockHeight>>;
+
+    async fn block(&self, height: BlockHeight) -> Result<Option<Block>>;
+
+    /// State after the block at `height`, or `None` if no block is stored there
+    async fn state_at(&self, height: BlockHeight) -> Result<Option<StateSnapshot>>;
+}
+
+/// Every account after a block, with the root over them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateSnapshot {
+    pub state_root: BlockHash,
+    pub accounts: BTreeMap<Address, AccountState>,
+}
+
+impl StateSnapshot {
+    /// Parse the result of `debug_stateAt`
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let state_root = parse_hash(value["state_root"].as_str().unwrap_or_default())?;
+        let mut accounts = BTreeMap::new();
+        for account in value["accounts"].as_array().into_iter().flatten() {
+            let address = Address::from_checksum_hex(account["address"].as_str().unwrap_or_default())?;
+            let state = AccountState {
+                balance: account["balance"].as_u64().ok_or_else(|| anyhow!("Account balance is missing"))?,
+                nonce: account["nonce"].as_u64().ok_or_else(|| anyhow!("Account nonce is missing"))?,
+            };
+            accounts.insert(address, state);
+        }
+        Ok(Self { state_root, accounts })
+    }
+}
+
+/// The chain in this node's own storage
+pub struct LocalChain {
+    storage: Arc<dyn BlockchainStorage>,
+    trace: Option<TraceConfig>,
+}
+
+impl LocalChain {
+    pub fn new(storage: Arc<dyn BlockchainStorage>, trace: Option<TraceConfig>) -> Self {
+        Self { storage, trace }
+    }
+}
+
+#[async_trait]
+impl ChainView for LocalChain {
+    async fn head_height(&self) -> Result<Option<BlockHeight>> {
+        self.storage.get_latest_block_height().await
+    }
+
+    async fn block(&self, height: BlockHeight) -> Result<Option<Block>> {
+        self.storage.get_block_by_height(height).await
+    }
+
+    async fn state_at(&self, height: BlockHeight) -> Result<Option<StateSnapshot>> {
+        let config = self.trace.as_ref().ok_or_else(|| anyhow!("Tracing is not configured on this node"))?;
+        let Some((_, ledger)) = trace::state_at(self.storage.as_ref(), config, height).await? else {
+            return Ok(None);
+        };
+        Ok(Some(StateSnapshot { state_root: ledger.state_root()?, accounts: ledger.accounts().into_iter().collect() }))
+    }
+}
+
+/// Another node, through its REST and JSON-RPC endpoints
+pub struct RemoteChain {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl RemoteChain {
+    pub fn new(url: &str) -> Self {
+        Self { client: reqwest::Client::new(), url: url.trim_end_matches('/').to_string() }
+    }
+
+    /// Result of a JSON-RPC call, or `None` if the node reports it not found
+    async fn call(&self, method: &str, params: Value) -> Result<Option<Value>> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = self.client.post(format!("{}/rpc", self.url)).json(&request).send().await?.json().await?;
+        if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+            if error["code"].as_i64() == Some(RESOURCE_NOT_FOUND) {
+                return Ok(None);
+            }
+            bail!("{} failed on {}: {}", method, self.url, error["message"].as_str().unwrap_or_default());
+        }
+        Ok(response.get("result").cloned())
+    }
+}
+
+#[async_trait]
+impl ChainView for RemoteChain {
+    async fn head_height(&self) -> Result<Option<BlockHeight>> {
+        Ok(self.call("chain_head", json!([])).await?.and_then(|head| head["height"].as_u64()))
+    }
+
+    async fn block(&self, height: BlockHeight) -> Result<Option<Block>> {
+        let response = self.client.get(format!("{}/blocks/{}", self.url, height)).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+
+    async fn state_at(&self, height: BlockHeight) -> Result<Option<StateSnapshot>> {
+        self.call("debug_stateAt", json!([height])).await?.map(|state| StateSnapshot::from_json(&state)).transpose()
+    }
+}
+
+/// Where two chains part ways; `divergence` is `None` when one chain is a
+/// prefix of the other
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DivergenceReport {
+    pub local_head: Option<BlockHeight>,
+    pub remote_head: Option<BlockHeight>,
+    /// Highest height both nodes store the same block at
+    pub last_common_height: Option<BlockHeight>,
+    pub divergence: Option<Divergence>,
+}
+
+/// The first height the nodes store different blocks at
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Divergence {
+    pub height: BlockHeight,
+    pub local_hash: Option<String>,
+    pub remote_hash: Option<String>,
+    /// Header fields that differ, by name
+    pub header: Vec<FieldDiff>,
+    pub state: StateComparison,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub local: Value,
+    pub remote: Value,
+}
+
+/// State after the divergent blocks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum StateComparison {
+    Matches {
+        state_root: String,
+    },
+    Differs {
+        local_root: String,
+        remote_root: String,
+        only_local: Vec<String>,
+        only_remote: Vec<String>,
+        changed: Vec<AccountDiff>,
+    },
+    /// Either side could not rebuild its state
+    Unavailable {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccountDiff {
+    pub address: String,
+    pub local: AccountState,
+    pub remote: AccountState,
+}
+
+/// Find and describe the first height `local` and `remote` disagree at
+pub async fn compare(local: &dyn ChainView, remote: &dyn ChainView) -> Result<DivergenceReport> {
+    let local_head = local.head_height().await?;
+    let remote_head = remote.head_height().await?;
+    let mut report = DivergenceReport { local_head, remote_head, last_common_height: None, divergence: None };
+    let (Some(local_top), Some(remote_top)) = (local_head, remote_head) else {
+        return Ok(report);
+    };
+
+    let top = local_top.min(remote_top);
+    if agree_at(local, remote, top).await? {
+        report.last_common_height = Some(top);
+        return Ok(report);
+    }
+
+    // Every height below `low` agrees and the nodes differ at `high`
+    let (mut low, mut high) = (0, top);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if agree_at(local, remote, mid).await? {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    report.last_common_height = low.checked_sub(1);
+    report.divergence = Some(describe(local, remote, low).await?);
+    Ok(report)
+}
+
+async fn agree_at(local: &dyn ChainView, remote: &dyn ChainView, height: BlockHeight) -> Result<bool> {
+    let local_hash = local.block(height).await?.map(|block| block.hash);
+    let remote_hash = remote.block(height).await?.map(|block| block.hash);
+    Ok(local_hash == remote_hash)
+}
+
+async fn describe(local: &dyn ChainView, remote: &dyn ChainView, height: BlockHeight) -> Result<Divergence> {
+    let local_block = local.block(height).await?;
+    let remote_block = remote.block(height).await?;
+    let header = match (&local_block, &remote_block) {
+        (Some(local_block), Some(remote_block)) => header_diff(local_block, remote_block)?,
+        _ => Vec::new(),
+    };
+
+    let state = match (local.state_at(height).await, remote.state_at(height).await) {
+        (Ok(Some(local_state)), Ok(Some(remote_state))) => state_diff(&local_state, &remote_state),
+        (Err(e), _) => StateComparison::Unavailable { reason: format!("local: {}", e) },
+        (_, Err(e)) => StateComparison::Unavailable { reason: format!("remote: {}", e) },
+        _ => StateComparison::Unavailable { reason: format!("block {} is missing on one node", height) },
+    };
+
+    Ok(Divergence {
+        height,
+        local_hash: local_block.map(|block| hex_hash(&block.hash)),
+        remote_hash: remote_block.map(|block| hex_hash(&block.hash)),
+        header,
+        state,
+    })
+}
+
+fn header_diff(local: &Block, remote: &Block) -> Result<Vec<FieldDiff>> {
+    let Value::Object(local_fields) = serde_json::to_value(&local.header)? else {
+        bail!("Block header did not serialize to an object");
+    };
+    let Value::Object(mut remote_fields) = serde_json::to_value(&remote.header)? else {
+        bail!("Block header did not serialize to an object");
+    };
+
+    let mut diffs = Vec::new();
+    for (field, local_value) in local_fields {
+        let remote_value = remote_fields.remove(&field).unwrap_or(Value::Null);
+        if local_value != remote_value {
+            diffs.push(FieldDiff { field, local: local_value, remote: remote_value });
+        }
+    }
+    // Fields only a newer header version has
+    for (field, remote_value) in remote_fields {
+        diffs.push(FieldDiff { field, local: Value::Null, remote: remote_value });
+    }
+    Ok(diffs)
+}
+
+fn state_diff(local: &StateSnapshot, remote: &StateSnapshot) -> StateComparison {
+    if local.state_root == remote.state_root {
+        return StateComparison::Matches { state_root: hex_hash(&local.state_root) };
+    }
+
+    let only_local = local
+        .accounts
+        .keys()
+        .filter(|address| !remote.accounts.contains_key(*address))
+        .map(|address| address.to_checksum_hex())
+        .collect();
+    let only_remote = remote
+        .accounts
+        .keys()
+        .filter(|address| !local.accounts.contains_key(*address))
+        .map(|address| address.to_checksum_hex())
+        .collect();
+    let changed = local
+        .accounts
+        .iter()
+        .filter_map(|(address, local_state)| {
+            let remote_state = remote.accounts.get(address)?;
+            (local_state != remote_state).then(|| AccountDiff {
+                address: address.to_checksum_hex(),
+                local: *local_state,
+                remote: *remote_state,
+            })
+        })
+        .collect();
+
+    StateComparison::Differs {
+        local_root: hex_hash(&local.state_root),
+        remote_root: hex_hash(&remote.state_root),
+        only_local,
+        only_remote,
+        changed,
+    }
+}
+
+fn hex_hash(hash: &BlockHash) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+fn parse_hash(text: &str) -> Result<BlockHash> {
+    let bytes = hex::decode(text.trim_start_matches("0x"))?;
+    bytes.try_into().map_err(|_| anyhow!("Expected a 32-byte hash, got {}", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MemoryStorage;
+    use blockchain_core::{FeeDistribution, TraceLimits, Transaction};
+
+    fn address(i: u8) -> Address {
+        [i; 20]
+    }
+
+    fn trace_config() -> TraceConfig {
+        TraceConfig {
+            fees: FeeDistribution::default(),
+            allocations: vec![(address(1), 1_000_000)],
+            limits: TraceLimits::default(),
+            max_replay_blocks: 100,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_finds_first_divergent_block() {
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        let transfer =
+            |to: u8, nonce| Transaction::new_transfer(address(1), address(to), 10, nonce, 21_000, 1).unwrap();
+        let first = Block::new(1, genesis.hash, vec![transfer(2, 0)], 1).unwrap();
+
+        let (local, remote) = (Arc::new(MemoryStorage::default()), Arc::new(MemoryStorage::default()));
+        for storage in [&local, &remote] {
+            storage.store_block(&genesis).await.unwrap();
+            storage.store_block(&first).await.unwrap();
+        }
+        // The nodes agree up to block 1, then pay different recipients
+        let mut previous = (first.hash, first.hash);
+        for height in 2..=5 {
+            let local_block = Block::new(height, previous.0, vec![transfer(2, height - 1)], 1).unwrap();
+            let remote_block = Block::new(height, previous.1, vec![transfer(3, height - 1)], 1).unwrap();
+            local.store_block(&local_block).await.unwrap();
+            remote.store_block(&remote_block).await.unwrap();
+            previous = (local_block.hash, remote_block.hash);
+        }
+        let extra = Block::new(6, previous.1, vec![], 1).unwrap();
+        remote.store_block(&extra).await.unwrap();
+
+        let local_view = LocalChain::new(local.clone(), Some(trace_config()));
+        let remote_view = LocalChain::new(remote.clone(), Some(trace_config()));
+        let report = compare(&local_view, &remote_view).await.unwrap();
+        assert_eq!((report.local_head, report.remote_head), (Some(5), Some(6)));
+        assert_eq!(report.last_common_height, Some(1));
+
+        let divergence = report.divergence.unwrap();
+        assert_eq!(divergence.height, 2);
+        assert!(divergence.header.iter().any(|diff| diff.field == "merkle_root"));
+        let StateComparison::Differs { only_local, only_remote, changed, .. } = divergence.state else {
+            panic!("expected differing state");
+        };
+        assert!(only_local.is_empty());
+        assert_eq!(only_remote, vec![address(3).to_checksum_hex()]);
+        // The sender's balance and nonce match; only the first recipient's differ
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].address, address(2).to_checksum_hex());
+        assert_eq!(changed[0].local.balance, changed[0].remote.balance + 10);
+
+        // Without a trace config the state cannot be rebuilt
+        let report = compare(&LocalChain::new(local, None), &remote_view).await.unwrap();
+        assert!(matches!(report.divergence.unwrap().state, StateComparison::Unavailable { .. }));
+
+        // A node compared with itself agrees up to its head
+        let report = compare(&remote_view, &remote_view).await.unwrap();
+        assert_eq!(report.last_common_height, Some(6));
+        assert!(report.divergence.is_none());
+    }
+}
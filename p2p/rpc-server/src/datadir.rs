@@ -0,0 +1,217 @@
+// p2p/rpc-server/src/datadir.rs
+//! On-disk layout of a node's data directory.
+//!
+//! ```text
+//! <data dir>/
+//!   VERSION       layout version, refused if newer than this build
+//!   GENESIS       hex hash of the genesis block the node was set up for
+//!   LOCK          pid of the running node
+//!   p2p/node.key  node identity key
+//! ```
+//!
+//! `rpc-server init` creates the directory; a plain start opens it, takes the
+//! lock so a second node cannot run on the same data, and checks the stored
+//! chain starts at the recorded genesis before anything is served.
+use anyhow::{anyhow, bail, Context, Result};
+use blockchain_core::BlockHash;
+use p2p_network::NodeIdentity;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Layout version written by this build
+pub const DATA_DIR_VERSION: u32 = 1;
+
+/// Data directory used when `NODE_DATA_DIR` is not set
+pub const DEFAULT_DATA_DIR: &str = "data";
+
+const VERSION_FILE: &str = "VERSION";
+const GENESIS_FILE: &str = "GENESIS";
+const LOCK_FILE: &str = "LOCK";
+const NODE_KEY_FILE: &str = "p2p/node.key";
+
+#[derive(Debug, Clone)]
+pub struct DataDir {
+    root: PathBuf,
+    genesis_hash: BlockHash,
+}
+
+impl DataDir {
+    /// Create the layout for `genesis_hash`, generating the node key.
+    ///
+    /// Running it again is harmless; it fails if the directory was set up
+    /// for another genesis.
+    pub fn init(root: &Path, genesis_hash: BlockHash) -> Result<Self> {
+        if root.join(GENESIS_FILE).exists() {
+            let existing = Self::read(root)?;
+            if existing.genesis_hash != genesis_hash {
+                bail!(
+                    "{} is already initialized for genesis 0x{}, not 0x{}",
+                    root.display(),
+                    hex::encode(existing.genesis_hash),
+                    hex::encode(genesis_hash)
+                );
+            }
+            existing.identity()?;
+            return Ok(existing);
+        }
+
+        std::fs::create_dir_all(root).with_context(|| format!("Cannot create {}", root.display()))?;
+        let dir = Self { root: root.to_path_buf(), genesis_hash };
+        dir.identity()?;
+        std::fs::write(root.join(GENESIS_FILE), format!("0x{}\n", hex::encode(genesis_hash)))?;
+        // Written last: a directory with a version marker is complete
+        std::fs::write(root.join(VERSION_FILE), format!("{}\n", DATA_DIR_VERSION))?;
+        Ok(dir)
+    }
+
+    /// Open an initialized directory and lock it for this process
+    pub fn open(root: &Path) -> Result<(Self, DataDirLock)> {
+        let dir = Self::read(root)?;
+        let lock = DataDirLock::acquire(&root.join(LOCK_FILE))?;
+        Ok((dir, lock))
+    }
+
+    fn read(root: &Path) -> Result<Self> {
+        let version = std::fs::read_to_string(root.join(VERSION_FILE)).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => anyhow!("{} is not initialized; run `rpc-server init` first", root.display()),
+            _ => anyhow!("Cannot read {}: {}", root.join(VERSION_FILE).display(), e),
+        })?;
+        let version: u32 = version
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("Invalid data directory version: {}", version.trim()))?;
+        if version > DATA_DIR_VERSION {
+            bail!(
+                "{} was written by a newer release (layout {}, this build supports {})",
+                root.display(),
+                version,
+                DATA_DIR_VERSION
+            );
+        }
+
+        let marker = std::fs::read_to_string(root.join(GENESIS_FILE))
+            .with_context(|| format!("Missing genesis marker in {}", root.display()))?;
+        let genesis_hash = parse_genesis_hash(marker.trim())?;
+        Ok(Self { root: root.to_path_buf(), genesis_hash })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn genesis_hash(&self) -> BlockHash {
+        self.genesis_hash
+    }
+
+    pub fn node_key_path(&self) -> PathBuf {
+        self.root.join(NODE_KEY_FILE)
+    }
+
+    /// The node key, created on first use
+    pub fn identity(&self) -> Result<NodeIdentity> {
+        NodeIdentity::load_or_generate(&self.node_key_path()).map_err(anyhow::Error::from)
+    }
+
+    /// Refuse to run against a database holding another chain
+    pub fn check_genesis(&self, stored: Option<BlockHash>) -> Result<()> {
+        match stored {
+            Some(hash) if hash != self.genesis_hash => bail!(
+                "Database genesis 0x{} does not match 0x{} recorded in {}",
+                hex::encode(hash),
+                hex::encode(self.genesis_hash),
+                self.root.display()
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parse a 32-byte genesis hash, with or without `0x`
+pub fn parse_genesis_hash(text: &str) -> Result<BlockHash> {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    hex::decode(digits)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("Genesis hash must be 32 hex-encoded bytes: {}", text))
+}
+
+/// Held while the node runs; removes the lockfile when dropped
+#[derive(Debug)]
+pub struct DataDirLock {
+    path: PathBuf,
+}
+
+impl DataDirLock {
+    fn acquire(path: &Path) -> Result<Self> {
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", std::process::id())?;
+                    return Ok(Self { path: path.to_path_buf() });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let holder = std::fs::read_to_string(path).unwrap_or_default();
+                    let holder = holder.trim();
+                    if !is_stale(holder) {
+                        bail!(
+                            "Data directory is in use by process {}; if no node is running, remove {}",
+                            holder,
+                            path.display()
+                        );
+                    }
+                    // Left behind by a node that crashed
+                    std::fs::remove_file(path)?;
+                }
+                Err(e) => return Err(e).with_context(|| format!("Cannot create {}", path.display())),
+            }
+        }
+    }
+}
+
+impl Drop for DataDirLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Whether a lockfile naming `holder` was left by a process that is gone.
+/// Only Linux can tell; elsewhere a lockfile is always honoured.
+fn is_stale(holder: &str) -> bool {
+    let Ok(pid) = holder.parse::<u32>() else {
+        return false;
+    };
+    cfg!(target_os = "linux") && pid != std::process::id() && !Path::new("/proc").join(pid.to_string()).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_lock_and_genesis_checks() {
+        let root = std::env::temp_dir().join(format!("node-data-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        assert!(DataDir::open(&root).unwrap_err().to_string().contains("rpc-server init"));
+
+        let dir = DataDir::init(&root, [7; 32]).unwrap();
+        let peer_id = dir.identity().unwrap().peer_id();
+        assert!(DataDir::init(&root, [8; 32]).is_err());
+        assert_eq!(DataDir::init(&root, [7; 32]).unwrap().identity().unwrap().peer_id(), peer_id);
+
+        // A second start on the same directory is refused until the first exits
+        let (opened, lock) = DataDir::open(&root).unwrap();
+        assert_eq!(opened.genesis_hash(), [7; 32]);
+        assert!(DataDir::open(&root).unwrap_err().to_string().contains("in use"));
+        drop(lock);
+        let (opened, _lock) = DataDir::open(&root).unwrap();
+
+        opened.check_genesis(None).unwrap();
+        opened.check_genesis(Some([7; 32])).unwrap();
+        assert!(opened.check_genesis(Some([9; 32])).is_err());
+
+        std::fs::write(root.join(VERSION_FILE), format!("{}", DATA_DIR_VERSION + 1)).unwrap();
+        assert!(DataDir::init(&root, [7; 32]).unwrap_err().to_string().contains("newer release"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
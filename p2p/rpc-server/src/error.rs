@@ -0,0 +1,292 @@
+// p2p/rpc-server/src/error.rs
+//! Error model shared by every API surface.
+//!
+//! Handlers fail with an `ApiError`: a machine-readable `ErrorCode`, a human
+//! message, whether retrying the same request may succeed, and optional
+//! structured details. Each transport renders it its own way, from the same
+//! code: REST as an HTTP status with the error in the body, JSON-RPC as an
+//! error code with the rest in `data`, and gRPC as a canonical status code.
+//! `GET /errors` lists the catalog for client generators.
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use blockchain_core::BlockchainError;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+
+use crate::jsonrpc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Request body is not valid JSON
+    ParseError,
+    /// Request is malformed, e.g. not a JSON-RPC 2.0 envelope
+    InvalidRequest,
+    MethodNotFound,
+    /// A parameter, path segment or query value is missing or malformed
+    InvalidParams,
+    NotFound,
+    /// Well formed, but refused by validation or the admission policy
+    Rejected,
+    /// Request exceeds a server-side limit
+    LimitExceeded,
+    /// Storage or another dependency failed; the request may succeed later
+    Unavailable,
+    Internal,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 9] = [
+        ErrorCode::ParseError,
+        ErrorCode::InvalidRequest,
+        ErrorCode::MethodNotFound,
+        ErrorCode::InvalidParams,
+        ErrorCode::NotFound,
+        ErrorCode::Rejected,
+        ErrorCode::LimitExceeded,
+        ErrorCode::Unavailable,
+        ErrorCode::Internal,
+    ];
+
+    /// Whether the same request may succeed if sent again later
+    pub fn retriable(self) -> bool {
+        matches!(self, ErrorCode::Unavailable)
+    }
+
+    pub fn http_status(self) -> StatusCode {
+        match self {
+            ErrorCode::ParseError | ErrorCode::InvalidRequest | ErrorCode::InvalidParams => StatusCode::BAD_REQUEST,
+            ErrorCode::MethodNotFound | ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Rejected => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::LimitExceeded => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn json_rpc_code(self) -> i64 {
+        match self {
+            ErrorCode::ParseError => jsonrpc::PARSE_ERROR,
+            ErrorCode::InvalidRequest => jsonrpc::INVALID_REQUEST,
+            ErrorCode::MethodNotFound => jsonrpc::METHOD_NOT_FOUND,
+            ErrorCode::InvalidParams => jsonrpc::INVALID_PARAMS,
+            ErrorCode::NotFound => jsonrpc::RESOURCE_NOT_FOUND,
+            ErrorCode::Rejected => jsonrpc::TRANSACTION_REJECTED,
+            ErrorCode::LimitExceeded => jsonrpc::LIMIT_EXCEEDED,
+            ErrorCode::Unavailable => jsonrpc::RESOURCE_UNAVAILABLE,
+            ErrorCode::Internal => jsonrpc::INTERNAL_ERROR,
+        }
+    }
+
+    /// Canonical gRPC status code
+    pub fn grpc_code(self) -> i32 {
+        match self {
+            ErrorCode::ParseError | ErrorCode::InvalidRequest | ErrorCode::InvalidParams => 3, // INVALID_ARGUMENT
+            ErrorCode::NotFound => 5,                                                         // NOT_FOUND
+            ErrorCode::LimitExceeded => 8,                                                    // RESOURCE_EXHAUSTED
+            ErrorCode::Rejected => 9,                                                         // FAILED_PRECONDITION
+            ErrorCode::MethodNotFound => 12,                                                  // UNIMPLEMENTED
+            ErrorCode::Internal => 13,                                                        // INTERNAL
+            ErrorCode::Unavailable => 14,                                                     // UNAVAILABLE
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ErrorCode::ParseError => "The request body is not valid JSON",
+            ErrorCode::InvalidRequest => "The request is not a valid request for this API",
+            ErrorCode::MethodNotFound => "The method does not exist",
+            ErrorCode::InvalidParams => "A parameter is missing or malformed",
+            ErrorCode::NotFound => "The requested block, transaction or resource does not exist",
+            ErrorCode::Rejected => "The request is well formed but was refused, e.g. by validation or admission",
+            ErrorCode::LimitExceeded => "The request exceeds a server-side limit",
+            ErrorCode::Unavailable => "Storage or another dependency is unavailable; retry later",
+            ErrorCode::Internal => "The server failed to handle the request",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorCode::ParseError => write!(f, "parse_error"),
+            ErrorCode::InvalidRequest => write!(f, "invalid_request"),
+            ErrorCode::MethodNotFound => write!(f, "method_not_found"),
+            ErrorCode::InvalidParams => write!(f, "invalid_params"),
+            ErrorCode::NotFound => write!(f, "not_found"),
+            ErrorCode::Rejected => write!(f, "rejected"),
+            ErrorCode::LimitExceeded => write!(f, "limit_exceeded"),
+            ErrorCode::Unavailable => write!(f, "unavailable"),
+            ErrorCode::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+/// Handler failure, the same on every transport
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApiError {
+    code: ErrorCode,
+    message: String,
+    retriable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), retriable: code.retriable(), details: None }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidParams, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn internal(err: impl fmt::Display) -> Self {
+        Self::new(ErrorCode::Internal, err.to_string())
+    }
+
+    /// Structured context a client can act on, e.g. the expected nonce
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Override the code's default, for refusals that lift on their own
+    pub fn with_retriable(mut self, retriable: bool) -> Self {
+        self.retriable = retriable;
+        self
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn is_retriable(&self) -> bool {
+        self.retriable
+    }
+
+    pub fn details(&self) -> Option<&Value> {
+        self.details.as_ref()
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<BlockchainError> for ApiError {
+    fn from(err: BlockchainError) -> Self {
+        let message = err.to_string();
+        match err {
+            BlockchainError::InvalidAddress { .. } | BlockchainError::InvalidBlockHash(_) => {
+                Self::new(ErrorCode::InvalidParams, message)
+            }
+            BlockchainError::InsufficientBalance { have, need } => {
+                Self::new(ErrorCode::Rejected, message).with_details(json!({ "have": have, "need": need }))
+            }
+            BlockchainError::InvalidNonce { expected, actual } => {
+                Self::new(ErrorCode::Rejected, message).with_details(json!({ "expected": expected, "actual": actual }))
+            }
+            BlockchainError::InvalidTransaction { .. }
+            | BlockchainError::InvalidSignature { .. }
+            | BlockchainError::InvalidFeeDistribution { .. }
+            | BlockchainError::BlockValidationFailed { .. }
+            | BlockchainError::ChainValidationFailed { .. } => Self::new(ErrorCode::Rejected, message),
+            // Storage reports every failure, including timeouts and lost nodes, this way
+            BlockchainError::StorageError(_) => Self::new(ErrorCode::Unavailable, message),
+            BlockchainError::InvalidChainParams { .. } | BlockchainError::SerializationError(_) => {
+                Self::new(ErrorCode::Internal, message)
+            }
+        }
+    }
+}
+
+/// Storage and other dependencies fail with `anyhow`; a wrapped `BlockchainError`
+/// keeps its own code, anything else may succeed once the dependency recovers
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<BlockchainError>() {
+            Ok(err) => err.into(),
+            Err(err) => Self::new(ErrorCode::Unavailable, err.to_string()),
+        }
+    }
+}
+
+/// Rendered as the status for its code, with `{"error": {...}}` as the body
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.code.http_status(), Json(json!({ "error": self }))).into_response()
+    }
+}
+
+/// One entry of the catalog served at `GET /errors`
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorCodeSpec {
+    pub code: ErrorCode,
+    pub description: &'static str,
+    pub retriable: bool,
+    pub http_status: u16,
+    pub json_rpc_code: i64,
+    pub grpc_code: i32,
+}
+
+/// Every error code with its rendering on each transport
+pub fn error_catalog() -> Vec<ErrorCodeSpec> {
+    ErrorCode::ALL
+        .into_iter()
+        .map(|code| ErrorCodeSpec {
+            code,
+            description: code.description(),
+            retriable: code.retriable(),
+            http_status: code.http_status().as_u16(),
+            json_rpc_code: code.json_rpc_code(),
+            grpc_code: code.grpc_code(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_maps_core_errors_consistently() {
+        let nonce = ApiError::from(BlockchainError::InvalidNonce { expected: 4, actual: 2 });
+        assert_eq!(nonce.code(), ErrorCode::Rejected);
+        assert_eq!(nonce.details(), Some(&json!({ "expected": 4, "actual": 2 })));
+
+        let storage = ApiError::from(BlockchainError::StorageError("timed out".to_string()));
+        assert_eq!(storage.code(), ErrorCode::Unavailable);
+        assert!(storage.is_retriable());
+        assert_eq!(storage.code().http_status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let wrapped = ApiError::from(anyhow::Error::from(BlockchainError::InvalidAddress { reason: "checksum".into() }));
+        assert_eq!(wrapped.code(), ErrorCode::InvalidParams);
+        assert_eq!(ApiError::from(anyhow::anyhow!("no replicas")).code(), ErrorCode::Unavailable);
+
+        let body = serde_json::to_value(&storage).unwrap();
+        assert_eq!(body["code"], "unavailable");
+        assert_eq!(body["retriable"], true);
+
+        // Codes stay distinct on every transport
+        let catalog = error_catalog();
+        let rpc_codes: HashSet<i64> = catalog.iter().map(|spec| spec.json_rpc_code).collect();
+        assert_eq!(rpc_codes.len(), ErrorCode::ALL.len());
+        assert!(catalog.iter().all(|spec| json!(spec.code) == spec.code.to_string()));
+    }
+}
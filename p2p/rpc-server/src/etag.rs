@@ -0,0 +1,61 @@
+// p2p/rpc-server/src/etag.rs
+use blockchain_core::{hash_data, BlockHash};
+
+use crate::fields::FieldSelection;
+
+/// Strong ETag for a block representation.
+///
+/// A block's content is fixed by its hash, so the hash identifies the full
+/// representation; sparse selections append a digest of the canonical field
+/// list, since each selection is a different byte-for-byte body.
+pub fn block_etag(hash: &BlockHash, fields: &FieldSelection) -> String {
+    if fields.is_all() {
+        format!("\"{}\"", hex::encode(hash))
+    } else {
+        let digest = hash_data(fields.to_string().as_bytes());
+        format!("\"{}-{}\"", hex::encode(hash), hex::encode(&digest[..8]))
+    }
+}
+
+/// Whether an `If-None-Match` header value matches `etag`, i.e. the client's
+/// copy is current and a 304 can be sent.
+///
+/// Uses the weak comparison RFC 9110 prescribes for `If-None-Match`.
+pub fn if_none_match(header: &str, etag: &str) -> bool {
+    let etag = opaque_tag(etag);
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || opaque_tag(candidate) == etag)
+}
+
+fn opaque_tag(tag: &str) -> &str {
+    tag.strip_prefix("W/").unwrap_or(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_etags() {
+        let hash = [0xab; 32];
+        let full = block_etag(&hash, &FieldSelection::all());
+        assert_eq!(full, format!("\"{}\"", "ab".repeat(32)));
+
+        let headers = block_etag(&hash, &FieldSelection::parse("header").unwrap());
+        assert_ne!(headers, full);
+        assert_eq!(headers, block_etag(&hash, &FieldSelection::parse("header,header.height").unwrap()));
+        assert_ne!(headers, block_etag(&[0xcd; 32], &FieldSelection::parse("header").unwrap()));
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = "\"abc\"";
+        assert!(if_none_match("\"abc\"", etag));
+        assert!(if_none_match("\"xyz\", W/\"abc\"", etag));
+        assert!(if_none_match("*", etag));
+        assert!(!if_none_match("\"abcd\"", etag));
+        assert!(!if_none_match("", etag));
+    }
+}
@@ -0,0 +1,170 @@
+// p2p/rpc-server/src/fields.rs
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Sparse fieldset requested with `?fields=hash,header.height,transactions.hash`.
+///
+/// Paths are dot separated. A path that ends on an array applies to every
+/// element, so `transactions.hash` keeps only the hash of each transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldSelection {
+    /// `None` selects the whole representation
+    fields: Option<FieldTree>,
+}
+
+/// Selected children; an empty tree keeps the whole value at that path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FieldTree(BTreeMap<String, FieldTree>);
+
+impl FieldSelection {
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Parse a comma-separated list of field paths; an empty list selects everything
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut tree = FieldTree::default();
+        let mut any = false;
+
+        for path in spec.split(',').map(str::trim).filter(|path| !path.is_empty()) {
+            let segments: Vec<&str> = path.split('.').collect();
+            if let Some(bad) = segments.iter().find(|segment| !is_field_name(segment)) {
+                return Err(format!("Invalid field name '{}' in '{}'", bad, path));
+            }
+            tree.insert(&segments);
+            any = true;
+        }
+
+        Ok(Self { fields: any.then_some(tree) })
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.fields.is_none()
+    }
+
+    /// Whether only paths under `prefix` were requested
+    pub fn only_under(&self, prefix: &str) -> bool {
+        match &self.fields {
+            Some(tree) => tree.0.len() == 1 && tree.0.contains_key(prefix),
+            None => false,
+        }
+    }
+
+    /// Strip `value` down to the selected fields
+    pub fn apply(&self, value: Value) -> Value {
+        match &self.fields {
+            Some(tree) => tree.apply(value),
+            None => value,
+        }
+    }
+}
+
+/// Canonical form: paths sorted and deduplicated, so equal selections format equally
+impl std::fmt::Display for FieldSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut paths = Vec::new();
+        if let Some(tree) = &self.fields {
+            tree.collect_paths("", &mut paths);
+        }
+        write!(f, "{}", paths.join(","))
+    }
+}
+
+impl FieldTree {
+    fn insert(&mut self, segments: &[&str]) {
+        let Some((first, rest)) = segments.split_first() else {
+            return;
+        };
+
+        match self.0.get_mut(*first) {
+            // Already selected whole
+            Some(child) if child.0.is_empty() => {}
+            Some(child) if rest.is_empty() => child.0.clear(),
+            Some(child) => child.insert(rest),
+            None => {
+                let mut child = FieldTree::default();
+                child.insert(rest);
+                self.0.insert(first.to_string(), child);
+            }
+        }
+    }
+
+    fn apply(&self, value: Value) -> Value {
+        if self.0.is_empty() {
+            return value;
+        }
+
+        match value {
+            Value::Object(mut object) => {
+                let mut selected = Map::new();
+                for (name, child) in &self.0 {
+                    if let Some(field) = object.remove(name) {
+                        selected.insert(name.clone(), child.apply(field));
+                    }
+                }
+                Value::Object(selected)
+            }
+            Value::Array(items) => Value::Array(items.into_iter().map(|item| self.apply(item)).collect()),
+            scalar => scalar,
+        }
+    }
+
+    fn collect_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        for (name, child) in &self.0 {
+            let path = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+            if child.0.is_empty() {
+                paths.push(path);
+            } else {
+                child.collect_paths(&path, paths);
+            }
+        }
+    }
+}
+
+fn is_field_name(segment: &str) -> bool {
+    !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select_nested_fields() {
+        let block = json!({
+            "hash": [1, 2],
+            "header": { "height": 7, "timestamp": "2025-01-01T00:00:00Z" },
+            "transactions": [
+                { "hash": [3], "nonce": 0 },
+                { "hash": [4], "nonce": 1 }
+            ],
+            "size": 512
+        });
+
+        let selection = FieldSelection::parse("header.height, transactions.hash,missing").unwrap();
+        assert_eq!(
+            selection.apply(block.clone()),
+            json!({
+                "header": { "height": 7 },
+                "transactions": [{ "hash": [3] }, { "hash": [4] }]
+            })
+        );
+
+        assert_eq!(FieldSelection::parse("").unwrap().apply(block.clone()), block);
+        assert!(FieldSelection::parse("header..height").is_err());
+        assert!(FieldSelection::parse("header.height;drop").is_err());
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        let a = FieldSelection::parse("transactions.hash,header,header.height,hash").unwrap();
+        let b = FieldSelection::parse("hash,header,transactions.hash").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.to_string(), "hash,header,transactions.hash");
+
+        assert!(FieldSelection::parse("header.height,header.nonce").unwrap().only_under("header"));
+        assert!(!a.only_under("header"));
+        assert!(FieldSelection::all().is_all());
+    }
+}
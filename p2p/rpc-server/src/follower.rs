@@ -0,0 +1,137 @@
+// p2p/rpc-server/src/follower.rs
+//! Read-only follower mode.
+//!
+//! A follower serves RPC from a keyspace another node writes, for analytics
+//! and explorer traffic. It opens storage read-only, stays out of consensus
+//! and the p2p network, and polls the head the writing node stores. Each new
+//! block is read once as it appears, so the storage caches hold it before
+//! clients ask. Methods that write are refused.
+use anyhow::Result;
+use blockchain_core::BlockHeight;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use storage_traits::BlockchainStorage;
+
+/// `NODE_MODE` value that starts a follower
+pub const FOLLOWER_MODE: &str = "follower";
+
+/// How often the stored chain head is polled
+pub const DEFAULT_FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most recent blocks read per poll; a follower further behind only warms
+/// the blocks nearest the head
+pub const DEFAULT_WARM_BLOCKS: u64 = 16;
+
+/// JSON-RPC methods a follower refuses
+pub const WRITE_METHODS: &[&str] = &["tx_sendRaw"];
+
+pub fn is_write_method(method: &str) -> bool {
+    WRITE_METHODS.contains(&method)
+}
+
+/// Chain head as last seen in storage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FollowedHead {
+    pub height: BlockHeight,
+    pub hash: String,
+    pub observed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+pub struct Follower {
+    head: RwLock<Option<FollowedHead>>,
+}
+
+impl Follower {
+    pub fn head(&self) -> Option<FollowedHead> {
+        self.head.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Check the stored head once, reading the blocks added since the last
+    /// poll. Returns the new head when it moved, including to a block at the
+    /// same height after a reorg.
+    pub async fn poll(&self, storage: &dyn BlockchainStorage, warm_blocks: u64) -> Result<Option<FollowedHead>> {
+        let Some(height) = storage.get_latest_block_height().await? else {
+            return Ok(None);
+        };
+        let Some(head) = storage.get_block_by_height(height).await? else {
+            return Ok(None);
+        };
+        let hash = format!("0x{}", hex::encode(head.hash));
+        let previous = self.head();
+        if previous.as_ref().is_some_and(|seen| seen.height == height && seen.hash == hash) {
+            return Ok(None);
+        }
+
+        let first_new = previous.map_or(0, |seen| seen.height.saturating_add(1).min(height));
+        let from = first_new.max(height.saturating_sub(warm_blocks));
+        for warm in from..height {
+            storage.get_block_by_height(warm).await?;
+        }
+
+        let followed = FollowedHead { height, hash, observed_at: Utc::now() };
+        *self.head.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(followed.clone());
+        Ok(Some(followed))
+    }
+}
+
+/// Follow the stored chain head until the task is aborted.
+///
+/// A failed poll keeps the last head; the next tick tries again.
+pub fn spawn_head_follower(
+    follower: Arc<Follower>,
+    storage: Arc<dyn BlockchainStorage>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match follower.poll(storage.as_ref(), DEFAULT_WARM_BLOCKS).await {
+                Ok(Some(head)) => tracing::debug!(height = head.height, hash = %head.hash, "followed chain head"),
+                Ok(None) => {}
+                Err(e) => tracing::warn!(error = %e, "failed to poll the chain head"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc::{dispatch, RpcRequest, TRANSACTION_REJECTED};
+    use crate::testing::MemoryStorage;
+    use blockchain_core::Block;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_follows_head_and_refuses_writes() {
+        let storage = MemoryStorage::default();
+        let follower = Follower::default();
+        assert_eq!(follower.poll(&storage, DEFAULT_WARM_BLOCKS).await.unwrap(), None);
+
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        storage.store_block(&genesis).await.unwrap();
+        let head = follower.poll(&storage, DEFAULT_WARM_BLOCKS).await.unwrap().unwrap();
+        assert_eq!(head.height, 0);
+        assert_eq!(follower.poll(&storage, DEFAULT_WARM_BLOCKS).await.unwrap(), None);
+
+        // A block replaced at the same height is a new head
+        let replacement = Block::new(0, [0; 32], vec![], 2).unwrap();
+        storage.store_block(&replacement).await.unwrap();
+        let head = follower.poll(&storage, DEFAULT_WARM_BLOCKS).await.unwrap().unwrap();
+        assert_eq!(head.hash, format!("0x{}", hex::encode(replacement.hash)));
+
+        let mut state = storage.into_state();
+        state.follower = Some(Arc::new(follower));
+        let request: RpcRequest = serde_json::from_value(
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "tx_sendRaw", "params": ["0x00"] }),
+        )
+        .unwrap();
+        let error = dispatch(&state, request).await.error.unwrap();
+        assert_eq!(error.code, TRANSACTION_REJECTED);
+        assert!(error.message.contains("read-only follower"));
+    }
+}
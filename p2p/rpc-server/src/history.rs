@@ -0,0 +1,103 @@
+// p2p/rpc-server/src/history.rs
+//! History for archive peers, rebuilt from stored blocks.
+//!
+//! Block bodies are read straight from storage. Receipts and state proofs
+//! come from replaying blocks onto the genesis allocations the same way
+//! tracing does, so they need a `TraceConfig` and are bounded by its
+//! `max_replay_blocks`.
+use async_trait::async_trait;
+use blockchain_core::{AccountProof, Address, Block, BlockHash, BlockHeight, TransactionReceipt, TxHash};
+use p2p_network::{HistorySource, NetworkError};
+use std::sync::Arc;
+use storage_traits::BlockchainStorage;
+
+use crate::trace::{self, TraceConfig};
+
+pub struct ReplayHistory {
+    storage: Arc<dyn BlockchainStorage>,
+    config: Arc<TraceConfig>,
+}
+
+impl ReplayHistory {
+    pub fn new(storage: Arc<dyn BlockchainStorage>, config: Arc<TraceConfig>) -> Self {
+        Self { storage, config }
+    }
+}
+
+fn storage_error(e: impl std::fmt::Display) -> NetworkError {
+    NetworkError::Storage(e.to_string())
+}
+
+#[async_trait]
+impl HistorySource for ReplayHistory {
+    async fn block_body(&self, height: BlockHeight) -> p2p_network::Result<Option<Block>> {
+        self.storage.get_block_by_height(height).await.map_err(storage_error)
+    }
+
+    async fn receipt(&self, tx_hash: &TxHash) -> p2p_network::Result<Option<TransactionReceipt>> {
+        trace::receipt(self.storage.as_ref(), &self.config, tx_hash).await.map_err(storage_error)
+    }
+
+    async fn state_proofs(
+        &self,
+        height: BlockHeight,
+        addresses: &[Address],
+    ) -> p2p_network::Result<Option<(BlockHash, Vec<AccountProof>)>> {
+        let Some((_, ledger)) = trace::state_at(self.storage.as_ref(), &self.config, height)
+            .await
+            .map_err(storage_error)?
+        else {
+            return Ok(None);
+        };
+        let state_root = ledger.state_root().map_err(storage_error)?;
+        let proofs = addresses
+            .iter()
+            .map(|address| ledger.account_proof(address).map_err(storage_error))
+            .collect::<p2p_network::Result<Vec<_>>>()?;
+        Ok(Some((state_root, proofs)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MemoryStorage;
+    use blockchain_core::{FeeDistribution, TraceLimits, Transaction};
+    use p2p_network::{ArchiveRequest, ArchiveServer, ArchiveServingConfig, HistoryItems, HistoryQuery};
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn test_archive_server_over_replayed_history() {
+        let storage = Arc::new(MemoryStorage::default());
+        let transfer = Transaction::new_transfer([1; 20], [2; 20], 10, 0, 21_000, 1).unwrap();
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        let block = Block::new(1, genesis.hash, vec![transfer.clone()], 1).unwrap();
+        storage.store_block(&genesis).await.unwrap();
+        storage.store_block(&block).await.unwrap();
+
+        let config = Arc::new(TraceConfig {
+            fees: FeeDistribution::default(),
+            allocations: vec![([1; 20], 100_000)],
+            limits: TraceLimits::default(),
+            max_replay_blocks: 10,
+        });
+        let history = Arc::new(ReplayHistory::new(storage, config));
+        let server = ArchiveServer::new(history, ArchiveServingConfig::default());
+        let serve = |request_id, query| {
+            let request = ArchiveRequest { request_id, query, voucher: None };
+            let server = &server;
+            async move { server.serve("peer", &request, Instant::now()).await.unwrap().items }
+        };
+
+        let receipts = serve(1, HistoryQuery::Receipts { tx_hashes: vec![transfer.hash] }).await;
+        let HistoryItems::Receipts { receipts } = receipts else { panic!("expected receipts") };
+        assert_eq!((receipts[0].block_height, receipts[0].index), (1, 0));
+
+        let proofs = serve(2, HistoryQuery::StateProofs { height: 1, addresses: vec![[2; 20]] }).await;
+        let HistoryItems::StateProofs { state_root: Some(root), proofs, .. } = proofs else {
+            panic!("expected state proofs")
+        };
+        assert_eq!(proofs[0].state.unwrap().balance, 10);
+        assert!(proofs[0].verify(&root).unwrap());
+    }
+}
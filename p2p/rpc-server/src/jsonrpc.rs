@@ -0,0 +1,936 @@
+// p2p/rpc-server/src/jsonrpc.rs
+//! JSON-RPC 2.0 endpoint for calls that do not map onto a REST resource.
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use blockchain_core::{
+    Address, AddressExt, Amount, BlockHeight, BlockchainError, GasOracleConfig, Nonce, Transaction, TxHash,
+};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use storage_traits::{EventFilter, ValidatorStats};
+
+use crate::error::{ApiError, ErrorCode};
+use crate::{follower, raw_tx, trace};
+use crate::AppState;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Requested block, transaction or resource does not exist
+pub const RESOURCE_NOT_FOUND: i64 = -32001;
+/// Storage or another dependency failed; the call may succeed later
+pub const RESOURCE_UNAVAILABLE: i64 = -32002;
+/// Transaction is well formed but was not admitted
+pub const TRANSACTION_REJECTED: i64 = -32003;
+/// Request exceeds a server-side limit
+pub const LIMIT_EXCEEDED: i64 = -32005;
+
+/// Addresses accepted by one `account_getBalances` call
+pub const MAX_BALANCE_ADDRESSES: usize = 256;
+
+/// Sub-calls accepted by one `multicall`
+pub const MAX_MULTICALL_ITEMS: usize = 64;
+
+/// Entries scanned by `events_replay` when no limit is given
+pub const DEFAULT_REPLAY_LIMIT: usize = 100;
+
+/// Hours covered by `validator_stats` when no range is given
+pub const DEFAULT_VALIDATOR_STATS_HOURS: i64 = 24;
+
+/// Total cost a `multicall` may spend, see `method_cost`
+pub const MULTICALL_COST_BUDGET: u64 = 512;
+
+/// Methods `multicall` may run; none of them change node state
+const READ_ONLY_METHODS: [&str; 8] = [
+    "account_getBalances",
+    "chain_randomness",
+    "debug_memoryStats",
+    "node_admissionState",
+    "node_peerId",
+    "node_readiness",
+    "tx_decodeRaw",
+    "tx_encode",
+];
+
+#[derive(Debug, Deserialize)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    /// Positional parameters
+    #[serde(default)]
+    pub params: Vec<Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RpcResponse {
+    pub jsonrpc: &'static str,
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+/// `ApiError` in JSON-RPC form: the numeric code for the `ErrorCode`, with
+/// the machine-readable code, retry flag and details in `data`
+#[derive(Debug, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: RpcErrorData,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RpcErrorData {
+    pub code: ErrorCode,
+    pub retriable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        ApiError::new(code, message).into()
+    }
+}
+
+impl From<ApiError> for RpcError {
+    fn from(error: ApiError) -> Self {
+        Self {
+            code: error.code().json_rpc_code(),
+            message: error.message().to_string(),
+            data: RpcErrorData {
+                code: error.code(),
+                retriable: error.is_retriable(),
+                details: error.details().cloned(),
+            },
+        }
+    }
+}
+
+impl From<blockchain_core::BlockchainError> for RpcError {
+    fn from(error: blockchain_core::BlockchainError) -> Self {
+        ApiError::from(error).into()
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(error: anyhow::Error) -> Self {
+        ApiError::from(error).into()
+    }
+}
+
+impl RpcResponse {
+    fn new(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self { jsonrpc: "2.0", id, result, error }
+    }
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new().route("/rpc", post(handle)).with_state(state)
+}
+
+/// Errors are reported in the body with status 200, as JSON-RPC clients expect
+async fn handle(State(state): State<AppState>, body: String) -> Json<RpcResponse> {
+    let body: Value = match serde_json::from_str(&body) {
+        Ok(body) => body,
+        Err(e) => {
+            return Json(RpcResponse::new(Value::Null, Err(RpcError::new(ErrorCode::ParseError, e.to_string()))))
+        }
+    };
+    let request: RpcRequest = match serde_json::from_value(body) {
+        Ok(request) => request,
+        Err(e) => {
+            return Json(RpcResponse::new(Value::Null, Err(RpcError::new(ErrorCode::InvalidRequest, e.to_string()))))
+        }
+    };
+    Json(dispatch(&state, request).await)
+}
+
+pub async fn dispatch(state: &AppState, request: RpcRequest) -> RpcResponse {
+    if request.jsonrpc != "2.0" {
+        return RpcResponse::new(request.id, Err(RpcError::new(ErrorCode::InvalidRequest, "jsonrpc must be \"2.0\"")));
+    }
+
+    let outcome = match request.method.as_str() {
+        "multicall" => multicall(state, &request.params).await,
+        method => call(state, method, &request.params).await,
+    };
+    RpcResponse::new(request.id, outcome)
+}
+
+async fn call(state: &AppState, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+    if state.follower.is_some() && follower::is_write_method(method) {
+        return Err(RpcError::new(
+            ErrorCode::Rejected,
+            format!("{} is not available on a read-only follower", method),
+        ));
+    }
+
+    match method {
+        "account_getBalances" => account_get_balances(state, params).await,
+        "chain_head" => chain_head(state).await,
+        "chain_randomness" => chain_randomness(state, params).await,
+        "debug_memoryStats" => to_result(&state.memory.stats()),
+        #[cfg(feature = "fault-injection")]
+        "debug_clearFaults" => apply_faults(state, dev_tools::FaultCommand::Clear),
+        #[cfg(feature = "fault-injection")]
+        "debug_setFaults" => debug_set_faults(state, params),
+        "debug_stateAt" => debug_state_at(state, params).await,
+        "debug_traceTransaction" => debug_trace_transaction(state, params).await,
+        "events_replay" => events_replay(state, params).await,
+        "node_admissionState" => node_admission_state(state),
+        "node_followerStatus" => node_follower_status(state),
+        "node_peerId" => node_peer_id(state),
+        "node_readiness" => node_readiness(state),
+        "suggest_gas_price" => suggest_gas_price(state).await,
+        "tx_decodeRaw" => tx_decode_raw(params),
+        "tx_sendRaw" => tx_send_raw(state, params).await,
+        "tx_encode" => tx_encode(params),
+        "tx_lifecycle" => tx_lifecycle(state, params).await,
+        "validator_stats" => validator_stats(state, params).await,
+        other => Err(RpcError::new(ErrorCode::MethodNotFound, format!("Unknown method: {}", other))),
+    }
+}
+
+/// Budget units charged for a call: one per storage lookup, at least one
+pub fn method_cost(method: &str, params: &[Value]) -> u64 {
+    match method {
+        "account_getBalances" => params
+            .first()
+            .and_then(Value::as_array)
+            .map_or(1, |addresses| addresses.len().max(1) as u64),
+        "suggest_gas_price" => 1 + GasOracleConfig::default().sample_blocks,
+        _ => 1,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AccountBalance {
+    address: String,
+    balance: Amount,
+    nonce: Nonce,
+}
+
+/// Balances in request order; unknown accounts report zero
+async fn account_get_balances(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let addresses: Vec<String> = param(params, 0, "addresses")?;
+    if addresses.len() > MAX_BALANCE_ADDRESSES {
+        return Err(RpcError::new(
+            ErrorCode::LimitExceeded,
+            format!("At most {} addresses per call, got {}", MAX_BALANCE_ADDRESSES, addresses.len()),
+        ));
+    }
+
+    let addresses = addresses
+        .iter()
+        .map(|text| Address::from_checksum_hex(text))
+        .collect::<blockchain_core::Result<Vec<Address>>>()
+        .map_err(RpcError::from)?;
+
+    let mut balances = Vec::with_capacity(addresses.len());
+    for address in &addresses {
+        let account = state.storage
+            .get_account(address)
+            .await
+            .map_err(RpcError::from)?;
+        balances.push(AccountBalance {
+            address: address.to_checksum_hex(),
+            balance: account.as_ref().map_or(0, |a| a.balance),
+            nonce: account.as_ref().map_or(0, |a| a.nonce),
+        });
+    }
+    to_result(&balances)
+}
+
+/// `events_replay(from_seq, filter?, limit?)`
+async fn events_replay(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let from_seq: u64 = param(params, 0, "from_seq")?;
+    let filter: EventFilter = optional_param(params, 1, "filter")?.unwrap_or_default();
+    let limit: usize = optional_param(params, 2, "limit")?.unwrap_or(DEFAULT_REPLAY_LIMIT);
+    state.query_budget.check_events(limit)?;
+
+    let page = state.events
+        .replay_events(from_seq, &filter, limit)
+        .await
+        .map_err(RpcError::from)?;
+    to_result(&page)
+}
+
+#[derive(Debug, Deserialize)]
+struct MulticallItem {
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+/// Run read-only calls in order, each reporting its own result or error.
+///
+/// Calls are charged against `MULTICALL_COST_BUDGET` as they run; once a call
+/// would overspend it, it and every later call fail with `LIMIT_EXCEEDED`
+/// without running.
+async fn multicall(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let items: Vec<MulticallItem> = param(params, 0, "calls")?;
+    if items.len() > MAX_MULTICALL_ITEMS {
+        return Err(RpcError::new(
+            ErrorCode::LimitExceeded,
+            format!("At most {} calls per multicall, got {}", MAX_MULTICALL_ITEMS, items.len()),
+        ));
+    }
+
+    let mut remaining = MULTICALL_COST_BUDGET;
+    let mut results = Vec::with_capacity(items.len());
+    for item in &items {
+        let outcome = if !READ_ONLY_METHODS.contains(&item.method.as_str()) {
+            Err(RpcError::new(ErrorCode::InvalidParams, format!("{} cannot be used in multicall", item.method)))
+        } else {
+            let cost = method_cost(&item.method, &item.params);
+            if cost > remaining {
+                remaining = 0;
+                Err(RpcError::new(ErrorCode::LimitExceeded, "Multicall cost budget exhausted"))
+            } else {
+                remaining -= cost;
+                call(state, &item.method, &item.params).await
+            }
+        };
+        results.push(match outcome {
+            Ok(result) => serde_json::json!({ "result": result }),
+            Err(error) => serde_json::json!({ "error": error }),
+        });
+    }
+    Ok(Value::Array(results))
+}
+
+fn tx_decode_raw(params: &[Value]) -> Result<Value, RpcError> {
+    let raw: String = param(params, 0, "raw transaction hex")?;
+    let decoded = raw_tx::decode_raw(&raw).map_err(|e| RpcError::new(ErrorCode::InvalidParams, e))?;
+    to_result(&decoded)
+}
+
+/// Current admission level and the bar it sets, for operators
+fn node_admission_state(state: &AppState) -> Result<Value, RpcError> {
+    let policy = state.admission.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    to_result(&policy.state())
+}
+
+/// Whether the node accepts transactions yet, and what it is waiting for
+fn node_readiness(state: &AppState) -> Result<Value, RpcError> {
+    let gate = state.readiness.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    to_result(&gate.status())
+}
+
+/// The peer id other nodes authenticate this node as
+fn node_peer_id(state: &AppState) -> Result<Value, RpcError> {
+    let peer_id = state
+        .peer_id
+        .as_ref()
+        .ok_or_else(|| RpcError::new(ErrorCode::Internal, "Node key is not available"))?;
+    Ok(serde_json::json!({ "peer_id": peer_id }))
+}
+
+/// Whether this node is a read-only follower, and the chain head it last saw
+fn node_follower_status(state: &AppState) -> Result<Value, RpcError> {
+    let head = state.follower.as_ref().and_then(|follower| follower.head());
+    Ok(serde_json::json!({ "follower": state.follower.is_some(), "head": head }))
+}
+
+/// Re-execute a stored transaction against the state before it, returning
+/// its call frames, steps and account accesses
+/// Height and hash of the highest stored block, or `null` on an empty chain
+async fn chain_head(state: &AppState) -> Result<Value, RpcError> {
+    let unavailable = |e: anyhow::Error| RpcError::new(ErrorCode::Unavailable, e.to_string());
+    let Some(height) = state.storage.get_latest_block_height().await.map_err(unavailable)? else {
+        return Ok(Value::Null);
+    };
+    let block = state.storage
+        .get_block_by_height(height)
+        .await
+        .map_err(unavailable)?
+        .ok_or_else(|| RpcError::new(ErrorCode::Internal, format!("Head block {} is missing", height)))?;
+    Ok(serde_json::json!({ "height": height, "hash": format!("0x{}", hex::encode(block.hash)) }))
+}
+
+/// `chain_randomness(height?)`: the beacon seed of a stored block, the head's
+/// by default. Blocks from before the beacon report a `null` seed; see
+/// `blockchain_core::beacon` for what a seed may safely decide.
+async fn chain_randomness(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let unavailable = |e: anyhow::Error| RpcError::new(ErrorCode::Unavailable, e.to_string());
+    let height = match optional_param::<BlockHeight>(params, 0, "height")? {
+        Some(height) => height,
+        None => match state.storage.get_latest_block_height().await.map_err(unavailable)? {
+            Some(head) => head,
+            None => return Ok(Value::Null),
+        },
+    };
+    let block = state.storage
+        .get_block_by_height(height)
+        .await
+        .map_err(unavailable)?
+        .ok_or_else(|| RpcError::new(ErrorCode::NotFound, format!("Block {} is not stored", height)))?;
+    Ok(serde_json::json!({
+        "height": height,
+        "hash": format!("0x{}", hex::encode(block.hash)),
+        "seed": block.header.random_seed.map(|seed| format!("0x{}", hex::encode(seed))),
+    }))
+}
+
+/// Gas prices to offer for slow, standard and fast inclusion, read from the
+/// transactions in recent blocks and never below this node's admission bar
+async fn suggest_gas_price(state: &AppState) -> Result<Value, RpcError> {
+    let unavailable = |e: anyhow::Error| RpcError::new(ErrorCode::Unavailable, e.to_string());
+    let config = GasOracleConfig::default();
+    let floor = state.admission.read().unwrap_or_else(|poisoned| poisoned.into_inner()).min_gas_price();
+
+    let head = state.storage.get_latest_block_height().await.map_err(unavailable)?;
+    let mut blocks = Vec::new();
+    if let Some(head) = head {
+        for height in head.saturating_sub(config.sample_blocks - 1)..=head {
+            blocks.extend(state.storage.get_block_by_height(height).await.map_err(unavailable)?);
+        }
+    }
+    let suggestion = config.suggest_from_blocks(&blocks, floor);
+    Ok(serde_json::json!({
+        "slow": suggestion.slow,
+        "standard": suggestion.standard,
+        "fast": suggestion.fast,
+        "sampled_blocks": blocks.len(),
+        "sampled_transactions": suggestion.sampled_transactions,
+        "min_gas_price": floor,
+    }))
+}
+
+/// `debug_stateAt(height)`: every account after the block at `height`, and
+/// the state root over them
+async fn debug_state_at(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let config = state
+        .trace
+        .as_ref()
+        .ok_or_else(|| RpcError::new(ErrorCode::Internal, "Tracing is not configured on this node"))?;
+    let height: BlockHeight = param(params, 0, "height")?;
+
+    let (block, ledger) = trace::state_at(state.storage.as_ref(), config, height)
+        .await?
+        .ok_or_else(|| RpcError::new(ErrorCode::NotFound, format!("Block {} is not stored", height)))?;
+    Ok(trace::render_state(&block, &ledger)?)
+}
+
+async fn debug_trace_transaction(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let config = state
+        .trace
+        .as_ref()
+        .ok_or_else(|| RpcError::new(ErrorCode::Internal, "Tracing is not configured on this node"))?;
+    let hash: String = param(params, 0, "transaction hash")?;
+    let tx_hash = parse_tx_hash(&hash)?;
+
+    let trace = trace::trace_transaction(state.storage.as_ref(), config, &tx_hash)
+        .await?
+        .ok_or_else(|| RpcError::new(ErrorCode::NotFound, format!("Transaction {} is not in a stored block", hash)))?;
+    Ok(trace::render(&trace))
+}
+
+/// Admit a raw signed transaction to the mempool, returning its hash
+async fn tx_send_raw(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let raw: String = param(params, 0, "raw transaction hex")?;
+    let decoded = raw_tx::decode_raw(&raw).map_err(|e| RpcError::new(ErrorCode::InvalidParams, e))?;
+    if !decoded.validity.valid {
+        return Err(RpcError::new(ErrorCode::InvalidParams, decoded.validity.errors.join("; ")));
+    }
+
+    // A node without peers or far behind the network could neither propagate nor order the transaction
+    let readiness = state.readiness.read().unwrap_or_else(|poisoned| poisoned.into_inner()).status();
+    if !readiness.ready {
+        let message = format!("Node not ready: {}", readiness.reasons.join("; "));
+        let details = to_result(&readiness)?;
+        return Err(RpcError::from(ApiError::new(ErrorCode::Unavailable, message).with_details(details)));
+    }
+
+    let tx = decoded.transaction;
+    state.admission
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .check(&tx)
+        // The admission bar moves with the relayer backlog, so a refused transaction may be admitted later
+        .map_err(|e| RpcError::from(ApiError::from(e).with_retriable(true)))?;
+
+    state.storage
+        .add_pending_transaction(&tx)
+        .await
+        .map_err(|e| match e.downcast::<BlockchainError>() {
+            // Storage refuses e.g. a replacement that does not raise the gas price enough
+            Ok(e) => RpcError::from(e),
+            Err(e) => RpcError::new(ErrorCode::Unavailable, e.to_string()),
+        })?;
+    Ok(Value::String(decoded.hash))
+}
+
+fn tx_encode(params: &[Value]) -> Result<Value, RpcError> {
+    let tx: Transaction = param(params, 0, "transaction")?;
+    let encoded = raw_tx::encode(&tx).map_err(|e| RpcError::new(ErrorCode::InvalidParams, e))?;
+    to_result(&encoded)
+}
+
+/// Where a transaction is, from the mempool to the relay target; `null` if unknown
+async fn tx_lifecycle(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let hash: String = param(params, 0, "transaction hash")?;
+    let tx_hash = parse_tx_hash(&hash)?;
+
+    let lifecycle = state.lifecycle
+        .tx_lifecycle(&tx_hash)
+        .await
+        .map_err(RpcError::from)?;
+    to_result(&lifecycle)
+}
+
+/// `validator_stats` range, both ends inclusive
+#[derive(Debug, Deserialize)]
+struct StatsRange {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct ValidatorStatsResult {
+    validator: String,
+    uptime: Option<f64>,
+    participation: Option<f64>,
+    #[serde(flatten)]
+    stats: ValidatorStats,
+}
+
+/// `validator_stats(validator, range?)`; the range defaults to the last day
+async fn validator_stats(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let validator: String = param(params, 0, "validator")?;
+    let validator = Address::from_checksum_hex(&validator).map_err(RpcError::from)?;
+    let range = optional_param(params, 1, "range")?.unwrap_or_else(|| {
+        let to = Utc::now();
+        StatsRange { from: to - Duration::hours(DEFAULT_VALIDATOR_STATS_HOURS), to }
+    });
+    if range.from > range.to {
+        return Err(RpcError::new(ErrorCode::InvalidParams, "Range must not end before it starts"));
+    }
+    state.query_budget.check_validator_stats(range.from, range.to)?;
+
+    let stats = state.validators
+        .validator_stats(&validator, range.from, range.to)
+        .await
+        .map_err(RpcError::from)?;
+    to_result(&ValidatorStatsResult {
+        validator: validator.to_checksum_hex(),
+        uptime: stats.total.uptime(),
+        participation: stats.total.participation(),
+        stats,
+    })
+}
+
+fn parse_tx_hash(hash: &str) -> Result<TxHash, RpcError> {
+    let digits = hash.strip_prefix("0x").unwrap_or(hash);
+    hex::decode(digits)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| RpcError::new(ErrorCode::InvalidParams, "Transaction hash must be 32 hex-encoded bytes"))
+}
+
+/// `debug_setFaults(config)`: replace the injected faults, returning the new configuration
+#[cfg(feature = "fault-injection")]
+fn debug_set_faults(state: &AppState, params: &[Value]) -> Result<Value, RpcError> {
+    let config: dev_tools::FaultConfig = param(params, 0, "config")?;
+    apply_faults(state, dev_tools::FaultCommand::Set { config })
+}
+
+#[cfg(feature = "fault-injection")]
+fn apply_faults(state: &AppState, command: dev_tools::FaultCommand) -> Result<Value, RpcError> {
+    let config = state.faults.apply(command).map_err(|e| RpcError::new(ErrorCode::InvalidParams, e))?;
+    to_result(&config)
+}
+
+fn param<T: serde::de::DeserializeOwned>(params: &[Value], index: usize, name: &str) -> Result<T, RpcError> {
+    let value = params
+        .get(index)
+        .ok_or_else(|| RpcError::new(ErrorCode::InvalidParams, format!("Missing parameter {}: {}", index, name)))?;
+    serde_json::from_value(value.clone())
+        .map_err(|e| RpcError::new(ErrorCode::InvalidParams, format!("Invalid {}: {}", name, e)))
+}
+
+/// Like `param`, but absent and `null` parameters are `None`
+fn optional_param<T: serde::de::DeserializeOwned>(
+    params: &[Value],
+    index: usize,
+    name: &str,
+) -> Result<Option<T>, RpcError> {
+    match params.get(index) {
+        None | Some(Value::Null) => Ok(None),
+        Some(_) => param(params, index, name).map(Some),
+    }
+}
+
+fn to_result<T: Serialize>(value: &T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(ErrorCode::Internal, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backpressure::observe_backlog;
+    use crate::readiness::observe_readiness;
+    use crate::testing::MemoryStorage;
+    use crate::TraceConfig;
+    use blockchain_core::{
+        AdmissionLevel, Block, FeeDistribution, KeyPair, ReadinessConfig, ReadinessGate, SignatureScheme, TraceLimits,
+    };
+    use scylla_adapter::model::{RelayerBatch, RelayerStatus};
+    use scylla_adapter::tx_lifecycle::LifecycleFacts;
+    use scylla_adapter::validator_stats::{attestation_records, slot_records};
+    use storage_traits::{BlockchainStorage, EventLog};
+    use serde_json::json;
+    use std::sync::{Arc, RwLock};
+
+    fn request(method: &str, params: Value) -> RpcRequest {
+        serde_json::from_value(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })).unwrap()
+    }
+
+    fn address(i: u16) -> Address {
+        let mut address = [0u8; 20];
+        address[..2].copy_from_slice(&i.to_be_bytes());
+        address[19] = 1;
+        address
+    }
+
+    #[tokio::test]
+    async fn test_get_balances_in_request_order() {
+        let storage = MemoryStorage::default();
+        storage.update_account(&address(1), 70, 3, "user").await.unwrap();
+        let state = storage.into_state();
+
+        let params = json!([[address(2).to_checksum_hex(), address(1).to_checksum_hex()]]);
+        let response = dispatch(&state, request("account_getBalances", params)).await;
+        let balances = response.result.unwrap();
+        assert_eq!(balances[0]["balance"], 0);
+        assert_eq!(balances[1]["balance"], 70);
+        assert_eq!(balances[1]["nonce"], 3);
+
+        let response = dispatch(&state, request("account_getBalances", json!([["0x1234"]]))).await;
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_multicall_budget_and_per_item_errors() {
+        let state = MemoryStorage::default().into_state();
+        let batch: Vec<String> = (0..200).map(|i| address(i).to_checksum_hex()).collect();
+
+        let calls = json!([[
+            { "method": "account_getBalances", "params": [batch] },
+            { "method": "tx_decodeRaw", "params": ["0x00"] },
+            { "method": "multicall", "params": [[]] },
+            { "method": "account_getBalances", "params": [batch] },
+            { "method": "account_getBalances", "params": [batch] },
+            { "method": "tx_decodeRaw", "params": ["0x00"] },
+        ]]);
+        let results = dispatch(&state, request("multicall", calls)).await.result.unwrap();
+        let results = results.as_array().unwrap();
+
+        assert_eq!(results[0]["result"].as_array().unwrap().len(), 200);
+        assert_eq!(results[1]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(results[2]["error"]["code"], INVALID_PARAMS);
+        assert!(results[3]["result"].is_array());
+        assert_eq!(results[4]["error"]["code"], LIMIT_EXCEEDED);
+        assert_eq!(results[5]["error"]["code"], LIMIT_EXCEEDED);
+    }
+
+    #[tokio::test]
+    async fn test_events_replay_pages_with_filter() {
+        let storage = MemoryStorage::default();
+        for (event_type, subject) in [("block_stored", "0x01"), ("block_rolled_back", "0x01"), ("block_stored", "0x02")] {
+            storage.publish_event(event_type, subject, "{}").await.unwrap();
+        }
+        let state = storage.into_state();
+
+        let params = json!([0, { "event_types": ["block_stored"] }, 2]);
+        let page = dispatch(&state, request("events_replay", params)).await.result.unwrap();
+        assert_eq!(page["events"].as_array().unwrap().len(), 1);
+        assert_eq!(page["next_seq"], 2);
+        assert_eq!(page["head_seq"], 3);
+
+        let page = dispatch(&state, request("events_replay", json!([2]))).await.result.unwrap();
+        assert_eq!(page["events"][0]["subject"], "0x02");
+        assert_eq!(page["next_seq"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_follows_backlog() {
+        let key = KeyPair::generate(SignatureScheme::Ed25519);
+        let from = Address::from_public_key(&key.public_key()).unwrap();
+        let raw = |nonce: u64, gas_price: u64| {
+            let mut tx = Transaction::new_transfer(from, address(2), 10, nonce, 21_000, gas_price).unwrap();
+            tx.sign(&key);
+            raw_tx::encode(&tx).unwrap().raw
+        };
+        let state = MemoryStorage::default().into_state();
+
+        let response = dispatch(&state, request("tx_sendRaw", json!([raw(0, 1)]))).await;
+        assert!(response.result.is_some(), "{:?}", response.error);
+
+        let backlog = MemoryStorage::default();
+        *backlog.relayer_queue_depth.lock().unwrap() = 6_000;
+        let level = observe_backlog(&state.admission, &backlog).await.unwrap();
+        assert_eq!(level, Some(AdmissionLevel::Shedding));
+
+        let response = dispatch(&state, request("tx_sendRaw", json!([raw(1, 1)]))).await;
+        let error = response.error.unwrap();
+        assert_eq!(error.code, TRANSACTION_REJECTED);
+        assert_eq!(error.data.code, ErrorCode::Rejected);
+        assert!(error.data.retriable);
+        let response = dispatch(&state, request("tx_sendRaw", json!([raw(2, 100)]))).await;
+        assert!(response.result.is_some(), "{:?}", response.error);
+
+        let admission = dispatch(&state, request("node_admissionState", json!([]))).await.result.unwrap();
+        assert_eq!(admission["level"], "shedding");
+        assert_eq!(admission["queue_depth"], 6_000);
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_waits_for_readiness() {
+        let key = KeyPair::generate(SignatureScheme::Ed25519);
+        let from = Address::from_public_key(&key.public_key()).unwrap();
+        let mut tx = Transaction::new_transfer(from, address(2), 10, 0, 21_000, 1).unwrap();
+        tx.sign(&key);
+        let raw = raw_tx::encode(&tx).unwrap().raw;
+        let mut state = MemoryStorage::default().into_state();
+        let config = ReadinessConfig { min_peers: 1, max_sync_lag: 5 };
+        state.readiness = Arc::new(RwLock::new(ReadinessGate::new(config)));
+
+        let error = dispatch(&state, request("tx_sendRaw", json!([raw]))).await.error.unwrap();
+        assert_eq!(error.code, RESOURCE_UNAVAILABLE);
+        assert!(error.data.retriable);
+        assert_eq!(error.message, "Node not ready: 0 of 1 required peers connected");
+        assert_eq!(error.data.details.unwrap()["peers"], 0);
+
+        let peers = MemoryStorage::default();
+        *peers.connected_peer_heights.lock().unwrap() = vec![3, 20];
+        let changed = observe_readiness(&state.readiness, state.storage.as_ref(), &peers).await.unwrap();
+        assert_eq!(changed, None);
+        let status = dispatch(&state, request("node_readiness", json!([]))).await.result.unwrap();
+        assert_eq!(status["peers"], 2);
+        assert_eq!(status["sync_lag"], 20);
+
+        for height in 0..=15 {
+            state.storage.store_block(&Block::new(height, [0; 32], vec![], 1).unwrap()).await.unwrap();
+        }
+        let changed = observe_readiness(&state.readiness, state.storage.as_ref(), &peers).await.unwrap();
+        assert_eq!(changed, Some(true));
+        let response = dispatch(&state, request("tx_sendRaw", json!([raw]))).await;
+        assert!(response.result.is_some(), "{:?}", response.error);
+    }
+
+    #[tokio::test]
+    async fn test_memory_stats_breakdown() {
+        let state = MemoryStorage::default().into_state();
+        state.memory.charge(blockchain_core::MemorySubsystem::OrphanPool, 4_096);
+
+        let stats = dispatch(&state, request("debug_memoryStats", json!([]))).await.result.unwrap();
+        assert_eq!(stats["used_bytes"], 4_096);
+        let orphans = &stats["subsystems"][1];
+        assert_eq!(orphans["subsystem"], "orphan_pool");
+        assert_eq!(orphans["used_bytes"], 4_096);
+        assert_eq!(orphans["budget_bytes"], 64 * 1024 * 1024);
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[tokio::test]
+    async fn test_set_and_clear_faults() {
+        let state = MemoryStorage::default().into_state();
+        let config = json!({
+            "query_failure_rate": 0.5,
+            "query_delay_ms": 0,
+            "query_targets": ["StoreBlock"],
+            "message_delay_ms": 20,
+            "message_jitter_ms": 0,
+            "peer_drop_rate": 0.0,
+        });
+        let response = dispatch(&state, request("debug_setFaults", json!([config]))).await;
+        assert_eq!(response.result.unwrap(), config);
+        assert_eq!(state.faults.message_fate(), dev_tools::MessageFate::Delay(std::time::Duration::from_millis(20)));
+
+        // An out-of-range rate is refused and leaves the faults as they were
+        let mut invalid = config.clone();
+        invalid["peer_drop_rate"] = json!(2.0);
+        let error = dispatch(&state, request("debug_setFaults", json!([invalid]))).await.error.unwrap();
+        assert_eq!(error.code, INVALID_PARAMS);
+        assert_eq!(state.faults.config().query_targets, vec!["StoreBlock".to_string()]);
+
+        let response = dispatch(&state, request("debug_clearFaults", json!([]))).await;
+        assert_eq!(response.result.unwrap()["query_failure_rate"], 0.0);
+        assert!(!state.faults.config().is_active());
+    }
+
+    #[tokio::test]
+    async fn test_node_peer_id() {
+        let mut state = MemoryStorage::default().into_state();
+        let response = dispatch(&state, request("node_peerId", json!([]))).await;
+        let error = response.error.unwrap();
+        assert_eq!(error.code, INTERNAL_ERROR);
+        assert_eq!(error.data.code, ErrorCode::Internal);
+        assert!(!error.data.retriable);
+
+        state.peer_id = Some("ab".repeat(32));
+        let result = dispatch(&state, request("node_peerId", json!([]))).await.result.unwrap();
+        assert_eq!(result["peer_id"], "ab".repeat(32));
+    }
+
+    #[tokio::test]
+    async fn test_tx_lifecycle_timeline() {
+        let tx = Transaction::new_transfer(address(1), address(2), 10, 0, 21_000, 1).unwrap();
+        let mut batch = RelayerBatch::new(vec![tx.hash], "relayer-1".to_string());
+        batch.start_processing(4);
+        batch.mark_failed();
+
+        let storage = MemoryStorage::default();
+        storage.lifecycle_facts.lock().unwrap().insert(
+            tx.hash,
+            LifecycleFacts { transaction: Some(tx.clone()), relayer_batches: vec![batch], ..Default::default() },
+        );
+        let state = storage.into_state();
+
+        let hash = format!("0x{}", hex::encode(tx.hash));
+        let lifecycle = dispatch(&state, request("tx_lifecycle", json!([hash]))).await.result.unwrap();
+        assert_eq!(lifecycle["stage"], "relayer");
+        assert_eq!(lifecycle["timeline"][0]["stage"], "mempool");
+        assert_eq!(lifecycle["timeline"][1]["status"], RelayerStatus::Failed.to_string());
+        assert_eq!(lifecycle["timeline"][1]["detail"], "1 failed attempts");
+
+        let unknown = format!("0x{}", "00".repeat(32));
+        let response = dispatch(&state, request("tx_lifecycle", json!([unknown]))).await;
+        assert_eq!(response.result, Some(Value::Null));
+
+        let response = dispatch(&state, request("tx_lifecycle", json!(["0x1234"]))).await;
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_validator_stats_range() {
+        let poa = blockchain_core::PoaConfig {
+            validators: vec![address(1), address(2)],
+            slot_duration_secs: 60,
+            genesis_time: "2025-06-01T00:00:00Z".parse().unwrap(),
+        };
+        let finality = blockchain_core::FinalityConfig { validators: poa.validators.clone(), interval: 10 };
+
+        let storage = MemoryStorage::default();
+        let received_at = poa.slot_start(4) + Duration::milliseconds(400);
+        *storage.validator_slots.lock().unwrap() =
+            slot_records(&poa, poa.slot_start(1), poa.slot_start(4), received_at);
+        *storage.validator_attestations.lock().unwrap() =
+            attestation_records(&finality, 10, &[address(2)], poa.slot_start(5));
+        let state = storage.into_state();
+
+        let range = json!({ "from": "2025-06-01T00:00:00Z", "to": "2025-06-01T06:00:00Z" });
+        let params = json!([address(1).to_checksum_hex(), range]);
+        let stats = dispatch(&state, request("validator_stats", params)).await.result.unwrap();
+        assert_eq!(stats["validator"], address(1).to_checksum_hex());
+        assert_eq!(stats["total"]["proposed_blocks"], 1);
+        assert_eq!(stats["total"]["missed_blocks"], 1);
+        assert_eq!(stats["total"]["avg_proposal_latency_ms"], 400.0);
+        assert_eq!(stats["uptime"], 0.5);
+        assert_eq!(stats["participation"], 0.0);
+        assert_eq!(stats["periods"].as_array().unwrap().len(), 1);
+
+        let backwards = json!({ "from": "2025-06-02T00:00:00Z", "to": "2025-06-01T00:00:00Z" });
+        let params = json!([address(1).to_checksum_hex(), backwards]);
+        let response = dispatch(&state, request("validator_stats", params)).await;
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_gas_price_from_recent_blocks() {
+        let storage = MemoryStorage::default();
+        let response = dispatch(&storage.into_state(), request("suggest_gas_price", json!([]))).await;
+        let idle = response.result.unwrap();
+        assert_eq!(idle["standard"], 1);
+        assert_eq!(idle["sampled_blocks"], 0);
+
+        let storage = MemoryStorage::default();
+        let transfers = (0..4)
+            .map(|nonce| Transaction::new_transfer(address(1), address(2), 10, nonce, 21_000, 10 + nonce * 10))
+            .collect::<blockchain_core::Result<Vec<_>>>()
+            .unwrap();
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        storage.store_block(&genesis).await.unwrap();
+        storage.store_block(&Block::new(1, genesis.hash, transfers, 1).unwrap()).await.unwrap();
+        let response = dispatch(&storage.into_state(), request("suggest_gas_price", json!([]))).await;
+        let suggestion = response.result.unwrap();
+        assert_eq!(suggestion["slow"], 10);
+        assert_eq!(suggestion["standard"], 20);
+        assert_eq!(suggestion["fast"], 30);
+        assert_eq!(suggestion["sampled_blocks"], 2);
+        assert_eq!(suggestion["sampled_transactions"], 4);
+    }
+
+    #[tokio::test]
+    async fn test_chain_randomness_reports_beacon_seeds() {
+        let storage = MemoryStorage::default();
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        let seed = blockchain_core::beacon::next_seed(&genesis);
+        let block = Block::new(1, genesis.hash, vec![], 1)
+            .unwrap()
+            .with_state_root([3; 32])
+            .unwrap()
+            .with_random_seed(seed)
+            .unwrap();
+        storage.store_block(&genesis).await.unwrap();
+        storage.store_block(&block).await.unwrap();
+        let state = storage.into_state();
+
+        let head = dispatch(&state, request("chain_randomness", json!([]))).await.result.unwrap();
+        assert_eq!(head["height"], 1);
+        assert_eq!(head["seed"], format!("0x{}", hex::encode(seed)));
+        let before = dispatch(&state, request("chain_randomness", json!([0]))).await.result.unwrap();
+        assert_eq!(before["seed"], Value::Null);
+        let missing = dispatch(&state, request("chain_randomness", json!([7]))).await;
+        assert_eq!(missing.error.unwrap().code, RESOURCE_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_trace_transaction_against_historical_state() {
+        let storage = MemoryStorage::default();
+        let genesis = Block::new(0, [0; 32], vec![], 1).unwrap();
+        let first = Transaction::new_transfer(address(1), address(2), 10, 0, 21_000, 1).unwrap();
+        let second = Transaction::new_transfer(address(1), address(2), 10, 1, 21_000, 1).unwrap();
+        let block = Block::new(1, genesis.hash, vec![first, second.clone()], 1).unwrap();
+        storage.store_block(&genesis).await.unwrap();
+        storage.store_block(&block).await.unwrap();
+
+        let mut state = storage.into_state();
+        let hash = format!("0x{}", hex::encode(second.hash));
+        let response = dispatch(&state, request("debug_traceTransaction", json!([hash]))).await;
+        assert_eq!(response.error.unwrap().code, INTERNAL_ERROR);
+
+        state.trace = Some(Arc::new(TraceConfig {
+            fees: FeeDistribution::default(),
+            allocations: vec![(address(1), 100_000)],
+            limits: TraceLimits::default(),
+            max_replay_blocks: 10,
+        }));
+        let trace = dispatch(&state, request("debug_traceTransaction", json!([hash]))).await.result.unwrap();
+        assert_eq!(trace["block_height"], 1);
+        assert_eq!(trace["index"], 1);
+        assert_eq!(trace["gas_used"], 21_000);
+        assert_eq!(trace["error"], Value::Null);
+        assert_eq!(trace["frames"][0]["from"], address(1).to_checksum_hex());
+        // Read before the transaction: the first transfer already applied
+        assert_eq!(trace["accesses"][0]["balance"], 100_000 - 21_010);
+        assert_eq!(trace["accesses"][0]["nonce"], 1);
+
+        let unknown = format!("0x{}", "00".repeat(32));
+        let response = dispatch(&state, request("debug_traceTransaction", json!([unknown]))).await;
+        assert_eq!(response.error.unwrap().code, RESOURCE_NOT_FOUND);
+    }
+}
@@ -0,0 +1,65 @@
+// p2p/rpc-server/src/lib.rs
+use blockchain_core::{AdmissionPolicy, MemoryAccountant, ReadinessGate};
+use std::sync::{Arc, RwLock};
+use storage_traits::{BlockchainStorage, EventLog, LifecycleLookup, ValidatorStatsLookup};
+
+pub mod backpressure;
+pub mod backup;
+pub mod capacity;
+pub mod clock_drift;
+pub mod compare;
+pub mod datadir;
+pub mod error;
+pub mod etag;
+pub mod fields;
+pub mod follower;
+pub mod history;
+pub mod jsonrpc;
+pub mod memory;
+pub mod migration;
+pub mod query_budget;
+pub mod raw_tx;
+pub mod readiness;
+pub mod rest;
+pub mod startup;
+pub mod storage_migration;
+pub mod storage_supervisor;
+pub mod trace;
+pub mod webhooks;
+
+#[cfg(test)]
+mod testing;
+
+pub use error::{ApiError, ErrorCode};
+pub use fields::FieldSelection;
+pub use follower::Follower;
+pub use query_budget::QueryBudget;
+pub use trace::TraceConfig;
+
+/// Shared state handed to every request handler
+#[derive(Clone)]
+pub struct AppState {
+    pub storage: Arc<dyn BlockchainStorage>,
+    pub events: Arc<dyn EventLog>,
+    /// Cross-stage transaction tracking for `tx_lifecycle`
+    pub lifecycle: Arc<dyn LifecycleLookup>,
+    /// Uptime and participation for `validator_stats`
+    pub validators: Arc<dyn ValidatorStatsLookup>,
+    /// Admission bar for submitted transactions, moved by the backlog monitor
+    pub admission: Arc<RwLock<AdmissionPolicy>>,
+    /// Peer and sync thresholds gating `tx_sendRaw`, moved by the readiness monitor
+    pub readiness: Arc<RwLock<ReadinessGate>>,
+    /// Per-subsystem memory usage for `debug_memoryStats`
+    pub memory: Arc<MemoryAccountant>,
+    /// This node's p2p peer id, for operators adding it to allowlists
+    pub peer_id: Option<String>,
+    /// Set on a read-only follower, which refuses write methods
+    pub follower: Option<Arc<Follower>>,
+    /// Largest range query the public endpoints run
+    pub query_budget: QueryBudget,
+    /// Genesis state for `debug_traceTransaction`; tracing is off without it
+    pub trace: Option<Arc<TraceConfig>>,
+    /// Injected storage and network faults, changed by `debug_setFaults`
+    #[cfg(feature = "fault-injection")]
+    pub faults: dev_tools::FaultInjector,
+}
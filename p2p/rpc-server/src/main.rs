@@ -1,3 +1,305 @@
-fn main() {
-    println!("Hello, world!");
+// p2p/rpc-server/src/main.rs
+use blockchain_core::{
+    AdmissionPolicy, BackpressureConfig, DriftConfig, DriftMonitor, MemoryAccountant, MemoryConfig, ReadinessConfig,
+    ReadinessGate, SystemClock,
+};
+use p2p_network::{ChainIdentity, NetworkConfig};
+use rpc_server::datadir::{parse_genesis_hash, DataDir, DEFAULT_DATA_DIR};
+use rpc_server::startup::{check_startup, StartupConfig};
+use rpc_server::backup;
+use rpc_server::capacity;
+use rpc_server::compare::{self, LocalChain, RemoteChain};
+use rpc_server::follower::{self, Follower};
+use rpc_server::webhooks::{self, HttpSender, WebhookConfig, WebhookDispatcher};
+use rpc_server::{
+    backpressure, clock_drift, jsonrpc, memory, migration, readiness, rest, storage_migration, storage_supervisor,
+    AppState, QueryBudget, TraceConfig,
+};
+use scylla_adapter::scylla_config::ScyllaConfig;
+use scylla_adapter::ScyllaAdapter;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use storage_traits::{BlockchainStorage, DualWriteConfig, DualWriteStorage, EventFilter, EventLog};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let data_dir = PathBuf::from(std::env::var("NODE_DATA_DIR").unwrap_or_else(|_| DEFAULT_DATA_DIR.to_string()));
+    if std::env::args().nth(1).as_deref() == Some("init") {
+        return init(&data_dir, std::env::args().nth(2));
+    }
+    if std::env::args().nth(1).as_deref() == Some("compare") {
+        return compare_remote(&std::env::args().skip(2).collect::<Vec<_>>()).await;
+    }
+    if std::env::args().nth(1).as_deref() == Some("backup") {
+        return run_backup(&data_dir, &std::env::args().skip(2).collect::<Vec<_>>()).await;
+    }
+    if std::env::args().nth(1).as_deref() == Some("storage") {
+        return match std::env::args().nth(2).as_deref() {
+            Some("cutover") => {
+                storage_migration::request_cutover(&data_dir)?;
+                println!("Cutover requested; the node switches once the new keyspace has caught up");
+                Ok(())
+            }
+            _ => anyhow::bail!("Usage: rpc-server storage cutover"),
+        };
+    }
+    if std::env::args().nth(1).as_deref() == Some("report") {
+        return report(&std::env::args().skip(2).collect::<Vec<_>>()).await;
+    }
+
+    // A follower serves a keyspace another node writes, without writing to it
+    let follower_mode = std::env::var("NODE_MODE").is_ok_and(|mode| mode == follower::FOLLOWER_MODE);
+    let mut config = ScyllaConfig::from_env()?;
+    config.read_only |= follower_mode;
+    let mut network = NetworkConfig::from_env();
+    let memory_config = MemoryConfig::default();
+    let addr = std::env::var("RPC_LISTEN_ADDR").unwrap_or_else(|_| "0.0.0.0:8080".to_string());
+    check_startup(&StartupConfig {
+        rpc_listen_addr: &addr,
+        scylla: &config,
+        p2p: &network,
+        memory: &memory_config,
+    })?;
+
+    // Held until the process exits, so a second node cannot share the data
+    let (data_dir, _lock) = DataDir::open(&data_dir)?;
+    if std::env::var("P2P_NODE_KEY_PATH").is_err() {
+        network.node_key_path = Some(data_dir.node_key_path());
+    }
+
+    let keepalive_interval = std::time::Duration::from_millis(config.supervisor.keepalive_interval_ms);
+    let migration_config = match std::env::var("SCYLLA_MIGRATION_KEYSPACE") {
+        Ok(keyspace) if !config.read_only => Some(ScyllaConfig { keyspace, ..config.clone() }),
+        _ => None,
+    };
+    let storage = Arc::new(ScyllaAdapter::new(config).await?);
+    storage_supervisor::spawn_storage_supervisor(storage.clone(), keepalive_interval);
+
+    // Chain reads and writes go through both keyspaces while migrating to another
+    let chain_storage: Arc<dyn BlockchainStorage> = match migration_config {
+        Some(migration_config) => {
+            let target = Arc::new(ScyllaAdapter::new(migration_config).await?);
+            storage_supervisor::spawn_storage_supervisor(target.clone(), keepalive_interval);
+            let dual = Arc::new(DualWriteStorage::new(storage.clone(), target, DualWriteConfig::default()));
+            storage_migration::spawn_migration_sync(
+                dual.clone(),
+                data_dir.root().to_path_buf(),
+                storage_migration::DEFAULT_MIGRATION_SYNC_BLOCKS,
+                storage_migration::DEFAULT_MIGRATION_SYNC_INTERVAL,
+            );
+            dual
+        }
+        None => storage.clone(),
+    };
+    let stored_genesis = storage.get_block_by_height(0).await?.map(|block| block.hash);
+    data_dir.check_genesis(stored_genesis)?;
+    let follower = if storage.is_read_only() {
+        let follower = Arc::new(Follower::default());
+        follower::spawn_head_follower(follower.clone(), storage.clone(), follower::DEFAULT_FOLLOW_INTERVAL);
+        Some(follower)
+    } else {
+        migration::spawn_format_migration(
+            storage.clone(),
+            migration::DEFAULT_MIGRATION_BATCH_BLOCKS,
+            migration::DEFAULT_MIGRATION_INTERVAL,
+        );
+        None
+    };
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    let min_gas_price = std::env::var("RPC_MIN_GAS_PRICE").ok().and_then(|v| v.parse().ok()).unwrap_or(1);
+    let admission = Arc::new(RwLock::new(AdmissionPolicy::new(min_gas_price, BackpressureConfig::default())));
+    backpressure::spawn_backlog_monitor(
+        admission.clone(),
+        storage.clone(),
+        backpressure::DEFAULT_BACKLOG_POLL_INTERVAL,
+    );
+
+    // Writes wait until the node can propagate them and is close to the network's head
+    let mut readiness_config = ReadinessConfig::default();
+    if let Ok(peers) = std::env::var("NODE_MIN_PEERS") {
+        readiness_config.min_peers = peers.parse().unwrap_or(readiness_config.min_peers);
+    }
+    if let Ok(lag) = std::env::var("NODE_MAX_SYNC_LAG") {
+        readiness_config.max_sync_lag = lag.parse().unwrap_or(readiness_config.max_sync_lag);
+    }
+    let readiness = Arc::new(RwLock::new(ReadinessGate::new(readiness_config)));
+    readiness::spawn_readiness_monitor(
+        readiness.clone(),
+        storage.clone(),
+        storage.clone(),
+        readiness::DEFAULT_READINESS_POLL_INTERVAL,
+    );
+
+    let ntp_server = std::env::var("NTP_SERVER").unwrap_or_else(|_| clock_drift::DEFAULT_NTP_SERVER.to_string());
+    let drift = Arc::new(DriftMonitor::new(Arc::new(SystemClock), DriftConfig::default()));
+    clock_drift::spawn_drift_monitor(drift, ntp_server, clock_drift::DEFAULT_DRIFT_POLL_INTERVAL);
+
+    let memory = Arc::new(MemoryAccountant::new(memory_config));
+    memory::spawn_memory_monitor(memory.clone(), memory::DEFAULT_MEMORY_POLL_INTERVAL);
+
+    // Same key file as the p2p layer, so the reported id is the one peers see
+    let peer_id = match network.identity() {
+        Ok(identity) => Some(identity.peer_id()),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to load the node key");
+            None
+        }
+    };
+
+    let query_budget = QueryBudget::from_env();
+    query_budget.validate().map_err(anyhow::Error::msg)?;
+    let trace = load_trace_config()?.map(Arc::new);
+
+    if let Ok(path) = std::env::var("WEBHOOK_CONFIG_PATH") {
+        // Activity from before startup is not delivered
+        let head_seq = storage.replay_events(0, &EventFilter::default(), 1).await?.head_seq;
+        let sender = Arc::new(HttpSender::new(std::time::Duration::from_secs(10))?);
+        let config = WebhookConfig::load(Path::new(&path))?;
+        let dispatcher = WebhookDispatcher::new(storage.clone(), storage.clone(), sender, config, head_seq)?;
+        webhooks::spawn_webhook_dispatcher(dispatcher);
+    }
+
+    // Shared with the storage layer, so RPC changes take effect on its queries
+    #[cfg(feature = "fault-injection")]
+    let faults = storage.fault_injector().clone();
+    let state = AppState {
+        storage: chain_storage,
+        events: storage.clone(),
+        lifecycle: storage.clone(),
+        validators: storage,
+        admission,
+        readiness,
+        memory,
+        peer_id,
+        follower,
+        query_budget,
+        trace,
+        #[cfg(feature = "fault-injection")]
+        faults,
+    };
+    let app = rest::router(state.clone()).merge(jsonrpc::router(state));
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// `rpc-server init [genesis_hash]`: create the data directory and node key; the
+/// hash may instead come from `GENESIS_HASH`
+fn init(data_dir: &Path, genesis_hash: Option<String>) -> anyhow::Result<()> {
+    let genesis_hash = genesis_hash
+        .or_else(|| std::env::var("GENESIS_HASH").ok())
+        .ok_or_else(|| anyhow::anyhow!("Usage: rpc-server init <genesis hash>, or set GENESIS_HASH"))?;
+    let dir = DataDir::init(data_dir, parse_genesis_hash(&genesis_hash)?)?;
+    println!("Initialized {}", dir.root().display());
+    println!("Peer id: {}", dir.identity()?.peer_id());
+    Ok(())
+}
+
+/// `rpc-server compare --remote <rpc url>`: report where this node's chain and
+/// the remote node's part ways, failing if they diverge
+async fn compare_remote(args: &[String]) -> anyhow::Result<()> {
+    let remote = match args {
+        [flag, url] if flag == "--remote" => url,
+        _ => anyhow::bail!("Usage: rpc-server compare --remote <rpc url>"),
+    };
+    let mut config = ScyllaConfig::from_env()?;
+    config.read_only = true;
+    let storage = Arc::new(ScyllaAdapter::new(config).await?);
+
+    let local = LocalChain::new(storage, load_trace_config()?);
+    let report = compare::compare(&local, &RemoteChain::new(remote)).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if let Some(divergence) = report.divergence {
+        anyhow::bail!("Chains diverge at block {}", divergence.height);
+    }
+    Ok(())
+}
+
+/// `rpc-server report capacity [--window <days>] [--horizon <days>]...`: project
+/// storage and bandwidth needs from current sizes and the window's growth
+async fn report(args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "Usage: rpc-server report capacity [--window <days>] [--horizon <days>]...";
+    let flags = match args {
+        [command, flags @ ..] if command == "capacity" => flags,
+        _ => anyhow::bail!(USAGE),
+    };
+    let mut window = capacity::DEFAULT_CAPACITY_WINDOW_DAYS;
+    let mut horizons = Vec::new();
+    for pair in flags.chunks(2) {
+        let days = |text: &String| text.parse::<u32>().map_err(|_| anyhow::anyhow!("Invalid number of days: {}", text));
+        match pair {
+            [flag, value] if flag == "--window" => window = days(value)?,
+            [flag, value] if flag == "--horizon" => horizons.push(days(value)?),
+            _ => anyhow::bail!(USAGE),
+        }
+    }
+    if horizons.is_empty() {
+        horizons = capacity::DEFAULT_CAPACITY_HORIZONS_DAYS.to_vec();
+    }
+
+    let mut config = ScyllaConfig::from_env()?;
+    config.read_only = true;
+    let storage = ScyllaAdapter::new(config).await?;
+    let sample = capacity::CapacitySample::collect(&storage, window, chrono::Utc::now()).await?;
+    println!("{}", serde_json::to_string_pretty(&sample.project(&horizons))?);
+    Ok(())
+}
+
+/// `rpc-server backup export <dir> [from] [to]` writes a manifest signed with
+/// the node key; `rpc-server backup restore <dir> [--trust <peer id>]...`
+/// restores one signed by the given peers, or by this node if none are given
+async fn run_backup(data_dir: &Path, args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "Usage: rpc-server backup export <dir> [from] [to] | restore <dir> [--trust <peer id>]...";
+    let (command, dir) = match args {
+        [command, dir, ..] => (command.as_str(), Path::new(dir)),
+        _ => anyhow::bail!(USAGE),
+    };
+    let (data_dir, _lock) = DataDir::open(data_dir)?;
+    let identity = data_dir.identity()?;
+    let mut config = ScyllaConfig::from_env()?;
+    config.read_only = command == "export";
+    let storage = ScyllaAdapter::new(config).await?;
+    let chain_id = storage
+        .get_chain_id()
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("The keyspace records no chain id"))?;
+
+    match (command, &args[2..]) {
+        ("export", range) => {
+            let parse = |text: &String| text.parse().map_err(|_| anyhow::anyhow!("Invalid height: {}", text));
+            let from = range.first().map(parse).transpose()?.unwrap_or(0);
+            let to = match range.get(1) {
+                Some(to) => parse(to)?,
+                None => storage.get_latest_block_height().await?.ok_or_else(|| anyhow::anyhow!("No blocks stored"))?,
+            };
+            let manifest =
+                backup::export(&storage, &identity, chain_id, from, to, backup::DEFAULT_CHUNK_BLOCKS, dir).await?;
+            println!("Exported blocks {}..={} in {} chunks", from, to, manifest.chunks.len());
+        }
+        ("restore", flags) => {
+            let mut trusted = Vec::new();
+            for pair in flags.chunks(2) {
+                match pair {
+                    [flag, peer_id] if flag == "--trust" => trusted.push(peer_id.clone()),
+                    _ => anyhow::bail!(USAGE),
+                }
+            }
+            if trusted.is_empty() {
+                trusted.push(identity.peer_id());
+            }
+            let target = ChainIdentity { chain_id, genesis_hash: data_dir.genesis_hash() };
+            let manifest = backup::restore(&storage, dir, target, &trusted).await?;
+            println!("Restored blocks {}..={}", manifest.from_height, manifest.to_height);
+        }
+        _ => anyhow::bail!(USAGE),
+    }
+    Ok(())
+}
+
+/// Genesis state for tracing, from the file named by `TRACE_CONFIG_PATH`
+fn load_trace_config() -> anyhow::Result<Option<TraceConfig>> {
+    match std::env::var("TRACE_CONFIG_PATH") {
+        Ok(path) => Ok(Some(TraceConfig::load(Path::new(&path))?)),
+        Err(_) => Ok(None),
+    }
 }
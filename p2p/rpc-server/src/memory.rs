@@ -0,0 +1,34 @@
+// p2p/rpc-server/src/memory.rs
+//! Periodic enforcement of the memory budgets.
+use blockchain_core::{MemoryAccountant, MemorySubsystem};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often budgets are checked
+pub const DEFAULT_MEMORY_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Evict from every subsystem over budget, returning what was enforced
+pub fn enforce_budgets(accountant: &MemoryAccountant) -> Vec<(MemorySubsystem, u64)> {
+    let enforced = accountant.enforce();
+    for (subsystem, freed) in &enforced {
+        tracing::warn!(
+            subsystem = %subsystem,
+            freed_bytes = freed,
+            used_bytes = accountant.used(*subsystem),
+            budget_bytes = accountant.config().budget(*subsystem),
+            "memory budget exceeded, evicting"
+        );
+    }
+    enforced
+}
+
+/// Keep subsystems within their budgets until the task is aborted
+pub fn spawn_memory_monitor(accountant: Arc<MemoryAccountant>, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            enforce_budgets(&accountant);
+        }
+    })
+}
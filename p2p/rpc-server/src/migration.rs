@@ -0,0 +1,41 @@
+// p2p/rpc-server/src/migration.rs
+//! Background rewrite of rows stored in an older blob format.
+use std::sync::Arc;
+use std::time::Duration;
+use storage_traits::FormatMigration;
+
+/// Pause between migration passes, leaving room for regular traffic
+pub const DEFAULT_MIGRATION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Blocks migrated per pass
+pub const DEFAULT_MIGRATION_BATCH_BLOCKS: u64 = 100;
+
+/// Migrate `batch_blocks` blocks every `interval` until the whole chain is in
+/// the current format. Progress is stored by the backend, so a restarted node
+/// resumes where it stopped; a failed pass is retried on the next tick.
+pub fn spawn_format_migration(
+    migration: Arc<dyn FormatMigration>,
+    batch_blocks: u64,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match migration.migrate_formats(batch_blocks).await {
+                Ok(progress) if progress.completed => {
+                    tracing::info!(height = progress.target_height, "stored blobs are in the current format");
+                    return;
+                }
+                Ok(progress) => tracing::info!(
+                    next_height = progress.next_height,
+                    target_height = progress.target_height,
+                    rewritten = progress.rows_rewritten,
+                    percent = format!("{:.1}", progress.fraction_done() * 100.0),
+                    "migrating stored blob formats"
+                ),
+                Err(e) => tracing::warn!(error = %e, "blob format migration pass failed"),
+            }
+        }
+    })
+}
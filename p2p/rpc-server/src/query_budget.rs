@@ -0,0 +1,135 @@
+// p2p/rpc-server/src/query_budget.rs
+//! Cost estimates and budgets for queries that scan a range.
+//!
+//! Event log and validator stats queries can cover arbitrarily large ranges.
+//! Before one runs, its cost is estimated in rows read, partitions touched
+//! and time covered. A query over budget is refused with `LIMIT_EXCEEDED`
+//! and the bounds that would fit, rather than left to time out in storage.
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::{ApiError, ErrorCode};
+
+/// Estimated work of one query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QueryCost {
+    pub rows: u64,
+    pub partitions: u64,
+    /// Time span covered, for range queries
+    pub range_hours: u64,
+}
+
+impl QueryCost {
+    /// An event log page of up to `limit` entries, from one partition
+    pub fn events(limit: usize) -> Self {
+        Self { rows: limit as u64, partitions: 1, range_hours: 0 }
+    }
+
+    /// Validator stats between `from` and `to`: one rollup partition per
+    /// hour, each read for slots and attestations
+    pub fn validator_stats(from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        let hours = span_hours(from, to);
+        Self { rows: hours.saturating_mul(2), partitions: hours, range_hours: hours }
+    }
+}
+
+/// Hours touched by `[from, to]`, counting partial hours
+fn span_hours(from: DateTime<Utc>, to: DateTime<Utc>) -> u64 {
+    let minutes = (to - from).num_minutes().max(0) as u64;
+    ((minutes + 59) / 60).max(1)
+}
+
+/// Largest query a public endpoint runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryBudget {
+    pub max_rows: u64,
+    pub max_partitions: u64,
+    pub max_range_hours: u64,
+}
+
+impl Default for QueryBudget {
+    fn default() -> Self {
+        Self { max_rows: 10_000, max_partitions: 24 * 31, max_range_hours: 24 * 31 }
+    }
+}
+
+impl QueryBudget {
+    /// Defaults overridden by `RPC_MAX_QUERY_ROWS`, `RPC_MAX_QUERY_PARTITIONS`
+    /// and `RPC_MAX_QUERY_RANGE_HOURS`
+    pub fn from_env() -> Self {
+        let mut budget = Self::default();
+        if let Ok(rows) = std::env::var("RPC_MAX_QUERY_ROWS") {
+            budget.max_rows = rows.parse().unwrap_or(budget.max_rows);
+        }
+        if let Ok(partitions) = std::env::var("RPC_MAX_QUERY_PARTITIONS") {
+            budget.max_partitions = partitions.parse().unwrap_or(budget.max_partitions);
+        }
+        if let Ok(hours) = std::env::var("RPC_MAX_QUERY_RANGE_HOURS") {
+            budget.max_range_hours = hours.parse().unwrap_or(budget.max_range_hours);
+        }
+        budget
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_rows == 0 || self.max_partitions == 0 || self.max_range_hours == 0 {
+            return Err("Query budget limits must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn allows(&self, cost: &QueryCost) -> bool {
+        cost.rows <= self.max_rows
+            && cost.partitions <= self.max_partitions
+            && cost.range_hours <= self.max_range_hours
+    }
+
+    /// Refuse an event log page over budget, suggesting the largest limit
+    pub fn check_events(&self, limit: usize) -> Result<(), ApiError> {
+        let cost = QueryCost::events(limit);
+        if self.allows(&cost) {
+            return Ok(());
+        }
+        Err(self.narrow(&cost, json!({ "limit": self.max_rows })))
+    }
+
+    /// Refuse a stats range over budget, suggesting the latest range that fits
+    pub fn check_validator_stats(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<(), ApiError> {
+        let cost = QueryCost::validator_stats(from, to);
+        if self.allows(&cost) {
+            return Ok(());
+        }
+        let hours = self.max_range_hours.min(self.max_partitions).min(self.max_rows / 2).max(1);
+        let suggested_from = to - Duration::hours(hours as i64);
+        Err(self.narrow(&cost, json!({ "from": suggested_from, "to": to })))
+    }
+
+    fn narrow(&self, cost: &QueryCost, suggested: serde_json::Value) -> ApiError {
+        ApiError::new(ErrorCode::LimitExceeded, "Query is too broad; narrow your query to the suggested bounds")
+            .with_details(json!({ "cost": cost, "budget": self, "suggested": suggested }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refuses_broad_queries_with_bounds_that_fit() {
+        let budget = QueryBudget { max_rows: 100, max_partitions: 48, max_range_hours: 24 };
+        budget.check_events(100).unwrap();
+        let error = budget.check_events(101).unwrap_err();
+        assert_eq!(error.code(), ErrorCode::LimitExceeded);
+        assert_eq!(error.details().unwrap()["suggested"]["limit"], 100);
+
+        let to: DateTime<Utc> = "2025-06-02T00:00:00Z".parse().unwrap();
+        budget.check_validator_stats(to - Duration::hours(24), to).unwrap();
+        let error = budget.check_validator_stats(to - Duration::days(7), to).unwrap_err();
+        let details = error.details().unwrap();
+        assert_eq!(details["cost"]["range_hours"], 168);
+        assert_eq!(details["suggested"]["from"], json!(to - Duration::hours(24)));
+
+        // A partial hour still touches a partition
+        assert_eq!(QueryCost::validator_stats(to, to + Duration::minutes(61)).partitions, 2);
+    }
+}
@@ -0,0 +1,55 @@
+// p2p/rpc-server/src/readiness.rs
+//! Feedback loop from peer connectivity and sync progress to the readiness gate.
+use anyhow::Result;
+use blockchain_core::ReadinessGate;
+use chrono::Utc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use storage_traits::{BlockchainStorage, ConnectedPeers};
+
+/// How often peers and the local head are sampled
+pub const DEFAULT_READINESS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Sample peers and the local head once and update `gate`, returning the new
+/// readiness on a change
+pub async fn observe_readiness(
+    gate: &RwLock<ReadinessGate>,
+    storage: &dyn BlockchainStorage,
+    peers: &dyn ConnectedPeers,
+) -> Result<Option<bool>> {
+    let local_height = storage.get_latest_block_height().await?;
+    let heights = peers.connected_peer_heights().await?;
+    let mut gate = gate.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let changed = gate.observe(heights.len(), local_height, heights.iter().copied().max(), Utc::now());
+    if let Some(ready) = changed {
+        let status = gate.status();
+        tracing::warn!(
+            ready,
+            peers = status.peers,
+            sync_lag = status.sync_lag,
+            reasons = %status.reasons.join("; "),
+            "node readiness changed"
+        );
+    }
+    Ok(changed)
+}
+
+/// Keep `gate` following peers and sync progress until the task is aborted.
+///
+/// A failed sample leaves the gate where it was.
+pub fn spawn_readiness_monitor(
+    gate: Arc<RwLock<ReadinessGate>>,
+    storage: Arc<dyn BlockchainStorage>,
+    peers: Arc<dyn ConnectedPeers>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = observe_readiness(&gate, storage.as_ref(), peers.as_ref()).await {
+                tracing::warn!(error = %e, "failed to sample node readiness");
+            }
+        }
+    })
+}
@@ -0,0 +1,228 @@
+// p2p/rpc-server/src/rest.rs
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use blockchain_core::{hash_serializable, Block, BlockHash, BlockHeight, Transaction};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use storage_traits::EventFilter;
+
+use crate::error::{error_catalog, ApiError};
+use crate::etag::{block_etag, if_none_match};
+use crate::fields::FieldSelection;
+use crate::AppState;
+
+/// Blocks addressed by hash never change
+const CACHE_IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// The block at a height can be replaced by a reorg, so caches must revalidate
+const CACHE_REVALIDATE: &str = "public, no-cache";
+
+/// Entries scanned per replay page when the client does not ask
+const DEFAULT_REPLAY_LIMIT: usize = 100;
+
+type ApiResult = Result<Response, ApiError>;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FieldsQuery {
+    /// Comma-separated field paths, e.g. `hash,header.height`
+    pub fields: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplayQuery {
+    /// Cursor returned as `next_seq` by the previous page
+    #[serde(default)]
+    pub from_seq: u64,
+    /// Comma-separated event types
+    pub types: Option<String>,
+    pub subject: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl ReplayQuery {
+    fn filter(&self) -> EventFilter {
+        EventFilter {
+            event_types: self
+                .types
+                .iter()
+                .flat_map(|types| types.split(','))
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect(),
+            subject: self.subject.clone(),
+        }
+    }
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/blocks/:height", get(block_by_height))
+        .route("/blocks/hash/:hash", get(block_by_hash))
+        .route("/transactions/:hash", get(transaction_by_hash))
+        .route("/events", get(replay_events))
+        .route("/errors", get(error_codes))
+        .with_state(state)
+}
+
+async fn block_by_height(
+    State(state): State<AppState>,
+    Path(height): Path<BlockHeight>,
+    Query(query): Query<FieldsQuery>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let fields = parse_fields(&query)?;
+
+    // Header-only selections are served without loading transactions
+    if fields.only_under("header") {
+        let header = state.storage
+            .get_block_headers(&[height])
+            .await
+            .map_err(ApiError::from)?
+            .pop()
+            .ok_or_else(|| ApiError::not_found("Block not found"))?;
+        let hash = hash_serializable(&header).map_err(ApiError::internal)?;
+        return Ok(cached_block(&hash, &fields, json!({ "header": header }), CACHE_REVALIDATE, &headers));
+    }
+
+    let block = state.storage
+        .get_block_by_height(height)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("Block not found"))?;
+    let value = block_value(&state, &block)?;
+    Ok(cached_block(&block.hash, &fields, value, CACHE_REVALIDATE, &headers))
+}
+
+async fn block_by_hash(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    Query(query): Query<FieldsQuery>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let fields = parse_fields(&query)?;
+    let hash = parse_hash(&hash)?;
+
+    // The hash fixes the content, so a matching tag needs no lookup at all
+    if let Some(candidates) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+        let etag = block_etag(&hash, &fields);
+        if if_none_match(candidates, &etag) {
+            return Ok(not_modified(&etag, CACHE_IMMUTABLE));
+        }
+    }
+
+    let block = state.storage
+        .get_block_by_hash(&hash)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("Block not found"))?;
+    let value = block_value(&state, &block)?;
+    Ok(cached_block(&block.hash, &fields, value, CACHE_IMMUTABLE, &headers))
+}
+
+/// Transactions carry a mutable status, so they are served without an ETag
+async fn transaction_by_hash(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    Query(query): Query<FieldsQuery>,
+) -> ApiResult {
+    let fields = parse_fields(&query)?;
+    let hash = parse_hash(&hash)?;
+
+    let tx = state.storage
+        .get_transaction(&hash)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("Transaction not found"))?;
+    let value = transaction_value(&tx)?;
+    Ok(Json(fields.apply(value)).into_response())
+}
+
+/// Page of the event log from `from_seq`; pass `next_seq` back to continue
+async fn replay_events(State(state): State<AppState>, Query(query): Query<ReplayQuery>) -> ApiResult {
+    let limit = query.limit.unwrap_or(DEFAULT_REPLAY_LIMIT);
+    state.query_budget.check_events(limit)?;
+    let page = state.events
+        .replay_events(query.from_seq, &query.filter(), limit)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(page).into_response())
+}
+
+/// Every error code the API returns, with its HTTP, JSON-RPC and gRPC rendering
+async fn error_codes() -> Json<Value> {
+    Json(json!({ "errors": error_catalog() }))
+}
+
+/// Block as JSON, with the `total_tips` its transactions paid the proposer
+/// when the node knows its chain's fee distribution from the trace config
+fn block_value(state: &AppState, block: &Block) -> Result<Value, ApiError> {
+    let mut value = serde_json::to_value(block).map_err(ApiError::internal)?;
+    if let (Some(trace), Value::Object(object)) = (&state.trace, &mut value) {
+        object.insert("total_tips".to_string(), json!(block.total_tips(&trace.fees)));
+    }
+    Ok(value)
+}
+
+/// Transaction as JSON, with the encoded `size` the mempool and block limits count
+fn transaction_value(tx: &Transaction) -> Result<Value, ApiError> {
+    let mut value = serde_json::to_value(tx).map_err(ApiError::internal)?;
+    if let Value::Object(object) = &mut value {
+        let size = tx.encoded_size().map_err(ApiError::internal)?;
+        object.insert("size".to_string(), json!(size));
+    }
+    Ok(value)
+}
+
+/// Block body with its ETag, or a 304 when the client's copy is current
+fn cached_block(
+    hash: &BlockHash,
+    fields: &FieldSelection,
+    value: Value,
+    cache_control: &'static str,
+    request: &HeaderMap,
+) -> Response {
+    let etag = block_etag(hash, fields);
+    let current = request
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|candidates| if_none_match(candidates, &etag));
+    if current {
+        return not_modified(&etag, cache_control);
+    }
+
+    let mut response = Json(fields.apply(value)).into_response();
+    set_cache_headers(response.headers_mut(), &etag, cache_control);
+    response
+}
+
+fn not_modified(etag: &str, cache_control: &'static str) -> Response {
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    set_cache_headers(response.headers_mut(), etag, cache_control);
+    response
+}
+
+fn set_cache_headers(headers: &mut HeaderMap, etag: &str, cache_control: &'static str) {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        headers.insert(header::ETAG, value);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+}
+
+fn parse_fields(query: &FieldsQuery) -> Result<FieldSelection, ApiError> {
+    match &query.fields {
+        Some(spec) => FieldSelection::parse(spec).map_err(ApiError::bad_request),
+        None => Ok(FieldSelection::all()),
+    }
+}
+
+fn parse_hash(hex_hash: &str) -> Result<BlockHash, ApiError> {
+    let digits = hex_hash.strip_prefix("0x").unwrap_or(hex_hash);
+    hex::decode(digits)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ApiError::bad_request("Hash must be 32 hex-encoded bytes"))
+}
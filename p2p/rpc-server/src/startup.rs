@@ -0,0 +1,85 @@
+// p2p/rpc-server/src/startup.rs
+//! Configuration checks run once at startup, before anything connects.
+use blockchain_core::{ConfigErrors, ConfigReport, MemoryConfig};
+use p2p_network::NetworkConfig;
+use scylla_adapter::scylla_config::ScyllaConfig;
+use std::net::SocketAddr;
+
+/// Everything the node is configured with
+pub struct StartupConfig<'a> {
+    pub rpc_listen_addr: &'a str,
+    pub scylla: &'a ScyllaConfig,
+    pub p2p: &'a NetworkConfig,
+    pub memory: &'a MemoryConfig,
+}
+
+/// Check every section and the settings that span sections, reporting all
+/// problems at once
+pub fn check_startup(config: &StartupConfig<'_>) -> Result<(), ConfigErrors> {
+    let mut report = ConfigReport::new();
+
+    let rpc_addr = config.rpc_listen_addr.parse::<SocketAddr>();
+    report.section("rpc", |rpc| {
+        rpc.check(
+            rpc_addr.is_err(),
+            "listen_addr",
+            format!("Invalid listen address: {}", config.rpc_listen_addr),
+        );
+    });
+    report.section("scylla", |scylla| config.scylla.check(scylla));
+    report.section("p2p", |p2p| config.p2p.check(p2p));
+    report.adopt("memory", config.memory.validate());
+
+    if let (Ok(rpc), Ok(p2p)) = (rpc_addr, config.p2p.listen_addr.parse::<SocketAddr>()) {
+        report.check(
+            listeners_collide(rpc, p2p),
+            "p2p.listen_addr",
+            format!("Port {} is also the RPC listen port ({})", p2p.port(), rpc),
+        );
+    }
+
+    report.finish()
+}
+
+/// Whether binding both addresses would fail; an unspecified address binds
+/// every interface
+fn listeners_collide(a: SocketAddr, b: SocketAddr) -> bool {
+    a.port() == b.port() && (a.ip() == b.ip() || a.ip().is_unspecified() || b.ip().is_unspecified())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p2p_network::NodeRole;
+
+    #[test]
+    fn test_reports_every_problem_before_connecting() {
+        let scylla = ScyllaConfig::default();
+        let memory = MemoryConfig::default();
+        let mut p2p = NetworkConfig::default();
+        let config = StartupConfig { rpc_listen_addr: "0.0.0.0:8080", scylla: &scylla, p2p: &p2p, memory: &memory };
+        check_startup(&config).unwrap();
+
+        p2p.listen_addr = "127.0.0.1:8080".to_string();
+        p2p.role = NodeRole::Archive;
+        p2p.outbound.normal_max_delay_ms = 5_000;
+        let mut scylla = ScyllaConfig::default();
+        scylla.retry_policy.base_delay_ms = scylla.retry_policy.max_delay_ms + 1;
+        let config = StartupConfig { rpc_listen_addr: "0.0.0.0:8080", scylla: &scylla, p2p: &p2p, memory: &memory };
+
+        let errors = check_startup(&config).unwrap_err();
+        let paths: Vec<&str> = errors.0.iter().map(|issue| issue.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "scylla.retry_policy.base_delay_ms",
+                // An archive role without snapshot_serving or archive_serving
+                "p2p.capabilities",
+                "p2p.capabilities",
+                "p2p.outbound.normal_max_delay_ms",
+                "p2p.listen_addr",
+            ]
+        );
+        assert!(errors.to_string().starts_with("Invalid configuration, 5 problems:"));
+    }
+}
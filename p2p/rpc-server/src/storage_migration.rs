@@ -0,0 +1,106 @@
+// p2p/rpc-server/src/storage_migration.rs
+//! Moving the node to another keyspace while it serves, through
+//! `DualWriteStorage`.
+//!
+//! With `SCYLLA_MIGRATION_KEYSPACE` set the node writes to both keyspaces and
+//! a background pass keeps the new one caught up. `rpc-server storage
+//! cutover` leaves a request in the data directory; the next pass that finds
+//! the new keyspace caught up moves reads to it and removes the request.
+//! Until then the request stays, and each pass logs why it is waiting. Every
+//! other process writing the keyspace must run dual writes as well, or its
+//! writes only reach one side.
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use storage_traits::{DualWriteStatus, DualWriteStorage};
+
+/// Left in the data directory by `rpc-server storage cutover`
+pub const CUTOVER_REQUEST_FILE: &str = "STORAGE_CUTOVER";
+
+/// Blocks copied to the new keyspace per pass
+pub const DEFAULT_MIGRATION_SYNC_BLOCKS: usize = 1_000;
+
+pub const DEFAULT_MIGRATION_SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Ask the node using `data_dir` to cut over once the new keyspace has caught up
+pub fn request_cutover(data_dir: &Path) -> Result<()> {
+    if !data_dir.is_dir() {
+        bail!("Data directory {} does not exist", data_dir.display());
+    }
+    std::fs::write(data_dir.join(CUTOVER_REQUEST_FILE), b"")?;
+    Ok(())
+}
+
+/// Catch the new keyspace up, then cut over if it was requested and nothing
+/// is left to copy
+pub async fn sync_pass(storage: &DualWriteStorage, data_dir: &Path, max_blocks: usize) -> Result<DualWriteStatus> {
+    let status = storage.sync(max_blocks).await?;
+    let request = data_dir.join(CUTOVER_REQUEST_FILE);
+    if status.cut_over || !request.exists() {
+        return Ok(status);
+    }
+    let status = storage.cutover().await?;
+    std::fs::remove_file(request)?;
+    Ok(status)
+}
+
+pub fn spawn_migration_sync(
+    storage: Arc<DualWriteStorage>,
+    data_dir: PathBuf,
+    max_blocks: usize,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        let mut cut_over = false;
+        loop {
+            ticker.tick().await;
+            match sync_pass(&storage, &data_dir, max_blocks).await {
+                Ok(status) if status.cut_over && !cut_over => {
+                    cut_over = true;
+                    tracing::info!("storage cut over, reads now served by the new keyspace");
+                }
+                Ok(status) => tracing::debug!(
+                    queued_writes = status.queued_writes,
+                    blocks_behind = status.blocks_behind,
+                    mismatches = status.mismatches,
+                    "storage migration pass"
+                ),
+                Err(e) => tracing::warn!(error = %e, "storage migration pass failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MemoryStorage;
+    use blockchain_core::Block;
+    use storage_traits::{BlockchainStorage, DualWriteConfig};
+
+    #[tokio::test]
+    async fn test_cutover_waits_for_catch_up() {
+        let dir = std::env::temp_dir().join(format!("node-migration-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (old, new) = (Arc::new(MemoryStorage::default()), Arc::new(MemoryStorage::default()));
+        let mut previous = [0; 32];
+        for height in 0..3 {
+            let block = Block::new(height, previous, Vec::new(), 1).unwrap();
+            previous = block.hash;
+            old.store_block(&block).await.unwrap();
+        }
+        let storage = DualWriteStorage::new(old, new.clone(), DualWriteConfig::default());
+
+        request_cutover(&dir).unwrap();
+        let err = sync_pass(&storage, &dir, 2).await.unwrap_err().to_string();
+        assert!(err.contains("1 blocks behind"), "{}", err);
+        assert!(dir.join(CUTOVER_REQUEST_FILE).exists());
+
+        assert!(sync_pass(&storage, &dir, 2).await.unwrap().cut_over);
+        assert!(!dir.join(CUTOVER_REQUEST_FILE).exists());
+        assert_eq!(new.get_latest_block_height().await.unwrap(), Some(2));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,41 @@
+// p2p/rpc-server/src/storage_supervisor.rs
+//! Background keep-alive of the storage connection.
+use std::sync::Arc;
+use std::time::Duration;
+use storage_traits::{ConnectionState, ConnectionSupervisor};
+
+/// Run a supervisor pass every `interval`, logging when storage becomes
+/// unreachable and when it has caught up again
+pub fn spawn_storage_supervisor(
+    supervisor: Arc<dyn ConnectionSupervisor>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        let mut previous = ConnectionState::Connected;
+        loop {
+            ticker.tick().await;
+            let health = supervisor.supervise_connection().await;
+            match (previous, health.state) {
+                (ConnectionState::Connected, ConnectionState::Degraded) => tracing::warn!(
+                    error = health.last_error.as_deref().unwrap_or_default(),
+                    "storage unreachable, queueing writes until it reconnects"
+                ),
+                (ConnectionState::Degraded, ConnectionState::Draining) => tracing::info!(
+                    queued_writes = health.queued_writes,
+                    "storage reconnected, replaying queued writes"
+                ),
+                (_, ConnectionState::Connected) if previous != ConnectionState::Connected => {
+                    tracing::info!(reconnects = health.reconnects, "storage connection restored")
+                }
+                (_, ConnectionState::Degraded) => tracing::debug!(
+                    error = health.last_error.as_deref().unwrap_or_default(),
+                    queued_writes = health.queued_writes,
+                    "storage still unreachable"
+                ),
+                _ => {}
+            }
+            previous = health.state;
+        }
+    })
+}
@@ -0,0 +1,187 @@
+// p2p/rpc-server/src/testing.rs
+//! In-memory storage backing handler tests.
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::{Address, Block, BlockHash, BlockHeader, BlockHeight, Transaction, TxHash};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use blockchain_core::{
+    AdmissionPolicy, BackpressureConfig, MemoryAccountant, MemoryConfig, ReadinessConfig, ReadinessGate,
+};
+use std::sync::{Arc, Mutex, RwLock};
+use scylla_adapter::tx_lifecycle::{build_lifecycle, LifecycleFacts};
+use scylla_adapter::validator_stats::{combine, hourly_periods, AttestationRecord, SlotRecord};
+use storage_traits::{
+    AccountModel, BlockchainStorage, ChainEvent, ConnectedPeers, EventFilter, EventLog, EventPage, LifecycleLookup,
+    RelayerBacklog, TransactionLifecycle, ValidatorStats, ValidatorStatsLookup,
+};
+
+use crate::{AppState, QueryBudget};
+
+#[derive(Default)]
+pub(crate) struct MemoryStorage {
+    pub blocks: Mutex<HashMap<BlockHeight, Block>>,
+    pub transactions: Mutex<HashMap<TxHash, Transaction>>,
+    pub accounts: Mutex<HashMap<Address, AccountModel>>,
+    pub events: Mutex<Vec<ChainEvent>>,
+    pub relayer_queue_depth: Mutex<u64>,
+    pub connected_peer_heights: Mutex<Vec<BlockHeight>>,
+    pub lifecycle_facts: Mutex<HashMap<TxHash, LifecycleFacts>>,
+    pub validator_slots: Mutex<Vec<SlotRecord>>,
+    pub validator_attestations: Mutex<Vec<AttestationRecord>>,
+}
+
+impl MemoryStorage {
+    pub fn into_state(self) -> AppState {
+        let storage = Arc::new(self);
+        AppState {
+            storage: storage.clone(),
+            events: storage.clone(),
+            lifecycle: storage.clone(),
+            validators: storage,
+            admission: Arc::new(RwLock::new(AdmissionPolicy::new(1, BackpressureConfig::default()))),
+            readiness: Arc::new(RwLock::new(ReadinessGate::new(ReadinessConfig::disabled()))),
+            memory: Arc::new(MemoryAccountant::new(MemoryConfig::default())),
+            peer_id: None,
+            follower: None,
+            query_budget: QueryBudget::default(),
+            trace: None,
+            #[cfg(feature = "fault-injection")]
+            faults: dev_tools::FaultInjector::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl RelayerBacklog for MemoryStorage {
+    async fn relayer_queue_depth(&self) -> Result<u64> {
+        Ok(*self.relayer_queue_depth.lock().unwrap())
+    }
+}
+
+#[async_trait]
+impl ConnectedPeers for MemoryStorage {
+    async fn connected_peer_heights(&self) -> Result<Vec<BlockHeight>> {
+        Ok(self.connected_peer_heights.lock().unwrap().clone())
+    }
+}
+
+#[async_trait]
+impl LifecycleLookup for MemoryStorage {
+    async fn tx_lifecycle(&self, tx_hash: &TxHash) -> Result<Option<TransactionLifecycle>> {
+        let facts = self.lifecycle_facts.lock().unwrap();
+        Ok(facts.get(tx_hash).and_then(|facts| build_lifecycle(tx_hash, facts)))
+    }
+}
+
+/// Always hourly, however long the range
+#[async_trait]
+impl ValidatorStatsLookup for MemoryStorage {
+    async fn validator_stats(
+        &self,
+        validator: &Address,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<ValidatorStats> {
+        let mut slots = self.validator_slots.lock().unwrap().clone();
+        slots.retain(|s| s.validator == *validator && s.slot_start >= from && s.slot_start <= to);
+        let mut attestations = self.validator_attestations.lock().unwrap().clone();
+        attestations.retain(|a| a.validator == *validator && a.recorded_at >= from && a.recorded_at <= to);
+
+        let periods = hourly_periods(&slots, &attestations);
+        Ok(ValidatorStats { total: combine(from, to, &periods), periods })
+    }
+}
+
+#[async_trait]
+impl EventLog for MemoryStorage {
+    async fn publish_event(&self, event_type: &str, subject: &str, payload: &str) -> Result<u64> {
+        let mut events = self.events.lock().unwrap();
+        let seq = events.len() as u64;
+        events.push(ChainEvent {
+            seq,
+            event_type: event_type.to_string(),
+            subject: subject.to_string(),
+            payload: payload.to_string(),
+            published_at: chrono::Utc::now(),
+        });
+        Ok(seq)
+    }
+
+    async fn replay_events(&self, from_seq: u64, filter: &EventFilter, limit: usize) -> Result<EventPage> {
+        let events = self.events.lock().unwrap();
+        let scanned: Vec<&ChainEvent> = events.iter().skip(from_seq as usize).take(limit.max(1)).collect();
+        Ok(EventPage {
+            next_seq: scanned.last().map_or(from_seq, |event| event.seq + 1),
+            events: scanned.into_iter().filter(|event| filter.matches(event)).cloned().collect(),
+            head_seq: events.len() as u64,
+        })
+    }
+}
+
+#[async_trait]
+impl BlockchainStorage for MemoryStorage {
+    async fn store_block(&self, block: &Block) -> Result<()> {
+        let mut transactions = self.transactions.lock().unwrap();
+        for tx in &block.transactions {
+            transactions.insert(tx.hash, tx.clone());
+        }
+        self.blocks.lock().unwrap().insert(block.header.height, block.clone());
+        Ok(())
+    }
+
+    async fn get_block_by_height(&self, height: BlockHeight) -> Result<Option<Block>> {
+        Ok(self.blocks.lock().unwrap().get(&height).cloned())
+    }
+
+    async fn get_block_by_hash(&self, hash: &BlockHash) -> Result<Option<Block>> {
+        Ok(self.blocks.lock().unwrap().values().find(|b| &b.hash == hash).cloned())
+    }
+
+    async fn get_block_headers(&self, heights: &[BlockHeight]) -> Result<Vec<BlockHeader>> {
+        let blocks = self.blocks.lock().unwrap();
+        Ok(heights.iter().filter_map(|h| blocks.get(h)).map(|b| b.header.clone()).collect())
+    }
+
+    async fn get_latest_block_height(&self) -> Result<Option<BlockHeight>> {
+        Ok(self.blocks.lock().unwrap().keys().max().copied())
+    }
+
+    async fn get_transaction(&self, tx_hash: &TxHash) -> Result<Option<Transaction>> {
+        Ok(self.transactions.lock().unwrap().get(tx_hash).cloned())
+    }
+
+    async fn add_pending_transaction(&self, tx: &Transaction) -> Result<()> {
+        self.transactions.lock().unwrap().insert(tx.hash, tx.clone());
+        Ok(())
+    }
+
+    async fn remove_pending_transaction(&self, tx_hash: &TxHash) -> Result<()> {
+        self.transactions.lock().unwrap().remove(tx_hash);
+        Ok(())
+    }
+
+    async fn get_pending_transactions(&self, limit: i32) -> Result<Vec<Transaction>> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(transactions.values().take(limit.max(0) as usize).cloned().collect())
+    }
+
+    async fn update_account(&self, address: &Address, balance: u64, nonce: u64, account_type: &str) -> Result<()> {
+        self.accounts.lock().unwrap().insert(
+            *address,
+            AccountModel {
+                address: *address,
+                balance,
+                nonce,
+                last_updated: chrono::Utc::now(),
+                account_type: account_type.to_string(),
+                code_hash: None,
+            },
+        );
+        Ok(())
+    }
+
+    async fn get_account(&self, address: &Address) -> Result<Option<AccountModel>> {
+        Ok(self.accounts.lock().unwrap().get(address).cloned())
+    }
+}
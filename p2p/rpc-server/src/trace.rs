@@ -0,0 +1,215 @@
+// p2p/rpc-server/src/trace.rs
+//! Re-executing stored transactions for `debug_traceTransaction`.
+//!
+//! Storage keeps only current account state, so the state a transaction ran
+//! against is rebuilt by replaying stored blocks onto the genesis
+//! allocations, then the transactions before it in its own block. Replay is
+//! linear in the height of that block; `max_replay_blocks` bounds it. The
+//! same replay gives the state after any block, for `debug_stateAt`, and
+//! the receipts of its transactions, for archive peers.
+use anyhow::Context;
+use blockchain_core::trace::CallFrame;
+use blockchain_core::{
+    Address, AddressExt, Amount, Block, BlockHeight, ExecutionTrace, FeeDistribution, Ledger, TraceLimits,
+    TransactionReceipt, TxHash,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use storage_traits::BlockchainStorage;
+
+use crate::error::{ApiError, ErrorCode};
+
+/// Highest block a transaction may be traced in when no limit is configured
+pub const DEFAULT_MAX_REPLAY_BLOCKS: u64 = 100_000;
+
+/// What a node needs to rebuild historical state; loaded from the file named
+/// by `TRACE_CONFIG_PATH`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceConfig {
+    /// Fee distribution of the chain spec
+    pub fees: FeeDistribution,
+    /// Genesis balances the replay starts from
+    #[serde(default)]
+    pub allocations: Vec<(Address, Amount)>,
+    #[serde(default)]
+    pub limits: TraceLimits,
+    #[serde(default = "default_max_replay_blocks")]
+    pub max_replay_blocks: u64,
+}
+
+fn default_max_replay_blocks() -> u64 {
+    DEFAULT_MAX_REPLAY_BLOCKS
+}
+
+impl TraceConfig {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("Invalid trace config in {}", path.display()))
+    }
+}
+
+/// Trace `tx_hash` against the state before it, or `None` if no stored block
+/// includes it
+pub async fn trace_transaction(
+    storage: &dyn BlockchainStorage,
+    config: &TraceConfig,
+    tx_hash: &TxHash,
+) -> Result<Option<ExecutionTrace>, ApiError> {
+    let unavailable = |e: anyhow::Error| ApiError::new(ErrorCode::Unavailable, e.to_string());
+    if storage.get_transaction(tx_hash).await.map_err(unavailable)?.is_none() {
+        return Ok(None);
+    }
+    let Some(head) = storage.get_latest_block_height().await.map_err(unavailable)? else {
+        return Ok(None);
+    };
+
+    let mut ledger = genesis_ledger(config)?;
+    for height in 0..=head {
+        let Some(block) = storage.get_block_by_height(height).await.map_err(unavailable)? else {
+            return Err(ApiError::internal(format!("Block {} is missing from storage", height)));
+        };
+        if let Some(index) = block.transactions.iter().position(|tx| tx.hash == *tx_hash) {
+            let mut before = block.clone();
+            before.transactions.truncate(index);
+            ledger.replay_block(&before, &config.fees)?;
+            let tx = &block.transactions[index];
+            return Ok(Some(ledger.trace_transaction(tx, &config.fees, height, index as u32, config.limits)));
+        }
+        if height >= config.max_replay_blocks {
+            return Err(ApiError::new(
+                ErrorCode::LimitExceeded,
+                format!("Transactions above block {} cannot be traced", config.max_replay_blocks),
+            ));
+        }
+        ledger.replay_block(&block, &config.fees)?;
+    }
+    Ok(None)
+}
+
+/// Receipt of `tx_hash` from replaying its block, or `None` if no stored
+/// block includes it
+pub async fn receipt(
+    storage: &dyn BlockchainStorage,
+    config: &TraceConfig,
+    tx_hash: &TxHash,
+) -> Result<Option<TransactionReceipt>, ApiError> {
+    let unavailable = |e: anyhow::Error| ApiError::new(ErrorCode::Unavailable, e.to_string());
+    if storage.get_transaction(tx_hash).await.map_err(unavailable)?.is_none() {
+        return Ok(None);
+    }
+    let Some(head) = storage.get_latest_block_height().await.map_err(unavailable)? else {
+        return Ok(None);
+    };
+
+    let mut ledger = genesis_ledger(config)?;
+    for height in 0..=head.min(config.max_replay_blocks) {
+        let Some(block) = storage.get_block_by_height(height).await.map_err(unavailable)? else {
+            return Err(ApiError::internal(format!("Block {} is missing from storage", height)));
+        };
+        let outcome = ledger.replay_block(&block, &config.fees)?;
+        if let Some(receipt) = outcome.receipts.into_iter().find(|receipt| receipt.tx_hash == *tx_hash) {
+            return Ok(Some(receipt));
+        }
+    }
+    Ok(None)
+}
+
+/// Account state after block `height`, or `None` if no block is stored there
+pub async fn state_at(
+    storage: &dyn BlockchainStorage,
+    config: &TraceConfig,
+    height: BlockHeight,
+) -> Result<Option<(Block, Ledger)>, ApiError> {
+    let unavailable = |e: anyhow::Error| ApiError::new(ErrorCode::Unavailable, e.to_string());
+    if height > config.max_replay_blocks {
+        return Err(ApiError::new(
+            ErrorCode::LimitExceeded,
+            format!("State above block {} cannot be rebuilt", config.max_replay_blocks),
+        ));
+    }
+    let Some(target) = storage.get_block_by_height(height).await.map_err(unavailable)? else {
+        return Ok(None);
+    };
+
+    let mut ledger = genesis_ledger(config)?;
+    for h in 0..height {
+        let Some(block) = storage.get_block_by_height(h).await.map_err(unavailable)? else {
+            return Err(ApiError::internal(format!("Block {} is missing from storage", h)));
+        };
+        ledger.replay_block(&block, &config.fees)?;
+    }
+    ledger.replay_block(&target, &config.fees)?;
+    Ok(Some((target, ledger)))
+}
+
+fn genesis_ledger(config: &TraceConfig) -> Result<Ledger, ApiError> {
+    let mut ledger = Ledger::new();
+    for (address, amount) in &config.allocations {
+        ledger.credit(address, *amount)?;
+    }
+    Ok(ledger)
+}
+
+/// JSON form of the state after `block`, with every account it holds
+pub fn render_state(block: &Block, ledger: &Ledger) -> Result<Value, ApiError> {
+    let accounts: Vec<Value> = ledger
+        .accounts()
+        .iter()
+        .map(|(address, account)| {
+            json!({
+                "address": address.to_checksum_hex(),
+                "balance": account.balance,
+                "nonce": account.nonce,
+            })
+        })
+        .collect();
+    Ok(json!({
+        "height": block.header.height,
+        "block_hash": format!("0x{}", hex::encode(block.hash)),
+        "state_root": format!("0x{}", hex::encode(ledger.state_root()?)),
+        "accounts": accounts,
+    }))
+}
+
+/// JSON form of a trace, with hex hashes and checksummed addresses
+pub fn render(trace: &ExecutionTrace) -> Value {
+    let frames: Vec<Value> = trace.frames.iter().map(render_frame).collect();
+    let accesses: Vec<Value> = trace
+        .accesses
+        .iter()
+        .map(|access| {
+            json!({
+                "kind": access.kind,
+                "address": access.address.to_checksum_hex(),
+                "balance": access.balance,
+                "nonce": access.nonce,
+            })
+        })
+        .collect();
+    json!({
+        "tx_hash": format!("0x{}", hex::encode(trace.tx_hash)),
+        "block_height": trace.block_height,
+        "index": trace.index,
+        "gas_used": trace.gas_used,
+        "error": trace.error,
+        "truncated": trace.truncated,
+        "frames": frames,
+        "steps": trace.steps,
+        "accesses": accesses,
+    })
+}
+
+fn render_frame(frame: &CallFrame) -> Value {
+    json!({
+        "kind": frame.kind,
+        "depth": frame.depth,
+        "from": frame.from.to_checksum_hex(),
+        "to": frame.to.map(|to| to.to_checksum_hex()),
+        "value": frame.value,
+        "gas_limit": frame.gas_limit,
+        "gas_used": frame.gas_used,
+        "input": format!("0x{}", hex::encode(&frame.input)),
+        "input_size": frame.input_size,
+    })
+}
@@ -0,0 +1,498 @@
+// p2p/rpc-server/src/webhooks.rs
+//! Batched address activity webhooks.
+//!
+//! The dispatcher tails `block_stored` events and turns each stored block's
+//! transactions into activity entries for the addresses they move funds in
+//! or out of. Every endpoint collects the entries for its own address list
+//! and receives them as one batch once `window_secs` has passed since the
+//! first entry, or sooner once `max_batch_size` entries are waiting.
+//!
+//! Bodies are signed with HMAC-SHA256 over `"{timestamp}.{body}"` using the
+//! endpoint's secret; receivers check them with `verify_signature`. A failed
+//! delivery is retried with exponential backoff until `max_attempts`, then
+//! dropped with a warning. Delivery is at least once, so receivers dedupe on
+//! `batch_id`, which stays the same across redeliveries.
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use blockchain_core::{Address, AddressExt, Amount, Block, BlockHeight};
+use chrono::{DateTime, Duration, Utc};
+use scylla_adapter::events::BLOCK_STORED;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+use storage_traits::{BlockchainStorage, EventFilter, EventLog};
+
+pub use webhook_signing::{sign, verify_signature, DEFAULT_SIGNATURE_TOLERANCE_SECS};
+
+/// Header carrying `sha256=<hex HMAC>` of the signed payload
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Header carrying the Unix time the signature was made at
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    /// Stable name, used in batch ids and logs
+    pub id: String,
+    pub url: String,
+    /// HMAC key shared with the receiver
+    pub secret: String,
+    /// Checksummed addresses whose activity the endpoint receives
+    pub addresses: Vec<String>,
+    /// How long activity collects before its batch is sent
+    pub window_secs: u64,
+    pub max_batch_size: usize,
+}
+
+/// Endpoints and delivery settings; loaded from the file named by
+/// `WEBHOOK_CONFIG_PATH`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub endpoints: Vec<WebhookEndpoint>,
+    /// Deliveries of one batch before it is dropped, counting the first
+    pub max_attempts: u32,
+    /// Wait after the first failed delivery; doubles with each failure
+    pub base_delay_secs: u64,
+    pub max_delay_secs: u64,
+    pub poll_interval_secs: u64,
+    /// Events read per poll
+    pub event_page: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            max_attempts: 6,
+            base_delay_secs: 5,
+            max_delay_secs: 600,
+            poll_interval_secs: 2,
+            event_page: 500,
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("Invalid webhook config in {}", path.display()))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        for endpoint in &self.endpoints {
+            if !ids.insert(&endpoint.id) {
+                bail!("Duplicate webhook endpoint {}", endpoint.id);
+            }
+            if !endpoint.url.starts_with("http://") && !endpoint.url.starts_with("https://") {
+                bail!("Webhook endpoint {} has an invalid URL: {}", endpoint.id, endpoint.url);
+            }
+            if endpoint.secret.is_empty() {
+                bail!("Webhook endpoint {} needs a secret", endpoint.id);
+            }
+            if endpoint.addresses.is_empty() {
+                bail!("Webhook endpoint {} needs at least one address", endpoint.id);
+            }
+            for address in &endpoint.addresses {
+                Address::from_checksum_hex(address)
+                    .with_context(|| format!("Webhook endpoint {} has an invalid address", endpoint.id))?;
+            }
+            if endpoint.window_secs == 0 || endpoint.max_batch_size == 0 {
+                bail!("Webhook endpoint {} needs a window and batch size greater than 0", endpoint.id);
+            }
+        }
+        if self.max_attempts == 0 {
+            bail!("Max delivery attempts must be greater than 0");
+        }
+        if self.base_delay_secs == 0 || self.base_delay_secs > self.max_delay_secs {
+            bail!("Base delivery delay must be greater than 0 and at most the maximum delay");
+        }
+        if self.poll_interval_secs == 0 || self.event_page == 0 {
+            bail!("Webhook poll interval and event page must be greater than 0");
+        }
+        Ok(())
+    }
+
+    /// Wait before redelivering a batch whose delivery failed `failures` times
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let doublings = failures.saturating_sub(1).min(31);
+        let secs = self.base_delay_secs.saturating_mul(1 << doublings).min(self.max_delay_secs);
+        Duration::seconds(secs as i64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+}
+
+/// Funds moving in or out of a watched address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressActivity {
+    pub address: String,
+    pub direction: Direction,
+    /// The other side; `None` for coinbase rewards
+    pub counterparty: Option<String>,
+    pub amount: Amount,
+    pub tx_hash: String,
+    pub block_height: BlockHeight,
+    pub block_hash: String,
+}
+
+/// Body of one delivery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookBatch {
+    pub batch_id: String,
+    pub endpoint: String,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub activity: Vec<AddressActivity>,
+}
+
+/// Activity for every address `block`'s transactions move funds in or out of
+pub fn block_activity(block: &Block) -> Vec<(Address, AddressActivity)> {
+    let block_hash = format!("0x{}", hex::encode(block.hash));
+    let mut activity = Vec::new();
+    for tx in &block.transactions {
+        let entry = |address: &Address, direction, counterparty: Option<Address>| AddressActivity {
+            address: address.to_checksum_hex(),
+            direction,
+            counterparty: counterparty.map(|other| other.to_checksum_hex()),
+            amount: tx.amount(),
+            tx_hash: format!("0x{}", hex::encode(tx.hash)),
+            block_height: block.header.height,
+            block_hash: block_hash.clone(),
+        };
+        let sender = (!tx.is_coinbase()).then(|| tx.sender());
+        if let Some(sender) = sender {
+            activity.push((sender, entry(&sender, Direction::Outgoing, tx.recipient())));
+        }
+        if let Some(recipient) = tx.recipient() {
+            activity.push((recipient, entry(&recipient, Direction::Incoming, sender)));
+        }
+    }
+    activity
+}
+
+/// Sends one signed body to an endpoint
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    async fn send(&self, url: &str, timestamp: i64, signature: &str, body: Vec<u8>) -> Result<()>;
+}
+
+pub struct HttpSender {
+    client: reqwest::Client,
+}
+
+impl HttpSender {
+    pub fn new(timeout: std::time::Duration) -> Result<Self> {
+        Ok(Self { client: reqwest::Client::builder().timeout(timeout).build()? })
+    }
+}
+
+#[async_trait]
+impl WebhookSender for HttpSender {
+    async fn send(&self, url: &str, timestamp: i64, signature: &str, body: Vec<u8>) -> Result<()> {
+        self.client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Outcome of one dispatcher pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DispatchReport {
+    pub delivered: usize,
+    /// Failed deliveries scheduled for another attempt
+    pub retrying: usize,
+    /// Batches that failed their last attempt
+    pub dropped: usize,
+}
+
+struct Redelivery {
+    batch: WebhookBatch,
+    attempts: u32,
+    due: DateTime<Utc>,
+}
+
+struct EndpointState {
+    endpoint: WebhookEndpoint,
+    addresses: HashSet<Address>,
+    pending: Vec<AddressActivity>,
+    window_start: Option<DateTime<Utc>>,
+    batches_sent: u64,
+    redeliveries: VecDeque<Redelivery>,
+}
+
+impl EndpointState {
+    /// Pending activity as batches if the window has closed or a batch is full
+    fn take_due(&mut self, now: DateTime<Utc>) -> Vec<WebhookBatch> {
+        let Some(window_start) = self.window_start else {
+            return Vec::new();
+        };
+        let window_closed = now - window_start >= Duration::seconds(self.endpoint.window_secs as i64);
+        if !window_closed && self.pending.len() < self.endpoint.max_batch_size {
+            return Vec::new();
+        }
+
+        let mut batches = Vec::new();
+        let pending = std::mem::take(&mut self.pending);
+        for chunk in pending.chunks(self.endpoint.max_batch_size) {
+            // A partial chunk of an open window keeps collecting
+            if !window_closed && chunk.len() < self.endpoint.max_batch_size {
+                self.pending = chunk.to_vec();
+                return batches;
+            }
+            self.batches_sent += 1;
+            batches.push(WebhookBatch {
+                batch_id: format!("{}-{}", self.endpoint.id, self.batches_sent),
+                endpoint: self.endpoint.id.clone(),
+                window_start,
+                window_end: now,
+                activity: chunk.to_vec(),
+            });
+        }
+        self.window_start = None;
+        batches
+    }
+}
+
+pub struct WebhookDispatcher {
+    storage: Arc<dyn BlockchainStorage>,
+    events: Arc<dyn EventLog>,
+    sender: Arc<dyn WebhookSender>,
+    config: WebhookConfig,
+    endpoints: Vec<EndpointState>,
+    cursor: u64,
+}
+
+impl WebhookDispatcher {
+    /// Dispatcher delivering activity from events at `from_seq` onwards
+    pub fn new(
+        storage: Arc<dyn BlockchainStorage>,
+        events: Arc<dyn EventLog>,
+        sender: Arc<dyn WebhookSender>,
+        config: WebhookConfig,
+        from_seq: u64,
+    ) -> Result<Self> {
+        config.validate()?;
+        let endpoints = config
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let addresses = endpoint
+                    .addresses
+                    .iter()
+                    .map(|address| Address::from_checksum_hex(address))
+                    .collect::<blockchain_core::Result<HashSet<_>>>()?;
+                Ok(EndpointState {
+                    endpoint: endpoint.clone(),
+                    addresses,
+                    pending: Vec::new(),
+                    window_start: None,
+                    batches_sent: 0,
+                    redeliveries: VecDeque::new(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { storage, events, sender, config, endpoints, cursor: from_seq })
+    }
+
+    pub fn config(&self) -> &WebhookConfig {
+        &self.config
+    }
+
+    /// Collect activity from newly stored blocks, then deliver the batches
+    /// and redeliveries that are due at `now`
+    pub async fn dispatch_once(&mut self, now: DateTime<Utc>) -> Result<DispatchReport> {
+        self.collect(now).await?;
+
+        let mut report = DispatchReport::default();
+        for index in 0..self.endpoints.len() {
+            let fresh = self.endpoints[index].take_due(now);
+            let endpoint = &mut self.endpoints[index];
+            let mut due: Vec<Redelivery> =
+                fresh.into_iter().map(|batch| Redelivery { batch, attempts: 0, due: now }).collect();
+            while endpoint.redeliveries.front().is_some_and(|redelivery| redelivery.due <= now) {
+                due.extend(endpoint.redeliveries.pop_front());
+            }
+
+            for mut delivery in due {
+                delivery.attempts += 1;
+                match deliver(self.sender.as_ref(), &endpoint.endpoint, &delivery.batch, now).await {
+                    Ok(()) => report.delivered += 1,
+                    Err(e) if delivery.attempts >= self.config.max_attempts => {
+                        tracing::warn!(
+                            endpoint = %endpoint.endpoint.id,
+                            batch_id = %delivery.batch.batch_id,
+                            attempts = delivery.attempts,
+                            error = %e,
+                            "dropped webhook batch after its last attempt"
+                        );
+                        report.dropped += 1;
+                    }
+                    Err(e) => {
+                        tracing::debug!(endpoint = %endpoint.endpoint.id, error = %e, "webhook delivery failed");
+                        delivery.due = now + self.config.backoff(delivery.attempts);
+                        let position = endpoint.redeliveries.partition_point(|queued| queued.due <= delivery.due);
+                        endpoint.redeliveries.insert(position, delivery);
+                        report.retrying += 1;
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Route activity from blocks stored since the cursor to the endpoints
+    /// watching its addresses
+    async fn collect(&mut self, now: DateTime<Utc>) -> Result<()> {
+        let filter = EventFilter { event_types: vec![BLOCK_STORED.to_string()], subject: None };
+        let page = self.events.replay_events(self.cursor, &filter, self.config.event_page).await?;
+        for event in &page.events {
+            let hash = hex::decode(event.subject.trim_start_matches("0x"))?;
+            let hash = hash.try_into().map_err(|_| anyhow::anyhow!("Invalid block hash {}", event.subject))?;
+            // A block rolled back before it was read has no activity to report
+            let Some(block) = self.storage.get_block_by_hash(&hash).await? else {
+                continue;
+            };
+            for (address, activity) in block_activity(&block) {
+                for endpoint in self.endpoints.iter_mut().filter(|endpoint| endpoint.addresses.contains(&address)) {
+                    endpoint.window_start.get_or_insert(now);
+                    endpoint.pending.push(activity.clone());
+                }
+            }
+        }
+        self.cursor = page.next_seq;
+        Ok(())
+    }
+}
+
+async fn deliver(
+    sender: &dyn WebhookSender,
+    endpoint: &WebhookEndpoint,
+    batch: &WebhookBatch,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let body = serde_json::to_vec(batch)?;
+    let timestamp = now.timestamp();
+    let signature = sign(&endpoint.secret, timestamp, &body);
+    sender.send(&endpoint.url, timestamp, &signature, body).await
+}
+
+/// Deliver webhooks every `poll_interval_secs` of the dispatcher's config
+pub fn spawn_webhook_dispatcher(mut dispatcher: WebhookDispatcher) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let interval = std::time::Duration::from_secs(dispatcher.config.poll_interval_secs);
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match dispatcher.dispatch_once(Utc::now()).await {
+                Ok(report) if report.delivered + report.retrying + report.dropped > 0 => {
+                    tracing::info!(
+                        delivered = report.delivered,
+                        retrying = report.retrying,
+                        dropped = report.dropped,
+                        "dispatched webhooks"
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "webhook dispatch pass failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MemoryStorage;
+    use blockchain_core::Transaction;
+    use std::sync::Mutex;
+
+    /// Records deliveries, failing the first `failures` of them
+    #[derive(Default)]
+    struct RecordingSender {
+        failures: Mutex<usize>,
+        delivered: Mutex<Vec<(i64, String, Vec<u8>)>>,
+    }
+
+    #[async_trait]
+    impl WebhookSender for RecordingSender {
+        async fn send(&self, _url: &str, timestamp: i64, signature: &str, body: Vec<u8>) -> Result<()> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                bail!("connection refused");
+            }
+            self.delivered.lock().unwrap().push((timestamp, signature.to_string(), body));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batches_watched_addresses_and_redelivers() {
+        let (exchange, other) = ([0xee; 20], [0x11; 20]);
+        let config = WebhookConfig {
+            endpoints: vec![WebhookEndpoint {
+                id: "exchange".to_string(),
+                url: "https://hooks.example.com/deposits".to_string(),
+                secret: "s3cret".to_string(),
+                addresses: vec![exchange.to_checksum_hex()],
+                window_secs: 60,
+                max_batch_size: 100,
+            }],
+            base_delay_secs: 10,
+            ..Default::default()
+        };
+
+        let storage = Arc::new(MemoryStorage::default());
+        let deposit = Transaction::new_transfer(other, exchange, 500, 0, 21_000, 1).unwrap();
+        let unrelated = Transaction::new_transfer(other, [0x22; 20], 7, 1, 21_000, 1).unwrap();
+        let block = Block::new(1, [0; 32], vec![deposit.clone(), unrelated], 1).unwrap();
+        storage.store_block(&block).await.unwrap();
+        let hash = format!("0x{}", hex::encode(block.hash));
+        storage.publish_event(BLOCK_STORED, &hash, "{}").await.unwrap();
+
+        let sender = Arc::new(RecordingSender { failures: Mutex::new(1), ..Default::default() });
+        let mut dispatcher =
+            WebhookDispatcher::new(storage.clone(), storage, sender.clone(), config, 0).unwrap();
+
+        // The window stays open for a minute, then the first delivery fails
+        let start = Utc::now();
+        assert_eq!(dispatcher.dispatch_once(start).await.unwrap(), DispatchReport::default());
+        let report = dispatcher.dispatch_once(start + Duration::seconds(60)).await.unwrap();
+        assert_eq!(report.retrying, 1);
+        let report = dispatcher.dispatch_once(start + Duration::seconds(65)).await.unwrap();
+        assert_eq!(report.delivered, 0);
+        let report = dispatcher.dispatch_once(start + Duration::seconds(70)).await.unwrap();
+        assert_eq!(report.delivered, 1);
+
+        let delivered = sender.delivered.lock().unwrap();
+        let (timestamp, signature, body) = &delivered[0];
+        let at = start + Duration::seconds(70);
+        assert!(verify_signature("s3cret", *timestamp, body, signature, at, DEFAULT_SIGNATURE_TOLERANCE_SECS));
+        assert!(!verify_signature("wrong", *timestamp, body, signature, at, DEFAULT_SIGNATURE_TOLERANCE_SECS));
+        let late = at + Duration::seconds(DEFAULT_SIGNATURE_TOLERANCE_SECS + 1);
+        assert!(!verify_signature("s3cret", *timestamp, body, signature, late, DEFAULT_SIGNATURE_TOLERANCE_SECS));
+
+        // Only the deposit to the watched address is reported
+        let batch: WebhookBatch = serde_json::from_slice(body).unwrap();
+        assert_eq!(batch.batch_id, "exchange-1");
+        assert_eq!(batch.activity.len(), 1);
+        assert_eq!(batch.activity[0].direction, Direction::Incoming);
+        assert_eq!(batch.activity[0].counterparty, Some(other.to_checksum_hex()));
+        assert_eq!(batch.activity[0].amount, 500);
+        assert_eq!(batch.activity[0].tx_hash, format!("0x{}", hex::encode(deposit.hash)));
+    }
+}
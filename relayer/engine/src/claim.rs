@@ -0,0 +1,214 @@
+// relayer/engine/src/claim.rs
+//! Splitting queued batches between relayers that share a keyspace.
+//!
+//! A relayer only submits batches it holds a claim on. Claims are leases
+//! taken with a lightweight transaction, so exactly one relayer holds each
+//! `commitment_id` at a time. The holder renews its lease while it works on
+//! the batch and releases it when done; if it crashes, the lease expires and
+//! another relayer can claim the batch. A relayer that fails to renew has
+//! lost the batch and must stop before submitting it. While the relayer's
+//! circuit breaker is open nothing is claimed, apart from single probes.
+use anyhow::{bail, Result};
+use chrono::Utc;
+use gateway_core::{Admission, CircuitBreaker};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use storage_traits::{ClaimStore, RelayerBatch};
+
+use crate::webhooks::LifecycleWebhooks;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimConfig {
+    /// Recorded as the holder of every claim
+    pub relayer_id: String,
+    /// How long a claim lasts without renewal
+    pub lease: Duration,
+    /// Batches claimed per call
+    pub claim_limit: i32,
+}
+
+impl Default for ClaimConfig {
+    fn default() -> Self {
+        Self { relayer_id: "relayer-1".to_string(), lease: Duration::from_secs(60), claim_limit: 10 }
+    }
+}
+
+impl ClaimConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.relayer_id.is_empty() {
+            bail!("Relayer id cannot be empty");
+        }
+        // Leases are stored as TTLs, which count whole seconds
+        if self.lease < Duration::from_secs(3) {
+            bail!("Claim lease must be at least 3 seconds");
+        }
+        if self.claim_limit <= 0 {
+            bail!("Claim limit must be greater than 0");
+        }
+        Ok(())
+    }
+
+    /// Renewal period, leaving two renewals' slack before the lease runs out
+    pub fn renew_interval(&self) -> Duration {
+        self.lease / 3
+    }
+}
+
+pub struct BatchClaimer {
+    store: Arc<dyn ClaimStore>,
+    config: ClaimConfig,
+    webhooks: Option<Arc<LifecycleWebhooks>>,
+    breaker: Option<CircuitBreaker>,
+}
+
+impl BatchClaimer {
+    pub fn new(store: Arc<dyn ClaimStore>, config: ClaimConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self { store, config, webhooks: None, breaker: None })
+    }
+
+    /// Announce every claimed batch, now `Processing`, through `webhooks`
+    pub fn with_webhooks(mut self, webhooks: Arc<LifecycleWebhooks>) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    pub fn config(&self) -> &ClaimConfig {
+        &self.config
+    }
+
+    /// Claim nothing while `breaker` is open, and one batch when it probes
+    pub fn with_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Queued batches this relayer now holds
+    pub async fn claim(&self) -> Result<Vec<RelayerBatch>> {
+        let limit = match self.breaker.as_ref().map(|breaker| breaker.admit(Instant::now())) {
+            Some(Admission::Paused) => return Ok(Vec::new()),
+            Some(Admission::Probe) => 1,
+            Some(Admission::Proceed) | None => self.config.claim_limit,
+        };
+        let claimed = self.store.claim_batches(&self.config.relayer_id, limit, self.config.lease).await?;
+        if let Some(webhooks) = &self.webhooks {
+            let now = Utc::now();
+            for batch in &claimed {
+                webhooks.notify(batch, now)?;
+            }
+        }
+        Ok(claimed)
+    }
+
+    /// Extend the claim on `batch`, failing if this relayer lost it
+    pub async fn renew(&self, batch: &RelayerBatch) -> Result<()> {
+        let renewed = self.store.renew_claim(batch.commitment_id, &self.config.relayer_id, self.config.lease).await?;
+        if !renewed {
+            bail!("Relayer {} lost its claim on batch {}", self.config.relayer_id, batch.commitment_id);
+        }
+        Ok(())
+    }
+
+    /// Give up the claim on `batch`, e.g. after it failed, so any relayer
+    /// can take its retry
+    pub async fn release(&self, batch: &RelayerBatch) -> Result<()> {
+        if !self.store.release_claim(batch.commitment_id, &self.config.relayer_id).await? {
+            tracing::warn!(
+                commitment_id = %batch.commitment_id,
+                lineage = %batch.batch_lineage_id,
+                "released a claim this relayer no longer held"
+            );
+        }
+        Ok(())
+    }
+
+    /// Run `work` on `batch`, renewing the claim every `renew_interval`.
+    /// If a renewal fails, `work` is dropped unfinished and the error is
+    /// returned, so a relayer never keeps submitting a batch it lost.
+    pub async fn hold<T>(&self, batch: &RelayerBatch, work: impl Future<Output = Result<T>>) -> Result<T> {
+        tokio::pin!(work);
+        let mut renewals = tokio::time::interval(self.config.renew_interval());
+        // The first tick completes immediately; the claim was just taken
+        renewals.tick().await;
+        loop {
+            tokio::select! {
+                outcome = &mut work => return outcome,
+                _ = renewals.tick() => self.renew(batch).await?,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    /// Queue whose claims behave like `IF NOT EXISTS` inserts, without expiry
+    #[derive(Default)]
+    struct MemoryClaims {
+        queue: Vec<RelayerBatch>,
+        holders: Mutex<HashMap<Uuid, String>>,
+    }
+
+    #[async_trait]
+    impl ClaimStore for MemoryClaims {
+        async fn claim_batches(&self, relayer_id: &str, limit: i32, _lease: Duration) -> Result<Vec<RelayerBatch>> {
+            let mut holders = self.holders.lock();
+            let mut claimed = Vec::new();
+            for batch in self.queue.iter().take(limit as usize) {
+                if !holders.contains_key(&batch.commitment_id) {
+                    holders.insert(batch.commitment_id, relayer_id.to_string());
+                    claimed.push(batch.clone());
+                }
+            }
+            Ok(claimed)
+        }
+
+        async fn renew_claim(&self, commitment_id: Uuid, relayer_id: &str, _lease: Duration) -> Result<bool> {
+            Ok(self.holders.lock().get(&commitment_id).is_some_and(|holder| holder == relayer_id))
+        }
+
+        async fn release_claim(&self, commitment_id: Uuid, relayer_id: &str) -> Result<bool> {
+            let mut holders = self.holders.lock();
+            if holders.get(&commitment_id).is_some_and(|holder| holder == relayer_id) {
+                holders.remove(&commitment_id);
+                return Ok(true);
+            }
+            Ok(false)
+        }
+    }
+
+    fn claimer(store: &Arc<MemoryClaims>, relayer_id: &str) -> BatchClaimer {
+        let config = ClaimConfig { relayer_id: relayer_id.to_string(), claim_limit: 3, ..Default::default() };
+        BatchClaimer::new(store.clone(), config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_each_batch_has_one_holder() {
+        let queue = (0..3).map(|i| RelayerBatch::new(vec![[i; 32]], String::new())).collect();
+        let store = Arc::new(MemoryClaims { queue, ..Default::default() });
+        let (a, b) = (claimer(&store, "relayer-a"), claimer(&store, "relayer-b"));
+
+        let held_by_a = a.claim().await.unwrap();
+        assert_eq!(held_by_a.len(), 3);
+        assert!(b.claim().await.unwrap().is_empty());
+        assert!(b.renew(&held_by_a[0]).await.is_err());
+
+        let outcome = a.hold(&held_by_a[0], async { Ok(7) }).await.unwrap();
+        assert_eq!(outcome, 7);
+
+        // Once released, the batch's retry can go to any relayer
+        a.release(&held_by_a[0]).await.unwrap();
+        let held_by_b = b.claim().await.unwrap();
+        assert_eq!(held_by_b.len(), 1);
+        assert_eq!(held_by_b[0].commitment_id, held_by_a[0].commitment_id);
+        assert!(a.renew(&held_by_a[0]).await.is_err());
+
+        assert!(ClaimConfig { lease: Duration::from_secs(1), ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,349 @@
+// relayer/engine/src/engine.rs
+//! Turning pending transactions into queued relayer batches.
+//!
+//! Each pass pulls a window of pending transactions a page at a time, so
+//! the pool is never read whole, and orders them by fee,
+//! highest first, while keeping every sender's transactions in nonce order,
+//! and cuts the result into batches of at most `max_batch_size`. A trailing
+//! batch smaller than `min_batch_size` waits for more transactions unless
+//! its oldest one has waited `max_batch_delay`.
+//!
+//! A batch is written to the queue, with its commitment, before its
+//! transactions leave the pending table. If the node stops in between, the
+//! next pass finds them in a queued batch and only removes them.
+use anyhow::{bail, Result};
+use blockchain_core::{Address, KeyPair, Transaction, TxHash};
+use chrono::{DateTime, Utc};
+use gateway_core::{build_commitment, codec_for};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use storage_traits::{PayloadEncoding, RelayerBatch, RelayerStore};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineConfig {
+    /// Recorded on every batch this engine creates
+    pub relayer_id: String,
+    pub max_batch_size: usize,
+    pub min_batch_size: usize,
+    /// How long a transaction may wait for a batch to fill up
+    pub max_batch_delay: Duration,
+    /// Pending transactions considered per pass
+    pub pull_limit: i32,
+    /// Pending transactions read per storage page
+    pub page_size: i32,
+    pub payload_encoding: PayloadEncoding,
+    pub interval: Duration,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            relayer_id: "relayer-1".to_string(),
+            max_batch_size: 100,
+            min_batch_size: 10,
+            max_batch_delay: Duration::from_secs(30),
+            pull_limit: 1_000,
+            page_size: 200,
+            payload_encoding: PayloadEncoding::default(),
+            interval: Duration::from_secs(5),
+        }
+    }
+}
+
+impl EngineConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.relayer_id.is_empty() {
+            bail!("Relayer id cannot be empty");
+        }
+        if self.min_batch_size == 0 {
+            bail!("Minimum batch size must be greater than 0");
+        }
+        if self.min_batch_size > self.max_batch_size {
+            bail!("Minimum batch size must not exceed the maximum batch size");
+        }
+        if self.pull_limit <= 0 || (self.pull_limit as usize) < self.max_batch_size {
+            bail!("Pull limit must be at least the maximum batch size");
+        }
+        if self.page_size <= 0 {
+            bail!("Page size must be greater than 0");
+        }
+        if self.interval.is_zero() {
+            bail!("Engine interval must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+pub struct RelayerEngine {
+    store: Arc<dyn RelayerStore>,
+    key: KeyPair,
+    config: EngineConfig,
+}
+
+impl RelayerEngine {
+    /// Engine signing its commitments with `key`
+    pub fn new(store: Arc<dyn RelayerStore>, key: KeyPair, config: EngineConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self { store, key, config })
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    /// Run one pass, returning the batches it queued
+    pub async fn drain_once(&self, now: DateTime<Utc>) -> Result<Vec<RelayerBatch>> {
+        let queued: HashSet<TxHash> = self
+            .store
+            .queued_batches(self.config.pull_limit)
+            .await?
+            .iter()
+            .flat_map(|batch| batch.tx_hashes.iter().copied())
+            .collect();
+
+        let mut pending = Vec::new();
+        let mut pulled = 0;
+        let mut paging_state = None;
+        loop {
+            let page_size = self.config.page_size.min(self.config.pull_limit - pulled);
+            let page = self.store.pending_page(page_size, paging_state).await?;
+            for tx in page.transactions {
+                pulled += 1;
+                if queued.contains(&tx.hash) {
+                    // Left behind by a pass that stopped after queueing its batch
+                    self.store.remove_pending_transaction(&tx.hash).await?;
+                } else {
+                    pending.push(tx);
+                }
+            }
+            paging_state = page.paging_state;
+            if paging_state.is_none() || pulled >= self.config.pull_limit {
+                break;
+            }
+        }
+
+        let ordered = order_by_priority(pending);
+        let max_delay = chrono::Duration::from_std(self.config.max_batch_delay)?;
+        let mut batches = Vec::new();
+        for chunk in ordered.chunks(self.config.max_batch_size) {
+            let overdue = chunk.iter().any(|tx| now - tx.timestamp >= max_delay);
+            if chunk.len() < self.config.min_batch_size && !overdue {
+                break;
+            }
+            batches.push(self.queue_batch(chunk).await?);
+        }
+        Ok(batches)
+    }
+
+    async fn queue_batch(&self, transactions: &[Transaction]) -> Result<RelayerBatch> {
+        let mut batch = RelayerBatch::new(
+            transactions.iter().map(|tx| tx.hash).collect(),
+            self.config.relayer_id.clone(),
+        );
+        // Carry on the lineage validation started, so both stages trace as one
+        for tx in transactions {
+            if let Some(lineage) = self.store.validation_lineage(&tx.hash).await? {
+                batch = batch.with_lineage(lineage);
+                break;
+            }
+        }
+        let codec = codec_for(self.config.payload_encoding);
+        batch.commitment_data = Some(build_commitment(&batch, transactions, codec.as_ref(), &self.key)?);
+
+        self.store.enqueue_batch(&batch).await?;
+        for tx in transactions {
+            self.store.remove_pending_transaction(&tx.hash).await?;
+        }
+        tracing::debug!(
+            commitment_id = %batch.commitment_id,
+            lineage = %batch.batch_lineage_id,
+            transactions = transactions.len(),
+            "queued relayer batch"
+        );
+        Ok(batch)
+    }
+}
+
+/// Highest fee first, each sender's transactions in nonce order: the next
+/// transaction is always the best among the lowest-nonce transaction of
+/// every sender. Ties go to the older transaction.
+fn order_by_priority(transactions: Vec<Transaction>) -> Vec<Transaction> {
+    let total = transactions.len();
+    let mut by_sender: HashMap<Address, VecDeque<Transaction>> = HashMap::new();
+    for tx in transactions {
+        by_sender.entry(tx.sender()).or_default().push_back(tx);
+    }
+    for queue in by_sender.values_mut() {
+        queue.make_contiguous().sort_by_key(|tx| tx.nonce);
+    }
+
+    let mut ordered = Vec::with_capacity(total);
+    loop {
+        let next = by_sender
+            .iter()
+            .filter_map(|(sender, queue)| queue.front().map(|tx| (*sender, tx)))
+            .max_by(|(_, a), (_, b)| compare_priority(a, b))
+            .map(|(sender, _)| sender);
+        let Some(tx) = next.and_then(|sender| by_sender.get_mut(&sender)).and_then(VecDeque::pop_front) else {
+            break;
+        };
+        ordered.push(tx);
+    }
+    ordered
+}
+
+fn compare_priority(a: &Transaction, b: &Transaction) -> Ordering {
+    a.total_fee()
+        .cmp(&b.total_fee())
+        .then_with(|| b.timestamp.cmp(&a.timestamp))
+        .then_with(|| b.hash.cmp(&a.hash))
+}
+
+/// Drain the pending queue every `interval` of the engine's config
+pub fn spawn_relayer_engine(engine: Arc<RelayerEngine>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(engine.config.interval);
+        loop {
+            ticker.tick().await;
+            match engine.drain_once(Utc::now()).await {
+                Ok(batches) if !batches.is_empty() => {
+                    let transactions: usize = batches.iter().map(|batch| batch.tx_hashes.len()).sum();
+                    tracing::info!(batches = batches.len(), transactions, "queued relayer batches");
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "relayer engine pass failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use blockchain_core::signature::SignatureScheme;
+    use blockchain_core::AddressExt;
+    use gateway_core::verify_commitment;
+    use parking_lot::Mutex;
+    use storage_traits::PendingTxPage;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        pending: Mutex<Vec<Transaction>>,
+        queued: Mutex<Vec<RelayerBatch>>,
+        lineages: Mutex<HashMap<TxHash, Uuid>>,
+    }
+
+    #[async_trait]
+    impl RelayerStore for MemoryStore {
+        /// The paging state is the index of the page's first transaction
+        async fn pending_page(&self, page_size: i32, paging_state: Option<Vec<u8>>) -> Result<PendingTxPage> {
+            let pending = self.pending.lock();
+            let start = paging_state.map_or(0, |state| u64::from_be_bytes(state.try_into().unwrap()) as usize);
+            let end = (start + page_size as usize).min(pending.len());
+            Ok(PendingTxPage {
+                transactions: pending[start.min(end)..end].to_vec(),
+                paging_state: (end < pending.len()).then(|| (end as u64).to_be_bytes().to_vec()),
+            })
+        }
+
+        async fn queued_batches(&self, limit: i32) -> Result<Vec<RelayerBatch>> {
+            Ok(self.queued.lock().iter().take(limit as usize).cloned().collect())
+        }
+
+        async fn enqueue_batch(&self, batch: &RelayerBatch) -> Result<()> {
+            self.queued.lock().push(batch.clone());
+            Ok(())
+        }
+
+        async fn remove_pending_transaction(&self, tx_hash: &TxHash) -> Result<()> {
+            self.pending.lock().retain(|tx| tx.hash != *tx_hash);
+            Ok(())
+        }
+
+        async fn validation_lineage(&self, tx_hash: &TxHash) -> Result<Option<Uuid>> {
+            Ok(self.lineages.lock().get(tx_hash).copied())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drains_pending_into_committed_batches() {
+        let alice = [1; 20];
+        let bob = [2; 20];
+        let a: Vec<Transaction> = [1, 1, 9]
+            .iter()
+            .enumerate()
+            .map(|(nonce, &gas_price)| Transaction::new_transfer(alice, [9; 20], 10, nonce as u64, 21_000, gas_price))
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        let b: Vec<Transaction> = (0..2)
+            .map(|nonce| Transaction::new_transfer(bob, [9; 20], 10, nonce, 21_000, 5).unwrap())
+            .collect();
+
+        let store = Arc::new(MemoryStore::default());
+        // Out of nonce order, as storage returns them
+        store.pending.lock().extend([a[2].clone(), b[1].clone(), a[0].clone(), b[0].clone(), a[1].clone()]);
+        let validated = Uuid::new_v4();
+        store.lineages.lock().insert(b[1].hash, validated);
+        let key = KeyPair::generate(SignatureScheme::Secp256k1);
+        let relayer = Address::from_public_key(&key.public_key()).unwrap();
+        let config = EngineConfig { max_batch_size: 2, min_batch_size: 2, ..Default::default() };
+        let engine = RelayerEngine::new(store.clone(), key, config).unwrap();
+
+        // Alice's high-fee nonce 2 waits for her earlier nonces
+        let now = Utc::now();
+        let batches = engine.drain_once(now).await.unwrap();
+        let hashes: Vec<Vec<TxHash>> = batches.iter().map(|batch| batch.tx_hashes.clone()).collect();
+        assert_eq!(hashes, vec![vec![b[0].hash, b[1].hash], vec![a[0].hash, a[1].hash]]);
+        // Bob's batch continues the lineage its validation started; Alice's starts one
+        assert_eq!(batches[0].batch_lineage_id, validated);
+        assert_ne!(batches[1].batch_lineage_id, validated);
+        for (batch, txs) in batches.iter().zip([&b[..], &a[..2]]) {
+            let commitment = batch.commitment_data.as_ref().unwrap();
+            let report = verify_commitment(&batch.commitment_id, commitment, txs, &relayer).unwrap();
+            assert!(report.valid, "{:?}", report.errors);
+        }
+
+        // The short trailing batch waits, but a transaction already queued is only removed
+        let pending: Vec<TxHash> = store.pending.lock().iter().map(|tx| tx.hash).collect();
+        assert_eq!(pending, vec![a[2].hash]);
+        store.pending.lock().push(b[0].clone());
+        assert!(engine.drain_once(now).await.unwrap().is_empty());
+        assert_eq!(store.pending.lock().len(), 1);
+
+        let later = now + chrono::Duration::seconds(31);
+        let batches = engine.drain_once(later).await.unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].tx_hashes, vec![a[2].hash]);
+        assert!(store.pending.lock().is_empty());
+        assert_eq!(store.queued.lock().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pulls_pages_up_to_the_limit() {
+        let store = Arc::new(MemoryStore::default());
+        let pending: Vec<Transaction> = (0..7u8)
+            .map(|sender| Transaction::new_transfer([sender + 1; 20], [9; 20], 10, 0, 21_000, 5).unwrap())
+            .collect();
+        store.pending.lock().extend(pending.iter().cloned());
+        let key = KeyPair::generate(SignatureScheme::Secp256k1);
+        let config = EngineConfig {
+            max_batch_size: 2,
+            min_batch_size: 1,
+            pull_limit: 5,
+            page_size: 2,
+            ..Default::default()
+        };
+        let engine = RelayerEngine::new(store.clone(), key, config).unwrap();
+
+        // Three pages, the last cut to one transaction, fill the window of five
+        let batches = engine.drain_once(Utc::now()).await.unwrap();
+        let queued: HashSet<TxHash> = batches.iter().flat_map(|batch| batch.tx_hashes.iter().copied()).collect();
+        assert_eq!(queued, pending[..5].iter().map(|tx| tx.hash).collect());
+        assert_eq!(store.pending.lock().len(), 2);
+        assert!(EngineConfig { page_size: 0, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,23 @@
+// relayer/engine/src/lib.rs
+//! Relayer engine: periodically drains the pending transaction queue into
+//! `RelayerBatch` records, each carrying a signed `CommitmentData`, and
+//! queues them in `relayer_queue` for submission, and puts failed batches
+//! back in the queue until they run out of retries, along with batches a
+//! crashed relayer left processing. Relayers sharing a keyspace split the
+//! queue through leased per-batch claims, and batch status changes are
+//! announced to operators through signed webhooks.
+pub mod claim;
+pub mod engine;
+pub mod recovery;
+pub mod retry;
+pub mod webhooks;
+
+pub use claim::{BatchClaimer, ClaimConfig};
+pub use engine::{spawn_relayer_engine, EngineConfig, RelayerEngine};
+pub use recovery::{spawn_batch_recovery, BatchRecovery, RecoveryConfig, StuckBatchStore, StuckRelayerBatches};
+pub use retry::{spawn_retry_worker, RetryConfig, RetryReport, RetryWorker};
+pub use storage_traits::{ClaimStore, RelayerStore, RetryStore};
+pub use webhooks::{
+    spawn_lifecycle_webhooks, HttpSender, LifecycleEndpoint, LifecycleEvent, LifecycleWebhookConfig, LifecycleWebhooks,
+    WebhookSender,
+};
@@ -0,0 +1,105 @@
+// relayer/engine/src/recovery.rs
+//! Returning batches stranded by a crashed relayer to the queue.
+//!
+//! A relayer that dies while a batch is `Processing` stops renewing its
+//! claim, but the batch stays `Processing`, and relayers only claim queued
+//! batches. Recovery puts back batches whose last attempt is older than
+//! `stuck_after`, once no relayer holds a claim on them, and counts the
+//! recovery in the batch's `recovery_count`. The retry count is kept, since
+//! the batch never got a verdict. `stuck_after` should exceed the claim
+//! lease, so a live relayer has renewed its claim long before.
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use storage_traits::{RelayerBatch, RelayerRecoveryStore};
+
+pub use batch_recovery::{spawn_batch_recovery, BatchRecovery, RecoveryConfig, StuckBatchStore};
+
+/// `relayer_queue` as stuck-batch recovery sees it
+pub struct StuckRelayerBatches(pub Arc<dyn RelayerRecoveryStore>);
+
+#[async_trait]
+impl StuckBatchStore for StuckRelayerBatches {
+    type Batch = RelayerBatch;
+    const STAGE: &'static str = "relayer";
+
+    /// Batches still `Processing` since an attempt before `stuck_since`
+    async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<RelayerBatch>> {
+        self.0.stuck_batches(stuck_since, limit).await
+    }
+
+    /// Left alone while a relayer holds a claim on it or once it left `Processing`
+    async fn recover_batch(&self, mut batch: RelayerBatch) -> Result<Option<RelayerBatch>> {
+        batch.recover();
+        Ok(self.0.recover_batch(&batch).await?.then_some(batch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use storage_traits::RelayerStatus;
+    use std::collections::HashSet;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        queue: Mutex<Vec<RelayerBatch>>,
+        /// Batches whose relayer still renews its claim
+        claimed: Mutex<HashSet<Uuid>>,
+    }
+
+    #[async_trait]
+    impl StuckBatchStore for MemoryStore {
+        type Batch = RelayerBatch;
+        const STAGE: &'static str = "relayer";
+
+        async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<RelayerBatch>> {
+            let queue = self.queue.lock();
+            Ok(queue
+                .iter()
+                .filter(|batch| batch.status == RelayerStatus::Processing)
+                .filter(|batch| batch.last_attempt.is_some_and(|at| at < stuck_since))
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        async fn recover_batch(&self, mut batch: RelayerBatch) -> Result<Option<RelayerBatch>> {
+            if self.claimed.lock().contains(&batch.commitment_id) {
+                return Ok(None);
+            }
+            batch.recover();
+            for stored in self.queue.lock().iter_mut().filter(|b| b.commitment_id == batch.commitment_id) {
+                *stored = batch.clone();
+            }
+            Ok(Some(batch))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requeues_only_abandoned_batches() {
+        let now = Utc::now();
+        let claimed_at = |minutes_ago| {
+            let mut batch = RelayerBatch::new(vec![[minutes_ago as u8; 32]], "relayer-1".to_string());
+            batch.mark_failed();
+            batch.claim("relayer-2", now - chrono::Duration::minutes(minutes_ago));
+            batch
+        };
+        let (abandoned, renewing, recent) = (claimed_at(30), claimed_at(30), claimed_at(1));
+        let store = Arc::new(MemoryStore::default());
+        store.queue.lock().extend([abandoned.clone(), renewing.clone(), recent]);
+        store.claimed.lock().insert(renewing.commitment_id);
+        let recovery = BatchRecovery::new(store.clone(), RecoveryConfig::default()).unwrap();
+
+        let recovered = recovery.recover_once(now).await.unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].commitment_id, abandoned.commitment_id);
+        assert_eq!(recovered[0].status, RelayerStatus::Queued);
+        assert_eq!((recovered[0].retry_count, recovered[0].recovery_count), (1, 1));
+        assert_eq!(store.queue.lock()[0].status, RelayerStatus::Queued);
+        assert!(recovery.recover_once(now).await.unwrap().is_empty());
+    }
+}
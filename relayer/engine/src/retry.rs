@@ -0,0 +1,240 @@
+// relayer/engine/src/retry.rs
+//! Putting failed relayer batches back in the queue.
+//!
+//! Each pass requeues failed batches whose backoff has elapsed, keeping
+//! their retry count so a relayer claims them again like any queued batch.
+//! The wait after the n-th failure is `base_delay * 2^(n-1)`, capped at
+//! `max_delay`, counted from the batch's last attempt. Batches that failed
+//! `max_retries` times are moved to `relayer_dead_letters` instead.
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use retry::Backoff;
+use std::sync::Arc;
+use std::time::Duration;
+use storage_traits::{RelayerBatch, RetryStore};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// Failed attempts before a batch is dead-lettered
+    pub max_retries: u32,
+    /// Wait after the first failure
+    pub base_delay: Duration,
+    /// Longest wait between attempts
+    pub max_delay: Duration,
+    /// Failed batches considered per pass
+    pub scan_limit: i32,
+    pub interval: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(600),
+            scan_limit: 100,
+            interval: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_retries == 0 {
+            bail!("Max retries must be greater than 0");
+        }
+        if self.base_delay.is_zero() {
+            bail!("Base retry delay must be greater than 0");
+        }
+        if self.base_delay > self.max_delay {
+            bail!("Base retry delay must not exceed the maximum retry delay");
+        }
+        if self.scan_limit <= 0 {
+            bail!("Scan limit must be greater than 0");
+        }
+        if self.interval.is_zero() {
+            bail!("Retry interval must be greater than 0");
+        }
+        Ok(())
+    }
+
+    /// Wait before retrying a batch that has failed `failures` times
+    pub fn backoff(&self, failures: u32) -> Duration {
+        Backoff::Exponential { base: self.base_delay, max: self.max_delay }.delay(failures)
+    }
+}
+
+/// Batches handled by one pass
+#[derive(Debug, Clone, Default)]
+pub struct RetryReport {
+    pub requeued: Vec<RelayerBatch>,
+    pub dead_lettered: Vec<RelayerBatch>,
+    /// Failed batches still in backoff
+    pub waiting: usize,
+}
+
+pub struct RetryWorker {
+    store: Arc<dyn RetryStore>,
+    config: RetryConfig,
+}
+
+impl RetryWorker {
+    pub fn new(store: Arc<dyn RetryStore>, config: RetryConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self { store, config })
+    }
+
+    pub fn config(&self) -> &RetryConfig {
+        &self.config
+    }
+
+    /// Whether `batch` has waited out its backoff at `now`
+    pub fn is_due(&self, batch: &RelayerBatch, now: DateTime<Utc>) -> Result<bool> {
+        let Some(last_attempt) = batch.last_attempt else {
+            return Ok(true);
+        };
+        let backoff = chrono::Duration::from_std(self.config.backoff(batch.retry_count))?;
+        Ok(now - last_attempt >= backoff)
+    }
+
+    /// Run one pass: dead-letter exhausted batches, then requeue the due ones
+    pub async fn retry_once(&self, now: DateTime<Utc>) -> Result<RetryReport> {
+        let mut report = RetryReport::default();
+        let max_retries = self.config.max_retries;
+
+        for batch in self.store.exhausted_batches(max_retries, self.config.scan_limit).await? {
+            self.store.dead_letter(&batch, now).await?;
+            report.dead_lettered.push(batch);
+        }
+
+        for mut batch in self.store.retryable_batches(max_retries, self.config.scan_limit).await? {
+            if !batch.can_retry(max_retries) {
+                continue;
+            }
+            if !self.is_due(&batch, now)? {
+                report.waiting += 1;
+                continue;
+            }
+            batch.requeue();
+            self.store.update_batch(&batch).await?;
+            report.requeued.push(batch);
+        }
+        Ok(report)
+    }
+}
+
+/// Retry failed batches every `interval` of the worker's config
+pub fn spawn_retry_worker(worker: Arc<RetryWorker>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(worker.config.interval);
+        loop {
+            ticker.tick().await;
+            match worker.retry_once(Utc::now()).await {
+                Ok(report) if !report.requeued.is_empty() || !report.dead_lettered.is_empty() => {
+                    tracing::info!(
+                        requeued = report.requeued.len(),
+                        dead_lettered = report.dead_lettered.len(),
+                        waiting = report.waiting,
+                        "retried failed relayer batches"
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "relayer retry pass failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+    use storage_traits::RelayerStatus;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        queue: Mutex<Vec<RelayerBatch>>,
+        dead_letters: Mutex<Vec<RelayerBatch>>,
+    }
+
+    impl MemoryStore {
+        fn failed(&self, pred: impl Fn(&RelayerBatch) -> bool, limit: i32) -> Vec<RelayerBatch> {
+            let queue = self.queue.lock();
+            queue
+                .iter()
+                .filter(|batch| batch.status == RelayerStatus::Failed && pred(batch))
+                .take(limit as usize)
+                .cloned()
+                .collect()
+        }
+    }
+
+    #[async_trait]
+    impl RetryStore for MemoryStore {
+        async fn retryable_batches(&self, max_retries: u32, limit: i32) -> Result<Vec<RelayerBatch>> {
+            Ok(self.failed(|batch| batch.retry_count < max_retries, limit))
+        }
+
+        async fn exhausted_batches(&self, max_retries: u32, limit: i32) -> Result<Vec<RelayerBatch>> {
+            Ok(self.failed(|batch| batch.retry_count >= max_retries, limit))
+        }
+
+        async fn update_batch(&self, batch: &RelayerBatch) -> Result<()> {
+            for stored in self.queue.lock().iter_mut().filter(|b| b.commitment_id == batch.commitment_id) {
+                *stored = batch.clone();
+            }
+            Ok(())
+        }
+
+        async fn dead_letter(&self, batch: &RelayerBatch, _at: DateTime<Utc>) -> Result<()> {
+            self.queue.lock().retain(|b| b.commitment_id != batch.commitment_id);
+            self.dead_letters.lock().push(batch.clone());
+            Ok(())
+        }
+    }
+
+    fn failed_batch(failures: u32, last_attempt: DateTime<Utc>) -> RelayerBatch {
+        let mut batch = RelayerBatch::new(vec![[failures as u8; 32]], "relayer-1".to_string());
+        for _ in 0..failures {
+            batch.mark_failed();
+        }
+        batch.last_attempt = Some(last_attempt);
+        batch
+    }
+
+    #[tokio::test]
+    async fn test_requeues_after_backoff_and_dead_letters_exhausted() {
+        let config = RetryConfig {
+            base_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(25),
+            ..Default::default()
+        };
+        let backoffs: Vec<u64> = (1..=4).map(|failures| config.backoff(failures).as_secs()).collect();
+        assert_eq!(backoffs, vec![10, 20, 25, 25]);
+
+        let now = Utc::now();
+        let seconds = chrono::Duration::seconds;
+        let store = Arc::new(MemoryStore::default());
+        let once = failed_batch(1, now - seconds(11));
+        let twice = failed_batch(2, now - seconds(11));
+        let exhausted = failed_batch(3, now - seconds(60));
+        store.queue.lock().extend([once.clone(), twice.clone(), exhausted.clone()]);
+        let worker = RetryWorker::new(store.clone(), config).unwrap();
+
+        // The second failure waits 20s, so only the first batch is due
+        let report = worker.retry_once(now).await.unwrap();
+        let requeued: Vec<_> = report.requeued.iter().map(|batch| batch.commitment_id).collect();
+        assert_eq!(requeued, vec![once.commitment_id]);
+        assert_eq!(report.requeued[0].retry_count, 1);
+        assert_eq!(report.waiting, 1);
+        assert_eq!(report.dead_lettered[0].commitment_id, exhausted.commitment_id);
+        assert_eq!(store.dead_letters.lock().len(), 1);
+        assert_eq!(store.queue.lock().len(), 2);
+        assert_eq!(store.queue.lock()[0].status, RelayerStatus::Queued);
+
+        let report = worker.retry_once(now + seconds(10)).await.unwrap();
+        assert_eq!(report.requeued[0].commitment_id, twice.commitment_id);
+        assert!(report.dead_lettered.is_empty());
+    }
+}
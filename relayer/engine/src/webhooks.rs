@@ -0,0 +1,389 @@
+// relayer/engine/src/webhooks.rs
+//! Webhooks fired when a relayer batch changes status.
+//!
+//! Whoever moves a batch to `Processing`, `Committed`, `Failed` or
+//! `Cancelled` hands it to `LifecycleWebhooks::notify` once the change is
+//! persisted; `BatchClaimer` does so for the batches it claims. Each endpoint
+//! subscribed to the new status gets one delivery, sent by the next
+//! `deliver_once` pass, so a slow receiver never holds up relaying.
+//!
+//! Bodies are signed with HMAC-SHA256 over `"{timestamp}.{body}"` using the
+//! endpoint's secret; receivers check them with `verify_signature`. A failed
+//! delivery is retried with exponential backoff until `max_attempts`, then
+//! dropped with a warning. Delivery is at least once and not ordered across
+//! retries, so receivers dedupe on `event_id` and order on `at`.
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use retry::Backoff;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use storage_traits::{RelayerBatch, RelayerStatus, TargetInclusion};
+use uuid::Uuid;
+
+pub use webhook_signing::{sign, verify_signature};
+
+/// Header carrying `sha256=<hex HMAC>` of the signed payload
+pub const SIGNATURE_HEADER: &str = "X-Relayer-Signature";
+
+/// Header carrying the Unix time the signature was made at
+pub const TIMESTAMP_HEADER: &str = "X-Relayer-Timestamp";
+
+/// Statuses that fire webhooks; `Queued` is the engine's business only
+pub const LIFECYCLE_STATUSES: [RelayerStatus; 4] =
+    [RelayerStatus::Processing, RelayerStatus::Committed, RelayerStatus::Failed, RelayerStatus::Cancelled];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LifecycleEndpoint {
+    /// Stable name, used in logs
+    pub id: String,
+    pub url: String,
+    /// HMAC key shared with the receiver
+    pub secret: String,
+    /// Statuses the endpoint is told about; empty for all of them
+    pub statuses: Vec<RelayerStatus>,
+}
+
+impl LifecycleEndpoint {
+    fn wants(&self, status: &RelayerStatus) -> bool {
+        self.statuses.is_empty() || self.statuses.contains(status)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleWebhookConfig {
+    pub endpoints: Vec<LifecycleEndpoint>,
+    /// Deliveries of one event before it is dropped, counting the first
+    pub max_attempts: u32,
+    /// Wait after the first failed delivery; doubles with each failure
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Undelivered events kept; the oldest is dropped past this
+    pub max_queued: usize,
+    pub interval: Duration,
+}
+
+impl Default for LifecycleWebhookConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            max_attempts: 8,
+            base_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(600),
+            max_queued: 10_000,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl LifecycleWebhookConfig {
+    pub fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        for endpoint in &self.endpoints {
+            if !ids.insert(&endpoint.id) {
+                bail!("Duplicate webhook endpoint {}", endpoint.id);
+            }
+            if !endpoint.url.starts_with("http://") && !endpoint.url.starts_with("https://") {
+                bail!("Webhook endpoint {} has an invalid URL: {}", endpoint.id, endpoint.url);
+            }
+            if endpoint.secret.is_empty() {
+                bail!("Webhook endpoint {} needs a secret", endpoint.id);
+            }
+            if let Some(status) = endpoint.statuses.iter().find(|status| !LIFECYCLE_STATUSES.contains(status)) {
+                bail!("Webhook endpoint {} subscribes to {}, which fires no webhooks", endpoint.id, status);
+            }
+        }
+        if self.max_attempts == 0 {
+            bail!("Max delivery attempts must be greater than 0");
+        }
+        if self.base_delay.is_zero() || self.base_delay > self.max_delay {
+            bail!("Base delivery delay must be greater than 0 and at most the maximum delay");
+        }
+        if self.max_queued == 0 {
+            bail!("Webhook queue size must be greater than 0");
+        }
+        if self.interval.is_zero() {
+            bail!("Webhook interval must be greater than 0");
+        }
+        Ok(())
+    }
+
+    /// Wait before redelivering an event whose delivery failed `failures` times
+    pub fn backoff(&self, failures: u32) -> Duration {
+        Backoff::Exponential { base: self.base_delay, max: self.max_delay }.delay(failures)
+    }
+}
+
+/// Body of one delivery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    /// Same across redeliveries and across endpoints
+    pub event_id: String,
+    pub commitment_id: Uuid,
+    /// Shared with the validation batch the transactions came through
+    pub batch_lineage_id: Uuid,
+    pub status: String,
+    pub relayer_id: String,
+    pub retry_count: u32,
+    pub transaction_count: usize,
+    /// `0x`-prefixed hex commitment root, once the batch has a commitment
+    pub merkle_root: Option<String>,
+    /// Where the commitment landed on the target, once committed
+    pub target_inclusion: Option<TargetInclusion>,
+    pub at: DateTime<Utc>,
+}
+
+impl LifecycleEvent {
+    pub fn new(batch: &RelayerBatch, at: DateTime<Utc>) -> Self {
+        Self {
+            // A batch reaches each status at most once per attempt
+            event_id: format!("{}-{}-{}", batch.commitment_id, batch.status, batch.retry_count),
+            commitment_id: batch.commitment_id,
+            batch_lineage_id: batch.batch_lineage_id,
+            status: batch.status.to_string(),
+            relayer_id: batch.relayer_id.clone(),
+            retry_count: batch.retry_count,
+            transaction_count: batch.tx_hashes.len(),
+            merkle_root: batch.commitment_data.as_ref().map(|data| format!("0x{}", hex::encode(data.merkle_root))),
+            target_inclusion: batch.target_inclusion.clone(),
+            at,
+        }
+    }
+}
+
+/// Sends one signed body to an endpoint
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    async fn send(&self, url: &str, timestamp: i64, signature: &str, body: Vec<u8>) -> Result<()>;
+}
+
+pub struct HttpSender {
+    client: reqwest::Client,
+}
+
+impl HttpSender {
+    pub fn new(timeout: Duration) -> Result<Self> {
+        Ok(Self { client: reqwest::Client::builder().timeout(timeout).build()? })
+    }
+}
+
+#[async_trait]
+impl WebhookSender for HttpSender {
+    async fn send(&self, url: &str, timestamp: i64, signature: &str, body: Vec<u8>) -> Result<()> {
+        self.client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Outcome of one delivery pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeliveryReport {
+    pub delivered: usize,
+    /// Failed deliveries scheduled for another attempt
+    pub retrying: usize,
+    /// Events that failed their last attempt
+    pub dropped: usize,
+}
+
+struct Delivery {
+    endpoint: usize,
+    event_id: String,
+    body: Vec<u8>,
+    attempts: u32,
+    due: DateTime<Utc>,
+}
+
+pub struct LifecycleWebhooks {
+    sender: Arc<dyn WebhookSender>,
+    config: LifecycleWebhookConfig,
+    /// Ordered by `due`
+    queue: Mutex<Vec<Delivery>>,
+}
+
+impl LifecycleWebhooks {
+    pub fn new(sender: Arc<dyn WebhookSender>, config: LifecycleWebhookConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self { sender, config, queue: Mutex::new(Vec::new()) })
+    }
+
+    pub fn config(&self) -> &LifecycleWebhookConfig {
+        &self.config
+    }
+
+    /// Queue `batch`'s current status for the endpoints subscribed to it
+    pub fn notify(&self, batch: &RelayerBatch, at: DateTime<Utc>) -> Result<()> {
+        if !LIFECYCLE_STATUSES.contains(&batch.status) {
+            return Ok(());
+        }
+        let event = LifecycleEvent::new(batch, at);
+        let body = serde_json::to_vec(&event)?;
+        let mut queue = self.queue.lock();
+        for (index, endpoint) in self.config.endpoints.iter().enumerate() {
+            if endpoint.wants(&batch.status) {
+                let event_id = event.event_id.clone();
+                schedule(&mut queue, Delivery { endpoint: index, event_id, body: body.clone(), attempts: 0, due: at });
+            }
+        }
+        while queue.len() > self.config.max_queued {
+            let oldest = queue.remove(0);
+            tracing::warn!(event_id = %oldest.event_id, "webhook queue full, dropped the oldest delivery");
+        }
+        Ok(())
+    }
+
+    /// Deliveries waiting to be sent or retried
+    pub fn queued(&self) -> usize {
+        self.queue.lock().len()
+    }
+
+    /// Send the deliveries due at `now`
+    pub async fn deliver_once(&self, now: DateTime<Utc>) -> Result<DeliveryReport> {
+        let due: Vec<Delivery> = {
+            let mut queue = self.queue.lock();
+            let split = queue.partition_point(|delivery| delivery.due <= now);
+            queue.drain(..split).collect()
+        };
+
+        let mut report = DeliveryReport::default();
+        for mut delivery in due {
+            let endpoint = &self.config.endpoints[delivery.endpoint];
+            delivery.attempts += 1;
+            let timestamp = now.timestamp();
+            let signature = sign(&endpoint.secret, timestamp, &delivery.body);
+            match self.sender.send(&endpoint.url, timestamp, &signature, delivery.body.clone()).await {
+                Ok(()) => report.delivered += 1,
+                Err(e) if delivery.attempts >= self.config.max_attempts => {
+                    tracing::warn!(
+                        endpoint = %endpoint.id,
+                        event_id = %delivery.event_id,
+                        attempts = delivery.attempts,
+                        error = %e,
+                        "dropped relayer webhook after its last attempt"
+                    );
+                    report.dropped += 1;
+                }
+                Err(e) => {
+                    tracing::debug!(endpoint = %endpoint.id, error = %e, "relayer webhook delivery failed");
+                    delivery.due = now + chrono::Duration::from_std(self.config.backoff(delivery.attempts))?;
+                    schedule(&mut self.queue.lock(), delivery);
+                    report.retrying += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+fn schedule(queue: &mut Vec<Delivery>, delivery: Delivery) {
+    let position = queue.partition_point(|queued| queued.due <= delivery.due);
+    queue.insert(position, delivery);
+}
+
+/// Send due webhooks every `interval` of the config
+pub fn spawn_lifecycle_webhooks(webhooks: Arc<LifecycleWebhooks>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(webhooks.config.interval);
+        loop {
+            ticker.tick().await;
+            match webhooks.deliver_once(Utc::now()).await {
+                Ok(report) if report.dropped > 0 => {
+                    tracing::warn!(dropped = report.dropped, retrying = report.retrying, "relayer webhooks dropped");
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "relayer webhook pass failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records deliveries, failing the first `failures` of them
+    #[derive(Default)]
+    struct RecordingSender {
+        failures: Mutex<usize>,
+        sent: Mutex<Vec<(String, i64, String, Vec<u8>)>>,
+    }
+
+    #[async_trait]
+    impl WebhookSender for RecordingSender {
+        async fn send(&self, url: &str, timestamp: i64, signature: &str, body: Vec<u8>) -> Result<()> {
+            let mut failures = self.failures.lock();
+            if *failures > 0 {
+                *failures -= 1;
+                bail!("503 Service Unavailable");
+            }
+            self.sent.lock().push((url.to_string(), timestamp, signature.to_string(), body));
+            Ok(())
+        }
+    }
+
+    fn endpoint(id: &str, statuses: Vec<RelayerStatus>) -> LifecycleEndpoint {
+        LifecycleEndpoint {
+            id: id.to_string(),
+            url: format!("https://backoffice.example/{}", id),
+            secret: format!("{}-secret", id),
+            statuses,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signed_deliveries_with_retry() {
+        let sender = Arc::new(RecordingSender::default());
+        let config = LifecycleWebhookConfig {
+            endpoints: vec![endpoint("all", Vec::new()), endpoint("settled", vec![RelayerStatus::Committed])],
+            max_attempts: 2,
+            ..Default::default()
+        };
+        let webhooks = LifecycleWebhooks::new(sender.clone(), config).unwrap();
+
+        let now = Utc::now();
+        let mut batch = RelayerBatch::new(vec![[1; 32]], "relayer-1".to_string());
+        webhooks.notify(&batch, now).unwrap();
+        assert_eq!(webhooks.queued(), 0);
+        batch.claim("relayer-1", now);
+        webhooks.notify(&batch, now).unwrap();
+        assert_eq!(webhooks.queued(), 1);
+
+        // The first attempt fails and is retried after the backoff
+        *sender.failures.lock() = 1;
+        let report = webhooks.deliver_once(now).await.unwrap();
+        assert_eq!(report, DeliveryReport { retrying: 1, ..Default::default() });
+        assert_eq!(webhooks.deliver_once(now).await.unwrap(), DeliveryReport::default());
+        let later = now + chrono::Duration::seconds(5);
+        assert_eq!(webhooks.deliver_once(later).await.unwrap().delivered, 1);
+
+        let (url, timestamp, signature, body) = sender.sent.lock()[0].clone();
+        assert_eq!(url, "https://backoffice.example/all");
+        assert!(verify_signature("all-secret", timestamp, &body, &signature, later, 300));
+        assert!(!verify_signature("settled-secret", timestamp, &body, &signature, later, 300));
+        let event: LifecycleEvent = serde_json::from_slice(&body).unwrap();
+        assert_eq!((event.commitment_id, event.status.as_str()), (batch.commitment_id, "processing"));
+        assert_eq!(event.batch_lineage_id, batch.batch_lineage_id);
+
+        // Failed goes to the catch-all endpoint only, and is dropped after its last attempt
+        batch.mark_failed();
+        webhooks.notify(&batch, later).unwrap();
+        *sender.failures.lock() = 2;
+        webhooks.deliver_once(later).await.unwrap();
+        let report = webhooks.deliver_once(later + chrono::Duration::seconds(5)).await.unwrap();
+        assert_eq!(report.dropped, 1);
+        assert_eq!(webhooks.queued(), 0);
+
+        let queued = vec![endpoint("queued", vec![RelayerStatus::Queued])];
+        assert!(LifecycleWebhookConfig { endpoints: queued, ..Default::default() }.validate().is_err());
+    }
+}
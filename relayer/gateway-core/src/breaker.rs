@@ -0,0 +1,227 @@
+// relayer/gateway-core/src/breaker.rs
+//! Circuit breaker pausing the relay pipeline while the destination is down.
+//!
+//! The breaker opens after `failure_threshold` consecutive failed relays.
+//! While it is open nothing is claimed, so batches stay `Queued` instead of
+//! burning their retries against a target that cannot take them. Every
+//! `probe_interval` one batch is let through as a probe: its success closes
+//! the breaker, its failure keeps it open for another interval. A probe that
+//! never reports back, e.g. because the queue was empty, is replaced by the
+//! next one after the same interval.
+//!
+//! Operators can force the breaker open or closed, overriding what the
+//! failures say, until the override is cleared. The breaker is shared by
+//! every clone, so the claimer, the router and an admin API see one state.
+use anyhow::{bail, Result};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerConfig {
+    /// Consecutive failed relays that open the breaker
+    pub failure_threshold: u32,
+    /// Wait between probes while open
+    pub probe_interval: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self { failure_threshold: 5, probe_interval: Duration::from_secs(30) }
+    }
+}
+
+impl BreakerConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.failure_threshold == 0 {
+            bail!("Breaker failure threshold must be greater than 0");
+        }
+        if self.probe_interval.is_zero() {
+            bail!("Breaker probe interval must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    Open,
+    /// Open, with a probe out
+    HalfOpen,
+}
+
+/// Operator decision that takes precedence over the failure count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerOverride {
+    ForceOpen,
+    ForceClosed,
+}
+
+/// What the pipeline may do right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    /// Relay as usual
+    Proceed,
+    /// Relay a single batch and report how it went
+    Probe,
+    /// Relay nothing; leave batches queued
+    Paused,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerMetrics {
+    pub state: BreakerState,
+    pub overridden: Option<BreakerOverride>,
+    pub consecutive_failures: u32,
+    /// Times the breaker opened on failures
+    pub opened: u64,
+    pub probes: u64,
+    /// Admissions refused while open
+    pub paused: u64,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: BreakerState,
+    overridden: Option<BreakerOverride>,
+    consecutive_failures: u32,
+    /// When the breaker opened or the last probe went out
+    since: Instant,
+    opened: u64,
+    probes: u64,
+    paused: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: BreakerConfig,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig) -> Result<Self> {
+        config.validate()?;
+        let inner = Inner {
+            state: BreakerState::Closed,
+            overridden: None,
+            consecutive_failures: 0,
+            since: Instant::now(),
+            opened: 0,
+            probes: 0,
+            paused: 0,
+        };
+        Ok(Self { config, inner: Arc::new(Mutex::new(inner)) })
+    }
+
+    pub fn config(&self) -> &BreakerConfig {
+        &self.config
+    }
+
+    /// Whether the pipeline may relay at `now`. A `Probe` must be followed
+    /// by `record_success` or `record_failure` for the batch it let through.
+    pub fn admit(&self, now: Instant) -> Admission {
+        let mut inner = self.inner.lock();
+        let admission = match (inner.overridden, inner.state) {
+            (Some(BreakerOverride::ForceClosed), _) | (None, BreakerState::Closed) => Admission::Proceed,
+            (Some(BreakerOverride::ForceOpen), _) => Admission::Paused,
+            (None, _) if now.saturating_duration_since(inner.since) >= self.config.probe_interval => {
+                inner.state = BreakerState::HalfOpen;
+                inner.since = now;
+                inner.probes += 1;
+                Admission::Probe
+            }
+            (None, _) => Admission::Paused,
+        };
+        if admission == Admission::Paused {
+            inner.paused += 1;
+        }
+        admission
+    }
+
+    /// A relay reached the destination; closes the breaker
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock();
+        if inner.state != BreakerState::Closed {
+            tracing::info!("relay target reachable again, circuit breaker closed");
+        }
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+    }
+
+    /// A relay failed at `now`; opens the breaker at the threshold, or
+    /// straight away if it was a probe
+    pub fn record_failure(&self, now: Instant) {
+        let mut inner = self.inner.lock();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        match inner.state {
+            BreakerState::Closed if inner.consecutive_failures >= self.config.failure_threshold => {
+                tracing::warn!(failures = inner.consecutive_failures, "relay target failing, circuit breaker opened");
+                inner.state = BreakerState::Open;
+                inner.since = now;
+                inner.opened += 1;
+            }
+            BreakerState::Closed => {}
+            BreakerState::Open | BreakerState::HalfOpen => {
+                inner.state = BreakerState::Open;
+                inner.since = now;
+            }
+        }
+    }
+
+    /// Force the breaker open or closed, or with `None` hand it back to the
+    /// failure count
+    pub fn set_override(&self, overridden: Option<BreakerOverride>) {
+        tracing::info!(?overridden, "circuit breaker override changed");
+        self.inner.lock().overridden = overridden;
+    }
+
+    pub fn metrics(&self) -> BreakerMetrics {
+        let inner = self.inner.lock();
+        BreakerMetrics {
+            state: inner.state,
+            overridden: inner.overridden,
+            consecutive_failures: inner.consecutive_failures,
+            opened: inner.opened,
+            probes: inner.probes,
+            paused: inner.paused,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_probes_and_closes() {
+        let config = BreakerConfig { failure_threshold: 2, probe_interval: Duration::from_secs(10) };
+        let breaker = CircuitBreaker::new(config).unwrap();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        breaker.record_failure(at(0));
+        assert_eq!(breaker.admit(at(0)), Admission::Proceed);
+        breaker.record_failure(at(1));
+        assert_eq!(breaker.admit(at(2)), Admission::Paused);
+
+        // A failed probe keeps it open for another interval
+        assert_eq!(breaker.admit(at(11)), Admission::Probe);
+        assert_eq!(breaker.admit(at(12)), Admission::Paused);
+        breaker.record_failure(at(13));
+        assert_eq!(breaker.admit(at(22)), Admission::Paused);
+        assert_eq!(breaker.admit(at(23)), Admission::Probe);
+        breaker.record_success();
+        assert_eq!(breaker.admit(at(24)), Admission::Proceed);
+
+        // Overrides win over the failure count until cleared
+        breaker.set_override(Some(BreakerOverride::ForceOpen));
+        assert_eq!(breaker.admit(at(25)), Admission::Paused);
+        breaker.set_override(None);
+        assert_eq!(breaker.admit(at(25)), Admission::Proceed);
+
+        let metrics = breaker.metrics();
+        assert_eq!((metrics.state, metrics.opened, metrics.probes, metrics.paused), (BreakerState::Closed, 1, 2, 4));
+        assert!(BreakerConfig { failure_threshold: 0, ..config }.validate().is_err());
+    }
+}
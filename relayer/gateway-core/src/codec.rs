@@ -0,0 +1,344 @@
+// relayer/gateway-core/src/codec.rs
+//! Encodings of the transaction payload a commitment carries.
+//!
+//! Posting raw batch data to the target or a DA layer is paid per byte, so
+//! the payload goes through a `BatchCodec`. The codec is named in the
+//! commitment's `payload_encoding`, which is all a verifier needs to get the
+//! transactions back.
+//!
+//! The sender-delta encoding targets relayer batches, where a few senders
+//! submit runs of transactions: each sender's address is written once and
+//! referenced by index, nonces are stored as the step from that sender's
+//! previous transaction and timestamps as the step from the previous
+//! transaction, all varint-encoded. Transaction hashes are not stored and are
+//! recomputed on decode, so a tampered payload cannot keep its hashes.
+use anyhow::Result;
+use bincode::Options;
+use blockchain_core::{
+    Address, Amount, BlockHeight, ChainId, Transaction, TransactionStatus, TransactionType,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use storage_traits::PayloadEncoding;
+
+/// Largest payload a decoder will inflate, so a small compressed payload
+/// cannot exhaust memory
+pub const MAX_DECODED_PAYLOAD_BYTES: usize = 64 * 1024 * 1024;
+
+const ZSTD_LEVEL: i32 = 19;
+
+/// Turns a batch's transactions into payload bytes and back
+pub trait BatchCodec: Send + Sync {
+    fn encoding(&self) -> PayloadEncoding;
+
+    fn encode(&self, transactions: &[Transaction]) -> Result<Vec<u8>>;
+
+    fn decode(&self, payload: &[u8]) -> Result<Vec<Transaction>>;
+}
+
+/// Codec for `encoding`
+pub fn codec_for(encoding: PayloadEncoding) -> Box<dyn BatchCodec> {
+    match encoding {
+        PayloadEncoding::Raw => Box::new(RawCodec),
+        PayloadEncoding::Zstd => Box::new(ZstdCodec { inner: RawCodec }),
+        PayloadEncoding::SenderDelta => Box::new(SenderDeltaCodec),
+        PayloadEncoding::SenderDeltaZstd => Box::new(ZstdCodec { inner: SenderDeltaCodec }),
+    }
+}
+
+/// Each transaction bincode-encoded as stored everywhere else, length-prefixed.
+///
+/// Payloads from before `chain_id` was always encoded leave it out of
+/// legacy transactions, so each is decoded on its own with
+/// `Transaction::decode_legacy`, which reads either layout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawCodec;
+
+impl BatchCodec for RawCodec {
+    fn encoding(&self) -> PayloadEncoding {
+        PayloadEncoding::Raw
+    }
+
+    fn encode(&self, transactions: &[Transaction]) -> Result<Vec<u8>> {
+        let encoded = transactions
+            .iter()
+            .map(bincode::serialize)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(bincode::serialize(&encoded)?)
+    }
+
+    fn decode(&self, payload: &[u8]) -> Result<Vec<Transaction>> {
+        let encoded: Vec<Vec<u8>> = bincode::options()
+            .with_fixint_encoding()
+            .with_limit(MAX_DECODED_PAYLOAD_BYTES as u64)
+            .deserialize(payload)?;
+        encoded
+            .iter()
+            .map(|tx| Ok(Transaction::decode_legacy(tx)?))
+            .collect()
+    }
+}
+
+/// zstd over another codec
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZstdCodec<C> {
+    pub inner: C,
+}
+
+impl<C: BatchCodec> BatchCodec for ZstdCodec<C> {
+    fn encoding(&self) -> PayloadEncoding {
+        match self.inner.encoding() {
+            PayloadEncoding::SenderDelta | PayloadEncoding::SenderDeltaZstd => PayloadEncoding::SenderDeltaZstd,
+            PayloadEncoding::Raw | PayloadEncoding::Zstd => PayloadEncoding::Zstd,
+        }
+    }
+
+    fn encode(&self, transactions: &[Transaction]) -> Result<Vec<u8>> {
+        Ok(zstd::bulk::compress(&self.inner.encode(transactions)?, ZSTD_LEVEL)?)
+    }
+
+    fn decode(&self, payload: &[u8]) -> Result<Vec<Transaction>> {
+        self.inner.decode(&zstd::bulk::decompress(payload, MAX_DECODED_PAYLOAD_BYTES)?)
+    }
+}
+
+/// Each sender written once; see the module docs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SenderDeltaCodec;
+
+#[derive(Serialize, Deserialize)]
+struct DeltaBatch {
+    senders: Vec<Address>,
+    transactions: Vec<DeltaTransaction>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DeltaTransaction {
+    kind: DeltaKind,
+    /// Nonce minus the same sender's previous nonce, wrapping
+    nonce_delta: u64,
+    gas_limit: u64,
+    gas_price: Amount,
+    /// Seconds since the previous transaction's timestamp
+    timestamp_delta_secs: i64,
+    timestamp_nanos: u32,
+    signature: Vec<u8>,
+    status: TransactionStatus,
+    chain_id: ChainId,
+}
+
+/// `TransactionType` with the sender replaced by its index in `senders`
+#[derive(Serialize, Deserialize)]
+enum DeltaKind {
+    Transfer { from: u32, to: Address, amount: Amount },
+    Deploy { from: u32, code: Vec<u8>, init_data: Vec<u8> },
+    Call { from: u32, to: Address, data: Vec<u8>, amount: Amount },
+    Coinbase { to: Address, amount: Amount, height: BlockHeight },
+}
+
+impl DeltaKind {
+    fn sender(&self) -> Option<u32> {
+        match self {
+            DeltaKind::Transfer { from, .. } | DeltaKind::Deploy { from, .. } | DeltaKind::Call { from, .. } => {
+                Some(*from)
+            }
+            DeltaKind::Coinbase { .. } => None,
+        }
+    }
+}
+
+impl BatchCodec for SenderDeltaCodec {
+    fn encoding(&self) -> PayloadEncoding {
+        PayloadEncoding::SenderDelta
+    }
+
+    fn encode(&self, transactions: &[Transaction]) -> Result<Vec<u8>> {
+        let mut senders = Vec::new();
+        let mut sender_index: HashMap<Address, u32> = HashMap::new();
+        let mut last_nonce: HashMap<u32, u64> = HashMap::new();
+        let mut last_timestamp = 0i64;
+
+        let mut encoded = Vec::with_capacity(transactions.len());
+        for tx in transactions {
+            let mut index_of = |address: &Address| -> Result<u32> {
+                if let Some(index) = sender_index.get(address) {
+                    return Ok(*index);
+                }
+                let index = u32::try_from(senders.len())?;
+                senders.push(*address);
+                sender_index.insert(*address, index);
+                Ok(index)
+            };
+            let kind = match &tx.tx_type {
+                TransactionType::Transfer { from, to, amount } => {
+                    DeltaKind::Transfer { from: index_of(from)?, to: *to, amount: *amount }
+                }
+                TransactionType::Deploy { from, code, init_data } => DeltaKind::Deploy {
+                    from: index_of(from)?,
+                    code: code.clone(),
+                    init_data: init_data.clone(),
+                },
+                TransactionType::Call { from, to, data, amount } => DeltaKind::Call {
+                    from: index_of(from)?,
+                    to: *to,
+                    data: data.clone(),
+                    amount: *amount,
+                },
+                TransactionType::Coinbase { to, amount, height } => {
+                    DeltaKind::Coinbase { to: *to, amount: *amount, height: *height }
+                }
+            };
+
+            let previous_nonce = kind.sender().and_then(|sender| last_nonce.insert(sender, tx.nonce));
+            let timestamp = tx.timestamp.timestamp();
+            encoded.push(DeltaTransaction {
+                nonce_delta: tx.nonce.wrapping_sub(previous_nonce.unwrap_or(0)),
+                gas_limit: tx.gas_limit,
+                gas_price: tx.gas_price,
+                timestamp_delta_secs: timestamp - last_timestamp,
+                timestamp_nanos: tx.timestamp.timestamp_subsec_nanos(),
+                signature: tx.signature.clone(),
+                status: tx.status.clone(),
+                chain_id: tx.chain_id,
+                kind,
+            });
+            last_timestamp = timestamp;
+        }
+
+        Ok(bincode::options().serialize(&DeltaBatch { senders, transactions: encoded })?)
+    }
+
+    fn decode(&self, payload: &[u8]) -> Result<Vec<Transaction>> {
+        let batch: DeltaBatch = bincode::options()
+            .with_limit(MAX_DECODED_PAYLOAD_BYTES as u64)
+            .deserialize(payload)?;
+        let sender = |index: u32| -> Result<Address> {
+            batch
+                .senders
+                .get(index as usize)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("Sender index {} out of range", index))
+        };
+
+        let mut last_nonce: HashMap<u32, u64> = HashMap::new();
+        let mut last_timestamp = 0i64;
+        let mut transactions = Vec::with_capacity(batch.transactions.len());
+        for delta in &batch.transactions {
+            let nonce = match delta.kind.sender() {
+                Some(index) => {
+                    let nonce = last_nonce.get(&index).copied().unwrap_or(0).wrapping_add(delta.nonce_delta);
+                    last_nonce.insert(index, nonce);
+                    nonce
+                }
+                None => delta.nonce_delta,
+            };
+            let seconds = last_timestamp
+                .checked_add(delta.timestamp_delta_secs)
+                .ok_or_else(|| anyhow::anyhow!("Timestamp delta overflows"))?;
+            last_timestamp = seconds;
+            let timestamp: DateTime<Utc> = Utc
+                .timestamp_opt(seconds, delta.timestamp_nanos)
+                .single()
+                .ok_or_else(|| anyhow::anyhow!("Timestamp {} out of range", seconds))?;
+
+            let tx_type = match &delta.kind {
+                DeltaKind::Transfer { from, to, amount } => {
+                    TransactionType::Transfer { from: sender(*from)?, to: *to, amount: *amount }
+                }
+                DeltaKind::Deploy { from, code, init_data } => TransactionType::Deploy {
+                    from: sender(*from)?,
+                    code: code.clone(),
+                    init_data: init_data.clone(),
+                },
+                DeltaKind::Call { from, to, data, amount } => TransactionType::Call {
+                    from: sender(*from)?,
+                    to: *to,
+                    data: data.clone(),
+                    amount: *amount,
+                },
+                DeltaKind::Coinbase { to, amount, height } => {
+                    TransactionType::Coinbase { to: *to, amount: *amount, height: *height }
+                }
+            };
+
+            let mut tx = Transaction {
+                hash: [0; 32],
+                tx_type,
+                nonce,
+                gas_limit: delta.gas_limit,
+                gas_price: delta.gas_price,
+                timestamp,
+                signature: delta.signature.clone(),
+                status: delta.status.clone(),
+                chain_id: delta.chain_id,
+            };
+            tx.hash = tx.calculate_hash()?;
+            transactions.push(tx);
+        }
+        Ok(transactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blockchain_core::{AddressExt, KeyPair, SignatureScheme};
+
+    fn batch() -> Vec<Transaction> {
+        let alice = KeyPair::generate(SignatureScheme::Secp256k1);
+        let bob = KeyPair::generate(SignatureScheme::Ed25519);
+        let mut txs = Vec::new();
+        for nonce in 0..20 {
+            let key = if nonce % 4 == 0 { &bob } else { &alice };
+            let from = Address::from_public_key(&key.public_key()).unwrap();
+            let mut tx = Transaction::new_transfer(from, [9; 20], 1_000 + nonce, 100 + nonce, 21_000, 3).unwrap();
+            tx.sign(key);
+            txs.push(tx);
+        }
+        txs.push(Transaction::new_call([4; 20], [5; 20], vec![1, 2, 3], 0, 7, 50_000, 3).unwrap());
+        txs
+    }
+
+    #[test]
+    fn test_codecs_round_trip() {
+        let txs = batch();
+        let raw_len = RawCodec.encode(&txs).unwrap().len();
+
+        for encoding in [
+            PayloadEncoding::Raw,
+            PayloadEncoding::Zstd,
+            PayloadEncoding::SenderDelta,
+            PayloadEncoding::SenderDeltaZstd,
+        ] {
+            let codec = codec_for(encoding);
+            assert_eq!(codec.encoding(), encoding);
+            let payload = codec.encode(&txs).unwrap();
+            assert_eq!(codec.decode(&payload).unwrap(), txs, "{}", encoding);
+            if encoding != PayloadEncoding::Raw {
+                assert!(payload.len() < raw_len, "{} is {} bytes, raw {}", encoding, payload.len(), raw_len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sender_delta_rejects_bad_index() {
+        let payload = bincode::options()
+            .serialize(&DeltaBatch {
+                senders: vec![],
+                transactions: vec![DeltaTransaction {
+                    kind: DeltaKind::Transfer { from: 0, to: [1; 20], amount: 1 },
+                    nonce_delta: 0,
+                    gas_limit: 21_000,
+                    gas_price: 1,
+                    timestamp_delta_secs: 1_700_000_000,
+                    timestamp_nanos: 0,
+                    signature: vec![],
+                    status: TransactionStatus::Pending,
+                    chain_id: 0,
+                }],
+            })
+            .unwrap();
+        assert!(SenderDeltaCodec.decode(&payload).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+// relayer/gateway-core/src/commitment.rs
+use crate::codec::BatchCodec;
+use anyhow::Result;
+use blockchain_core::{hash_serializable, merkle_root, BlockHash, KeyPair, Transaction};
+use storage_traits::{CommitmentData, RelayerBatch};
+use uuid::Uuid;
+
+/// Digest the relayer signs, binding the batch id to its aggregate values
+pub fn batch_hash(
+    commitment_id: &Uuid,
+    merkle_root: &BlockHash,
+    transaction_count: u32,
+    total_gas_used: u64,
+    total_fees: u64,
+) -> Result<BlockHash> {
+    Ok(hash_serializable(&(
+        commitment_id.as_bytes(),
+        merkle_root,
+        transaction_count,
+        total_gas_used,
+        total_fees,
+    ))?)
+}
+
+/// Commitment over `transactions`, which must be the batch's transactions in
+/// the batch's order. `proof_data` is `key`'s signature over the batch hash
+/// and `payload` the transactions encoded with `codec`.
+pub fn build_commitment(
+    batch: &RelayerBatch,
+    transactions: &[Transaction],
+    codec: &dyn BatchCodec,
+    key: &KeyPair,
+) -> Result<CommitmentData> {
+    if !transactions.iter().map(|tx| tx.hash).eq(batch.tx_hashes.iter().copied()) {
+        return Err(anyhow::anyhow!(
+            "Batch {} lists {} transactions, got {} that do not match",
+            batch.commitment_id,
+            batch.tx_hashes.len(),
+            transactions.len()
+        ));
+    }
+
+    let totals = Totals::of(transactions)?;
+    let batch_hash = totals.batch_hash(&batch.commitment_id)?;
+
+    Ok(CommitmentData {
+        merkle_root: totals.merkle_root,
+        transaction_count: totals.transaction_count,
+        total_gas_used: totals.total_gas_used,
+        total_fees: totals.total_fees,
+        batch_hash,
+        proof_data: key.sign(&batch_hash),
+        payload_encoding: codec.encoding(),
+        payload: codec.encode(transactions)?,
+    })
+}
+
+/// Aggregate values a commitment states about its transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Totals {
+    pub merkle_root: BlockHash,
+    pub transaction_count: u32,
+    pub total_gas_used: u64,
+    pub total_fees: u64,
+}
+
+impl Totals {
+    pub fn of(transactions: &[Transaction]) -> Result<Self> {
+        let hashes: Vec<BlockHash> = transactions.iter().map(|tx| tx.hash).collect();
+        Ok(Self {
+            merkle_root: merkle_root(&hashes),
+            transaction_count: u32::try_from(transactions.len())?,
+            total_gas_used: transactions.iter().map(|tx| tx.gas_limit).sum(),
+            total_fees: transactions.iter().map(|tx| tx.total_fee()).sum(),
+        })
+    }
+
+    pub fn batch_hash(&self, commitment_id: &Uuid) -> Result<BlockHash> {
+        batch_hash(
+            commitment_id,
+            &self.merkle_root,
+            self.transaction_count,
+            self.total_gas_used,
+            self.total_fees,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{BatchCodec, SenderDeltaCodec};
+    use blockchain_core::SignatureScheme;
+    use storage_traits::PayloadEncoding;
+
+    #[test]
+    fn test_commitment_covers_batch() {
+        let txs: Vec<Transaction> = (0..3)
+            .map(|nonce| Transaction::new_transfer([1; 20], [2; 20], 10, nonce, 21_000, 2).unwrap())
+            .collect();
+        let batch = RelayerBatch::new(txs.iter().map(|tx| tx.hash).collect(), "relayer-1".to_string());
+        let key = KeyPair::generate(SignatureScheme::Secp256k1);
+
+        let commitment = build_commitment(&batch, &txs, &SenderDeltaCodec, &key).unwrap();
+        assert_eq!(commitment.transaction_count, 3);
+        assert_eq!(commitment.total_gas_used, 63_000);
+        assert_eq!(commitment.total_fees, 126_000);
+        assert_eq!(commitment.merkle_root, merkle_root(&batch.tx_hashes));
+        assert_eq!(
+            commitment.batch_hash,
+            batch_hash(&batch.commitment_id, &commitment.merkle_root, 3, 63_000, 126_000).unwrap()
+        );
+
+        assert_eq!(commitment.payload_encoding, PayloadEncoding::SenderDelta);
+        assert_eq!(SenderDeltaCodec.decode(&commitment.payload).unwrap(), txs);
+
+        assert!(build_commitment(&batch, &txs[..2], &SenderDeltaCodec, &key).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+// relayer/gateway-core/src/confirmation.rs
+//! Tracking submitted commitments until they are final on the target chain.
+//!
+//! A submission is polled until its transaction sits `confirmations` blocks
+//! deep on the target's canonical chain. If the target reorganizes the
+//! transaction away, or it never shows up, the submission is sent again.
+//! Only a confirmed inclusion moves the batch to `Committed`.
+use anyhow::Result;
+use async_trait::async_trait;
+use std::time::Duration;
+use storage_traits::{CommitmentData, RelaySubmission, RelayerBatch, TargetInclusion};
+
+/// What the watcher needs from a target chain
+#[async_trait]
+pub trait TargetChain: Send + Sync {
+    /// Broadcast the submission, returning the target's transaction id
+    async fn submit(&self, submission: &RelaySubmission) -> Result<String>;
+
+    async fn head_height(&self) -> Result<u64>;
+
+    /// Block containing `tx_id` on the target's current canonical chain
+    async fn find_inclusion(&self, tx_id: &str) -> Result<Option<TargetInclusion>>;
+
+    /// Forget `tx_id`, which the target lost, before its submission is sent again
+    async fn abandon(&self, _tx_id: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmationConfig {
+    /// Depth at which an inclusion is treated as final, counting its own block
+    pub confirmations: u64,
+    pub poll_interval: Duration,
+    /// Polls a never-included transaction may go unseen before it is resent
+    pub max_missed_polls: u32,
+    /// Resubmissions before the batch is given up on
+    pub max_resubmissions: u32,
+}
+
+impl Default for ConfirmationConfig {
+    fn default() -> Self {
+        Self {
+            confirmations: 12,
+            poll_interval: Duration::from_secs(15),
+            max_missed_polls: 20,
+            max_resubmissions: 3,
+        }
+    }
+}
+
+/// A submission being watched on its target
+#[derive(Debug, Clone)]
+pub struct TrackedSubmission {
+    pub submission: RelaySubmission,
+    pub tx_id: String,
+    /// Inclusion seen at the last poll
+    pub inclusion: Option<TargetInclusion>,
+    pub resubmissions: u32,
+    missed_polls: u32,
+}
+
+impl TrackedSubmission {
+    pub fn new(submission: RelaySubmission, tx_id: String) -> Self {
+        Self { submission, tx_id, inclusion: None, resubmissions: 0, missed_polls: 0 }
+    }
+}
+
+/// Result of one poll
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InclusionStatus {
+    /// Not in a block yet
+    Pending,
+    /// In a block, not yet deep enough
+    Included { confirmations: u64 },
+    /// Dropped or reorganized away and sent again under `tx_id`
+    Resubmitted { tx_id: String },
+    Confirmed(TargetInclusion),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfirmationWatcher {
+    config: ConfirmationConfig,
+}
+
+impl ConfirmationWatcher {
+    pub fn new(config: ConfirmationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Check `tracked` once, resubmitting if the target lost it
+    pub async fn poll(&self, target: &dyn TargetChain, tracked: &mut TrackedSubmission) -> Result<InclusionStatus> {
+        let Some(inclusion) = target.find_inclusion(&tracked.tx_id).await? else {
+            // A transaction that was in a block and no longer is was reorged out
+            let reorged = tracked.inclusion.take().is_some();
+            tracked.missed_polls += 1;
+            if !reorged && tracked.missed_polls < self.config.max_missed_polls {
+                return Ok(InclusionStatus::Pending);
+            }
+            return self.resubmit(target, tracked).await;
+        };
+
+        tracked.missed_polls = 0;
+        let head = target.head_height().await?;
+        let confirmations = head.saturating_sub(inclusion.block_height) + 1;
+        if tracked.inclusion.as_ref().is_some_and(|previous| previous.block_hash != inclusion.block_hash) {
+            tracing::warn!(
+                tx_id = %tracked.tx_id,
+                block_height = inclusion.block_height,
+                "commitment moved to another target block after a reorg"
+            );
+        }
+        tracked.inclusion = Some(inclusion.clone());
+
+        if head >= inclusion.block_height && confirmations >= self.config.confirmations {
+            Ok(InclusionStatus::Confirmed(inclusion))
+        } else {
+            Ok(InclusionStatus::Included { confirmations })
+        }
+    }
+
+    /// Poll until `tracked` is confirmed, then mark `batch` committed with
+    /// `commitment` at the confirmed inclusion.
+    ///
+    /// Marks the batch failed and returns the error if the target keeps
+    /// losing the transaction past `max_resubmissions`.
+    pub async fn watch(
+        &self,
+        target: &dyn TargetChain,
+        tracked: TrackedSubmission,
+        batch: &mut RelayerBatch,
+        commitment: CommitmentData,
+    ) -> Result<TargetInclusion> {
+        match self.wait(target, tracked).await {
+            Ok(inclusion) => {
+                batch.mark_committed(commitment, inclusion.clone());
+                Ok(inclusion)
+            }
+            Err(e) => {
+                batch.mark_failed();
+                Err(e)
+            }
+        }
+    }
+
+    /// Poll until `tracked` is confirmed, returning the confirmed inclusion
+    pub async fn wait(&self, target: &dyn TargetChain, mut tracked: TrackedSubmission) -> Result<TargetInclusion> {
+        loop {
+            if let InclusionStatus::Confirmed(inclusion) = self.poll(target, &mut tracked).await? {
+                return Ok(inclusion);
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+        }
+    }
+
+    async fn resubmit(&self, target: &dyn TargetChain, tracked: &mut TrackedSubmission) -> Result<InclusionStatus> {
+        if tracked.resubmissions >= self.config.max_resubmissions {
+            return Err(anyhow::anyhow!(
+                "Commitment {} lost on the target after {} resubmissions",
+                tracked.submission.commitment_id,
+                tracked.resubmissions
+            ));
+        }
+
+        target.abandon(&tracked.tx_id).await?;
+        let tx_id = target.submit(&tracked.submission).await?;
+        tracing::info!(
+            commitment_id = %tracked.submission.commitment_id,
+            previous_tx_id = %tracked.tx_id,
+            tx_id = %tx_id,
+            "resubmitted commitment"
+        );
+        tracked.tx_id = tx_id.clone();
+        tracked.resubmissions += 1;
+        tracked.missed_polls = 0;
+        Ok(InclusionStatus::Resubmitted { tx_id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use parking_lot::Mutex;
+    use storage_traits::{PayloadEncoding, RelayerStatus};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    /// Target whose blocks and head the test moves by hand
+    #[derive(Default)]
+    struct ScriptedTarget {
+        head: Mutex<u64>,
+        included: Mutex<HashMap<String, TargetInclusion>>,
+        submitted: Mutex<Vec<String>>,
+    }
+
+    impl ScriptedTarget {
+        fn include(&self, tx_id: &str, block_height: u64, block_hash: &str) {
+            self.included.lock().insert(
+                tx_id.to_string(),
+                TargetInclusion { tx_id: tx_id.to_string(), block_height, block_hash: block_hash.to_string() },
+            );
+        }
+    }
+
+    #[async_trait]
+    impl TargetChain for ScriptedTarget {
+        async fn submit(&self, _submission: &RelaySubmission) -> Result<String> {
+            let mut submitted = self.submitted.lock();
+            let tx_id = format!("tx-{}", submitted.len() + 1);
+            submitted.push(tx_id.clone());
+            Ok(tx_id)
+        }
+
+        async fn head_height(&self) -> Result<u64> {
+            Ok(*self.head.lock())
+        }
+
+        async fn find_inclusion(&self, tx_id: &str) -> Result<Option<TargetInclusion>> {
+            Ok(self.included.lock().get(tx_id).cloned())
+        }
+    }
+
+    fn tracked() -> TrackedSubmission {
+        let submission = RelaySubmission {
+            commitment_id: Uuid::new_v4(),
+            target: "ethereum".to_string(),
+            batch_hash: [1; 32],
+            calldata: vec![],
+            estimated_gas: 41_000,
+            prepared_at: Utc::now(),
+        };
+        TrackedSubmission::new(submission, "tx-0".to_string())
+    }
+
+    fn watcher() -> ConfirmationWatcher {
+        ConfirmationWatcher::new(ConfirmationConfig {
+            confirmations: 3,
+            poll_interval: Duration::ZERO,
+            max_missed_polls: 2,
+            max_resubmissions: 1,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_confirms_after_depth_and_survives_reorg() {
+        let target = ScriptedTarget::default();
+        let watcher = watcher();
+        let mut tracked = tracked();
+
+        *target.head.lock() = 100;
+        target.include("tx-0", 100, "0xaa");
+        assert_eq!(watcher.poll(&target, &mut tracked).await.unwrap(), InclusionStatus::Included { confirmations: 1 });
+
+        // The block is reorged out and the transaction with it
+        target.included.lock().clear();
+        let status = watcher.poll(&target, &mut tracked).await.unwrap();
+        assert_eq!(status, InclusionStatus::Resubmitted { tx_id: "tx-1".to_string() });
+
+        target.include("tx-1", 101, "0xbb");
+        *target.head.lock() = 103;
+        let mut batch = RelayerBatch::new(vec![], "relayer-1".to_string());
+        let commitment = CommitmentData {
+            merkle_root: [0; 32],
+            transaction_count: 0,
+            total_gas_used: 0,
+            total_fees: 0,
+            batch_hash: [1; 32],
+            proof_data: vec![],
+            payload_encoding: PayloadEncoding::Raw,
+            payload: vec![],
+        };
+        let inclusion = watcher.watch(&target, tracked, &mut batch, commitment).await.unwrap();
+        assert_eq!(inclusion.block_hash, "0xbb");
+        assert_eq!(batch.status, RelayerStatus::Committed);
+        assert_eq!(batch.target_block_height, Some(101));
+        assert_eq!(batch.target_inclusion, Some(inclusion));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_resubmissions() {
+        let target = ScriptedTarget::default();
+        let watcher = watcher();
+        let mut tracked = tracked();
+
+        assert_eq!(watcher.poll(&target, &mut tracked).await.unwrap(), InclusionStatus::Pending);
+        assert!(matches!(watcher.poll(&target, &mut tracked).await.unwrap(), InclusionStatus::Resubmitted { .. }));
+        assert_eq!(watcher.poll(&target, &mut tracked).await.unwrap(), InclusionStatus::Pending);
+        assert!(watcher.poll(&target, &mut tracked).await.is_err());
+        assert_eq!(target.submitted.lock().len(), 1);
+    }
+}
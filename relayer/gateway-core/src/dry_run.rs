@@ -0,0 +1,128 @@
+// relayer/gateway-core/src/dry_run.rs
+use anyhow::Result;
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::sync::Arc;
+use storage_traits::RelaySubmission;
+
+pub use storage_traits::SubmissionLog;
+
+/// Comma-separated targets that start in dry-run mode
+pub const DRY_RUN_TARGETS_ENV: &str = "RELAYER_DRY_RUN_TARGETS";
+
+/// What the relayer does with a prepared submission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dispatch {
+    /// Target is live; the caller submits it
+    Send(RelaySubmission),
+    /// Target is in dry-run mode; the submission was logged and persisted
+    Recorded,
+}
+
+/// Per-target dry-run switches, shared by every clone and changeable while
+/// the relayer runs
+#[derive(Debug, Clone, Default)]
+pub struct DryRunControl {
+    targets: Arc<RwLock<HashSet<String>>>,
+}
+
+impl DryRunControl {
+    pub fn from_env() -> Self {
+        let control = Self::default();
+        if let Ok(targets) = std::env::var(DRY_RUN_TARGETS_ENV) {
+            for target in targets.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                control.set(target, true);
+            }
+        }
+        control
+    }
+
+    pub fn set(&self, target: &str, dry_run: bool) {
+        let mut targets = self.targets.write();
+        if dry_run {
+            targets.insert(target.to_string());
+        } else {
+            targets.remove(target);
+        }
+    }
+
+    pub fn is_dry_run(&self, target: &str) -> bool {
+        self.targets.read().contains(target)
+    }
+
+    /// Targets currently in dry-run mode, sorted
+    pub fn dry_run_targets(&self) -> Vec<String> {
+        let mut targets: Vec<String> = self.targets.read().iter().cloned().collect();
+        targets.sort();
+        targets
+    }
+
+    /// Hand `submission` back for sending, or record it if its target is
+    /// in dry-run mode
+    pub async fn dispatch(&self, log: &dyn SubmissionLog, submission: RelaySubmission) -> Result<Dispatch> {
+        if !self.is_dry_run(&submission.target) {
+            return Ok(Dispatch::Send(submission));
+        }
+
+        tracing::info!(
+            target_name = %submission.target,
+            commitment_id = %submission.commitment_id,
+            batch_hash = %hex::encode(submission.batch_hash),
+            estimated_gas = submission.estimated_gas,
+            calldata = %hex::encode(&submission.calldata),
+            "dry run: commitment not submitted"
+        );
+        log.record(&submission).await?;
+        Ok(Dispatch::Recorded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use parking_lot::Mutex;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct MemoryLog {
+        recorded: Mutex<Vec<RelaySubmission>>,
+    }
+
+    #[async_trait]
+    impl SubmissionLog for MemoryLog {
+        async fn record(&self, submission: &RelaySubmission) -> Result<()> {
+            self.recorded.lock().push(submission.clone());
+            Ok(())
+        }
+    }
+
+    fn submission(target: &str) -> RelaySubmission {
+        RelaySubmission {
+            commitment_id: Uuid::new_v4(),
+            target: target.to_string(),
+            batch_hash: [3; 32],
+            calldata: vec![1, 2, 3],
+            estimated_gas: 41_048,
+            prepared_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_toggle_per_target() {
+        let control = DryRunControl::default();
+        let log = MemoryLog::default();
+        let shared = control.clone();
+        shared.set("ethereum", true);
+
+        assert_eq!(control.dispatch(&log, submission("ethereum")).await.unwrap(), Dispatch::Recorded);
+        let live = submission("polygon");
+        assert_eq!(control.dispatch(&log, live.clone()).await.unwrap(), Dispatch::Send(live));
+        assert_eq!(log.recorded.lock().len(), 1);
+
+        shared.set("ethereum", false);
+        assert!(control.dry_run_targets().is_empty());
+        assert!(matches!(control.dispatch(&log, submission("ethereum")).await.unwrap(), Dispatch::Send(_)));
+    }
+}
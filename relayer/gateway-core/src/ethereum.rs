@@ -0,0 +1,307 @@
+// relayer/gateway-core/src/ethereum.rs
+//! Ethereum L1 as a relay target.
+//!
+//! Commitments are sent as calls to the commitment contract at
+//! `EthereumConfig::contract`, with the calldata from `submission::prepare`.
+//! The L1 transaction hash is the submission's tx id; inclusion is read from
+//! its receipt, which the node only returns while the transaction is on the
+//! canonical chain. A reverted call is an error, so the batch is marked
+//! failed rather than committed. `ConfirmationWatcher` does the waiting:
+//! a batch becomes `Committed` once its call is `confirmations` blocks deep.
+//! Calls pay the gas price `GasOracle` suggests from the target's recent
+//! blocks, at the configured speed. Their nonces come from `NonceManager`,
+//! so concurrent commitments from the signing account never collide and a
+//! lost call's nonce is reused by its resubmission.
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, BlockId, BlockNumber, TransactionRequest, H256, U256};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use storage_traits::{RelaySubmission, RelayerBatch, TargetInclusion};
+
+use crate::confirmation::{ConfirmationConfig, ConfirmationWatcher, TargetChain, TrackedSubmission};
+use crate::gas_oracle::{FeeOracleConfig, FeeSource, GasOracle};
+use crate::nonce::{NonceManager, NonceSource};
+use crate::submission;
+use crate::target::RelayTarget;
+
+/// Target name recorded on submissions sent to Ethereum
+pub const ETHEREUM_TARGET: &str = "ethereum";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthereumConfig {
+    pub rpc_url: String,
+    pub chain_id: u64,
+    /// Hex address of the commitment contract
+    pub contract: String,
+    /// Hex private key of the account paying for commitments
+    pub signer_key: String,
+    /// Blocks a commitment must be buried under, counting its own
+    pub confirmations: u64,
+    /// Gas limit sent, as a percentage of the estimate
+    pub gas_limit_percent: u64,
+    /// Gas price sampling and the speed commitments pay for
+    pub gas_oracle: FeeOracleConfig,
+    pub poll_interval: Duration,
+}
+
+impl Default for EthereumConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: "http://localhost:8545".to_string(),
+            chain_id: 1,
+            contract: String::new(),
+            signer_key: String::new(),
+            confirmations: 12,
+            gas_limit_percent: 120,
+            gas_oracle: FeeOracleConfig::default(),
+            poll_interval: Duration::from_secs(12),
+        }
+    }
+}
+
+impl EthereumConfig {
+    pub fn validate(&self) -> Result<()> {
+        Address::from_str(&self.contract)
+            .map_err(|_| anyhow!("Invalid commitment contract address: {}", self.contract))?;
+        if self.confirmations == 0 {
+            bail!("Confirmations must be greater than 0");
+        }
+        if self.gas_limit_percent < 100 {
+            bail!("Gas limit must be at least 100% of the estimate");
+        }
+        self.gas_oracle.validate()?;
+        Ok(())
+    }
+
+    /// Watcher settings for this target, keeping the other defaults
+    pub fn confirmation_config(&self) -> ConfirmationConfig {
+        ConfirmationConfig {
+            confirmations: self.confirmations,
+            poll_interval: self.poll_interval,
+            ..Default::default()
+        }
+    }
+}
+
+/// Gas prices paid in recent Ethereum blocks
+pub struct EthereumFeeSource {
+    provider: Provider<Http>,
+}
+
+impl EthereumFeeSource {
+    pub fn new(provider: Provider<Http>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl FeeSource for EthereumFeeSource {
+    async fn recent_gas_prices(&self, blocks: u64) -> Result<Vec<u64>> {
+        let head = self.provider.get_block_number().await?.as_u64();
+        let mut prices = Vec::new();
+        for number in head.saturating_sub(blocks - 1)..=head {
+            let Some(block) = self.provider.get_block_with_txs(BlockNumber::Number(number.into())).await? else {
+                continue;
+            };
+            prices.extend(
+                block
+                    .transactions
+                    .iter()
+                    .filter_map(|tx| tx.gas_price)
+                    .map(|price| if price > U256::from(u64::MAX) { u64::MAX } else { price.as_u64() }),
+            );
+        }
+        Ok(prices)
+    }
+}
+
+#[async_trait]
+impl NonceSource for Provider<Http> {
+    async fn pending_nonce(&self, account: &str) -> Result<u64> {
+        transaction_count(self, account, BlockNumber::Pending).await
+    }
+
+    async fn confirmed_nonce(&self, account: &str) -> Result<u64> {
+        transaction_count(self, account, BlockNumber::Latest).await
+    }
+}
+
+async fn transaction_count(provider: &Provider<Http>, account: &str, block: BlockNumber) -> Result<u64> {
+    let address = Address::from_str(account).map_err(|_| anyhow!("Invalid Ethereum account: {}", account))?;
+    Ok(provider.get_transaction_count(address, Some(BlockId::Number(block))).await?.as_u64())
+}
+
+pub struct EthereumTarget {
+    client: SignerMiddleware<Provider<Http>, LocalWallet>,
+    contract: Address,
+    gas_oracle: GasOracle,
+    nonces: NonceManager,
+    /// Hex address of the signing account
+    account: String,
+    config: EthereumConfig,
+}
+
+impl EthereumTarget {
+    pub fn new(config: EthereumConfig) -> Result<Self> {
+        config.validate()?;
+        let provider = Provider::<Http>::try_from(config.rpc_url.as_str())
+            .with_context(|| format!("Invalid Ethereum RPC URL: {}", config.rpc_url))?;
+        let wallet = LocalWallet::from_str(&config.signer_key)
+            .map_err(|e| anyhow!("Invalid Ethereum signer key: {}", e))?
+            .with_chain_id(config.chain_id);
+        let gas_oracle = GasOracle::new(Arc::new(EthereumFeeSource::new(provider.clone())), config.gas_oracle)?;
+        let nonces = NonceManager::new(Arc::new(provider.clone()));
+        let account = format!("{:#x}", wallet.address());
+        Ok(Self {
+            client: SignerMiddleware::new(provider, wallet),
+            contract: Address::from_str(&config.contract)?,
+            gas_oracle,
+            nonces,
+            account,
+            config,
+        })
+    }
+
+    pub fn gas_oracle(&self) -> &GasOracle {
+        &self.gas_oracle
+    }
+
+    /// Send `batch`'s commitment and wait until it is confirmed, leaving the
+    /// batch `Committed`, or `Failed` if the target kept losing or reverting it
+    pub async fn commit(&self, batch: &mut RelayerBatch) -> Result<TargetInclusion> {
+        let commitment = batch
+            .commitment_data
+            .clone()
+            .ok_or_else(|| anyhow!("Batch {} has no commitment to submit", batch.commitment_id))?;
+        let prepared = submission::prepare(ETHEREUM_TARGET, batch, &commitment);
+        let tx_id = TargetChain::submit(self, &prepared).await?;
+        tracing::info!(commitment_id = %batch.commitment_id, tx_id = %tx_id, "submitted commitment to Ethereum");
+
+        let watcher = ConfirmationWatcher::new(self.config.confirmation_config());
+        let outcome = watcher.watch(self, TrackedSubmission::new(prepared, tx_id), batch, commitment).await;
+        self.settle(&outcome).await;
+        outcome
+    }
+
+    pub fn nonces(&self) -> &NonceManager {
+        &self.nonces
+    }
+
+    /// Release the nonce of a confirmed call, or realign nonces with the
+    /// target after a call that was not confirmed
+    async fn settle(&self, outcome: &Result<TargetInclusion>) {
+        match outcome {
+            Ok(inclusion) => self.nonces.confirmed(&self.account, &inclusion.tx_id).await,
+            Err(_) => {
+                if let Err(e) = self.nonces.resync(&self.account).await {
+                    tracing::warn!(error = %e, "failed to resync the Ethereum signer's nonces");
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TargetChain for EthereumTarget {
+    async fn submit(&self, submission: &RelaySubmission) -> Result<String> {
+        let gas_limit = submission.estimated_gas.saturating_mul(self.config.gas_limit_percent) / 100;
+        let gas_price = self.gas_oracle.gas_price().await?;
+        let send = |nonce: u64| async move {
+            let request = TransactionRequest::new()
+                .to(self.contract)
+                .data(submission.calldata.clone())
+                .gas(U256::from(gas_limit))
+                .gas_price(U256::from(gas_price))
+                .nonce(nonce)
+                .chain_id(self.config.chain_id);
+            let pending = self
+                .client
+                .send_transaction(request, None)
+                .await
+                .with_context(|| format!("Failed to submit commitment {}", submission.commitment_id))?;
+            Ok::<_, anyhow::Error>(format!("{:#x}", pending.tx_hash()))
+        };
+        let (_, tx_id) = self.nonces.submit(&self.account, send).await?;
+        Ok(tx_id)
+    }
+
+    async fn head_height(&self) -> Result<u64> {
+        Ok(self.client.get_block_number().await?.as_u64())
+    }
+
+    async fn find_inclusion(&self, tx_id: &str) -> Result<Option<TargetInclusion>> {
+        let hash = H256::from_str(tx_id).map_err(|_| anyhow!("Invalid Ethereum transaction hash: {}", tx_id))?;
+        let Some(receipt) = self.client.get_transaction_receipt(hash).await? else {
+            return Ok(None);
+        };
+        let (Some(block_number), Some(block_hash)) = (receipt.block_number, receipt.block_hash) else {
+            return Ok(None);
+        };
+        if receipt.status.is_some_and(|status| status.is_zero()) {
+            bail!("Commitment transaction {} reverted in block {}", tx_id, block_number);
+        }
+        Ok(Some(TargetInclusion {
+            tx_id: tx_id.to_string(),
+            block_height: block_number.as_u64(),
+            block_hash: format!("{:#x}", block_hash),
+        }))
+    }
+
+    async fn abandon(&self, tx_id: &str) -> Result<()> {
+        self.nonces.abandoned(&self.account, tx_id).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RelayTarget for EthereumTarget {
+    fn name(&self) -> &str {
+        ETHEREUM_TARGET
+    }
+
+    /// Fee in wei at the suggested gas price, for the gas limit `submit` sends
+    async fn estimate_fee(&self, submission: &RelaySubmission) -> Result<u64> {
+        let gas_limit = submission.estimated_gas.saturating_mul(self.config.gas_limit_percent) / 100;
+        Ok(self.gas_oracle.gas_price().await?.saturating_mul(gas_limit))
+    }
+
+    async fn submit(&self, submission: &RelaySubmission) -> Result<String> {
+        TargetChain::submit(self, submission).await
+    }
+
+    async fn confirm(&self, submission: &RelaySubmission, tx_id: &str) -> Result<TargetInclusion> {
+        let watcher = ConfirmationWatcher::new(self.config.confirmation_config());
+        let outcome = watcher.wait(self, TrackedSubmission::new(submission.clone(), tx_id.to_string())).await;
+        self.settle(&outcome).await;
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_validation() {
+        // Anvil's first development account and deployment address
+        let config = EthereumConfig {
+            contract: "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string(),
+            signer_key: "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".to_string(),
+            chain_id: 31337,
+            confirmations: 3,
+            ..Default::default()
+        };
+        config.validate().unwrap();
+        assert_eq!(config.confirmation_config().confirmations, 3);
+        assert!(EthereumTarget::new(config.clone()).is_ok());
+
+        assert!(EthereumConfig { contract: "0x1234".to_string(), ..config.clone() }.validate().is_err());
+        assert!(EthereumConfig { confirmations: 0, ..config.clone() }.validate().is_err());
+        assert!(EthereumConfig { gas_limit_percent: 90, ..config }.validate().is_err());
+    }
+}
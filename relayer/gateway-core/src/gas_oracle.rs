@@ -0,0 +1,144 @@
+// relayer/gateway-core/src/gas_oracle.rs
+//! Gas prices for commitment submissions, sampled from the target chain.
+//!
+//! A `FeeSource` reports the gas prices paid in the target's most recent
+//! blocks; `GasOracle` turns them into a `FeeSuggestion` and keeps it for
+//! `refresh_interval`, so a burst of submissions costs one round of sampling.
+//! If sampling fails while a suggestion is still cached, the cached one is
+//! used rather than failing the submission.
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::{FeeSpeed, FeeSuggestion, GasOracleConfig};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Recent gas prices on a relay target
+#[async_trait]
+pub trait FeeSource: Send + Sync {
+    /// Gas prices paid in the last `blocks` blocks
+    async fn recent_gas_prices(&self, blocks: u64) -> Result<Vec<u64>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeOracleConfig {
+    pub sampling: GasOracleConfig,
+    /// Speed submissions pay for
+    pub speed: FeeSpeed,
+    /// Lowest price ever suggested
+    pub floor: u64,
+    /// Highest price submissions pay, whatever the target's recent prices
+    pub ceiling: Option<u64>,
+    pub refresh_interval: Duration,
+}
+
+impl Default for FeeOracleConfig {
+    fn default() -> Self {
+        Self {
+            sampling: GasOracleConfig::default(),
+            speed: FeeSpeed::Standard,
+            floor: 1,
+            ceiling: None,
+            refresh_interval: Duration::from_secs(12),
+        }
+    }
+}
+
+impl FeeOracleConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.sampling.validate()?;
+        if self.ceiling.is_some_and(|ceiling| ceiling < self.floor) {
+            anyhow::bail!("Gas price ceiling must not be below the floor");
+        }
+        Ok(())
+    }
+}
+
+pub struct GasOracle {
+    source: Arc<dyn FeeSource>,
+    config: FeeOracleConfig,
+    cached: Mutex<Option<(Instant, FeeSuggestion)>>,
+}
+
+impl GasOracle {
+    pub fn new(source: Arc<dyn FeeSource>, config: FeeOracleConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self { source, config, cached: Mutex::new(None) })
+    }
+
+    pub fn config(&self) -> &FeeOracleConfig {
+        &self.config
+    }
+
+    /// Current suggestion, sampling the target if the cached one is stale
+    pub async fn suggest(&self) -> Result<FeeSuggestion> {
+        let cached = *self.cached.lock();
+        if let Some((sampled_at, suggestion)) = cached {
+            if sampled_at.elapsed() < self.config.refresh_interval {
+                return Ok(suggestion);
+            }
+        }
+        match self.source.recent_gas_prices(self.config.sampling.sample_blocks).await {
+            Ok(prices) => {
+                let suggestion = self.config.sampling.suggest(prices, self.config.floor);
+                *self.cached.lock() = Some((Instant::now(), suggestion));
+                Ok(suggestion)
+            }
+            Err(e) => match cached {
+                Some((_, suggestion)) => {
+                    tracing::warn!(error = %e, "gas price sampling failed, using the last suggestion");
+                    Ok(suggestion)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Gas price a submission pays at the configured speed, capped at the ceiling
+    pub async fn gas_price(&self) -> Result<u64> {
+        let price = self.suggest().await?.price(self.config.speed);
+        Ok(self.config.ceiling.map_or(price, |ceiling| price.min(ceiling)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serves fixed prices, failing once they are exhausted
+    struct ScriptedSource {
+        samples: Mutex<Vec<Vec<u64>>>,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl FeeSource for ScriptedSource {
+        async fn recent_gas_prices(&self, _blocks: u64) -> Result<Vec<u64>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.samples.lock().pop().ok_or_else(|| anyhow::anyhow!("target unreachable"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caches_and_caps_suggestions() {
+        let source = Arc::new(ScriptedSource { samples: Mutex::new(vec![vec![10, 20, 30, 400]]), calls: 0.into() });
+        let config = FeeOracleConfig {
+            sampling: GasOracleConfig { fast_percentile: 100, ..Default::default() },
+            speed: FeeSpeed::Fast,
+            ceiling: Some(100),
+            refresh_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        let oracle = GasOracle::new(source.clone(), config).unwrap();
+
+        assert_eq!(oracle.suggest().await.unwrap().standard, 20);
+        // Fast would pay 400; the next sample fails and the last one stands in
+        assert_eq!(oracle.gas_price().await.unwrap(), 100);
+        assert_eq!(source.calls.load(Ordering::SeqCst), 2);
+
+        let empty = Arc::new(ScriptedSource { samples: Mutex::new(Vec::new()), calls: 0.into() });
+        assert!(GasOracle::new(empty, config).unwrap().suggest().await.is_err());
+        assert!(FeeOracleConfig { floor: 200, ..config }.validate().is_err());
+    }
+}
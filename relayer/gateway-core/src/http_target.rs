@@ -0,0 +1,129 @@
+// relayer/gateway-core/src/http_target.rs
+//! An HTTP endpoint as a relay target.
+//!
+//! Submissions are POSTed as JSON to `{url}/submissions`, which answers with
+//! the id it filed them under. Confirmation polls `{url}/submissions/{id}`
+//! until the endpoint reports the submission final or rejected. The endpoint
+//! charges nothing per submission beyond the configured flat fee.
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use storage_traits::{RelaySubmission, TargetInclusion};
+
+use crate::target::RelayTarget;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpTargetConfig {
+    /// Name routing rules refer to the target by
+    pub name: String,
+    /// Base URL of the endpoint, without a trailing slash
+    pub url: String,
+    /// Fee reported for every submission
+    #[serde(default)]
+    pub fee: u64,
+    pub poll_interval: Duration,
+    /// Status checks before an unconfirmed submission is given up on
+    pub max_polls: u32,
+    pub request_timeout: Duration,
+}
+
+impl Default for HttpTargetConfig {
+    fn default() -> Self {
+        Self {
+            name: "http".to_string(),
+            url: "http://localhost:8080".to_string(),
+            fee: 0,
+            poll_interval: Duration::from_secs(2),
+            max_polls: 30,
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl HttpTargetConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            bail!("HTTP target name must not be empty");
+        }
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            bail!("Invalid HTTP target URL: {}", self.url);
+        }
+        if self.max_polls == 0 {
+            bail!("Max polls must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Accepted {
+    id: String,
+}
+
+/// Endpoint's view of a submission
+#[derive(Debug, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum SubmissionState {
+    Pending,
+    Confirmed { inclusion: TargetInclusion },
+    Rejected { reason: String },
+}
+
+pub struct HttpTarget {
+    client: reqwest::Client,
+    config: HttpTargetConfig,
+}
+
+impl HttpTarget {
+    pub fn new(config: HttpTargetConfig) -> Result<Self> {
+        config.validate()?;
+        let client = reqwest::Client::builder().timeout(config.request_timeout).build()?;
+        Ok(Self { client, config })
+    }
+}
+
+#[async_trait]
+impl RelayTarget for HttpTarget {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn estimate_fee(&self, _submission: &RelaySubmission) -> Result<u64> {
+        Ok(self.config.fee)
+    }
+
+    async fn submit(&self, submission: &RelaySubmission) -> Result<String> {
+        let accepted: Accepted = self
+            .client
+            .post(format!("{}/submissions", self.config.url))
+            .json(submission)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("Failed to submit commitment {}", submission.commitment_id))?;
+        Ok(accepted.id)
+    }
+
+    async fn confirm(&self, submission: &RelaySubmission, tx_id: &str) -> Result<TargetInclusion> {
+        let url = format!("{}/submissions/{}", self.config.url, tx_id);
+        for _ in 0..self.config.max_polls {
+            let state: SubmissionState = self.client.get(&url).send().await?.error_for_status()?.json().await?;
+            match state {
+                SubmissionState::Confirmed { inclusion } => return Ok(inclusion),
+                SubmissionState::Rejected { reason } => {
+                    bail!("{} rejected commitment {}: {}", self.config.name, submission.commitment_id, reason)
+                }
+                SubmissionState::Pending => tokio::time::sleep(self.config.poll_interval).await,
+            }
+        }
+        Err(anyhow!(
+            "Commitment {} was not confirmed by {} after {} polls",
+            submission.commitment_id,
+            self.config.name,
+            self.config.max_polls
+        ))
+    }
+}
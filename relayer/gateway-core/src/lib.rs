@@ -1,14 +1,34 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+// relayer/gateway-core/src/lib.rs
+//! Relayer core: turns queued batches into signed commitments with encoded
+//! transaction payloads, prepares the submissions that carry them to each
+//! relay target, and lets the receiving side verify a commitment against its
+//! transactions.
+pub mod breaker;
+pub mod codec;
+pub mod commitment;
+pub mod submission;
+pub mod dry_run;
+pub mod confirmation;
+pub mod target;
+pub mod gas_oracle;
+pub mod nonce;
+pub mod verify;
+#[cfg(feature = "ethereum")]
+pub mod ethereum;
+#[cfg(feature = "http")]
+pub mod http_target;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use breaker::{Admission, BreakerConfig, BreakerMetrics, BreakerOverride, BreakerState, CircuitBreaker};
+pub use codec::{codec_for, BatchCodec, RawCodec, SenderDeltaCodec, ZstdCodec};
+pub use commitment::{batch_hash, build_commitment};
+pub use confirmation::{ConfirmationConfig, ConfirmationWatcher, InclusionStatus, TargetChain, TrackedSubmission};
+pub use dry_run::{Dispatch, DryRunControl, SubmissionLog};
+pub use gas_oracle::{FeeOracleConfig, FeeSource, GasOracle};
+pub use nonce::{NonceManager, NonceSource};
+pub use submission::{encode_calldata, estimate_gas, prepare};
+pub use target::{RelayRouter, RelayTarget, RouteRule, RoutingConfig, TargetPolicy, TxKind};
+pub use verify::{verify_commitment, verify_payload, VerificationReport};
+#[cfg(feature = "ethereum")]
+pub use ethereum::{EthereumConfig, EthereumFeeSource, EthereumTarget};
+#[cfg(feature = "http")]
+pub use http_target::{HttpTarget, HttpTargetConfig};
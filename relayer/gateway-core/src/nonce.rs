@@ -0,0 +1,174 @@
+// relayer/gateway-core/src/nonce.rs
+//! Nonces for the relayer's signing accounts on a target chain.
+//!
+//! Submissions from one account are serialized: a submission holds the
+//! account's lock from picking its nonce until the target accepted or
+//! refused the transaction, so concurrent batches never send the same nonce.
+//! A nonce whose transaction failed to send, or was given up on before it
+//! was included, becomes a gap that the next submission fills first;
+//! otherwise every later transaction would wait behind it forever.
+//! `resync` realigns the account with the target after a restart or after
+//! transactions were sent from it elsewhere.
+use anyhow::Result;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Target chain's view of an account's nonces
+#[async_trait]
+pub trait NonceSource: Send + Sync {
+    /// Nonce of the account's next transaction, counting ones in the mempool
+    async fn pending_nonce(&self, account: &str) -> Result<u64>;
+
+    /// Nonce of the account's next transaction, counting only included ones
+    async fn confirmed_nonce(&self, account: &str) -> Result<u64>;
+}
+
+#[derive(Debug, Default)]
+struct AccountNonces {
+    /// Next fresh nonce; `None` until read from the target
+    next: Option<u64>,
+    /// Sent and not yet included, by nonce
+    in_flight: BTreeMap<u64, String>,
+    /// Nonces below `next` with no transaction the target will include
+    gaps: BTreeSet<u64>,
+}
+
+pub struct NonceManager {
+    source: Arc<dyn NonceSource>,
+    accounts: Mutex<HashMap<String, Arc<tokio::sync::Mutex<AccountNonces>>>>,
+}
+
+impl NonceManager {
+    pub fn new(source: Arc<dyn NonceSource>) -> Self {
+        Self { source, accounts: Mutex::new(HashMap::new()) }
+    }
+
+    fn account(&self, account: &str) -> Arc<tokio::sync::Mutex<AccountNonces>> {
+        self.accounts.lock().entry(account.to_string()).or_default().clone()
+    }
+
+    /// Send a transaction from `account` with the nonce `send` is given,
+    /// returning the nonce and the transaction id `send` returned. Other
+    /// submissions from the account wait until `send` finishes.
+    pub async fn submit<F, Fut>(&self, account: &str, send: F) -> Result<(u64, String)>
+    where
+        F: FnOnce(u64) -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        let state = self.account(account);
+        let mut nonces = state.lock().await;
+        let nonce = match nonces.gaps.pop_first() {
+            Some(gap) => gap,
+            None => match nonces.next {
+                Some(next) => next,
+                None => self.source.pending_nonce(account).await?,
+            },
+        };
+        if nonce >= nonces.next.unwrap_or(0) {
+            nonces.next = Some(nonce + 1);
+        }
+
+        match send(nonce).await {
+            Ok(tx_id) => {
+                nonces.in_flight.insert(nonce, tx_id.clone());
+                Ok((nonce, tx_id))
+            }
+            Err(e) => {
+                tracing::debug!(account, nonce, error = %e, "submission failed, nonce left for the next one");
+                nonces.gaps.insert(nonce);
+                Err(e)
+            }
+        }
+    }
+
+    /// Record that `tx_id` from `account` was included
+    pub async fn confirmed(&self, account: &str, tx_id: &str) {
+        let state = self.account(account);
+        state.lock().await.in_flight.retain(|_, sent| sent != tx_id);
+    }
+
+    /// Give up on `tx_id` from `account`, e.g. after it was dropped from the
+    /// mempool or replaced by a resubmission, freeing its nonce
+    pub async fn abandoned(&self, account: &str, tx_id: &str) {
+        let state = self.account(account);
+        let mut nonces = state.lock().await;
+        let abandoned: Vec<u64> =
+            nonces.in_flight.iter().filter(|(_, sent)| *sent == tx_id).map(|(&nonce, _)| nonce).collect();
+        for nonce in abandoned {
+            nonces.in_flight.remove(&nonce);
+            nonces.gaps.insert(nonce);
+        }
+    }
+
+    /// Nonces of `account`'s sent transactions not yet included
+    pub async fn in_flight(&self, account: &str) -> Vec<u64> {
+        self.account(account).lock().await.in_flight.keys().copied().collect()
+    }
+
+    /// Realign `account` with the target: nonces it has included are done,
+    /// and without anything outstanding the next nonce is re-read from it
+    pub async fn resync(&self, account: &str) -> Result<()> {
+        let state = self.account(account);
+        let mut nonces = state.lock().await;
+        let confirmed = self.source.confirmed_nonce(account).await?;
+        nonces.in_flight.retain(|&nonce, _| nonce >= confirmed);
+        nonces.gaps.retain(|&nonce| nonce >= confirmed);
+        if nonces.in_flight.is_empty() && nonces.gaps.is_empty() {
+            nonces.next = Some(self.source.pending_nonce(account).await?);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+
+    struct FixedSource {
+        pending: u64,
+        confirmed: u64,
+    }
+
+    #[async_trait]
+    impl NonceSource for FixedSource {
+        async fn pending_nonce(&self, _account: &str) -> Result<u64> {
+            Ok(self.pending)
+        }
+
+        async fn confirmed_nonce(&self, _account: &str) -> Result<u64> {
+            Ok(self.confirmed)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serialized_nonces_and_gaps() {
+        let manager = NonceManager::new(Arc::new(FixedSource { pending: 5, confirmed: 7 }));
+        let send = |nonce: u64| async move { Ok(format!("tx-{}", nonce)) };
+
+        let (a, b, c) = tokio::join!(
+            manager.submit("relayer", send),
+            manager.submit("relayer", send),
+            manager.submit("relayer", send)
+        );
+        let mut nonces = vec![a.unwrap().0, b.unwrap().0, c.unwrap().0];
+        nonces.sort_unstable();
+        assert_eq!(nonces, vec![5, 6, 7]);
+        assert_eq!(manager.submit("other", send).await.unwrap().0, 5);
+
+        // A failed send and an abandoned transaction leave gaps, filled in order
+        let failed = manager.submit("relayer", |_| async { bail!("connection reset") }).await;
+        assert!(failed.is_err());
+        manager.abandoned("relayer", "tx-6").await;
+        assert_eq!(manager.submit("relayer", send).await.unwrap().0, 6);
+        assert_eq!(manager.submit("relayer", send).await.unwrap().0, 8);
+        assert_eq!(manager.submit("relayer", send).await.unwrap().0, 9);
+
+        manager.confirmed("relayer", "tx-9").await;
+        manager.resync("relayer").await.unwrap();
+        assert_eq!(manager.in_flight("relayer").await, vec![7, 8]);
+    }
+}
@@ -0,0 +1,99 @@
+// relayer/gateway-core/src/submission.rs
+//! Calldata and gas estimates for the commitment contract call.
+//!
+//! Calldata is the 4-byte selector of `COMMIT_SIGNATURE` followed by the
+//! packed commitment: commitment id (16), merkle root (32), transaction
+//! count (u32), gas used (u64), fees (u64), batch hash (32) and the proof as
+//! a u32 length and its bytes. Integers are big-endian.
+use chrono::Utc;
+use sha3::{Digest, Keccak256};
+use storage_traits::{CommitmentData, RelaySubmission, RelayerBatch};
+
+/// Contract entry point a commitment is submitted to
+pub const COMMIT_SIGNATURE: &str = "commitBatch(bytes16,bytes32,uint32,uint64,uint64,bytes32,bytes)";
+
+/// Intrinsic cost of any transaction on the target
+pub const BASE_TX_GAS: u64 = 21_000;
+
+/// Cost of the storage slot the contract writes per commitment
+pub const COMMITMENT_STORAGE_GAS: u64 = 20_000;
+
+const ZERO_BYTE_GAS: u64 = 4;
+const NONZERO_BYTE_GAS: u64 = 16;
+
+pub fn commit_selector() -> [u8; 4] {
+    let digest = Keccak256::digest(COMMIT_SIGNATURE.as_bytes());
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+pub fn encode_calldata(commitment_id: &uuid::Uuid, commitment: &CommitmentData) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + 108 + 4 + commitment.proof_data.len());
+    out.extend_from_slice(&commit_selector());
+    out.extend_from_slice(commitment_id.as_bytes());
+    out.extend_from_slice(&commitment.merkle_root);
+    out.extend_from_slice(&commitment.transaction_count.to_be_bytes());
+    out.extend_from_slice(&commitment.total_gas_used.to_be_bytes());
+    out.extend_from_slice(&commitment.total_fees.to_be_bytes());
+    out.extend_from_slice(&commitment.batch_hash);
+    out.extend_from_slice(&(commitment.proof_data.len() as u32).to_be_bytes());
+    out.extend_from_slice(&commitment.proof_data);
+    out
+}
+
+/// Gas the commitment call is expected to use: intrinsic cost, calldata
+/// bytes and the commitment's storage write
+pub fn estimate_gas(calldata: &[u8]) -> u64 {
+    let data_gas: u64 = calldata
+        .iter()
+        .map(|&byte| if byte == 0 { ZERO_BYTE_GAS } else { NONZERO_BYTE_GAS })
+        .sum();
+    BASE_TX_GAS + data_gas + COMMITMENT_STORAGE_GAS
+}
+
+/// Submission carrying `commitment` for `batch` to `target`
+pub fn prepare(target: &str, batch: &RelayerBatch, commitment: &CommitmentData) -> RelaySubmission {
+    let calldata = encode_calldata(&batch.commitment_id, commitment);
+    RelaySubmission {
+        commitment_id: batch.commitment_id,
+        target: target.to_string(),
+        batch_hash: commitment.batch_hash,
+        estimated_gas: estimate_gas(&calldata),
+        calldata,
+        prepared_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage_traits::PayloadEncoding;
+
+    #[test]
+    fn test_calldata_layout_and_gas() {
+        let batch = RelayerBatch::new(vec![[7; 32]], "relayer-1".to_string());
+        let commitment = CommitmentData {
+            merkle_root: [7; 32],
+            transaction_count: 1,
+            total_gas_used: 21_000,
+            total_fees: 21_000,
+            batch_hash: [9; 32],
+            proof_data: vec![0, 1, 2],
+            payload_encoding: PayloadEncoding::Raw,
+            payload: vec![],
+        };
+
+        let submission = prepare("ethereum", &batch, &commitment);
+        let calldata = &submission.calldata;
+        assert_eq!(calldata.len(), 4 + 16 + 32 + 4 + 8 + 8 + 32 + 4 + 3);
+        assert_eq!(calldata[..4], commit_selector());
+        assert_eq!(&calldata[4..20], batch.commitment_id.as_bytes());
+        assert_eq!(&calldata[calldata.len() - 3..], &[0, 1, 2]);
+
+        let zeros = calldata.iter().filter(|&&byte| byte == 0).count() as u64;
+        let nonzero = calldata.len() as u64 - zeros;
+        assert_eq!(
+            submission.estimated_gas,
+            BASE_TX_GAS + COMMITMENT_STORAGE_GAS + zeros * ZERO_BYTE_GAS + nonzero * NONZERO_BYTE_GAS
+        );
+    }
+}
@@ -0,0 +1,269 @@
+// relayer/gateway-core/src/target.rs
+//! Relay destinations and the rules that pick one per batch.
+//!
+//! A `RelayTarget` is anything a commitment can be sent to: a chain through
+//! `TargetChain` and `ConfirmationWatcher`, or an HTTP endpoint. The
+//! `RelayRouter` holds the registered targets and sends each batch to the
+//! first rule that matches it, or to the default target. A rule matches a
+//! batch if any of its transactions matches, so one large transfer is enough
+//! to send the whole batch to the target reserved for large transfers.
+//! With a `CircuitBreaker` attached, every call that fails to reach a target
+//! counts toward opening it; a fee over its limit does not.
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use blockchain_core::{Amount, Transaction, TransactionType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+use storage_traits::{RelaySubmission, RelayerBatch, TargetInclusion};
+
+use crate::breaker::CircuitBreaker;
+use crate::submission;
+
+/// A destination commitments are relayed to
+#[async_trait]
+pub trait RelayTarget: Send + Sync {
+    /// Name routing rules and submissions refer to the target by
+    fn name(&self) -> &str;
+
+    /// Expected cost of sending `submission`, in the target's fee unit
+    async fn estimate_fee(&self, submission: &RelaySubmission) -> Result<u64>;
+
+    /// Send the submission, returning the target's id for it
+    async fn submit(&self, submission: &RelaySubmission) -> Result<String>;
+
+    /// Wait until the submission sent as `tx_id` is final on the target
+    async fn confirm(&self, submission: &RelaySubmission, tx_id: &str) -> Result<TargetInclusion>;
+}
+
+/// Transaction kinds a rule can match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxKind {
+    Transfer,
+    Call,
+    Deploy,
+    Coinbase,
+}
+
+impl TxKind {
+    pub fn of(tx: &Transaction) -> Self {
+        match tx.tx_type {
+            TransactionType::Transfer { .. } => TxKind::Transfer,
+            TransactionType::Call { .. } => TxKind::Call,
+            TransactionType::Deploy { .. } => TxKind::Deploy,
+            TransactionType::Coinbase { .. } => TxKind::Coinbase,
+        }
+    }
+}
+
+/// Send batches holding a matching transaction to `target`. A transaction
+/// matches if its kind is listed, or no kinds are, and it moves at least
+/// `min_amount`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteRule {
+    pub target: String,
+    #[serde(default)]
+    pub tx_kinds: Vec<TxKind>,
+    #[serde(default)]
+    pub min_amount: Option<Amount>,
+}
+
+impl RouteRule {
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        (self.tx_kinds.is_empty() || self.tx_kinds.contains(&TxKind::of(tx)))
+            && self.min_amount.map_or(true, |min| tx.amount() >= min)
+    }
+}
+
+/// Settings of one registered target
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetPolicy {
+    /// Estimated fee above which the batch is not sent; it fails and is
+    /// retried later
+    #[serde(default)]
+    pub max_fee: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    /// Target of batches no rule matches
+    pub default_target: String,
+    /// Checked in order; the first match wins
+    #[serde(default)]
+    pub rules: Vec<RouteRule>,
+    #[serde(default)]
+    pub targets: HashMap<String, TargetPolicy>,
+}
+
+pub struct RelayRouter {
+    config: RoutingConfig,
+    targets: HashMap<String, Arc<dyn RelayTarget>>,
+    breaker: Option<CircuitBreaker>,
+}
+
+impl RelayRouter {
+    /// Router over `targets`, refusing rules that name an unregistered target
+    pub fn new(config: RoutingConfig, targets: Vec<Arc<dyn RelayTarget>>) -> Result<Self> {
+        let targets: HashMap<String, Arc<dyn RelayTarget>> =
+            targets.into_iter().map(|target| (target.name().to_string(), target)).collect();
+        let named = std::iter::once(&config.default_target)
+            .chain(config.rules.iter().map(|rule| &rule.target))
+            .chain(config.targets.keys());
+        for name in named {
+            if !targets.contains_key(name) {
+                bail!("Relay target {} is not registered", name);
+            }
+        }
+        Ok(Self { config, targets, breaker: None })
+    }
+
+    /// Report every relay's outcome to `breaker`
+    pub fn with_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Target for a batch of `transactions`
+    pub fn route(&self, transactions: &[Transaction]) -> &Arc<dyn RelayTarget> {
+        let name = self
+            .config
+            .rules
+            .iter()
+            .find(|rule| transactions.iter().any(|tx| rule.matches(tx)))
+            .map_or(&self.config.default_target, |rule| &rule.target);
+        &self.targets[name]
+    }
+
+    /// Send `batch`'s commitment to the target its `transactions` route to
+    /// and wait for it to be final, leaving the batch `Committed`, or
+    /// `Failed` on any error so the retry worker picks it up again
+    pub async fn relay(&self, batch: &mut RelayerBatch, transactions: &[Transaction]) -> Result<TargetInclusion> {
+        let commitment = batch
+            .commitment_data
+            .clone()
+            .ok_or_else(|| anyhow!("Batch {} has no commitment to relay", batch.commitment_id))?;
+        let target = self.route(transactions);
+        let prepared = submission::prepare(target.name(), batch, &commitment);
+
+        match self.send(target.as_ref(), &prepared).await {
+            Ok(inclusion) => {
+                batch.mark_committed(commitment, inclusion.clone());
+                Ok(inclusion)
+            }
+            Err(e) => {
+                batch.mark_failed();
+                Err(e)
+            }
+        }
+    }
+
+    async fn send(&self, target: &dyn RelayTarget, prepared: &RelaySubmission) -> Result<TargetInclusion> {
+        let fee = self.reach(target.estimate_fee(prepared)).await?;
+        let max_fee = self.config.targets.get(target.name()).and_then(|policy| policy.max_fee);
+        if let Some(max_fee) = max_fee.filter(|&max_fee| fee > max_fee) {
+            bail!("Fee {} on {} exceeds the limit of {}", fee, target.name(), max_fee);
+        }
+        let tx_id = self.reach(target.submit(prepared)).await?;
+        tracing::info!(
+            commitment_id = %prepared.commitment_id,
+            target = target.name(),
+            tx_id = %tx_id,
+            "submitted commitment"
+        );
+        let inclusion = self.reach(target.confirm(prepared, &tx_id)).await?;
+        if let Some(breaker) = &self.breaker {
+            breaker.record_success();
+        }
+        Ok(inclusion)
+    }
+
+    /// Await a call to a target, counting its failure against the breaker
+    async fn reach<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        let outcome = call.await;
+        if let (Err(_), Some(breaker)) = (&outcome, &self.breaker) {
+            breaker.record_failure(Instant::now());
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::RawCodec;
+    use crate::commitment::build_commitment;
+    use blockchain_core::{KeyPair, SignatureScheme};
+    use parking_lot::Mutex;
+    use storage_traits::RelayerStatus;
+
+    struct FixedTarget {
+        name: String,
+        fee: u64,
+        sent: Mutex<Vec<String>>,
+    }
+
+    impl FixedTarget {
+        fn new(name: &str, fee: u64) -> Arc<Self> {
+            Arc::new(Self { name: name.to_string(), fee, sent: Mutex::new(Vec::new()) })
+        }
+    }
+
+    #[async_trait]
+    impl RelayTarget for FixedTarget {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn estimate_fee(&self, _submission: &RelaySubmission) -> Result<u64> {
+            Ok(self.fee)
+        }
+
+        async fn submit(&self, _submission: &RelaySubmission) -> Result<String> {
+            let mut sent = self.sent.lock();
+            let n = sent.len();
+            sent.push(format!("{}-{}", self.name, n));
+            Ok(sent.last().cloned().unwrap_or_default())
+        }
+
+        async fn confirm(&self, _submission: &RelaySubmission, tx_id: &str) -> Result<TargetInclusion> {
+            Ok(TargetInclusion { tx_id: tx_id.to_string(), block_height: 7, block_hash: "0xaa".to_string() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_rule_and_enforces_fee_limit() {
+        let l1 = FixedTarget::new("l1", 500);
+        let l2 = FixedTarget::new("l2", 5);
+        let config = RoutingConfig {
+            default_target: "l2".to_string(),
+            rules: vec![RouteRule { target: "l1".to_string(), tx_kinds: vec![], min_amount: Some(1_000) }],
+            targets: HashMap::from([("l1".to_string(), TargetPolicy { max_fee: Some(100) })]),
+        };
+        let targets: Vec<Arc<dyn RelayTarget>> = vec![l1.clone(), l2.clone()];
+        let router = RelayRouter::new(config.clone(), targets).unwrap();
+        assert!(RelayRouter::new(config, vec![l2.clone() as Arc<dyn RelayTarget>]).is_err());
+
+        let key = KeyPair::generate(SignatureScheme::Secp256k1);
+        let small = Transaction::new_transfer([1; 20], [2; 20], 10, 0, 21_000, 1).unwrap();
+        let large = Transaction::new_transfer([1; 20], [2; 20], 5_000, 1, 21_000, 1).unwrap();
+
+        let transactions = vec![small.clone()];
+        let mut batch = RelayerBatch::new(vec![small.hash], "relayer-1".to_string());
+        batch.commitment_data = Some(build_commitment(&batch, &transactions, &RawCodec, &key).unwrap());
+        let inclusion = router.relay(&mut batch, &transactions).await.unwrap();
+        assert_eq!(inclusion.tx_id, "l2-0");
+        assert_eq!(batch.status, RelayerStatus::Committed);
+
+        // One large transfer sends the batch to l1, whose fee is over its limit
+        let transactions = vec![small.clone(), large.clone()];
+        let mut batch = RelayerBatch::new(vec![small.hash, large.hash], "relayer-1".to_string());
+        batch.commitment_data = Some(build_commitment(&batch, &transactions, &RawCodec, &key).unwrap());
+        assert_eq!(router.route(&transactions).name(), "l1");
+        assert!(router.relay(&mut batch, &transactions).await.unwrap_err().to_string().contains("exceeds"));
+        assert_eq!(batch.status, RelayerStatus::Failed);
+        assert!(l1.sent.lock().is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+// relayer/gateway-core/src/verify.rs
+//! Verification of relayed commitments by the receiving side.
+use crate::codec::codec_for;
+use crate::commitment::Totals;
+use anyhow::Result;
+use blockchain_core::signature::{self, SignatureScheme};
+use blockchain_core::{Address, AddressExt, Transaction};
+use serde::Serialize;
+use storage_traits::CommitmentData;
+use uuid::Uuid;
+
+/// Outcome of checking a commitment against the transactions it references
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerificationReport {
+    pub commitment_id: Uuid,
+    pub valid: bool,
+    pub merkle_root_valid: bool,
+    /// Count, gas and fee totals match the transactions
+    pub totals_valid: bool,
+    pub batch_hash_valid: bool,
+    pub signature_valid: bool,
+    /// Address the signature recovers to, if it is well formed
+    pub signer: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// Check `commitment` for batch `commitment_id` against `transactions`, in
+/// batch order.
+///
+/// The merkle root and totals are recomputed from the transactions, the
+/// batch hash from the commitment's own fields, and `proof_data` must be
+/// `relayer`'s signature over that hash. A secp256k1 signature over any other
+/// digest still recovers some key, so the signer is only meaningful compared
+/// against the relayer the counterparty expects. Failures are listed in the
+/// report rather than returned as errors so a counterparty sees every
+/// problem at once.
+pub fn verify_commitment(
+    commitment_id: &Uuid,
+    commitment: &CommitmentData,
+    transactions: &[Transaction],
+    relayer: &Address,
+) -> Result<VerificationReport> {
+    let mut errors = Vec::new();
+
+    let mut tx_errors = 0;
+    for tx in transactions {
+        match tx.calculate_hash() {
+            Ok(hash) if hash == tx.hash => {}
+            _ => {
+                tx_errors += 1;
+                errors.push(format!("Transaction 0x{} does not match its hash", hex::encode(tx.hash)));
+            }
+        }
+    }
+
+    let totals = Totals::of(transactions)?;
+    let merkle_root_valid = tx_errors == 0 && totals.merkle_root == commitment.merkle_root;
+    if totals.merkle_root != commitment.merkle_root {
+        errors.push(format!(
+            "Merkle root 0x{} does not match recomputed 0x{}",
+            hex::encode(commitment.merkle_root),
+            hex::encode(totals.merkle_root)
+        ));
+    }
+
+    let stated = Totals {
+        merkle_root: commitment.merkle_root,
+        transaction_count: commitment.transaction_count,
+        total_gas_used: commitment.total_gas_used,
+        total_fees: commitment.total_fees,
+    };
+    let totals_valid = stated.transaction_count == totals.transaction_count
+        && stated.total_gas_used == totals.total_gas_used
+        && stated.total_fees == totals.total_fees;
+    if !totals_valid {
+        errors.push(format!(
+            "Commitment states {} transactions, {} gas and {} fees; transactions give {}, {} and {}",
+            stated.transaction_count,
+            stated.total_gas_used,
+            stated.total_fees,
+            totals.transaction_count,
+            totals.total_gas_used,
+            totals.total_fees
+        ));
+    }
+
+    let expected_hash = stated.batch_hash(commitment_id)?;
+    let batch_hash_valid = expected_hash == commitment.batch_hash;
+    if !batch_hash_valid {
+        errors.push("Batch hash does not match the commitment fields".to_string());
+    }
+
+    let signer = recover_relayer(&expected_hash, &commitment.proof_data);
+    let signature_valid = match &signer {
+        Ok(signer) if signer == relayer => true,
+        Ok(signer) => {
+            errors.push(format!(
+                "Batch hash signed by {}, expected relayer {}",
+                signer.to_checksum_hex(),
+                relayer.to_checksum_hex()
+            ));
+            false
+        }
+        Err(e) => {
+            errors.push(format!("Relayer signature: {}", e));
+            false
+        }
+    };
+
+    Ok(VerificationReport {
+        commitment_id: *commitment_id,
+        valid: errors.is_empty(),
+        merkle_root_valid,
+        totals_valid,
+        batch_hash_valid,
+        signature_valid,
+        signer: signer.ok().map(|address| address.to_checksum_hex()),
+        errors,
+    })
+}
+
+/// Decode the transactions `commitment` carries in its payload and verify
+/// the commitment against them.
+///
+/// Errors only if the payload cannot be decoded with its stated encoding.
+pub fn verify_payload(
+    commitment_id: &Uuid,
+    commitment: &CommitmentData,
+    relayer: &Address,
+) -> Result<(Vec<Transaction>, VerificationReport)> {
+    let transactions = codec_for(commitment.payload_encoding)
+        .decode(&commitment.payload)
+        .map_err(|e| anyhow::anyhow!("Undecodable {} payload: {}", commitment.payload_encoding, e))?;
+    let report = verify_commitment(commitment_id, commitment, &transactions, relayer)?;
+    Ok((transactions, report))
+}
+
+fn recover_relayer(batch_hash: &[u8; 32], proof: &[u8]) -> Result<Address> {
+    let scheme = SignatureScheme::of_signature(proof)
+        .ok_or_else(|| anyhow::anyhow!("unrecognized signature of {} bytes", proof.len()))?;
+    let public_key = signature::verify_signature(scheme, batch_hash, proof)?;
+    Ok(Address::from_public_key(&public_key)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_commitment;
+    use crate::codec::{SenderDeltaCodec, ZstdCodec};
+    use blockchain_core::KeyPair;
+    use storage_traits::{PayloadEncoding, RelayerBatch};
+
+    fn committed_batch(key: &KeyPair) -> (RelayerBatch, Vec<Transaction>, CommitmentData, Address) {
+        let txs: Vec<Transaction> = (0..3)
+            .map(|nonce| Transaction::new_transfer([1; 20], [2; 20], 5, nonce, 21_000, 1).unwrap())
+            .collect();
+        let batch = RelayerBatch::new(txs.iter().map(|tx| tx.hash).collect(), "relayer-1".to_string());
+        let commitment = build_commitment(&batch, &txs, &ZstdCodec { inner: SenderDeltaCodec }, key).unwrap();
+        let relayer = Address::from_public_key(&key.public_key()).unwrap();
+        (batch, txs, commitment, relayer)
+    }
+
+    #[test]
+    fn test_valid_commitment() {
+        let key = KeyPair::generate(SignatureScheme::Ed25519);
+        let (batch, txs, commitment, relayer) = committed_batch(&key);
+
+        let report = verify_commitment(&batch.commitment_id, &commitment, &txs, &relayer).unwrap();
+        assert!(report.valid, "{:?}", report.errors);
+        assert_eq!(report.signer, Some(relayer.to_checksum_hex()));
+
+        let report = verify_commitment(&batch.commitment_id, &commitment, &txs, &[9; 20]).unwrap();
+        assert!(!report.signature_valid && report.batch_hash_valid);
+    }
+
+    #[test]
+    fn test_reports_each_failure() {
+        let key = KeyPair::generate(SignatureScheme::Secp256k1);
+        let (batch, txs, commitment, relayer) = committed_batch(&key);
+
+        let report = verify_commitment(&batch.commitment_id, &commitment, &txs[..2], &relayer).unwrap();
+        assert!(!report.merkle_root_valid && !report.totals_valid);
+        assert!(report.batch_hash_valid && report.signature_valid);
+
+        let mut inflated = commitment.clone();
+        inflated.total_fees += 1;
+        let report = verify_commitment(&batch.commitment_id, &inflated, &txs, &relayer).unwrap();
+        assert!(!report.totals_valid && !report.batch_hash_valid && !report.signature_valid);
+        assert_eq!(report.errors.len(), 3);
+
+        let report = verify_commitment(&Uuid::new_v4(), &commitment, &txs, &relayer).unwrap();
+        assert!(!report.batch_hash_valid && !report.valid);
+    }
+
+    #[test]
+    fn test_verify_from_payload() {
+        let key = KeyPair::generate(SignatureScheme::Ed25519);
+        let (batch, txs, commitment, relayer) = committed_batch(&key);
+
+        let (decoded, report) = verify_payload(&batch.commitment_id, &commitment, &relayer).unwrap();
+        assert_eq!(decoded, txs);
+        assert!(report.valid, "{:?}", report.errors);
+
+        // A payload swapped for other transactions no longer matches the root
+        let mut swapped = commitment.clone();
+        swapped.payload = codec_for(commitment.payload_encoding).encode(&txs[..1]).unwrap();
+        let (_, report) = verify_payload(&batch.commitment_id, &swapped, &relayer).unwrap();
+        assert!(!report.merkle_root_valid);
+
+        let mut mislabeled = commitment;
+        mislabeled.payload_encoding = PayloadEncoding::Raw;
+        assert!(verify_payload(&batch.commitment_id, &mislabeled, &relayer).is_err());
+    }
+}
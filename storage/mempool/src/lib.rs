@@ -0,0 +1,14 @@
+// storage/mempool/src/lib.rs
+//! In-memory mempool: pending transactions held by hash, ordered by
+//! effective gas price with each sender's transactions in nonce order,
+//! parked while a gap in those nonces holds them back, and announced to
+//! subscribers as they come and go. Storage lags behind the pool: changes
+//! are queued and written to the Scylla pending table by a background task,
+//! which a restarted node reads back from.
+pub mod mempool;
+pub mod pool;
+pub mod store;
+
+pub use mempool::{spawn_write_behind, Mempool, MempoolConfig, MempoolEvent, RemovalReason};
+pub use pool::{effective_gas_price, Advanced, Inserted, TxPool};
+pub use store::PendingStore;
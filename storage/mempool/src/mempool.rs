@@ -0,0 +1,423 @@
+// storage/mempool/src/mempool.rs
+//! The shared mempool: the pool behind a lock, change notifications and
+//! write-behind persistence.
+//!
+//! Parked transactions are persisted like ready ones, and a transaction is
+//! announced as promoted when the gap before it fills.
+//!
+//! Every change is announced on a broadcast channel; a subscriber that falls
+//! more than `event_capacity` events behind skips the oldest. Each change
+//! also queues a write for its transaction, replacing any write still queued
+//! for it, so a transaction added and removed between flushes costs only a
+//! delete of a row that may not exist. A flush that fails puts its writes
+//! back unless a newer one for the same transaction was queued meanwhile.
+//! Until a flush succeeds, storage lags the pool, and a node that stops
+//! loses whatever was still queued.
+use anyhow::{bail, Result};
+use blockchain_core::{Address, Nonce, Transaction, TxHash, DEFAULT_REPLACEMENT_BUMP_PERCENT};
+use futures::Stream;
+use parking_lot::Mutex;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::pool::{Inserted, TxPool};
+use crate::store::PendingStore;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MempoolConfig {
+    pub max_transactions: usize,
+    /// Transactions a sender may have parked behind a nonce gap
+    pub max_future_per_sender: usize,
+    /// Percent a replacement must raise the gas price by. Keep it at least
+    /// the storage's `pending.replacement_bump_percent`, or storage refuses
+    /// replacements the pool accepted.
+    pub replacement_bump_percent: u64,
+    /// Events buffered for each subscriber
+    pub event_capacity: usize,
+    pub flush_interval: Duration,
+    /// Writes sent to storage per flush
+    pub flush_limit: usize,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            max_transactions: 50_000,
+            max_future_per_sender: 64,
+            replacement_bump_percent: DEFAULT_REPLACEMENT_BUMP_PERCENT,
+            event_capacity: 1_024,
+            flush_interval: Duration::from_secs(1),
+            flush_limit: 500,
+        }
+    }
+}
+
+impl MempoolConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_transactions == 0 {
+            bail!("Mempool must hold at least one transaction");
+        }
+        if self.max_future_per_sender > self.max_transactions {
+            bail!("Mempool cannot park more transactions per sender than it holds");
+        }
+        if self.event_capacity == 0 {
+            bail!("Mempool event capacity must be at least 1");
+        }
+        if self.flush_limit == 0 {
+            bail!("Mempool flush limit must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalReason {
+    /// Handed out by `take_best`
+    Taken,
+    /// Removed by a caller without its nonce being used, e.g. on eviction
+    Dropped,
+    /// Superseded by a transaction with the same nonce paying the replacement bump
+    Replaced,
+    /// Its nonce was used on chain
+    Stale,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MempoolEvent {
+    /// Added ready to be taken
+    Added(TxHash),
+    /// Added behind a nonce gap
+    Parked(TxHash),
+    /// A parked transaction became ready
+    Promoted(TxHash),
+    Removed { tx_hash: TxHash, reason: RemovalReason },
+}
+
+#[derive(Debug, Clone)]
+enum PendingWrite {
+    Add(Transaction),
+    Remove,
+}
+
+pub struct Mempool {
+    config: MempoolConfig,
+    pool: Mutex<TxPool>,
+    events: broadcast::Sender<MempoolEvent>,
+    /// At most one write per transaction, the latest
+    writes: Mutex<HashMap<TxHash, PendingWrite>>,
+}
+
+impl Mempool {
+    pub fn new(config: MempoolConfig) -> Result<Self> {
+        config.validate()?;
+        let (events, _) = broadcast::channel(config.event_capacity);
+        let pool = TxPool::new(config.max_transactions, config.max_future_per_sender)
+            .with_replacement_bump(config.replacement_bump_percent);
+        Ok(Self { pool: Mutex::new(pool), events, writes: Mutex::default(), config })
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pool.lock().is_empty()
+    }
+
+    /// Transactions parked behind a nonce gap
+    pub fn parked(&self) -> usize {
+        self.pool.lock().parked()
+    }
+
+    pub fn contains(&self, tx_hash: &TxHash) -> bool {
+        self.pool.lock().contains(tx_hash)
+    }
+
+    pub fn get(&self, tx_hash: &TxHash) -> Option<Transaction> {
+        self.pool.lock().get(tx_hash).cloned()
+    }
+
+    /// Writes not yet flushed to storage
+    pub fn unflushed(&self) -> usize {
+        self.writes.lock().len()
+    }
+
+    /// Add `tx`, replacing a pending transaction of the same sender and nonce
+    /// if `tx` raises the gas price by `replacement_bump_percent`. `account_nonce` is the sender's next nonce
+    /// on chain; a transaction past a gap in its sender's nonces is parked.
+    pub fn insert(&self, tx: Transaction, account_nonce: Nonce) -> Result<()> {
+        let tx_hash = tx.hash;
+        // Writes are queued under the pool lock so they reach storage in the pool's order
+        let mut pool = self.pool.lock();
+        let inserted = pool.insert(tx.clone(), account_nonce)?;
+        let mut writes = self.writes.lock();
+        if let Some(replaced) = inserted.replaced {
+            writes.insert(replaced.hash, PendingWrite::Remove);
+            self.announce(MempoolEvent::Removed { tx_hash: replaced.hash, reason: RemovalReason::Replaced });
+        }
+        writes.insert(tx_hash, PendingWrite::Add(tx));
+        self.announce(if inserted.parked { MempoolEvent::Parked(tx_hash) } else { MempoolEvent::Added(tx_hash) });
+        for promoted in inserted.promoted {
+            self.announce(MempoolEvent::Promoted(promoted));
+        }
+        Ok(())
+    }
+
+    /// Record that `sender`'s account nonce reached `account_nonce` on chain,
+    /// e.g. after a block, dropping its transactions below it and promoting
+    /// parked ones that are now ready. Returns the dropped transactions.
+    pub fn advance_account(&self, sender: &Address, account_nonce: Nonce) -> Vec<Transaction> {
+        let mut pool = self.pool.lock();
+        let advanced = pool.advance_account(sender, account_nonce);
+        let mut writes = self.writes.lock();
+        for tx in &advanced.stale {
+            writes.insert(tx.hash, PendingWrite::Remove);
+            self.announce(MempoolEvent::Removed { tx_hash: tx.hash, reason: RemovalReason::Stale });
+        }
+        for promoted in advanced.promoted {
+            self.announce(MempoolEvent::Promoted(promoted));
+        }
+        advanced.stale
+    }
+
+    /// Remove a transaction whose nonce was not used; the sender's later ones
+    /// are parked until a transaction with that nonce arrives again
+    pub fn remove(&self, tx_hash: &TxHash) -> Option<Transaction> {
+        let mut pool = self.pool.lock();
+        let tx = pool.remove(tx_hash)?;
+        self.writes.lock().insert(*tx_hash, PendingWrite::Remove);
+        self.announce(MempoolEvent::Removed { tx_hash: *tx_hash, reason: RemovalReason::Dropped });
+        Some(tx)
+    }
+
+    /// Remove and return up to `limit` transactions, best first; each sender's
+    /// come out in nonce order
+    pub fn take_best(&self, limit: usize) -> Vec<Transaction> {
+        let mut pool = self.pool.lock();
+        let taken: Vec<Transaction> = std::iter::from_fn(|| pool.pop_best()).take(limit).collect();
+        let mut writes = self.writes.lock();
+        for tx in &taken {
+            writes.insert(tx.hash, PendingWrite::Remove);
+            self.announce(MempoolEvent::Removed { tx_hash: tx.hash, reason: RemovalReason::Taken });
+        }
+        taken
+    }
+
+    /// Changes from now on; the stream ends when the mempool is dropped
+    pub fn subscribe(&self) -> impl Stream<Item = MempoolEvent> {
+        futures::stream::unfold(self.events.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::warn!(missed, "mempool subscriber fell behind, skipped events");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    /// Load up to `limit` transactions persisted before a restart, parking
+    /// them against their senders' current account nonces. They are already
+    /// stored, so no writes are queued beyond deleting any a stored
+    /// replacement supersedes; ones the pool rejects, such as those whose
+    /// nonce was used meanwhile, are skipped. Returns how many were loaded.
+    pub async fn restore(&self, store: &dyn PendingStore, limit: i32) -> Result<usize> {
+        let mut stored = store.load_pending(limit).await?;
+        // Lowest nonces first, so each sender's transactions are ready as soon as they can be
+        stored.sort_by_key(|tx| (tx.sender(), tx.nonce));
+        let mut account_nonces: HashMap<Address, Nonce> = HashMap::new();
+        for tx in &stored {
+            if let Entry::Vacant(entry) = account_nonces.entry(tx.sender()) {
+                let account_nonce = store.account_nonce(entry.key()).await?;
+                entry.insert(account_nonce);
+            }
+        }
+
+        let mut pool = self.pool.lock();
+        let mut restored = 0;
+        for tx in stored {
+            let tx_hash = tx.hash;
+            let account_nonce = account_nonces[&tx.sender()];
+            match pool.insert(tx, account_nonce) {
+                Ok(Inserted { replaced: None, .. }) => restored += 1,
+                Ok(Inserted { replaced: Some(replaced), .. }) => {
+                    self.writes.lock().insert(replaced.hash, PendingWrite::Remove);
+                }
+                Err(e) => tracing::debug!(tx_hash = %hex::encode(tx_hash), error = %e, "skipped stored transaction"),
+            }
+        }
+        Ok(restored)
+    }
+
+    /// Send up to `flush_limit` queued writes to `store`, returning how many
+    /// were written
+    pub async fn flush_once(&self, store: &dyn PendingStore) -> Result<usize> {
+        let batch: Vec<(TxHash, PendingWrite)> = {
+            let mut writes = self.writes.lock();
+            let hashes: Vec<TxHash> = writes.keys().take(self.config.flush_limit).copied().collect();
+            hashes.into_iter().filter_map(|hash| writes.remove(&hash).map(|write| (hash, write))).collect()
+        };
+
+        for (sent, (tx_hash, write)) in batch.iter().enumerate() {
+            let result = match write {
+                PendingWrite::Add(tx) => store.add_pending(tx).await,
+                PendingWrite::Remove => store.remove_pending(tx_hash).await,
+            };
+            if let Err(e) = result {
+                let mut writes = self.writes.lock();
+                for (tx_hash, write) in &batch[sent..] {
+                    writes.entry(*tx_hash).or_insert_with(|| write.clone());
+                }
+                return Err(e);
+            }
+        }
+        Ok(batch.len())
+    }
+
+    fn announce(&self, event: MempoolEvent) {
+        // Fails only when nobody is subscribed
+        let _ = self.events.send(event);
+    }
+}
+
+/// Flush the mempool's writes to `store` every `flush_interval` of its config
+pub fn spawn_write_behind(mempool: Arc<Mempool>, store: Arc<dyn PendingStore>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(mempool.config.flush_interval);
+        loop {
+            ticker.tick().await;
+            match mempool.flush_once(store.as_ref()).await {
+                Ok(written) if written > 0 => tracing::debug!(written, "flushed mempool writes"),
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, unflushed = mempool.unflushed(), "mempool flush failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use futures::StreamExt;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        rows: Mutex<HashMap<TxHash, Transaction>>,
+        nonces: Mutex<HashMap<Address, Nonce>>,
+        failures: Mutex<u32>,
+    }
+
+    impl MemoryStore {
+        fn fail(&self) -> Result<()> {
+            let mut failures = self.failures.lock();
+            if *failures > 0 {
+                *failures -= 1;
+                bail!("Write timed out");
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl PendingStore for MemoryStore {
+        async fn add_pending(&self, tx: &Transaction) -> Result<()> {
+            self.fail()?;
+            self.rows.lock().insert(tx.hash, tx.clone());
+            Ok(())
+        }
+
+        async fn remove_pending(&self, tx_hash: &TxHash) -> Result<()> {
+            self.fail()?;
+            self.rows.lock().remove(tx_hash);
+            Ok(())
+        }
+
+        async fn load_pending(&self, limit: i32) -> Result<Vec<Transaction>> {
+            Ok(self.rows.lock().values().take(limit as usize).cloned().collect())
+        }
+
+        async fn account_nonce(&self, address: &Address) -> Result<Nonce> {
+            Ok(self.nonces.lock().get(address).copied().unwrap_or(0))
+        }
+    }
+
+    fn transfer(from: u8, nonce: u64, gas_price: u64) -> Transaction {
+        Transaction::new_transfer([from; 20], [9; 20], 10, nonce, 21_000, gas_price).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_announces_changes_and_persists_behind() {
+        let mempool = Mempool::new(MempoolConfig::default()).unwrap();
+        let store = MemoryStore::default();
+        let mut events = Box::pin(mempool.subscribe());
+
+        let (a, b, c) = (transfer(1, 0, 5), transfer(2, 0, 7), transfer(3, 0, 1));
+        for tx in [a.clone(), b.clone(), c.clone()] {
+            mempool.insert(tx, 0).unwrap();
+        }
+        // Removed before any flush, so only a delete reaches storage
+        assert_eq!(mempool.remove(&c.hash).map(|tx| tx.hash), Some(c.hash));
+        let bumped = transfer(1, 0, 9);
+        mempool.insert(bumped.clone(), 0).unwrap();
+        assert_eq!(mempool.unflushed(), 4);
+
+        let mut seen = Vec::new();
+        for _ in 0..6 {
+            seen.push(events.next().await.unwrap());
+        }
+        assert_eq!(seen[3], MempoolEvent::Removed { tx_hash: c.hash, reason: RemovalReason::Dropped });
+        assert_eq!(seen[4], MempoolEvent::Removed { tx_hash: a.hash, reason: RemovalReason::Replaced });
+        assert_eq!(seen[5], MempoolEvent::Added(bumped.hash));
+
+        // A failed flush keeps its writes for the next one
+        *store.failures.lock() = 1;
+        assert!(mempool.flush_once(&store).await.is_err());
+        assert_eq!(mempool.unflushed(), 4);
+        assert_eq!(mempool.flush_once(&store).await.unwrap(), 4);
+        let mut stored: Vec<TxHash> = store.rows.lock().keys().copied().collect();
+        stored.sort();
+        let mut expected = vec![b.hash, bumped.hash];
+        expected.sort();
+        assert_eq!(stored, expected);
+
+        let taken: Vec<TxHash> = mempool.take_best(10).iter().map(|tx| tx.hash).collect();
+        assert_eq!(taken, vec![bumped.hash, b.hash]);
+        assert_eq!(mempool.flush_once(&store).await.unwrap(), 2);
+        assert!(store.rows.lock().is_empty());
+
+        // A transaction past a nonce gap waits until the gap fills
+        let (d1, d2) = (transfer(4, 1, 3), transfer(4, 2, 3));
+        mempool.insert(d2.clone(), 1).unwrap();
+        assert_eq!((mempool.parked(), mempool.take_best(10)), (1, Vec::new()));
+        mempool.insert(d1.clone(), 1).unwrap();
+        assert_eq!(mempool.parked(), 0);
+        for _ in 0..4 {
+            seen.push(events.next().await.unwrap());
+        }
+        assert_eq!(seen[8..], [MempoolEvent::Parked(d2.hash), MempoolEvent::Added(d1.hash)]);
+        assert_eq!(events.next().await, Some(MempoolEvent::Promoted(d2.hash)));
+
+        // Another node's block used nonce 1, so d1 is dropped and d2 is next
+        assert_eq!(mempool.advance_account(&d1.sender(), 2), vec![d1.clone()]);
+        let stale = MempoolEvent::Removed { tx_hash: d1.hash, reason: RemovalReason::Stale };
+        assert_eq!(events.next().await, Some(stale));
+        assert_eq!(mempool.take_best(10), vec![d2]);
+        mempool.flush_once(&store).await.unwrap();
+        assert!(store.rows.lock().is_empty());
+
+        // A restarted node picks up what was stored, parking what follows a gap
+        let e3 = transfer(5, 3, 1);
+        store.rows.lock().extend([(b.hash, b.clone()), (a.hash, a.clone()), (e3.hash, e3.clone())]);
+        store.nonces.lock().extend([(a.sender(), 1), (e3.sender(), 2)]);
+        let restarted = Mempool::new(MempoolConfig::default()).unwrap();
+        assert_eq!(restarted.restore(&store, 100).await.unwrap(), 2);
+        assert_eq!((restarted.get(&b.hash), restarted.unflushed()), (Some(b), 0));
+        assert_eq!((restarted.contains(&a.hash), restarted.parked()), (false, 1));
+    }
+}
@@ -0,0 +1,424 @@
+// storage/mempool/src/pool.rs
+//! Pending transactions indexed for building batches.
+//!
+//! Each sender's transactions are split by nonce. Ready ones run without a
+//! gap from the nonce the sender's next transaction must carry; the rest are
+//! parked as future transactions until the gap before them fills, either by
+//! a transaction arriving with the missing nonce or by the account nonce
+//! moving past it. Only a sender's first ready transaction can be taken, so
+//! the heap ranks those alone: highest effective gas price first, ties going
+//! to the transaction that arrived first. Heap entries are not removed when
+//! their transaction leaves the pool or stops being its sender's first; they
+//! are skipped when they reach the top, and the heap is rebuilt once they
+//! pile up.
+use anyhow::{bail, Result};
+use blockchain_core::{
+    min_replacement_gas_price, Address, Amount, Nonce, Transaction, TransactionType, TxHash,
+    DEFAULT_REPLACEMENT_BUMP_PERCENT,
+};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+
+/// Heap entries allowed beyond two per sender before the heap is rebuilt
+const STALE_ENTRY_SLACK: usize = 64;
+
+/// Price per gas a transaction competes on. Without a base fee that is its
+/// gas price; a base fee paid by every transaction would not reorder them.
+pub fn effective_gas_price(tx: &Transaction) -> Amount {
+    tx.gas_price
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Ranked {
+    gas_price: Amount,
+    arrival: u64,
+    tx_hash: TxHash,
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.gas_price
+            .cmp(&other.gas_price)
+            .then_with(|| other.arrival.cmp(&self.arrival))
+            .then_with(|| other.tx_hash.cmp(&self.tx_hash))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Pooled {
+    tx: Transaction,
+    arrival: u64,
+}
+
+/// What an insert did besides adding the transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inserted {
+    /// The pending transaction with the same sender and nonce it replaced
+    pub replaced: Option<Transaction>,
+    /// Whether it was parked behind a nonce gap
+    pub parked: bool,
+    /// Parked transactions it made ready, in nonce order
+    pub promoted: Vec<TxHash>,
+}
+
+/// What moving a sender's account nonce forward did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Advanced {
+    /// Transactions whose nonce the account has already used
+    pub stale: Vec<Transaction>,
+    /// Parked transactions that became ready, in nonce order
+    pub promoted: Vec<TxHash>,
+}
+
+/// The pool itself, without notifications or persistence
+#[derive(Debug, Default)]
+pub struct TxPool {
+    max_transactions: usize,
+    max_future_per_sender: usize,
+    replacement_bump_percent: u64,
+    transactions: HashMap<TxHash, Pooled>,
+    ready: HashMap<Address, BTreeMap<Nonce, TxHash>>,
+    future: HashMap<Address, BTreeMap<Nonce, TxHash>>,
+    /// Nonce each sender with pending transactions must use next
+    next_nonces: HashMap<Address, Nonce>,
+    heap: BinaryHeap<Ranked>,
+    arrivals: u64,
+}
+
+impl TxPool {
+    pub fn new(max_transactions: usize, max_future_per_sender: usize) -> Self {
+        Self {
+            max_transactions,
+            max_future_per_sender,
+            replacement_bump_percent: DEFAULT_REPLACEMENT_BUMP_PERCENT,
+            ..Default::default()
+        }
+    }
+
+    /// Require replacements to raise the gas price by `percent`
+    pub fn with_replacement_bump(mut self, percent: u64) -> Self {
+        self.replacement_bump_percent = percent;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Transactions parked behind a nonce gap
+    pub fn parked(&self) -> usize {
+        self.future.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn contains(&self, tx_hash: &TxHash) -> bool {
+        self.transactions.contains_key(tx_hash)
+    }
+
+    pub fn is_parked(&self, tx_hash: &TxHash) -> bool {
+        self.get(tx_hash)
+            .and_then(|tx| self.future.get(&tx.sender()).map(|nonces| nonces.get(&tx.nonce) == Some(tx_hash)))
+            .unwrap_or(false)
+    }
+
+    pub fn get(&self, tx_hash: &TxHash) -> Option<&Transaction> {
+        self.transactions.get(tx_hash).map(|pooled| &pooled.tx)
+    }
+
+    /// Add `tx`. `account_nonce` is the sender's next nonce on chain; it is
+    /// used only while the pool holds nothing from the sender, since the
+    /// pool's own count runs ahead of the chain once transactions are taken.
+    /// A transaction with the nonce of one already pending replaces it only
+    /// by raising the gas price by the replacement bump.
+    pub fn insert(&mut self, tx: Transaction, account_nonce: Nonce) -> Result<Inserted> {
+        if matches!(tx.tx_type, TransactionType::Coinbase { .. }) {
+            bail!("Coinbase transactions are never pending");
+        }
+        if self.contains(&tx.hash) {
+            bail!("Transaction 0x{} is already pending", hex::encode(tx.hash));
+        }
+
+        let sender = tx.sender();
+        let next_nonce = self.next_nonces.get(&sender).copied().unwrap_or(account_nonce);
+        if tx.nonce < next_nonce {
+            bail!("Transaction 0x{} reuses nonce {}, sender is at {}", hex::encode(tx.hash), tx.nonce, next_nonce);
+        }
+
+        let existing = [&self.ready, &self.future]
+            .into_iter()
+            .find_map(|by_sender| by_sender.get(&sender).and_then(|nonces| nonces.get(&tx.nonce)))
+            .copied();
+        if let Some(existing) = existing {
+            return self.replace(existing, tx);
+        }
+        if self.len() >= self.max_transactions {
+            bail!("Mempool is full ({} transactions)", self.max_transactions);
+        }
+
+        let ready_end = next_nonce + self.ready.get(&sender).map_or(0, BTreeMap::len) as Nonce;
+        let parked = tx.nonce > ready_end;
+        if parked && self.future.get(&sender).map_or(0, BTreeMap::len) >= self.max_future_per_sender {
+            bail!(
+                "Transaction 0x{} has nonce {} past a gap at {} and its sender already has {} parked",
+                hex::encode(tx.hash),
+                tx.nonce,
+                ready_end,
+                self.max_future_per_sender
+            );
+        }
+
+        let (tx_hash, nonce) = (tx.hash, tx.nonce);
+        self.next_nonces.insert(sender, next_nonce);
+        self.arrivals += 1;
+        self.transactions.insert(tx_hash, Pooled { tx, arrival: self.arrivals });
+        if parked {
+            self.future.entry(sender).or_default().insert(nonce, tx_hash);
+            return Ok(Inserted { parked, ..Default::default() });
+        }
+        self.push_ready(sender, nonce, tx_hash);
+        Ok(Inserted { promoted: self.promote(&sender), ..Default::default() })
+    }
+
+    /// Remove a transaction without its nonce being used, e.g. on eviction.
+    /// The sender's later ready transactions now follow a gap and are parked.
+    /// Use `advance_account` for transactions the chain included.
+    pub fn remove(&mut self, tx_hash: &TxHash) -> Option<Transaction> {
+        let pooled = self.transactions.remove(tx_hash)?;
+        let (sender, nonce) = (pooled.tx.sender(), pooled.tx.nonce);
+        if let Some(nonces) = self.future.get_mut(&sender) {
+            if nonces.get(&nonce) == Some(tx_hash) {
+                nonces.remove(&nonce);
+                self.forget_if_idle(&sender);
+                return Some(pooled.tx);
+            }
+        }
+        if let Some(nonces) = self.ready.get_mut(&sender) {
+            let mut behind = nonces.split_off(&nonce);
+            behind.remove(&nonce);
+            if !behind.is_empty() {
+                self.future.entry(sender).or_default().extend(behind);
+            }
+        }
+        self.forget_if_idle(&sender);
+        Some(pooled.tx)
+    }
+
+    /// Record that `sender`'s account nonce reached `account_nonce`, dropping
+    /// transactions below it and promoting parked ones it makes ready. Does
+    /// nothing for a nonce the pool is already at or past.
+    pub fn advance_account(&mut self, sender: &Address, account_nonce: Nonce) -> Advanced {
+        let mut advanced = Advanced::default();
+        match self.next_nonces.get_mut(sender) {
+            Some(next_nonce) if *next_nonce < account_nonce => *next_nonce = account_nonce,
+            _ => return advanced,
+        }
+
+        for by_sender in [&mut self.ready, &mut self.future] {
+            if let Some(nonces) = by_sender.get_mut(sender) {
+                let current = nonces.split_off(&account_nonce);
+                let used = std::mem::replace(nonces, current);
+                advanced.stale.extend(used.values().filter_map(|hash| self.transactions.remove(hash)).map(|p| p.tx));
+            }
+        }
+        if let Some(front) = self.ready_front(sender) {
+            self.rank(front);
+        }
+        advanced.promoted = self.promote(sender);
+        self.forget_if_idle(sender);
+        advanced
+    }
+
+    /// Remove and return the best transaction that can be taken now. Its
+    /// nonce counts as used, so the sender's next ready transaction follows.
+    pub fn pop_best(&mut self) -> Option<Transaction> {
+        while let Some(top) = self.heap.pop() {
+            let Some(sender) = self.get(&top.tx_hash).map(Transaction::sender) else {
+                continue;
+            };
+            if self.ready_front(&sender) != Some(top.tx_hash) {
+                continue;
+            }
+            let pooled = self.transactions.remove(&top.tx_hash)?;
+            if let Some(nonces) = self.ready.get_mut(&sender) {
+                nonces.remove(&pooled.tx.nonce);
+            }
+            self.next_nonces.insert(sender, pooled.tx.nonce + 1);
+            if let Some(front) = self.ready_front(&sender) {
+                self.rank(front);
+            }
+            self.forget_if_idle(&sender);
+            return Some(pooled.tx);
+        }
+        None
+    }
+
+    fn replace(&mut self, existing: TxHash, tx: Transaction) -> Result<Inserted> {
+        let current = self.transactions[&existing].tx.gas_price;
+        let required = min_replacement_gas_price(current, self.replacement_bump_percent);
+        if tx.gas_price < required {
+            bail!(
+                "Transaction 0x{} replaces nonce {} at gas price {}, below the {} a replacement of {} needs",
+                hex::encode(tx.hash),
+                tx.nonce,
+                tx.gas_price,
+                required,
+                current
+            );
+        }
+
+        let (sender, nonce, tx_hash) = (tx.sender(), tx.nonce, tx.hash);
+        let replaced = self.transactions.remove(&existing).map(|pooled| pooled.tx);
+        self.arrivals += 1;
+        self.transactions.insert(tx_hash, Pooled { tx, arrival: self.arrivals });
+        let parked = self.future.get(&sender).is_some_and(|nonces| nonces.contains_key(&nonce));
+        let by_sender = if parked { &mut self.future } else { &mut self.ready };
+        by_sender.entry(sender).or_default().insert(nonce, tx_hash);
+        if self.ready_front(&sender) == Some(tx_hash) {
+            self.rank(tx_hash);
+        }
+        Ok(Inserted { replaced, parked, promoted: Vec::new() })
+    }
+
+    fn push_ready(&mut self, sender: Address, nonce: Nonce, tx_hash: TxHash) {
+        self.ready.entry(sender).or_default().insert(nonce, tx_hash);
+        if self.ready_front(&sender) == Some(tx_hash) {
+            self.rank(tx_hash);
+        }
+    }
+
+    /// Move parked transactions that now continue the ready run into it
+    fn promote(&mut self, sender: &Address) -> Vec<TxHash> {
+        let mut promoted = Vec::new();
+        loop {
+            let next_nonce = self.next_nonces.get(sender).copied().unwrap_or_default();
+            let ready_end = next_nonce + self.ready.get(sender).map_or(0, BTreeMap::len) as Nonce;
+            let Some(tx_hash) = self.future.get_mut(sender).and_then(|nonces| nonces.remove(&ready_end)) else {
+                break;
+            };
+            self.push_ready(*sender, ready_end, tx_hash);
+            promoted.push(tx_hash);
+        }
+        promoted
+    }
+
+    fn ready_front(&self, sender: &Address) -> Option<TxHash> {
+        self.ready.get(sender).and_then(|nonces| nonces.values().next()).copied()
+    }
+
+    /// Drop a sender's empty maps, and its nonce once nothing of it is pending
+    fn forget_if_idle(&mut self, sender: &Address) {
+        for by_sender in [&mut self.ready, &mut self.future] {
+            if by_sender.get(sender).is_some_and(BTreeMap::is_empty) {
+                by_sender.remove(sender);
+            }
+        }
+        if !self.ready.contains_key(sender) && !self.future.contains_key(sender) {
+            self.next_nonces.remove(sender);
+        }
+    }
+
+    fn rank(&mut self, tx_hash: TxHash) {
+        let pooled = &self.transactions[&tx_hash];
+        self.heap.push(Ranked { gas_price: effective_gas_price(&pooled.tx), arrival: pooled.arrival, tx_hash });
+        if self.heap.len() > 2 * self.ready.len() + STALE_ENTRY_SLACK {
+            self.rebuild_heap();
+        }
+    }
+
+    fn rebuild_heap(&mut self) {
+        let fronts: Vec<TxHash> = self.ready.values().filter_map(|nonces| nonces.values().next()).copied().collect();
+        self.heap = fronts
+            .into_iter()
+            .map(|tx_hash| {
+                let pooled = &self.transactions[&tx_hash];
+                Ranked { gas_price: effective_gas_price(&pooled.tx), arrival: pooled.arrival, tx_hash }
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(from: u8, nonce: Nonce, gas_price: Amount) -> Transaction {
+        Transaction::new_transfer([from; 20], [9; 20], 10, nonce, 21_000, gas_price).unwrap()
+    }
+
+    #[test]
+    fn test_pops_by_gas_price_in_sender_nonce_order() {
+        let mut pool = TxPool::new(4, 4);
+        let (a0, a1, b0) = (transfer(1, 0, 2), transfer(1, 1, 50), transfer(2, 0, 10));
+        // Alice's pricey nonce 1 arrives before her nonce 0
+        for tx in [a1.clone(), b0.clone(), a0.clone()] {
+            pool.insert(tx, 0).unwrap();
+        }
+        assert_eq!(pool.get(&a1.hash).map(|tx| tx.nonce), Some(1));
+        assert!(pool.insert(a0.clone(), 0).is_err());
+
+        // Replacing a nonce needs a 10% higher price; the pool is then full
+        assert!(pool.insert(transfer(2, 0, 10), 0).is_err());
+        let b0_bumped = transfer(2, 0, 11);
+        assert_eq!(pool.insert(b0_bumped.clone(), 0).unwrap().replaced.map(|tx| tx.hash), Some(b0.hash));
+        pool.insert(transfer(3, 0, 1), 0).unwrap();
+        assert!(pool.insert(transfer(4, 0, 100), 0).is_err());
+
+        let order: Vec<TxHash> = std::iter::from_fn(|| pool.pop_best()).map(|tx| tx.hash).collect();
+        assert_eq!(order[..3], [b0_bumped.hash, a0.hash, a1.hash]);
+        assert_eq!(order.len(), 4);
+        assert!(pool.is_empty());
+
+        // Removing a sender's first transaction parks the ones behind it
+        for tx in [a0.clone(), a1.clone(), b0.clone()] {
+            pool.insert(tx, 0).unwrap();
+        }
+        assert_eq!(pool.remove(&a0.hash).map(|tx| tx.hash), Some(a0.hash));
+        assert!(pool.is_parked(&a1.hash));
+        assert_eq!(pool.pop_best().map(|tx| tx.hash), Some(b0.hash));
+        assert_eq!(pool.pop_best(), None);
+    }
+
+    #[test]
+    fn test_parks_transactions_until_the_nonce_gap_fills() {
+        let mut pool = TxPool::new(10, 2);
+        let (a5, a6, a8) = (transfer(1, 5, 90), transfer(1, 6, 80), transfer(1, 8, 70));
+        assert!(pool.insert(transfer(1, 2, 1), 3).is_err());
+        for tx in [a5.clone(), a6.clone()] {
+            assert!(pool.insert(tx, 4).unwrap().parked);
+        }
+        assert!(pool.insert(a8.clone(), 4).is_err());
+        assert_eq!(pool.parked(), 2);
+        assert_eq!(pool.pop_best(), None);
+
+        // Nonce 4 fills the gap; nonce 7 then joins the run and frees a slot for 8
+        let a4 = transfer(1, 4, 1);
+        assert_eq!(pool.insert(a4.clone(), 4).unwrap().promoted, vec![a5.hash, a6.hash]);
+        pool.insert(transfer(1, 7, 1), 4).unwrap();
+        assert!(pool.insert(a8.clone(), 4).unwrap().promoted.is_empty());
+        assert!(!pool.is_parked(&a8.hash));
+        assert_eq!(pool.pop_best().map(|tx| tx.hash), Some(a4.hash));
+
+        // Another node's block used nonces 5 and 6
+        let advanced = pool.advance_account(&a5.sender(), 7);
+        let stale: Vec<TxHash> = advanced.stale.iter().map(|tx| tx.hash).collect();
+        assert_eq!(stale, vec![a5.hash, a6.hash]);
+        let order: Vec<Nonce> = std::iter::from_fn(|| pool.pop_best()).map(|tx| tx.nonce).collect();
+        assert_eq!(order, vec![7, 8]);
+        assert!(pool.is_empty());
+
+        // A forgotten sender starts again from the account nonce it arrives with
+        let b1 = transfer(2, 1, 5);
+        assert!(pool.insert(b1.clone(), 0).unwrap().parked);
+        assert_eq!(pool.advance_account(&b1.sender(), 1).promoted, vec![b1.hash]);
+        assert_eq!(pool.pop_best().map(|tx| tx.hash), Some(b1.hash));
+    }
+}
@@ -0,0 +1,39 @@
+// storage/mempool/src/store.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::{Address, Nonce, Transaction, TxHash};
+use scylla_adapter::ScyllaAdapter;
+
+/// Where the mempool persists its transactions
+#[async_trait]
+pub trait PendingStore: Send + Sync {
+    async fn add_pending(&self, tx: &Transaction) -> Result<()>;
+
+    /// Succeeds when the transaction was never stored
+    async fn remove_pending(&self, tx_hash: &TxHash) -> Result<()>;
+
+    /// Up to `limit` stored transactions, in no particular order
+    async fn load_pending(&self, limit: i32) -> Result<Vec<Transaction>>;
+
+    /// Next nonce of `address` on chain, 0 for an unknown account
+    async fn account_nonce(&self, address: &Address) -> Result<Nonce>;
+}
+
+#[async_trait]
+impl PendingStore for ScyllaAdapter {
+    async fn add_pending(&self, tx: &Transaction) -> Result<()> {
+        self.add_pending_transaction(tx).await
+    }
+
+    async fn remove_pending(&self, tx_hash: &TxHash) -> Result<()> {
+        self.remove_pending_transaction(tx_hash).await
+    }
+
+    async fn load_pending(&self, limit: i32) -> Result<Vec<Transaction>> {
+        self.get_pending_transactions(limit).await
+    }
+
+    async fn account_nonce(&self, address: &Address) -> Result<Nonce> {
+        Ok(self.get_account(address).await?.map_or(0, |account| account.nonce))
+    }
+}
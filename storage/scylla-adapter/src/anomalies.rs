@@ -0,0 +1,263 @@
+// storage/scylla-adapter/src/anomalies.rs
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use storage_traits::StorageOperation;
+
+use crate::model::{ChainAnomaly, ChainMetric, ChainStatsPeriod};
+use crate::scylla_config::AnomalyDetectionConfig;
+use crate::{queries, ScyllaAdapter};
+
+/// Receiver for newly flagged anomalies, implemented by the alerting engine
+#[async_trait::async_trait]
+pub trait AnomalyAlerts: Send + Sync {
+    async fn raise(&self, anomaly: &ChainAnomaly) -> Result<()>;
+}
+
+impl ScyllaAdapter {
+    /// Score the last `lookback_hours` of complete hourly stats, record
+    /// anomalies in the `anomalies` table and raise the new ones.
+    ///
+    /// The trend is rebuilt from the stored hours on every run. Anomalies are
+    /// inserted with a lightweight transaction, so a period flagged by an
+    /// earlier run is not raised again.
+    pub async fn detect_chain_anomalies(
+        &self,
+        now: DateTime<Utc>,
+        alerts: &dyn AnomalyAlerts,
+    ) -> Result<Vec<ChainAnomaly>> {
+        self.fault_point(StorageOperation::DetectAnomalies).await?;
+        let config = &self.config.anomaly_detection;
+        let session = self.session_for(StorageOperation::DetectAnomalies);
+
+        let from = now - Duration::hours(config.lookback_hours);
+        let mut hours = Vec::new();
+        let mut day = from.date_naive();
+        while day <= now.date_naive() {
+            hours.extend(self.hourly_chain_stats(day).await?);
+            day += Duration::days(1);
+        }
+        // The current hour is still being filled in
+        hours.retain(|hour| hour.period_start >= from && hour.period_start + Duration::hours(1) <= now);
+        hours.sort_by_key(|hour| hour.period_start);
+
+        let mut raised = Vec::new();
+        for metric in ChainMetric::ALL {
+            for anomaly in detect(config, metric, &hours, now) {
+                let result = session
+                    .query(
+                        queries::INSERT_CHAIN_ANOMALY,
+                        (
+                            metric.to_string(),
+                            anomaly.period_start,
+                            anomaly.observed,
+                            anomaly.expected,
+                            anomaly.z_score,
+                            anomaly.detected_at,
+                        ),
+                    )
+                    .await?;
+                let applied = result.maybe_first_row()?
+                    .as_ref()
+                    .and_then(|row| row.columns[0].as_ref())
+                    .and_then(|col| col.as_boolean())
+                    .unwrap_or(false);
+
+                if applied {
+                    alerts.raise(&anomaly).await?;
+                    raised.push(anomaly);
+                }
+            }
+        }
+
+        Ok(raised)
+    }
+
+    /// Anomalies recorded for `metric` in periods starting at or after `since`, newest first
+    pub async fn get_chain_anomalies(
+        &self,
+        metric: ChainMetric,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ChainAnomaly>> {
+        self.fault_point(StorageOperation::GetAnomalies).await?;
+        let rows = self.session_for(StorageOperation::GetAnomalies)
+            .query(queries::GET_CHAIN_ANOMALIES_SINCE, (metric.to_string(), since))
+            .await?;
+
+        rows.rows.unwrap_or_default()
+            .into_iter()
+            .map(|row| {
+                let timestamp = |i: usize, name: &str| {
+                    row.columns[i].as_ref()
+                        .and_then(|col| col.as_datetime())
+                        .ok_or_else(|| anyhow::anyhow!("Missing {}", name))
+                };
+                let double = |i: usize| row.columns[i].as_ref().and_then(|col| col.as_double()).unwrap_or(0.0);
+
+                Ok(ChainAnomaly {
+                    metric,
+                    period_start: timestamp(0, "period_start")?,
+                    observed: double(1),
+                    expected: double(2),
+                    z_score: double(3),
+                    detected_at: timestamp(4, "detected_at")?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Exponentially weighted mean and variance of one metric
+#[derive(Debug, Clone)]
+pub struct EwmaDetector {
+    alpha: f64,
+    threshold: f64,
+    warmup: usize,
+    mean: f64,
+    variance: f64,
+    observed: usize,
+}
+
+impl EwmaDetector {
+    pub fn new(config: &AnomalyDetectionConfig) -> Self {
+        Self {
+            alpha: config.ewma_alpha,
+            threshold: config.z_threshold,
+            warmup: config.warmup_periods.max(1),
+            mean: 0.0,
+            variance: 0.0,
+            observed: 0,
+        }
+    }
+
+    /// Score `value` against the trend so far, then fold it into the trend.
+    ///
+    /// Returns the expected value and z-score when `value` is anomalous.
+    /// Nothing is flagged until `warmup_periods` values have been seen.
+    pub fn observe(&mut self, value: f64) -> Option<(f64, f64)> {
+        if self.observed == 0 {
+            self.mean = value;
+            self.observed = 1;
+            return None;
+        }
+
+        let expected = self.mean;
+        let diff = value - expected;
+        let flagged = if self.observed >= self.warmup {
+            // A perfectly flat history still flags any departure from it
+            let std_dev = self.variance.sqrt().max(f64::EPSILON * expected.abs().max(1.0));
+            let z_score = diff / std_dev;
+            (z_score.abs() >= self.threshold).then_some((expected, z_score))
+        } else {
+            None
+        };
+
+        let increment = self.alpha * diff;
+        self.mean += increment;
+        self.variance = (1.0 - self.alpha) * (self.variance + diff * increment);
+        self.observed += 1;
+
+        flagged
+    }
+}
+
+/// Value of `metric` for an hourly period, if it has one
+pub fn metric_value(metric: ChainMetric, period: &ChainStatsPeriod) -> Option<f64> {
+    match metric {
+        // An hour without blocks is a stall lasting at least the whole hour
+        ChainMetric::BlockTime if period.total_blocks == 0 => Some(3600.0),
+        ChainMetric::BlockTime => Some(period.avg_block_time),
+        ChainMetric::TxVolume => Some(period.total_transactions as f64),
+        ChainMetric::FeeLevel if period.total_transactions == 0 => None,
+        ChainMetric::FeeLevel => Some(period.total_fees as f64 / period.total_transactions as f64),
+    }
+}
+
+/// Anomalies of `metric` across `periods`, which must be in chronological order
+pub fn detect(
+    config: &AnomalyDetectionConfig,
+    metric: ChainMetric,
+    periods: &[ChainStatsPeriod],
+    detected_at: DateTime<Utc>,
+) -> Vec<ChainAnomaly> {
+    let mut detector = EwmaDetector::new(config);
+
+    periods
+        .iter()
+        .filter_map(|period| {
+            let observed = metric_value(metric, period)?;
+            let (expected, z_score) = detector.observe(observed)?;
+            Some(ChainAnomaly {
+                metric,
+                period_start: period.period_start,
+                observed,
+                expected,
+                z_score,
+                detected_at,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::StatsGranularity;
+    use chrono::TimeZone;
+
+    fn hours(block_times: &[f64]) -> Vec<ChainStatsPeriod> {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        block_times
+            .iter()
+            .enumerate()
+            .map(|(i, &block_time)| ChainStatsPeriod {
+                granularity: StatsGranularity::Hourly,
+                period_start: start + Duration::hours(i as i64),
+                total_blocks: if block_time == 0.0 { 0 } else { (3600.0 / block_time) as u64 },
+                total_transactions: 1_000 + (i as u64 % 3) * 10,
+                total_value: 0,
+                total_fees: 21_000_000,
+                avg_block_time: block_time,
+                avg_tx_per_block: 0.0,
+                network_hash_rate: 0,
+                active_addresses: 0,
+            })
+            .collect()
+    }
+
+    fn config() -> AnomalyDetectionConfig {
+        AnomalyDetectionConfig { warmup_periods: 5, ..AnomalyDetectionConfig::default() }
+    }
+
+    #[test]
+    fn test_detects_block_time_stall() {
+        let mut block_times: Vec<f64> = (0..30).map(|i| 12.0 + (i % 3) as f64 * 0.5).collect();
+        block_times.push(0.0);
+
+        let periods = hours(&block_times);
+        let anomalies = detect(&config(), ChainMetric::BlockTime, &periods, Utc::now());
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].period_start, periods[30].period_start);
+        assert_eq!(anomalies[0].observed, 3600.0);
+        assert!(anomalies[0].z_score > 4.0);
+
+        // Steady transaction volume and fee levels are not flagged
+        assert!(detect(&config(), ChainMetric::TxVolume, &periods, Utc::now()).is_empty());
+        assert!(detect(&config(), ChainMetric::FeeLevel, &periods, Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn test_warmup_suppresses_early_flags() {
+        let mut detector = EwmaDetector::new(&config());
+        for value in [10.0, 11.0, 10.0, 1_000.0] {
+            assert!(detector.observe(value).is_none());
+        }
+
+        let mut flat = EwmaDetector::new(&config());
+        for _ in 0..10 {
+            assert!(flat.observe(5.0).is_none());
+        }
+        let (expected, z_score) = flat.observe(4.0).unwrap();
+        assert_eq!(expected, 5.0);
+        assert!(z_score < 0.0);
+    }
+}
@@ -0,0 +1,246 @@
+// storage/scylla-adapter/src/archive.rs
+use anyhow::{anyhow, Result};
+use blockchain_core::{BlockHeight, TxHash};
+use chrono::Utc;
+use std::io::Read;
+use std::sync::Arc;
+use storage_traits::StorageOperation;
+
+use crate::model::ArchivalReport;
+use crate::relayer_queue::applied;
+use crate::{encryption, format, queries, ScyllaAdapter};
+
+/// `system_config` key holding the next block height to archive
+const ARCHIVE_CHECKPOINT_KEY: &str = "tx_archive_height";
+
+/// Train a zstd dictionary from serialized transaction samples
+pub fn train_dictionary(samples: &[Vec<u8>], max_size: usize) -> Result<Vec<u8>> {
+    if samples.is_empty() {
+        return Err(anyhow!("Cannot train a dictionary without samples"));
+    }
+    Ok(zstd::dict::from_samples(samples, max_size)?)
+}
+
+/// Compress a blob with a trained dictionary
+pub fn compress_with_dictionary(data: &[u8], dictionary: &[u8], level: i32) -> Result<Vec<u8>> {
+    let mut compressor = zstd::bulk::Compressor::with_dictionary(level, dictionary)?;
+    Ok(compressor.compress(data)?)
+}
+
+/// Decompress a blob produced by `compress_with_dictionary`
+pub fn decompress_with_dictionary(data: &[u8], dictionary: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = zstd::stream::read::Decoder::with_dictionary(data, dictionary)?;
+    let mut decompressed = Vec::new();
+    decoder.read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+impl ScyllaAdapter {
+    /// Train a new dictionary from transactions in a block range and store it.
+    ///
+    /// Returns the id of the new dictionary, which `archive_transactions` uses
+    /// for subsequent rows.
+    pub async fn train_archive_dictionary(
+        &self,
+        from_height: BlockHeight,
+        to_height: BlockHeight,
+    ) -> Result<i32> {
+        let archival = &self.config.archival;
+        let mut samples = Vec::new();
+
+        'blocks: for height in from_height..=to_height {
+            if let Some(block) = self.get_block_by_height(height).await? {
+                for tx in &block.transactions {
+                    samples.push(format::encode(tx)?);
+                    if samples.len() >= archival.training_sample_limit {
+                        break 'blocks;
+                    }
+                }
+            }
+        }
+
+        let dictionary = train_dictionary(&samples, archival.dictionary_size)?;
+        let session = self.session_for(StorageOperation::TrainArchiveDictionary);
+
+        // Concurrent trainers may read the same maximum; only one claims each id
+        let mut dict_id = 0;
+        loop {
+            let rows = session.query(queries::GET_MAX_DICTIONARY_ID, ()).await?;
+            let max_id = rows.maybe_first_row()?
+                .as_ref()
+                .and_then(|row| row.columns[0].as_ref())
+                .and_then(|col| col.as_int())
+                .unwrap_or(0);
+            dict_id = dict_id.max(max_id) + 1;
+
+            let result = session
+                .query(
+                    queries::INSERT_DICTIONARY,
+                    (dict_id, dictionary.clone(), Utc::now(), samples.len() as i32),
+                )
+                .await?;
+            if applied(&result) {
+                break;
+            }
+        }
+
+        self.dictionaries.write().await.insert(dict_id, Arc::new(dictionary));
+        Ok(dict_id)
+    }
+
+    /// Recompress transactions of blocks older than the configured age with a dictionary.
+    ///
+    /// Resumes from the last archived height and reports bytes before and after.
+    pub async fn archive_transactions(
+        &self,
+        chain_height: BlockHeight,
+        dict_id: i32,
+    ) -> Result<ArchivalReport> {
+        let archival = &self.config.archival;
+        let mut report = ArchivalReport {
+            dictionary_id: dict_id,
+            ..Default::default()
+        };
+
+        if chain_height < archival.min_age_blocks {
+            return Ok(report);
+        }
+        let up_to = chain_height - archival.min_age_blocks;
+        let dictionary = self.load_dictionary(dict_id).await?;
+        let session = self.session_for(StorageOperation::ArchiveTransactions);
+
+        let mut height = self.archive_checkpoint().await?;
+        while height <= up_to {
+            let block = match self.get_block_by_height(height).await? {
+                Some(block) => block,
+                None => break,
+            };
+
+            for tx in &block.transactions {
+                let rows = session
+                    .query(queries::GET_TX_DATA_WITH_DICTIONARY, (tx.hash.to_vec(),))
+                    .await?;
+                let row = match rows.maybe_first_row()? {
+                    Some(row) => row,
+                    None => continue,
+                };
+
+                // Already archived rows keep their dictionary
+                if row.columns[1].as_ref().and_then(|col| col.as_int()).is_some() {
+                    continue;
+                }
+                let stored = match row.columns[0].as_ref().and_then(|col| col.as_blob()) {
+                    Some(blob) => blob.clone(),
+                    None => continue,
+                };
+
+                let plain = self.encryptor.decrypt(encryption::TRANSACTIONS_CONTEXT, &tx.hash, &stored)?;
+                let compressed = compress_with_dictionary(&plain, &dictionary, archival.compression_level)?;
+                let archived = self.encryptor.encrypt(encryption::TRANSACTIONS_CONTEXT, &tx.hash, compressed)?;
+
+                report.rows_archived += 1;
+                report.bytes_before += stored.len() as u64;
+                report.bytes_after += archived.len() as u64;
+
+                session
+                    .query(queries::UPDATE_ARCHIVED_TX_DATA, (archived, dict_id, tx.hash.to_vec()))
+                    .await?;
+            }
+
+            height += 1;
+            self.set_archive_checkpoint(height).await?;
+        }
+
+        report.archived_up_to = height.saturating_sub(1);
+        Ok(report)
+    }
+
+    /// Decode the stored `tx_data` blob of `tx_hash`, decompressing archived rows
+    pub(crate) async fn decode_tx_data(
+        &self,
+        tx_hash: &TxHash,
+        stored: &[u8],
+        dict_id: Option<i32>,
+    ) -> Result<Vec<u8>> {
+        let data = self.encryptor.decrypt(encryption::TRANSACTIONS_CONTEXT, tx_hash, stored)?;
+        match dict_id {
+            Some(dict_id) => {
+                let dictionary = self.load_dictionary(dict_id).await?;
+                decompress_with_dictionary(&data, &dictionary)
+            }
+            None => Ok(data),
+        }
+    }
+
+    /// Fetch a dictionary, caching it for later reads
+    pub(crate) async fn load_dictionary(&self, dict_id: i32) -> Result<Arc<Vec<u8>>> {
+        if let Some(dictionary) = self.dictionaries.read().await.get(&dict_id) {
+            return Ok(dictionary.clone());
+        }
+
+        let rows = self.session_for(StorageOperation::GetTransaction)
+            .query(queries::GET_DICTIONARY, (dict_id,))
+            .await?;
+        let dictionary = rows.maybe_first_row()?
+            .and_then(|row| row.columns[0].clone())
+            .and_then(|col| col.into_blob())
+            .ok_or_else(|| anyhow!("Compression dictionary {} not found", dict_id))?;
+
+        let dictionary = Arc::new(dictionary);
+        self.dictionaries.write().await.insert(dict_id, dictionary.clone());
+        Ok(dictionary)
+    }
+
+    async fn archive_checkpoint(&self) -> Result<BlockHeight> {
+        let rows = self.session_for(StorageOperation::ArchiveTransactions)
+            .query(queries::GET_CONFIG, (ARCHIVE_CHECKPOINT_KEY,))
+            .await?;
+
+        Ok(rows.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_text())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0))
+    }
+
+    async fn set_archive_checkpoint(&self, height: BlockHeight) -> Result<()> {
+        self.session_for(StorageOperation::ArchiveTransactions)
+            .query(
+                queries::SET_CONFIG,
+                (ARCHIVE_CHECKPOINT_KEY, height.to_string(), Utc::now(), "tx_archiver"),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(i: u32) -> Vec<u8> {
+        format!(
+            "{{\"from\":\"0x0101010101\",\"to\":\"0x0202020202\",\"amount\":{},\"nonce\":{},\"gas_limit\":21000,\"gas_price\":20}}",
+            i * 7,
+            i
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_dictionary_roundtrip() {
+        let samples: Vec<Vec<u8>> = (0..500).map(sample).collect();
+        let dictionary = train_dictionary(&samples, 4096).unwrap();
+
+        let data = sample(1234);
+        let compressed = compress_with_dictionary(&data, &dictionary, 19).unwrap();
+        assert!(compressed.len() < data.len());
+        assert_eq!(decompress_with_dictionary(&compressed, &dictionary).unwrap(), data);
+    }
+
+    #[test]
+    fn test_train_requires_samples() {
+        assert!(train_dictionary(&[], 4096).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+// storage/scylla-adapter/src/capacity.rs
+use anyhow::Result;
+use std::collections::BTreeMap;
+use storage_traits::StorageOperation;
+
+use crate::model::{DriverMetrics, TableSize};
+use crate::{queries, ScyllaAdapter};
+
+impl ScyllaAdapter {
+    /// Estimated size of every table in the keyspace, largest first.
+    ///
+    /// Read from `system.size_estimates` of the node that answers, which
+    /// covers the token ranges that node owns, so a cluster's total is
+    /// roughly this times the node count over the replication factor.
+    pub async fn get_table_sizes(&self) -> Result<Vec<TableSize>> {
+        self.fault_point(StorageOperation::GetTableSizes).await?;
+        let rows = self.session_for(StorageOperation::GetTableSizes)
+            .query(queries::GET_TABLE_SIZE_ESTIMATES, (self.config.keyspace.as_str(),))
+            .await?;
+
+        let mut tables: BTreeMap<String, TableSize> = BTreeMap::new();
+        for row in rows.rows.unwrap_or_default() {
+            let Some(table) = row.columns[0].as_ref().and_then(|col| col.as_text()).cloned() else {
+                continue;
+            };
+            let bigint = |i: usize| {
+                row.columns[i].as_ref().and_then(|col| col.as_bigint()).unwrap_or(0).max(0) as u64
+            };
+            let (mean_partition_size, partitions) = (bigint(1), bigint(2));
+            let size = tables.entry(table.clone()).or_insert_with(|| TableSize { table, ..Default::default() });
+            size.estimated_bytes = size.estimated_bytes.saturating_add(mean_partition_size.saturating_mul(partitions));
+            size.estimated_partitions = size.estimated_partitions.saturating_add(partitions);
+        }
+
+        let mut sizes: Vec<TableSize> = tables.into_values().collect();
+        sizes.sort_by(|a, b| b.estimated_bytes.cmp(&a.estimated_bytes));
+        Ok(sizes)
+    }
+
+    /// Query counts and latencies of the primary session
+    pub fn driver_metrics(&self) -> DriverMetrics {
+        let metrics = self.primary_session().get_metrics();
+        DriverMetrics {
+            queries: metrics.get_queries_num(),
+            errors: metrics.get_errors_num(),
+            mean_latency_ms: metrics.get_latency_avg_ms().unwrap_or(0),
+            p99_latency_ms: metrics.get_latency_percentile_ms(99.0).unwrap_or(0),
+        }
+    }
+}
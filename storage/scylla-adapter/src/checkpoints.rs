@@ -0,0 +1,89 @@
+// storage/scylla-adapter/src/checkpoints.rs
+use anyhow::Result;
+use blockchain_core::{BlockHeight, ChainId, Checkpoint};
+use chrono::Utc;
+use scylla::frame::response::result::Row;
+use storage_traits::StorageOperation;
+
+use crate::{queries, ScyllaAdapter};
+
+impl ScyllaAdapter {
+    /// Persist a finalized checkpoint.
+    ///
+    /// Checkpoints are write-once: storing the same block again is a no-op,
+    /// while a different block at an already finalized height is an error,
+    /// since it means two quorums disagreed.
+    pub async fn store_checkpoint(&self, chain_id: ChainId, checkpoint: &Checkpoint) -> Result<()> {
+        self.fault_point(StorageOperation::StoreCheckpoint).await?;
+        let result = self.session_for(StorageOperation::StoreCheckpoint)
+            .query(
+                queries::INSERT_CHECKPOINT,
+                (
+                    chain_id as i64,
+                    checkpoint.height as i64,
+                    checkpoint.block_hash.to_vec(),
+                    bincode::serialize(&checkpoint.signatures)?,
+                    Utc::now(),
+                ),
+            )
+            .await?;
+
+        let applied = result.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_boolean())
+            .unwrap_or(false);
+        if applied {
+            return Ok(());
+        }
+
+        match self.get_checkpoint(chain_id, checkpoint.height).await? {
+            Some(existing) if existing.block_hash == checkpoint.block_hash => Ok(()),
+            Some(existing) => Err(anyhow::anyhow!(
+                "Height {} already finalized at block {}, refusing {}",
+                checkpoint.height,
+                hex::encode(existing.block_hash),
+                hex::encode(checkpoint.block_hash)
+            )),
+            None => Err(anyhow::anyhow!("Checkpoint at height {} was not stored", checkpoint.height)),
+        }
+    }
+
+    pub async fn get_checkpoint(&self, chain_id: ChainId, height: BlockHeight) -> Result<Option<Checkpoint>> {
+        self.fault_point(StorageOperation::GetCheckpoint).await?;
+        let rows = self.session_for(StorageOperation::GetCheckpoint)
+            .query(queries::GET_CHECKPOINT, (chain_id as i64, height as i64))
+            .await?;
+        rows.maybe_first_row()?.map(parse_checkpoint).transpose()
+    }
+
+    /// Highest finalized checkpoint, which a restarting node hands to
+    /// `Chain::finalize` once it has replayed past it
+    pub async fn get_latest_checkpoint(&self, chain_id: ChainId) -> Result<Option<Checkpoint>> {
+        self.fault_point(StorageOperation::GetCheckpoint).await?;
+        let rows = self.session_for(StorageOperation::GetCheckpoint)
+            .query(queries::GET_LATEST_CHECKPOINT, (chain_id as i64,))
+            .await?;
+        rows.maybe_first_row()?.map(parse_checkpoint).transpose()
+    }
+}
+
+fn parse_checkpoint(row: Row) -> Result<Checkpoint> {
+    let blob = |i: usize, name: &str| {
+        row.columns[i].as_ref()
+            .and_then(|col| col.as_blob())
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Missing checkpoint {}", name))
+    };
+
+    Ok(Checkpoint {
+        height: row.columns[0].as_ref()
+            .and_then(|col| col.as_bigint())
+            .ok_or_else(|| anyhow::anyhow!("Missing checkpoint height"))? as BlockHeight,
+        block_hash: blob(1, "block_hash")?
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Invalid checkpoint block_hash"))?,
+        signatures: bincode::deserialize(&blob(2, "signatures")?)?,
+    })
+}
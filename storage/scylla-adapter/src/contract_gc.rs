@@ -0,0 +1,319 @@
+// storage/scylla-adapter/src/contract_gc.rs
+//! Reclaiming the storage, account rows and code of destroyed contracts.
+//!
+//! A contract references its code with a `contract_code_owners` row, so
+//! storing a deployment twice or releasing a contract twice leaves the count
+//! right, and a collection interrupted anywhere makes the same decisions when
+//! it runs again. Code nothing references is deleted with a timestamp
+//! backdated by `contract_gc.gc_grace_secs`: a deployment rewrites its code
+//! before adding its reference, so code being deployed again outlives a
+//! collection that counted no references for it.
+//!
+//! Each tombstone also marks its bucket in `contract_gc_buckets`, and a run
+//! scans the marked buckets up to the cutoff. A bucket wholly below the
+//! cutoff is unmarked with a delete dated before its scan began, so a
+//! tombstone that lands in it later marks it again rather than being skipped.
+use anyhow::Result;
+use blockchain_core::{hash_data, Address, BlockHash, BlockHeight, Transaction, TransactionType};
+use chrono::{Duration, Utc};
+use storage_traits::StorageOperation;
+
+use crate::model::ContractGcReport;
+use crate::{queries, ScyllaAdapter};
+
+/// Number of block heights grouped into one `contract_tombstones` partition
+pub const TOMBSTONE_BUCKET_SIZE: u64 = 10_000;
+
+impl ScyllaAdapter {
+    /// Store contract code and record `address` as running it. Both writes
+    /// are idempotent, and the code is rewritten even when present so a
+    /// concurrent collection cannot remove it.
+    pub async fn store_contract_code(&self, code_hash: &BlockHash, address: &Address, code: &[u8]) -> Result<()> {
+        self.fault_point(StorageOperation::StoreContractCode).await?;
+        let session = self.session_for(StorageOperation::StoreContractCode);
+        session
+            .query(queries::INSERT_CONTRACT_CODE, (code_hash.to_vec(), code.to_vec(), Utc::now()))
+            .await?;
+        session
+            .query(queries::INSERT_CODE_OWNER, (code_hash.to_vec(), address.to_vec()))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_contract_code(&self, code_hash: &BlockHash) -> Result<Option<Vec<u8>>> {
+        self.fault_point(StorageOperation::GetContractCode).await?;
+        let rows = self.session_for(StorageOperation::GetContractCode)
+            .query(queries::GET_CONTRACT_CODE, (code_hash.to_vec(),))
+            .await?;
+        Ok(rows.maybe_first_row()?.and_then(|row| row.columns[0].clone()).and_then(|col| col.into_blob()))
+    }
+
+    /// Register the contract `tx` deploys, if it is a deployment: its code,
+    /// its reference to the code and its account row
+    pub(crate) async fn store_deployed_contract(&self, tx: &Transaction) -> Result<()> {
+        let (TransactionType::Deploy { code, .. }, Some(address)) = (&tx.tx_type, tx.contract_address()) else {
+            return Ok(());
+        };
+        let code_hash = hash_data(code);
+        self.store_contract_code(&code_hash, &address, code).await?;
+        self.session_for(StorageOperation::StoreContractCode)
+            .query(queries::SET_CONTRACT_ACCOUNT, (code_hash.to_vec(), address.to_vec()))
+            .await?;
+        Ok(())
+    }
+
+    /// Undo `store_deployed_contract` for a deployment whose block is rolled back
+    pub(crate) async fn release_deployed_contract(&self, tx: &Transaction) -> Result<()> {
+        let (TransactionType::Deploy { code, .. }, Some(address)) = (&tx.tx_type, tx.contract_address()) else {
+            return Ok(());
+        };
+        self.release_contract_code(StorageOperation::RollbackBlocks, &hash_data(code), &address).await?;
+        self.session_for(StorageOperation::RollbackBlocks)
+            .query(queries::CLEAR_CONTRACT_ACCOUNT, (address.to_vec(),))
+            .await?;
+        Ok(())
+    }
+
+    /// Record that a contract self-destructed at the given height
+    pub async fn mark_contract_destroyed(
+        &self,
+        address: &Address,
+        code_hash: &BlockHash,
+        destroyed_height: BlockHeight,
+    ) -> Result<()> {
+        self.fault_point(StorageOperation::MarkContractDestroyed).await?;
+        let bucket = (destroyed_height / TOMBSTONE_BUCKET_SIZE) as i64;
+        let session = self.session_for(StorageOperation::MarkContractDestroyed);
+
+        session
+            .query(
+                queries::INSERT_CONTRACT_TOMBSTONE,
+                (bucket, destroyed_height as i64, address.to_vec(), code_hash.to_vec()),
+            )
+            .await?;
+        // After the tombstone, so a run that missed the tombstone keeps the mark
+        session.query(queries::INSERT_CONTRACT_GC_BUCKET, (bucket,)).await?;
+        Ok(())
+    }
+
+    /// Remove storage, account rows and unreferenced code of contracts destroyed
+    /// at least `retention_blocks` below `finalized_height`.
+    ///
+    /// Each tombstone is deleted only after its contract is reclaimed, and
+    /// every step can be repeated, so an interrupted run resumes where it
+    /// stopped.
+    pub async fn collect_contract_garbage(
+        &self,
+        finalized_height: BlockHeight,
+        retention_blocks: u64,
+    ) -> Result<ContractGcReport> {
+        self.fault_point(StorageOperation::CollectContractGarbage).await?;
+        let mut report = ContractGcReport::default();
+
+        if finalized_height < retention_blocks {
+            return Ok(report);
+        }
+        let cutoff = finalized_height - retention_blocks;
+        report.cutoff_height = cutoff;
+
+        let session = self.session_for(StorageOperation::CollectContractGarbage);
+        let grace = Duration::seconds(self.config.contract_gc.gc_grace_secs as i64);
+        let last_bucket = cutoff / TOMBSTONE_BUCKET_SIZE;
+        let buckets = session.query(queries::GET_CONTRACT_GC_BUCKETS, (last_bucket as i64,)).await?;
+
+        for row in buckets.rows.unwrap_or_default() {
+            let Some(bucket) = row.columns[0].as_ref().and_then(|col| col.as_bigint()) else {
+                continue;
+            };
+            let scan_started = Utc::now() - grace;
+            let rows = session
+                .query(queries::GET_CONTRACT_TOMBSTONES, (bucket, cutoff as i64))
+                .await?;
+
+            for row in rows.rows.unwrap_or_default() {
+                let destroyed_height = row.columns[0].as_ref()
+                    .and_then(|col| col.as_bigint())
+                    .ok_or_else(|| anyhow::anyhow!("Missing destroyed_height"))?;
+                let address = row.columns[1].as_ref()
+                    .and_then(|col| col.as_blob())
+                    .ok_or_else(|| anyhow::anyhow!("Missing address"))?
+                    .clone();
+                let code_hash = row.columns[2].as_ref()
+                    .and_then(|col| col.as_blob())
+                    .cloned();
+
+                report.storage_rows_reclaimed += self.reclaim_contract_storage(&address).await?;
+
+                if let Some(code_hash) = code_hash {
+                    let op = StorageOperation::CollectContractGarbage;
+                    if self.release_contract_code(op, &code_hash, &address).await? {
+                        report.code_entries_reclaimed += 1;
+                    }
+                }
+
+                session
+                    .query(
+                        queries::DELETE_CONTRACT_TOMBSTONE,
+                        (bucket, destroyed_height, address),
+                    )
+                    .await?;
+                report.contracts_collected += 1;
+            }
+
+            // The cutoff's own bucket may still receive eligible tombstones
+            if (bucket as u64) < last_bucket {
+                session
+                    .query(queries::DELETE_CONTRACT_GC_BUCKET, (scan_started.timestamp_micros(), bucket))
+                    .await?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Delete a destroyed contract's storage slots and account row, returning the slot count
+    async fn reclaim_contract_storage(&self, address: &[u8]) -> Result<u64> {
+        let session = self.session_for(StorageOperation::CollectContractGarbage);
+
+        let count_rows = session
+            .query(queries::COUNT_CONTRACT_STORAGE, (address.to_vec(),))
+            .await?;
+        let slots = count_rows.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_bigint())
+            .unwrap_or(0) as u64;
+
+        session
+            .query(queries::DELETE_CONTRACT_STORAGE, (address.to_vec(),))
+            .await?;
+        session
+            .query(queries::DELETE_ACCOUNT, (address.to_vec(),))
+            .await?;
+
+        Ok(slots)
+    }
+
+    /// Drop `address`'s reference to contract code, deleting the code once
+    /// nothing references it. Returns whether the code was deleted.
+    pub(crate) async fn release_contract_code(
+        &self,
+        op: StorageOperation,
+        code_hash: &[u8],
+        address: &[u8],
+    ) -> Result<bool> {
+        let session = self.session_for(op);
+        session
+            .query(queries::DELETE_CODE_OWNER, (code_hash.to_vec(), address.to_vec()))
+            .await?;
+
+        let owners = session
+            .query(queries::COUNT_CODE_OWNERS, (code_hash.to_vec(),))
+            .await?
+            .maybe_first_row()?
+            .and_then(|row| row.columns[0].as_ref().and_then(|col| col.as_bigint()))
+            .unwrap_or(0);
+        if owners > 0 {
+            return Ok(false);
+        }
+
+        let deleted_at = Utc::now() - Duration::seconds(self.config.contract_gc.gc_grace_secs as i64);
+        session
+            .query(queries::DELETE_CONTRACT_CODE, (deleted_at.timestamp_micros(), code_hash.to_vec()))
+            .await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scylla_config::ScyllaConfig;
+    use uuid::Uuid;
+
+    async fn adapter() -> ScyllaAdapter {
+        let mut config = ScyllaConfig::default();
+        // Nothing deploys concurrently, so deletes need not be backdated
+        config.contract_gc.gc_grace_secs = 0;
+        ScyllaAdapter::new(config).await.unwrap()
+    }
+
+    fn unique_address() -> Address {
+        let mut address = [0u8; 20];
+        address[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+        address
+    }
+
+    async fn has_tombstone(adapter: &ScyllaAdapter, height: BlockHeight, address: &Address) -> bool {
+        let bucket = (height / TOMBSTONE_BUCKET_SIZE) as i64;
+        let rows = adapter.session_for(StorageOperation::CollectContractGarbage)
+            .query(queries::GET_CONTRACT_TOMBSTONES, (bucket, height as i64))
+            .await
+            .unwrap();
+        rows.rows.unwrap_or_default().iter().any(|row| {
+            row.columns[1].as_ref().and_then(|col| col.as_blob()).is_some_and(|found| found == address)
+        })
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires ScyllaDB setup
+    async fn test_shared_code_outlives_one_contract() {
+        let adapter = adapter().await;
+        let code = Uuid::new_v4().as_bytes().to_vec();
+        let code_hash = hash_data(&code);
+        let (first, second) = (unique_address(), unique_address());
+
+        // A retried store adds no second reference
+        adapter.store_contract_code(&code_hash, &first, &code).await.unwrap();
+        adapter.store_contract_code(&code_hash, &first, &code).await.unwrap();
+        adapter.store_contract_code(&code_hash, &second, &code).await.unwrap();
+
+        adapter.mark_contract_destroyed(&first, &code_hash, 5).await.unwrap();
+        adapter.collect_contract_garbage(50_000, 100).await.unwrap();
+        assert!(!has_tombstone(&adapter, 5, &first).await);
+        // Releasing again, as a run resumed after a crash would, changes nothing
+        let op = StorageOperation::CollectContractGarbage;
+        assert!(!adapter.release_contract_code(op, &code_hash, &first).await.unwrap());
+        assert_eq!(adapter.get_contract_code(&code_hash).await.unwrap(), Some(code.clone()));
+
+        adapter.mark_contract_destroyed(&second, &code_hash, 6).await.unwrap();
+        adapter.collect_contract_garbage(50_000, 100).await.unwrap();
+        assert_eq!(adapter.get_contract_code(&code_hash).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires ScyllaDB setup
+    async fn test_late_tombstone_in_collected_bucket() {
+        let adapter = adapter().await;
+        // A bucket of its own, so other runs' tombstones do not interfere
+        let base = TOMBSTONE_BUCKET_SIZE * (1 + (Uuid::new_v4().as_u128() % 1_000_000) as u64);
+        let finalized = base + 3 * TOMBSTONE_BUCKET_SIZE;
+        let (early, late) = (unique_address(), unique_address());
+
+        adapter.mark_contract_destroyed(&early, &hash_data(&early), base + 1).await.unwrap();
+        let report = adapter.collect_contract_garbage(finalized, 0).await.unwrap();
+        assert!(report.contracts_collected >= 1);
+        assert!(!has_tombstone(&adapter, base + 1, &early).await);
+
+        // The bucket was emptied and unmarked before this tombstone landed in it
+        adapter.mark_contract_destroyed(&late, &hash_data(&late), base + 2).await.unwrap();
+        adapter.collect_contract_garbage(finalized, 0).await.unwrap();
+        assert!(!has_tombstone(&adapter, base + 2, &late).await);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires ScyllaDB setup
+    async fn test_deployment_registers_and_rollback_releases_code() {
+        let adapter = adapter().await;
+        let code = Uuid::new_v4().as_bytes().to_vec();
+        let deploy = Transaction::new_deploy(unique_address(), code.clone(), vec![], 0, 100_000, 1).unwrap();
+        let code_hash = hash_data(&code);
+
+        adapter.store_deployed_contract(&deploy).await.unwrap();
+        adapter.store_deployed_contract(&deploy).await.unwrap();
+        assert_eq!(adapter.get_contract_code(&code_hash).await.unwrap(), Some(code));
+
+        adapter.release_deployed_contract(&deploy).await.unwrap();
+        assert_eq!(adapter.get_contract_code(&code_hash).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,70 @@
+// storage/scylla-adapter/src/dry_runs.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use storage_traits::{StorageOperation, SubmissionLog};
+
+use crate::model::RelaySubmission;
+use crate::{queries, ScyllaAdapter};
+
+impl ScyllaAdapter {
+    /// Record a submission a relayer in dry-run mode built but did not send
+    pub async fn record_dry_run_submission(&self, submission: &RelaySubmission) -> Result<()> {
+        self.fault_point(StorageOperation::RecordDryRun).await?;
+        self.session_for(StorageOperation::RecordDryRun)
+            .query(
+                queries::INSERT_DRY_RUN_SUBMISSION,
+                (
+                    &submission.target,
+                    submission.prepared_at,
+                    submission.commitment_id,
+                    submission.batch_hash.to_vec(),
+                    &submission.calldata,
+                    submission.estimated_gas as i64,
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Most recent dry-run submissions for `target`, newest first
+    pub async fn get_dry_run_submissions(&self, target: &str, limit: i32) -> Result<Vec<RelaySubmission>> {
+        self.fault_point(StorageOperation::GetDryRuns).await?;
+        let rows = self.session_for(StorageOperation::GetDryRuns)
+            .query(queries::GET_DRY_RUN_SUBMISSIONS, (target, limit))
+            .await?;
+
+        let mut submissions = Vec::new();
+        for row in rows.rows.unwrap_or_default() {
+            let batch_hash = row.columns[1].as_ref()
+                .and_then(|col| col.as_blob())
+                .and_then(|bytes| bytes.as_slice().try_into().ok())
+                .ok_or_else(|| anyhow::anyhow!("Missing dry-run batch_hash"))?;
+
+            submissions.push(RelaySubmission {
+                commitment_id: row.columns[0].as_ref()
+                    .and_then(|col| col.as_uuid())
+                    .ok_or_else(|| anyhow::anyhow!("Missing dry-run commitment_id"))?,
+                target: target.to_string(),
+                batch_hash,
+                calldata: row.columns[2].as_ref()
+                    .and_then(|col| col.as_blob())
+                    .cloned()
+                    .unwrap_or_default(),
+                estimated_gas: row.columns[3].as_ref()
+                    .and_then(|col| col.as_bigint())
+                    .unwrap_or(0) as u64,
+                prepared_at: row.columns[4].as_ref()
+                    .and_then(|col| col.as_datetime())
+                    .ok_or_else(|| anyhow::anyhow!("Missing dry-run prepared_at"))?,
+            });
+        }
+        Ok(submissions)
+    }
+}
+
+#[async_trait]
+impl SubmissionLog for ScyllaAdapter {
+    async fn record(&self, submission: &RelaySubmission) -> Result<()> {
+        self.record_dry_run_submission(submission).await
+    }
+}
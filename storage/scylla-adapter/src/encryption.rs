@@ -0,0 +1,385 @@
+// storage/scylla-adapter/src/encryption.rs
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, Result};
+use rand::RngCore;
+use std::collections::HashMap;
+
+use crate::scylla_config::{EncryptionConfig, WrappedDataKey};
+
+/// Marker prefixed to every encrypted blob
+pub const ENCRYPTED_BLOB_MAGIC: [u8; 4] = *b"BENC";
+
+/// Current encrypted blob format version
+pub const ENCRYPTED_BLOB_VERSION: u8 = 2;
+
+/// Format whose associated data omitted the row key; still readable, rewritten by rotation
+pub const UNKEYED_BLOB_VERSION: u8 = 1;
+
+/// Associated-data context for `blocks.block_data`
+pub const BLOCKS_CONTEXT: &[u8] = b"blocks";
+
+/// Associated-data context for `transactions.tx_data`
+pub const TRANSACTIONS_CONTEXT: &[u8] = b"transactions";
+
+/// Associated-data context for `pending_transactions.tx_data`
+pub const PENDING_CONTEXT: &[u8] = b"pending_transactions";
+
+/// Associated-data context for `payload_blobs.data`
+pub const PAYLOADS_CONTEXT: &[u8] = b"payload_blobs";
+
+/// Row key bound into a `blocks.block_data` blob: the big-endian height
+pub fn height_key(height: u64) -> [u8; 8] {
+    height.to_be_bytes()
+}
+
+const DATA_KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+// magic + version + key id
+const HEADER_LEN: usize = ENCRYPTED_BLOB_MAGIC.len() + 1 + 4;
+
+/// Wraps and unwraps data keys with a master key held by a KMS.
+///
+/// Data keys are unwrapped once when the encryptor is built, so
+/// implementations may block on a remote call.
+pub trait KeyWrapper: Send + Sync {
+    /// Encrypt a data key under the given master key
+    fn wrap_key(&self, master_key_id: &str, data_key: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decrypt a data key previously wrapped under the given master key
+    fn unwrap_key(&self, master_key_id: &str, wrapped_key: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Key wrapper backed by a single in-process master key.
+///
+/// Intended for development and tests; production deployments should
+/// implement `KeyWrapper` against their KMS.
+pub struct LocalKeyWrapper {
+    master_key_id: String,
+    cipher: Aes256Gcm,
+}
+
+impl LocalKeyWrapper {
+    /// Create a wrapper from a 32-byte master key
+    pub fn new(master_key_id: impl Into<String>, master_key: &[u8]) -> Result<Self> {
+        let cipher = Aes256Gcm::new_from_slice(master_key)
+            .map_err(|_| anyhow!("Master key must be {} bytes", DATA_KEY_LEN))?;
+        Ok(Self {
+            master_key_id: master_key_id.into(),
+            cipher,
+        })
+    }
+
+    /// Create a wrapper from `SCYLLA_MASTER_KEY_ID` and a hex `SCYLLA_MASTER_KEY`
+    pub fn from_env() -> Result<Self> {
+        let key_id = std::env::var("SCYLLA_MASTER_KEY_ID").unwrap_or_else(|_| "local".to_string());
+        let key_hex = std::env::var("SCYLLA_MASTER_KEY")
+            .map_err(|_| anyhow!("SCYLLA_MASTER_KEY is not set"))?;
+        Self::new(key_id, &hex::decode(key_hex.trim())?)
+    }
+
+    fn check_master_key(&self, master_key_id: &str) -> Result<()> {
+        if master_key_id != self.master_key_id {
+            return Err(anyhow!("Unknown master key: {}", master_key_id));
+        }
+        Ok(())
+    }
+}
+
+impl KeyWrapper for LocalKeyWrapper {
+    fn wrap_key(&self, master_key_id: &str, data_key: &[u8]) -> Result<Vec<u8>> {
+        self.check_master_key(master_key_id)?;
+        seal(&self.cipher, master_key_id.as_bytes(), data_key)
+    }
+
+    fn unwrap_key(&self, master_key_id: &str, wrapped_key: &[u8]) -> Result<Vec<u8>> {
+        self.check_master_key(master_key_id)?;
+        open(&self.cipher, master_key_id.as_bytes(), wrapped_key)
+    }
+}
+
+/// Envelope encryption for blob columns (`block_data`, `tx_data`).
+///
+/// Blobs are written as `magic | version | key_id | nonce | ciphertext`, with
+/// the table context and the row's primary key bound as associated data, so a
+/// ciphertext copied to another row fails to decrypt. Blobs without the magic
+/// prefix are legacy plaintext, accepted only while encryption is disabled or
+/// `allow_plaintext` is set to migrate an existing keyspace.
+pub struct BlobEncryptor {
+    active_key_id: Option<u32>,
+    keys: HashMap<u32, Aes256Gcm>,
+    allow_plaintext: bool,
+}
+
+impl BlobEncryptor {
+    /// Encryptor that passes blobs through unchanged
+    pub fn disabled() -> Self {
+        Self {
+            active_key_id: None,
+            keys: HashMap::new(),
+            allow_plaintext: true,
+        }
+    }
+
+    /// Unwrap all configured data keys and build the encryptor
+    pub fn from_config(config: &EncryptionConfig, wrapper: &dyn KeyWrapper) -> Result<Self> {
+        let mut keys = HashMap::new();
+        for data_key in &config.data_keys {
+            let wrapped = hex::decode(&data_key.wrapped_key)?;
+            let raw = wrapper.unwrap_key(&data_key.master_key_id, &wrapped)?;
+            let cipher = Aes256Gcm::new_from_slice(&raw)
+                .map_err(|_| anyhow!("Data key {} must be {} bytes", data_key.key_id, DATA_KEY_LEN))?;
+            keys.insert(data_key.key_id, cipher);
+        }
+
+        let active_key_id = if config.enabled {
+            if !keys.contains_key(&config.active_key_id) {
+                return Err(anyhow!("Active data key {} is not configured", config.active_key_id));
+            }
+            Some(config.active_key_id)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            active_key_id,
+            keys,
+            allow_plaintext: !config.enabled || config.allow_plaintext,
+        })
+    }
+
+    /// Whether new blobs are encrypted
+    pub fn is_enabled(&self) -> bool {
+        self.active_key_id.is_some()
+    }
+
+    /// Encrypt a blob with the active data key; `context` and the row's primary
+    /// key `row_key` are bound as associated data
+    pub fn encrypt(&self, context: &[u8], row_key: &[u8], plaintext: Vec<u8>) -> Result<Vec<u8>> {
+        let key_id = match self.active_key_id {
+            Some(key_id) => key_id,
+            None => return Ok(plaintext),
+        };
+        let cipher = &self.keys[&key_id];
+
+        let mut blob = Vec::with_capacity(HEADER_LEN + NONCE_LEN + plaintext.len() + 16);
+        blob.extend_from_slice(&ENCRYPTED_BLOB_MAGIC);
+        blob.push(ENCRYPTED_BLOB_VERSION);
+        blob.extend_from_slice(&key_id.to_be_bytes());
+        let aad = associated_data(&blob[..HEADER_LEN], context, Some(row_key));
+        blob.extend_from_slice(&seal(cipher, &aad, &plaintext)?);
+        Ok(blob)
+    }
+
+    /// Decrypt a blob stored under `row_key`, returning legacy plaintext
+    /// blobs unchanged when plaintext is allowed
+    pub fn decrypt(&self, context: &[u8], row_key: &[u8], blob: &[u8]) -> Result<Vec<u8>> {
+        let key_id = match Self::key_id_of(blob) {
+            Some(key_id) => key_id,
+            None if self.allow_plaintext => return Ok(blob.to_vec()),
+            None => {
+                return Err(anyhow!(
+                    "Blob is not encrypted; set encryption.allow_plaintext while migrating an existing keyspace"
+                ))
+            }
+        };
+
+        let row_key = match blob[ENCRYPTED_BLOB_MAGIC.len()] {
+            ENCRYPTED_BLOB_VERSION => Some(row_key),
+            UNKEYED_BLOB_VERSION => None,
+            version => return Err(anyhow!("Unsupported encrypted blob version: {}", version)),
+        };
+
+        let cipher = self
+            .keys
+            .get(&key_id)
+            .ok_or_else(|| anyhow!("Data key {} is not available for decryption", key_id))?;
+        let aad = associated_data(&blob[..HEADER_LEN], context, row_key);
+        open(cipher, &aad, &blob[HEADER_LEN..])
+    }
+
+    /// Data key id of an encrypted blob, `None` for plaintext
+    pub fn key_id_of(blob: &[u8]) -> Option<u32> {
+        if blob.len() < HEADER_LEN || blob[..ENCRYPTED_BLOB_MAGIC.len()] != ENCRYPTED_BLOB_MAGIC {
+            return None;
+        }
+        let mut key_id = [0u8; 4];
+        key_id.copy_from_slice(&blob[ENCRYPTED_BLOB_MAGIC.len() + 1..HEADER_LEN]);
+        Some(u32::from_be_bytes(key_id))
+    }
+
+    /// Whether a stored blob should be rewritten under the active key and format
+    pub fn needs_rotation(&self, blob: &[u8]) -> bool {
+        match self.active_key_id {
+            Some(active) => {
+                Self::key_id_of(blob) != Some(active) || blob[ENCRYPTED_BLOB_MAGIC.len()] != ENCRYPTED_BLOB_VERSION
+            }
+            None => Self::key_id_of(blob).is_some(),
+        }
+    }
+
+    /// Re-encrypt a stored blob under the active key
+    pub fn reencrypt(&self, context: &[u8], row_key: &[u8], blob: &[u8]) -> Result<Vec<u8>> {
+        let plaintext = self.decrypt(context, row_key, blob)?;
+        self.encrypt(context, row_key, plaintext)
+    }
+}
+
+/// Generate a fresh data key and wrap it for storage in the config
+pub fn generate_data_key(
+    wrapper: &dyn KeyWrapper,
+    master_key_id: &str,
+    key_id: u32,
+) -> Result<WrappedDataKey> {
+    let mut raw = [0u8; DATA_KEY_LEN];
+    rand::thread_rng().fill_bytes(&mut raw);
+    let wrapped = wrapper.wrap_key(master_key_id, &raw)?;
+
+    Ok(WrappedDataKey {
+        key_id,
+        master_key_id: master_key_id.to_string(),
+        wrapped_key: hex::encode(wrapped),
+    })
+}
+
+/// `header | context`, followed by `len(context) | row_key` from version 2 on
+fn associated_data(header: &[u8], context: &[u8], row_key: Option<&[u8]>) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(header.len() + context.len() + 4 + row_key.unwrap_or_default().len());
+    aad.extend_from_slice(header);
+    aad.extend_from_slice(context);
+    if let Some(row_key) = row_key {
+        aad.extend_from_slice(&(context.len() as u32).to_be_bytes());
+        aad.extend_from_slice(row_key);
+    }
+    aad
+}
+
+/// Encrypt to `nonce | ciphertext`
+fn seal(cipher: &Aes256Gcm, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|_| anyhow!("Blob encryption failed"))?;
+
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt `nonce | ciphertext`
+fn open(cipher: &Aes256Gcm, aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        return Err(anyhow!("Encrypted payload is truncated"));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| anyhow!("Blob decryption failed: wrong key or tampered data"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrapper() -> LocalKeyWrapper {
+        LocalKeyWrapper::new("test-master", &[7u8; 32]).unwrap()
+    }
+
+    fn config_with_keys(wrapper: &LocalKeyWrapper, ids: &[u32], active: u32) -> EncryptionConfig {
+        EncryptionConfig {
+            enabled: true,
+            allow_plaintext: false,
+            active_key_id: active,
+            data_keys: ids
+                .iter()
+                .map(|id| generate_data_key(wrapper, "test-master", *id).unwrap())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let wrapper = wrapper();
+        let encryptor = BlobEncryptor::from_config(&config_with_keys(&wrapper, &[1], 1), &wrapper).unwrap();
+
+        let blob = encryptor.encrypt(b"blocks", &7i64.to_be_bytes(), b"block bytes".to_vec()).unwrap();
+        assert_eq!(BlobEncryptor::key_id_of(&blob), Some(1));
+        assert_eq!(encryptor.decrypt(b"blocks", &7i64.to_be_bytes(), &blob).unwrap(), b"block bytes");
+    }
+
+    #[test]
+    fn test_plaintext_passthrough() {
+        let wrapper = wrapper();
+        let mut config = config_with_keys(&wrapper, &[1], 1);
+        let legacy = b"legacy bincode".to_vec();
+
+        // A plaintext blob in an encrypted keyspace is refused unless migrating
+        let strict = BlobEncryptor::from_config(&config, &wrapper).unwrap();
+        assert!(strict.decrypt(b"blocks", b"row", &legacy).is_err());
+        config.allow_plaintext = true;
+        let migrating = BlobEncryptor::from_config(&config, &wrapper).unwrap();
+        assert_eq!(migrating.decrypt(b"blocks", b"row", &legacy).unwrap(), legacy);
+        assert!(migrating.needs_rotation(&legacy));
+
+        let disabled = BlobEncryptor::disabled();
+        assert_eq!(disabled.encrypt(b"blocks", b"row", legacy.clone()).unwrap(), legacy);
+        assert_eq!(disabled.decrypt(b"blocks", b"row", &legacy).unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_wrong_context_rejected() {
+        let wrapper = wrapper();
+        let encryptor = BlobEncryptor::from_config(&config_with_keys(&wrapper, &[1], 1), &wrapper).unwrap();
+
+        let blob = encryptor.encrypt(b"blocks", b"row-1", b"data".to_vec()).unwrap();
+        assert!(encryptor.decrypt(b"transactions", b"row-1", &blob).is_err());
+        // Swapping ciphertexts between rows of the same table is detected
+        assert!(encryptor.decrypt(b"blocks", b"row-2", &blob).is_err());
+
+        let mut tampered = blob.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(encryptor.decrypt(b"blocks", b"row-1", &tampered).is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let wrapper = wrapper();
+        let mut config = config_with_keys(&wrapper, &[1], 1);
+        let old = BlobEncryptor::from_config(&config, &wrapper).unwrap();
+        let blob = old.encrypt(b"transactions", b"hash", b"tx".to_vec()).unwrap();
+
+        // Add a new active key while keeping the old one for reads
+        config.data_keys.push(generate_data_key(&wrapper, "test-master", 2).unwrap());
+        config.active_key_id = 2;
+        let rotated = BlobEncryptor::from_config(&config, &wrapper).unwrap();
+
+        assert!(rotated.needs_rotation(&blob));
+        assert_eq!(rotated.decrypt(b"transactions", b"hash", &blob).unwrap(), b"tx");
+
+        let rewritten = rotated.reencrypt(b"transactions", b"hash", &blob).unwrap();
+        assert_eq!(BlobEncryptor::key_id_of(&rewritten), Some(2));
+        assert!(!rotated.needs_rotation(&rewritten));
+    }
+
+    #[test]
+    fn test_unkeyed_blobs_readable_and_rotated() {
+        let wrapper = wrapper();
+        let encryptor = BlobEncryptor::from_config(&config_with_keys(&wrapper, &[1], 1), &wrapper).unwrap();
+
+        // A version 1 blob bound only the table context
+        let mut blob = ENCRYPTED_BLOB_MAGIC.to_vec();
+        blob.push(UNKEYED_BLOB_VERSION);
+        blob.extend_from_slice(&1u32.to_be_bytes());
+        let aad = associated_data(&blob, b"blocks", None);
+        blob.extend_from_slice(&seal(&encryptor.keys[&1], &aad, b"old").unwrap());
+
+        assert_eq!(encryptor.decrypt(b"blocks", b"row", &blob).unwrap(), b"old");
+        assert!(encryptor.needs_rotation(&blob));
+        let rewritten = encryptor.reencrypt(b"blocks", b"row", &blob).unwrap();
+        assert!(!encryptor.needs_rotation(&rewritten));
+    }
+}
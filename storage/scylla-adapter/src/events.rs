@@ -0,0 +1,248 @@
+// storage/scylla-adapter/src/events.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::Block;
+use chrono::{DateTime, Duration, Utc};
+use scylla::frame::response::result::Row;
+use storage_traits::{
+    BlockRolledBackV1, BlockStoredV1, ChainEvent, EventFilter, EventLog, EventPage, EventPayload, StorageOperation,
+};
+
+use crate::{queries, ScyllaAdapter};
+
+/// Sequence numbers per `event_log` partition
+pub const EVENT_BUCKET_SIZE: u64 = 100_000;
+
+/// Published after a block and its indexes are written
+pub const BLOCK_STORED: &str = BlockStoredV1::EVENT_TYPE;
+
+/// Published after a block is removed by `rollback_to_height`
+pub const BLOCK_ROLLED_BACK: &str = BlockRolledBackV1::EVENT_TYPE;
+
+/// `event_sequence` row holding the log's counter
+const EVENT_SEQUENCE_NAME: &str = "events";
+
+/// Lost sequence races tolerated before a publish gives up
+const MAX_SEQUENCE_ATTEMPTS: usize = 16;
+
+impl ScyllaAdapter {
+    /// Append an event to the log under the next sequence number.
+    ///
+    /// Delivery is at least once: a retried publish gets a new number, so
+    /// consumers that need exactly once dedupe on `subject`.
+    pub async fn publish_event(&self, event_type: &str, subject: &str, payload: &str) -> Result<u64> {
+        self.fault_point(StorageOperation::PublishEvent).await?;
+        let seq = self.allocate_event_seq().await?;
+        let ttl = (self.config.event_log.retention_days * 86_400) as i32;
+
+        self.session_for(StorageOperation::PublishEvent)
+            .query(
+                queries::INSERT_EVENT,
+                (
+                    (seq / EVENT_BUCKET_SIZE) as i64,
+                    seq as i64,
+                    event_type,
+                    subject,
+                    payload,
+                    Utc::now(),
+                    ttl,
+                ),
+            )
+            .await?;
+        Ok(seq)
+    }
+
+    /// Events from `from_seq` onwards matching `filter`, scanning at most
+    /// `limit` entries (capped by `max_replay_page`).
+    ///
+    /// A sequence number that was allocated but has no row yet is waited for
+    /// while the event after it is younger than `gap_grace_secs`, so a
+    /// publish still in flight is not skipped. Older gaps are expired or
+    /// abandoned events and are stepped over.
+    pub async fn replay_events(&self, from_seq: u64, filter: &EventFilter, limit: usize) -> Result<EventPage> {
+        self.fault_point(StorageOperation::ReplayEvents).await?;
+        let config = &self.config.event_log;
+        let session = self.session_for(StorageOperation::ReplayEvents);
+
+        let head_seq = self.event_head(StorageOperation::ReplayEvents).await?.unwrap_or(0);
+        let limit = limit.clamp(1, config.max_replay_page);
+        let grace = Duration::seconds(config.gap_grace_secs);
+        let now = Utc::now();
+
+        let mut page = EventPage { events: Vec::new(), next_seq: from_seq, head_seq };
+        let mut scan_from = from_seq;
+        let mut scanned = 0;
+        while scan_from < head_seq && scanned < limit {
+            let bucket = scan_from / EVENT_BUCKET_SIZE;
+            let rows = session
+                .query(queries::GET_EVENTS_FROM, (bucket as i64, scan_from as i64, (limit - scanned) as i32))
+                .await?;
+            let entries = rows
+                .rows
+                .unwrap_or_default()
+                .iter()
+                .map(parse_event)
+                .collect::<Result<Vec<_>>>()?;
+
+            scanned += entries.len();
+            if !advance(&mut page, entries, filter, now, grace) {
+                break;
+            }
+            scan_from = (bucket + 1) * EVENT_BUCKET_SIZE;
+        }
+
+        Ok(page)
+    }
+
+    /// Publish the `block_stored` / `block_rolled_back` event for `block`
+    pub(crate) async fn publish_block_event(&self, event_type: &str, block: &Block) -> Result<u64> {
+        let hash = format!("0x{}", hex::encode(block.hash));
+        let height = block.header.height;
+        let payload = match event_type {
+            BLOCK_ROLLED_BACK => BlockRolledBackV1 { height, hash: hash.clone() }.to_json()?,
+            _ => BlockStoredV1 { height, hash: hash.clone() }.to_json()?,
+        };
+        self.publish_event(event_type, &hash, &payload).await
+    }
+
+    /// Claim the next sequence number with a compare-and-set on the counter row
+    async fn allocate_event_seq(&self) -> Result<u64> {
+        let session = self.session_for(StorageOperation::PublishEvent);
+
+        for _ in 0..MAX_SEQUENCE_ATTEMPTS {
+            let Some(current) = self.event_head(StorageOperation::PublishEvent).await? else {
+                session.query(queries::INIT_EVENT_SEQUENCE, (EVENT_SEQUENCE_NAME,)).await?;
+                continue;
+            };
+
+            let result = session
+                .query(
+                    queries::ADVANCE_EVENT_SEQUENCE,
+                    (current as i64 + 1, EVENT_SEQUENCE_NAME, current as i64),
+                )
+                .await?;
+            let applied = result.maybe_first_row()?
+                .as_ref()
+                .and_then(|row| row.columns[0].as_ref())
+                .and_then(|col| col.as_boolean())
+                .unwrap_or(false);
+            if applied {
+                return Ok(current);
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Event sequence still contended after {} attempts",
+            MAX_SEQUENCE_ATTEMPTS
+        ))
+    }
+
+    /// Sequence number the next event will get, `None` before the first publish
+    async fn event_head(&self, op: StorageOperation) -> Result<Option<u64>> {
+        let rows = self.session_for(op)
+            .query(queries::GET_EVENT_SEQUENCE, (EVENT_SEQUENCE_NAME,))
+            .await?;
+        Ok(rows.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_bigint())
+            .map(|next| next as u64))
+    }
+}
+
+#[async_trait]
+impl EventLog for ScyllaAdapter {
+    async fn publish_event(&self, event_type: &str, subject: &str, payload: &str) -> Result<u64> {
+        ScyllaAdapter::publish_event(self, event_type, subject, payload).await
+    }
+
+    async fn replay_events(&self, from_seq: u64, filter: &EventFilter, limit: usize) -> Result<EventPage> {
+        ScyllaAdapter::replay_events(self, from_seq, filter, limit).await
+    }
+}
+
+/// Move the page cursor over `entries`, keeping those matching `filter`.
+///
+/// Returns false when it stopped at a gap that may still be filled.
+fn advance(
+    page: &mut EventPage,
+    entries: Vec<ChainEvent>,
+    filter: &EventFilter,
+    now: DateTime<Utc>,
+    grace: Duration,
+) -> bool {
+    for event in entries {
+        if event.seq > page.next_seq && now - event.published_at < grace {
+            return false;
+        }
+        page.next_seq = event.seq + 1;
+        if filter.matches(&event) {
+            page.events.push(event);
+        }
+    }
+    true
+}
+
+fn parse_event(row: &Row) -> Result<ChainEvent> {
+    let text = |i: usize, name: &str| {
+        row.columns[i].as_ref()
+            .and_then(|col| col.as_text())
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Missing event {}", name))
+    };
+
+    Ok(ChainEvent {
+        seq: row.columns[0].as_ref()
+            .and_then(|col| col.as_bigint())
+            .ok_or_else(|| anyhow::anyhow!("Missing event seq"))? as u64,
+        event_type: text(1, "event_type")?,
+        subject: text(2, "subject")?,
+        payload: text(3, "payload")?,
+        published_at: row.columns[4].as_ref()
+            .and_then(|col| col.as_datetime())
+            .ok_or_else(|| anyhow::anyhow!("Missing event published_at"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(seq: u64, event_type: &str, age_secs: i64, now: DateTime<Utc>) -> ChainEvent {
+        ChainEvent {
+            seq,
+            event_type: event_type.to_string(),
+            subject: format!("0x{:02x}", seq),
+            payload: "{}".to_string(),
+            published_at: now - Duration::seconds(age_secs),
+        }
+    }
+
+    #[test]
+    fn test_cursor_skips_filtered_and_stale_gaps() {
+        let now = Utc::now();
+        let filter = EventFilter { event_types: vec![BLOCK_STORED.to_string()], subject: None };
+        let mut page = EventPage { next_seq: 3, ..EventPage::default() };
+
+        // 3 and 4 expired long ago; 6 was never written
+        let entries = vec![
+            event(5, BLOCK_STORED, 600, now),
+            event(7, BLOCK_ROLLED_BACK, 500, now),
+            event(8, BLOCK_STORED, 400, now),
+        ];
+        assert!(advance(&mut page, entries, &filter, now, Duration::seconds(30)));
+        assert_eq!(page.next_seq, 9);
+        assert_eq!(page.events.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![5, 8]);
+    }
+
+    #[test]
+    fn test_cursor_waits_on_fresh_gap() {
+        let now = Utc::now();
+        let mut page = EventPage { next_seq: 10, ..EventPage::default() };
+
+        let entries = vec![event(10, BLOCK_STORED, 5, now), event(12, BLOCK_STORED, 2, now)];
+        assert!(!advance(&mut page, entries, &EventFilter::default(), now, Duration::seconds(30)));
+        assert_eq!(page.next_seq, 11);
+        assert_eq!(page.events.len(), 1);
+    }
+}
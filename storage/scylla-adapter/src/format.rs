@@ -0,0 +1,264 @@
+// storage/scylla-adapter/src/format.rs
+//! Versioned encoding of the serialized blob columns (`block_data`,
+//! `tx_data`, `header_data`).
+//!
+//! Blobs are written as `magic | version | bincode`, inside any encryption
+//! or archive compression. Blobs without the magic prefix were written
+//! before versioning and are read as version 0. Each stored type decodes
+//! every version it has ever been written in, so changing an encoding only
+//! needs a new `BLOB_FORMAT_VERSION` and a `decode_version` arm for the old
+//! layout. `migrate_blob_formats` then rewrites old rows in the background,
+//! recording its progress in `system_config` so it resumes after a restart.
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use blockchain_core::{Block, BlockHeader, BlockHeight, Transaction};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use storage_traits::{FormatMigration, FormatMigrationProgress, StorageOperation};
+
+use crate::archive::compress_with_dictionary;
+use crate::{encryption, queries, ScyllaAdapter};
+
+/// Marker prefixed to every versioned blob
+pub const BLOB_FORMAT_MAGIC: [u8; 4] = *b"BFMT";
+
+/// Format new blobs are written in
+pub const BLOB_FORMAT_VERSION: u8 = 2;
+
+/// First format whose transactions always encode `chain_id`
+pub const CHAIN_ID_FORMAT_VERSION: u8 = 2;
+
+/// Version reported for blobs written before versioning
+pub const LEGACY_FORMAT_VERSION: u8 = 0;
+
+const HEADER_LEN: usize = BLOB_FORMAT_MAGIC.len() + 1;
+
+/// `system_config` key holding the next block height to migrate
+const MIGRATION_CHECKPOINT_KEY: &str = "blob_format_height";
+
+/// A type stored as a versioned blob
+pub trait StoredFormat: Serialize + DeserializeOwned {
+    /// Decode `bytes` written under `version`, which is never newer than
+    /// `BLOB_FORMAT_VERSION`. The default reads every version with the
+    /// current layout; override it once the layout changes.
+    fn decode_version(version: u8, bytes: &[u8]) -> Result<Self> {
+        let _ = version;
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+impl StoredFormat for Block {}
+impl StoredFormat for BlockHeader {}
+
+impl StoredFormat for Transaction {
+    fn decode_version(version: u8, bytes: &[u8]) -> Result<Self> {
+        if version < CHAIN_ID_FORMAT_VERSION {
+            return Ok(Transaction::decode_legacy(bytes)?);
+        }
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// Serialize `value` in the current format
+pub fn encode<T: StoredFormat>(value: &T) -> Result<Vec<u8>> {
+    let body = bincode::serialize(value)?;
+    let mut blob = Vec::with_capacity(HEADER_LEN + body.len());
+    blob.extend_from_slice(&BLOB_FORMAT_MAGIC);
+    blob.push(BLOB_FORMAT_VERSION);
+    blob.extend_from_slice(&body);
+    Ok(blob)
+}
+
+/// Deserialize a blob in any format this build knows
+pub fn decode<T: StoredFormat>(blob: &[u8]) -> Result<T> {
+    let version = format_version(blob);
+    if version > BLOB_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Blob format {} was written by a newer release; this build reads up to {}",
+            version,
+            BLOB_FORMAT_VERSION
+        ));
+    }
+    let body = if version == LEGACY_FORMAT_VERSION { blob } else { &blob[HEADER_LEN..] };
+    T::decode_version(version, body)
+}
+
+/// Format a blob was written in
+pub fn format_version(blob: &[u8]) -> u8 {
+    if blob.len() < HEADER_LEN || blob[..BLOB_FORMAT_MAGIC.len()] != BLOB_FORMAT_MAGIC {
+        return LEGACY_FORMAT_VERSION;
+    }
+    blob[BLOB_FORMAT_MAGIC.len()]
+}
+
+/// Whether a blob should be rewritten in the current format
+pub fn needs_migration(blob: &[u8]) -> bool {
+    format_version(blob) < BLOB_FORMAT_VERSION
+}
+
+/// Re-encode a blob of `T` in the current format, or `None` if it already is
+pub fn upgrade<T: StoredFormat>(blob: &[u8]) -> Result<Option<Vec<u8>>> {
+    if !needs_migration(blob) {
+        return Ok(None);
+    }
+    encode(&decode::<T>(blob)?).map(Some)
+}
+
+impl ScyllaAdapter {
+    /// Rewrite the block, header and transaction rows of up to `max_blocks`
+    /// blocks that are still in an older format.
+    ///
+    /// Resumes from the height recorded by the previous pass and stops at the
+    /// chain height seen when it starts; blocks stored later are already
+    /// written in the current format.
+    pub async fn migrate_blob_formats(&self, max_blocks: u64) -> Result<FormatMigrationProgress> {
+        self.fault_point(StorageOperation::MigrateBlobFormat).await?;
+        let mut progress = FormatMigrationProgress {
+            next_height: self.migration_checkpoint().await?,
+            ..Default::default()
+        };
+        let Some(target_height) = self.get_latest_block_height().await? else {
+            progress.completed = true;
+            return Ok(progress);
+        };
+        progress.target_height = target_height;
+
+        let session = self.session_for(StorageOperation::MigrateBlobFormat);
+        let end = progress.next_height.saturating_add(max_blocks).min(target_height.saturating_add(1));
+        while progress.next_height < end {
+            let height = progress.next_height;
+            let rows = session.query(queries::GET_BLOCK_DATA, (height as i64,)).await?;
+            let stored = rows.maybe_first_row()?
+                .and_then(|row| row.columns[0].clone())
+                .and_then(|col| col.into_blob());
+
+            if let Some(stored) = stored {
+                progress.rows_scanned += 1;
+                let row_key = encryption::height_key(height);
+                let block_data = self.encryptor.decrypt(encryption::BLOCKS_CONTEXT, &row_key, &stored)?;
+                let block: Block = decode(&block_data)?;
+                if let Some(upgraded) = upgrade::<Block>(&block_data)? {
+                    let encrypted = self.encryptor.encrypt(encryption::BLOCKS_CONTEXT, &row_key, upgraded)?;
+                    session.query(queries::UPDATE_BLOCK_DATA, (encrypted, height as i64)).await?;
+                    progress.rows_rewritten += 1;
+                }
+
+                // Header rows are cheap to rewrite and always written in the current format
+                self.store_block_header(height, &block.hash, &block.header).await?;
+
+                for tx in &block.transactions {
+                    progress.rows_scanned += 1;
+                    if self.migrate_transaction_row(tx).await? {
+                        progress.rows_rewritten += 1;
+                    }
+                }
+            }
+
+            progress.next_height += 1;
+            self.set_migration_checkpoint(progress.next_height).await?;
+        }
+
+        progress.completed = progress.next_height > target_height;
+        Ok(progress)
+    }
+
+    /// Rewrite one `transactions` row in the current format, keeping archived
+    /// rows compressed with their dictionary
+    async fn migrate_transaction_row(&self, tx: &Transaction) -> Result<bool> {
+        let session = self.session_for(StorageOperation::MigrateBlobFormat);
+        let rows = session
+            .query(queries::GET_TX_DATA_WITH_DICTIONARY, (tx.hash.to_vec(),))
+            .await?;
+        let Some(row) = rows.maybe_first_row()? else {
+            return Ok(false);
+        };
+        let Some(stored) = row.columns[0].as_ref().and_then(|col| col.as_blob()) else {
+            return Ok(false);
+        };
+        let dict_id = row.columns[1].as_ref().and_then(|col| col.as_int());
+
+        let tx_data = self.decode_tx_data(&tx.hash, stored, dict_id).await?;
+        let Some(upgraded) = upgrade::<Transaction>(&tx_data)? else {
+            return Ok(false);
+        };
+
+        match dict_id {
+            Some(dict_id) => {
+                let dictionary = self.load_dictionary(dict_id).await?;
+                let level = self.config.archival.compression_level;
+                let compressed = compress_with_dictionary(&upgraded, &dictionary, level)?;
+                let encrypted = self.encryptor.encrypt(encryption::TRANSACTIONS_CONTEXT, &tx.hash, compressed)?;
+                session
+                    .query(queries::UPDATE_ARCHIVED_TX_DATA, (encrypted, dict_id, tx.hash.to_vec()))
+                    .await?;
+            }
+            None => {
+                let encrypted = self.encryptor.encrypt(encryption::TRANSACTIONS_CONTEXT, &tx.hash, upgraded)?;
+                session.query(queries::UPDATE_TX_DATA, (encrypted, tx.hash.to_vec())).await?;
+            }
+        }
+        Ok(true)
+    }
+
+    async fn migration_checkpoint(&self) -> Result<BlockHeight> {
+        let rows = self.session_for(StorageOperation::MigrateBlobFormat)
+            .query(queries::GET_CONFIG, (MIGRATION_CHECKPOINT_KEY,))
+            .await?;
+
+        Ok(rows.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_text())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0))
+    }
+
+    async fn set_migration_checkpoint(&self, height: BlockHeight) -> Result<()> {
+        self.session_for(StorageOperation::MigrateBlobFormat)
+            .query(
+                queries::SET_CONFIG,
+                (MIGRATION_CHECKPOINT_KEY, height.to_string(), Utc::now(), "format_migration"),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FormatMigration for ScyllaAdapter {
+    async fn migrate_formats(&self, max_blocks: u64) -> Result<FormatMigrationProgress> {
+        self.migrate_blob_formats(max_blocks).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_legacy_and_rejects_newer_formats() {
+        let tx = Transaction::new_transfer([1; 20], [2; 20], 10, 0, 21_000, 1).unwrap();
+
+        let current = encode(&tx).unwrap();
+        assert_eq!(format_version(&current), BLOB_FORMAT_VERSION);
+        assert_eq!(decode::<Transaction>(&current).unwrap().hash, tx.hash);
+        assert_eq!(upgrade::<Transaction>(&current).unwrap(), None);
+
+        // Written before versioning: plain bincode, without the legacy chain id
+        let body = bincode::serialize(&tx).unwrap();
+        let legacy = body[..body.len() - 8].to_vec();
+        assert_eq!(format_version(&legacy), LEGACY_FORMAT_VERSION);
+        assert_eq!(decode::<Transaction>(&legacy).unwrap(), tx);
+        assert_eq!(upgrade::<Transaction>(&legacy).unwrap(), Some(current.clone()));
+
+        // Version 1 used the same transaction layout
+        let v1 = [&BLOB_FORMAT_MAGIC[..], &[1], &legacy].concat();
+        assert_eq!(decode::<Transaction>(&v1).unwrap(), tx);
+        assert_eq!(upgrade::<Transaction>(&v1).unwrap(), Some(current.clone()));
+
+        let mut newer = current;
+        newer[BLOB_FORMAT_MAGIC.len()] = BLOB_FORMAT_VERSION + 1;
+        assert!(decode::<Transaction>(&newer).unwrap_err().to_string().contains("newer release"));
+    }
+}
@@ -0,0 +1,88 @@
+// storage/scylla-adapter/src/headers.rs
+use anyhow::Result;
+use blockchain_core::{BlockHash, BlockHeader, BlockHeight};
+use std::collections::BTreeMap;
+use storage_traits::StorageOperation;
+
+use crate::{format, queries, ScyllaAdapter};
+
+impl ScyllaAdapter {
+    /// Write the header-only row for a block.
+    ///
+    /// Headers are public and committed to by the block hash, so unlike
+    /// `block_data` they are stored unencrypted.
+    pub(crate) async fn store_block_header(
+        &self,
+        height: BlockHeight,
+        hash: &BlockHash,
+        header: &BlockHeader,
+    ) -> Result<()> {
+        self.session_for(StorageOperation::StoreBlock)
+            .query(
+                queries::INSERT_BLOCK_HEADER,
+                (height as i64, hash.to_vec(), format::encode(header)?),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Headers at the given heights, read from `block_headers` without touching `block_data`.
+    ///
+    /// Blocks stored before the header table existed fall back to a full block
+    /// read and, unless the adapter is read-only, get their header row
+    /// written for later requests.
+    pub async fn get_block_headers(&self, heights: &[BlockHeight]) -> Result<Vec<BlockHeader>> {
+        self.fault_point(StorageOperation::GetBlockHeaders).await?;
+        if heights.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<i64> = heights.iter().map(|&h| h as i64).collect();
+        let rows = self.session_for(StorageOperation::GetBlockHeaders)
+            .query(queries::GET_BLOCK_HEADERS, (keys,))
+            .await?;
+
+        let mut headers = BTreeMap::new();
+        for row in rows.rows.unwrap_or_default() {
+            let header_data = row.columns[2].as_ref()
+                .and_then(|col| col.as_blob())
+                .ok_or_else(|| anyhow::anyhow!("Missing header data"))?;
+            let header: BlockHeader = format::decode(header_data)?;
+            headers.insert(header.height, header);
+        }
+
+        for height in missing_heights(heights, &headers) {
+            if let Some(block) = self.get_block_by_height(height).await? {
+                if !self.is_read_only() {
+                    self.store_block_header(height, &block.hash, &block.header).await?;
+                }
+                headers.insert(height, block.header);
+            }
+        }
+
+        Ok(headers.into_values().collect())
+    }
+}
+
+/// Requested heights with no header row
+fn missing_heights(requested: &[BlockHeight], found: &BTreeMap<BlockHeight, BlockHeader>) -> Vec<BlockHeight> {
+    let mut missing: Vec<BlockHeight> = requested.iter().copied().filter(|h| !found.contains_key(h)).collect();
+    missing.sort_unstable();
+    missing.dedup();
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blockchain_core::Block;
+
+    #[test]
+    fn test_missing_heights() {
+        let genesis = Block::genesis().unwrap();
+        let found = BTreeMap::from([(0, genesis.header)]);
+
+        assert_eq!(missing_heights(&[4, 0, 2, 4], &found), vec![2, 4]);
+        assert!(missing_heights(&[0], &found).is_empty());
+    }
+}
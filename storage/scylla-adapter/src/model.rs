@@ -1,19 +1,16 @@
 // storage/scylla-adapter/src/dao.rs
-use blockchain_core::{Address, TxHash, BlockHash, BlockHeight};
+use blockchain_core::{Address, TxHash, BlockHeight};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-/// Account model for database storage
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AccountModel {
-    pub address: Address,
-    pub balance: u64,
-    pub nonce: u64,
-    pub last_updated: DateTime<Utc>,
-    pub account_type: String, // "user" or "contract"
-    pub code_hash: Option<BlockHash>, // For contract accounts
-}
+pub use storage_traits::AccountModel;
+pub use storage_traits::relayer_queue::{
+    CommitmentData, PayloadEncoding, RelaySubmission, RelayerBatch, RelayerStatus, TargetInclusion,
+};
+pub use storage_traits::validation_queue::{
+    BalanceChange, FailedTransaction, GasEstimate, ValidationBatch, ValidationResult, ValidationStatus,
+};
 
 /// Transaction reference for address lookups
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,156 +23,12 @@ pub struct AddressTransaction {
     pub is_sender: bool,
 }
 
-/// Validation batch model
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ValidationBatch {
-    pub queue_id: Uuid,
-    pub batch_timestamp: DateTime<Utc>,
-    pub tx_hashes: Vec<TxHash>,
-    pub validation_status: ValidationStatus,
-    pub validator_id: String,
-    pub started_at: Option<DateTime<Utc>>,
-    pub completed_at: Option<DateTime<Utc>>,
-    pub validation_result: Option<ValidationResult>,
-}
-
-/// Validation status enum
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum ValidationStatus {
-    Pending,
-    Processing,
-    Validated,
-    Failed,
-    Rejected,
-}
-
-impl std::fmt::Display for ValidationStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ValidationStatus::Pending => write!(f, "pending"),
-            ValidationStatus::Processing => write!(f, "processing"),
-            ValidationStatus::Validated => write!(f, "validated"),
-            ValidationStatus::Failed => write!(f, "failed"),
-            ValidationStatus::Rejected => write!(f, "rejected"),
-        }
-    }
-}
-
-impl std::str::FromStr for ValidationStatus {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "pending" => Ok(ValidationStatus::Pending),
-            "processing" => Ok(ValidationStatus::Processing),
-            "validated" => Ok(ValidationStatus::Validated),
-            "failed" => Ok(ValidationStatus::Failed),
-            "rejected" => Ok(ValidationStatus::Rejected),
-            _ => Err(format!("Invalid validation status: {}", s)),
-        }
-    }
-}
-
-/// Validation result details
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ValidationResult {
-    pub is_valid: bool,
-    pub validated_transactions: Vec<TxHash>,
-    pub failed_transactions: Vec<FailedTransaction>,
-    pub gas_estimates: Vec<GasEstimate>,
-    pub balance_changes: Vec<BalanceChange>,
-    pub validation_time_ms: u64,
-    pub error_message: Option<String>,
-}
-
-/// Failed transaction details
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FailedTransaction {
-    pub tx_hash: TxHash,
-    pub error_code: String,
-    pub error_message: String,
-    pub suggested_gas_limit: Option<u64>,
-}
-
-/// Gas estimation for transactions
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GasEstimate {
-    pub tx_hash: TxHash,
-    pub estimated_gas: u64,
-    pub gas_price_suggestion: u64,
-    pub execution_time_estimate_ms: u64,
-}
-
-/// Balance change tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BalanceChange {
-    pub address: Address,
-    pub old_balance: u64,
-    pub new_balance: u64,
-    pub old_nonce: u64,
-    pub new_nonce: u64,
-}
-
-/// Relayer batch model
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RelayerBatch {
-    pub commitment_id: Uuid,
-    pub batch_timestamp: DateTime<Utc>,
-    pub tx_hashes: Vec<TxHash>,
-    pub status: RelayerStatus,
-    pub relayer_id: String,
-    pub retry_count: u32,
-    pub last_attempt: Option<DateTime<Utc>>,
-    pub target_block_height: Option<BlockHeight>,
-    pub commitment_data: Option<CommitmentData>,
-}
-
-/// Relayer status enum
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum RelayerStatus {
-    Queued,
-    Processing,
-    Committed,
-    Failed,
-    Cancelled,
-}
-
-impl std::fmt::Display for RelayerStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            RelayerStatus::Queued => write!(f, "queued"),
-            RelayerStatus::Processing => write!(f, "processing"),
-            RelayerStatus::Committed => write!(f, "committed"),
-            RelayerStatus::Failed => write!(f, "failed"),
-            RelayerStatus::Cancelled => write!(f, "cancelled"),
-        }
-    }
-}
-
-impl std::str::FromStr for RelayerStatus {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "queued" => Ok(RelayerStatus::Queued),
-            "processing" => Ok(RelayerStatus::Processing),
-            "committed" => Ok(RelayerStatus::Committed),
-            "failed" => Ok(RelayerStatus::Failed),
-            "cancelled" => Ok(RelayerStatus::Cancelled),
-            _ => Err(format!("Invalid relayer status: {}", s)),
-        }
-    }
-}
-
-/// Commitment data for relayer
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CommitmentData {
-    pub merkle_root: BlockHash,
-    pub transaction_count: u32,
-    pub total_gas_used: u64,
-    pub total_fees: u64,
-    pub batch_hash: BlockHash,
-    pub proof_data: Vec<u8>, // Cryptographic proof
+/// Every queued batch of one set of transactions, oldest first in each stage
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchLineage {
+    pub batch_lineage_id: Uuid,
+    pub validation_batches: Vec<ValidationBatch>,
+    pub relayer_batches: Vec<RelayerBatch>,
 }
 
 /// Network peer model
@@ -234,6 +87,86 @@ pub struct ChainStats {
     pub avg_block_time: f64, // in seconds
     pub network_hash_rate: u64,
     pub active_addresses: u64,
+    pub pending_transactions: u64,
+    pub pending_bytes: u64,
+    pub fees_burned: u64,
+    pub fees_to_treasury: u64,
+    pub fees_to_proposers: u64,
+    /// Priority tips paid to proposers, on top of `fees_to_proposers`
+    pub tips_to_proposers: u64,
+}
+
+/// Transaction count and encoded bytes currently in the mempool
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct MempoolUsage {
+    pub transaction_count: u64,
+    pub total_bytes: u64,
+}
+
+/// Estimated on-disk size of one table, from the sampling node's token ranges
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSize {
+    pub table: String,
+    pub estimated_bytes: u64,
+    pub estimated_partitions: u64,
+}
+
+/// Driver counters since the adapter connected
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct DriverMetrics {
+    pub queries: u64,
+    pub errors: u64,
+    pub mean_latency_ms: u64,
+    pub p99_latency_ms: u64,
+}
+
+/// Per-datacenter node availability
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatacenterHealth {
+    pub datacenter: String,
+    pub is_local: bool,
+    pub total_nodes: u32,
+    pub down_nodes: u32,
+    pub is_healthy: bool,
+    pub serving_failover: bool, // Remote DC receiving traffic while local is degraded
+}
+
+/// Outcome of a contract storage garbage collection run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContractGcReport {
+    pub cutoff_height: BlockHeight,
+    pub contracts_collected: u64,
+    pub storage_rows_reclaimed: u64,
+    pub code_entries_reclaimed: u64,
+}
+
+/// Outcome of a payload blob garbage collection run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayloadGcReport {
+    pub releases_checked: u64,
+    pub blobs_deleted: u64,
+    /// Released within the grace period, left for a later run
+    pub releases_deferred: u64,
+}
+
+/// Outcome of a transaction archival run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchivalReport {
+    pub dictionary_id: i32,
+    pub archived_up_to: BlockHeight,
+    pub rows_archived: u64,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl ArchivalReport {
+    /// Fraction of stored bytes saved by archival
+    pub fn savings_ratio(&self) -> f64 {
+        if self.bytes_before == 0 {
+            return 0.0;
+        }
+        1.0 - self.bytes_after as f64 / self.bytes_before as f64
+    }
 }
 
 /// Hourly chain statistics
@@ -251,6 +184,112 @@ pub struct HourlyChainStats {
     pub active_addresses: u64,
 }
 
+/// Resolution chain statistics are served at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsGranularity {
+    Hourly,
+    Daily,
+    Weekly,
+}
+
+impl std::fmt::Display for StatsGranularity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatsGranularity::Hourly => write!(f, "hourly"),
+            StatsGranularity::Daily => write!(f, "daily"),
+            StatsGranularity::Weekly => write!(f, "weekly"),
+        }
+    }
+}
+
+/// Chain statistics for one hour, day or week
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainStatsPeriod {
+    pub granularity: StatsGranularity,
+    pub period_start: DateTime<Utc>,
+    pub total_blocks: u64,
+    pub total_transactions: u64,
+    pub total_value: u64,
+    pub total_fees: u64,
+    pub avg_block_time: f64,
+    pub avg_tx_per_block: f64,
+    pub network_hash_rate: u64,
+    pub active_addresses: u64,
+}
+
+/// Outcome of a chain statistics rollup run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsRollupReport {
+    pub days_rolled_up: u64,
+    pub weeks_rolled_up: u64,
+    /// Per-validator daily rows written
+    pub validator_days_rolled_up: u64,
+    /// Last day whose hourly rows have been rolled up
+    pub rolled_up_through: Option<chrono::NaiveDate>,
+}
+
+/// Outcome of rolling the stored chain back to a height
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RollbackReport {
+    /// Heights removed, highest first
+    pub removed_heights: Vec<BlockHeight>,
+    /// Transactions moved back to the mempool
+    pub requeued_transactions: u64,
+}
+
+/// Chain metric watched for anomalies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainMetric {
+    /// Average seconds between blocks
+    BlockTime,
+    /// Transactions per period
+    TxVolume,
+    /// Average fee per transaction
+    FeeLevel,
+}
+
+impl ChainMetric {
+    pub const ALL: [ChainMetric; 3] = [ChainMetric::BlockTime, ChainMetric::TxVolume, ChainMetric::FeeLevel];
+}
+
+impl std::fmt::Display for ChainMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainMetric::BlockTime => write!(f, "block_time"),
+            ChainMetric::TxVolume => write!(f, "tx_volume"),
+            ChainMetric::FeeLevel => write!(f, "fee_level"),
+        }
+    }
+}
+
+impl std::str::FromStr for ChainMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block_time" => Ok(ChainMetric::BlockTime),
+            "tx_volume" => Ok(ChainMetric::TxVolume),
+            "fee_level" => Ok(ChainMetric::FeeLevel),
+            _ => Err(format!("Unknown chain metric: {}", s)),
+        }
+    }
+}
+
+/// A period whose metric strayed too far from its recent trend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainAnomaly {
+    pub metric: ChainMetric,
+    pub period_start: DateTime<Utc>,
+    pub observed: f64,
+    /// EWMA of the preceding periods
+    pub expected: f64,
+    /// Standard deviations between `observed` and `expected`; negative when below trend
+    pub z_score: f64,
+    pub detected_at: DateTime<Utc>,
+}
+
 /// System configuration model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemConfig {
@@ -318,70 +357,23 @@ pub struct MempoolStats {
     pub newest_pending_age_seconds: u64,
 }
 
-impl ValidationBatch {
-    pub fn new(tx_hashes: Vec<TxHash>, validator_id: String) -> Self {
+impl DatacenterHealth {
+    pub fn new(datacenter: String, is_local: bool) -> Self {
         Self {
-            queue_id: Uuid::new_v4(),
-            batch_timestamp: Utc::now(),
-            tx_hashes,
-            validation_status: ValidationStatus::Pending,
-            validator_id,
-            started_at: None,
-            completed_at: None,
-            validation_result: None,
+            datacenter,
+            is_local,
+            total_nodes: 0,
+            down_nodes: 0,
+            is_healthy: true,
+            serving_failover: false,
         }
     }
 
-    pub fn start_processing(&mut self) {
-        self.validation_status = ValidationStatus::Processing;
-        self.started_at = Some(Utc::now());
-    }
-
-    pub fn complete_validation(&mut self, result: ValidationResult) {
-        self.validation_status = if result.is_valid {
-            ValidationStatus::Validated
-        } else {
-            ValidationStatus::Failed
-        };
-        self.completed_at = Some(Utc::now());
-        self.validation_result = Some(result);
-    }
-}
-
-impl RelayerBatch {
-    pub fn new(tx_hashes: Vec<TxHash>, relayer_id: String) -> Self {
-        Self {
-            commitment_id: Uuid::new_v4(),
-            batch_timestamp: Utc::now(),
-            tx_hashes,
-            status: RelayerStatus::Queued,
-            relayer_id,
-            retry_count: 0,
-            last_attempt: None,
-            target_block_height: None,
-            commitment_data: None,
+    pub fn healthy_ratio(&self) -> f64 {
+        if self.total_nodes == 0 {
+            return 0.0;
         }
-    }
-
-    pub fn start_processing(&mut self, target_block_height: BlockHeight) {
-        self.status = RelayerStatus::Processing;
-        self.last_attempt = Some(Utc::now());
-        self.target_block_height = Some(target_block_height);
-    }
-
-    pub fn mark_committed(&mut self, commitment_data: CommitmentData) {
-        self.status = RelayerStatus::Committed;
-        self.commitment_data = Some(commitment_data);
-    }
-
-    pub fn mark_failed(&mut self) {
-        self.status = RelayerStatus::Failed;
-        self.retry_count += 1;
-        self.last_attempt = Some(Utc::now());
-    }
-
-    pub fn can_retry(&self, max_retries: u32) -> bool {
-        self.retry_count < max_retries && self.status == RelayerStatus::Failed
+        (self.total_nodes - self.down_nodes) as f64 / self.total_nodes as f64
     }
 }
 
@@ -0,0 +1,231 @@
+// storage/scylla-adapter/src/network_peers.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use scylla::frame::response::result::Row;
+use std::net::SocketAddr;
+use storage_traits::{ConnectedPeers, KnownPeer, PeerBan, PeerStore, StorageOperation};
+
+use crate::model::{NetworkPeer, PeerStatus};
+use crate::{queries, ScyllaAdapter};
+
+/// Rows scanned for connected peers; the table also holds every peer
+/// discovery has heard of
+const CONNECTED_PEER_SCAN_LIMIT: i32 = 1_000;
+
+impl ScyllaAdapter {
+    pub async fn store_network_peer(&self, peer: &NetworkPeer) -> Result<()> {
+        self.fault_point(StorageOperation::StoreNetworkPeer).await?;
+        self.session_for(StorageOperation::StoreNetworkPeer)
+            .query(
+                queries::UPDATE_PEER,
+                (
+                    &peer.peer_id,
+                    peer.ip_address,
+                    peer.port as i32,
+                    peer.last_seen,
+                    &peer.version,
+                    peer.chain_height as i64,
+                    peer.status.to_string(),
+                    peer.connection_count as i32,
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` peers with a known address
+    pub async fn get_network_peers(&self, limit: i32) -> Result<Vec<NetworkPeer>> {
+        self.fault_point(StorageOperation::GetNetworkPeers).await?;
+        let rows = self.session_for(StorageOperation::GetNetworkPeers)
+            .query(queries::GET_NETWORK_PEERS, (limit,))
+            .await?;
+
+        let mut peers = Vec::new();
+        for row in rows.rows.unwrap_or_default() {
+            if let Some(peer) = network_peer_from_row(row)? {
+                peers.push(peer);
+            }
+        }
+        Ok(peers)
+    }
+
+    pub async fn get_network_peer(&self, peer_id: &str) -> Result<Option<NetworkPeer>> {
+        self.fault_point(StorageOperation::GetNetworkPeer).await?;
+        let rows = self.session_for(StorageOperation::GetNetworkPeer)
+            .query(queries::GET_PEER_BY_ID, (peer_id,))
+            .await?;
+
+        match rows.rows.unwrap_or_default().into_iter().next() {
+            Some(row) => network_peer_from_row(row),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a ban on the peer's row with a TTL ending when the ban does
+    pub async fn ban_network_peer(&self, ban: &PeerBan) -> Result<()> {
+        let remaining = ban.until - Utc::now().timestamp();
+        if remaining <= 0 {
+            return Ok(());
+        }
+        let until = Utc
+            .timestamp_opt(ban.until, 0)
+            .single()
+            .ok_or_else(|| anyhow::anyhow!("Invalid ban expiry: {}", ban.until))?;
+
+        self.fault_point(StorageOperation::BanNetworkPeer).await?;
+        self.session_for(StorageOperation::BanNetworkPeer)
+            .query(
+                queries::BAN_PEER,
+                (i32::try_from(remaining).unwrap_or(i32::MAX), until, &ban.reason, &ban.peer_id),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Bans in force at `now`; expired ones have normally been dropped by their TTL already
+    pub async fn get_peer_bans(&self, now: i64) -> Result<Vec<PeerBan>> {
+        self.fault_point(StorageOperation::GetPeerBans).await?;
+        let rows = self.session_for(StorageOperation::GetPeerBans)
+            .query(queries::GET_PEER_BANS, ())
+            .await?;
+
+        let mut bans = Vec::new();
+        for row in rows.rows.unwrap_or_default() {
+            let Some(until) = row.columns[1].as_ref().and_then(|col| col.as_datetime()) else {
+                continue;
+            };
+            if until.timestamp() <= now {
+                continue;
+            }
+            bans.push(PeerBan {
+                peer_id: row.columns[0].as_ref()
+                    .and_then(|col| col.as_text())
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("Missing peer_id"))?,
+                until: until.timestamp(),
+                reason: row.columns[2].as_ref()
+                    .and_then(|col| col.as_text())
+                    .cloned()
+                    .unwrap_or_default(),
+            });
+        }
+        Ok(bans)
+    }
+}
+
+/// Peer from a `network_peers` row selected in `UPDATE_PEER` column order
+/// followed by `banned_until`; `None` for rows holding only a signed peer
+/// record, which have no address
+fn network_peer_from_row(row: Row) -> Result<Option<NetworkPeer>> {
+    let Some(ip_address) = row.columns[1].as_ref().and_then(|col| col.as_inet()) else {
+        return Ok(None);
+    };
+
+    Ok(Some(NetworkPeer {
+        peer_id: row.columns[0].as_ref()
+            .and_then(|col| col.as_text())
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Missing peer_id"))?,
+        ip_address,
+        port: row.columns[2].as_ref()
+            .and_then(|col| col.as_int())
+            .and_then(|port| u16::try_from(port).ok())
+            .ok_or_else(|| anyhow::anyhow!("Missing or invalid peer port"))?,
+        last_seen: row.columns[3].as_ref()
+            .and_then(|col| col.as_datetime())
+            .unwrap_or_else(Utc::now),
+        version: row.columns[4].as_ref()
+            .and_then(|col| col.as_text())
+            .cloned()
+            .unwrap_or_default(),
+        chain_height: row.columns[5].as_ref()
+            .and_then(|col| col.as_bigint())
+            .unwrap_or(0) as u64,
+        // A ban in force overrides whatever status discovery last wrote
+        status: if row.columns[8].as_ref()
+            .and_then(|col| col.as_datetime())
+            .is_some_and(|until| until > Utc::now())
+        {
+            PeerStatus::Banned
+        } else {
+            row.columns[6].as_ref()
+                .and_then(|col| col.as_text())
+                .and_then(|status| status.parse().ok())
+                .unwrap_or(PeerStatus::Disconnected)
+        },
+        connection_count: row.columns[7].as_ref()
+            .and_then(|col| col.as_int())
+            .unwrap_or(0) as u32,
+    }))
+}
+
+#[async_trait]
+impl PeerStore for ScyllaAdapter {
+    async fn save_known_peers(&self, peers: &[KnownPeer]) -> Result<()> {
+        for known in peers {
+            let mut peer = NetworkPeer::new(
+                known.peer_id.clone(),
+                known.addr.ip(),
+                known.addr.port(),
+                known.protocol_version.map(|v| v.to_string()).unwrap_or_default(),
+            );
+            if let Some(last_seen) = Utc.timestamp_opt(known.last_seen, 0).single() {
+                peer.last_seen = last_seen;
+            }
+            self.store_network_peer(&peer).await?;
+        }
+        Ok(())
+    }
+
+    async fn record_connected_peer(&self, known: &KnownPeer, chain_height: u64) -> Result<()> {
+        let previous = self.get_network_peer(&known.peer_id).await?;
+        let mut peer = NetworkPeer::new(
+            known.peer_id.clone(),
+            known.addr.ip(),
+            known.addr.port(),
+            known.protocol_version.map(|v| v.to_string()).unwrap_or_default(),
+        );
+        peer.chain_height = chain_height;
+        peer.status = PeerStatus::Connected;
+        peer.connection_count = previous.map_or(0, |p| p.connection_count).saturating_add(1);
+        self.store_network_peer(&peer).await
+    }
+
+    async fn load_known_peers(&self, limit: usize) -> Result<Vec<KnownPeer>> {
+        let limit = i32::try_from(limit).unwrap_or(i32::MAX);
+        Ok(self
+            .get_network_peers(limit)
+            .await?
+            .into_iter()
+            .filter(|peer| peer.status != PeerStatus::Banned)
+            .map(|peer| KnownPeer {
+                addr: SocketAddr::new(peer.ip_address, peer.port),
+                protocol_version: peer.version.parse().ok(),
+                last_seen: peer.last_seen.timestamp(),
+                peer_id: peer.peer_id,
+            })
+            .collect())
+    }
+
+    async fn record_peer_ban(&self, ban: &PeerBan) -> Result<()> {
+        self.ban_network_peer(ban).await
+    }
+
+    async fn load_peer_bans(&self, now: i64) -> Result<Vec<PeerBan>> {
+        self.get_peer_bans(now).await
+    }
+}
+
+#[async_trait]
+impl ConnectedPeers for ScyllaAdapter {
+    async fn connected_peer_heights(&self) -> Result<Vec<u64>> {
+        Ok(self
+            .get_network_peers(CONNECTED_PEER_SCAN_LIMIT)
+            .await?
+            .into_iter()
+            .filter(|peer| peer.status == PeerStatus::Connected)
+            .map(|peer| peer.chain_height)
+            .collect())
+    }
+}
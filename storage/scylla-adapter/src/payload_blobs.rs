@@ -0,0 +1,230 @@
+// storage/scylla-adapter/src/payload_blobs.rs
+//! Large transaction payloads stored once, by content hash.
+//!
+//! Deploy code, init data and call data of at least
+//! `payload_blobs.min_size` bytes go to `payload_blobs` under their SHA-256
+//! hash. The transaction's `tx_data` keeps the field empty and
+//! `payload_refs` maps the field name to the hash; `get_transaction` puts
+//! the bytes back before returning it. `block_data` still holds whole
+//! transactions, so block reads, rollback and migration never resolve
+//! anything.
+//!
+//! A reference is a `payload_blob_refs` row per transaction, so storing or
+//! rolling back a transaction twice leaves the count right. A rolled back
+//! transaction releases its blobs, and `collect_payload_blobs` deletes
+//! released blobs nothing references any more. That delete is backdated by
+//! `gc_grace_secs`: storing a transaction rewrites its blobs before adding
+//! its references, so a blob a transaction is being stored with outlives a
+//! collection that counted no references for it.
+use anyhow::{anyhow, Result};
+use blockchain_core::{hash_data, Transaction, TransactionType, TxHash};
+use chrono::{Duration, Utc};
+use scylla::frame::response::result::CqlValue;
+use std::collections::HashMap;
+use storage_traits::StorageOperation;
+
+use crate::model::PayloadGcReport;
+use crate::{encryption, queries, ScyllaAdapter};
+
+/// `payload_refs` key of `Deploy::code`
+pub const CODE_FIELD: &str = "code";
+
+/// `payload_refs` key of `Deploy::init_data`
+pub const INIT_DATA_FIELD: &str = "init_data";
+
+/// `payload_refs` key of `Call::data`
+pub const CALL_DATA_FIELD: &str = "data";
+
+fn payload_fields(tx: &mut Transaction) -> Vec<(&'static str, &mut Vec<u8>)> {
+    match &mut tx.tx_type {
+        TransactionType::Deploy { code, init_data, .. } => vec![(CODE_FIELD, code), (INIT_DATA_FIELD, init_data)],
+        TransactionType::Call { data, .. } => vec![(CALL_DATA_FIELD, data)],
+        TransactionType::Transfer { .. } | TransactionType::Coinbase { .. } => Vec::new(),
+    }
+}
+
+/// `tx` with every payload of at least `min_size` bytes emptied, and those
+/// payloads by field name
+pub fn split_payloads(tx: &Transaction, min_size: usize) -> (Transaction, Vec<(&'static str, Vec<u8>)>) {
+    let mut stripped = tx.clone();
+    let payloads = payload_fields(&mut stripped)
+        .into_iter()
+        .filter(|(_, bytes)| bytes.len() >= min_size)
+        .map(|(field, bytes)| (field, std::mem::take(bytes)))
+        .collect();
+    (stripped, payloads)
+}
+
+/// Put payloads taken out by `split_payloads` back into `tx`
+pub fn restore_payloads(tx: &mut Transaction, mut payloads: HashMap<String, Vec<u8>>) -> Result<()> {
+    for (field, bytes) in payload_fields(tx) {
+        if let Some(payload) = payloads.remove(field) {
+            *bytes = payload;
+        }
+    }
+    match payloads.keys().next() {
+        Some(field) => Err(anyhow!("Transaction 0x{} has no payload field {}", hex::encode(tx.hash), field)),
+        None => Ok(()),
+    }
+}
+
+impl ScyllaAdapter {
+    /// Store `tx`'s large payloads and reference them from it, returning `tx`
+    /// without them and its `payload_refs`, `None` when nothing was large
+    pub(crate) async fn externalize_payloads(
+        &self,
+        tx: &Transaction,
+    ) -> Result<(Transaction, Option<HashMap<String, Vec<u8>>>)> {
+        let (stripped, payloads) = split_payloads(tx, self.config.payload_blobs.min_size);
+        if payloads.is_empty() {
+            return Ok((stripped, None));
+        }
+
+        let session = self.session_for(StorageOperation::StoreTransaction);
+        let mut refs = HashMap::new();
+        for (field, bytes) in payloads {
+            let blob_hash = hash_data(&bytes).to_vec();
+            let size = bytes.len() as i64;
+            let data = self.encryptor.encrypt(encryption::PAYLOADS_CONTEXT, &blob_hash, bytes)?;
+            // Always rewritten, even when present, so a concurrent collection cannot remove it
+            session.query(queries::INSERT_PAYLOAD_BLOB, (blob_hash.clone(), data, size)).await?;
+            session.query(queries::INSERT_PAYLOAD_REF, (blob_hash.clone(), tx.hash.to_vec())).await?;
+            refs.insert(field.to_string(), blob_hash);
+        }
+        Ok((stripped, Some(refs)))
+    }
+
+    /// Fetch the blobs a `payload_refs` column points at into `tx`
+    pub(crate) async fn resolve_payloads(&self, tx: &mut Transaction, payload_refs: Option<&CqlValue>) -> Result<()> {
+        let Some(refs) = payload_refs.and_then(|col| col.as_map()) else {
+            return Ok(());
+        };
+
+        let session = self.session_for(StorageOperation::GetTransaction);
+        let mut payloads = HashMap::new();
+        for (field, blob_hash) in refs {
+            let (Some(field), Some(blob_hash)) = (field.as_text(), blob_hash.as_blob()) else {
+                continue;
+            };
+            let rows = session.query(queries::GET_PAYLOAD_BLOB, (blob_hash.clone(),)).await?;
+            let stored = rows.maybe_first_row()?
+                .and_then(|row| row.columns[0].clone())
+                .and_then(|col| col.into_blob())
+                .ok_or_else(|| anyhow!("Payload blob 0x{} is missing", hex::encode(blob_hash)))?;
+            let bytes = self.encryptor.decrypt(encryption::PAYLOADS_CONTEXT, blob_hash, &stored)?;
+            if hash_data(&bytes).as_slice() != blob_hash.as_slice() {
+                return Err(anyhow!("Payload blob 0x{} does not match its hash", hex::encode(blob_hash)));
+            }
+            payloads.insert(field.clone(), bytes);
+        }
+        restore_payloads(tx, payloads)
+    }
+
+    /// Drop `tx_hash`'s references and queue the blobs for collection; call
+    /// before deleting its `transactions` row
+    pub(crate) async fn release_payloads(&self, tx_hash: &TxHash) -> Result<()> {
+        let session = self.session_for(StorageOperation::RollbackBlocks);
+        let rows = session.query(queries::GET_TX_PAYLOAD_REFS, (tx_hash.to_vec(),)).await?;
+        let Some(refs) = rows.maybe_first_row()?.and_then(|row| row.columns[0].clone()) else {
+            return Ok(());
+        };
+
+        for blob_hash in refs.as_map().into_iter().flatten().filter_map(|(_, hash)| hash.as_blob()) {
+            session.query(queries::DELETE_PAYLOAD_REF, (blob_hash.clone(), tx_hash.to_vec())).await?;
+            session.query(queries::INSERT_PAYLOAD_RELEASE, (blob_hash.clone(), Utc::now())).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete released blobs nothing references, checking up to `limit`
+    /// releases. Releases younger than the grace period are left for a later
+    /// run, since the delete would not yet reach their blobs.
+    pub async fn collect_payload_blobs(&self, limit: i32) -> Result<PayloadGcReport> {
+        self.fault_point(StorageOperation::CollectPayloadBlobs).await?;
+        let session = self.session_for(StorageOperation::CollectPayloadBlobs);
+        let grace = Duration::seconds(self.config.payload_blobs.gc_grace_secs as i64);
+        let mut report = PayloadGcReport::default();
+
+        let releases = session.query(queries::GET_PAYLOAD_RELEASES, (limit,)).await?;
+        for row in releases.rows.unwrap_or_default() {
+            let Some(blob_hash) = row.columns[0].as_ref().and_then(|col| col.as_blob()).cloned() else {
+                continue;
+            };
+            let released_at = row.columns[1].as_ref().and_then(|col| col.as_datetime()).unwrap_or_default();
+            let deleted_at = Utc::now() - grace;
+            if released_at > deleted_at {
+                report.releases_deferred += 1;
+                continue;
+            }
+            report.releases_checked += 1;
+
+            let refs = session
+                .query(queries::COUNT_PAYLOAD_REFS, (blob_hash.clone(),))
+                .await?
+                .maybe_first_row()?
+                .and_then(|row| row.columns[0].as_ref().and_then(|col| col.as_bigint()))
+                .unwrap_or(0);
+            if refs == 0 {
+                session
+                    .query(queries::DELETE_PAYLOAD_BLOB, (deleted_at.timestamp_micros(), blob_hash.clone()))
+                    .await?;
+                report.blobs_deleted += 1;
+            }
+            session.query(queries::DELETE_PAYLOAD_RELEASE, (blob_hash,)).await?;
+        }
+        Ok(report)
+    }
+
+    /// Re-encrypt the blobs `tx_hash` references that are under a retired
+    /// data key, returning how many were rewritten
+    pub(crate) async fn rotate_payload_encryption(&self, tx_hash: &TxHash) -> Result<u64> {
+        let session = self.primary_session();
+        let rows = session.query(queries::GET_TX_PAYLOAD_REFS, (tx_hash.to_vec(),)).await?;
+        let Some(refs) = rows.maybe_first_row()?.and_then(|row| row.columns[0].clone()) else {
+            return Ok(0);
+        };
+
+        let mut rewritten = 0;
+        for blob_hash in refs.as_map().into_iter().flatten().filter_map(|(_, hash)| hash.as_blob()) {
+            let rows = session.query(queries::GET_PAYLOAD_BLOB, (blob_hash.clone(),)).await?;
+            let Some(stored) = rows.maybe_first_row()?
+                .and_then(|row| row.columns[0].clone())
+                .and_then(|col| col.into_blob())
+            else {
+                continue;
+            };
+            if self.encryptor.needs_rotation(&stored) {
+                let reencrypted = self.encryptor.reencrypt(encryption::PAYLOADS_CONTEXT, blob_hash, &stored)?;
+                session.query(queries::UPDATE_PAYLOAD_BLOB_DATA, (reencrypted, blob_hash.clone())).await?;
+                rewritten += 1;
+            }
+        }
+        Ok(rewritten)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splits_and_restores_large_payloads() {
+        let deploy = Transaction::new_deploy([1; 20], vec![7; 2048], vec![1, 2], 0, 100_000, 1).unwrap();
+        let (stripped, payloads) = split_payloads(&deploy, 1024);
+        assert_eq!(payloads, vec![(CODE_FIELD, vec![7; 2048])]);
+        assert!(matches!(&stripped.tx_type, TransactionType::Deploy { code, init_data, .. }
+            if code.is_empty() && init_data == &vec![1, 2]));
+        // Hash and signature still describe the whole transaction
+        assert_eq!((stripped.hash, &stripped.signature), (deploy.hash, &deploy.signature));
+
+        let mut restored = stripped.clone();
+        let found = payloads.into_iter().map(|(field, bytes)| (field.to_string(), bytes)).collect();
+        restore_payloads(&mut restored, found).unwrap();
+        assert_eq!(restored, deploy);
+
+        let transfer = Transaction::new_transfer([1; 20], [2; 20], 5, 0, 21_000, 1).unwrap();
+        assert!(split_payloads(&transfer, 1).1.is_empty());
+        let stray = HashMap::from([(CALL_DATA_FIELD.to_string(), vec![1])]);
+        assert!(restore_payloads(&mut transfer.clone(), stray).is_err());
+    }
+}
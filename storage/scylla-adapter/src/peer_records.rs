@@ -0,0 +1,47 @@
+// storage/scylla-adapter/src/peer_records.rs
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use storage_traits::StorageOperation;
+
+use crate::{queries, ScyllaAdapter};
+
+impl ScyllaAdapter {
+    /// Persist a signed peer record as received.
+    ///
+    /// The record is stored opaque; callers verify it again after loading,
+    /// before dialing any address it advertises.
+    pub async fn store_peer_record(
+        &self,
+        peer_id: &str,
+        public_key: &[u8],
+        record: &[u8],
+        signed_at: DateTime<Utc>,
+    ) -> Result<()> {
+        self.fault_point(StorageOperation::StorePeerRecord).await?;
+        self.session_for(StorageOperation::StorePeerRecord)
+            .query(
+                queries::UPSERT_PEER_RECORD,
+                (
+                    peer_id,
+                    public_key.to_vec(),
+                    record.to_vec(),
+                    signed_at,
+                    signed_at.timestamp_micros(),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Latest stored record for a peer
+    pub async fn get_peer_record(&self, peer_id: &str) -> Result<Option<Vec<u8>>> {
+        self.fault_point(StorageOperation::GetPeerRecord).await?;
+        let rows = self.session_for(StorageOperation::GetPeerRecord)
+            .query(queries::GET_PEER_RECORD, (peer_id,))
+            .await?;
+
+        Ok(rows.maybe_first_row()?
+            .and_then(|row| row.columns[0].clone())
+            .and_then(|col| col.into_blob()))
+    }
+}
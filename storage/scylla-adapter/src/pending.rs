@@ -0,0 +1,305 @@
+// storage/scylla-adapter/src/pending.rs
+use anyhow::Result;
+use blockchain_core::{Address, Transaction};
+use bytes::Bytes;
+use chrono::{DateTime, Duration, Utc};
+use futures::{future, Stream, TryStreamExt};
+use scylla::frame::response::result::Row;
+use scylla::frame::value::Counter;
+use scylla::query::Query;
+use storage_traits::StorageOperation;
+
+use crate::model::MempoolUsage;
+use crate::relayer_queue::applied;
+use crate::{encryption, format, queries, ScyllaAdapter};
+
+pub use storage_traits::PendingTxPage;
+
+/// Seconds a pending transaction is kept after it was last submitted
+pub const PENDING_TX_TTL_SECS: i64 = 3600;
+
+/// Width in seconds of a `mempool_usage` bucket
+pub const MEMPOOL_USAGE_BUCKET_SECS: i64 = 300;
+
+/// `mempool_usage` bucket of a pending row written at `now`, the one its TTL ends in
+pub fn usage_bucket(now: DateTime<Utc>) -> i64 {
+    (now.timestamp() + PENDING_TX_TTL_SECS).div_euclid(MEMPOOL_USAGE_BUCKET_SECS)
+}
+
+/// Buckets that can hold live rows at `now`, from the one ending now to the
+/// one a row written now falls in. Rows that expired earlier in the current
+/// bucket are still counted until it ends.
+pub fn live_usage_buckets(now: DateTime<Utc>) -> Vec<i64> {
+    (now.timestamp().div_euclid(MEMPOOL_USAGE_BUCKET_SECS)..=usage_bucket(now)).collect()
+}
+
+/// Size and `mempool_usage` bucket counted for a stored pending row; rows
+/// written before usage was counted have no bucket
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CountedUsage {
+    pub tx_size: i64,
+    pub bucket: Option<i64>,
+}
+
+impl CountedUsage {
+    /// From the `tx_size, usage_bucket` columns starting at `first`
+    pub(crate) fn from_row(row: &Row, first: usize) -> Self {
+        Self {
+            tx_size: row.columns[first].as_ref().and_then(|col| col.as_bigint()).unwrap_or(0),
+            bucket: row.columns[first + 1].as_ref().and_then(|col| col.as_bigint()),
+        }
+    }
+}
+
+/// Filters applied while reading the mempool
+#[derive(Debug, Clone, Default)]
+pub struct PendingTxFilter {
+    /// Only transactions from this sender; served by `pending_tx_sender_idx`
+    pub sender: Option<Address>,
+    /// Minimum gas price in wei
+    pub min_gas_price: Option<u64>,
+    /// Skip transactions that have waited longer than this
+    pub max_age: Option<Duration>,
+}
+
+impl PendingTxFilter {
+    pub fn by_sender(sender: Address) -> Self {
+        Self {
+            sender: Some(sender),
+            ..Default::default()
+        }
+    }
+
+    pub fn min_gas_price(mut self, gas_price: u64) -> Self {
+        self.min_gas_price = Some(gas_price);
+        self
+    }
+
+    pub fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// Check the non-key columns of a pending row
+    pub fn matches(&self, gas_price: u64, received_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        if self.min_gas_price.is_some_and(|min| gas_price < min) {
+            return false;
+        }
+        if self.max_age.is_some_and(|age| now - received_at > age) {
+            return false;
+        }
+        true
+    }
+}
+
+impl ScyllaAdapter {
+    /// Number of pending transactions and their total encoded size, summed
+    /// over the live `mempool_usage` buckets rather than the pool itself. A
+    /// counter write lost or repeated by a failure only skews its own bucket,
+    /// which leaves the window with the rows it counted.
+    pub async fn get_mempool_usage(&self) -> Result<MempoolUsage> {
+        self.fault_point(StorageOperation::GetMempoolUsage).await?;
+        let rows = self.session_for(StorageOperation::GetMempoolUsage)
+            .query(queries::GET_MEMPOOL_USAGE, (live_usage_buckets(Utc::now()),))
+            .await?;
+
+        let (mut transaction_count, mut total_bytes) = (0i64, 0i64);
+        for row in rows.rows.unwrap_or_default() {
+            let counter = |i: usize| row.columns[i].as_ref().and_then(|col| col.as_counter()).map_or(0, |c| c.0);
+            transaction_count += counter(0);
+            total_bytes += counter(1);
+        }
+        Ok(MempoolUsage {
+            transaction_count: transaction_count.max(0) as u64,
+            total_bytes: total_bytes.max(0) as u64,
+        })
+    }
+
+    /// Add `count` transactions of `bytes` in total, either possibly
+    /// negative, to a `mempool_usage` bucket
+    pub(crate) async fn add_mempool_usage(
+        &self,
+        operation: StorageOperation,
+        bucket: i64,
+        count: i64,
+        bytes: i64,
+    ) -> Result<()> {
+        self.session_for(operation)
+            .query(queries::ADD_MEMPOOL_USAGE, (Counter(count), Counter(bytes), bucket))
+            .await?;
+        Ok(())
+    }
+
+    /// Stream pending transactions matching `filter`, fetching `page_size` rows at a time.
+    ///
+    /// Rows are decoded as they arrive, so the pool is never materialized in memory.
+    pub async fn stream_pending_transactions(
+        &self,
+        filter: PendingTxFilter,
+        page_size: i32,
+    ) -> Result<impl Stream<Item = Result<Transaction>> + '_> {
+        self.fault_point(StorageOperation::GetPendingTransactions).await?;
+        let session = self.session_for(StorageOperation::GetPendingTransactions);
+        let rows = match filter.sender {
+            Some(sender) => {
+                let query = Query::new(queries::GET_PENDING_TX_PAGE_BY_SENDER).with_page_size(page_size);
+                session.query_iter(query, (sender.to_vec(),)).await?
+            }
+            None => {
+                let query = Query::new(queries::GET_PENDING_TX_PAGE).with_page_size(page_size);
+                session.query_iter(query, ()).await?
+            }
+        };
+
+        let now = Utc::now();
+        Ok(rows
+            .map_err(anyhow::Error::from)
+            .try_filter_map(move |row| future::ready(self.decode_pending_row(&row, &filter, now))))
+    }
+
+    /// Fetch a single page of pending transactions matching `filter`.
+    ///
+    /// Pass the returned `paging_state` back in to continue; a page may hold
+    /// fewer than `page_size` transactions when rows are filtered out.
+    pub async fn get_pending_transactions_page(
+        &self,
+        filter: &PendingTxFilter,
+        page_size: i32,
+        paging_state: Option<Vec<u8>>,
+    ) -> Result<PendingTxPage> {
+        self.fault_point(StorageOperation::GetPendingTransactions).await?;
+        let session = self.session_for(StorageOperation::GetPendingTransactions);
+        let paging_state = paging_state.map(Bytes::from);
+        let result = match filter.sender {
+            Some(sender) => {
+                let query = Query::new(queries::GET_PENDING_TX_PAGE_BY_SENDER).with_page_size(page_size);
+                session.query_paged(query, (sender.to_vec(),), paging_state).await?
+            }
+            None => {
+                let query = Query::new(queries::GET_PENDING_TX_PAGE).with_page_size(page_size);
+                session.query_paged(query, (), paging_state).await?
+            }
+        };
+
+        let now = Utc::now();
+        let next_state = result.paging_state.as_ref().map(|state| state.to_vec());
+        let mut transactions = Vec::new();
+        for row in result.rows.unwrap_or_default() {
+            if let Some(tx) = self.decode_pending_row(&row, filter, now)? {
+                transactions.push(tx);
+            }
+        }
+
+        Ok(PendingTxPage {
+            transactions,
+            paging_state: next_state,
+        })
+    }
+
+    /// Re-encrypt pending transactions under a retired data key, returning
+    /// how many were rewritten; rows removed meanwhile are left removed
+    pub(crate) async fn rotate_pending_encryption(&self) -> Result<u64> {
+        let session = self.primary_session();
+        let rows = session.query(queries::GET_PENDING_TX_CIPHERTEXTS, ()).await?;
+
+        let mut rewritten = 0;
+        for row in rows.rows.unwrap_or_default() {
+            let Some(stored) = row.columns[3].as_ref().and_then(|col| col.as_blob()) else {
+                continue;
+            };
+            if !self.encryptor.needs_rotation(stored) {
+                continue;
+            }
+            let priority_score = row.columns[0].as_ref()
+                .and_then(|col| col.as_bigint())
+                .ok_or_else(|| anyhow::anyhow!("Missing priority_score"))?;
+            let timestamp = row.columns[1].as_ref()
+                .and_then(|col| col.as_datetime())
+                .ok_or_else(|| anyhow::anyhow!("Missing timestamp"))?;
+            let tx_hash = row.columns[2].as_ref()
+                .and_then(|col| col.as_blob())
+                .ok_or_else(|| anyhow::anyhow!("Missing tx_hash"))?;
+            let ttl = row.columns[4].as_ref().and_then(|col| col.as_int()).unwrap_or(0);
+
+            let reencrypted = self.encryptor.reencrypt(encryption::PENDING_CONTEXT, tx_hash, stored)?;
+            let result = session
+                .query(
+                    queries::UPDATE_PENDING_TX_DATA,
+                    (ttl, reencrypted, priority_score, timestamp, tx_hash.clone()),
+                )
+                .await?;
+            if applied(&result) {
+                rewritten += 1;
+            }
+        }
+        Ok(rewritten)
+    }
+
+    /// Decode a `(gas_price, timestamp, tx_data, tx_hash)` row, or `None` when filtered out
+    fn decode_pending_row(
+        &self,
+        row: &Row,
+        filter: &PendingTxFilter,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Transaction>> {
+        let gas_price = row.columns[0].as_ref()
+            .and_then(|col| col.as_bigint())
+            .unwrap_or(0) as u64;
+        let received_at = row.columns[1].as_ref()
+            .and_then(|col| col.as_datetime())
+            .unwrap_or(now);
+
+        if !filter.matches(gas_price, received_at, now) {
+            return Ok(None);
+        }
+
+        let tx_hash = row.columns[3].as_ref().and_then(|col| col.as_blob());
+        match (row.columns[2].as_ref().and_then(|col| col.as_blob()), tx_hash) {
+            (Some(tx_data), Some(tx_hash)) => {
+                let tx_data = self.encryptor.decrypt(encryption::PENDING_CONTEXT, tx_hash, tx_data)?;
+                Ok(Some(format::decode(&tx_data)?))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_live_buckets_cover_every_unexpired_row() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 2, 30).unwrap();
+        let buckets = live_usage_buckets(now);
+        assert_eq!(buckets.first(), Some(&now.timestamp().div_euclid(MEMPOOL_USAGE_BUCKET_SECS)));
+        assert_eq!(buckets.last(), Some(&usage_bucket(now)));
+
+        // Rows written within the TTL are read; older ones have left the window
+        for age in [0, 1, 1_800, PENDING_TX_TTL_SECS - 1] {
+            assert!(buckets.contains(&usage_bucket(now - Duration::seconds(age))), "age {}", age);
+        }
+        let expired = usage_bucket(now - Duration::seconds(PENDING_TX_TTL_SECS + MEMPOOL_USAGE_BUCKET_SECS));
+        assert!(!buckets.contains(&expired));
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        let now = Utc::now();
+        let filter = PendingTxFilter::default();
+        assert!(filter.matches(0, now - Duration::days(7), now));
+    }
+
+    #[test]
+    fn test_gas_price_and_age_filters() {
+        let now = Utc::now();
+        let filter = PendingTxFilter::by_sender([1u8; 20])
+            .min_gas_price(20)
+            .max_age(Duration::minutes(10));
+
+        assert!(filter.matches(20, now - Duration::minutes(5), now));
+        assert!(!filter.matches(19, now - Duration::minutes(5), now));
+        assert!(!filter.matches(25, now - Duration::minutes(11), now));
+    }
+}
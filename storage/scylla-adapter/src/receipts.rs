@@ -0,0 +1,162 @@
+// storage/scylla-adapter/src/receipts.rs
+use anyhow::Result;
+use blockchain_core::{BlockHash, BlockHeight, BlockOutcome, FeeSplit, TransactionReceipt, TxHash};
+use std::collections::HashSet;
+use storage_traits::StorageOperation;
+
+use crate::intent_log::Intent;
+use crate::{queries, ScyllaAdapter};
+
+impl ScyllaAdapter {
+    /// Confirm the transactions of the applied block `block_hash`: record
+    /// their receipts, add the block's fees to the chain totals and drop them
+    /// from the pending pool, under an intent.
+    ///
+    /// The per-block row is inserted with a lightweight transaction and the
+    /// counters are only bumped when it was new, so re-storing a block after a
+    /// retry does not count its fees twice.
+    pub async fn store_block_outcome(
+        &self,
+        height: BlockHeight,
+        block_hash: &BlockHash,
+        outcome: &BlockOutcome,
+    ) -> Result<()> {
+        self.fault_point(StorageOperation::StoreReceipts).await?;
+        let intent = Intent::ConfirmBlock { height, block_hash: *block_hash, outcome: outcome.clone() };
+        let handle = self.begin_intent(&intent).await?;
+        self.apply_block_outcome(height, outcome).await?;
+        self.complete_intent(handle).await
+    }
+
+    /// Write everything `store_block_outcome` records; every step can be replayed
+    pub(crate) async fn apply_block_outcome(&self, height: BlockHeight, outcome: &BlockOutcome) -> Result<()> {
+        let session = self.session_for(StorageOperation::StoreReceipts);
+
+        for receipt in &outcome.receipts {
+            session
+                .query(
+                    queries::INSERT_TRANSACTION_RECEIPT,
+                    (
+                        receipt.tx_hash.to_vec(),
+                        receipt.block_height as i64,
+                        receipt.index as i32,
+                        receipt.fee.burned as i64,
+                        receipt.fee.treasury as i64,
+                        receipt.fee.proposer as i64,
+                        receipt.fee.tip as i64,
+                    ),
+                )
+                .await?;
+        }
+
+        let fees = &outcome.fees;
+        let result = session
+            .query(
+                queries::INSERT_BLOCK_FEE_SPLIT,
+                (height as i64, fees.burned as i64, fees.treasury as i64, fees.proposer as i64, fees.tip as i64),
+            )
+            .await?;
+        let applied = result.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_boolean())
+            .unwrap_or(false);
+
+        if applied {
+            session
+                .query(
+                    queries::INCREMENT_FEE_TOTALS,
+                    (
+                        scylla::frame::value::Counter(fees.burned as i64),
+                        scylla::frame::value::Counter(fees.treasury as i64),
+                        scylla::frame::value::Counter(fees.proposer as i64),
+                        scylla::frame::value::Counter(fees.tip as i64),
+                    ),
+                )
+                .await?;
+        }
+
+        for receipt in &outcome.receipts {
+            self.remove_pending_transaction_now(&receipt.tx_hash).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Undo `apply_block_outcome` for a block no longer stored at `height`,
+    /// keeping the receipts of `kept` transactions, which the block stored
+    /// there now also confirmed.
+    ///
+    /// The fee split row is keyed by height alone, so it is only removed when
+    /// no block is stored at `height` that could own it.
+    pub(crate) async fn discard_block_outcome(
+        &self,
+        height: BlockHeight,
+        outcome: &BlockOutcome,
+        kept: &HashSet<TxHash>,
+        height_vacant: bool,
+    ) -> Result<()> {
+        let session = self.session_for(StorageOperation::RollbackBlocks);
+        for receipt in outcome.receipts.iter().filter(|receipt| !kept.contains(&receipt.tx_hash)) {
+            session.query(queries::DELETE_TRANSACTION_RECEIPT, (receipt.tx_hash.to_vec(),)).await?;
+        }
+        if height_vacant {
+            self.rollback_fee_split(height as i64).await?;
+        }
+        Ok(())
+    }
+
+    /// Receipt of a transaction included in an applied block
+    pub async fn get_transaction_receipt(&self, tx_hash: &TxHash) -> Result<Option<TransactionReceipt>> {
+        self.fault_point(StorageOperation::GetReceipt).await?;
+        let rows = self.session_for(StorageOperation::GetReceipt)
+            .query(queries::GET_TRANSACTION_RECEIPT, (tx_hash.to_vec(),))
+            .await?;
+
+        let Some(row) = rows.maybe_first_row()? else {
+            return Ok(None);
+        };
+        let bigint = |i: usize| {
+            row.columns[i].as_ref()
+                .and_then(|col| col.as_bigint())
+                .ok_or_else(|| anyhow::anyhow!("Missing receipt column {}", i))
+        };
+
+        Ok(Some(TransactionReceipt {
+            tx_hash: *tx_hash,
+            block_height: bigint(1)? as BlockHeight,
+            index: row.columns[2].as_ref().and_then(|col| col.as_int()).unwrap_or(0) as u32,
+            fee: FeeSplit {
+                burned: bigint(3)? as u64,
+                treasury: bigint(4)? as u64,
+                proposer: bigint(5)? as u64,
+                // Receipts stored before tips have none
+                tip: row.columns[6].as_ref().and_then(|col| col.as_bigint()).unwrap_or(0) as u64,
+            },
+        }))
+    }
+
+    /// Fees burned, paid to the treasury and paid to proposers, and tips, since genesis
+    pub(crate) async fn get_fee_totals(&self) -> Result<FeeSplit> {
+        let rows = self.session_for(StorageOperation::GetChainStats)
+            .query(queries::GET_FEE_TOTALS, ())
+            .await?;
+
+        let Some(row) = rows.maybe_first_row()? else {
+            return Ok(FeeSplit::default());
+        };
+        let counter = |i: usize| {
+            row.columns[i].as_ref()
+                .and_then(|col| col.as_counter())
+                .map(|counter| counter.0 as u64)
+                .unwrap_or(0)
+        };
+
+        Ok(FeeSplit {
+            burned: counter(0),
+            treasury: counter(1),
+            proposer: counter(2),
+            tip: counter(3),
+        })
+    }
+}
@@ -0,0 +1,391 @@
+// storage/scylla-adapter/src/relayer_queue.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use blockchain_core::TxHash;
+use storage_traits::{
+    ClaimStore, PendingTxPage, RelayerBacklog, RelayerRecoveryStore, RelayerStore, RetryStore, StorageOperation,
+};
+use uuid::Uuid;
+
+use crate::model::{RelayerBatch, RelayerStatus};
+use crate::pending::PendingTxFilter;
+use crate::tx_lifecycle::{decode_relayer_batch, hash_list};
+use crate::{queries, ScyllaAdapter};
+
+impl ScyllaAdapter {
+    /// Persist a batch that `mark_committed` moved to `Committed`, together
+    /// with its commitment and target inclusion
+    pub async fn mark_relayer_batch_committed(&self, batch: &RelayerBatch) -> Result<()> {
+        self.fault_point(StorageOperation::CommitRelayerBatch).await?;
+        let (Some(commitment), Some(inclusion)) = (&batch.commitment_data, &batch.target_inclusion) else {
+            return Err(anyhow::anyhow!(
+                "Batch {} has no confirmed commitment to record",
+                batch.commitment_id
+            ));
+        };
+        if batch.status != RelayerStatus::Committed {
+            return Err(anyhow::anyhow!("Batch {} is {}, not committed", batch.commitment_id, batch.status));
+        }
+
+        self.session_for(StorageOperation::CommitRelayerBatch)
+            .query(
+                queries::MARK_RELAYER_BATCH_COMMITTED,
+                (
+                    inclusion.block_height as i64,
+                    bincode::serialize(commitment)?,
+                    bincode::serialize(inclusion)?,
+                    batch.batch_timestamp,
+                    batch.commitment_id,
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Persist a status change made with `start_processing` or `mark_failed`
+    pub async fn update_relayer_status(&self, batch: &RelayerBatch) -> Result<()> {
+        self.fault_point(StorageOperation::UpdateRelayerStatus).await?;
+        self.session_for(StorageOperation::UpdateRelayerStatus)
+            .query(
+                queries::UPDATE_RELAYER_STATUS,
+                (
+                    batch.status.to_string(),
+                    batch.retry_count as i32,
+                    batch.last_attempt,
+                    batch.target_block_height.map(|height| height as i64),
+                    batch.batch_timestamp,
+                    batch.commitment_id,
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Batches waiting to be submitted, up to `limit`
+    pub async fn get_queued_relayer_batches(&self, limit: i32) -> Result<Vec<RelayerBatch>> {
+        self.fault_point(StorageOperation::GetQueuedRelayerBatches).await?;
+        let rows = self.session_for(StorageOperation::GetQueuedRelayerBatches)
+            .query(queries::GET_PENDING_RELAYER_BATCHES, (limit,))
+            .await?;
+        rows.rows.unwrap_or_default().iter().map(decode_relayer_batch).collect()
+    }
+
+    /// Claim up to `limit` queued batches for `relayer_id`, moving them to
+    /// `Processing`. Each claim is a lease in `relayer_claims` taken with
+    /// `IF NOT EXISTS`, so however many relayers share the keyspace exactly
+    /// one holds a batch until it releases the claim or the lease expires.
+    /// Batches another relayer holds, or that left the queue since they were
+    /// read, are skipped, so fewer than `limit` may be returned even when
+    /// more are queued.
+    pub async fn claim_pending_batches(
+        &self,
+        relayer_id: &str,
+        limit: i32,
+        lease: Duration,
+    ) -> Result<Vec<RelayerBatch>> {
+        self.fault_point(StorageOperation::ClaimRelayerBatches).await?;
+        let session = self.session_for(StorageOperation::ClaimRelayerBatches);
+        let rows = session.query(queries::GET_PENDING_RELAYER_BATCHES, (limit,)).await?;
+
+        let now = Utc::now();
+        let mut claimed = Vec::new();
+        for row in rows.rows.unwrap_or_default() {
+            let mut batch = decode_relayer_batch(&row)?;
+            if self.claim_relayer_batch(&batch, relayer_id, now, lease).await? {
+                batch.claim(relayer_id, now);
+                claimed.push(batch);
+            }
+        }
+        Ok(claimed)
+    }
+
+    /// Take the claim on `batch`, read as queued, and move it to `Processing`.
+    ///
+    /// Another relayer may have claimed, committed and released the batch
+    /// since it was read, so the claim alone does not show it is still
+    /// queued; the status only changes `IF status = 'queued'`. When it has
+    /// moved on, the claim is given back and `false` returned.
+    pub(crate) async fn claim_relayer_batch(
+        &self,
+        batch: &RelayerBatch,
+        relayer_id: &str,
+        now: DateTime<Utc>,
+        lease: Duration,
+    ) -> Result<bool> {
+        let session = self.session_for(StorageOperation::ClaimRelayerBatches);
+        let claim = (batch.commitment_id, relayer_id, now, lease_ttl(lease));
+        if !applied(&session.query(queries::INSERT_RELAYER_CLAIM, claim).await?) {
+            return Ok(false);
+        }
+        let update = session
+            .query(
+                queries::CLAIM_RELAYER_BATCH,
+                (relayer_id, now, batch.batch_timestamp, batch.commitment_id),
+            )
+            .await?;
+        if !applied(&update) {
+            session.query(queries::DELETE_RELAYER_CLAIM, (batch.commitment_id, relayer_id)).await?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Extend `relayer_id`'s claim on a batch by `lease`. Returns `false` if
+    /// the claim expired or another relayer holds it, in which case the
+    /// batch must not be submitted.
+    pub async fn renew_relayer_claim(&self, commitment_id: Uuid, relayer_id: &str, lease: Duration) -> Result<bool> {
+        self.fault_point(StorageOperation::RenewRelayerClaim).await?;
+        let result = self.session_for(StorageOperation::RenewRelayerClaim)
+            .query(
+                queries::RENEW_RELAYER_CLAIM,
+                (lease_ttl(lease), relayer_id, Utc::now(), commitment_id, relayer_id),
+            )
+            .await?;
+        Ok(applied(&result))
+    }
+
+    /// Give up `relayer_id`'s claim on a batch, so a retry can be claimed by
+    /// any relayer. Returns `false` if it no longer held the claim.
+    pub async fn release_relayer_claim(&self, commitment_id: Uuid, relayer_id: &str) -> Result<bool> {
+        self.fault_point(StorageOperation::ReleaseRelayerClaim).await?;
+        let result = self.session_for(StorageOperation::ReleaseRelayerClaim)
+            .query(queries::DELETE_RELAYER_CLAIM, (commitment_id, relayer_id))
+            .await?;
+        Ok(applied(&result))
+    }
+
+    /// Up to `limit` batches `Processing` since an attempt before `attempted_before`
+    pub async fn get_stuck_relayer_batches(
+        &self,
+        attempted_before: DateTime<Utc>,
+        limit: i32,
+    ) -> Result<Vec<RelayerBatch>> {
+        self.fault_point(StorageOperation::RecoverRelayerBatches).await?;
+        let rows = self.session_for(StorageOperation::RecoverRelayerBatches)
+            .query(queries::GET_STUCK_RELAYER_BATCHES, (attempted_before, limit))
+            .await?;
+        rows.rows.unwrap_or_default().iter().map(decode_relayer_batch).collect()
+    }
+
+    /// Persist a batch `recover` put back in the queue, provided no relayer
+    /// holds a claim on it and it is still processing. The reset is made
+    /// while holding the batch's claim as `RECOVERY_CLAIMANT`, so it cannot
+    /// race a relayer claiming the batch. Returns `false` when the batch's
+    /// relayer is still renewing its claim or the batch has moved on.
+    pub async fn recover_relayer_batch(&self, batch: &RelayerBatch) -> Result<bool> {
+        self.fault_point(StorageOperation::RecoverRelayerBatches).await?;
+        let session = self.session_for(StorageOperation::RecoverRelayerBatches);
+        let claim = (batch.commitment_id, RECOVERY_CLAIMANT, Utc::now(), lease_ttl(RECOVERY_LEASE));
+        if !applied(&session.query(queries::INSERT_RELAYER_CLAIM, claim).await?) {
+            return Ok(false);
+        }
+        let reset = session
+            .query(
+                queries::RECOVER_RELAYER_BATCH,
+                (batch.recovery_count as i32, batch.batch_timestamp, batch.commitment_id),
+            )
+            .await?;
+        session.query(queries::DELETE_RELAYER_CLAIM, (batch.commitment_id, RECOVERY_CLAIMANT)).await?;
+        Ok(applied(&reset))
+    }
+
+    /// Failed batches that have been retried fewer than `max_retries` times
+    pub async fn get_failed_batches(&self, max_retries: u32, limit: i32) -> Result<Vec<RelayerBatch>> {
+        self.fault_point(StorageOperation::GetFailedRelayerBatches).await?;
+        let rows = self.session_for(StorageOperation::GetFailedRelayerBatches)
+            .query(queries::GET_FAILED_RELAYER_BATCHES, (max_retries as i32, limit))
+            .await?;
+        rows.rows.unwrap_or_default().iter().map(decode_relayer_batch).collect()
+    }
+
+    /// Failed batches that have used up `max_retries` retries
+    pub async fn get_exhausted_batches(&self, max_retries: u32, limit: i32) -> Result<Vec<RelayerBatch>> {
+        self.fault_point(StorageOperation::GetFailedRelayerBatches).await?;
+        let rows = self.session_for(StorageOperation::GetFailedRelayerBatches)
+            .query(queries::GET_EXHAUSTED_RELAYER_BATCHES, (max_retries as i32, limit))
+            .await?;
+        rows.rows.unwrap_or_default().iter().map(decode_relayer_batch).collect()
+    }
+
+    /// Move a batch out of the queue into `relayer_dead_letters`. The dead
+    /// letter is written first, so a crash in between leaves the batch in
+    /// both tables rather than in neither.
+    pub async fn dead_letter_relayer_batch(&self, batch: &RelayerBatch, at: DateTime<Utc>) -> Result<()> {
+        self.fault_point(StorageOperation::DeadLetterRelayerBatch).await?;
+        let session = self.session_for(StorageOperation::DeadLetterRelayerBatch);
+        let commitment = batch.commitment_data.as_ref().map(bincode::serialize).transpose()?;
+        session
+            .query(
+                queries::INSERT_RELAYER_DEAD_LETTER,
+                (
+                    batch.commitment_id,
+                    batch.batch_timestamp,
+                    hash_list(&batch.tx_hashes),
+                    &batch.relayer_id,
+                    batch.retry_count as i32,
+                    batch.last_attempt,
+                    commitment,
+                    at,
+                    batch.batch_lineage_id,
+                ),
+            )
+            .await?;
+        session
+            .query(queries::DELETE_RELAYER_BATCH, (batch.batch_timestamp, batch.commitment_id))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_relayer_batch(
+        &self,
+        batch_timestamp: DateTime<Utc>,
+        commitment_id: Uuid,
+    ) -> Result<Option<RelayerBatch>> {
+        self.fault_point(StorageOperation::GetRelayerBatch).await?;
+        let rows = self.session_for(StorageOperation::GetRelayerBatch)
+            .query(queries::GET_RELAYER_BATCH, (batch_timestamp, commitment_id))
+            .await?;
+        rows.maybe_first_row()?.map(|row| decode_relayer_batch(&row)).transpose()
+    }
+
+    /// Number of batches still waiting in the relayer queue
+    pub async fn relayer_queue_depth(&self) -> Result<u64> {
+        self.fault_point(StorageOperation::GetRelayerQueueDepth).await?;
+        let rows = self.session_for(StorageOperation::GetRelayerQueueDepth)
+            .query(queries::COUNT_QUEUED_RELAYER_BATCHES, ())
+            .await?;
+        Ok(rows.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_bigint())
+            .unwrap_or(0) as u64)
+    }
+}
+
+/// Holder recorded on the claims taken while recovering stuck batches
+pub const RECOVERY_CLAIMANT: &str = "stuck-batch-recovery";
+
+/// Lease of a recovery claim; a crash before releasing it holds the batch only this long
+const RECOVERY_LEASE: Duration = Duration::from_secs(30);
+
+/// Whether a lightweight transaction took effect
+pub(crate) fn applied(result: &scylla::QueryResult) -> bool {
+    result.rows.as_ref()
+        .and_then(|rows| rows.first())
+        .and_then(|row| row.columns[0].as_ref())
+        .and_then(|col| col.as_boolean())
+        .unwrap_or(false)
+}
+
+/// Lease as a TTL in whole seconds, at least one
+fn lease_ttl(lease: Duration) -> i32 {
+    lease.as_secs().clamp(1, i32::MAX as u64) as i32
+}
+
+#[async_trait]
+impl RelayerBacklog for ScyllaAdapter {
+    async fn relayer_queue_depth(&self) -> Result<u64> {
+        ScyllaAdapter::relayer_queue_depth(self).await
+    }
+}
+
+#[async_trait]
+impl RelayerStore for ScyllaAdapter {
+    async fn pending_page(&self, page_size: i32, paging_state: Option<Vec<u8>>) -> Result<PendingTxPage> {
+        self.get_pending_transactions_page(&PendingTxFilter::default(), page_size, paging_state).await
+    }
+
+    async fn queued_batches(&self, limit: i32) -> Result<Vec<RelayerBatch>> {
+        self.get_queued_relayer_batches(limit).await
+    }
+
+    async fn enqueue_batch(&self, batch: &RelayerBatch) -> Result<()> {
+        self.store_relayer_batch(batch).await
+    }
+
+    async fn remove_pending_transaction(&self, tx_hash: &TxHash) -> Result<()> {
+        ScyllaAdapter::remove_pending_transaction(self, tx_hash).await
+    }
+
+    async fn validation_lineage(&self, tx_hash: &TxHash) -> Result<Option<Uuid>> {
+        ScyllaAdapter::validation_lineage(self, tx_hash).await
+    }
+}
+
+#[async_trait]
+impl RetryStore for ScyllaAdapter {
+    async fn retryable_batches(&self, max_retries: u32, limit: i32) -> Result<Vec<RelayerBatch>> {
+        self.get_failed_batches(max_retries, limit).await
+    }
+
+    async fn exhausted_batches(&self, max_retries: u32, limit: i32) -> Result<Vec<RelayerBatch>> {
+        self.get_exhausted_batches(max_retries, limit).await
+    }
+
+    async fn update_batch(&self, batch: &RelayerBatch) -> Result<()> {
+        self.update_relayer_status(batch).await
+    }
+
+    async fn dead_letter(&self, batch: &RelayerBatch, at: DateTime<Utc>) -> Result<()> {
+        self.dead_letter_relayer_batch(batch, at).await
+    }
+}
+
+#[async_trait]
+impl ClaimStore for ScyllaAdapter {
+    async fn claim_batches(&self, relayer_id: &str, limit: i32, lease: Duration) -> Result<Vec<RelayerBatch>> {
+        self.claim_pending_batches(relayer_id, limit, lease).await
+    }
+
+    async fn renew_claim(&self, commitment_id: Uuid, relayer_id: &str, lease: Duration) -> Result<bool> {
+        self.renew_relayer_claim(commitment_id, relayer_id, lease).await
+    }
+
+    async fn release_claim(&self, commitment_id: Uuid, relayer_id: &str) -> Result<bool> {
+        self.release_relayer_claim(commitment_id, relayer_id).await
+    }
+}
+
+#[async_trait]
+impl RelayerRecoveryStore for ScyllaAdapter {
+    async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<RelayerBatch>> {
+        self.get_stuck_relayer_batches(stuck_since, limit).await
+    }
+
+    async fn recover_batch(&self, batch: &RelayerBatch) -> Result<bool> {
+        self.recover_relayer_batch(batch).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scylla_config::ScyllaConfig;
+
+    #[tokio::test]
+    #[ignore] // Requires ScyllaDB setup
+    async fn test_stale_claim_leaves_batch_alone() {
+        let adapter = ScyllaAdapter::new(ScyllaConfig::default()).await.unwrap();
+        let queued = RelayerBatch::new(vec![[1; 32]], String::new());
+        let mut committed = queued.clone();
+        committed.status = RelayerStatus::Committed;
+        adapter.store_relayer_batch(&committed).await.unwrap();
+
+        // `queued` is what a relayer read before another claimed, committed
+        // and released the batch: the claim is free, the status has moved on
+        let lease = Duration::from_secs(60);
+        assert!(!adapter.claim_relayer_batch(&queued, "relayer-b", Utc::now(), lease).await.unwrap());
+        let stored = adapter.get_relayer_batch(queued.batch_timestamp, queued.commitment_id).await.unwrap().unwrap();
+        assert_eq!(stored.status, RelayerStatus::Committed);
+        // The claim was given back
+        assert!(!adapter.release_relayer_claim(queued.commitment_id, "relayer-b").await.unwrap());
+
+        let fresh = RelayerBatch::new(vec![[2; 32]], String::new());
+        adapter.store_relayer_batch(&fresh).await.unwrap();
+        assert!(adapter.claim_relayer_batch(&fresh, "relayer-b", Utc::now(), lease).await.unwrap());
+        let stored = adapter.get_relayer_batch(fresh.batch_timestamp, fresh.commitment_id).await.unwrap().unwrap();
+        assert_eq!(stored.status, RelayerStatus::Processing);
+    }
+}
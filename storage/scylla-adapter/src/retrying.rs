@@ -0,0 +1,72 @@
+// storage/scylla-adapter/src/retrying.rs
+//! Retrying storage calls that fail for transient reasons.
+//!
+//! A coordinator that is unavailable, overloaded or still bootstrapping
+//! refused the query, so it can always be sent again. A timeout or broken
+//! connection leaves the write possibly applied, so only idempotent calls
+//! repeat it. Retries follow `retry_policy` and are capped by a budget
+//! shared by every call, so an outage does not multiply the load on a
+//! cluster that is already struggling.
+use anyhow::Result;
+use retry::{Idempotency, Outcome, Retry};
+use scylla::transport::errors::{DbError, QueryError};
+use std::future::Future;
+
+use crate::ScyllaAdapter;
+
+/// Retries in reserve for calls that have not yet earned any
+pub(crate) const RETRY_BUDGET_RESERVE: u32 = 10;
+
+/// Retries earned per call
+pub(crate) const RETRY_BUDGET_RATIO: f64 = 0.1;
+
+impl ScyllaAdapter {
+    /// Run a storage call under the adapter's retry policy and budget
+    pub(crate) async fn retrying<T, F, Fut>(&self, operation: &str, idempotency: Idempotency, attempt: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        Retry::new(&self.retry_policy)
+            .idempotency(idempotency)
+            .budget(&self.retry_budget)
+            .observe(&self.retry_stats)
+            .run(operation, classify, attempt)
+            .await
+    }
+
+    /// Retries and give-ups of storage calls since the adapter connected
+    pub fn retry_counts(&self) -> retry::RetryCounts {
+        self.retry_stats.counts()
+    }
+}
+
+/// Whether a failed call reached the cluster, judged from the driver error
+pub(crate) fn classify(error: &anyhow::Error) -> Outcome {
+    match error.downcast_ref::<QueryError>() {
+        Some(QueryError::DbError(db_error, _)) => match db_error {
+            DbError::Unavailable { .. } | DbError::Overloaded | DbError::IsBootstrapping => Outcome::NotApplied,
+            DbError::ReadTimeout { .. } | DbError::WriteTimeout { .. } => Outcome::Unknown,
+            _ => Outcome::Permanent,
+        },
+        Some(QueryError::UnableToAllocStreamId) => Outcome::NotApplied,
+        Some(QueryError::TimeoutError | QueryError::RequestTimeout(_) | QueryError::IoError(_)) => Outcome::Unknown,
+        _ => Outcome::Permanent,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scylla::statement::Consistency;
+
+    #[test]
+    fn test_classifies_driver_errors() {
+        let unavailable = DbError::Unavailable { consistency: Consistency::Quorum, required: 2, alive: 1 };
+        let db_error = |error| anyhow::Error::from(QueryError::DbError(error, String::new()));
+        assert_eq!(classify(&db_error(unavailable)), Outcome::NotApplied);
+        assert_eq!(classify(&db_error(DbError::SyntaxError)), Outcome::Permanent);
+        assert_eq!(classify(&QueryError::TimeoutError.into()), Outcome::Unknown);
+        assert_eq!(classify(&anyhow::anyhow!("Block 7 not found")), Outcome::Permanent);
+    }
+}
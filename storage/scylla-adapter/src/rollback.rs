@@ -0,0 +1,245 @@
+// storage/scylla-adapter/src/rollback.rs
+use anyhow::Result;
+use blockchain_core::{Block, BlockHash, BlockHeight, Transaction, TransactionStatus, TxHash};
+use std::collections::HashSet;
+use storage_traits::StorageOperation;
+
+use crate::intent_log::Intent;
+use crate::model::RollbackReport;
+use crate::{encryption, events, format, queries, ScyllaAdapter};
+
+/// Rows undoing one block removes, worked out from the block and whatever
+/// is stored at its height now, so a replayed rollback plans the same writes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RollbackPlan {
+    /// Transactions whose rows, indexes, receipts and payloads are removed
+    pub removed: Vec<Transaction>,
+    /// Transactions returned to the mempool, marked pending
+    pub requeued: Vec<Transaction>,
+    /// Whether rows keyed by height go too; not once another block is stored there
+    pub clear_height: bool,
+}
+
+impl RollbackPlan {
+    pub(crate) fn new(block: &Block, stored: Option<&Block>) -> Self {
+        let successor = stored.filter(|stored| stored.hash != block.hash);
+        let shared: HashSet<TxHash> = successor.iter()
+            .flat_map(|stored| stored.transactions.iter().map(|tx| tx.hash))
+            .collect();
+
+        let removed: Vec<Transaction> = block.transactions.iter()
+            .filter(|tx| !shared.contains(&tx.hash))
+            .cloned()
+            .collect();
+        // A coinbase is only valid in the block that minted it
+        let requeued = removed.iter()
+            .filter(|tx| !tx.is_coinbase())
+            .map(|tx| {
+                let mut pending = tx.clone();
+                pending.status = TransactionStatus::Pending;
+                pending
+            })
+            .collect();
+        Self { removed, requeued, clear_height: successor.is_none() }
+    }
+}
+
+impl ScyllaAdapter {
+    /// Remove every stored block above `height`, e.g. after the chain reorganized
+    /// onto a branch forking at `height`.
+    ///
+    /// Blocks are removed from the tip down, each under its own intent, so a
+    /// crash leaves a prefix of the old chain that recovery finishes trimming.
+    /// Their transactions return to the mempool, except coinbases, and a
+    /// `block_rolled_back` event is published per block. Contracts the
+    /// blocks deployed release their code and revert to user accounts; other
+    /// account rows are left for the caller to rewrite from the new branch.
+    pub async fn rollback_to_height(&self, height: BlockHeight) -> Result<RollbackReport> {
+        self.fault_point(StorageOperation::RollbackBlocks).await?;
+
+        let mut above = Vec::new();
+        let mut next = height + 1;
+        while let Some(block) = self.stored_block(next).await? {
+            above.push(block);
+            next += 1;
+        }
+
+        let mut report = RollbackReport::default();
+        for block in above.iter().rev() {
+            let intent = Intent::RollbackBlock { block: block.clone() };
+            let handle = self.begin_intent(&intent).await?;
+            report.requeued_transactions += self.apply_rollback_block(block).await?;
+            self.complete_intent(handle).await?;
+            self.publish_block_event(events::BLOCK_ROLLED_BACK, block).await?;
+            report.removed_heights.push(block.header.height);
+        }
+
+        Ok(report)
+    }
+
+    /// Undo everything `apply_store_block` and `store_block_outcome` wrote for
+    /// `block`; every step is idempotent so it can be replayed.
+    ///
+    /// When another block is stored at the same height by then, rows keyed by
+    /// height and the transactions both blocks contain are left to it.
+    ///
+    /// Returns the number of transactions moved back to pending.
+    pub(crate) async fn apply_rollback_block(&self, block: &Block) -> Result<u64> {
+        let session = self.session_for(StorageOperation::RollbackBlocks);
+        let height = block.header.height as i64;
+        let stored = self.stored_block(block.header.height).await?;
+        let plan = RollbackPlan::new(block, stored.as_ref());
+
+        for tx in &plan.removed {
+            let tx_hash = tx.hash.to_vec();
+            if !tx.is_coinbase() {
+                session
+                    .query(queries::DELETE_TX_BY_ADDRESS, (tx.sender().to_vec(), tx.timestamp, tx_hash.clone()))
+                    .await?;
+            }
+            if let Some(recipient) = tx.recipient() {
+                session
+                    .query(queries::DELETE_TX_BY_ADDRESS, (recipient.to_vec(), tx.timestamp, tx_hash.clone()))
+                    .await?;
+            }
+            session.query(queries::DELETE_TRANSACTION_RECEIPT, (tx_hash.clone(),)).await?;
+            self.release_payloads(&tx.hash).await?;
+            self.release_deployed_contract(tx).await?;
+            session.query(queries::DELETE_TRANSACTION, (tx_hash,)).await?;
+        }
+        for tx in &plan.requeued {
+            self.add_pending_transaction(tx).await?;
+        }
+        session.query(queries::DELETE_BLOCK_BY_HASH, (block.hash.to_vec(),)).await?;
+        if plan.clear_height {
+            session.query(queries::DELETE_TRANSACTIONS_BY_BLOCK, (height,)).await?;
+            self.rollback_fee_split(height).await?;
+            session.query(queries::DELETE_BLOCK_HEADER, (height,)).await?;
+            session.query(queries::DELETE_BLOCK, (height,)).await?;
+        }
+
+        Ok(plan.requeued.len() as u64)
+    }
+
+    /// Subtract a block's fees from the chain totals, once
+    pub(crate) async fn rollback_fee_split(&self, height: i64) -> Result<()> {
+        let session = self.session_for(StorageOperation::RollbackBlocks);
+
+        let rows = session.query(queries::GET_BLOCK_FEE_SPLIT, (height,)).await?;
+        let Some(row) = rows.maybe_first_row()? else {
+            return Ok(());
+        };
+        let fee = |i: usize| row.columns[i].as_ref().and_then(|col| col.as_bigint()).unwrap_or(0);
+        let (burned, treasury, proposer, tip) = (fee(0), fee(1), fee(2), fee(3));
+
+        // Only the run that deletes the row may adjust the counters
+        let result = session.query(queries::DELETE_BLOCK_FEE_SPLIT, (height,)).await?;
+        let applied = result.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_boolean())
+            .unwrap_or(false);
+
+        if applied {
+            session
+                .query(
+                    queries::DECREMENT_FEE_TOTALS,
+                    (
+                        scylla::frame::value::Counter(burned),
+                        scylla::frame::value::Counter(treasury),
+                        scylla::frame::value::Counter(proposer),
+                        scylla::frame::value::Counter(tip),
+                    ),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Hash of the block stored at `height`, without decoding it
+    pub(crate) async fn stored_block_hash(&self, height: BlockHeight) -> Result<Option<BlockHash>> {
+        let rows = self.session_for(StorageOperation::RollbackBlocks)
+            .query(queries::GET_BLOCK_HASH, (height as i64,))
+            .await?;
+        Ok(rows.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_blob())
+            .and_then(|hash| hash.as_slice().try_into().ok()))
+    }
+
+    /// Block at `height` read from the primary, so a lagging replica cannot hide the tip
+    pub(crate) async fn stored_block(&self, height: BlockHeight) -> Result<Option<Block>> {
+        let rows = self.session_for(StorageOperation::RollbackBlocks)
+            .query(queries::GET_BLOCK_DATA, (height as i64,))
+            .await?;
+
+        let Some(stored) = rows.maybe_first_row()?
+            .and_then(|row| row.columns[0].clone())
+            .and_then(|col| col.into_blob())
+        else {
+            return Ok(None);
+        };
+
+        let block_data = self.encryptor.decrypt(encryption::BLOCKS_CONTEXT, &encryption::height_key(height), &stored)?;
+        Ok(Some(format::decode(&block_data)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intent_log::Resolution;
+
+    fn confirmed_transfer(sender: u8) -> Transaction {
+        let mut tx = Transaction::new_transfer([sender; 20], [2; 20], 10, 0, 21_000, 1).unwrap();
+        tx.status = TransactionStatus::Confirmed { block_height: 5, block_hash: [1; 32] };
+        tx
+    }
+
+    fn produced_block(producer: u8, transfers: Vec<Transaction>) -> Block {
+        let mut transactions = vec![Transaction::new_coinbase([producer; 20], 50, 5).unwrap()];
+        transactions.extend(transfers);
+        Block::new(5, [1; 32], transactions, 1).unwrap()
+    }
+
+    #[test]
+    fn test_coinbase_is_removed_but_not_requeued() {
+        let block = produced_block(9, vec![confirmed_transfer(3), confirmed_transfer(4)]);
+        let plan = RollbackPlan::new(&block, Some(&block));
+
+        assert_eq!(plan.removed, block.transactions);
+        assert_eq!(plan.requeued.len(), 2);
+        assert!(plan.requeued.iter().all(|tx| !tx.is_coinbase() && tx.status == TransactionStatus::Pending));
+        assert_eq!(
+            plan.requeued.iter().map(|tx| tx.hash).collect::<Vec<_>>(),
+            block.transactions[1..].iter().map(|tx| tx.hash).collect::<Vec<_>>()
+        );
+        assert!(plan.clear_height);
+    }
+
+    #[test]
+    fn test_replayed_rollback_plans_the_same_writes() {
+        let block = produced_block(9, vec![confirmed_transfer(3), confirmed_transfer(4)]);
+        let intent = Intent::RollbackBlock { block: block.clone() };
+
+        // Interrupted before or after the block row went, recovery always completes
+        // the rollback and plans the same removals
+        assert_eq!(intent.resolution(Some(block.hash), None), Resolution::Complete);
+        assert_eq!(intent.resolution(None, None), Resolution::Complete);
+        assert_eq!(RollbackPlan::new(&block, Some(&block)), RollbackPlan::new(&block, None));
+
+        // Once the new branch stored its block at that height, the replay
+        // leaves it the height rows and the transactions both blocks contain
+        let successor = produced_block(8, vec![block.transactions[2].clone(), confirmed_transfer(6)]);
+        let plan = RollbackPlan::new(&block, Some(&successor));
+        assert!(!plan.clear_height);
+        assert_eq!(
+            plan.removed.iter().map(|tx| tx.hash).collect::<Vec<_>>(),
+            vec![block.transactions[0].hash, block.transactions[1].hash]
+        );
+        assert_eq!(plan.requeued.len(), 1);
+        assert_eq!(plan.requeued[0].hash, block.transactions[1].hash);
+        assert_eq!(plan, RollbackPlan::new(&block, Some(&successor)));
+    }
+}
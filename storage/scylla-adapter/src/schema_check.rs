@@ -0,0 +1,420 @@
+// storage/scylla-adapter/src/schema_check.rs
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use storage_traits::StorageOperation;
+
+use crate::{queries, ScyllaAdapter};
+
+/// Schema this build was written against
+pub const EXPECTED_SCHEMA_CQL: &str = include_str!("../../../scylladb/schema.cql");
+
+/// Role of a column in its table's primary key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    PartitionKey,
+    Clustering,
+    Regular,
+    Static,
+}
+
+impl std::fmt::Display for ColumnKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ColumnKind::PartitionKey => write!(f, "partition_key"),
+            ColumnKind::Clustering => write!(f, "clustering"),
+            ColumnKind::Regular => write!(f, "regular"),
+            ColumnKind::Static => write!(f, "static"),
+        }
+    }
+}
+
+impl std::str::FromStr for ColumnKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "partition_key" => Ok(ColumnKind::PartitionKey),
+            "clustering" => Ok(ColumnKind::Clustering),
+            "regular" => Ok(ColumnKind::Regular),
+            "static" => Ok(ColumnKind::Static),
+            _ => Err(anyhow!("Invalid column kind: {}", s)),
+        }
+    }
+}
+
+/// Expected or live definition of a single column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSchema {
+    pub cql_type: String,
+    pub kind: ColumnKind,
+    /// Clustering order ("asc"/"desc") for clustering columns
+    pub clustering_order: Option<String>,
+}
+
+/// Columns of a table by name, plus the statement that creates it
+#[derive(Debug, Clone, Default)]
+pub struct TableSchema {
+    pub columns: BTreeMap<String, ColumnSchema>,
+    pub create_statement: String,
+}
+
+/// A difference between the live keyspace and the expected schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaMismatch {
+    MissingTable { table: String },
+    MissingColumn { table: String, column: String, cql_type: String },
+    TypeMismatch { table: String, column: String, expected: String, found: String },
+    KindMismatch { table: String, column: String, expected: ColumnKind, found: ColumnKind },
+    ClusteringOrderMismatch { table: String, column: String, expected: String, found: String },
+}
+
+impl SchemaMismatch {
+    pub fn table(&self) -> &str {
+        match self {
+            SchemaMismatch::MissingTable { table }
+            | SchemaMismatch::MissingColumn { table, .. }
+            | SchemaMismatch::TypeMismatch { table, .. }
+            | SchemaMismatch::KindMismatch { table, .. }
+            | SchemaMismatch::ClusteringOrderMismatch { table, .. } => table,
+        }
+    }
+
+    /// Whether the table has to be recreated (and its data copied) to fix this
+    pub fn requires_rebuild(&self) -> bool {
+        !matches!(
+            self,
+            SchemaMismatch::MissingTable { .. } | SchemaMismatch::MissingColumn { .. }
+        )
+    }
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaMismatch::MissingTable { table } => write!(f, "table {} is missing", table),
+            SchemaMismatch::MissingColumn { table, column, cql_type } => {
+                write!(f, "{}.{} ({}) is missing", table, column, cql_type)
+            }
+            SchemaMismatch::TypeMismatch { table, column, expected, found } => {
+                write!(f, "{}.{} has type {}, expected {}", table, column, found, expected)
+            }
+            SchemaMismatch::KindMismatch { table, column, expected, found } => {
+                write!(f, "{}.{} is a {} column, expected {}", table, column, found, expected)
+            }
+            SchemaMismatch::ClusteringOrderMismatch { table, column, expected, found } => {
+                write!(f, "{}.{} is clustered {}, expected {}", table, column, found, expected)
+            }
+        }
+    }
+}
+
+/// Result of comparing a keyspace against the expected schema
+#[derive(Debug, Clone, Default)]
+pub struct SchemaReport {
+    pub keyspace: String,
+    pub mismatches: Vec<SchemaMismatch>,
+}
+
+impl SchemaReport {
+    pub fn is_compatible(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// CQL needed to bring the keyspace up to this build's schema
+    pub fn migration(&self, expected: &BTreeMap<String, TableSchema>) -> String {
+        let mut statements = Vec::new();
+        let mut rebuilt = Vec::new();
+
+        for mismatch in &self.mismatches {
+            match mismatch {
+                SchemaMismatch::MissingTable { table } => {
+                    if let Some(schema) = expected.get(table) {
+                        statements.push(schema.create_statement.clone());
+                    }
+                }
+                SchemaMismatch::MissingColumn { table, column, cql_type } => {
+                    statements.push(format!(
+                        "ALTER TABLE {}.{} ADD {} {};",
+                        self.keyspace, table, column, cql_type
+                    ));
+                }
+                other => {
+                    let table = other.table().to_string();
+                    if !rebuilt.contains(&table) {
+                        rebuilt.push(table);
+                    }
+                }
+            }
+        }
+
+        // Key and type changes cannot be altered in place
+        for table in rebuilt {
+            statements.push(format!(
+                "-- {} must be recreated and its data copied:\nDROP TABLE {}.{};\n{}",
+                table,
+                self.keyspace,
+                table,
+                expected.get(&table).map(|schema| schema.create_statement.as_str()).unwrap_or("")
+            ));
+        }
+
+        statements.join("\n")
+    }
+}
+
+/// Parse the `CREATE TABLE` statements of a CQL schema file
+pub fn parse_schema(cql: &str) -> BTreeMap<String, TableSchema> {
+    let mut tables = BTreeMap::new();
+    let mut lines = cql.lines();
+
+    while let Some(line) = lines.next() {
+        let Some(name) = line
+            .trim()
+            .strip_prefix("CREATE TABLE IF NOT EXISTS ")
+            .and_then(|rest| rest.strip_suffix('('))
+        else {
+            continue;
+        };
+
+        let mut statement = vec![line.to_string()];
+        let mut columns: Vec<(String, String)> = Vec::new();
+        let mut partition_key = Vec::new();
+        let mut clustering_key = Vec::new();
+        let mut options = String::new();
+
+        for line in lines.by_ref() {
+            statement.push(line.to_string());
+            let body = strip_comment(line).trim();
+
+            if !options.is_empty() || body.starts_with(')') {
+                options.push_str(body);
+                options.push(' ');
+                if body.ends_with(';') {
+                    break;
+                }
+            } else if let Some(key) = body.strip_prefix("PRIMARY KEY") {
+                (partition_key, clustering_key) = parse_primary_key(key);
+            } else if let Some((column, cql_type)) = body.trim_end_matches(',').split_once(' ') {
+                columns.push((column.to_string(), cql_type.trim().to_lowercase()));
+            }
+        }
+
+        let clustering_order = parse_clustering_order(&options);
+        let mut table = TableSchema {
+            create_statement: statement.join("\n"),
+            ..Default::default()
+        };
+        for (column, cql_type) in columns {
+            let (kind, order) = if partition_key.contains(&column) {
+                (ColumnKind::PartitionKey, None)
+            } else if clustering_key.contains(&column) {
+                let order = clustering_order.get(&column).cloned().unwrap_or_else(|| "asc".to_string());
+                (ColumnKind::Clustering, Some(order))
+            } else {
+                (ColumnKind::Regular, None)
+            };
+            table.columns.insert(column, ColumnSchema { cql_type, kind, clustering_order: order });
+        }
+
+        tables.insert(name.trim().to_string(), table);
+    }
+
+    tables
+}
+
+/// Compare live tables against the expected schema.
+///
+/// Extra live tables and columns are tolerated, since adding them is
+/// backwards compatible.
+pub fn compare_schema(
+    keyspace: &str,
+    expected: &BTreeMap<String, TableSchema>,
+    live: &BTreeMap<String, TableSchema>,
+) -> SchemaReport {
+    let mut mismatches = Vec::new();
+
+    for (table, expected_table) in expected {
+        let Some(live_table) = live.get(table) else {
+            mismatches.push(SchemaMismatch::MissingTable { table: table.clone() });
+            continue;
+        };
+
+        for (column, want) in &expected_table.columns {
+            let Some(have) = live_table.columns.get(column) else {
+                mismatches.push(SchemaMismatch::MissingColumn {
+                    table: table.clone(),
+                    column: column.clone(),
+                    cql_type: want.cql_type.clone(),
+                });
+                continue;
+            };
+
+            if have.cql_type != want.cql_type {
+                mismatches.push(SchemaMismatch::TypeMismatch {
+                    table: table.clone(),
+                    column: column.clone(),
+                    expected: want.cql_type.clone(),
+                    found: have.cql_type.clone(),
+                });
+            }
+            if have.kind != want.kind {
+                mismatches.push(SchemaMismatch::KindMismatch {
+                    table: table.clone(),
+                    column: column.clone(),
+                    expected: want.kind,
+                    found: have.kind,
+                });
+            } else if have.clustering_order != want.clustering_order {
+                mismatches.push(SchemaMismatch::ClusteringOrderMismatch {
+                    table: table.clone(),
+                    column: column.clone(),
+                    expected: want.clustering_order.clone().unwrap_or_default(),
+                    found: have.clustering_order.clone().unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    SchemaReport {
+        keyspace: keyspace.to_string(),
+        mismatches,
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.split("--").next().unwrap_or("")
+}
+
+/// Split `((a, b), c, d)` or `(a, c, d)` into partition and clustering columns
+fn parse_primary_key(key: &str) -> (Vec<String>, Vec<String>) {
+    let key = key.trim().trim_end_matches(',');
+    let key = key.strip_prefix('(').and_then(|k| k.strip_suffix(')')).unwrap_or(key);
+    let split = |s: &str| -> Vec<String> {
+        s.split(',')
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect()
+    };
+
+    match key.strip_prefix('(').and_then(|rest| rest.split_once(')')) {
+        Some((partition, clustering)) => (split(partition), split(clustering)),
+        None => {
+            let mut columns = split(key);
+            let clustering = columns.split_off(1.min(columns.len()));
+            (columns, clustering)
+        }
+    }
+}
+
+/// Extract `CLUSTERING ORDER BY (a DESC, b ASC)` from a table's options
+fn parse_clustering_order(options: &str) -> BTreeMap<String, String> {
+    let mut order = BTreeMap::new();
+    if let Some(rest) = options.split("CLUSTERING ORDER BY (").nth(1) {
+        let clause = rest.split(')').next().unwrap_or("");
+        for entry in clause.split(',') {
+            if let Some((column, direction)) = entry.trim().split_once(' ') {
+                order.insert(column.to_string(), direction.trim().to_lowercase());
+            }
+        }
+    }
+    order
+}
+
+impl ScyllaAdapter {
+    /// Compare the live keyspace against the schema this build expects
+    pub async fn check_schema(&self) -> Result<SchemaReport> {
+        let rows = self.session_for(StorageOperation::VerifySchema)
+            .query(queries::GET_KEYSPACE_COLUMNS, (self.config.keyspace.as_str(),))
+            .await?;
+
+        let mut live: BTreeMap<String, TableSchema> = BTreeMap::new();
+        for row in rows.rows.unwrap_or_default() {
+            let text = |i: usize| {
+                row.columns[i].as_ref()
+                    .and_then(|col| col.as_text())
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            let kind: ColumnKind = text(2).parse()?;
+            let clustering_order = match kind {
+                ColumnKind::Clustering => Some(text(3).to_lowercase()),
+                _ => None,
+            };
+
+            live.entry(text(0)).or_default().columns.insert(
+                text(1),
+                ColumnSchema { cql_type: text(4), kind, clustering_order },
+            );
+        }
+
+        let expected = parse_schema(EXPECTED_SCHEMA_CQL);
+        Ok(compare_schema(&self.config.keyspace, &expected, &live))
+    }
+
+    /// Refuse to run against a keyspace this build cannot safely use
+    pub(crate) async fn verify_schema(&self) -> Result<()> {
+        let report = self.check_schema().await?;
+        if report.is_compatible() {
+            return Ok(());
+        }
+
+        let expected = parse_schema(EXPECTED_SCHEMA_CQL);
+        let problems: Vec<String> = report.mismatches.iter().map(|m| format!("  - {}", m)).collect();
+        Err(anyhow!(
+            "Keyspace {} is incompatible with this build:\n{}\n\nMigration required:\n{}",
+            report.keyspace,
+            problems.join("\n"),
+            report.migration(&expected)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bundled_schema() {
+        let tables = parse_schema(EXPECTED_SCHEMA_CQL);
+
+        let by_address = &tables["transactions_by_address"];
+        assert_eq!(by_address.columns["address"].kind, ColumnKind::PartitionKey);
+        assert_eq!(by_address.columns["timestamp"].kind, ColumnKind::Clustering);
+        assert_eq!(by_address.columns["timestamp"].clustering_order.as_deref(), Some("desc"));
+        assert_eq!(by_address.columns["tx_hash"].clustering_order.as_deref(), Some("asc"));
+        assert_eq!(by_address.columns["amount"].cql_type, "bigint");
+
+        let queue = &tables["validation_queue"];
+        assert_eq!(queue.columns["tx_hashes"].cql_type, "list<blob>");
+        assert_eq!(queue.columns["validation_status"].kind, ColumnKind::Regular);
+    }
+
+    #[test]
+    fn test_identical_schema_is_compatible() {
+        let expected = parse_schema(EXPECTED_SCHEMA_CQL);
+        assert!(compare_schema("blockchain", &expected, &expected).is_compatible());
+    }
+
+    #[test]
+    fn test_mismatches_and_migration() {
+        let expected = parse_schema(EXPECTED_SCHEMA_CQL);
+        let mut live = expected.clone();
+        live.remove("intent_log");
+        live.get_mut("transactions").unwrap().columns.remove("compression_dict_id");
+        live.get_mut("transactions_by_address")
+            .unwrap()
+            .columns
+            .get_mut("timestamp")
+            .unwrap()
+            .clustering_order = Some("asc".to_string());
+
+        let report = compare_schema("blockchain", &expected, &live);
+        assert_eq!(report.mismatches.len(), 3);
+
+        let migration = report.migration(&expected);
+        assert!(migration.contains("CREATE TABLE IF NOT EXISTS intent_log"));
+        assert!(migration.contains("ALTER TABLE blockchain.transactions ADD compression_dict_id int;"));
+        assert!(migration.contains("DROP TABLE blockchain.transactions_by_address;"));
+    }
+}
@@ -1,5 +1,8 @@
 // storage/scylla-adapter/src/scylla-config.rs
+use blockchain_core::{ClassificationConfig, ConfigReport, DEFAULT_REPLACEMENT_BUMP_PERCENT};
+use scylla::statement::{Consistency, SerialConsistency};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// ScyllaDB configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,10 +29,184 @@ pub struct ScyllaConfig {
     pub read_consistency: String,
     /// Consistency level for writes
     pub write_consistency: String,
+    /// Consistency of the Paxos round of lightweight transactions
+    /// (`SERIAL` or `LOCAL_SERIAL`)
+    #[serde(default = "default_serial_consistency")]
+    pub serial_consistency: String,
     /// Retry policy configuration
     pub retry_policy: RetryPolicyConfig,
     /// Load balancing policy
     pub load_balancing_policy: String,
+    /// Multi-datacenter placement and failover
+    pub datacenter: DatacenterConfig,
+    /// Per-operation consistency overrides
+    pub consistency_overrides: ConsistencyOverrides,
+    /// At-rest encryption of blob columns
+    pub encryption: EncryptionConfig,
+    /// Dedicated endpoints for replica-safe reads; `None` reads from `nodes`
+    pub read_replica: Option<ReadReplicaConfig>,
+    /// Dictionary compression of historical transactions
+    pub archival: ArchivalConfig,
+    /// Anomaly detection over hourly chain stats
+    pub anomaly_detection: AnomalyDetectionConfig,
+    /// Retention and paging of the replayable event log
+    pub event_log: EventLogConfig,
+    /// Built-in heuristics tagging stored transactions with a category
+    pub classification: ClassificationConfig,
+    /// Refuse every write, for followers serving a keyspace another node writes
+    pub read_only: bool,
+    /// Keep-alive and reconnection of the driver sessions
+    #[serde(default)]
+    pub supervisor: SupervisorConfig,
+    /// Content-addressed storage of large transaction payloads
+    #[serde(default)]
+    pub payload_blobs: PayloadBlobConfig,
+    /// Admission rules of `pending_transactions`
+    #[serde(default)]
+    pub pending: PendingPoolConfig,
+    /// Collection of destroyed contracts
+    #[serde(default)]
+    pub contract_gc: ContractGcConfig,
+    /// Ownership of write-ahead intents
+    #[serde(default)]
+    pub intent_log: IntentLogConfig,
+}
+
+/// Archival recompression settings for historical `tx_data`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivalConfig {
+    /// Blocks behind the chain head before transactions are archived
+    pub min_age_blocks: u64,
+    /// zstd compression level used for archived rows
+    pub compression_level: i32,
+    /// Maximum trained dictionary size in bytes
+    pub dictionary_size: usize,
+    /// Maximum number of transactions sampled for training
+    pub training_sample_limit: usize,
+}
+
+/// EWMA/z-score anomaly detection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyDetectionConfig {
+    /// Weight of the newest period in the moving average, in (0, 1]
+    pub ewma_alpha: f64,
+    /// Absolute z-score at or above which a period is flagged
+    pub z_threshold: f64,
+    /// Periods observed before anything is flagged
+    pub warmup_periods: usize,
+    /// Hours of history the trend is rebuilt from on each run
+    pub lookback_hours: i64,
+}
+
+/// Replayable event log settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLogConfig {
+    /// Days an event stays replayable before its row expires
+    pub retention_days: u32,
+    /// Largest number of entries one replay call scans
+    pub max_replay_page: usize,
+    /// Seconds a missing sequence number is waited for before replay skips it
+    pub gap_grace_secs: i64,
+}
+
+/// Session keep-alive and reconnection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupervisorConfig {
+    /// Milliseconds between keep-alive queries
+    pub keepalive_interval_ms: u64,
+    /// Keep-alives failed in a row before the sessions are rebuilt
+    pub failure_threshold: u32,
+    /// Writes queued during an outage; further writes are refused
+    pub max_queued_writes: usize,
+}
+
+/// Settings for storing large payloads apart from `tx_data`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadBlobConfig {
+    /// Deploy code, init data and call data of at least this many bytes are stored as blobs
+    pub min_size: usize,
+    /// Seconds a released blob waits before collection; must exceed the time
+    /// a transaction write takes and the clock skew between nodes
+    pub gc_grace_secs: u64,
+}
+
+/// Settings for collecting destroyed contracts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractGcConfig {
+    /// Seconds the deletes of unreferenced code and emptied tombstone buckets
+    /// are backdated by; must exceed the time a deployment's writes take and
+    /// the clock skew between nodes
+    pub gc_grace_secs: u64,
+}
+
+/// Ownership of the intents an adapter records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentLogConfig {
+    /// Stable id of this writer, so a restarted node recovers its own
+    /// intents at once; a random id per connection when unset
+    pub writer_id: Option<String>,
+    /// Seconds a writer's lease lasts after its last intent; other adapters
+    /// recover its intents only once it expired, so it must exceed the
+    /// longest multi-table write
+    pub lease_secs: u64,
+}
+
+/// Rules for adding to `pending_transactions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingPoolConfig {
+    /// Percent a transaction must raise the gas price by to replace the
+    /// pending one with its sender and nonce
+    pub replacement_bump_percent: u64,
+}
+
+/// Read replica endpoints used for explorer-style reads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadReplicaConfig {
+    /// Node addresses serving read traffic
+    pub nodes: Vec<String>,
+    /// Datacenter placement for the read session
+    pub datacenter: DatacenterConfig,
+}
+
+/// Multi-datacenter placement and failover configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatacenterConfig {
+    /// Datacenter this node prefers; requests are routed to its replicas first
+    pub local_datacenter: Option<String>,
+    /// Preferred rack inside the local datacenter
+    pub local_rack: Option<String>,
+    /// Whether requests may fail over to remote datacenters when local replicas are down
+    pub permit_dc_failover: bool,
+    /// Fraction of live nodes below which a datacenter is reported as degraded
+    pub min_healthy_ratio: f64,
+}
+
+/// Operation classes that can carry their own consistency level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationClass {
+    /// Explorer-style lookups (blocks, transactions, address history)
+    ExplorerRead,
+    /// Chain head updates (block and confirmed transaction writes)
+    HeadUpdate,
+    /// Mempool inserts and removals
+    Mempool,
+    /// Account balance and nonce reads/writes
+    AccountState,
+}
+
+/// Consistency level overrides per operation class.
+///
+/// Unset entries fall back to `read_consistency` / `write_consistency`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyOverrides {
+    /// Consistency for explorer reads
+    pub explorer_reads: Option<String>,
+    /// Consistency for chain head updates
+    pub head_updates: Option<String>,
+    /// Consistency for mempool operations
+    pub mempool: Option<String>,
+    /// Consistency for account state operations
+    pub account_state: Option<String>,
 }
 
 /// Retry policy configuration
@@ -45,6 +222,32 @@ pub struct RetryPolicyConfig {
     pub exponential_backoff: bool,
 }
 
+/// Envelope encryption configuration for `block_data` / `tx_data` blobs
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EncryptionConfig {
+    /// Whether new blobs are encrypted
+    pub enabled: bool,
+    /// Accept unencrypted blobs while encryption is enabled; set only while
+    /// migrating an existing keyspace, until rotation has rewritten every row
+    #[serde(default)]
+    pub allow_plaintext: bool,
+    /// Data key used for new writes
+    pub active_key_id: u32,
+    /// All data keys, including retired ones still needed for reads
+    pub data_keys: Vec<WrappedDataKey>,
+}
+
+/// Data key wrapped by a KMS master key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrappedDataKey {
+    /// Identifier stored in each encrypted blob
+    pub key_id: u32,
+    /// Master key that wraps this data key
+    pub master_key_id: String,
+    /// Hex-encoded wrapped key
+    pub wrapped_key: String,
+}
+
 impl Default for ScyllaConfig {
     fn default() -> Self {
         Self {
@@ -59,8 +262,116 @@ impl Default for ScyllaConfig {
             use_compression: true,
             read_consistency: "LOCAL_QUORUM".to_string(),
             write_consistency: "LOCAL_QUORUM".to_string(),
+            serial_consistency: default_serial_consistency(),
             retry_policy: RetryPolicyConfig::default(),
             load_balancing_policy: "DcAwareRoundRobinPolicy".to_string(),
+            datacenter: DatacenterConfig::default(),
+            consistency_overrides: ConsistencyOverrides::default(),
+            encryption: EncryptionConfig::default(),
+            read_replica: None,
+            archival: ArchivalConfig::default(),
+            anomaly_detection: AnomalyDetectionConfig::default(),
+            event_log: EventLogConfig::default(),
+            classification: ClassificationConfig::default(),
+            read_only: false,
+            supervisor: SupervisorConfig::default(),
+            payload_blobs: PayloadBlobConfig::default(),
+            pending: PendingPoolConfig::default(),
+            contract_gc: ContractGcConfig::default(),
+            intent_log: IntentLogConfig::default(),
+        }
+    }
+}
+
+impl Default for ArchivalConfig {
+    fn default() -> Self {
+        Self {
+            min_age_blocks: 100_000,
+            compression_level: 19,
+            dictionary_size: 110 * 1024,
+            training_sample_limit: 100_000,
+        }
+    }
+}
+
+impl Default for AnomalyDetectionConfig {
+    fn default() -> Self {
+        Self {
+            ewma_alpha: 0.1,
+            z_threshold: 4.0,
+            warmup_periods: 24,
+            lookback_hours: 7 * 24,
+        }
+    }
+}
+
+impl Default for EventLogConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: 7,
+            max_replay_page: 1000,
+            gap_grace_secs: 30,
+        }
+    }
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            keepalive_interval_ms: 5000,
+            failure_threshold: 3,
+            max_queued_writes: 10_000,
+        }
+    }
+}
+
+impl Default for PendingPoolConfig {
+    fn default() -> Self {
+        Self {
+            replacement_bump_percent: DEFAULT_REPLACEMENT_BUMP_PERCENT,
+        }
+    }
+}
+
+impl Default for ContractGcConfig {
+    fn default() -> Self {
+        Self { gc_grace_secs: 600 }
+    }
+}
+
+impl Default for IntentLogConfig {
+    fn default() -> Self {
+        Self { writer_id: None, lease_secs: 300 }
+    }
+}
+
+impl Default for PayloadBlobConfig {
+    fn default() -> Self {
+        Self {
+            min_size: 1024,
+            gc_grace_secs: 600,
+        }
+    }
+}
+
+impl Default for DatacenterConfig {
+    fn default() -> Self {
+        Self {
+            local_datacenter: None,
+            local_rack: None,
+            permit_dc_failover: true,
+            min_healthy_ratio: 0.5,
+        }
+    }
+}
+
+impl Default for ConsistencyOverrides {
+    fn default() -> Self {
+        Self {
+            explorer_reads: Some("LOCAL_ONE".to_string()),
+            head_updates: Some("LOCAL_QUORUM".to_string()),
+            mempool: None,
+            account_state: None,
         }
     }
 }
@@ -76,6 +387,20 @@ impl Default for RetryPolicyConfig {
     }
 }
 
+impl RetryPolicyConfig {
+    /// The policy for storage calls: `max_retries` retries after the first
+    /// attempt, with equal jitter so clients retrying together spread out
+    pub fn policy(&self) -> retry::RetryPolicy {
+        let base = Duration::from_millis(self.base_delay_ms);
+        let backoff = if self.exponential_backoff {
+            retry::Backoff::Exponential { base, max: Duration::from_millis(self.max_delay_ms) }
+        } else {
+            retry::Backoff::Fixed(base)
+        };
+        retry::RetryPolicy { max_attempts: self.max_retries.saturating_add(1), backoff, jitter: retry::Jitter::Equal }
+    }
+}
+
 impl ScyllaConfig {
     /// Load configuration from environment variables
     pub fn from_env() -> Result<Self, std::env::VarError> {
@@ -117,6 +442,10 @@ impl ScyllaConfig {
             config.use_compression = compression.parse().unwrap_or(config.use_compression);
         }
         
+        if let Ok(read_only) = std::env::var("SCYLLA_READ_ONLY") {
+            config.read_only = read_only.parse().unwrap_or(config.read_only);
+        }
+        
         if let Ok(consistency) = std::env::var("SCYLLA_READ_CONSISTENCY") {
             config.read_consistency = consistency;
         }
@@ -124,54 +453,436 @@ impl ScyllaConfig {
         if let Ok(consistency) = std::env::var("SCYLLA_WRITE_CONSISTENCY") {
             config.write_consistency = consistency;
         }
+
+        if let Ok(consistency) = std::env::var("SCYLLA_SERIAL_CONSISTENCY") {
+            config.serial_consistency = consistency;
+        }
         
-        Ok(config)
-    }
-    
-    /// Validate the configuration
-    pub fn validate(&self) -> Result<(), String> {
-        if self.nodes.is_empty() {
-            return Err("At least one ScyllaDB node must be specified".to_string());
+        if let Ok(dc) = std::env::var("SCYLLA_LOCAL_DC") {
+            config.datacenter.local_datacenter = Some(dc);
         }
         
-        if self.keyspace.is_empty() {
-            return Err("Keyspace name cannot be empty".to_string());
+        if let Ok(rack) = std::env::var("SCYLLA_LOCAL_RACK") {
+            config.datacenter.local_rack = Some(rack);
         }
         
-        if self.username.is_empty() {
-            return Err("Username cannot be empty".to_string());
+        if let Ok(failover) = std::env::var("SCYLLA_PERMIT_DC_FAILOVER") {
+            config.datacenter.permit_dc_failover = failover.parse().unwrap_or(config.datacenter.permit_dc_failover);
         }
         
-        if self.connection_timeout_ms == 0 {
-            return Err("Connection timeout must be greater than 0".to_string());
+        if let Ok(consistency) = std::env::var("SCYLLA_EXPLORER_READ_CONSISTENCY") {
+            config.consistency_overrides.explorer_reads = Some(consistency);
         }
         
-        if self.request_timeout_ms == 0 {
-            return Err("Request timeout must be greater than 0".to_string());
+        if let Ok(consistency) = std::env::var("SCYLLA_HEAD_UPDATE_CONSISTENCY") {
+            config.consistency_overrides.head_updates = Some(consistency);
         }
         
-        if self.max_connections_per_node == 0 {
-            return Err("Max connections per node must be greater than 0".to_string());
+        if let Ok(consistency) = std::env::var("SCYLLA_MEMPOOL_CONSISTENCY") {
+            config.consistency_overrides.mempool = Some(consistency);
         }
         
-        if self.pool_size == 0 {
-            return Err("Pool size must be greater than 0".to_string());
+        if let Ok(consistency) = std::env::var("SCYLLA_ACCOUNT_STATE_CONSISTENCY") {
+            config.consistency_overrides.account_state = Some(consistency);
+        }
+        
+        if let Ok(nodes) = std::env::var("SCYLLA_READ_NODES") {
+            let mut replica = ReadReplicaConfig {
+                nodes: nodes.split(',').map(|s| s.trim().to_string()).collect(),
+                datacenter: config.datacenter.clone(),
+            };
+            if let Ok(dc) = std::env::var("SCYLLA_READ_LOCAL_DC") {
+                replica.datacenter.local_datacenter = Some(dc);
+                replica.datacenter.local_rack = None;
+            }
+            config.read_replica = Some(replica);
+        }
+        
+        if let Ok(age) = std::env::var("SCYLLA_ARCHIVE_MIN_AGE_BLOCKS") {
+            config.archival.min_age_blocks = age.parse().unwrap_or(config.archival.min_age_blocks);
+        }
+        
+        if let Ok(level) = std::env::var("SCYLLA_ARCHIVE_COMPRESSION_LEVEL") {
+            config.archival.compression_level = level.parse().unwrap_or(config.archival.compression_level);
+        }
+        
+        if let Ok(alpha) = std::env::var("SCYLLA_ANOMALY_EWMA_ALPHA") {
+            config.anomaly_detection.ewma_alpha = alpha.parse().unwrap_or(config.anomaly_detection.ewma_alpha);
+        }
+        
+        if let Ok(threshold) = std::env::var("SCYLLA_ANOMALY_Z_THRESHOLD") {
+            config.anomaly_detection.z_threshold = threshold.parse().unwrap_or(config.anomaly_detection.z_threshold);
+        }
+        
+        if let Ok(days) = std::env::var("SCYLLA_EVENT_RETENTION_DAYS") {
+            config.event_log.retention_days = days.parse().unwrap_or(config.event_log.retention_days);
+        }
+        
+        if let Ok(interval) = std::env::var("SCYLLA_KEEPALIVE_INTERVAL_MS") {
+            let default = config.supervisor.keepalive_interval_ms;
+            config.supervisor.keepalive_interval_ms = interval.parse().unwrap_or(default);
+        }
+        
+        if let Ok(max) = std::env::var("SCYLLA_MAX_QUEUED_WRITES") {
+            config.supervisor.max_queued_writes = max.parse().unwrap_or(config.supervisor.max_queued_writes);
+        }
+        
+        if let Ok(enabled) = std::env::var("SCYLLA_ENCRYPTION_ENABLED") {
+            config.encryption.enabled = enabled.parse().unwrap_or(config.encryption.enabled);
+        }
+        
+        if let Ok(key_id) = std::env::var("SCYLLA_ACTIVE_DATA_KEY_ID") {
+            config.encryption.active_key_id = key_id.parse().unwrap_or(config.encryption.active_key_id);
+        }
+        
+        // Format: key_id:master_key_id:wrapped_hex[,...]
+        if let Ok(keys) = std::env::var("SCYLLA_DATA_KEYS") {
+            config.encryption.data_keys = keys
+                .split(',')
+                .filter_map(|entry| {
+                    let mut parts = entry.trim().splitn(3, ':');
+                    Some(WrappedDataKey {
+                        key_id: parts.next()?.parse().ok()?,
+                        master_key_id: parts.next()?.to_string(),
+                        wrapped_key: parts.next()?.to_string(),
+                    })
+                })
+                .collect();
+        }
+        
+        Ok(config)
+    }
+    
+    /// Validate the configuration, stopping at the first problem
+    pub fn validate(&self) -> Result<(), String> {
+        let mut report = ConfigReport::new();
+        self.check(&mut report);
+        match report.issues().first() {
+            Some(issue) => Err(issue.message.clone()),
+            None => Ok(()),
         }
+    }
+    
+    /// Record every problem in `report`, under field paths relative to this section
+    pub fn check(&self, report: &mut ConfigReport) {
+        report.check(self.nodes.is_empty(), "nodes", "At least one ScyllaDB node must be specified");
+        report.check(self.keyspace.is_empty(), "keyspace", "Keyspace name cannot be empty");
+        report.check(self.username.is_empty(), "username", "Username cannot be empty");
+        report.check(
+            self.connection_timeout_ms == 0,
+            "connection_timeout_ms",
+            "Connection timeout must be greater than 0",
+        );
+        report.check(self.request_timeout_ms == 0, "request_timeout_ms", "Request timeout must be greater than 0");
+        report.check(
+            self.max_connections_per_node == 0,
+            "max_connections_per_node",
+            "Max connections per node must be greater than 0",
+        );
+        report.check(self.pool_size == 0, "pool_size", "Pool size must be greater than 0");
+        
+        report.section("retry_policy", |retry| {
+            let policy = &self.retry_policy;
+            retry.check(
+                policy.base_delay_ms > policy.max_delay_ms,
+                "base_delay_ms",
+                format!(
+                    "Base retry delay {}ms exceeds the maximum delay {}ms",
+                    policy.base_delay_ms, policy.max_delay_ms
+                ),
+            );
+        });
         
         // Validate consistency levels
-        let valid_consistency = [
-            "ANY", "ONE", "TWO", "THREE", "QUORUM", "ALL",
-            "LOCAL_QUORUM", "EACH_QUORUM", "SERIAL", "LOCAL_SERIAL", "LOCAL_ONE"
+        report.check(
+            parse_consistency(&self.read_consistency).is_none(),
+            "read_consistency",
+            invalid_consistency("read", &self.read_consistency),
+        );
+        report.check(
+            parse_consistency(&self.write_consistency).is_none(),
+            "write_consistency",
+            invalid_consistency("write", &self.write_consistency),
+        );
+        report.check(
+            parse_serial_consistency(&self.serial_consistency).is_none(),
+            "serial_consistency",
+            format!(
+                "Invalid serial consistency level: {} (expected SERIAL or LOCAL_SERIAL)",
+                self.serial_consistency
+            ),
+        );
+        
+        let overrides = [
+            ("explorer_reads", &self.consistency_overrides.explorer_reads),
+            ("head_updates", &self.consistency_overrides.head_updates),
+            ("mempool", &self.consistency_overrides.mempool),
+            ("account_state", &self.consistency_overrides.account_state),
         ];
         
-        if !valid_consistency.contains(&self.read_consistency.as_str()) {
-            return Err(format!("Invalid read consistency level: {}", self.read_consistency));
-        }
+        report.section("consistency_overrides", |section| {
+            for (name, level) in overrides {
+                if let Some(level) = level {
+                    section.check(parse_consistency(level).is_none(), name, invalid_consistency(name, level));
+                }
+            }
+        });
         
-        if !valid_consistency.contains(&self.write_consistency.as_str()) {
-            return Err(format!("Invalid write consistency level: {}", self.write_consistency));
+        // Validate datacenter settings
+        report.section("datacenter", |dc| {
+            let datacenter = &self.datacenter;
+            dc.check(
+                datacenter.local_datacenter.as_deref() == Some(""),
+                "local_datacenter",
+                "Local datacenter name cannot be empty",
+            );
+            dc.check(
+                datacenter.local_rack.is_some() && datacenter.local_datacenter.is_none(),
+                "local_rack",
+                "Local rack requires a local datacenter",
+            );
+            dc.check(
+                !datacenter.permit_dc_failover && datacenter.local_datacenter.is_none(),
+                "permit_dc_failover",
+                "Disabling DC failover requires a local datacenter",
+            );
+            dc.check(
+                !(0.0..=1.0).contains(&datacenter.min_healthy_ratio),
+                "min_healthy_ratio",
+                "Minimum healthy ratio must be between 0 and 1",
+            );
+        });
+        
+        if let Some(replica) = &self.read_replica {
+            report.section("read_replica", |section| {
+                section.check(replica.nodes.is_empty(), "nodes", "Read replica must specify at least one node");
+                section.check(
+                    replica.datacenter.local_datacenter.as_deref() == Some(""),
+                    "datacenter.local_datacenter",
+                    "Read replica datacenter name cannot be empty",
+                );
+            });
         }
         
-        Ok(())
+        report.section("archival", |section| {
+            section.check(
+                !(1..=22).contains(&self.archival.compression_level),
+                "compression_level",
+                "Archive compression level must be between 1 and 22",
+            );
+            section.check(
+                self.archival.dictionary_size == 0 || self.archival.training_sample_limit == 0,
+                "dictionary_size",
+                "Archive dictionary size and sample limit must be greater than 0",
+            );
+        });
+        
+        report.section("anomaly_detection", |section| {
+            let anomaly = &self.anomaly_detection;
+            section.check(
+                anomaly.ewma_alpha.is_nan() || anomaly.ewma_alpha <= 0.0 || anomaly.ewma_alpha > 1.0,
+                "ewma_alpha",
+                "Anomaly EWMA alpha must be in (0, 1]",
+            );
+            section.check(
+                anomaly.z_threshold.is_nan() || anomaly.z_threshold <= 0.0,
+                "z_threshold",
+                "Anomaly z-score threshold must be greater than 0",
+            );
+            section.check(
+                anomaly.lookback_hours <= anomaly.warmup_periods as i64,
+                "lookback_hours",
+                "Anomaly lookback must cover more hours than the warmup",
+            );
+        });
+        
+        report.section("event_log", |section| {
+            let events = &self.event_log;
+            // CQL caps TTLs at 20 years
+            section.check(
+                events.retention_days == 0 || events.retention_days > 7300,
+                "retention_days",
+                "Event retention must be between 1 and 7300 days",
+            );
+            section.check(
+                events.max_replay_page == 0,
+                "max_replay_page",
+                "Event replay page size must be greater than 0",
+            );
+            section.check(events.gap_grace_secs < 0, "gap_grace_secs", "Event gap grace period cannot be negative");
+        });
+
+        report.adopt("classification", self.classification.validate());
+        
+        report.section("supervisor", |section| {
+            let supervisor = &self.supervisor;
+            section.check(
+                supervisor.keepalive_interval_ms == 0,
+                "keepalive_interval_ms",
+                "Keep-alive interval must be greater than 0",
+            );
+            section.check(
+                supervisor.failure_threshold == 0,
+                "failure_threshold",
+                "Keep-alive failure threshold must be greater than 0",
+            );
+        });
+
+        report.section("payload_blobs", |section| {
+            // An empty payload would otherwise become a blob every stored transaction references
+            section.check(
+                self.payload_blobs.min_size == 0,
+                "min_size",
+                "Payload blob minimum size must be greater than 0",
+            );
+        });
+        
+        // Validate encryption keys
+        report.section("encryption", |section| {
+            let mut key_ids = std::collections::HashSet::new();
+            for key in &self.encryption.data_keys {
+                section.check(
+                    !key_ids.insert(key.key_id),
+                    "data_keys",
+                    format!("Duplicate data key id: {}", key.key_id),
+                );
+            }
+            section.check(
+                self.encryption.enabled && !key_ids.contains(&self.encryption.active_key_id),
+                "active_key_id",
+                format!(
+                    "Active data key {} is not among the configured data keys",
+                    self.encryption.active_key_id
+                ),
+            );
+        });
+    }
+    
+    /// Consistency level to use for the given operation class
+    pub fn consistency_for(&self, class: OperationClass) -> Consistency {
+        let (level, fallback) = match class {
+            OperationClass::ExplorerRead => (&self.consistency_overrides.explorer_reads, &self.read_consistency),
+            OperationClass::HeadUpdate => (&self.consistency_overrides.head_updates, &self.write_consistency),
+            OperationClass::Mempool => (&self.consistency_overrides.mempool, &self.write_consistency),
+            OperationClass::AccountState => (&self.consistency_overrides.account_state, &self.write_consistency),
+        };
+        
+        level
+            .as_deref()
+            .and_then(parse_consistency)
+            .or_else(|| parse_consistency(fallback))
+            .unwrap_or(Consistency::LocalQuorum)
+    }
+
+    /// Serial consistency of lightweight transactions
+    pub fn serial_consistency(&self) -> SerialConsistency {
+        parse_serial_consistency(&self.serial_consistency).unwrap_or(SerialConsistency::LocalSerial)
+    }
+}
+
+/// Parse a CQL consistency level name (e.g. `LOCAL_QUORUM`) usable for
+/// both reads and writes. `ANY` only applies to writes, and the serial
+/// levels only to the Paxos round of lightweight transactions, so neither
+/// is accepted here.
+pub fn parse_consistency(level: &str) -> Option<Consistency> {
+    match level {
+        "ONE" => Some(Consistency::One),
+        "TWO" => Some(Consistency::Two),
+        "THREE" => Some(Consistency::Three),
+        "QUORUM" => Some(Consistency::Quorum),
+        "ALL" => Some(Consistency::All),
+        "LOCAL_QUORUM" => Some(Consistency::LocalQuorum),
+        "EACH_QUORUM" => Some(Consistency::EachQuorum),
+        "LOCAL_ONE" => Some(Consistency::LocalOne),
+        _ => None,
+    }
+}
+
+/// Parse a serial consistency level name for lightweight transactions
+pub fn parse_serial_consistency(level: &str) -> Option<SerialConsistency> {
+    match level {
+        "SERIAL" => Some(SerialConsistency::Serial),
+        "LOCAL_SERIAL" => Some(SerialConsistency::LocalSerial),
+        _ => None,
+    }
+}
+
+/// Why `level` is not a regular consistency level for `name`
+fn invalid_consistency(name: &str, level: &str) -> String {
+    match level {
+        "SERIAL" | "LOCAL_SERIAL" => {
+            format!("{} only applies to lightweight transactions; set it as serial_consistency, not {}", level, name)
+        }
+        "ANY" => format!("ANY only applies to writes, so it cannot be the {} consistency level", name),
+        _ => format!("Invalid {} consistency level: {}", name, level),
+    }
+}
+
+fn default_serial_consistency() -> String {
+    "LOCAL_SERIAL".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consistency_for_prefers_overrides_and_falls_back() {
+        let mut config = ScyllaConfig {
+            read_consistency: "QUORUM".to_string(),
+            write_consistency: "EACH_QUORUM".to_string(),
+            ..Default::default()
+        };
+        config.consistency_overrides = ConsistencyOverrides {
+            explorer_reads: Some("LOCAL_ONE".to_string()),
+            head_updates: None,
+            mempool: Some("ONE".to_string()),
+            account_state: Some("bogus".to_string()),
+        };
+
+        assert_eq!(config.consistency_for(OperationClass::ExplorerRead), Consistency::LocalOne);
+        assert_eq!(config.consistency_for(OperationClass::HeadUpdate), Consistency::EachQuorum);
+        assert_eq!(config.consistency_for(OperationClass::Mempool), Consistency::One);
+        // An unparseable override falls back rather than failing the operation
+        assert_eq!(config.consistency_for(OperationClass::AccountState), Consistency::EachQuorum);
+
+        config.consistency_overrides.explorer_reads = None;
+        assert_eq!(config.consistency_for(OperationClass::ExplorerRead), Consistency::Quorum);
+        config.write_consistency = "SERIAL".to_string();
+        assert_eq!(config.consistency_for(OperationClass::HeadUpdate), Consistency::LocalQuorum);
+    }
+
+    #[test]
+    fn test_serial_and_write_only_levels_are_not_regular_consistency() {
+        for level in ["ANY", "SERIAL", "LOCAL_SERIAL", "local_quorum", ""] {
+            assert_eq!(parse_consistency(level), None, "{}", level);
+        }
+        assert_eq!(parse_consistency("LOCAL_QUORUM"), Some(Consistency::LocalQuorum));
+        assert_eq!(parse_serial_consistency("SERIAL"), Some(SerialConsistency::Serial));
+        assert_eq!(parse_serial_consistency("LOCAL_SERIAL"), Some(SerialConsistency::LocalSerial));
+        assert_eq!(parse_serial_consistency("QUORUM"), None);
+    }
+
+    #[test]
+    fn test_validate_rejects_misplaced_consistency_levels() {
+        assert!(ScyllaConfig::default().validate().is_ok());
+        assert_eq!(ScyllaConfig::default().serial_consistency(), SerialConsistency::LocalSerial);
+
+        let mut config = ScyllaConfig::default();
+        config.consistency_overrides.mempool = Some("LOCAL_SERIAL".to_string());
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("serial_consistency"), "{}", error);
+
+        let mut config = ScyllaConfig::default();
+        config.consistency_overrides.explorer_reads = Some("ANY".to_string());
+        assert!(config.validate().unwrap_err().contains("ANY"));
+
+        let mut config = ScyllaConfig::default();
+        config.read_consistency = "SERIAL".to_string();
+        assert!(config.validate().is_err());
+
+        let mut config = ScyllaConfig::default();
+        config.serial_consistency = "QUORUM".to_string();
+        assert!(config.validate().unwrap_err().contains("serial consistency"));
     }
 }
@@ -5,8 +5,8 @@ pub const INSERT_BLOCK: &str = r#"
     INSERT INTO blocks (
         height, hash, previous_hash, merkle_root, timestamp, nonce, 
         difficulty, version, transaction_count, size, total_value, 
-        total_fees, block_data
-    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        total_fees, block_data, state_root
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
 "#;
 
 pub const GET_BLOCK_BY_HEIGHT: &str = r#"
@@ -20,6 +20,18 @@ pub const GET_BLOCK_BY_HASH: &str = r#"
     SELECT height FROM blocks_by_hash WHERE hash = ?
 "#;
 
+pub const GET_BLOCK_DATA: &str = r#"
+    SELECT block_data FROM blocks WHERE height = ?
+"#;
+
+pub const GET_BLOCK_HASH: &str = r#"
+    SELECT hash FROM blocks WHERE height = ?
+"#;
+
+pub const UPDATE_BLOCK_DATA: &str = r#"
+    UPDATE blocks SET block_data = ? WHERE height = ?
+"#;
+
 pub const GET_RECENT_BLOCKS: &str = r#"
     SELECT height, hash, timestamp, transaction_count, total_value, total_fees
     FROM recent_blocks 
@@ -27,22 +39,51 @@ pub const GET_RECENT_BLOCKS: &str = r#"
     LIMIT ?
 "#;
 
+pub const INSERT_BLOCK_HEADER: &str = r#"
+    INSERT INTO block_headers (height, hash, header_data) VALUES (?, ?, ?)
+"#;
+
+pub const GET_BLOCK_HEADERS: &str = r#"
+    SELECT height, hash, header_data FROM block_headers WHERE height IN ?
+"#;
+
 // Transaction operations
+// Clears compression_dict_id, so a row stored again over an archived one is not read as compressed
 pub const INSERT_TRANSACTION: &str = r#"
     INSERT INTO transactions (
         tx_hash, block_height, tx_index, sender, recipient, amount,
         tx_type, nonce, gas_limit, gas_price, timestamp, status,
-        signature, tx_data
-    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        signature, tx_data, compression_dict_id, category, payload_refs
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, null, ?, ?)
 "#;
 
 pub const GET_TRANSACTION: &str = r#"
     SELECT tx_hash, block_height, tx_index, sender, recipient, amount,
            tx_type, nonce, gas_limit, gas_price, timestamp, status,
-           signature, tx_data
+           signature, tx_data, compression_dict_id, payload_refs
     FROM transactions WHERE tx_hash = ?
 "#;
 
+pub const GET_TX_PAYLOAD_REFS: &str = r#"
+    SELECT payload_refs FROM transactions WHERE tx_hash = ?
+"#;
+
+pub const GET_TX_DATA: &str = r#"
+    SELECT tx_data FROM transactions WHERE tx_hash = ?
+"#;
+
+pub const UPDATE_TX_DATA: &str = r#"
+    UPDATE transactions SET tx_data = ? WHERE tx_hash = ?
+"#;
+
+pub const GET_TX_DATA_WITH_DICTIONARY: &str = r#"
+    SELECT tx_data, compression_dict_id FROM transactions WHERE tx_hash = ?
+"#;
+
+pub const UPDATE_ARCHIVED_TX_DATA: &str = r#"
+    UPDATE transactions SET tx_data = ?, compression_dict_id = ? WHERE tx_hash = ?
+"#;
+
 pub const INSERT_TX_BY_ADDRESS: &str = r#"
     INSERT INTO transactions_by_address (
         address, timestamp, tx_hash, block_height, tx_type, amount, is_sender
@@ -68,13 +109,43 @@ pub const GET_TX_BY_BLOCK: &str = r#"
 pub const INSERT_PENDING_TX: &str = r#"
     INSERT INTO pending_transactions (
         tx_hash, priority_score, timestamp, sender, nonce,
-        gas_price, gas_limit, tx_data
-    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        gas_price, gas_limit, tx_size, tx_data, usage_bucket
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    IF NOT EXISTS
+    USING TTL ?
+"#;
+
+// Rewrites every column, so none outlives the others, and moves the row to a
+// new usage bucket only if no concurrent resubmission moved it first
+pub const REFRESH_PENDING_TX: &str = r#"
+    UPDATE pending_transactions USING TTL ?
+    SET sender = ?, nonce = ?, gas_price = ?, gas_limit = ?, tx_size = ?, tx_data = ?, usage_bucket = ?
+    WHERE priority_score = ? AND timestamp = ? AND tx_hash = ?
+    IF usage_bucket = ?
+"#;
+
+pub const GET_PENDING_TX_USAGE: &str = r#"
+    SELECT priority_score, timestamp, tx_size, usage_bucket
+    FROM pending_transactions
+    WHERE tx_hash = ?
+    ALLOW FILTERING
 "#;
 
 pub const DELETE_PENDING_TX: &str = r#"
     DELETE FROM pending_transactions 
     WHERE priority_score = ? AND timestamp = ? AND tx_hash = ?
+    IF EXISTS
+"#;
+
+pub const ADD_MEMPOOL_USAGE: &str = r#"
+    UPDATE mempool_usage
+    SET transaction_count = transaction_count + ?,
+        total_bytes = total_bytes + ?
+    WHERE bucket = ?
+"#;
+
+pub const GET_MEMPOOL_USAGE: &str = r#"
+    SELECT transaction_count, total_bytes FROM mempool_usage WHERE bucket IN ?
 "#;
 
 pub const GET_PENDING_TX_BY_PRIORITY: &str = r#"
@@ -84,6 +155,30 @@ pub const GET_PENDING_TX_BY_PRIORITY: &str = r#"
     LIMIT ?
 "#;
 
+pub const GET_PENDING_TX_PAGE: &str = r#"
+    SELECT gas_price, timestamp, tx_data, tx_hash
+    FROM pending_transactions
+"#;
+
+pub const GET_PENDING_TX_PAGE_BY_SENDER: &str = r#"
+    SELECT gas_price, timestamp, tx_data, tx_hash
+    FROM pending_transactions
+    WHERE sender = ?
+"#;
+
+pub const GET_PENDING_TX_CIPHERTEXTS: &str = r#"
+    SELECT priority_score, timestamp, tx_hash, tx_data, TTL(tx_data)
+    FROM pending_transactions
+"#;
+
+// Keeps the row's remaining TTL, and never recreates a row removed meanwhile
+pub const UPDATE_PENDING_TX_DATA: &str = r#"
+    UPDATE pending_transactions USING TTL ?
+    SET tx_data = ?
+    WHERE priority_score = ? AND timestamp = ? AND tx_hash = ?
+    IF EXISTS
+"#;
+
 pub const GET_PENDING_TX_BY_SENDER: &str = r#"
     SELECT tx_hash, nonce, tx_data
     FROM pending_transactions 
@@ -91,6 +186,13 @@ pub const GET_PENDING_TX_BY_SENDER: &str = r#"
     ALLOW FILTERING
 "#;
 
+pub const GET_PENDING_TX_BY_SENDER_NONCE: &str = r#"
+    SELECT tx_hash, gas_price, tx_size, usage_bucket
+    FROM pending_transactions
+    WHERE sender = ? AND nonce = ?
+    ALLOW FILTERING
+"#;
+
 // Account operations
 pub const UPDATE_ACCOUNT: &str = r#"
     INSERT INTO accounts (address, balance, nonce, last_updated, account_type, code_hash)
@@ -110,27 +212,235 @@ pub const GET_ACCOUNT_NONCE: &str = r#"
     SELECT nonce FROM accounts WHERE address = ?
 "#;
 
+// Schema introspection
+pub const GET_KEYSPACE_COLUMNS: &str = r#"
+    SELECT table_name, column_name, kind, clustering_order, type
+    FROM system_schema.columns
+    WHERE keyspace_name = ?
+"#;
+
+// Write-ahead intent log
+pub const INSERT_INTENT: &str = r#"
+    INSERT INTO intent_log (shard, created_at, intent_id, operation, payload, writer_id)
+    VALUES (?, ?, ?, ?, ?, ?)
+"#;
+
+pub const DELETE_INTENT: &str = r#"
+    DELETE FROM intent_log WHERE shard = ? AND created_at = ? AND intent_id = ?
+"#;
+
+pub const GET_PENDING_INTENTS: &str = r#"
+    SELECT created_at, intent_id, payload, writer_id
+    FROM intent_log
+    WHERE shard = ?
+"#;
+
+pub const UPDATE_INTENT_PAYLOAD: &str = r#"
+    UPDATE intent_log SET payload = ?
+    WHERE shard = ? AND created_at = ? AND intent_id = ?
+    IF EXISTS
+"#;
+
+pub const RENEW_INTENT_WRITER: &str = r#"
+    INSERT INTO intent_writers (writer_id, renewed_at)
+    VALUES (?, ?)
+    USING TTL ?
+"#;
+
+pub const DELETE_INTENT_WRITER: &str = r#"
+    DELETE FROM intent_writers WHERE writer_id = ?
+"#;
+
+pub const GET_INTENT_WRITER: &str = r#"
+    SELECT renewed_at FROM intent_writers WHERE writer_id = ?
+"#;
+
+// Archive compression dictionaries
+pub const INSERT_DICTIONARY: &str = r#"
+    INSERT INTO compression_dictionaries (dict_id, dictionary, trained_at, sample_count)
+    VALUES (?, ?, ?, ?)
+    IF NOT EXISTS
+"#;
+
+pub const GET_DICTIONARY: &str = r#"
+    SELECT dictionary FROM compression_dictionaries WHERE dict_id = ?
+"#;
+
+pub const GET_MAX_DICTIONARY_ID: &str = r#"
+    SELECT MAX(dict_id) FROM compression_dictionaries
+"#;
+
+// Contract storage and code operations
+pub const INSERT_CONTRACT_CODE: &str = r#"
+    INSERT INTO contract_code (code_hash, code, created_at) VALUES (?, ?, ?)
+"#;
+
+pub const GET_CONTRACT_CODE: &str = r#"
+    SELECT code FROM contract_code WHERE code_hash = ?
+"#;
+
+// Backdated by the caller, so code a deployment rewrote since outlives the delete
+pub const DELETE_CONTRACT_CODE: &str = r#"
+    DELETE FROM contract_code USING TIMESTAMP ? WHERE code_hash = ?
+"#;
+
+pub const INSERT_CODE_OWNER: &str = r#"
+    INSERT INTO contract_code_owners (code_hash, address) VALUES (?, ?)
+"#;
+
+pub const DELETE_CODE_OWNER: &str = r#"
+    DELETE FROM contract_code_owners WHERE code_hash = ? AND address = ?
+"#;
+
+pub const COUNT_CODE_OWNERS: &str = r#"
+    SELECT COUNT(*) FROM contract_code_owners WHERE code_hash = ?
+"#;
+
+pub const SET_CONTRACT_ACCOUNT: &str = r#"
+    UPDATE accounts SET account_type = 'contract', code_hash = ? WHERE address = ?
+"#;
+
+pub const CLEAR_CONTRACT_ACCOUNT: &str = r#"
+    UPDATE accounts SET account_type = 'user', code_hash = null WHERE address = ?
+"#;
+
+// Payload blob operations
+pub const INSERT_PAYLOAD_BLOB: &str = r#"
+    INSERT INTO payload_blobs (blob_hash, data, size) VALUES (?, ?, ?)
+"#;
+
+pub const GET_PAYLOAD_BLOB: &str = r#"
+    SELECT data FROM payload_blobs WHERE blob_hash = ?
+"#;
+
+pub const UPDATE_PAYLOAD_BLOB_DATA: &str = r#"
+    UPDATE payload_blobs SET data = ? WHERE blob_hash = ?
+"#;
+
+// Backdated by the caller, so a blob rewritten since outlives the delete
+pub const DELETE_PAYLOAD_BLOB: &str = r#"
+    DELETE FROM payload_blobs USING TIMESTAMP ? WHERE blob_hash = ?
+"#;
+
+pub const INSERT_PAYLOAD_REF: &str = r#"
+    INSERT INTO payload_blob_refs (blob_hash, tx_hash) VALUES (?, ?)
+"#;
+
+pub const DELETE_PAYLOAD_REF: &str = r#"
+    DELETE FROM payload_blob_refs WHERE blob_hash = ? AND tx_hash = ?
+"#;
+
+pub const COUNT_PAYLOAD_REFS: &str = r#"
+    SELECT COUNT(*) FROM payload_blob_refs WHERE blob_hash = ?
+"#;
+
+pub const INSERT_PAYLOAD_RELEASE: &str = r#"
+    INSERT INTO payload_blob_releases (blob_hash, released_at) VALUES (?, ?)
+"#;
+
+pub const GET_PAYLOAD_RELEASES: &str = r#"
+    SELECT blob_hash, released_at FROM payload_blob_releases LIMIT ?
+"#;
+
+pub const DELETE_PAYLOAD_RELEASE: &str = r#"
+    DELETE FROM payload_blob_releases WHERE blob_hash = ?
+"#;
+
+pub const COUNT_CONTRACT_STORAGE: &str = r#"
+    SELECT COUNT(*) FROM contract_storage WHERE address = ?
+"#;
+
+pub const DELETE_CONTRACT_STORAGE: &str = r#"
+    DELETE FROM contract_storage WHERE address = ?
+"#;
+
+pub const DELETE_ACCOUNT: &str = r#"
+    DELETE FROM accounts WHERE address = ?
+"#;
+
+pub const INSERT_CONTRACT_TOMBSTONE: &str = r#"
+    INSERT INTO contract_tombstones (bucket, destroyed_height, address, code_hash)
+    VALUES (?, ?, ?, ?)
+"#;
+
+pub const GET_CONTRACT_TOMBSTONES: &str = r#"
+    SELECT destroyed_height, address, code_hash
+    FROM contract_tombstones
+    WHERE bucket = ? AND destroyed_height <= ?
+"#;
+
+pub const DELETE_CONTRACT_TOMBSTONE: &str = r#"
+    DELETE FROM contract_tombstones
+    WHERE bucket = ? AND destroyed_height = ? AND address = ?
+"#;
+
+pub const INSERT_CONTRACT_GC_BUCKET: &str = r#"
+    INSERT INTO contract_gc_buckets (shard, bucket) VALUES (0, ?)
+"#;
+
+pub const GET_CONTRACT_GC_BUCKETS: &str = r#"
+    SELECT bucket FROM contract_gc_buckets WHERE shard = 0 AND bucket <= ?
+"#;
+
+// Dated by the caller before its scan, so a bucket marked again since stays marked
+pub const DELETE_CONTRACT_GC_BUCKET: &str = r#"
+    DELETE FROM contract_gc_buckets USING TIMESTAMP ? WHERE shard = 0 AND bucket = ?
+"#;
+
 // Validation queue operations
 pub const INSERT_VALIDATION_BATCH: &str = r#"
     INSERT INTO validation_queue (
         queue_id, batch_timestamp, tx_hashes, validation_status,
-        validator_id, started_at, validation_result
-    ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        validator_id, started_at, completed_at, validation_result, recovery_count, batch_lineage_id
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
 "#;
 
 pub const UPDATE_VALIDATION_STATUS: &str = r#"
     UPDATE validation_queue 
-    SET validation_status = ?, completed_at = ?, validation_result = ?
+    SET validation_status = ?, started_at = ?, completed_at = ?, validation_result = ?
     WHERE batch_timestamp = ? AND queue_id = ?
 "#;
 
 pub const GET_PENDING_VALIDATION: &str = r#"
-    SELECT queue_id, batch_timestamp, tx_hashes, validator_id, started_at
+    SELECT queue_id, batch_timestamp, tx_hashes, validation_status,
+           validator_id, started_at, completed_at, validation_result, recovery_count, batch_lineage_id
     FROM validation_queue 
     WHERE validation_status = 'pending'
     LIMIT ?
 "#;
 
+// Lightweight transaction, so a batch is handed to one validator only
+pub const CLAIM_VALIDATION_BATCH: &str = r#"
+    UPDATE validation_queue
+    SET validation_status = 'processing', validator_id = ?, started_at = ?
+    WHERE batch_timestamp = ? AND queue_id = ?
+    IF validation_status = 'pending'
+"#;
+
+pub const GET_STUCK_VALIDATION_BATCHES: &str = r#"
+    SELECT queue_id, batch_timestamp, tx_hashes, validation_status,
+           validator_id, started_at, completed_at, validation_result, recovery_count, batch_lineage_id
+    FROM validation_queue
+    WHERE validation_status = 'processing' AND started_at < ?
+    LIMIT ?
+    ALLOW FILTERING
+"#;
+
+// Conditional on the stalled claim, so a validator finishing the batch meanwhile wins
+pub const RECOVER_VALIDATION_BATCH: &str = r#"
+    UPDATE validation_queue
+    SET validation_status = 'pending', started_at = null, recovery_count = ?
+    WHERE batch_timestamp = ? AND queue_id = ?
+    IF validation_status = 'processing' AND started_at = ?
+"#;
+
+pub const GET_VALIDATION_BATCH: &str = r#"
+    SELECT queue_id, batch_timestamp, tx_hashes, validation_status,
+           validator_id, started_at, completed_at, validation_result, recovery_count, batch_lineage_id
+    FROM validation_queue
+    WHERE batch_timestamp = ? AND queue_id = ?
+"#;
+
 pub const GET_VALIDATION_RESULT: &str = r#"
     SELECT validation_status, validation_result, completed_at
     FROM validation_queue 
@@ -141,8 +451,51 @@ pub const GET_VALIDATION_RESULT: &str = r#"
 pub const INSERT_RELAYER_BATCH: &str = r#"
     INSERT INTO relayer_queue (
         commitment_id, batch_timestamp, tx_hashes, status,
-        relayer_id, retry_count, last_attempt, commitment_data
-    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        relayer_id, retry_count, last_attempt, target_block_height,
+        commitment_data, target_inclusion, recovery_count, batch_lineage_id
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+pub const GET_RELAYER_BATCH: &str = r#"
+    SELECT commitment_id, batch_timestamp, tx_hashes, status,
+           relayer_id, retry_count, last_attempt, target_block_height,
+           commitment_data, target_inclusion, recovery_count, batch_lineage_id
+    FROM relayer_queue
+    WHERE batch_timestamp = ? AND commitment_id = ?
+"#;
+
+// Batch membership by transaction
+pub const INSERT_TRANSACTION_BATCH: &str = r#"
+    INSERT INTO transaction_batches (tx_hash, stage, batch_timestamp, batch_id, batch_lineage_id)
+    VALUES (?, ?, ?, ?, ?)
+"#;
+
+pub const GET_TRANSACTION_BATCHES: &str = r#"
+    SELECT stage, batch_timestamp, batch_id
+    FROM transaction_batches WHERE tx_hash = ?
+"#;
+
+// Lineage of the latest validation batch holding a transaction
+pub const GET_VALIDATION_LINEAGE: &str = r#"
+    SELECT batch_lineage_id FROM transaction_batches
+    WHERE tx_hash = ? AND stage = 'validation'
+    ORDER BY stage DESC, batch_timestamp DESC
+    LIMIT 1
+"#;
+
+// Batches by lineage
+pub const INSERT_BATCH_LINEAGE: &str = r#"
+    INSERT INTO batch_lineage (batch_lineage_id, stage, batch_timestamp, batch_id)
+    VALUES (?, ?, ?, ?)
+"#;
+
+pub const GET_BATCH_LINEAGE: &str = r#"
+    SELECT stage, batch_timestamp, batch_id
+    FROM batch_lineage WHERE batch_lineage_id = ?
+"#;
+
+pub const GET_PENDING_TX_RECEIVED_AT: &str = r#"
+    SELECT timestamp FROM pending_transactions WHERE tx_hash = ? ALLOW FILTERING
 "#;
 
 pub const UPDATE_RELAYER_STATUS: &str = r#"
@@ -151,19 +504,103 @@ pub const UPDATE_RELAYER_STATUS: &str = r#"
     WHERE batch_timestamp = ? AND commitment_id = ?
 "#;
 
+// Written only by the holder of the batch's claim in relayer_claims
+// Conditional, so a claim on a batch read before it left the queue changes nothing
+pub const CLAIM_RELAYER_BATCH: &str = r#"
+    UPDATE relayer_queue
+    SET status = 'processing', relayer_id = ?, last_attempt = ?
+    WHERE batch_timestamp = ? AND commitment_id = ?
+    IF status = 'queued'
+"#;
+
+// Lightweight transactions, so each batch has at most one live claim
+pub const INSERT_RELAYER_CLAIM: &str = r#"
+    INSERT INTO relayer_claims (commitment_id, relayer_id, claimed_at)
+    VALUES (?, ?, ?)
+    IF NOT EXISTS
+    USING TTL ?
+"#;
+
+pub const RENEW_RELAYER_CLAIM: &str = r#"
+    UPDATE relayer_claims USING TTL ?
+    SET relayer_id = ?, claimed_at = ?
+    WHERE commitment_id = ?
+    IF relayer_id = ?
+"#;
+
+pub const DELETE_RELAYER_CLAIM: &str = r#"
+    DELETE FROM relayer_claims WHERE commitment_id = ?
+    IF relayer_id = ?
+"#;
+
+pub const MARK_RELAYER_BATCH_COMMITTED: &str = r#"
+    UPDATE relayer_queue
+    SET status = 'committed', target_block_height = ?, commitment_data = ?, target_inclusion = ?
+    WHERE batch_timestamp = ? AND commitment_id = ?
+"#;
+
 pub const GET_PENDING_RELAYER_BATCHES: &str = r#"
-    SELECT commitment_id, batch_timestamp, tx_hashes, relayer_id, 
-           retry_count, commitment_data
+    SELECT commitment_id, batch_timestamp, tx_hashes, status,
+           relayer_id, retry_count, last_attempt, target_block_height,
+           commitment_data, target_inclusion, recovery_count, batch_lineage_id
     FROM relayer_queue 
     WHERE status = 'queued'
     LIMIT ?
 "#;
 
+pub const GET_STUCK_RELAYER_BATCHES: &str = r#"
+    SELECT commitment_id, batch_timestamp, tx_hashes, status,
+           relayer_id, retry_count, last_attempt, target_block_height,
+           commitment_data, target_inclusion, recovery_count, batch_lineage_id
+    FROM relayer_queue
+    WHERE status = 'processing' AND last_attempt < ?
+    LIMIT ?
+    ALLOW FILTERING
+"#;
+
+// Run while holding the batch's claim, and conditional so a commit meanwhile wins
+pub const RECOVER_RELAYER_BATCH: &str = r#"
+    UPDATE relayer_queue
+    SET status = 'queued', recovery_count = ?
+    WHERE batch_timestamp = ? AND commitment_id = ?
+    IF status = 'processing'
+"#;
+
+pub const COUNT_QUEUED_RELAYER_BATCHES: &str = r#"
+    SELECT COUNT(*) FROM relayer_queue
+    WHERE status = 'queued'
+    ALLOW FILTERING
+"#;
+
 pub const GET_FAILED_RELAYER_BATCHES: &str = r#"
-    SELECT commitment_id, batch_timestamp, tx_hashes, retry_count
+    SELECT commitment_id, batch_timestamp, tx_hashes, status,
+           relayer_id, retry_count, last_attempt, target_block_height,
+           commitment_data, target_inclusion, recovery_count, batch_lineage_id
     FROM relayer_queue 
     WHERE status = 'failed' AND retry_count < ?
     LIMIT ?
+    ALLOW FILTERING
+"#;
+
+pub const GET_EXHAUSTED_RELAYER_BATCHES: &str = r#"
+    SELECT commitment_id, batch_timestamp, tx_hashes, status,
+           relayer_id, retry_count, last_attempt, target_block_height,
+           commitment_data, target_inclusion, recovery_count, batch_lineage_id
+    FROM relayer_queue
+    WHERE status = 'failed' AND retry_count >= ?
+    LIMIT ?
+    ALLOW FILTERING
+"#;
+
+pub const INSERT_RELAYER_DEAD_LETTER: &str = r#"
+    INSERT INTO relayer_dead_letters (
+        commitment_id, batch_timestamp, tx_hashes, relayer_id,
+        retry_count, last_attempt, commitment_data, dead_lettered_at, batch_lineage_id
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+pub const DELETE_RELAYER_BATCH: &str = r#"
+    DELETE FROM relayer_queue WHERE batch_timestamp = ? AND commitment_id = ?
 "#;
 
 // Network peer operations
@@ -183,11 +620,119 @@ pub const GET_ACTIVE_PEERS: &str = r#"
 
 pub const GET_PEER_BY_ID: &str = r#"
     SELECT peer_id, ip_address, port, last_seen, version, 
-           chain_height, status, connection_count
+           chain_height, status, connection_count, banned_until
     FROM network_peers 
     WHERE peer_id = ?
 "#;
 
+pub const GET_NETWORK_PEERS: &str = r#"
+    SELECT peer_id, ip_address, port, last_seen, version,
+           chain_height, status, connection_count, banned_until
+    FROM network_peers
+    LIMIT ?
+"#;
+
+// Separate from UPDATE_PEER so discovery refreshing a peer never lifts its ban
+pub const BAN_PEER: &str = r#"
+    UPDATE network_peers USING TTL ?
+    SET banned_until = ?, ban_reason = ?
+    WHERE peer_id = ?
+"#;
+
+pub const GET_PEER_BANS: &str = r#"
+    SELECT peer_id, banned_until, ban_reason
+    FROM network_peers
+"#;
+
+// Write timestamp is the record's own signing time, so an older record never overwrites a newer one
+pub const UPSERT_PEER_RECORD: &str = r#"
+    INSERT INTO network_peers (peer_id, public_key, peer_record, record_timestamp)
+    VALUES (?, ?, ?, ?)
+    USING TIMESTAMP ?
+"#;
+
+pub const GET_PEER_RECORD: &str = r#"
+    SELECT peer_record FROM network_peers WHERE peer_id = ?
+"#;
+
+// Transaction receipt and fee distribution operations
+pub const INSERT_TRANSACTION_RECEIPT: &str = r#"
+    INSERT INTO transaction_receipts (
+        tx_hash, block_height, tx_index, fee_burned, fee_treasury, fee_proposer, fee_tip
+    ) VALUES (?, ?, ?, ?, ?, ?, ?)
+"#;
+
+pub const GET_TRANSACTION_RECEIPT: &str = r#"
+    SELECT tx_hash, block_height, tx_index, fee_burned, fee_treasury, fee_proposer, fee_tip
+    FROM transaction_receipts WHERE tx_hash = ?
+"#;
+
+pub const INSERT_BLOCK_FEE_SPLIT: &str = r#"
+    INSERT INTO block_fee_splits (block_height, fee_burned, fee_treasury, fee_proposer, fee_tip)
+    VALUES (?, ?, ?, ?, ?)
+    IF NOT EXISTS
+"#;
+
+pub const INCREMENT_FEE_TOTALS: &str = r#"
+    UPDATE fee_totals
+    SET total_burned = total_burned + ?,
+        total_treasury = total_treasury + ?,
+        total_proposer = total_proposer + ?,
+        total_tips = total_tips + ?
+    WHERE scope = 'chain'
+"#;
+
+pub const GET_FEE_TOTALS: &str = r#"
+    SELECT total_burned, total_treasury, total_proposer, total_tips FROM fee_totals WHERE scope = 'chain'
+"#;
+
+// Rollback operations, removing everything a block write added
+pub const DELETE_BLOCK: &str = r#"
+    DELETE FROM blocks WHERE height = ?
+"#;
+
+pub const DELETE_BLOCK_BY_HASH: &str = r#"
+    DELETE FROM blocks_by_hash WHERE hash = ?
+"#;
+
+pub const DELETE_BLOCK_HEADER: &str = r#"
+    DELETE FROM block_headers WHERE height = ?
+"#;
+
+pub const DELETE_TRANSACTION: &str = r#"
+    DELETE FROM transactions WHERE tx_hash = ?
+"#;
+
+pub const DELETE_TRANSACTIONS_BY_BLOCK: &str = r#"
+    DELETE FROM transactions_by_block WHERE block_height = ?
+"#;
+
+pub const DELETE_TX_BY_ADDRESS: &str = r#"
+    DELETE FROM transactions_by_address WHERE address = ? AND timestamp = ? AND tx_hash = ?
+"#;
+
+pub const DELETE_TRANSACTION_RECEIPT: &str = r#"
+    DELETE FROM transaction_receipts WHERE tx_hash = ?
+"#;
+
+pub const GET_BLOCK_FEE_SPLIT: &str = r#"
+    SELECT fee_burned, fee_treasury, fee_proposer, fee_tip FROM block_fee_splits WHERE block_height = ?
+"#;
+
+pub const DELETE_BLOCK_FEE_SPLIT: &str = r#"
+    DELETE FROM block_fee_splits WHERE block_height = ?
+    IF EXISTS
+"#;
+
+pub const DECREMENT_FEE_TOTALS: &str = r#"
+    UPDATE fee_totals
+    SET total_burned = total_burned - ?,
+        total_treasury = total_treasury - ?,
+        total_proposer = total_proposer - ?,
+        total_tips = total_tips - ?
+    WHERE scope = 'chain'
+"#;
+
 // Chain statistics operations
 pub const INSERT_CHAIN_STATS: &str = r#"
     INSERT INTO chain_stats (
@@ -214,6 +759,163 @@ pub const GET_LATEST_CHAIN_STATS: &str = r#"
     LIMIT 1
 "#;
 
+// Chain statistics rollups
+pub const INSERT_CHAIN_STATS_DAILY: &str = r#"
+    INSERT INTO chain_stats_daily (
+        stat_year, stat_date, total_blocks, total_transactions,
+        total_value, total_fees, avg_block_time, avg_tx_per_block,
+        network_hash_rate, active_addresses
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+pub const GET_CHAIN_STATS_DAILY_RANGE: &str = r#"
+    SELECT stat_date, total_blocks, total_transactions, total_value,
+           total_fees, avg_block_time, avg_tx_per_block, network_hash_rate,
+           active_addresses
+    FROM chain_stats_daily
+    WHERE stat_year = ? AND stat_date >= ? AND stat_date <= ?
+"#;
+
+pub const INSERT_CHAIN_STATS_WEEKLY: &str = r#"
+    INSERT INTO chain_stats_weekly (
+        stat_year, week_start, total_blocks, total_transactions,
+        total_value, total_fees, avg_block_time, avg_tx_per_block,
+        network_hash_rate, active_addresses
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+pub const GET_CHAIN_STATS_WEEKLY_RANGE: &str = r#"
+    SELECT week_start, total_blocks, total_transactions, total_value,
+           total_fees, avg_block_time, avg_tx_per_block, network_hash_rate,
+           active_addresses
+    FROM chain_stats_weekly
+    WHERE stat_year = ? AND week_start >= ? AND week_start <= ?
+"#;
+
+// Validator performance
+pub const INSERT_VALIDATOR_SLOT: &str = r#"
+    INSERT INTO validator_slots (
+        stat_date, validator, slot, slot_start, proposed, latency_ms
+    ) VALUES (?, ?, ?, ?, ?, ?)
+"#;
+
+pub const GET_VALIDATOR_SLOTS_BY_DATE: &str = r#"
+    SELECT validator, slot, slot_start, proposed, latency_ms
+    FROM validator_slots
+    WHERE stat_date = ?
+"#;
+
+pub const GET_VALIDATOR_SLOTS: &str = r#"
+    SELECT validator, slot, slot_start, proposed, latency_ms
+    FROM validator_slots
+    WHERE stat_date = ? AND validator = ?
+"#;
+
+pub const INSERT_VALIDATOR_ATTESTATION: &str = r#"
+    INSERT INTO validator_attestations (
+        stat_date, validator, checkpoint_height, signed, recorded_at
+    ) VALUES (?, ?, ?, ?, ?)
+"#;
+
+pub const GET_VALIDATOR_ATTESTATIONS_BY_DATE: &str = r#"
+    SELECT validator, checkpoint_height, signed, recorded_at
+    FROM validator_attestations
+    WHERE stat_date = ?
+"#;
+
+pub const GET_VALIDATOR_ATTESTATIONS: &str = r#"
+    SELECT validator, checkpoint_height, signed, recorded_at
+    FROM validator_attestations
+    WHERE stat_date = ? AND validator = ?
+"#;
+
+pub const INSERT_VALIDATOR_STATS_DAILY: &str = r#"
+    INSERT INTO validator_stats_daily (
+        validator, stat_year, stat_date, proposed_blocks, missed_blocks,
+        avg_proposal_latency_ms, attestations_signed, attestations_expected
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+pub const GET_VALIDATOR_STATS_DAILY_RANGE: &str = r#"
+    SELECT stat_date, proposed_blocks, missed_blocks, avg_proposal_latency_ms,
+           attestations_signed, attestations_expected
+    FROM validator_stats_daily
+    WHERE validator = ? AND stat_year = ? AND stat_date >= ? AND stat_date <= ?
+"#;
+
+pub const INSERT_CHAIN_ANOMALY: &str = r#"
+    INSERT INTO anomalies (metric, period_start, observed, expected, z_score, detected_at)
+    VALUES (?, ?, ?, ?, ?, ?)
+    IF NOT EXISTS
+"#;
+
+pub const GET_CHAIN_ANOMALIES_SINCE: &str = r#"
+    SELECT period_start, observed, expected, z_score, detected_at
+    FROM anomalies
+    WHERE metric = ? AND period_start >= ?
+"#;
+
+// Checkpoint operations
+pub const INSERT_CHECKPOINT: &str = r#"
+    INSERT INTO checkpoints (chain_id, height, block_hash, signatures, finalized_at)
+    VALUES (?, ?, ?, ?, ?)
+    IF NOT EXISTS
+"#;
+
+pub const GET_CHECKPOINT: &str = r#"
+    SELECT height, block_hash, signatures FROM checkpoints
+    WHERE chain_id = ? AND height = ?
+"#;
+
+pub const GET_LATEST_CHECKPOINT: &str = r#"
+    SELECT height, block_hash, signatures FROM checkpoints
+    WHERE chain_id = ?
+    LIMIT 1
+"#;
+
+// Relayer dry-run operations
+pub const INSERT_DRY_RUN_SUBMISSION: &str = r#"
+    INSERT INTO relayer_dry_runs (
+        target, prepared_at, commitment_id, batch_hash, calldata, estimated_gas
+    ) VALUES (?, ?, ?, ?, ?, ?)
+"#;
+
+pub const GET_DRY_RUN_SUBMISSIONS: &str = r#"
+    SELECT commitment_id, batch_hash, calldata, estimated_gas, prepared_at
+    FROM relayer_dry_runs
+    WHERE target = ?
+    LIMIT ?
+"#;
+
+// Event log operations
+pub const INIT_EVENT_SEQUENCE: &str = r#"
+    INSERT INTO event_sequence (name, next_seq) VALUES (?, 0)
+    IF NOT EXISTS
+"#;
+
+pub const ADVANCE_EVENT_SEQUENCE: &str = r#"
+    UPDATE event_sequence SET next_seq = ?
+    WHERE name = ?
+    IF next_seq = ?
+"#;
+
+pub const GET_EVENT_SEQUENCE: &str = r#"
+    SELECT next_seq FROM event_sequence WHERE name = ?
+"#;
+
+pub const INSERT_EVENT: &str = r#"
+    INSERT INTO event_log (bucket, seq, event_type, subject, payload, published_at)
+    VALUES (?, ?, ?, ?, ?, ?)
+    USING TTL ?
+"#;
+
+pub const GET_EVENTS_FROM: &str = r#"
+    SELECT seq, event_type, subject, payload, published_at
+    FROM event_log
+    WHERE bucket = ? AND seq >= ?
+    LIMIT ?
+"#;
+
 // System configuration operations
 pub const GET_CONFIG: &str = r#"
     SELECT config_value FROM system_config WHERE config_key = ?
@@ -279,3 +981,14 @@ pub const CLEANUP_OLD_PEER_DATA: &str = r#"
     DELETE FROM network_peers 
     WHERE last_seen < ?
 "#;
+
+// Capacity planning; size_estimates is refreshed by each node every few minutes
+pub const GET_TABLE_SIZE_ESTIMATES: &str = r#"
+    SELECT table_name, mean_partition_size, partitions_count
+    FROM system.size_estimates WHERE keyspace_name = ?
+"#;
+
+// Session keep-alive
+pub const KEEPALIVE: &str = r#"
+    SELECT now() FROM system.local
+"#;
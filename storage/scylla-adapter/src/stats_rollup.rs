@@ -0,0 +1,324 @@
+// storage/scylla-adapter/src/stats_rollup.rs
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use scylla::frame::response::result::Row;
+use storage_traits::StorageOperation;
+
+use crate::model::{ChainStatsPeriod, StatsGranularity, StatsRollupReport};
+use crate::{queries, ScyllaAdapter};
+
+/// Hourly rows expire after this many days (`chain_stats` TTL)
+pub const HOURLY_RETENTION_DAYS: i64 = 90;
+
+/// Daily rows expire after this many days (`chain_stats_daily` TTL)
+pub const DAILY_RETENTION_DAYS: i64 = 5 * 365;
+
+/// `system_config` key holding the first day not yet rolled up
+const ROLLUP_CHECKPOINT_KEY: &str = "stats_rollup_date";
+
+impl ScyllaAdapter {
+    /// Roll complete days of hourly stats into `chain_stats_daily`, and each
+    /// week into `chain_stats_weekly` once its Sunday is rolled up. Validator
+    /// slots and attestations are rolled into `validator_stats_daily` alongside.
+    ///
+    /// Meant to be run periodically by the node's scheduler. Progress is
+    /// checkpointed per day, and rollup rows are overwritten rather than
+    /// accumulated, so an interrupted or repeated run is harmless.
+    pub async fn roll_up_chain_stats(&self, now: DateTime<Utc>) -> Result<StatsRollupReport> {
+        self.fault_point(StorageOperation::RollUpChainStats).await?;
+        let session = self.session_for(StorageOperation::RollUpChainStats);
+
+        let today = now.date_naive();
+        let mut day = match self.stats_rollup_checkpoint().await? {
+            Some(day) => day,
+            None => today - Duration::days(HOURLY_RETENTION_DAYS),
+        };
+        let mut report = StatsRollupReport::default();
+
+        while day < today {
+            let hours = self.hourly_chain_stats(day).await?;
+            if let Some(daily) = aggregate(StatsGranularity::Daily, start_of(day), &hours) {
+                session
+                    .query(queries::INSERT_CHAIN_STATS_DAILY, period_values(day, &daily))
+                    .await?;
+                report.days_rolled_up += 1;
+            }
+            report.validator_days_rolled_up += self.roll_up_validator_stats(day).await?;
+
+            if day.weekday() == chrono::Weekday::Sun {
+                let monday = week_start(day);
+                let days = self.chain_stats_between(StatsGranularity::Daily, monday, day).await?;
+                if let Some(weekly) = aggregate(StatsGranularity::Weekly, start_of(monday), &days) {
+                    session
+                        .query(queries::INSERT_CHAIN_STATS_WEEKLY, period_values(monday, &weekly))
+                        .await?;
+                    report.weeks_rolled_up += 1;
+                }
+            }
+
+            report.rolled_up_through = Some(day);
+            day += Duration::days(1);
+            self.set_stats_rollup_checkpoint(day).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Chain statistics covering `from..=to`, at the finest granularity still retained for that range
+    pub async fn get_chain_stats_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ChainStatsPeriod>> {
+        self.fault_point(StorageOperation::GetChainStatsRange).await?;
+        if from > to {
+            return Ok(Vec::new());
+        }
+
+        let granularity = granularity_for(from, to, now);
+        let mut periods = match granularity {
+            StatsGranularity::Hourly => {
+                let mut hours = Vec::new();
+                let mut day = from.date_naive();
+                while day <= to.date_naive() {
+                    hours.extend(self.hourly_chain_stats(day).await?);
+                    day += Duration::days(1);
+                }
+                hours.retain(|hour| hour.period_start >= from && hour.period_start <= to);
+                hours
+            }
+            StatsGranularity::Daily => {
+                self.chain_stats_between(granularity, from.date_naive(), to.date_naive()).await?
+            }
+            StatsGranularity::Weekly => {
+                self.chain_stats_between(granularity, week_start(from.date_naive()), to.date_naive()).await?
+            }
+        };
+
+        periods.sort_by_key(|period| period.period_start);
+        Ok(periods)
+    }
+
+    pub(crate) async fn hourly_chain_stats(&self, day: NaiveDate) -> Result<Vec<ChainStatsPeriod>> {
+        let rows = self.session_for(StorageOperation::GetChainStatsRange)
+            .query(queries::GET_CHAIN_STATS_BY_DATE, (day,))
+            .await?;
+
+        rows.rows.unwrap_or_default()
+            .into_iter()
+            .map(|row| {
+                let hour = row.columns[0].as_ref()
+                    .and_then(|col| col.as_int())
+                    .ok_or_else(|| anyhow::anyhow!("Missing stat_hour"))?;
+                parse_period(StatsGranularity::Hourly, start_of(day) + Duration::hours(hour as i64), &row)
+            })
+            .collect()
+    }
+
+    /// Daily or weekly rows whose period starts within `from..=to`
+    async fn chain_stats_between(
+        &self,
+        granularity: StatsGranularity,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ChainStatsPeriod>> {
+        let query = match granularity {
+            StatsGranularity::Weekly => queries::GET_CHAIN_STATS_WEEKLY_RANGE,
+            _ => queries::GET_CHAIN_STATS_DAILY_RANGE,
+        };
+        let session = self.session_for(StorageOperation::GetChainStatsRange);
+
+        // Rollup tables are partitioned by year
+        let mut periods = Vec::new();
+        for year in from.year()..=to.year() {
+            let rows = session.query(query, (year, from, to)).await?;
+            for row in rows.rows.unwrap_or_default() {
+                let start = row.columns[0].as_ref()
+                    .and_then(|col| col.as_naive_date())
+                    .ok_or_else(|| anyhow::anyhow!("Missing period start"))?;
+                periods.push(parse_period(granularity, start_of(start), &row)?);
+            }
+        }
+        Ok(periods)
+    }
+
+    async fn stats_rollup_checkpoint(&self) -> Result<Option<NaiveDate>> {
+        let rows = self.session_for(StorageOperation::RollUpChainStats)
+            .query(queries::GET_CONFIG, (ROLLUP_CHECKPOINT_KEY,))
+            .await?;
+
+        Ok(rows.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_text())
+            .and_then(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()))
+    }
+
+    async fn set_stats_rollup_checkpoint(&self, day: NaiveDate) -> Result<()> {
+        self.session_for(StorageOperation::RollUpChainStats)
+            .query(
+                queries::SET_CONFIG,
+                (ROLLUP_CHECKPOINT_KEY, day.format("%Y-%m-%d").to_string(), Utc::now(), "stats_rollup"),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Granularity for a requested range: hourly for short recent ranges, daily
+/// up to a year while daily rows are retained, weekly beyond that
+pub fn granularity_for(from: DateTime<Utc>, to: DateTime<Utc>, now: DateTime<Utc>) -> StatsGranularity {
+    let span = to - from;
+    let age = now - from;
+
+    if span <= Duration::days(7) && age <= Duration::days(HOURLY_RETENTION_DAYS) {
+        StatsGranularity::Hourly
+    } else if span <= Duration::days(366) && age <= Duration::days(DAILY_RETENTION_DAYS) {
+        StatsGranularity::Daily
+    } else {
+        StatsGranularity::Weekly
+    }
+}
+
+/// Monday of the ISO week containing `day`
+pub fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+pub(crate) fn start_of(day: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default())
+}
+
+/// Combine finer periods into one. Block times are weighted by block count;
+/// distinct addresses cannot be summed, so the busiest period is kept as a lower bound.
+pub fn aggregate(
+    granularity: StatsGranularity,
+    period_start: DateTime<Utc>,
+    periods: &[ChainStatsPeriod],
+) -> Option<ChainStatsPeriod> {
+    if periods.is_empty() {
+        return None;
+    }
+
+    let total_blocks: u64 = periods.iter().map(|p| p.total_blocks).sum();
+    let total_transactions: u64 = periods.iter().map(|p| p.total_transactions).sum();
+    let weighted_block_time: f64 = periods.iter().map(|p| p.avg_block_time * p.total_blocks as f64).sum();
+    let (avg_block_time, avg_tx_per_block) = if total_blocks == 0 {
+        (0.0, 0.0)
+    } else {
+        (
+            weighted_block_time / total_blocks as f64,
+            total_transactions as f64 / total_blocks as f64,
+        )
+    };
+
+    Some(ChainStatsPeriod {
+        granularity,
+        period_start,
+        total_blocks,
+        total_transactions,
+        total_value: periods.iter().map(|p| p.total_value).sum(),
+        total_fees: periods.iter().map(|p| p.total_fees).sum(),
+        avg_block_time,
+        avg_tx_per_block,
+        network_hash_rate: periods.iter().map(|p| p.network_hash_rate).sum::<u64>() / periods.len() as u64,
+        active_addresses: periods.iter().map(|p| p.active_addresses).max().unwrap_or(0),
+    })
+}
+
+/// Parse the metric columns that follow the period key in every stats query
+fn parse_period(granularity: StatsGranularity, period_start: DateTime<Utc>, row: &Row) -> Result<ChainStatsPeriod> {
+    let bigint = |i: usize| row.columns[i].as_ref().and_then(|col| col.as_bigint()).unwrap_or(0) as u64;
+    let double = |i: usize| row.columns[i].as_ref().and_then(|col| col.as_double()).unwrap_or(0.0);
+
+    Ok(ChainStatsPeriod {
+        granularity,
+        period_start,
+        total_blocks: bigint(1),
+        total_transactions: bigint(2),
+        total_value: bigint(3),
+        total_fees: bigint(4),
+        avg_block_time: double(5),
+        avg_tx_per_block: double(6),
+        network_hash_rate: bigint(7),
+        active_addresses: bigint(8),
+    })
+}
+
+fn period_values(
+    start: NaiveDate,
+    period: &ChainStatsPeriod,
+) -> (i32, NaiveDate, i64, i64, i64, i64, f64, f64, i64, i64) {
+    (
+        start.year(),
+        start,
+        period.total_blocks as i64,
+        period.total_transactions as i64,
+        period.total_value as i64,
+        period.total_fees as i64,
+        period.avg_block_time,
+        period.avg_tx_per_block,
+        period.network_hash_rate as i64,
+        period.active_addresses as i64,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hour(blocks: u64, transactions: u64, block_time: f64, addresses: u64) -> ChainStatsPeriod {
+        ChainStatsPeriod {
+            granularity: StatsGranularity::Hourly,
+            period_start: Utc::now(),
+            total_blocks: blocks,
+            total_transactions: transactions,
+            total_value: 10,
+            total_fees: 1,
+            avg_block_time: block_time,
+            avg_tx_per_block: 0.0,
+            network_hash_rate: 100,
+            active_addresses: addresses,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_periods() {
+        let start = start_of(NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+        let day = aggregate(
+            StatsGranularity::Daily,
+            start,
+            &[hour(300, 900, 12.0, 40), hour(100, 100, 20.0, 70)],
+        )
+        .unwrap();
+
+        assert_eq!(day.total_blocks, 400);
+        assert_eq!(day.total_value, 20);
+        assert_eq!(day.avg_block_time, 14.0);
+        assert_eq!(day.avg_tx_per_block, 2.5);
+        assert_eq!(day.active_addresses, 70);
+        assert_eq!(day.period_start, start);
+        assert!(aggregate(StatsGranularity::Daily, start, &[]).is_none());
+    }
+
+    #[test]
+    fn test_granularity_selection() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        let ago = |days: i64| now - Duration::days(days);
+
+        assert_eq!(granularity_for(ago(1), now, now), StatsGranularity::Hourly);
+        assert_eq!(granularity_for(ago(30), now, now), StatsGranularity::Daily);
+        // A short range whose hourly rows have expired
+        assert_eq!(granularity_for(ago(200), ago(199), now), StatsGranularity::Daily);
+        assert_eq!(granularity_for(ago(3 * 365), now, now), StatsGranularity::Weekly);
+        assert_eq!(granularity_for(ago(6 * 365), ago(6 * 365 - 1), now), StatsGranularity::Weekly);
+    }
+
+    #[test]
+    fn test_week_start() {
+        let sunday = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        assert_eq!(week_start(sunday), NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+        assert_eq!(week_start(week_start(sunday)), week_start(sunday));
+    }
+}
@@ -0,0 +1,286 @@
+// storage/scylla-adapter/src/supervisor.rs
+//! Keep-alive and reconnection of the driver sessions.
+//!
+//! The driver reconnects to individual nodes on its own, but once every node
+//! has restarted a session can stay broken until the process restarts. A
+//! supervisor pass sends a keep-alive query on each session; after
+//! `failure_threshold` failures in a row the adapter is `Degraded` and the
+//! next passes rebuild both sessions and re-prepare the statements.
+//!
+//! While degraded, reads and writes without a queue fail fast with the
+//! outage's cause instead of a driver timeout. Writes through
+//! `BlockchainStorage` (blocks, pending transactions, accounts) are queued
+//! up to `max_queued_writes` and reported as accepted. After reconnecting
+//! the adapter is `Draining`: queued writes are replayed in order, and writes
+//! arriving meanwhile join the back of the queue so they land after them.
+//! Once the queue is empty the adapter is `Connected` again.
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use blockchain_core::{Address, Block, Transaction, TxHash};
+use chrono::{DateTime, Utc};
+use scylla::Session;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use storage_traits::{ConnectionHealth, ConnectionState, ConnectionSupervisor};
+
+use crate::scylla_config::SupervisorConfig;
+use crate::scylla_queries as queries;
+use crate::ScyllaAdapter;
+
+/// Session in use for each access mode
+pub(crate) struct Sessions {
+    /// Writes and consistency-critical reads
+    pub primary: Arc<Session>,
+    /// Replica-safe reads; same as `primary` without a read replica
+    pub read: Arc<Session>,
+}
+
+/// A write held back while storage is unreachable
+#[derive(Debug, Clone)]
+pub enum QueuedWrite {
+    StoreBlock(Block),
+    AddPendingTransaction(Transaction),
+    RemovePendingTransaction(TxHash),
+    UpdateAccount { address: Address, balance: u64, nonce: u64, account_type: String },
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: ConnectionState,
+    consecutive_failures: u32,
+    degraded_since: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    queue: VecDeque<QueuedWrite>,
+    reconnects: u64,
+}
+
+/// Connection state shared by every operation of an adapter
+#[derive(Debug)]
+pub(crate) struct SessionSupervisor {
+    config: SupervisorConfig,
+    inner: Mutex<Inner>,
+}
+
+impl SessionSupervisor {
+    pub fn new(config: SupervisorConfig) -> Self {
+        let inner = Inner {
+            state: ConnectionState::Connected,
+            consecutive_failures: 0,
+            degraded_since: None,
+            last_error: None,
+            queue: VecDeque::new(),
+            reconnects: 0,
+        };
+        Self { config, inner: Mutex::new(inner) }
+    }
+
+    /// Fail fast while degraded
+    pub fn check_available(&self) -> Result<()> {
+        let inner = self.inner.lock().unwrap();
+        match (inner.state, inner.degraded_since) {
+            (ConnectionState::Degraded, Some(since)) => Err(anyhow!(
+                "Storage unavailable since {}: {}",
+                since.to_rfc3339(),
+                inner.last_error.as_deref().unwrap_or("connection lost")
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Queue the write built by `write` unless connected, returning whether
+    /// it was queued; fails when the queue is full
+    pub fn defer(&self, write: impl FnOnce() -> QueuedWrite) -> Result<bool> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state == ConnectionState::Connected {
+            return Ok(false);
+        }
+        if inner.queue.len() >= self.config.max_queued_writes {
+            return Err(anyhow!(
+                "Storage unavailable and {} writes already queued: {}",
+                inner.queue.len(),
+                inner.last_error.as_deref().unwrap_or("connection lost")
+            ));
+        }
+        inner.queue.push_back(write());
+        Ok(true)
+    }
+
+    /// Record a keep-alive; enough failures in a row degrade the connection
+    pub fn record_keepalive(&self, result: &Result<()>, now: DateTime<Utc>) {
+        let mut inner = self.inner.lock().unwrap();
+        match result {
+            Ok(()) => inner.consecutive_failures = 0,
+            Err(e) => {
+                inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+                inner.last_error = Some(e.to_string());
+                if inner.consecutive_failures >= self.config.failure_threshold {
+                    inner.state = ConnectionState::Degraded;
+                    inner.degraded_since.get_or_insert(now);
+                }
+            }
+        }
+    }
+
+    /// A reconnect or replay failed; stay or fall back to degraded
+    pub fn record_outage(&self, error: &anyhow::Error, now: DateTime<Utc>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = ConnectionState::Degraded;
+        inner.degraded_since.get_or_insert(now);
+        inner.last_error = Some(error.to_string());
+    }
+
+    /// New sessions are up; queued writes are replayed next
+    pub fn reconnected(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = ConnectionState::Draining;
+        inner.consecutive_failures = 0;
+        inner.reconnects += 1;
+    }
+
+    /// Oldest queued write, or `None` once the queue is empty, in which case
+    /// the connection is back to connected
+    pub fn next_queued(&self) -> Option<QueuedWrite> {
+        let mut inner = self.inner.lock().unwrap();
+        let next = inner.queue.pop_front();
+        if next.is_none() && inner.state == ConnectionState::Draining {
+            inner.state = ConnectionState::Connected;
+            inner.degraded_since = None;
+            inner.last_error = None;
+        }
+        next
+    }
+
+    /// Put back a write whose replay failed, ahead of the rest
+    pub fn requeue(&self, write: QueuedWrite) {
+        self.inner.lock().unwrap().queue.push_front(write);
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.inner.lock().unwrap().state
+    }
+
+    pub fn health(&self) -> ConnectionHealth {
+        let inner = self.inner.lock().unwrap();
+        ConnectionHealth {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            degraded_since: inner.degraded_since,
+            last_error: inner.last_error.clone(),
+            queued_writes: inner.queue.len(),
+            reconnects: inner.reconnects,
+        }
+    }
+}
+
+impl ScyllaAdapter {
+    /// Where the connection to the cluster stands
+    pub fn connection_health(&self) -> ConnectionHealth {
+        self.supervisor.health()
+    }
+
+    /// One supervisor pass: a keep-alive while connected, otherwise a
+    /// reconnect followed by replaying queued writes
+    pub async fn supervise_sessions(&self) -> ConnectionHealth {
+        if self.supervisor.state() == ConnectionState::Connected {
+            let result = self.keepalive().await;
+            self.supervisor.record_keepalive(&result, Utc::now());
+            return self.supervisor.health();
+        }
+
+        if self.supervisor.state() == ConnectionState::Degraded {
+            match self.reconnect().await {
+                Ok(()) => self.supervisor.reconnected(),
+                Err(e) => {
+                    self.supervisor.record_outage(&e, Utc::now());
+                    return self.supervisor.health();
+                }
+            }
+        }
+        if let Err(e) = self.replay_queued().await {
+            self.supervisor.record_outage(&e, Utc::now());
+        }
+        self.supervisor.health()
+    }
+
+    async fn keepalive(&self) -> Result<()> {
+        let sessions = {
+            let sessions = self.sessions.read().unwrap();
+            [sessions.primary.clone(), sessions.read.clone()]
+        };
+        for session in sessions {
+            session.query(queries::KEEPALIVE, ()).await?;
+        }
+        Ok(())
+    }
+
+    /// Replace both sessions and prepare the statements on the new ones
+    async fn reconnect(&self) -> Result<()> {
+        let sessions = Self::build_sessions(&self.config).await?;
+        *self.sessions.write().unwrap() = sessions;
+        self.prepare_statements().await
+    }
+
+    /// Apply queued writes oldest first until the queue is empty
+    async fn replay_queued(&self) -> Result<()> {
+        while let Some(write) = self.supervisor.next_queued() {
+            let result = match &write {
+                QueuedWrite::StoreBlock(block) => self.store_block_now(block).await,
+                QueuedWrite::AddPendingTransaction(tx) => self.add_pending_transaction_now(tx).await,
+                QueuedWrite::RemovePendingTransaction(tx_hash) => self.remove_pending_transaction_now(tx_hash).await,
+                QueuedWrite::UpdateAccount { address, balance, nonce, account_type } => {
+                    self.update_account_now(address, *balance, *nonce, account_type).await
+                }
+            };
+            if let Err(e) = result {
+                self.supervisor.requeue(write);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ConnectionSupervisor for ScyllaAdapter {
+    async fn supervise_connection(&self) -> ConnectionHealth {
+        self.supervise_sessions().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degrades_queues_and_drains() {
+        let config = SupervisorConfig { keepalive_interval_ms: 1000, failure_threshold: 2, max_queued_writes: 2 };
+        let supervisor = SessionSupervisor::new(config);
+        let now = Utc::now();
+        let down: Result<()> = Err(anyhow!("connection refused"));
+        let write = || QueuedWrite::RemovePendingTransaction([1; 32]);
+
+        assert!(!supervisor.defer(write).unwrap());
+        supervisor.record_keepalive(&down, now);
+        assert!(supervisor.check_available().is_ok());
+        supervisor.record_keepalive(&down, now);
+        assert_eq!(supervisor.state(), ConnectionState::Degraded);
+        assert!(supervisor.check_available().unwrap_err().to_string().ends_with("connection refused"));
+
+        assert!(supervisor.defer(write).unwrap());
+        assert!(supervisor.defer(write).unwrap());
+        assert!(supervisor.defer(write).is_err());
+
+        // Writes arriving while draining go behind the queue
+        supervisor.reconnected();
+        assert!(supervisor.check_available().is_ok());
+        assert!(supervisor.next_queued().is_some());
+        assert!(supervisor.defer(write).unwrap());
+        assert!(supervisor.next_queued().is_some());
+        assert!(supervisor.next_queued().is_some());
+        assert!(supervisor.next_queued().is_none());
+
+        let health = supervisor.health();
+        assert_eq!((health.state, health.queued_writes, health.reconnects), (ConnectionState::Connected, 0, 1));
+        assert_eq!(health.degraded_since, None);
+    }
+}
@@ -0,0 +1,535 @@
+// storage/scylla-adapter/src/tx_lifecycle.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::{Transaction, TransactionReceipt, TxHash};
+use chrono::{DateTime, Utc};
+use scylla::frame::response::result::Row;
+use storage_traits::{LifecycleEntry, LifecycleLookup, LifecycleStage, StorageOperation, TransactionLifecycle};
+use uuid::Uuid;
+
+use crate::model::{BatchLineage, RelayerBatch, ValidationBatch};
+use crate::{queries, ScyllaAdapter};
+
+/// `stage` value of validation rows in `transaction_batches`
+const VALIDATION_STAGE: &str = "validation";
+
+/// `stage` value of relayer rows in `transaction_batches`
+const RELAYER_STAGE: &str = "relayer";
+
+/// What each stage has recorded about one transaction
+#[derive(Debug, Clone, Default)]
+pub struct LifecycleFacts {
+    pub transaction: Option<Transaction>,
+    /// When the mempool received it, while it is still pending
+    pub pending_since: Option<DateTime<Utc>>,
+    pub receipt: Option<TransactionReceipt>,
+    /// Timestamp of the block the receipt points at
+    pub block_time: Option<DateTime<Utc>>,
+    pub validation_batches: Vec<ValidationBatch>,
+    pub relayer_batches: Vec<RelayerBatch>,
+}
+
+/// Merge the facts into a timeline, `None` when no stage has seen the transaction
+pub fn build_lifecycle(tx_hash: &TxHash, facts: &LifecycleFacts) -> Option<TransactionLifecycle> {
+    let mut timeline = Vec::new();
+
+    match (facts.pending_since, &facts.transaction) {
+        (Some(since), _) => timeline.push(entry(LifecycleStage::Mempool, "pending", Some(since), None, None)),
+        (None, Some(tx)) => timeline.push(entry(LifecycleStage::Mempool, "submitted", Some(tx.timestamp), None, None)),
+        (None, None) => {}
+    }
+
+    for batch in &facts.validation_batches {
+        let (status, detail) = validation_outcome(tx_hash, batch);
+        timeline.push(entry(
+            LifecycleStage::Validation,
+            status,
+            batch.completed_at.or(batch.started_at).or(Some(batch.batch_timestamp)),
+            Some(batch.queue_id.to_string()),
+            detail,
+        ));
+    }
+
+    if let Some(receipt) = &facts.receipt {
+        timeline.push(entry(
+            LifecycleStage::Block,
+            "included",
+            facts.block_time,
+            Some(receipt.block_height.to_string()),
+            Some(format!("index {}", receipt.index)),
+        ));
+    }
+
+    for batch in &facts.relayer_batches {
+        let attempted_at = batch.last_attempt.or(Some(batch.batch_timestamp));
+        timeline.push(entry(
+            LifecycleStage::Relayer,
+            batch.status.to_string(),
+            attempted_at,
+            Some(batch.commitment_id.to_string()),
+            (batch.retry_count > 0).then(|| format!("{} failed attempts", batch.retry_count)),
+        ));
+        if let Some(commitment) = &batch.commitment_data {
+            let status = if batch.target_inclusion.is_some() { "committed" } else { "submitted" };
+            timeline.push(entry(
+                LifecycleStage::Commitment,
+                status,
+                attempted_at,
+                Some(format!("0x{}", hex::encode(commitment.batch_hash))),
+                Some(format!("{} transactions", commitment.transaction_count)),
+            ));
+        }
+        if let Some(inclusion) = &batch.target_inclusion {
+            timeline.push(entry(
+                LifecycleStage::TargetConfirmation,
+                "confirmed",
+                attempted_at,
+                Some(inclusion.tx_id.clone()),
+                Some(format!("block {} ({})", inclusion.block_height, inclusion.block_hash)),
+            ));
+        }
+    }
+
+    timeline.sort_by_key(|entry| (entry.stage, entry.at));
+    let stage = timeline.last()?.stage;
+    Some(TransactionLifecycle { tx_hash: *tx_hash, stage, timeline })
+}
+
+/// This transaction's own result where the batch reports one, else the batch status
+fn validation_outcome(tx_hash: &TxHash, batch: &ValidationBatch) -> (String, Option<String>) {
+    let Some(result) = &batch.validation_result else {
+        return (batch.validation_status.to_string(), None);
+    };
+    if let Some(failed) = result.failed_transactions.iter().find(|failed| &failed.tx_hash == tx_hash) {
+        return ("failed".to_string(), Some(format!("{}: {}", failed.error_code, failed.error_message)));
+    }
+    if result.validated_transactions.contains(tx_hash) {
+        return ("validated".to_string(), None);
+    }
+    (batch.validation_status.to_string(), result.error_message.clone())
+}
+
+fn entry(
+    stage: LifecycleStage,
+    status: impl Into<String>,
+    at: Option<DateTime<Utc>>,
+    reference: Option<String>,
+    detail: Option<String>,
+) -> LifecycleEntry {
+    LifecycleEntry { stage, status: status.into(), at, reference, detail }
+}
+
+impl ScyllaAdapter {
+    /// Write a validation batch's queue row and index its transactions.
+    ///
+    /// The row is overwritten, so call again after `start_processing` or
+    /// `complete_validation` to record the new state.
+    pub async fn store_validation_batch(&self, batch: &ValidationBatch) -> Result<()> {
+        self.fault_point(StorageOperation::StoreValidationBatch).await?;
+        let result = batch.validation_result.as_ref().map(bincode::serialize).transpose()?;
+        self.session_for(StorageOperation::StoreValidationBatch)
+            .query(
+                queries::INSERT_VALIDATION_BATCH,
+                (
+                    batch.queue_id,
+                    batch.batch_timestamp,
+                    hash_list(&batch.tx_hashes),
+                    batch.validation_status.to_string(),
+                    &batch.validator_id,
+                    batch.started_at,
+                    batch.completed_at,
+                    result,
+                    batch.recovery_count as i32,
+                    batch.batch_lineage_id,
+                ),
+            )
+            .await?;
+        self.index_batch(
+            StorageOperation::StoreValidationBatch,
+            VALIDATION_STAGE,
+            &batch.tx_hashes,
+            batch.batch_timestamp,
+            batch.queue_id,
+            batch.batch_lineage_id,
+        )
+        .await
+    }
+
+    /// Write a relayer batch's queue row and index its transactions.
+    ///
+    /// Like `store_validation_batch`, later calls overwrite the row.
+    pub async fn store_relayer_batch(&self, batch: &RelayerBatch) -> Result<()> {
+        self.fault_point(StorageOperation::StoreRelayerBatch).await?;
+        let commitment = batch.commitment_data.as_ref().map(bincode::serialize).transpose()?;
+        let inclusion = batch.target_inclusion.as_ref().map(bincode::serialize).transpose()?;
+        self.session_for(StorageOperation::StoreRelayerBatch)
+            .query(
+                queries::INSERT_RELAYER_BATCH,
+                (
+                    batch.commitment_id,
+                    batch.batch_timestamp,
+                    hash_list(&batch.tx_hashes),
+                    batch.status.to_string(),
+                    &batch.relayer_id,
+                    batch.retry_count as i32,
+                    batch.last_attempt,
+                    batch.target_block_height.map(|h| h as i64),
+                    commitment,
+                    inclusion,
+                    batch.recovery_count as i32,
+                    batch.batch_lineage_id,
+                ),
+            )
+            .await?;
+        self.index_batch(
+            StorageOperation::StoreRelayerBatch,
+            RELAYER_STAGE,
+            &batch.tx_hashes,
+            batch.batch_timestamp,
+            batch.commitment_id,
+            batch.batch_lineage_id,
+        )
+        .await
+    }
+
+    /// Record each transaction's membership in a batch for `tx_lifecycle`,
+    /// and the batch's place in its lineage for `get_batch_lineage`
+    async fn index_batch(
+        &self,
+        op: StorageOperation,
+        stage: &str,
+        tx_hashes: &[TxHash],
+        batch_timestamp: DateTime<Utc>,
+        batch_id: Uuid,
+        batch_lineage_id: Uuid,
+    ) -> Result<()> {
+        let session = self.session_for(op);
+        for tx_hash in tx_hashes {
+            session
+                .query(
+                    queries::INSERT_TRANSACTION_BATCH,
+                    (tx_hash.to_vec(), stage, batch_timestamp, batch_id, batch_lineage_id),
+                )
+                .await?;
+        }
+        session
+            .query(queries::INSERT_BATCH_LINEAGE, (batch_lineage_id, stage, batch_timestamp, batch_id))
+            .await?;
+        Ok(())
+    }
+
+    /// Lineage of the latest validation batch `tx_hash` was placed in, for
+    /// relayer batches to carry on
+    pub async fn validation_lineage(&self, tx_hash: &TxHash) -> Result<Option<Uuid>> {
+        self.fault_point(StorageOperation::GetValidationLineage).await?;
+        let rows = self.session_for(StorageOperation::GetValidationLineage)
+            .query(queries::GET_VALIDATION_LINEAGE, (tx_hash.to_vec(),))
+            .await?;
+        Ok(rows.maybe_first_row()?.and_then(|row| row.columns[0].as_ref().and_then(|col| col.as_uuid())))
+    }
+
+    /// Every validation and relayer batch sharing `batch_lineage_id`.
+    ///
+    /// Batches whose queue rows have expired are left out, so a lineage
+    /// older than the queue TTL comes back empty.
+    pub async fn get_batch_lineage(&self, batch_lineage_id: Uuid) -> Result<BatchLineage> {
+        self.fault_point(StorageOperation::GetBatchLineage).await?;
+        let session = self.session_for(StorageOperation::GetBatchLineage);
+
+        let mut lineage = BatchLineage { batch_lineage_id, ..Default::default() };
+        let members = session.query(queries::GET_BATCH_LINEAGE, (batch_lineage_id,)).await?;
+        for row in members.rows.unwrap_or_default() {
+            let stage = row.columns[0].as_ref().and_then(|col| col.as_text()).cloned().unwrap_or_default();
+            let batch_timestamp = row.columns[1].as_ref()
+                .and_then(|col| col.as_datetime())
+                .ok_or_else(|| anyhow::anyhow!("Missing batch_timestamp"))?;
+            let batch_id = row.columns[2].as_ref()
+                .and_then(|col| col.as_uuid())
+                .ok_or_else(|| anyhow::anyhow!("Missing batch_id"))?;
+
+            match stage.as_str() {
+                VALIDATION_STAGE => {
+                    let rows = session.query(queries::GET_VALIDATION_BATCH, (batch_timestamp, batch_id)).await?;
+                    if let Some(row) = rows.maybe_first_row()? {
+                        lineage.validation_batches.push(decode_validation_batch(&row)?);
+                    }
+                }
+                RELAYER_STAGE => {
+                    let rows = session.query(queries::GET_RELAYER_BATCH, (batch_timestamp, batch_id)).await?;
+                    if let Some(row) = rows.maybe_first_row()? {
+                        lineage.relayer_batches.push(decode_relayer_batch(&row)?);
+                    }
+                }
+                other => return Err(anyhow::anyhow!("Unknown batch stage: {}", other)),
+            }
+        }
+        Ok(lineage)
+    }
+
+    /// Timeline of a transaction from the mempool through to the relay target
+    pub async fn tx_lifecycle(&self, tx_hash: &TxHash) -> Result<Option<TransactionLifecycle>> {
+        self.fault_point(StorageOperation::GetTransactionLifecycle).await?;
+        let session = self.session_for(StorageOperation::GetTransactionLifecycle);
+
+        let pending_since = session
+            .query(queries::GET_PENDING_TX_RECEIVED_AT, (tx_hash.to_vec(),))
+            .await?
+            .maybe_first_row()?
+            .and_then(|row| row.columns[0].as_ref().and_then(|col| col.as_datetime()));
+
+        let receipt = self.get_transaction_receipt(tx_hash).await?;
+        let block_time = match &receipt {
+            Some(receipt) => self
+                .get_block_headers(&[receipt.block_height])
+                .await?
+                .pop()
+                .map(|header| header.timestamp),
+            None => None,
+        };
+
+        let mut facts = LifecycleFacts {
+            transaction: self.get_transaction(tx_hash).await?,
+            pending_since,
+            receipt,
+            block_time,
+            ..Default::default()
+        };
+
+        let memberships = session.query(queries::GET_TRANSACTION_BATCHES, (tx_hash.to_vec(),)).await?;
+        for row in memberships.rows.unwrap_or_default() {
+            let stage = row.columns[0].as_ref().and_then(|col| col.as_text()).cloned().unwrap_or_default();
+            let batch_timestamp = row.columns[1].as_ref()
+                .and_then(|col| col.as_datetime())
+                .ok_or_else(|| anyhow::anyhow!("Missing batch_timestamp"))?;
+            let batch_id = row.columns[2].as_ref()
+                .and_then(|col| col.as_uuid())
+                .ok_or_else(|| anyhow::anyhow!("Missing batch_id"))?;
+
+            // The queue row can expire before its index row; skip it
+            match stage.as_str() {
+                VALIDATION_STAGE => {
+                    let rows = session.query(queries::GET_VALIDATION_BATCH, (batch_timestamp, batch_id)).await?;
+                    if let Some(row) = rows.maybe_first_row()? {
+                        facts.validation_batches.push(decode_validation_batch(&row)?);
+                    }
+                }
+                RELAYER_STAGE => {
+                    let rows = session.query(queries::GET_RELAYER_BATCH, (batch_timestamp, batch_id)).await?;
+                    if let Some(row) = rows.maybe_first_row()? {
+                        facts.relayer_batches.push(decode_relayer_batch(&row)?);
+                    }
+                }
+                other => return Err(anyhow::anyhow!("Unknown batch stage: {}", other)),
+            }
+        }
+
+        Ok(build_lifecycle(tx_hash, &facts))
+    }
+}
+
+#[async_trait]
+impl LifecycleLookup for ScyllaAdapter {
+    async fn tx_lifecycle(&self, tx_hash: &TxHash) -> Result<Option<TransactionLifecycle>> {
+        ScyllaAdapter::tx_lifecycle(self, tx_hash).await
+    }
+}
+
+pub(crate) fn hash_list(tx_hashes: &[TxHash]) -> Vec<Vec<u8>> {
+    tx_hashes.iter().map(|hash| hash.to_vec()).collect()
+}
+
+fn decode_hash_list(row: &Row, index: usize) -> Vec<TxHash> {
+    row.columns[index].as_ref()
+        .and_then(|col| col.as_list())
+        .map(|hashes| {
+            hashes
+                .iter()
+                .filter_map(|hash| hash.as_blob())
+                .filter_map(|bytes| bytes.as_slice().try_into().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Decode a row selected by `GET_VALIDATION_BATCH`, `GET_PENDING_VALIDATION` or
+/// `GET_STUCK_VALIDATION_BATCHES`
+pub(crate) fn decode_validation_batch(row: &Row) -> Result<ValidationBatch> {
+    let queue_id = row.columns[0].as_ref()
+        .and_then(|col| col.as_uuid())
+        .ok_or_else(|| anyhow::anyhow!("Missing queue_id"))?;
+    Ok(ValidationBatch {
+        queue_id,
+        batch_timestamp: row.columns[1].as_ref()
+            .and_then(|col| col.as_datetime())
+            .ok_or_else(|| anyhow::anyhow!("Missing batch_timestamp"))?,
+        tx_hashes: decode_hash_list(row, 2),
+        validation_status: row.columns[3].as_ref()
+            .and_then(|col| col.as_text())
+            .ok_or_else(|| anyhow::anyhow!("Missing validation_status"))?
+            .parse()
+            .map_err(anyhow::Error::msg)?,
+        validator_id: row.columns[4].as_ref().and_then(|col| col.as_text()).cloned().unwrap_or_default(),
+        started_at: row.columns[5].as_ref().and_then(|col| col.as_datetime()),
+        completed_at: row.columns[6].as_ref().and_then(|col| col.as_datetime()),
+        validation_result: row.columns[7].as_ref()
+            .and_then(|col| col.as_blob())
+            .map(|bytes| bincode::deserialize(bytes))
+            .transpose()?,
+        recovery_count: row.columns[8].as_ref().and_then(|col| col.as_int()).unwrap_or(0) as u32,
+        // Rows written before lineages existed start their own
+        batch_lineage_id: row.columns[9].as_ref().and_then(|col| col.as_uuid()).unwrap_or(queue_id),
+    })
+}
+
+/// Decode a row selected by `GET_RELAYER_BATCH`, `GET_PENDING_RELAYER_BATCHES`,
+/// `GET_FAILED_RELAYER_BATCHES`, or `GET_STUCK_RELAYER_BATCHES`
+pub(crate) fn decode_relayer_batch(row: &Row) -> Result<RelayerBatch> {
+    let commitment_id = row.columns[0].as_ref()
+        .and_then(|col| col.as_uuid())
+        .ok_or_else(|| anyhow::anyhow!("Missing commitment_id"))?;
+    Ok(RelayerBatch {
+        commitment_id,
+        batch_timestamp: row.columns[1].as_ref()
+            .and_then(|col| col.as_datetime())
+            .ok_or_else(|| anyhow::anyhow!("Missing batch_timestamp"))?,
+        tx_hashes: decode_hash_list(row, 2),
+        status: row.columns[3].as_ref()
+            .and_then(|col| col.as_text())
+            .ok_or_else(|| anyhow::anyhow!("Missing relayer status"))?
+            .parse()
+            .map_err(anyhow::Error::msg)?,
+        relayer_id: row.columns[4].as_ref().and_then(|col| col.as_text()).cloned().unwrap_or_default(),
+        retry_count: row.columns[5].as_ref().and_then(|col| col.as_int()).unwrap_or(0) as u32,
+        last_attempt: row.columns[6].as_ref().and_then(|col| col.as_datetime()),
+        target_block_height: row.columns[7].as_ref().and_then(|col| col.as_bigint()).map(|h| h as u64),
+        commitment_data: row.columns[8].as_ref()
+            .and_then(|col| col.as_blob())
+            .map(|bytes| bincode::deserialize(bytes))
+            .transpose()?,
+        target_inclusion: row.columns[9].as_ref()
+            .and_then(|col| col.as_blob())
+            .map(|bytes| bincode::deserialize(bytes))
+            .transpose()?,
+        recovery_count: row.columns[10].as_ref().and_then(|col| col.as_int()).unwrap_or(0) as u32,
+        batch_lineage_id: row.columns[11].as_ref().and_then(|col| col.as_uuid()).unwrap_or(commitment_id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        CommitmentData, FailedTransaction, PayloadEncoding, TargetInclusion, ValidationResult,
+    };
+    use blockchain_core::FeeSplit;
+    use chrono::Duration;
+
+    fn validation_result(validated: Vec<TxHash>, failed: Vec<TxHash>) -> ValidationResult {
+        ValidationResult {
+            is_valid: failed.is_empty(),
+            validated_transactions: validated,
+            failed_transactions: failed
+                .into_iter()
+                .map(|tx_hash| FailedTransaction {
+                    tx_hash,
+                    error_code: "NONCE_TOO_LOW".to_string(),
+                    error_message: "nonce already used".to_string(),
+                    suggested_gas_limit: None,
+                })
+                .collect(),
+            gas_estimates: vec![],
+            balance_changes: vec![],
+            validation_time_ms: 3,
+            error_message: None,
+        }
+    }
+
+    #[test]
+    fn test_unknown_transaction_has_no_lifecycle() {
+        assert_eq!(build_lifecycle(&[1; 32], &LifecycleFacts::default()), None);
+    }
+
+    #[test]
+    fn test_pending_transaction_stays_in_mempool() {
+        let since = Utc::now();
+        let facts = LifecycleFacts { pending_since: Some(since), ..Default::default() };
+
+        let lifecycle = build_lifecycle(&[1; 32], &facts).unwrap();
+        assert_eq!(lifecycle.stage, LifecycleStage::Mempool);
+        assert_eq!(lifecycle.timeline.len(), 1);
+        assert_eq!(lifecycle.timeline[0].status, "pending");
+        assert_eq!(lifecycle.timeline[0].at, Some(since));
+    }
+
+    #[test]
+    fn test_timeline_through_target_confirmation() {
+        let tx = Transaction::new_transfer([1; 20], [2; 20], 5, 0, 21_000, 1).unwrap();
+        let other = [7; 32];
+
+        // Failed once alongside a bad transaction, then validated on retry
+        let mut first = ValidationBatch::new(vec![tx.hash, other], "validator-1".to_string());
+        first.start_processing();
+        first.complete_validation(validation_result(vec![], vec![tx.hash]));
+        let mut second = ValidationBatch::new(vec![tx.hash], "validator-1".to_string());
+        second.complete_validation(validation_result(vec![tx.hash], vec![]));
+        second.completed_at = second.completed_at.map(|at| at + Duration::seconds(5));
+
+        let mut relayed = RelayerBatch::new(vec![tx.hash], "relayer-1".to_string());
+        relayed.start_processing(12);
+        relayed.mark_committed(
+            CommitmentData {
+                merkle_root: [0; 32],
+                transaction_count: 1,
+                total_gas_used: 21_000,
+                total_fees: 21_000,
+                batch_hash: [0xab; 32],
+                proof_data: vec![],
+                payload_encoding: PayloadEncoding::Raw,
+                payload: vec![],
+            },
+            TargetInclusion { tx_id: "0xfeed".to_string(), block_height: 40, block_hash: "0xbb".to_string() },
+        );
+
+        let facts = LifecycleFacts {
+            transaction: Some(tx.clone()),
+            receipt: Some(TransactionReceipt {
+                tx_hash: tx.hash,
+                block_height: 9,
+                index: 2,
+                fee: FeeSplit::default(),
+            }),
+            // Listed out of order; the timeline sorts them
+            validation_batches: vec![second.clone(), first.clone()],
+            relayer_batches: vec![relayed.clone()],
+            ..Default::default()
+        };
+
+        let lifecycle = build_lifecycle(&tx.hash, &facts).unwrap();
+        assert_eq!(lifecycle.stage, LifecycleStage::TargetConfirmation);
+        let stages: Vec<_> = lifecycle.timeline.iter().map(|e| (e.stage, e.status.as_str())).collect();
+        assert_eq!(
+            stages,
+            vec![
+                (LifecycleStage::Mempool, "submitted"),
+                (LifecycleStage::Validation, "failed"),
+                (LifecycleStage::Validation, "validated"),
+                (LifecycleStage::Block, "included"),
+                (LifecycleStage::Relayer, "committed"),
+                (LifecycleStage::Commitment, "committed"),
+                (LifecycleStage::TargetConfirmation, "confirmed"),
+            ]
+        );
+
+        let timeline = &lifecycle.timeline;
+        assert_eq!(timeline[1].reference, Some(first.queue_id.to_string()));
+        assert_eq!(timeline[1].detail.as_deref(), Some("NONCE_TOO_LOW: nonce already used"));
+        assert_eq!(timeline[3].reference.as_deref(), Some("9"));
+        assert_eq!(timeline[5].reference, Some(format!("0x{}", "ab".repeat(32))));
+        assert_eq!(timeline[6].reference.as_deref(), Some("0xfeed"));
+
+        // Until the batch completes, its own status is reported
+        let mut queued = ValidationBatch::new(vec![tx.hash], "validator-2".to_string());
+        queued.start_processing();
+        assert_eq!(validation_outcome(&tx.hash, &queued), ("processing".to_string(), None));
+    }
+}
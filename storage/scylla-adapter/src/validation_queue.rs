@@ -0,0 +1,155 @@
+// storage/scylla-adapter/src/validation_queue.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::{AccountState, Address, Transaction, TxHash};
+use chrono::{DateTime, Utc};
+use storage_traits::{StorageOperation, ValidationRecoveryStore, ValidationStore};
+use uuid::Uuid;
+
+use crate::model::ValidationBatch;
+use crate::tx_lifecycle::decode_validation_batch;
+use crate::{queries, ScyllaAdapter};
+
+impl ScyllaAdapter {
+    /// Persist a status change made with `start_processing` or
+    /// `complete_validation`, together with the result
+    pub async fn update_validation_status(&self, batch: &ValidationBatch) -> Result<()> {
+        self.fault_point(StorageOperation::UpdateValidationStatus).await?;
+        let result = batch.validation_result.as_ref().map(bincode::serialize).transpose()?;
+        self.session_for(StorageOperation::UpdateValidationStatus)
+            .query(
+                queries::UPDATE_VALIDATION_STATUS,
+                (
+                    batch.validation_status.to_string(),
+                    batch.started_at,
+                    batch.completed_at,
+                    result,
+                    batch.batch_timestamp,
+                    batch.queue_id,
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Batches no validator has started on, up to `limit`
+    pub async fn get_pending_validation_batches(&self, limit: i32) -> Result<Vec<ValidationBatch>> {
+        self.fault_point(StorageOperation::GetPendingValidationBatches).await?;
+        let rows = self.session_for(StorageOperation::GetPendingValidationBatches)
+            .query(queries::GET_PENDING_VALIDATION, (limit,))
+            .await?;
+        rows.rows.unwrap_or_default().iter().map(decode_validation_batch).collect()
+    }
+
+    /// Claim up to `limit` pending batches for `validator_id`, moving them to
+    /// `Processing`. A batch another validator claimed first is skipped.
+    pub async fn claim_validation_batches(&self, validator_id: &str, limit: i32) -> Result<Vec<ValidationBatch>> {
+        self.fault_point(StorageOperation::ClaimValidationBatches).await?;
+        let session = self.session_for(StorageOperation::ClaimValidationBatches);
+        let rows = session.query(queries::GET_PENDING_VALIDATION, (limit,)).await?;
+
+        let now = Utc::now();
+        let mut claimed = Vec::new();
+        for row in rows.rows.unwrap_or_default() {
+            let mut batch = decode_validation_batch(&row)?;
+            let result = session
+                .query(
+                    queries::CLAIM_VALIDATION_BATCH,
+                    (validator_id, now, batch.batch_timestamp, batch.queue_id),
+                )
+                .await?;
+            let applied = result.maybe_first_row()?
+                .as_ref()
+                .and_then(|row| row.columns[0].as_ref())
+                .and_then(|col| col.as_boolean())
+                .unwrap_or(false);
+            if !applied {
+                continue;
+            }
+            batch.claim(validator_id, now);
+            claimed.push(batch);
+        }
+        Ok(claimed)
+    }
+
+    /// Up to `limit` batches `Processing` since before `started_before`
+    pub async fn get_stuck_validation_batches(
+        &self,
+        started_before: DateTime<Utc>,
+        limit: i32,
+    ) -> Result<Vec<ValidationBatch>> {
+        self.fault_point(StorageOperation::RecoverValidationBatches).await?;
+        let rows = self.session_for(StorageOperation::RecoverValidationBatches)
+            .query(queries::GET_STUCK_VALIDATION_BATCHES, (started_before, limit))
+            .await?;
+        rows.rows.unwrap_or_default().iter().map(decode_validation_batch).collect()
+    }
+
+    /// Persist a batch `recover` put back in the queue, provided it is still
+    /// processing under the claim started at `claimed_at`. Returns `false`
+    /// when its validator finished it or another pass recovered it first.
+    pub async fn recover_validation_batch(
+        &self,
+        batch: &ValidationBatch,
+        claimed_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        self.fault_point(StorageOperation::RecoverValidationBatches).await?;
+        let result = self.session_for(StorageOperation::RecoverValidationBatches)
+            .query(
+                queries::RECOVER_VALIDATION_BATCH,
+                (batch.recovery_count as i32, batch.batch_timestamp, batch.queue_id, claimed_at),
+            )
+            .await?;
+        Ok(result.maybe_first_row()?
+            .as_ref()
+            .and_then(|row| row.columns[0].as_ref())
+            .and_then(|col| col.as_boolean())
+            .unwrap_or(false))
+    }
+
+    pub async fn get_validation_batch(
+        &self,
+        batch_timestamp: DateTime<Utc>,
+        queue_id: Uuid,
+    ) -> Result<Option<ValidationBatch>> {
+        self.fault_point(StorageOperation::GetValidationBatch).await?;
+        let rows = self.session_for(StorageOperation::GetValidationBatch)
+            .query(queries::GET_VALIDATION_BATCH, (batch_timestamp, queue_id))
+            .await?;
+        rows.maybe_first_row()?.map(|row| decode_validation_batch(&row)).transpose()
+    }
+}
+
+#[async_trait]
+impl ValidationStore for ScyllaAdapter {
+    async fn claim_batches(&self, validator_id: &str, limit: i32) -> Result<Vec<ValidationBatch>> {
+        self.claim_validation_batches(validator_id, limit).await
+    }
+
+    async fn pending_transaction(&self, tx_hash: &TxHash) -> Result<Option<Transaction>> {
+        self.get_pending_transaction(tx_hash).await
+    }
+
+    async fn account(&self, address: &Address) -> Result<AccountState> {
+        Ok(self
+            .get_account(address)
+            .await?
+            .map(|account| AccountState { balance: account.balance, nonce: account.nonce })
+            .unwrap_or_default())
+    }
+
+    async fn update_batch(&self, batch: &ValidationBatch) -> Result<()> {
+        self.update_validation_status(batch).await
+    }
+}
+
+#[async_trait]
+impl ValidationRecoveryStore for ScyllaAdapter {
+    async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<ValidationBatch>> {
+        self.get_stuck_validation_batches(stuck_since, limit).await
+    }
+
+    async fn recover_batch(&self, batch: &ValidationBatch, claimed_at: Option<DateTime<Utc>>) -> Result<bool> {
+        self.recover_validation_batch(batch, claimed_at).await
+    }
+}
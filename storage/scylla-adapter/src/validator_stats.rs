@@ -0,0 +1,433 @@
+// storage/scylla-adapter/src/validator_stats.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::{Address, BlockHeight, FinalityConfig, PoaConfig};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+use scylla::frame::response::result::Row;
+use std::collections::BTreeMap;
+use storage_traits::{StorageOperation, ValidatorStats, ValidatorStatsLookup, ValidatorStatsPeriod};
+
+use crate::model::StatsGranularity;
+use crate::stats_rollup::{granularity_for, start_of, week_start};
+use crate::{queries, ScyllaAdapter};
+
+/// One validator's assigned slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotRecord {
+    pub validator: Address,
+    pub slot: u64,
+    pub slot_start: DateTime<Utc>,
+    pub proposed: bool,
+    /// Slot start until the block was received, for proposed slots
+    pub latency_ms: Option<u64>,
+}
+
+/// Whether one finality validator signed a finalized checkpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttestationRecord {
+    pub validator: Address,
+    pub checkpoint_height: BlockHeight,
+    pub signed: bool,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Slot records for a block with `block_timestamp`, received at
+/// `received_at`, on top of a parent with `parent_timestamp`
+pub fn slot_records(
+    config: &PoaConfig,
+    parent_timestamp: DateTime<Utc>,
+    block_timestamp: DateTime<Utc>,
+    received_at: DateTime<Utc>,
+) -> Vec<SlotRecord> {
+    config
+        .slot_outcomes(parent_timestamp, block_timestamp)
+        .into_iter()
+        .map(|outcome| {
+            let slot_start = config.slot_start(outcome.slot);
+            SlotRecord {
+                validator: outcome.proposer,
+                slot: outcome.slot,
+                slot_start,
+                proposed: outcome.proposed,
+                latency_ms: outcome
+                    .proposed
+                    .then(|| (received_at - slot_start).num_milliseconds().max(0) as u64),
+            }
+        })
+        .collect()
+}
+
+/// One record per finality validator for a checkpoint finalized with `signers`
+pub fn attestation_records(
+    config: &FinalityConfig,
+    checkpoint_height: BlockHeight,
+    signers: &[Address],
+    recorded_at: DateTime<Utc>,
+) -> Vec<AttestationRecord> {
+    config
+        .validators
+        .iter()
+        .map(|validator| AttestationRecord {
+            validator: *validator,
+            checkpoint_height,
+            signed: signers.contains(validator),
+            recorded_at,
+        })
+        .collect()
+}
+
+/// Count one validator's records into a single period
+pub fn summarize(
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    slots: &[SlotRecord],
+    attestations: &[AttestationRecord],
+) -> ValidatorStatsPeriod {
+    let latencies: Vec<u64> = slots.iter().filter(|s| s.proposed).filter_map(|s| s.latency_ms).collect();
+    let avg_proposal_latency_ms = if latencies.is_empty() {
+        0.0
+    } else {
+        latencies.iter().sum::<u64>() as f64 / latencies.len() as f64
+    };
+
+    ValidatorStatsPeriod {
+        period_start,
+        period_end,
+        proposed_blocks: slots.iter().filter(|s| s.proposed).count() as u64,
+        missed_blocks: slots.iter().filter(|s| !s.proposed).count() as u64,
+        avg_proposal_latency_ms,
+        attestations_signed: attestations.iter().filter(|a| a.signed).count() as u64,
+        attestations_expected: attestations.len() as u64,
+    }
+}
+
+/// Combine finer periods into one. Latency is weighted by proposed blocks.
+pub fn combine(
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    periods: &[ValidatorStatsPeriod],
+) -> ValidatorStatsPeriod {
+    let proposed_blocks: u64 = periods.iter().map(|p| p.proposed_blocks).sum();
+    let weighted_latency: f64 = periods.iter().map(|p| p.avg_proposal_latency_ms * p.proposed_blocks as f64).sum();
+
+    ValidatorStatsPeriod {
+        period_start,
+        period_end,
+        proposed_blocks,
+        missed_blocks: periods.iter().map(|p| p.missed_blocks).sum(),
+        avg_proposal_latency_ms: if proposed_blocks == 0 { 0.0 } else { weighted_latency / proposed_blocks as f64 },
+        attestations_signed: periods.iter().map(|p| p.attestations_signed).sum(),
+        attestations_expected: periods.iter().map(|p| p.attestations_expected).sum(),
+    }
+}
+
+/// Hourly periods of one validator's records, oldest first; hours without records are omitted
+pub fn hourly_periods(slots: &[SlotRecord], attestations: &[AttestationRecord]) -> Vec<ValidatorStatsPeriod> {
+    let mut hours: BTreeMap<DateTime<Utc>, (Vec<SlotRecord>, Vec<AttestationRecord>)> = BTreeMap::new();
+    for slot in slots {
+        hours.entry(hour_start(slot.slot_start)).or_default().0.push(slot.clone());
+    }
+    for attestation in attestations {
+        hours.entry(hour_start(attestation.recorded_at)).or_default().1.push(attestation.clone());
+    }
+
+    hours
+        .into_iter()
+        .map(|(start, (slots, attestations))| summarize(start, start + Duration::hours(1), &slots, &attestations))
+        .collect()
+}
+
+fn hour_start(time: DateTime<Utc>) -> DateTime<Utc> {
+    start_of(time.date_naive()) + Duration::hours(time.hour() as i64)
+}
+
+impl ScyllaAdapter {
+    /// Store slot outcomes, e.g. from `slot_records` as each block is
+    /// imported. Recording a slot again overwrites it.
+    pub async fn record_validator_slots(&self, slots: &[SlotRecord]) -> Result<()> {
+        self.fault_point(StorageOperation::RecordValidatorActivity).await?;
+        let session = self.session_for(StorageOperation::RecordValidatorActivity);
+        for record in slots {
+            session
+                .query(
+                    queries::INSERT_VALIDATOR_SLOT,
+                    (
+                        record.slot_start.date_naive(),
+                        record.validator.to_vec(),
+                        record.slot as i64,
+                        record.slot_start,
+                        record.proposed,
+                        record.latency_ms.map(|ms| ms as i64),
+                    ),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Store the attestations of a finalized checkpoint, see `attestation_records`
+    pub async fn record_validator_attestations(&self, attestations: &[AttestationRecord]) -> Result<()> {
+        self.fault_point(StorageOperation::RecordValidatorActivity).await?;
+        let session = self.session_for(StorageOperation::RecordValidatorActivity);
+        for record in attestations {
+            session
+                .query(
+                    queries::INSERT_VALIDATOR_ATTESTATION,
+                    (
+                        record.recorded_at.date_naive(),
+                        record.validator.to_vec(),
+                        record.checkpoint_height as i64,
+                        record.signed,
+                        record.recorded_at,
+                    ),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Roll one day of slot and attestation rows into `validator_stats_daily`,
+    /// returning how many validators had activity that day
+    pub(crate) async fn roll_up_validator_stats(&self, day: NaiveDate) -> Result<u64> {
+        let session = self.session_for(StorageOperation::RollUpChainStats);
+
+        let mut validators: BTreeMap<Address, (Vec<SlotRecord>, Vec<AttestationRecord>)> = BTreeMap::new();
+        let rows = session.query(queries::GET_VALIDATOR_SLOTS_BY_DATE, (day,)).await?;
+        for row in rows.rows.unwrap_or_default() {
+            let slot = decode_slot(&row)?;
+            validators.entry(slot.validator).or_default().0.push(slot);
+        }
+        let rows = session.query(queries::GET_VALIDATOR_ATTESTATIONS_BY_DATE, (day,)).await?;
+        for row in rows.rows.unwrap_or_default() {
+            let attestation = decode_attestation(&row)?;
+            validators.entry(attestation.validator).or_default().1.push(attestation);
+        }
+
+        let start = start_of(day);
+        for (validator, (slots, attestations)) in &validators {
+            let daily = summarize(start, start + Duration::days(1), slots, attestations);
+            session
+                .query(
+                    queries::INSERT_VALIDATOR_STATS_DAILY,
+                    (
+                        validator.to_vec(),
+                        day.year(),
+                        day,
+                        daily.proposed_blocks as i64,
+                        daily.missed_blocks as i64,
+                        daily.avg_proposal_latency_ms,
+                        daily.attestations_signed as i64,
+                        daily.attestations_expected as i64,
+                    ),
+                )
+                .await?;
+        }
+        Ok(validators.len() as u64)
+    }
+
+    /// Performance of `validator` over `from..=to`, at the same granularity
+    /// `get_chain_stats_range` picks for the range. Weekly periods are
+    /// combined from daily rollups.
+    pub async fn get_validator_stats(
+        &self,
+        validator: &Address,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<ValidatorStats> {
+        self.fault_point(StorageOperation::GetValidatorStats).await?;
+        if from > to {
+            return Ok(ValidatorStats { total: combine(from, to, &[]), periods: Vec::new() });
+        }
+
+        let mut periods = match granularity_for(from, to, now) {
+            StatsGranularity::Hourly => {
+                let (mut slots, mut attestations) = (Vec::new(), Vec::new());
+                let mut day = from.date_naive();
+                while day <= to.date_naive() {
+                    let (day_slots, day_attestations) = self.validator_activity(validator, day).await?;
+                    slots.extend(day_slots);
+                    attestations.extend(day_attestations);
+                    day += Duration::days(1);
+                }
+                slots.retain(|slot| slot.slot_start >= from && slot.slot_start <= to);
+                attestations.retain(|a| a.recorded_at >= from && a.recorded_at <= to);
+                hourly_periods(&slots, &attestations)
+            }
+            StatsGranularity::Daily => {
+                self.daily_validator_stats(validator, from.date_naive(), to.date_naive()).await?
+            }
+            StatsGranularity::Weekly => {
+                let days = self.daily_validator_stats(validator, week_start(from.date_naive()), to.date_naive()).await?;
+                let mut weeks: BTreeMap<NaiveDate, Vec<ValidatorStatsPeriod>> = BTreeMap::new();
+                for day in days {
+                    weeks.entry(week_start(day.period_start.date_naive())).or_default().push(day);
+                }
+                weeks
+                    .into_iter()
+                    .map(|(monday, days)| combine(start_of(monday), start_of(monday) + Duration::weeks(1), &days))
+                    .collect()
+            }
+        };
+
+        periods.sort_by_key(|period| period.period_start);
+        Ok(ValidatorStats { total: combine(from, to, &periods), periods })
+    }
+
+    async fn validator_activity(
+        &self,
+        validator: &Address,
+        day: NaiveDate,
+    ) -> Result<(Vec<SlotRecord>, Vec<AttestationRecord>)> {
+        let session = self.session_for(StorageOperation::GetValidatorStats);
+
+        let rows = session.query(queries::GET_VALIDATOR_SLOTS, (day, validator.to_vec())).await?;
+        let slots = rows.rows.unwrap_or_default().iter().map(decode_slot).collect::<Result<Vec<_>>>()?;
+        let rows = session.query(queries::GET_VALIDATOR_ATTESTATIONS, (day, validator.to_vec())).await?;
+        let attestations = rows.rows.unwrap_or_default().iter().map(decode_attestation).collect::<Result<Vec<_>>>()?;
+        Ok((slots, attestations))
+    }
+
+    /// Daily rollups whose day falls within `from..=to`
+    async fn daily_validator_stats(
+        &self,
+        validator: &Address,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ValidatorStatsPeriod>> {
+        let session = self.session_for(StorageOperation::GetValidatorStats);
+
+        // Partitioned by validator and year like the chain stats rollups
+        let mut periods = Vec::new();
+        for year in from.year()..=to.year() {
+            let rows = session
+                .query(queries::GET_VALIDATOR_STATS_DAILY_RANGE, (validator.to_vec(), year, from, to))
+                .await?;
+            for row in rows.rows.unwrap_or_default() {
+                let day = row.columns[0].as_ref()
+                    .and_then(|col| col.as_naive_date())
+                    .ok_or_else(|| anyhow::anyhow!("Missing stat_date"))?;
+                let bigint = |i: usize| row.columns[i].as_ref().and_then(|col| col.as_bigint()).unwrap_or(0) as u64;
+                periods.push(ValidatorStatsPeriod {
+                    period_start: start_of(day),
+                    period_end: start_of(day) + Duration::days(1),
+                    proposed_blocks: bigint(1),
+                    missed_blocks: bigint(2),
+                    avg_proposal_latency_ms: row.columns[3].as_ref().and_then(|col| col.as_double()).unwrap_or(0.0),
+                    attestations_signed: bigint(4),
+                    attestations_expected: bigint(5),
+                });
+            }
+        }
+        Ok(periods)
+    }
+}
+
+#[async_trait]
+impl ValidatorStatsLookup for ScyllaAdapter {
+    async fn validator_stats(
+        &self,
+        validator: &Address,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<ValidatorStats> {
+        self.get_validator_stats(validator, from, to, Utc::now()).await
+    }
+}
+
+fn decode_validator(row: &Row) -> Result<Address> {
+    row.columns[0].as_ref()
+        .and_then(|col| col.as_blob())
+        .and_then(|bytes| bytes.as_slice().try_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("Missing validator"))
+}
+
+/// Decode a row selected by `GET_VALIDATOR_SLOTS` or `GET_VALIDATOR_SLOTS_BY_DATE`
+fn decode_slot(row: &Row) -> Result<SlotRecord> {
+    Ok(SlotRecord {
+        validator: decode_validator(row)?,
+        slot: row.columns[1].as_ref()
+            .and_then(|col| col.as_bigint())
+            .ok_or_else(|| anyhow::anyhow!("Missing slot"))? as u64,
+        slot_start: row.columns[2].as_ref()
+            .and_then(|col| col.as_datetime())
+            .ok_or_else(|| anyhow::anyhow!("Missing slot_start"))?,
+        proposed: row.columns[3].as_ref().and_then(|col| col.as_boolean()).unwrap_or(false),
+        latency_ms: row.columns[4].as_ref().and_then(|col| col.as_bigint()).map(|ms| ms as u64),
+    })
+}
+
+/// Decode a row selected by `GET_VALIDATOR_ATTESTATIONS` or `GET_VALIDATOR_ATTESTATIONS_BY_DATE`
+fn decode_attestation(row: &Row) -> Result<AttestationRecord> {
+    Ok(AttestationRecord {
+        validator: decode_validator(row)?,
+        checkpoint_height: row.columns[1].as_ref()
+            .and_then(|col| col.as_bigint())
+            .ok_or_else(|| anyhow::anyhow!("Missing checkpoint_height"))? as BlockHeight,
+        signed: row.columns[2].as_ref().and_then(|col| col.as_boolean()).unwrap_or(false),
+        recorded_at: row.columns[3].as_ref()
+            .and_then(|col| col.as_datetime())
+            .ok_or_else(|| anyhow::anyhow!("Missing recorded_at"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn poa() -> PoaConfig {
+        PoaConfig {
+            validators: vec![[1; 20], [2; 20]],
+            slot_duration_secs: 600,
+            genesis_time: Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_slot_records_latency_and_misses() {
+        let config = poa();
+        let received_at = config.slot_start(3) + Duration::milliseconds(250);
+        let records = slot_records(&config, config.slot_start(0), config.slot_start(3), received_at);
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].validator, [2; 20]);
+        assert!(!records[0].proposed && records[0].latency_ms.is_none());
+        assert_eq!(records[2].latency_ms, Some(250));
+
+        let mine: Vec<SlotRecord> = records.into_iter().filter(|r| r.validator == [2; 20]).collect();
+        let period = summarize(config.genesis_time, config.genesis_time + Duration::hours(1), &mine, &[]);
+        assert_eq!((period.proposed_blocks, period.missed_blocks), (1, 1));
+        assert_eq!(period.uptime(), Some(0.5));
+        assert_eq!(period.participation(), None);
+    }
+
+    #[test]
+    fn test_hourly_periods_and_combine() {
+        let config = poa();
+        let finality = FinalityConfig { validators: vec![[1; 20], [2; 20], [3; 20]], interval: 10 };
+        let mut slots = Vec::new();
+        for (slot, latency) in [(0, 100), (2, 300), (6, 900)] {
+            let start = config.slot_start(slot);
+            let received_at = start + Duration::milliseconds(latency);
+            slots.extend(slot_records(&config, start - Duration::minutes(20), start, received_at));
+        }
+        let slots: Vec<SlotRecord> = slots.into_iter().filter(|s| s.validator == [1; 20]).collect();
+        let mut attestations = attestation_records(&finality, 10, &[[1; 20], [3; 20]], config.slot_start(1));
+        attestations.extend(attestation_records(&finality, 20, &[[2; 20], [3; 20]], config.slot_start(7)));
+        attestations.retain(|a| a.validator == [1; 20]);
+
+        let hours = hourly_periods(&slots, &attestations);
+        assert_eq!(hours.len(), 2);
+        assert_eq!(hours[0].period_start, config.genesis_time);
+        assert_eq!(hours[0].avg_proposal_latency_ms, 200.0);
+        assert_eq!((hours[0].attestations_signed, hours[0].attestations_expected), (1, 1));
+        assert_eq!((hours[1].proposed_blocks, hours[1].attestations_signed), (1, 0));
+
+        let total = combine(config.genesis_time, config.slot_start(7), &hours);
+        assert_eq!(total.proposed_blocks, 3);
+        assert_eq!(total.avg_proposal_latency_ms, 1300.0 / 3.0);
+        assert_eq!(total.participation(), Some(0.5));
+        assert_eq!(combine(config.genesis_time, config.genesis_time, &[]).avg_proposal_latency_ms, 0.0);
+    }
+}
@@ -0,0 +1,128 @@
+// storage/storage-traits/src/blockchain_storage.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::{Address, Block, BlockHash, BlockHeader, BlockHeight, Transaction, TxHash};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Account model for database storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountModel {
+    pub address: Address,
+    pub balance: u64,
+    pub nonce: u64,
+    pub last_updated: DateTime<Utc>,
+    pub account_type: String, // "user" or "contract"
+    pub code_hash: Option<BlockHash>, // For contract accounts
+}
+
+/// Chain, mempool and account storage used by node services.
+///
+/// Relayer and validation crates depend on this trait rather than on a
+/// concrete backend, so storage can be swapped without touching them.
+#[async_trait]
+pub trait BlockchainStorage: Send + Sync {
+    /// Store a block together with its transactions
+    async fn store_block(&self, block: &Block) -> Result<()>;
+
+    async fn get_block_by_height(&self, height: BlockHeight) -> Result<Option<Block>>;
+
+    async fn get_block_by_hash(&self, hash: &BlockHash) -> Result<Option<Block>>;
+
+    /// Headers at the given heights in ascending height order, without loading
+    /// block bodies; heights that are not stored are skipped
+    async fn get_block_headers(&self, heights: &[BlockHeight]) -> Result<Vec<BlockHeader>>;
+
+    /// Height of the chain head, `None` before genesis is stored
+    async fn get_latest_block_height(&self) -> Result<Option<BlockHeight>>;
+
+    async fn get_transaction(&self, tx_hash: &TxHash) -> Result<Option<Transaction>>;
+
+    async fn add_pending_transaction(&self, tx: &Transaction) -> Result<()>;
+
+    async fn remove_pending_transaction(&self, tx_hash: &TxHash) -> Result<()>;
+
+    async fn get_pending_transactions(&self, limit: i32) -> Result<Vec<Transaction>>;
+
+    async fn update_account(
+        &self,
+        address: &Address,
+        balance: u64,
+        nonce: u64,
+        account_type: &str,
+    ) -> Result<()>;
+
+    async fn get_account(&self, address: &Address) -> Result<Option<AccountModel>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Minimal backend proving the trait is object safe and usable behind `Arc<dyn _>`
+    #[derive(Default)]
+    struct MemoryStorage {
+        blocks: Mutex<HashMap<BlockHeight, Block>>,
+    }
+
+    #[async_trait]
+    impl BlockchainStorage for MemoryStorage {
+        async fn store_block(&self, block: &Block) -> Result<()> {
+            self.blocks.lock().unwrap().insert(block.header.height, block.clone());
+            Ok(())
+        }
+
+        async fn get_block_by_height(&self, height: BlockHeight) -> Result<Option<Block>> {
+            Ok(self.blocks.lock().unwrap().get(&height).cloned())
+        }
+
+        async fn get_block_by_hash(&self, hash: &BlockHash) -> Result<Option<Block>> {
+            Ok(self.blocks.lock().unwrap().values().find(|b| &b.hash == hash).cloned())
+        }
+
+        async fn get_block_headers(&self, heights: &[BlockHeight]) -> Result<Vec<BlockHeader>> {
+            let blocks = self.blocks.lock().unwrap();
+            Ok(heights.iter().filter_map(|h| blocks.get(h)).map(|b| b.header.clone()).collect())
+        }
+
+        async fn get_latest_block_height(&self) -> Result<Option<BlockHeight>> {
+            Ok(self.blocks.lock().unwrap().keys().max().copied())
+        }
+
+        async fn get_transaction(&self, _tx_hash: &TxHash) -> Result<Option<Transaction>> {
+            Ok(None)
+        }
+
+        async fn add_pending_transaction(&self, _tx: &Transaction) -> Result<()> {
+            Ok(())
+        }
+
+        async fn remove_pending_transaction(&self, _tx_hash: &TxHash) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_pending_transactions(&self, _limit: i32) -> Result<Vec<Transaction>> {
+            Ok(Vec::new())
+        }
+
+        async fn update_account(&self, _: &Address, _: u64, _: u64, _: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_account(&self, _address: &Address) -> Result<Option<AccountModel>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_storage_behind_trait_object() {
+        let storage: Arc<dyn BlockchainStorage> = Arc::new(MemoryStorage::default());
+        let genesis = Block::genesis().unwrap();
+
+        storage.store_block(&genesis).await.unwrap();
+        assert_eq!(storage.get_latest_block_height().await.unwrap(), Some(0));
+        assert_eq!(storage.get_block_by_hash(&genesis.hash).await.unwrap(), Some(genesis));
+    }
+}
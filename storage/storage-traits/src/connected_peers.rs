@@ -0,0 +1,12 @@
+// storage/storage-traits/src/connected_peers.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::BlockHeight;
+
+/// Read side of the peers the p2p layer marked connected, for services that
+/// wait on the node being in touch with the network
+#[async_trait]
+pub trait ConnectedPeers: Send + Sync {
+    /// Chain height each connected peer reported at its handshake
+    async fn connected_peer_heights(&self) -> Result<Vec<BlockHeight>>;
+}
@@ -0,0 +1,37 @@
+// storage/storage-traits/src/connection.rs
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connected,
+    /// The backend is unreachable; reads fail fast and writes are queued
+    Degraded,
+    /// Reconnected, replaying the writes queued while degraded
+    Draining,
+}
+
+/// How the storage backend's connection is holding up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionHealth {
+    pub state: ConnectionState,
+    /// Keep-alives failed in a row
+    pub consecutive_failures: u32,
+    /// When the current outage began
+    pub degraded_since: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Writes waiting to be replayed
+    pub queued_writes: usize,
+    /// Times the connection was rebuilt
+    pub reconnects: u64,
+}
+
+/// Keep-alive and reconnection of a storage backend's connection
+#[async_trait]
+pub trait ConnectionSupervisor: Send + Sync {
+    /// Check the connection, rebuilding it and replaying queued writes once
+    /// it has failed persistently, and report where it stands
+    async fn supervise_connection(&self) -> ConnectionHealth;
+}
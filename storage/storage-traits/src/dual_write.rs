@@ -0,0 +1,505 @@
+// storage/storage-traits/src/dual_write.rs
+//! Moving between storage backends without downtime.
+//!
+//! `DualWriteStorage` wraps two backends. Every write goes to the primary
+//! first, which decides whether it succeeded, and then to the secondary; a
+//! secondary write that fails is kept and retried by `sync`, up to
+//! `max_retry_queue` writes. Reads are served by the primary, and every
+//! `verify_every`th block, transaction or account read is also made against
+//! the secondary and compared.
+//!
+//! `sync` also copies blocks the secondary is missing below the primary's
+//! head, with their transactions. Accounts cannot be listed through
+//! `BlockchainStorage`, so accounts last written before dual writing began
+//! must be copied by other means; verification reports those missing from
+//! the secondary as mismatches.
+//!
+//! Once nothing is queued, the secondary has every block and no read has
+//! disagreed, `cutover` swaps the roles: reads move to the new backend while
+//! writes keep reaching the old one, which can be dropped at the next restart.
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use blockchain_core::{Address, Block, BlockHash, BlockHeader, BlockHeight, Transaction, TxHash};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::{AccountModel, BlockchainStorage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DualWriteConfig {
+    /// Every how many reads one is verified against the secondary; 0 disables it
+    pub verify_every: u64,
+    /// Failed secondary writes kept for retry; past this the secondary needs a resync
+    pub max_retry_queue: usize,
+}
+
+impl Default for DualWriteConfig {
+    fn default() -> Self {
+        Self { verify_every: 100, max_retry_queue: 10_000 }
+    }
+}
+
+/// How far the secondary is from taking over
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DualWriteStatus {
+    pub cut_over: bool,
+    /// Secondary writes waiting for a retry
+    pub queued_writes: usize,
+    /// Secondary writes dropped because the retry queue was full
+    pub dropped_writes: u64,
+    pub primary_height: Option<BlockHeight>,
+    pub secondary_height: Option<BlockHeight>,
+    /// Blocks up to the primary's head the secondary is missing
+    pub blocks_behind: u64,
+    pub reads_verified: u64,
+    pub mismatches: u64,
+    pub last_mismatch: Option<String>,
+}
+
+impl DualWriteStatus {
+    /// Whether the secondary holds everything the primary does
+    pub fn caught_up(&self) -> bool {
+        self.queued_writes == 0 && self.dropped_writes == 0 && self.blocks_behind == 0
+    }
+}
+
+#[derive(Debug, Clone)]
+enum SecondaryWrite {
+    StoreBlock(Block),
+    AddPendingTransaction(Transaction),
+    RemovePendingTransaction(TxHash),
+    UpdateAccount { address: Address, balance: u64, nonce: u64, account_type: String },
+}
+
+#[derive(Debug, Default)]
+struct State {
+    retries: VecDeque<SecondaryWrite>,
+    dropped_writes: u64,
+    reads: u64,
+    reads_verified: u64,
+    mismatches: u64,
+    last_mismatch: Option<String>,
+    /// Heights below this are known to be on the secondary
+    backfilled_to: BlockHeight,
+    cut_over: bool,
+}
+
+struct Backends {
+    primary: Arc<dyn BlockchainStorage>,
+    secondary: Arc<dyn BlockchainStorage>,
+}
+
+pub struct DualWriteStorage {
+    backends: RwLock<Backends>,
+    config: DualWriteConfig,
+    state: Mutex<State>,
+}
+
+impl DualWriteStorage {
+    pub fn new(
+        primary: Arc<dyn BlockchainStorage>,
+        secondary: Arc<dyn BlockchainStorage>,
+        config: DualWriteConfig,
+    ) -> Self {
+        Self { backends: RwLock::new(Backends { primary, secondary }), config, state: Mutex::default() }
+    }
+
+    fn primary(&self) -> Arc<dyn BlockchainStorage> {
+        self.backends.read().unwrap().primary.clone()
+    }
+
+    fn secondary(&self) -> Arc<dyn BlockchainStorage> {
+        self.backends.read().unwrap().secondary.clone()
+    }
+
+    async fn apply(storage: &dyn BlockchainStorage, write: &SecondaryWrite) -> Result<()> {
+        match write {
+            SecondaryWrite::StoreBlock(block) => storage.store_block(block).await,
+            SecondaryWrite::AddPendingTransaction(tx) => storage.add_pending_transaction(tx).await,
+            SecondaryWrite::RemovePendingTransaction(tx_hash) => storage.remove_pending_transaction(tx_hash).await,
+            SecondaryWrite::UpdateAccount { address, balance, nonce, account_type } => {
+                storage.update_account(address, *balance, *nonce, account_type).await
+            }
+        }
+    }
+
+    /// Mirror a write the primary accepted; earlier failed writes go first so
+    /// the secondary sees writes in order
+    async fn mirror(&self, write: SecondaryWrite) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if !state.retries.is_empty() {
+                Self::queue_retry(&mut state, &self.config, write);
+                return;
+            }
+        }
+        if Self::apply(self.secondary().as_ref(), &write).await.is_err() {
+            Self::queue_retry(&mut self.state.lock().unwrap(), &self.config, write);
+        }
+    }
+
+    fn queue_retry(state: &mut State, config: &DualWriteConfig, write: SecondaryWrite) {
+        if state.retries.len() >= config.max_retry_queue {
+            state.dropped_writes += 1;
+        } else {
+            state.retries.push_back(write);
+        }
+    }
+
+    /// Whether this read should also be made against the secondary
+    fn sample(&self) -> bool {
+        if self.config.verify_every == 0 {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.reads += 1;
+        state.reads % self.config.verify_every == 0
+    }
+
+    fn record_verification(&self, mismatch: Option<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.reads_verified += 1;
+        if let Some(mismatch) = mismatch {
+            state.mismatches += 1;
+            state.last_mismatch = Some(mismatch);
+        }
+    }
+
+    /// Compare a sampled block read; a block the secondary has not received
+    /// yet is lag, not a mismatch
+    fn verify_block(&self, read: &Option<Block>, secondary: Result<Option<Block>>) {
+        let mismatch = match (read, secondary) {
+            (Some(block), Ok(Some(other))) if other.hash != block.hash => Some(format!(
+                "Block {} is 0x{} on the primary, 0x{} on the secondary",
+                block.header.height,
+                hex::encode(block.hash),
+                hex::encode(other.hash)
+            )),
+            (_, Err(e)) => Some(format!("Secondary read failed: {}", e)),
+            _ => None,
+        };
+        self.record_verification(mismatch);
+    }
+
+    pub async fn status(&self) -> Result<DualWriteStatus> {
+        let primary_height = self.primary().get_latest_block_height().await?;
+        let secondary_height = self.secondary().get_latest_block_height().await?;
+        let blocks_behind = self.missing_blocks(primary_height).await?.len() as u64;
+        let state = self.state.lock().unwrap();
+        Ok(DualWriteStatus {
+            cut_over: state.cut_over,
+            queued_writes: state.retries.len(),
+            dropped_writes: state.dropped_writes,
+            primary_height,
+            secondary_height,
+            blocks_behind,
+            reads_verified: state.reads_verified,
+            mismatches: state.mismatches,
+            last_mismatch: state.last_mismatch.clone(),
+        })
+    }
+
+    /// Heights up to `head` the secondary does not hold, above those already
+    /// confirmed; the run of heights found present moves the confirmed mark
+    async fn missing_blocks(&self, head: Option<BlockHeight>) -> Result<Vec<BlockHeight>> {
+        let Some(head) = head else {
+            return Ok(Vec::new());
+        };
+        let from = self.state.lock().unwrap().backfilled_to;
+        let heights: Vec<BlockHeight> = (from..=head).collect();
+        let mut present = HashSet::new();
+        for chunk in heights.chunks(1_000) {
+            present.extend(self.secondary().get_block_headers(chunk).await?.into_iter().map(|header| header.height));
+        }
+        let missing: Vec<BlockHeight> = heights.into_iter().filter(|height| !present.contains(height)).collect();
+        let mut state = self.state.lock().unwrap();
+        state.backfilled_to = state.backfilled_to.max(missing.first().map_or(head + 1, |first| *first));
+        Ok(missing)
+    }
+
+    /// Retry failed secondary writes, then copy up to `max_blocks` blocks the
+    /// secondary is missing
+    pub async fn sync(&self, max_blocks: usize) -> Result<DualWriteStatus> {
+        let secondary = self.secondary();
+        loop {
+            let Some(write) = self.state.lock().unwrap().retries.front().cloned() else {
+                break;
+            };
+            Self::apply(secondary.as_ref(), &write).await?;
+            self.state.lock().unwrap().retries.pop_front();
+        }
+
+        let primary = self.primary();
+        let head = primary.get_latest_block_height().await?;
+        let missing = self.missing_blocks(head).await?;
+        for height in missing.iter().copied().take(max_blocks) {
+            if let Some(block) = primary.get_block_by_height(height).await? {
+                secondary.store_block(&block).await?;
+            }
+        }
+        if let Some(head) = head.filter(|_| missing.len() <= max_blocks) {
+            // Later blocks reach the secondary by dual writes, or the retry queue
+            let mut state = self.state.lock().unwrap();
+            state.backfilled_to = state.backfilled_to.max(head + 1);
+        }
+        self.status().await
+    }
+
+    /// Swap the backends once the secondary has caught up and no verified
+    /// read has disagreed
+    pub async fn cutover(&self) -> Result<DualWriteStatus> {
+        let status = self.status().await?;
+        if status.cut_over {
+            bail!("Storage has already been cut over");
+        }
+        if !status.caught_up() {
+            bail!(
+                "Secondary is not caught up: {} writes queued, {} dropped, {} blocks behind",
+                status.queued_writes,
+                status.dropped_writes,
+                status.blocks_behind
+            );
+        }
+        if status.mismatches > 0 {
+            bail!(
+                "{} verified reads disagreed, last: {}",
+                status.mismatches,
+                status.last_mismatch.as_deref().unwrap_or_default()
+            );
+        }
+
+        {
+            let mut backends = self.backends.write().unwrap();
+            let Backends { primary, secondary } = &mut *backends;
+            std::mem::swap(primary, secondary);
+            let mut state = self.state.lock().unwrap();
+            state.cut_over = true;
+        }
+        self.status().await
+    }
+}
+
+#[async_trait]
+impl BlockchainStorage for DualWriteStorage {
+    async fn store_block(&self, block: &Block) -> Result<()> {
+        self.primary().store_block(block).await?;
+        self.mirror(SecondaryWrite::StoreBlock(block.clone())).await;
+        Ok(())
+    }
+
+    async fn get_block_by_height(&self, height: BlockHeight) -> Result<Option<Block>> {
+        let block = self.primary().get_block_by_height(height).await?;
+        if self.sample() {
+            self.verify_block(&block, self.secondary().get_block_by_height(height).await);
+        }
+        Ok(block)
+    }
+
+    async fn get_block_by_hash(&self, hash: &BlockHash) -> Result<Option<Block>> {
+        let block = self.primary().get_block_by_hash(hash).await?;
+        if self.sample() {
+            self.verify_block(&block, self.secondary().get_block_by_hash(hash).await);
+        }
+        Ok(block)
+    }
+
+    async fn get_block_headers(&self, heights: &[BlockHeight]) -> Result<Vec<BlockHeader>> {
+        self.primary().get_block_headers(heights).await
+    }
+
+    async fn get_latest_block_height(&self) -> Result<Option<BlockHeight>> {
+        self.primary().get_latest_block_height().await
+    }
+
+    async fn get_transaction(&self, tx_hash: &TxHash) -> Result<Option<Transaction>> {
+        let tx = self.primary().get_transaction(tx_hash).await?;
+        if self.sample() {
+            let mismatch = match (&tx, self.secondary().get_transaction(tx_hash).await) {
+                (Some(tx), Ok(Some(other))) if other != *tx => {
+                    Some(format!("Transaction 0x{} differs on the secondary", hex::encode(tx_hash)))
+                }
+                (_, Err(e)) => Some(format!("Secondary read failed: {}", e)),
+                _ => None,
+            };
+            self.record_verification(mismatch);
+        }
+        Ok(tx)
+    }
+
+    async fn add_pending_transaction(&self, tx: &Transaction) -> Result<()> {
+        self.primary().add_pending_transaction(tx).await?;
+        self.mirror(SecondaryWrite::AddPendingTransaction(tx.clone())).await;
+        Ok(())
+    }
+
+    async fn remove_pending_transaction(&self, tx_hash: &TxHash) -> Result<()> {
+        self.primary().remove_pending_transaction(tx_hash).await?;
+        self.mirror(SecondaryWrite::RemovePendingTransaction(*tx_hash)).await;
+        Ok(())
+    }
+
+    async fn get_pending_transactions(&self, limit: i32) -> Result<Vec<Transaction>> {
+        self.primary().get_pending_transactions(limit).await
+    }
+
+    async fn update_account(
+        &self,
+        address: &Address,
+        balance: u64,
+        nonce: u64,
+        account_type: &str,
+    ) -> Result<()> {
+        self.primary().update_account(address, balance, nonce, account_type).await?;
+        let write = SecondaryWrite::UpdateAccount {
+            address: *address,
+            balance,
+            nonce,
+            account_type: account_type.to_string(),
+        };
+        self.mirror(write).await;
+        Ok(())
+    }
+
+    async fn get_account(&self, address: &Address) -> Result<Option<AccountModel>> {
+        let account = self.primary().get_account(address).await?;
+        if self.sample() {
+            let key = |account: &AccountModel| (account.balance, account.nonce, account.account_type.clone());
+            let mismatch = match (&account, self.secondary().get_account(address).await) {
+                (Some(account), Ok(other)) if other.as_ref().map(key) != Some(key(account)) => {
+                    Some(format!("Account 0x{} differs on the secondary", hex::encode(address)))
+                }
+                (_, Err(e)) => Some(format!("Secondary read failed: {}", e)),
+                _ => None,
+            };
+            self.record_verification(mismatch);
+        }
+        Ok(account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct Backend {
+        blocks: Mutex<HashMap<BlockHeight, Block>>,
+        accounts: Mutex<HashMap<Address, (u64, u64)>>,
+        failing_writes: AtomicBool,
+    }
+
+    impl Backend {
+        fn check_writable(&self) -> Result<()> {
+            if self.failing_writes.load(Ordering::Relaxed) {
+                bail!("backend unavailable");
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl BlockchainStorage for Backend {
+        async fn store_block(&self, block: &Block) -> Result<()> {
+            self.check_writable()?;
+            self.blocks.lock().unwrap().insert(block.header.height, block.clone());
+            Ok(())
+        }
+
+        async fn get_block_by_height(&self, height: BlockHeight) -> Result<Option<Block>> {
+            Ok(self.blocks.lock().unwrap().get(&height).cloned())
+        }
+
+        async fn get_block_by_hash(&self, hash: &BlockHash) -> Result<Option<Block>> {
+            Ok(self.blocks.lock().unwrap().values().find(|b| &b.hash == hash).cloned())
+        }
+
+        async fn get_block_headers(&self, heights: &[BlockHeight]) -> Result<Vec<BlockHeader>> {
+            let blocks = self.blocks.lock().unwrap();
+            Ok(heights.iter().filter_map(|h| blocks.get(h)).map(|b| b.header.clone()).collect())
+        }
+
+        async fn get_latest_block_height(&self) -> Result<Option<BlockHeight>> {
+            Ok(self.blocks.lock().unwrap().keys().max().copied())
+        }
+
+        async fn get_transaction(&self, _tx_hash: &TxHash) -> Result<Option<Transaction>> {
+            Ok(None)
+        }
+
+        async fn add_pending_transaction(&self, _tx: &Transaction) -> Result<()> {
+            self.check_writable()
+        }
+
+        async fn remove_pending_transaction(&self, _tx_hash: &TxHash) -> Result<()> {
+            self.check_writable()
+        }
+
+        async fn get_pending_transactions(&self, _limit: i32) -> Result<Vec<Transaction>> {
+            Ok(Vec::new())
+        }
+
+        async fn update_account(&self, address: &Address, balance: u64, nonce: u64, _: &str) -> Result<()> {
+            self.check_writable()?;
+            self.accounts.lock().unwrap().insert(*address, (balance, nonce));
+            Ok(())
+        }
+
+        async fn get_account(&self, address: &Address) -> Result<Option<AccountModel>> {
+            Ok(self.accounts.lock().unwrap().get(address).map(|(balance, nonce)| AccountModel {
+                address: *address,
+                balance: *balance,
+                nonce: *nonce,
+                last_updated: Utc::now(),
+                account_type: "user".to_string(),
+                code_hash: None,
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_retry_verify_and_cutover() {
+        let (old, new) = (Arc::new(Backend::default()), Arc::new(Backend::default()));
+        let mut previous = [0; 32];
+        let mut blocks = Vec::new();
+        for height in 0..4 {
+            let block = Block::new(height, previous, Vec::new(), 1).unwrap();
+            previous = block.hash;
+            blocks.push(block);
+        }
+        for block in &blocks[..3] {
+            old.store_block(block).await.unwrap();
+        }
+        let config = DualWriteConfig { verify_every: 1, max_retry_queue: 10 };
+        let storage = DualWriteStorage::new(old.clone(), new.clone(), config);
+
+        // Writes the secondary refuses wait for the next sync
+        storage.store_block(&blocks[3]).await.unwrap();
+        new.failing_writes.store(true, Ordering::Relaxed);
+        storage.update_account(&[1; 20], 500, 2, "user").await.unwrap();
+        let status = storage.status().await.unwrap();
+        assert_eq!((status.queued_writes, status.blocks_behind), (1, 3));
+        assert!(storage.cutover().await.unwrap_err().to_string().contains("not caught up"));
+
+        new.failing_writes.store(false, Ordering::Relaxed);
+        assert_eq!(storage.sync(2).await.unwrap().blocks_behind, 1);
+        let status = storage.sync(2).await.unwrap();
+        assert!(status.caught_up(), "{:?}", status);
+        assert!(storage.get_account(&[1; 20]).await.unwrap().is_some());
+        assert_eq!(storage.get_block_by_height(0).await.unwrap(), Some(blocks[0].clone()));
+
+        let status = storage.cutover().await.unwrap();
+        assert!(status.cut_over && status.mismatches == 0 && status.reads_verified == 2);
+        assert!(storage.cutover().await.is_err());
+
+        // Reads now come from the new backend and are checked against the old one
+        old.blocks.lock().unwrap().insert(0, blocks[1].clone());
+        assert_eq!(storage.get_block_by_height(0).await.unwrap(), Some(blocks[0].clone()));
+        let status = storage.status().await.unwrap();
+        assert_eq!(status.mismatches, 1);
+        assert!(status.last_mismatch.unwrap().starts_with("Block 0 "));
+    }
+}
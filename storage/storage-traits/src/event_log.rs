@@ -0,0 +1,80 @@
+// storage/storage-traits/src/event_log.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Event recorded in the replayable log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainEvent {
+    /// Position in the log; strictly increasing, possibly with gaps
+    pub seq: u64,
+    /// Kind of event, e.g. `block_stored`
+    pub event_type: String,
+    /// What the event is about, e.g. a block hash; filters match it exactly
+    pub subject: String,
+    /// JSON body
+    pub payload: String,
+    pub published_at: DateTime<Utc>,
+}
+
+/// Events a consumer wants to see; the default matches everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Accepted event types; empty accepts all
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub subject: Option<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &ChainEvent) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && self.subject.iter().all(|subject| *subject == event.subject)
+    }
+}
+
+/// One page of a replay
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventPage {
+    /// Matching events in sequence order
+    pub events: Vec<ChainEvent>,
+    /// Cursor to pass as `from_seq` for the next page. It moves past
+    /// filtered-out events too, so a page can be empty yet make progress.
+    pub next_seq: u64,
+    /// Sequence number the next published event will get
+    pub head_seq: u64,
+}
+
+/// Append-only log of published events that consumers replay by cursor
+#[async_trait]
+pub trait EventLog: Send + Sync {
+    /// Append an event, returning its sequence number
+    async fn publish_event(&self, event_type: &str, subject: &str, payload: &str) -> Result<u64>;
+
+    /// Events from `from_seq` onwards matching `filter`, scanning at most `limit` entries
+    async fn replay_events(&self, from_seq: u64, filter: &EventFilter, limit: usize) -> Result<EventPage>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_matching() {
+        let event = ChainEvent {
+            seq: 3,
+            event_type: "block_stored".to_string(),
+            subject: "0xab".to_string(),
+            payload: "{}".to_string(),
+            published_at: Utc::now(),
+        };
+
+        assert!(EventFilter::default().matches(&event));
+        let by_type = EventFilter { event_types: vec!["block_stored".to_string()], subject: None };
+        assert!(by_type.matches(&event));
+        let other_subject = EventFilter { subject: Some("0xcd".to_string()), ..by_type };
+        assert!(!other_subject.matches(&event));
+    }
+}
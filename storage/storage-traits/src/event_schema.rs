@@ -0,0 +1,285 @@
+// storage/storage-traits/src/event_schema.rs
+//! Versioned payloads of published events.
+//!
+//! Every payload the event log carries is declared once in `event_payloads!`,
+//! which generates its struct, its serde JSON and prost protobuf encoders,
+//! and the `EventSchema` describing it. From the schemas come a JSON Schema
+//! and a `.proto` message per payload, for consumers outside this workspace.
+//!
+//! Published schemas are recorded in `schemas/events.json`. A version may
+//! gain fields, which old consumers ignore and new consumers default, but
+//! removing a field or changing its tag or type breaks consumers, so it
+//! needs a new version. Version 1 of a payload is published under its bare
+//! event type and later versions under `{event_type}.v{version}`, so both
+//! can be published side by side while consumers move over.
+use anyhow::{bail, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+/// A payload type with a registered schema
+pub trait EventPayload: Serialize + DeserializeOwned + prost::Message + Default + Sized {
+    const EVENT_TYPE: &'static str;
+    const VERSION: u32;
+
+    fn schema() -> EventSchema;
+
+    /// Event type this version is published under
+    fn wire_type() -> String {
+        wire_type(Self::EVENT_TYPE, Self::VERSION)
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    fn from_json(payload: &str) -> Result<Self> {
+        Ok(serde_json::from_str(payload)?)
+    }
+
+    fn to_proto(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    fn from_proto(payload: &[u8]) -> Result<Self> {
+        Ok(Self::decode(payload)?)
+    }
+}
+
+/// Event type version `version` of `event_type` is published under
+pub fn wire_type(event_type: &str, version: u32) -> String {
+    match version {
+        1 => event_type.to_string(),
+        _ => format!("{}.v{}", event_type, version),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSchema {
+    pub name: String,
+    /// Protobuf field number
+    pub tag: u32,
+    /// Protobuf scalar type, e.g. `uint64`
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSchema {
+    pub event_type: String,
+    pub version: u32,
+    /// Protobuf message and JSON Schema title
+    pub message: String,
+    pub fields: Vec<FieldSchema>,
+}
+
+impl EventSchema {
+    /// JSON Schema for the payload. Nothing is `required`: payloads decode with
+    /// `#[serde(default)]`, so fields missing from older producers read as defaults.
+    pub fn json_schema(&self) -> Value {
+        let properties: serde_json::Map<String, Value> =
+            self.fields.iter().map(|field| (field.name.clone(), json_type(&field.kind))).collect();
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": format!("events/{}/v{}", self.event_type, self.version),
+            "title": self.message,
+            "type": "object",
+            "properties": properties,
+        })
+    }
+
+    pub fn proto_message(&self) -> String {
+        let mut out = format!("// {} v{}\nmessage {} {{\n", self.event_type, self.version, self.message);
+        for field in &self.fields {
+            out.push_str(&format!("  {} {} = {};\n", field.kind, field.name, field.tag));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Why a consumer of `self` cannot read `current`; empty if it can
+    pub fn incompatibilities(&self, current: &EventSchema) -> Vec<String> {
+        let mut problems = Vec::new();
+        for field in &self.fields {
+            match current.fields.iter().find(|candidate| candidate.name == field.name) {
+                None => problems.push(format!("field `{}` was removed", field.name)),
+                Some(now) if now.tag != field.tag => {
+                    problems.push(format!("field `{}` moved from tag {} to {}", field.name, field.tag, now.tag))
+                }
+                Some(now) if now.kind != field.kind => {
+                    problems.push(format!("field `{}` changed from {} to {}", field.name, field.kind, now.kind))
+                }
+                Some(_) => {}
+            }
+        }
+        for field in &current.fields {
+            let reused = self.fields.iter().any(|old| old.tag == field.tag && old.name != field.name);
+            if reused {
+                problems.push(format!("field `{}` reuses tag {}", field.name, field.tag));
+            }
+        }
+        problems
+    }
+}
+
+fn json_type(kind: &str) -> Value {
+    match kind {
+        "uint32" | "uint64" | "fixed32" | "fixed64" => json!({ "type": "integer", "minimum": 0 }),
+        "int32" | "int64" | "sint32" | "sint64" => json!({ "type": "integer" }),
+        "double" | "float" => json!({ "type": "number" }),
+        "bool" => json!({ "type": "boolean" }),
+        _ => json!({ "type": "string" }),
+    }
+}
+
+/// Check `current` against the `published` schemas: every published version
+/// must still be registered and readable by its consumers
+pub fn check_registry(published: &[EventSchema], current: &[EventSchema]) -> Result<()> {
+    let mut seen = HashSet::new();
+    for schema in current {
+        if !seen.insert((&schema.event_type, schema.version)) {
+            bail!("{} v{} is registered twice", schema.event_type, schema.version);
+        }
+        let mut tags = HashSet::new();
+        if !schema.fields.iter().all(|field| tags.insert(field.tag)) {
+            bail!("{} v{} uses a field tag twice", schema.event_type, schema.version);
+        }
+    }
+
+    for old in published {
+        let registered = current.iter().find(|now| now.event_type == old.event_type && now.version == old.version);
+        let Some(now) = registered else {
+            bail!("{} v{} was published but is no longer registered", old.event_type, old.version);
+        };
+        let problems = old.incompatibilities(now);
+        if !problems.is_empty() {
+            bail!(
+                "{} v{} changed incompatibly ({}); register a new version instead",
+                old.event_type,
+                old.version,
+                problems.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `.proto` file declaring every registered payload
+pub fn registry_proto() -> String {
+    let mut out = String::from("syntax = \"proto3\";\n\npackage chain.events;\n");
+    for schema in registry() {
+        out.push('\n');
+        out.push_str(&schema.proto_message());
+    }
+    out
+}
+
+macro_rules! event_payloads {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident = ($event_type:expr, $version:tt) {
+            $($(#[$field_meta:meta])* $tag:literal => $field:ident: $ty:ty as $kind:ident),* $(,)?
+        }
+    )*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Clone, PartialEq, Serialize, Deserialize, prost::Message)]
+            #[serde(default)]
+            pub struct $name {
+                $(
+                    $(#[$field_meta])*
+                    #[prost($kind, tag = $tag)]
+                    pub $field: $ty,
+                )*
+            }
+
+            impl EventPayload for $name {
+                const EVENT_TYPE: &'static str = $event_type;
+                const VERSION: u32 = $version;
+
+                fn schema() -> EventSchema {
+                    EventSchema {
+                        event_type: $event_type.to_string(),
+                        version: $version,
+                        message: stringify!($name).to_string(),
+                        fields: vec![$(FieldSchema {
+                            name: stringify!($field).to_string(),
+                            tag: $tag,
+                            kind: stringify!($kind).to_string(),
+                        }),*],
+                    }
+                }
+            }
+        )*
+
+        /// Schemas of every registered payload version
+        pub fn registry() -> Vec<EventSchema> {
+            vec![$($name::schema()),*]
+        }
+    };
+}
+
+event_payloads! {
+    /// Published after a block and its indexes are written
+    BlockStoredV1 = ("block_stored", 1) {
+        1 => height: u64 as uint64,
+        /// `0x`-prefixed hex block hash
+        2 => hash: String as string,
+    }
+
+    /// Published after a block is removed by a rollback
+    BlockRolledBackV1 = ("block_rolled_back", 1) {
+        1 => height: u64 as uint64,
+        /// `0x`-prefixed hex block hash
+        2 => hash: String as string,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECORD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/schemas/events.json");
+
+    /// Fails when a registered payload changed incompatibly without a version
+    /// bump. After a compatible change, rerun with `UPDATE_EVENT_SCHEMAS=1` to
+    /// record it.
+    #[test]
+    fn test_registry_matches_published_schemas() {
+        let current = registry();
+        let published: Vec<EventSchema> = serde_json::from_str(&std::fs::read_to_string(RECORD_PATH).unwrap()).unwrap();
+        check_registry(&published, &current).unwrap();
+        if std::env::var_os("UPDATE_EVENT_SCHEMAS").is_some() {
+            std::fs::write(RECORD_PATH, serde_json::to_string_pretty(&current).unwrap() + "\n").unwrap();
+        } else {
+            assert_eq!(published, current, "schemas/events.json is out of date; rerun with UPDATE_EVENT_SCHEMAS=1");
+        }
+
+        // Dropping a field or retyping one must not pass as the same version
+        let mut removed = published[0].clone();
+        removed.fields.pop();
+        assert!(check_registry(&published, &[removed, published[1].clone()]).is_err());
+        let mut retyped = published[0].clone();
+        retyped.fields[0].kind = "string".to_string();
+        assert!(!published[0].incompatibilities(&retyped).is_empty());
+        let mut extended = published[0].clone();
+        extended.fields.push(FieldSchema { name: "proposer".to_string(), tag: 3, kind: "string".to_string() });
+        assert!(published[0].incompatibilities(&extended).is_empty());
+    }
+
+    #[test]
+    fn test_payload_encodings() {
+        let payload = BlockStoredV1 { height: 42, hash: "0xab".to_string() };
+        assert_eq!(payload.to_json().unwrap(), r#"{"height":42,"hash":"0xab"}"#);
+        assert_eq!(BlockStoredV1::from_proto(&payload.to_proto()).unwrap(), payload);
+        // Fields added in a later release read as defaults from older payloads
+        assert_eq!(BlockStoredV1::from_json(r#"{"height":42}"#).unwrap().hash, "");
+        assert_eq!(BlockStoredV1::wire_type(), "block_stored");
+        assert_eq!(wire_type("block_stored", 2), "block_stored.v2");
+
+        let schema = BlockStoredV1::schema().json_schema();
+        assert_eq!(schema["properties"]["height"]["type"], "integer");
+        assert!(schema.get("required").is_none());
+        assert!(registry_proto().contains("message BlockRolledBackV1 {\n  uint64 height = 1;\n  string hash = 2;\n}"));
+    }
+}
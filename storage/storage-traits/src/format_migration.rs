@@ -0,0 +1,38 @@
+// storage/storage-traits/src/format_migration.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::BlockHeight;
+use serde::{Deserialize, Serialize};
+
+/// Where the rewrite of stored blobs into the current format stands
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatMigrationProgress {
+    /// Blocks below this height are in the current format
+    pub next_height: BlockHeight,
+    /// Chain height the migration is working towards
+    pub target_height: BlockHeight,
+    /// Rows read by the last pass
+    pub rows_scanned: u64,
+    /// Rows the last pass rewrote because they were in an older format
+    pub rows_rewritten: u64,
+    /// Every stored block up to `target_height` has been migrated
+    pub completed: bool,
+}
+
+impl FormatMigrationProgress {
+    /// Share of the chain already migrated, from 0.0 to 1.0
+    pub fn fraction_done(&self) -> f64 {
+        if self.completed {
+            return 1.0;
+        }
+        self.next_height as f64 / (self.target_height + 1) as f64
+    }
+}
+
+/// Background rewrite of rows written by older releases
+#[async_trait]
+pub trait FormatMigration: Send + Sync {
+    /// Migrate the rows of up to `max_blocks` blocks, resuming where the
+    /// previous pass stopped
+    async fn migrate_formats(&self, max_blocks: u64) -> Result<FormatMigrationProgress>;
+}
@@ -1,5 +1,233 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
+// storage/storage-traits/src/lib.rs
+pub mod blockchain_storage;
+pub mod connected_peers;
+pub mod connection;
+pub mod dual_write;
+pub mod event_log;
+pub mod event_schema;
+pub mod format_migration;
+pub mod peer_store;
+pub mod relayer_backlog;
+pub mod relayer_queue;
+pub mod tx_lifecycle;
+pub mod validation_queue;
+pub mod validator_stats;
+
+pub use blockchain_storage::{AccountModel, BlockchainStorage};
+pub use connected_peers::ConnectedPeers;
+pub use connection::{ConnectionHealth, ConnectionState, ConnectionSupervisor};
+pub use dual_write::{DualWriteConfig, DualWriteStatus, DualWriteStorage};
+pub use event_log::{ChainEvent, EventFilter, EventLog, EventPage};
+pub use event_schema::{BlockRolledBackV1, BlockStoredV1, EventPayload, EventSchema};
+pub use format_migration::{FormatMigration, FormatMigrationProgress};
+pub use peer_store::{KnownPeer, PeerBan, PeerStore};
+pub use relayer_backlog::RelayerBacklog;
+pub use relayer_queue::{
+    ClaimStore, CommitmentData, PayloadEncoding, PendingTxPage, RelaySubmission, RelayerBatch, RelayerRecoveryStore,
+    RelayerStatus, RelayerStore, RetryStore, SubmissionLog, TargetInclusion,
+};
+pub use tx_lifecycle::{LifecycleEntry, LifecycleLookup, LifecycleStage, TransactionLifecycle};
+pub use validation_queue::{
+    BalanceChange, FailedTransaction, GasEstimate, ValidationBatch, ValidationRecoveryStore, ValidationResult,
+    ValidationStatus, ValidationStore,
+};
+pub use validator_stats::{ValidatorStats, ValidatorStatsLookup, ValidatorStatsPeriod};
+
+/// How a storage operation touches the database.
+///
+/// Backends with separate read replicas use this to route operations:
+/// only `ReplicaRead` operations may be served by a lagging replica.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessMode {
+    /// Mutates state; must go to the primary write path
+    Write,
+    /// Read whose result feeds consensus or state transitions; must see the latest writes
+    ConsistentRead,
+    /// Explorer-style read that tolerates replica lag
+    ReplicaRead,
+}
+
+/// Operations exposed by blockchain storage backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorageOperation {
+    StoreBlock,
+    GetBlockByHeight,
+    GetBlockByHash,
+    GetBlockHeaders,
+    StoreTransaction,
+    GetTransaction,
+    AddPendingTransaction,
+    RemovePendingTransaction,
+    GetPendingTransactions,
+    UpdateAccount,
+    GetAccount,
+    GetAddressTransactions,
+    GetLatestBlockHeight,
+    GetChainStats,
+    StoreContractCode,
+    MarkContractDestroyed,
+    CollectContractGarbage,
+    GetContractCode,
+    TrainArchiveDictionary,
+    ArchiveTransactions,
+    RecordIntent,
+    RecoverIntents,
+    VerifySchema,
+    GetMempoolUsage,
+    StorePeerRecord,
+    GetPeerRecord,
+    StoreReceipts,
+    GetReceipt,
+    RollUpChainStats,
+    GetChainStatsRange,
+    DetectAnomalies,
+    GetAnomalies,
+    RollbackBlocks,
+    PublishEvent,
+    ReplayEvents,
+    RecordDryRun,
+    GetDryRuns,
+    StoreCheckpoint,
+    GetCheckpoint,
+    CommitRelayerBatch,
+    GetRelayerQueueDepth,
+    UpdateRelayerStatus,
+    GetQueuedRelayerBatches,
+    MigrateBlobFormat,
+    ClaimRelayerBatches,
+    GetFailedRelayerBatches,
+    GetRelayerBatch,
+    UpdateValidationStatus,
+    GetPendingValidationBatches,
+    GetValidationBatch,
+    StoreNetworkPeer,
+    GetNetworkPeers,
+    GetNetworkPeer,
+    StoreValidationBatch,
+    StoreRelayerBatch,
+    GetTransactionLifecycle,
+    RecordValidatorActivity,
+    GetValidatorStats,
+    BanNetworkPeer,
+    GetPeerBans,
+    DeadLetterRelayerBatch,
+    RenewRelayerClaim,
+    ReleaseRelayerClaim,
+    ClaimValidationBatches,
+    GetPendingTransaction,
+    GetChainId,
+    RecoverValidationBatches,
+    RecoverRelayerBatches,
+    GetTableSizes,
+    GetBatchLineage,
+    GetValidationLineage,
+    CollectPayloadBlobs,
+}
+
+impl StorageOperation {
+    /// Access mode used to route this operation
+    pub const fn access_mode(self) -> AccessMode {
+        match self {
+            StorageOperation::StoreBlock
+            | StorageOperation::StoreTransaction
+            | StorageOperation::AddPendingTransaction
+            | StorageOperation::RemovePendingTransaction
+            | StorageOperation::UpdateAccount
+            | StorageOperation::StoreContractCode
+            | StorageOperation::MarkContractDestroyed
+            | StorageOperation::CollectContractGarbage
+            | StorageOperation::TrainArchiveDictionary
+            | StorageOperation::ArchiveTransactions
+            | StorageOperation::RecordIntent
+            | StorageOperation::RecoverIntents
+            | StorageOperation::StorePeerRecord
+            | StorageOperation::StoreReceipts
+            | StorageOperation::RollUpChainStats
+            | StorageOperation::DetectAnomalies
+            | StorageOperation::RollbackBlocks
+            | StorageOperation::PublishEvent
+            | StorageOperation::RecordDryRun
+            | StorageOperation::StoreCheckpoint
+            | StorageOperation::CommitRelayerBatch
+            | StorageOperation::UpdateRelayerStatus
+            | StorageOperation::MigrateBlobFormat
+            | StorageOperation::ClaimRelayerBatches
+            | StorageOperation::UpdateValidationStatus
+            | StorageOperation::StoreNetworkPeer
+            | StorageOperation::StoreValidationBatch
+            | StorageOperation::StoreRelayerBatch
+            | StorageOperation::RecordValidatorActivity
+            | StorageOperation::BanNetworkPeer
+            | StorageOperation::DeadLetterRelayerBatch
+            | StorageOperation::RenewRelayerClaim
+            | StorageOperation::ReleaseRelayerClaim
+            | StorageOperation::ClaimValidationBatches
+            | StorageOperation::RecoverValidationBatches
+            | StorageOperation::RecoverRelayerBatches
+            | StorageOperation::CollectPayloadBlobs => AccessMode::Write,
+
+            // Batch building, nonce checks and head tracking must not see stale data
+            StorageOperation::GetPendingTransactions
+            | StorageOperation::GetAccount
+            | StorageOperation::GetLatestBlockHeight
+            | StorageOperation::VerifySchema
+            // A lagging replica could hide an event the cursor then skips
+            | StorageOperation::ReplayEvents
+            // Restoring an older checkpoint than the one finalized would reopen reorgs
+            | StorageOperation::GetCheckpoint
+            // Read back to bump the connection count it then rewrites
+            | StorageOperation::GetNetworkPeer
+            // The engine skips transactions already queued; a stale read would batch them twice
+            | StorageOperation::GetQueuedRelayerBatches
+            // Retrying off a stale read would resend a batch another relayer already retried
+            | StorageOperation::GetFailedRelayerBatches
+            // Validators pick work from this; a stale read hands out finished batches again
+            | StorageOperation::GetPendingValidationBatches
+            // A transaction missing on a lagging replica would fail validation it should pass
+            | StorageOperation::GetPendingTransaction
+            // A relayer batch queued off a stale read would start a new lineage
+            | StorageOperation::GetValidationLineage => AccessMode::ConsistentRead,
+
+            StorageOperation::GetBlockByHeight
+            | StorageOperation::GetBlockByHash
+            | StorageOperation::GetBlockHeaders
+            | StorageOperation::GetTransaction
+            | StorageOperation::GetAddressTransactions
+            | StorageOperation::GetChainStats
+            | StorageOperation::GetMempoolUsage
+            | StorageOperation::GetPeerRecord
+            | StorageOperation::GetReceipt
+            | StorageOperation::GetChainStatsRange
+            | StorageOperation::GetAnomalies
+            | StorageOperation::GetDryRuns
+            // Code is content-addressed, so a replica either has the right bytes or none
+            | StorageOperation::GetContractCode
+            // Back-pressure reacts to a trend; a slightly stale count is fine
+            | StorageOperation::GetRelayerQueueDepth
+            // Discovery re-verifies restored peers by contacting them
+            | StorageOperation::GetNetworkPeers
+            // A ban missing on a lagging replica only readmits the peer until it misbehaves again
+            | StorageOperation::GetPeerBans
+            // Support lookups; a stage missing for a moment is refreshed by asking again
+            | StorageOperation::GetTransactionLifecycle
+            // Dashboard reads of past slots
+            | StorageOperation::GetValidatorStats
+            // Status lookups of a single batch
+            | StorageOperation::GetRelayerBatch
+            | StorageOperation::GetValidationBatch
+            // Written once when the keyspace is created
+            | StorageOperation::GetChainId
+            // Estimates for capacity planning, approximate on any node
+            | StorageOperation::GetTableSizes
+            // Tracing a batch after the fact, like the lifecycle lookup
+            | StorageOperation::GetBatchLineage => AccessMode::ReplicaRead,
+        }
+    }
+
+    /// Whether this operation may be served by a read replica
+    pub const fn is_replica_safe(self) -> bool {
+        matches!(self.access_mode(), AccessMode::ReplicaRead)
+    }
 }
 
 #[cfg(test)]
@@ -7,8 +235,19 @@ mod tests {
     use super::*;
 
     #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    fn test_writes_never_replica_safe() {
+        assert_eq!(StorageOperation::StoreBlock.access_mode(), AccessMode::Write);
+        assert!(!StorageOperation::StoreBlock.is_replica_safe());
+        assert!(!StorageOperation::UpdateAccount.is_replica_safe());
+    }
+
+    #[test]
+    fn test_read_routing() {
+        assert!(StorageOperation::GetAddressTransactions.is_replica_safe());
+        assert!(!StorageOperation::GetAccount.is_replica_safe());
+        assert_eq!(
+            StorageOperation::GetLatestBlockHeight.access_mode(),
+            AccessMode::ConsistentRead
+        );
     }
 }
@@ -0,0 +1,43 @@
+// storage/storage-traits/src/peer_store.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use std::net::SocketAddr;
+
+/// Peer found through discovery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownPeer {
+    pub peer_id: String,
+    pub addr: SocketAddr,
+    /// From the peer's signed record; `None` for bootnodes not yet heard from
+    pub protocol_version: Option<u32>,
+    /// Unix seconds
+    pub last_seen: i64,
+}
+
+/// Ban issued by peer scoring
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerBan {
+    pub peer_id: String,
+    /// Unix seconds
+    pub until: i64,
+    pub reason: String,
+}
+
+/// Where discovery keeps the peers it found, so a restarted node does not
+/// start over from its bootnodes
+#[async_trait]
+pub trait PeerStore: Send + Sync {
+    async fn save_known_peers(&self, peers: &[KnownPeer]) -> Result<()>;
+
+    /// Mark `peer` connected after an accepted handshake, at the height it reported
+    async fn record_connected_peer(&self, peer: &KnownPeer, chain_height: u64) -> Result<()>;
+
+    /// Up to `limit` stored peers that are not banned, in no particular order
+    async fn load_known_peers(&self, limit: usize) -> Result<Vec<KnownPeer>>;
+
+    /// Keep `ban` until it expires; later peer updates leave it in place
+    async fn record_peer_ban(&self, ban: &PeerBan) -> Result<()>;
+
+    /// Bans still in force at `now` (Unix seconds)
+    async fn load_peer_bans(&self, now: i64) -> Result<Vec<PeerBan>>;
+}
@@ -0,0 +1,10 @@
+// storage/storage-traits/src/relayer_backlog.rs
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Read side of the relayer queue, for services that react to its depth
+#[async_trait]
+pub trait RelayerBacklog: Send + Sync {
+    /// Batches queued for the relayer and not yet picked up
+    async fn relayer_queue_depth(&self) -> Result<u64>;
+}
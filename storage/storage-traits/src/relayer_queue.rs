@@ -0,0 +1,286 @@
+// storage/storage-traits/src/relayer_queue.rs
+//! Relayer batches and the storage the relayer works them through.
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::{BlockHash, BlockHeight, Transaction, TxHash};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Relayer batch model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayerBatch {
+    pub commitment_id: Uuid,
+    pub batch_timestamp: DateTime<Utc>,
+    pub tx_hashes: Vec<TxHash>,
+    pub status: RelayerStatus,
+    pub relayer_id: String,
+    pub retry_count: u32,
+    pub last_attempt: Option<DateTime<Utc>>,
+    pub target_block_height: Option<BlockHeight>,
+    pub commitment_data: Option<CommitmentData>,
+    /// Where the commitment landed on the target, once confirmed
+    pub target_inclusion: Option<TargetInclusion>,
+    /// Times the batch was put back after its relayer stalled
+    pub recovery_count: u32,
+    /// Shared with the validation batch its transactions came through
+    pub batch_lineage_id: Uuid,
+}
+
+/// Relayer status enum
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RelayerStatus {
+    Queued,
+    Processing,
+    Committed,
+    Failed,
+    Cancelled,
+}
+
+impl std::fmt::Display for RelayerStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelayerStatus::Queued => write!(f, "queued"),
+            RelayerStatus::Processing => write!(f, "processing"),
+            RelayerStatus::Committed => write!(f, "committed"),
+            RelayerStatus::Failed => write!(f, "failed"),
+            RelayerStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::str::FromStr for RelayerStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "queued" => Ok(RelayerStatus::Queued),
+            "processing" => Ok(RelayerStatus::Processing),
+            "committed" => Ok(RelayerStatus::Committed),
+            "failed" => Ok(RelayerStatus::Failed),
+            "cancelled" => Ok(RelayerStatus::Cancelled),
+            _ => Err(format!("Invalid relayer status: {}", s)),
+        }
+    }
+}
+
+/// Commitment data for relayer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitmentData {
+    pub merkle_root: BlockHash,
+    pub transaction_count: u32,
+    pub total_gas_used: u64,
+    pub total_fees: u64,
+    pub batch_hash: BlockHash,
+    pub proof_data: Vec<u8>, // Cryptographic proof
+    /// Codec `payload` was written with; verifiers decode with the same one
+    pub payload_encoding: PayloadEncoding,
+    /// The batch's transactions as relayed to the target or DA layer
+    pub payload: Vec<u8>,
+}
+
+/// Encoding of a commitment's transaction payload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadEncoding {
+    /// Bincode transactions
+    #[default]
+    Raw,
+    /// Raw, zstd-compressed
+    Zstd,
+    /// Senders stored once and referenced by index, nonces and timestamps
+    /// as deltas, hashes recomputed on decode
+    SenderDelta,
+    /// Sender delta, zstd-compressed
+    SenderDeltaZstd,
+}
+
+impl std::fmt::Display for PayloadEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PayloadEncoding::Raw => write!(f, "raw"),
+            PayloadEncoding::Zstd => write!(f, "zstd"),
+            PayloadEncoding::SenderDelta => write!(f, "sender_delta"),
+            PayloadEncoding::SenderDeltaZstd => write!(f, "sender_delta_zstd"),
+        }
+    }
+}
+
+impl std::str::FromStr for PayloadEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(PayloadEncoding::Raw),
+            "zstd" => Ok(PayloadEncoding::Zstd),
+            "sender_delta" => Ok(PayloadEncoding::SenderDelta),
+            "sender_delta_zstd" => Ok(PayloadEncoding::SenderDeltaZstd),
+            _ => Err(format!("Unknown payload encoding: {}", s)),
+        }
+    }
+}
+
+/// Target-chain block holding a submitted commitment transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetInclusion {
+    /// Transaction id as the target reports it
+    pub tx_id: String,
+    pub block_height: u64,
+    pub block_hash: String,
+}
+
+/// Commitment transaction prepared for a relay target, sent or recorded by a dry run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelaySubmission {
+    pub commitment_id: Uuid,
+    /// Name of the relay target it was prepared for
+    pub target: String,
+    pub batch_hash: BlockHash,
+    /// Encoded contract call carrying the commitment
+    pub calldata: Vec<u8>,
+    pub estimated_gas: u64,
+    pub prepared_at: DateTime<Utc>,
+}
+
+impl RelayerBatch {
+    pub fn new(tx_hashes: Vec<TxHash>, relayer_id: String) -> Self {
+        Self {
+            commitment_id: Uuid::new_v4(),
+            batch_timestamp: Utc::now(),
+            tx_hashes,
+            status: RelayerStatus::Queued,
+            relayer_id,
+            retry_count: 0,
+            last_attempt: None,
+            target_block_height: None,
+            commitment_data: None,
+            target_inclusion: None,
+            recovery_count: 0,
+            batch_lineage_id: Uuid::new_v4(),
+        }
+    }
+
+    /// Continue the lineage of an earlier batch of the same transactions
+    pub fn with_lineage(mut self, batch_lineage_id: Uuid) -> Self {
+        self.batch_lineage_id = batch_lineage_id;
+        self
+    }
+
+    /// Take a queued batch for submission by `relayer_id`
+    pub fn claim(&mut self, relayer_id: &str, at: DateTime<Utc>) {
+        self.status = RelayerStatus::Processing;
+        self.relayer_id = relayer_id.to_string();
+        self.last_attempt = Some(at);
+    }
+
+    pub fn start_processing(&mut self, target_block_height: BlockHeight) {
+        self.status = RelayerStatus::Processing;
+        self.last_attempt = Some(Utc::now());
+        self.target_block_height = Some(target_block_height);
+    }
+
+    /// Record the commitment as final on the target at `inclusion`
+    pub fn mark_committed(&mut self, commitment_data: CommitmentData, inclusion: TargetInclusion) {
+        self.status = RelayerStatus::Committed;
+        self.target_block_height = Some(inclusion.block_height);
+        self.commitment_data = Some(commitment_data);
+        self.target_inclusion = Some(inclusion);
+    }
+
+    pub fn mark_failed(&mut self) {
+        self.status = RelayerStatus::Failed;
+        self.retry_count += 1;
+        self.last_attempt = Some(Utc::now());
+    }
+
+    pub fn can_retry(&self, max_retries: u32) -> bool {
+        self.retry_count < max_retries && self.status == RelayerStatus::Failed
+    }
+
+    /// Put a failed batch back in the queue, keeping its retry count
+    pub fn requeue(&mut self) {
+        self.status = RelayerStatus::Queued;
+    }
+
+    /// Return a batch whose relayer stalled to the queue; the stall is not a
+    /// failed attempt, so the retry count is kept
+    pub fn recover(&mut self) {
+        self.status = RelayerStatus::Queued;
+        self.recovery_count += 1;
+    }
+}
+
+/// One page of pending transactions
+#[derive(Debug, Clone)]
+pub struct PendingTxPage {
+    pub transactions: Vec<Transaction>,
+    /// Opaque cursor for the next page; `None` once the pool is exhausted
+    pub paging_state: Option<Vec<u8>>,
+}
+
+/// What the relayer engine needs from storage
+#[async_trait]
+pub trait RelayerStore: Send + Sync {
+    /// One page of pending transactions, in no particular order; pass the
+    /// returned paging state back in for the next page
+    async fn pending_page(&self, page_size: i32, paging_state: Option<Vec<u8>>) -> Result<PendingTxPage>;
+
+    /// Batches written but not yet picked up for submission
+    async fn queued_batches(&self, limit: i32) -> Result<Vec<RelayerBatch>>;
+
+    /// Write a new batch to the relayer queue
+    async fn enqueue_batch(&self, batch: &RelayerBatch) -> Result<()>;
+
+    async fn remove_pending_transaction(&self, tx_hash: &TxHash) -> Result<()>;
+
+    /// Lineage of the validation batch `tx_hash` went through, if any
+    async fn validation_lineage(&self, tx_hash: &TxHash) -> Result<Option<Uuid>>;
+}
+
+/// What the relayer's retry worker needs from storage
+#[async_trait]
+pub trait RetryStore: Send + Sync {
+    /// Failed batches retried fewer than `max_retries` times
+    async fn retryable_batches(&self, max_retries: u32, limit: i32) -> Result<Vec<RelayerBatch>>;
+
+    /// Failed batches that have used up their retries
+    async fn exhausted_batches(&self, max_retries: u32, limit: i32) -> Result<Vec<RelayerBatch>>;
+
+    /// Persist the batch's status, retry count and last attempt
+    async fn update_batch(&self, batch: &RelayerBatch) -> Result<()>;
+
+    /// Move the batch from the queue to the dead-letter table
+    async fn dead_letter(&self, batch: &RelayerBatch, at: DateTime<Utc>) -> Result<()>;
+}
+
+/// What relayers sharing a keyspace need to split batches between them
+#[async_trait]
+pub trait ClaimStore: Send + Sync {
+    /// Take up to `limit` queued batches for `relayer_id`, each leased to it
+    /// for `lease`; batches another relayer holds are skipped
+    async fn claim_batches(&self, relayer_id: &str, limit: i32, lease: Duration) -> Result<Vec<RelayerBatch>>;
+
+    /// Extend a claim `relayer_id` still holds; `false` if it lost it
+    async fn renew_claim(&self, commitment_id: Uuid, relayer_id: &str, lease: Duration) -> Result<bool>;
+
+    /// Give up a claim; `false` if `relayer_id` no longer held it
+    async fn release_claim(&self, commitment_id: Uuid, relayer_id: &str) -> Result<bool>;
+}
+
+/// What returning batches stranded by a crashed relayer needs from storage
+#[async_trait]
+pub trait RelayerRecoveryStore: Send + Sync {
+    /// Batches still `Processing` since an attempt before `stuck_since`
+    async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<RelayerBatch>>;
+
+    /// Persist a batch `recover` put back in the queue; `false`, leaving it
+    /// alone, while a relayer holds a claim on it or once it left `Processing`
+    async fn recover_batch(&self, batch: &RelayerBatch) -> Result<bool>;
+}
+
+/// Where submissions that were not sent are kept for inspection
+#[async_trait]
+pub trait SubmissionLog: Send + Sync {
+    async fn record(&self, submission: &RelaySubmission) -> Result<()>;
+}
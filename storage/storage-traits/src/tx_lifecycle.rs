@@ -0,0 +1,49 @@
+// storage/storage-traits/src/tx_lifecycle.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::TxHash;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Step on a transaction's path from submission to the relay target, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleStage {
+    Mempool,
+    Validation,
+    Block,
+    Relayer,
+    Commitment,
+    TargetConfirmation,
+}
+
+/// One point on a transaction's timeline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LifecycleEntry {
+    pub stage: LifecycleStage,
+    /// Stage-specific state, e.g. `pending`, `validated` or `committed`
+    pub status: String,
+    /// When the entry's state was reached, if recorded
+    pub at: Option<DateTime<Utc>>,
+    /// Batch id, block height or target transaction the entry refers to
+    pub reference: Option<String>,
+    /// Failure reason or other context
+    pub detail: Option<String>,
+}
+
+/// Everything known about where a transaction is
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionLifecycle {
+    pub tx_hash: TxHash,
+    /// Furthest stage reached
+    pub stage: LifecycleStage,
+    /// Entries ordered by stage, then by time within a stage
+    pub timeline: Vec<LifecycleEntry>,
+}
+
+/// Answers "where is this transaction?" across mempool, validation, chain and relayer
+#[async_trait]
+pub trait LifecycleLookup: Send + Sync {
+    /// Timeline of `tx_hash`, `None` when no stage has seen it
+    async fn tx_lifecycle(&self, tx_hash: &TxHash) -> Result<Option<TransactionLifecycle>>;
+}
@@ -0,0 +1,179 @@
+// storage/storage-traits/src/validation_queue.rs
+//! Validation batches and the storage validators work them through.
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::{AccountState, Address, Transaction, TxHash};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Validation batch model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationBatch {
+    pub queue_id: Uuid,
+    pub batch_timestamp: DateTime<Utc>,
+    pub tx_hashes: Vec<TxHash>,
+    pub validation_status: ValidationStatus,
+    pub validator_id: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub validation_result: Option<ValidationResult>,
+    /// Times the batch was put back after its validator stalled
+    pub recovery_count: u32,
+    /// Shared with the relayer batches that carry these transactions on
+    pub batch_lineage_id: Uuid,
+}
+
+/// Validation status enum
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ValidationStatus {
+    Pending,
+    Processing,
+    Validated,
+    Failed,
+    Rejected,
+}
+
+impl std::fmt::Display for ValidationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationStatus::Pending => write!(f, "pending"),
+            ValidationStatus::Processing => write!(f, "processing"),
+            ValidationStatus::Validated => write!(f, "validated"),
+            ValidationStatus::Failed => write!(f, "failed"),
+            ValidationStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+impl std::str::FromStr for ValidationStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(ValidationStatus::Pending),
+            "processing" => Ok(ValidationStatus::Processing),
+            "validated" => Ok(ValidationStatus::Validated),
+            "failed" => Ok(ValidationStatus::Failed),
+            "rejected" => Ok(ValidationStatus::Rejected),
+            _ => Err(format!("Invalid validation status: {}", s)),
+        }
+    }
+}
+
+/// Validation result details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationResult {
+    pub is_valid: bool,
+    pub validated_transactions: Vec<TxHash>,
+    pub failed_transactions: Vec<FailedTransaction>,
+    pub gas_estimates: Vec<GasEstimate>,
+    pub balance_changes: Vec<BalanceChange>,
+    pub validation_time_ms: u64,
+    pub error_message: Option<String>,
+}
+
+/// Failed transaction details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedTransaction {
+    pub tx_hash: TxHash,
+    pub error_code: String,
+    pub error_message: String,
+    pub suggested_gas_limit: Option<u64>,
+}
+
+/// Gas estimation for transactions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasEstimate {
+    pub tx_hash: TxHash,
+    pub estimated_gas: u64,
+    /// Gas the transaction needs plus a safety margin; 0 in results stored before it
+    #[serde(default)]
+    pub suggested_gas_limit: u64,
+    pub gas_price_suggestion: u64,
+    pub execution_time_estimate_ms: u64,
+}
+
+/// Balance change tracking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceChange {
+    pub address: Address,
+    pub old_balance: u64,
+    pub new_balance: u64,
+    pub old_nonce: u64,
+    pub new_nonce: u64,
+}
+
+impl ValidationBatch {
+    pub fn new(tx_hashes: Vec<TxHash>, validator_id: String) -> Self {
+        Self {
+            queue_id: Uuid::new_v4(),
+            batch_timestamp: Utc::now(),
+            tx_hashes,
+            validation_status: ValidationStatus::Pending,
+            validator_id,
+            started_at: None,
+            completed_at: None,
+            validation_result: None,
+            recovery_count: 0,
+            batch_lineage_id: Uuid::new_v4(),
+        }
+    }
+
+    pub fn start_processing(&mut self) {
+        self.validation_status = ValidationStatus::Processing;
+        self.started_at = Some(Utc::now());
+    }
+
+    /// Take a pending batch for validation by `validator_id`
+    pub fn claim(&mut self, validator_id: &str, at: DateTime<Utc>) {
+        self.validation_status = ValidationStatus::Processing;
+        self.validator_id = validator_id.to_string();
+        self.started_at = Some(at);
+    }
+
+    /// Return a batch whose validator stalled to the pending queue
+    pub fn recover(&mut self) {
+        self.validation_status = ValidationStatus::Pending;
+        self.started_at = None;
+        self.recovery_count += 1;
+    }
+
+    pub fn complete_validation(&mut self, result: ValidationResult) {
+        self.validation_status = if result.is_valid {
+            ValidationStatus::Validated
+        } else {
+            ValidationStatus::Failed
+        };
+        self.completed_at = Some(Utc::now());
+        self.validation_result = Some(result);
+    }
+}
+
+/// What the validation engine needs from storage
+#[async_trait]
+pub trait ValidationStore: Send + Sync {
+    /// Take up to `limit` pending batches for `validator_id`; batches another
+    /// validator claimed are skipped
+    async fn claim_batches(&self, validator_id: &str, limit: i32) -> Result<Vec<ValidationBatch>>;
+
+    /// A transaction still waiting in the mempool
+    async fn pending_transaction(&self, tx_hash: &TxHash) -> Result<Option<Transaction>>;
+
+    /// Stored balance and nonce; the default for an account never written
+    async fn account(&self, address: &Address) -> Result<AccountState>;
+
+    /// Persist the batch's status, timestamps and result
+    async fn update_batch(&self, batch: &ValidationBatch) -> Result<()>;
+}
+
+/// What returning batches stranded by a crashed validator needs from storage
+#[async_trait]
+pub trait ValidationRecoveryStore: Send + Sync {
+    /// Batches still `Processing` under claims started before `stuck_since`
+    async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<ValidationBatch>>;
+
+    /// Persist a batch `recover` put back in the queue, provided it is still
+    /// processing under the claim started at `claimed_at`; `false` otherwise
+    async fn recover_batch(&self, batch: &ValidationBatch, claimed_at: Option<DateTime<Utc>>) -> Result<bool>;
+}
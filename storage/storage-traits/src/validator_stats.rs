@@ -0,0 +1,57 @@
+// storage/storage-traits/src/validator_stats.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use blockchain_core::Address;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A validator's block production and checkpoint attestations over one period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorStatsPeriod {
+    pub period_start: DateTime<Utc>,
+    /// Exclusive
+    pub period_end: DateTime<Utc>,
+    pub proposed_blocks: u64,
+    /// Assigned slots that passed without a block
+    pub missed_blocks: u64,
+    /// Mean time from slot start until the block was received, in milliseconds
+    pub avg_proposal_latency_ms: f64,
+    pub attestations_signed: u64,
+    /// Checkpoints the validator was expected to sign
+    pub attestations_expected: u64,
+}
+
+impl ValidatorStatsPeriod {
+    /// Share of assigned slots that produced a block, `None` without assigned slots
+    pub fn uptime(&self) -> Option<f64> {
+        let assigned = self.proposed_blocks + self.missed_blocks;
+        (assigned > 0).then(|| self.proposed_blocks as f64 / assigned as f64)
+    }
+
+    /// Share of expected checkpoint attestations that were signed
+    pub fn participation(&self) -> Option<f64> {
+        (self.attestations_expected > 0)
+            .then(|| self.attestations_signed as f64 / self.attestations_expected as f64)
+    }
+}
+
+/// Performance of one validator over a requested range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorStats {
+    /// The whole range combined
+    pub total: ValidatorStatsPeriod,
+    /// Hourly, daily or weekly periods depending on the range, oldest first
+    pub periods: Vec<ValidatorStatsPeriod>,
+}
+
+/// Per-validator uptime and participation for operators and dashboards
+#[async_trait]
+pub trait ValidatorStatsLookup: Send + Sync {
+    /// Stats of `validator` for `from..=to`; periods without activity are omitted
+    async fn validator_stats(
+        &self,
+        validator: &Address,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<ValidatorStats>;
+}
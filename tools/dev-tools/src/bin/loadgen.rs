@@ -0,0 +1,20 @@
+// tools/dev-tools/src/bin/loadgen.rs
+use clap::Parser;
+use dev_tools::loadgen::{self, LoadgenConfig};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let config = LoadgenConfig::parse();
+    println!(
+        "Generating {} tps for {}s against {}",
+        config.tps, config.duration_secs, config.rpc_url
+    );
+
+    let report = loadgen::run(config).await?;
+    print!("{}", report);
+
+    if report.nonces_confirmed_twice > 0 {
+        anyhow::bail!("{} sender nonces had two transactions confirmed", report.nonces_confirmed_twice);
+    }
+    Ok(())
+}
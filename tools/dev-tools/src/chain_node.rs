@@ -0,0 +1,357 @@
+// tools/dev-tools/src/chain_node.rs
+//! A blockchain node assembled for `Simulation`.
+//!
+//! `ChainNode` runs the node's consensus path on the simulated transport:
+//! blocks go through `blockchain_core::Chain` for validation, fork choice,
+//! orphan buffering and ledger execution, and the chain reads the
+//! simulation's `MockClock`. Pending transactions wait in a nonce-ordered
+//! in-memory pool and blocks live in the chain's in-memory block tree, so the
+//! Scylla-backed mempool and storage are not part of a simulated node; their
+//! behaviour is covered by the storage crates' own tests.
+//!
+//! Block production rotates by height: the node at `height % node_count`
+//! produces block `height` once its production timer fires with
+//! transactions pending. A block whose parent is unknown is buffered and the
+//! missing parent is requested from the sender.
+use crate::sim::{NodeIndex, SimContext, SimNode};
+use anyhow::{Context, Result};
+use blockchain_core::{
+    Address, AddressExt, Block, BlockHash, Chain, ChainSpec, ChainUpdate, Clock, KeyPair, Ledger, MockClock, Nonce,
+    SignatureScheme, Transaction, TxHash,
+};
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Simulated time between a node's production attempts
+pub const DEFAULT_BLOCK_INTERVAL_MS: u64 = 1_000;
+
+/// Gas limit of the transfers a node submits
+const TRANSFER_GAS: u64 = 21_000;
+
+/// What simulated nodes send each other
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChainMessage {
+    Transaction(Transaction),
+    Block(Block),
+    /// Ask for a block the sender is missing
+    GetBlock(BlockHash),
+}
+
+impl ChainMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("chain messages always serialize")
+    }
+
+    pub fn decode(payload: &[u8]) -> Result<Self> {
+        serde_json::from_slice(payload).context("Malformed chain message")
+    }
+}
+
+pub struct ChainNode {
+    key: KeyPair,
+    address: Address,
+    chain: Chain,
+    /// Pending transactions by sender and nonce, so a sender's transactions
+    /// are taken in order
+    mempool: BTreeMap<(Address, Nonce), Transaction>,
+    seen: HashSet<TxHash>,
+    block_interval_ms: u64,
+    /// Transfers to submit at start
+    transfers: usize,
+    timer_set: bool,
+    /// Real instant paired with the simulated time the node started at, so
+    /// orphan expiry follows simulated time
+    epoch: (Instant, DateTime<Utc>),
+    /// Messages that failed to decode or apply, kept for assertions
+    rejected: Vec<String>,
+}
+
+impl ChainNode {
+    /// Node signing with `key` on a chain starting at `genesis`; every node
+    /// of a simulation must get the same spec, genesis and allocations
+    pub fn new(key: KeyPair, spec: ChainSpec, genesis: Block, allocations: Ledger, clock: &MockClock) -> Result<Self> {
+        let address = Address::from_public_key(&key.public_key())?;
+        let chain = Chain::new(spec, genesis, allocations)?.with_clock(Arc::new(clock.clone()));
+        Ok(Self {
+            key,
+            address,
+            chain,
+            mempool: BTreeMap::new(),
+            seen: HashSet::new(),
+            block_interval_ms: DEFAULT_BLOCK_INTERVAL_MS,
+            transfers: 0,
+            timer_set: false,
+            epoch: (Instant::now(), clock.now()),
+            rejected: Vec::new(),
+        })
+    }
+
+    pub fn with_block_interval_ms(mut self, block_interval_ms: u64) -> Self {
+        self.block_interval_ms = block_interval_ms.max(1);
+        self
+    }
+
+    /// Sign and gossip `count` transfers from this node's account at start
+    pub fn with_transfers(mut self, count: usize) -> Self {
+        self.transfers = count;
+        self
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    pub fn mempool_len(&self) -> usize {
+        self.mempool.len()
+    }
+
+    pub fn rejected(&self) -> &[String] {
+        &self.rejected
+    }
+
+    /// Admit a transaction to the mempool; `false` if it was already known
+    fn admit(&mut self, tx: Transaction) -> Result<bool> {
+        if !self.seen.insert(tx.hash) {
+            return Ok(false);
+        }
+        tx.validate_structure()?;
+        self.chain.spec().params.validate_transaction(&tx)?;
+        tx.verify_sender(SignatureScheme::Ed25519)?;
+
+        let sender = tx.sender();
+        if tx.nonce >= self.chain.ledger().account(&sender).nonce {
+            self.mempool.insert((sender, tx.nonce), tx);
+        }
+        Ok(true)
+    }
+
+    /// Pending transactions that apply on top of the tip, each sender's in
+    /// nonce order, within the block gas limit
+    fn select_transactions(&self) -> Vec<Transaction> {
+        let ledger = self.chain.ledger();
+        let gas_limit = self.chain.spec().params.block_gas_limit;
+        let mut next_nonce: HashMap<Address, Nonce> = HashMap::new();
+        let mut gas = 0u64;
+        let mut selected = Vec::new();
+
+        for ((sender, nonce), tx) in &self.mempool {
+            let expected = next_nonce.entry(*sender).or_insert_with(|| ledger.account(sender).nonce);
+            if *nonce != *expected || gas.saturating_add(tx.gas_limit) > gas_limit {
+                continue;
+            }
+            *expected += 1;
+            gas += tx.gas_limit;
+            selected.push(tx.clone());
+        }
+        selected
+    }
+
+    fn is_producer(&self, ctx: &SimContext<'_>) -> bool {
+        (self.chain.height() + 1) % ctx.node_count() as u64 == ctx.node() as u64
+    }
+
+    fn produce(&mut self, ctx: &mut SimContext<'_>) -> Result<()> {
+        let transactions = self.select_transactions();
+        if transactions.is_empty() {
+            return Ok(());
+        }
+
+        // Stamped with simulated time, so a replay builds the same block; the
+        // chain rejects a block no later than its parent
+        let tip = self.chain.tip().clone();
+        let height = tip.header.height + 1;
+        let timestamp = ctx.now().max(tip.header.timestamp + Duration::milliseconds(1));
+        let coinbase = self.chain.spec().coinbase_for(self.address, height, &transactions)?;
+
+        let mut all = Vec::with_capacity(transactions.len() + 1);
+        all.push(coinbase.with_timestamp(timestamp)?);
+        all.extend(transactions);
+        let block = Block::new(height, tip.hash, all, tip.header.difficulty)?.with_timestamp(timestamp)?;
+
+        self.apply(block.clone(), ctx)?;
+        ctx.broadcast(&ChainMessage::Block(block).encode());
+        Ok(())
+    }
+
+    /// Offer `block` to the chain and bring the mempool in line with the
+    /// resulting main chain
+    fn apply(&mut self, block: Block, ctx: &mut SimContext<'_>) -> Result<Option<BlockHash>> {
+        let elapsed = (ctx.now() - self.epoch.1).to_std().unwrap_or_default();
+        let mut missing = None;
+
+        for update in self.chain.accept_block(block, self.epoch.0 + elapsed)? {
+            match update {
+                ChainUpdate::Orphaned { missing_parent } => missing = Some(missing_parent),
+                ChainUpdate::Reorged { rolled_back, .. } => {
+                    for tx in rolled_back.into_iter().flat_map(|block| block.transactions) {
+                        if !tx.is_coinbase() {
+                            self.mempool.insert((tx.sender(), tx.nonce), tx);
+                        }
+                    }
+                }
+                ChainUpdate::Duplicate | ChainUpdate::Extended(_) | ChainUpdate::SideChain => {}
+            }
+        }
+
+        let ledger = self.chain.ledger();
+        self.mempool.retain(|(sender, nonce), _| *nonce >= ledger.account(sender).nonce);
+        Ok(missing)
+    }
+
+    fn arm_timer(&mut self, ctx: &mut SimContext<'_>) {
+        if !self.timer_set && !self.mempool.is_empty() {
+            self.timer_set = true;
+            ctx.set_timer(self.block_interval_ms);
+        }
+    }
+
+    fn handle(&mut self, ctx: &mut SimContext<'_>, from: NodeIndex, payload: &[u8]) -> Result<()> {
+        match ChainMessage::decode(payload)? {
+            ChainMessage::Transaction(tx) => {
+                if self.admit(tx)? {
+                    ctx.broadcast(payload);
+                }
+            }
+            ChainMessage::Block(block) => {
+                let known = self.chain.get_block(&block.hash).is_some();
+                if let Some(missing) = self.apply(block, ctx)? {
+                    ctx.send(from, ChainMessage::GetBlock(missing).encode());
+                } else if !known {
+                    ctx.broadcast(payload);
+                }
+            }
+            ChainMessage::GetBlock(hash) => {
+                if let Some(block) = self.chain.get_block(&hash) {
+                    ctx.send(from, ChainMessage::Block(block.clone()).encode());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn submit_transfers(&mut self, ctx: &mut SimContext<'_>) -> Result<()> {
+        let chain_id = self.chain.spec().params.chain_id;
+        let first_nonce = self.chain.ledger().account(&self.address).nonce;
+
+        for nonce in first_nonce..first_nonce + self.transfers as Nonce {
+            let to: Address = ctx.rng().gen();
+            let amount = ctx.rng().gen_range(1..1_000);
+            let mut tx = Transaction::new_transfer(self.address, to, amount, nonce, TRANSFER_GAS, 1)?
+                .with_chain_id(chain_id)?
+                .with_timestamp(ctx.now())?;
+            tx.sign(&self.key);
+
+            let message = ChainMessage::Transaction(tx.clone()).encode();
+            self.admit(tx)?;
+            ctx.broadcast(&message);
+        }
+        Ok(())
+    }
+}
+
+impl SimNode for ChainNode {
+    fn on_start(&mut self, ctx: &mut SimContext<'_>) {
+        if let Err(e) = self.submit_transfers(ctx) {
+            self.rejected.push(e.to_string());
+        }
+        self.arm_timer(ctx);
+    }
+
+    fn on_message(&mut self, ctx: &mut SimContext<'_>, from: NodeIndex, payload: &[u8]) {
+        if let Err(e) = self.handle(ctx, from, payload) {
+            self.rejected.push(e.to_string());
+        }
+        self.arm_timer(ctx);
+    }
+
+    fn on_timer(&mut self, ctx: &mut SimContext<'_>) {
+        self.timer_set = false;
+        if self.is_producer(ctx) {
+            if let Err(e) = self.produce(ctx) {
+                self.rejected.push(e.to_string());
+            }
+        }
+        self.arm_timer(ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SimConfig, Simulation};
+    use blockchain_core::{ChainParams, EmissionSchedule, FeeDistribution};
+    use chrono::TimeZone;
+
+    const NODES: usize = 4;
+    const TRANSFERS: usize = 3;
+
+    fn spec() -> ChainSpec {
+        ChainSpec {
+            params: ChainParams::testnet(),
+            fees: FeeDistribution { burn_bps: 0, treasury_bps: 0, treasury: None, base_gas_price: None },
+            emission: EmissionSchedule::Fixed { reward: 50 },
+            ..ChainSpec::default()
+        }
+    }
+
+    fn run(seed: u64, drop_rate: f64) -> Simulation<ChainNode> {
+        let keys: Vec<KeyPair> = (0..NODES)
+            .map(|index| KeyPair::from_secret_bytes(SignatureScheme::Ed25519, &[index as u8 + 1; 32]).unwrap())
+            .collect();
+        let mut allocations = Ledger::new();
+        for key in &keys {
+            allocations.credit(&Address::from_public_key(&key.public_key()).unwrap(), 1_000_000).unwrap();
+        }
+        let genesis = Block::new(0, [0; 32], vec![], 1)
+            .unwrap()
+            .with_timestamp(Utc.timestamp_opt(1_600_000_000, 0).unwrap())
+            .unwrap();
+
+        let config = SimConfig { seed, drop_rate, ..Default::default() };
+        let mut sim = Simulation::new(config, NODES, |index, clock, _| {
+            ChainNode::new(keys[index].clone(), spec(), genesis.clone(), allocations.clone(), clock)
+                .unwrap()
+                .with_transfers(TRANSFERS)
+        })
+        .unwrap();
+        sim.start();
+        sim.run(100_000);
+        sim
+    }
+
+    #[test]
+    fn test_nodes_converge_on_one_chain() {
+        let sim = run(7, 0.0);
+        let tip = sim.nodes()[0].chain().tip().hash;
+
+        for node in sim.nodes() {
+            assert!(node.rejected().is_empty(), "{:?}", node.rejected());
+            assert_eq!(node.chain().tip().hash, tip);
+            assert_eq!(node.mempool_len(), 0);
+            assert_eq!(node.chain().ledger().account(&node.address()).nonce, TRANSFERS as Nonce);
+        }
+        // Every transfer reached node 1 before its first timer, so it produced block 1
+        assert!(sim.nodes()[0].chain().height() >= 1);
+        let first = sim.nodes()[0].chain().block_at(1).unwrap();
+        assert_eq!(first.coinbase().unwrap().recipient(), Some(sim.nodes()[1].address()));
+    }
+
+    #[test]
+    fn test_same_seed_replays_the_same_chain() {
+        let first = run(42, 0.05);
+        let replay = run(42, 0.05);
+        assert_eq!(first.trace(), replay.trace());
+        assert!(first.nodes()[0].chain().height() > 0);
+        for (node, replayed) in first.nodes().iter().zip(replay.nodes()) {
+            assert_eq!(node.chain().tip().hash, replayed.chain().tip().hash);
+        }
+    }
+}
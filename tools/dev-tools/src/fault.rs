@@ -0,0 +1,202 @@
+// tools/dev-tools/src/fault.rs
+use parking_lot::RwLock;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Faults to inject into storage queries and network traffic
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaultConfig {
+    /// Fraction of storage queries to fail (0.0 - 1.0)
+    pub query_failure_rate: f64,
+    /// Extra latency added to every storage query
+    pub query_delay_ms: u64,
+    /// Storage operations affected; empty means all of them
+    pub query_targets: Vec<String>,
+    /// Fixed delay added to every network message
+    pub message_delay_ms: u64,
+    /// Random extra delay of up to this many milliseconds
+    pub message_jitter_ms: u64,
+    /// Fraction of messages that cause the sending peer to be dropped (0.0 - 1.0)
+    pub peer_drop_rate: f64,
+}
+
+impl FaultConfig {
+    pub fn is_active(&self) -> bool {
+        self.query_failure_rate > 0.0
+            || self.query_delay_ms > 0
+            || self.message_delay_ms > 0
+            || self.message_jitter_ms > 0
+            || self.peer_drop_rate > 0.0
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.query_failure_rate) {
+            return Err("query_failure_rate must be between 0.0 and 1.0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.peer_drop_rate) {
+            return Err("peer_drop_rate must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// What the network layer should do with an incoming message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFate {
+    Deliver,
+    Delay(Duration),
+    DropPeer,
+}
+
+/// Admin command to change injected faults at runtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum FaultCommand {
+    Set { config: FaultConfig },
+    Clear,
+    Get,
+}
+
+/// Shared, runtime-adjustable fault injector.
+///
+/// Clones share the same configuration, so a handle given to the admin RPC
+/// controls the storage and network layers it was handed to.
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    config: Arc<RwLock<FaultConfig>>,
+}
+
+impl FaultInjector {
+    pub fn new(config: FaultConfig) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(config)),
+        }
+    }
+
+    pub fn config(&self) -> FaultConfig {
+        self.config.read().clone()
+    }
+
+    pub fn set(&self, config: FaultConfig) -> Result<(), String> {
+        config.validate()?;
+        *self.config.write() = config;
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        *self.config.write() = FaultConfig::default();
+    }
+
+    /// Apply an admin command, returning the resulting configuration
+    pub fn apply(&self, command: FaultCommand) -> Result<FaultConfig, String> {
+        match command {
+            FaultCommand::Set { config } => self.set(config)?,
+            FaultCommand::Clear => self.clear(),
+            FaultCommand::Get => {}
+        }
+        Ok(self.config())
+    }
+
+    /// Delay to add before running a storage query
+    pub fn query_delay(&self, operation: &str) -> Option<Duration> {
+        let config = self.config.read();
+        if config.query_delay_ms == 0 || !Self::targets(&config, operation) {
+            return None;
+        }
+        Some(Duration::from_millis(config.query_delay_ms))
+    }
+
+    /// Whether a storage query should fail
+    pub fn should_fail_query(&self, operation: &str) -> bool {
+        let config = self.config.read();
+        Self::targets(&config, operation) && Self::roll(config.query_failure_rate)
+    }
+
+    /// Decide the fate of a network message
+    pub fn message_fate(&self) -> MessageFate {
+        let config = self.config.read();
+        if Self::roll(config.peer_drop_rate) {
+            return MessageFate::DropPeer;
+        }
+
+        let jitter = match config.message_jitter_ms {
+            0 => 0,
+            max => rand::thread_rng().gen_range(0..=max),
+        };
+        match config.message_delay_ms + jitter {
+            0 => MessageFate::Deliver,
+            delay => MessageFate::Delay(Duration::from_millis(delay)),
+        }
+    }
+
+    fn targets(config: &FaultConfig, operation: &str) -> bool {
+        config.query_targets.is_empty() || config.query_targets.iter().any(|t| t == operation)
+    }
+
+    fn roll(rate: f64) -> bool {
+        rate > 0.0 && rand::thread_rng().gen_bool(rate.min(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_by_default() {
+        let faults = FaultInjector::default();
+        assert!(!faults.config().is_active());
+        assert!(!faults.should_fail_query("StoreBlock"));
+        assert_eq!(faults.query_delay("StoreBlock"), None);
+        assert_eq!(faults.message_fate(), MessageFate::Deliver);
+    }
+
+    #[test]
+    fn test_targeted_query_failures() {
+        let faults = FaultInjector::default();
+        let handle = faults.clone();
+        handle
+            .set(FaultConfig {
+                query_failure_rate: 1.0,
+                query_delay_ms: 5,
+                query_targets: vec!["StoreBlock".to_string()],
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert!(faults.should_fail_query("StoreBlock"));
+        assert!(!faults.should_fail_query("GetAccount"));
+        assert_eq!(faults.query_delay("StoreBlock"), Some(Duration::from_millis(5)));
+    }
+
+    #[test]
+    fn test_message_fate() {
+        let faults = FaultInjector::new(FaultConfig {
+            message_delay_ms: 100,
+            message_jitter_ms: 50,
+            ..Default::default()
+        });
+        match faults.message_fate() {
+            MessageFate::Delay(delay) => {
+                assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(150))
+            }
+            other => panic!("unexpected fate {:?}", other),
+        }
+
+        faults.apply(FaultCommand::Set {
+            config: FaultConfig { peer_drop_rate: 1.0, ..Default::default() },
+        }).unwrap();
+        assert_eq!(faults.message_fate(), MessageFate::DropPeer);
+
+        assert!(!faults.apply(FaultCommand::Clear).unwrap().is_active());
+    }
+
+    #[test]
+    fn test_rejects_invalid_rates() {
+        let faults = FaultInjector::default();
+        let config = FaultConfig { query_failure_rate: 1.5, ..Default::default() };
+        assert!(faults.set(config).is_err());
+    }
+}
@@ -1,14 +1,11 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+// tools/dev-tools/src/lib.rs
+//! Tooling for exercising nodes in test environments
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod chain_node;
+pub mod fault;
+pub mod loadgen;
+pub mod sim;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use chain_node::{ChainMessage, ChainNode};
+pub use fault::{FaultCommand, FaultConfig, FaultInjector, MessageFate};
+pub use sim::{NodeIndex, SimConfig, SimContext, SimNode, Simulation};
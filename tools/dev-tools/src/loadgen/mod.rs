@@ -0,0 +1,170 @@
+// tools/dev-tools/src/loadgen/mod.rs
+//! Sustained synthetic transaction traffic for soak and capacity tests
+
+pub mod rpc;
+pub mod senders;
+pub mod stats;
+
+use anyhow::Result;
+use blockchain_core::params::MAINNET_CHAIN_ID;
+use blockchain_core::{ChainId, SignatureScheme};
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use storage_traits::LifecycleStage;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+pub use rpc::RpcClient;
+pub use senders::{SenderPool, SignedTransaction};
+pub use stats::{LatencySummary, LoadReport, Outcome};
+
+/// Load generator settings
+#[derive(Debug, Clone, Parser)]
+#[command(name = "loadgen", about = "Generate signed transactions at a fixed rate against a node's RPC")]
+pub struct LoadgenConfig {
+    /// JSON-RPC endpoint of the node under test
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    pub rpc_url: String,
+    /// Target transactions per second
+    #[arg(long, default_value_t = 100)]
+    pub tps: u32,
+    /// How long to generate traffic for
+    #[arg(long, default_value_t = 60)]
+    pub duration_secs: u64,
+    /// Number of generated sender accounts (ignored with --keys)
+    #[arg(long, default_value_t = 16)]
+    pub senders: usize,
+    /// File with one hex-encoded secret key per line, for funded accounts
+    #[arg(long)]
+    pub keys: Option<PathBuf>,
+    /// Signature scheme of the chain under test (secp256k1 or ed25519)
+    #[arg(long, default_value_t = SignatureScheme::Secp256k1)]
+    pub signature_scheme: SignatureScheme,
+    /// Chain id transactions are bound to (0 sends legacy unbound transactions)
+    #[arg(long, default_value_t = MAINNET_CHAIN_ID)]
+    pub chain_id: ChainId,
+    /// Start each sender at its on-chain nonce instead of zero
+    #[arg(long)]
+    pub sync_nonces: bool,
+    /// Fraction of transactions that reuse an already-spent nonce (0.0 - 1.0)
+    #[arg(long, default_value_t = 0.0)]
+    pub conflict_rate: f64,
+    /// Upper bound on transactions awaiting confirmation
+    #[arg(long, default_value_t = 10_000)]
+    pub max_in_flight: usize,
+    /// Give up on a transaction after this long unconfirmed
+    #[arg(long, default_value_t = 120)]
+    pub confirm_timeout_secs: u64,
+    /// Interval between confirmation polls
+    #[arg(long, default_value_t = 250)]
+    pub poll_interval_ms: u64,
+    /// Transfer amount per transaction
+    #[arg(long, default_value_t = 1)]
+    pub amount: u64,
+    #[arg(long, default_value_t = 21_000)]
+    pub gas_limit: u64,
+    #[arg(long, default_value_t = 1_000_000_000)]
+    pub gas_price: u64,
+}
+
+impl LoadgenConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tps == 0 {
+            return Err("tps must be greater than 0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.conflict_rate) {
+            return Err("conflict_rate must be between 0.0 and 1.0".to_string());
+        }
+        if self.keys.is_none() && self.senders == 0 {
+            return Err("At least one sender is required".to_string());
+        }
+        if self.max_in_flight == 0 {
+            return Err("max_in_flight must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Run a load test to completion and report the results
+pub async fn run(config: LoadgenConfig) -> Result<LoadReport> {
+    config.validate().map_err(|e| anyhow::anyhow!(e))?;
+
+    let rpc = Arc::new(RpcClient::new(&config.rpc_url));
+    let pool = match &config.keys {
+        Some(path) => SenderPool::from_key_file(path, config.signature_scheme)?,
+        None => SenderPool::generate(config.senders, config.signature_scheme),
+    };
+    if config.sync_nonces {
+        for sender in pool.senders() {
+            sender.set_next_nonce(rpc.get_nonce(&sender.address).await?);
+        }
+    }
+
+    let pool = Arc::new(pool);
+    let in_flight = Arc::new(Semaphore::new(config.max_in_flight));
+    let mut tasks = JoinSet::new();
+    let mut report = LoadReport::default();
+
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / config.tps as f64));
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(config.duration_secs);
+
+    while Instant::now() < deadline {
+        ticker.tick().await;
+
+        // Submission stalls rather than growing memory when confirmations lag
+        let permit = in_flight.clone().acquire_owned().await?;
+        let conflict = rand::random::<f64>() < config.conflict_rate;
+        let signed = pool.next_transaction(&config, conflict)?;
+        let rpc = rpc.clone();
+        let config = config.clone();
+
+        tasks.spawn(async move {
+            let outcome = submit_and_confirm(&rpc, &config, &signed).await;
+            drop(permit);
+            (signed, outcome)
+        });
+
+        while let Some(done) = tasks.try_join_next() {
+            let (signed, outcome) = done?;
+            report.record(&signed, outcome);
+        }
+    }
+
+    while let Some(done) = tasks.join_next().await {
+        let (signed, outcome) = done?;
+        report.record(&signed, outcome);
+    }
+
+    report.elapsed = started.elapsed();
+    Ok(report)
+}
+
+/// Submit one transaction and poll until a block includes it or it times out
+async fn submit_and_confirm(
+    rpc: &RpcClient,
+    config: &LoadgenConfig,
+    signed: &SignedTransaction,
+) -> Outcome {
+    let submitted = Instant::now();
+    if let Err(e) = rpc.send_raw(&signed.transaction).await {
+        return Outcome::Rejected(e.to_string());
+    }
+
+    let timeout = Duration::from_secs(config.confirm_timeout_secs);
+    let poll = Duration::from_millis(config.poll_interval_ms);
+    while submitted.elapsed() < timeout {
+        tokio::time::sleep(poll).await;
+        match rpc.lifecycle(&signed.transaction.hash).await {
+            Ok(Some(lifecycle)) if lifecycle.stage >= LifecycleStage::Block => {
+                return Outcome::Confirmed(submitted.elapsed());
+            }
+            // Still pending, or not yet visible on the node we poll
+            Ok(_) | Err(_) => {}
+        }
+    }
+
+    Outcome::TimedOut
+}
@@ -0,0 +1,72 @@
+// tools/dev-tools/src/loadgen/rpc.rs
+use anyhow::{anyhow, Result};
+use blockchain_core::{Address, AddressExt, Nonce, Transaction, TxHash};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use storage_traits::TransactionLifecycle;
+
+/// Minimal JSON-RPC 2.0 client for the node's transaction endpoints
+pub struct RpcClient {
+    http: reqwest::Client,
+    url: String,
+    next_id: AtomicU64,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.to_string(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Call `method`, returning its decoded `result`
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+
+        let response: Value = self.http.post(&self.url).json(&request).send().await?.json().await?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("{} failed: {}", method, error));
+        }
+        let result = response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("{} returned no result", method))?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Submit a signed transaction to the mempool as canonical hex, returning its hash
+    pub async fn send_raw(&self, tx: &Transaction) -> Result<String> {
+        let raw = format!("0x{}", hex::encode(tx.to_canonical_bytes()));
+        self.call("tx_sendRaw", json!([raw])).await
+    }
+
+    /// Where a transaction is, `None` if no stage has seen it
+    pub async fn lifecycle(&self, hash: &TxHash) -> Result<Option<TransactionLifecycle>> {
+        self.call("tx_lifecycle", json!([format!("0x{}", hex::encode(hash))])).await
+    }
+
+    /// Next nonce the node expects from an account
+    pub async fn get_nonce(&self, address: &Address) -> Result<Nonce> {
+        let balances: Vec<AccountBalance> =
+            self.call("account_getBalances", json!([[address.to_checksum_hex()]])).await?;
+        balances
+            .first()
+            .map(|balance| balance.nonce)
+            .ok_or_else(|| anyhow!("account_getBalances returned no entry for {}", address.to_checksum_hex()))
+    }
+}
+
+/// Entry of an `account_getBalances` result
+#[derive(Debug, Deserialize)]
+struct AccountBalance {
+    nonce: Nonce,
+}
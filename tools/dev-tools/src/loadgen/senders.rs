@@ -0,0 +1,171 @@
+// tools/dev-tools/src/loadgen/senders.rs
+use anyhow::{anyhow, Result};
+use blockchain_core::{Address, AddressExt, KeyPair, Nonce, SignatureScheme, Transaction};
+use rand::Rng;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use super::LoadgenConfig;
+
+/// A sending account with a locally coordinated nonce
+pub struct Sender {
+    pub key: KeyPair,
+    pub address: Address,
+    next_nonce: AtomicU64,
+}
+
+impl Sender {
+    pub fn new(key: KeyPair) -> Self {
+        let address = Address::from_public_key(&key.public_key()).expect("KeyPair public keys derive addresses");
+
+        Self {
+            key,
+            address,
+            next_nonce: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set_next_nonce(&self, nonce: Nonce) {
+        self.next_nonce.store(nonce, Ordering::SeqCst);
+    }
+
+    /// Reserve the next nonce; concurrent callers never get the same one
+    pub fn allocate_nonce(&self) -> Nonce {
+        self.next_nonce.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Most recently allocated nonce, if any
+    pub fn last_nonce(&self) -> Option<Nonce> {
+        self.next_nonce.load(Ordering::SeqCst).checked_sub(1)
+    }
+}
+
+/// A transaction ready to submit
+#[derive(Debug, Clone)]
+pub struct SignedTransaction {
+    pub transaction: Transaction,
+    /// Reuses a nonce already submitted by the same sender
+    pub conflict: bool,
+}
+
+/// Round-robin set of senders
+pub struct SenderPool {
+    senders: Vec<Sender>,
+    cursor: AtomicUsize,
+}
+
+impl SenderPool {
+    pub fn new(senders: Vec<Sender>) -> Self {
+        Self {
+            senders,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// Fresh random accounts, for nodes that do not check balances
+    pub fn generate(count: usize, scheme: SignatureScheme) -> Self {
+        Self::new((0..count).map(|_| Sender::new(KeyPair::generate(scheme))).collect())
+    }
+
+    /// Load funded accounts from a file of hex secret keys
+    pub fn from_key_file(path: &Path, scheme: SignatureScheme) -> Result<Self> {
+        let mut senders = Vec::new();
+        for line in std::fs::read_to_string(path)?.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bytes = hex::decode(line.trim_start_matches("0x"))?;
+            senders.push(Sender::new(KeyPair::from_secret_bytes(scheme, &bytes)?));
+        }
+
+        if senders.is_empty() {
+            return Err(anyhow!("No keys found in {}", path.display()));
+        }
+        Ok(Self::new(senders))
+    }
+
+    pub fn senders(&self) -> &[Sender] {
+        &self.senders
+    }
+
+    /// Build and sign the next transfer, rotating through senders.
+    ///
+    /// A conflicting transaction reuses the sender's last nonce with a
+    /// different recipient, so at most one of the pair can be included.
+    pub fn next_transaction(&self, config: &LoadgenConfig, conflict: bool) -> Result<SignedTransaction> {
+        let index = self.cursor.fetch_add(1, Ordering::Relaxed) % self.senders.len();
+        let sender = &self.senders[index];
+
+        let (nonce, conflict) = match sender.last_nonce() {
+            Some(nonce) if conflict => (nonce, true),
+            _ => (sender.allocate_nonce(), false),
+        };
+
+        let recipient: Address = rand::thread_rng().gen();
+        let mut transaction = Transaction::new_transfer(
+            sender.address,
+            recipient,
+            config.amount,
+            nonce,
+            config.gas_limit,
+            config.gas_price,
+        )?
+        .with_chain_id(config.chain_id)?;
+        transaction.sign(&sender.key);
+
+        Ok(SignedTransaction { transaction, conflict })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_concurrent_nonce_allocation_is_gapless() {
+        let sender = Arc::new(Sender::new(KeyPair::generate(SignatureScheme::Secp256k1)));
+        sender.set_next_nonce(7);
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let sender = sender.clone();
+                std::thread::spawn(move || (0..250).map(|_| sender.allocate_nonce()).collect::<Vec<_>>())
+            })
+            .collect();
+
+        let mut nonces: Vec<Nonce> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+        nonces.sort_unstable();
+        assert_eq!(nonces, (7..1007).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_conflicts_reuse_last_nonce() {
+        let config = LoadgenConfig::parse_from(["loadgen"]);
+        let pool = SenderPool::generate(1, SignatureScheme::Secp256k1);
+
+        // Nothing to conflict with before the first transaction
+        let first = pool.next_transaction(&config, true).unwrap();
+        assert!(!first.conflict);
+
+        let conflicting = pool.next_transaction(&config, true).unwrap();
+        assert!(conflicting.conflict);
+        assert_eq!(conflicting.transaction.nonce, first.transaction.nonce);
+        assert_ne!(conflicting.transaction.hash, first.transaction.hash);
+
+        assert_eq!(pool.next_transaction(&config, false).unwrap().transaction.nonce, 1);
+        assert!(first.transaction.verify_sender(SignatureScheme::Secp256k1).is_ok());
+    }
+
+    #[test]
+    fn test_ed25519_senders() {
+        let config = LoadgenConfig::parse_from(["loadgen", "--signature-scheme", "ed25519"]);
+        let pool = SenderPool::generate(2, config.signature_scheme);
+
+        let signed = pool.next_transaction(&config, false).unwrap();
+        assert_eq!(signed.transaction.signature_scheme(), Some(SignatureScheme::Ed25519));
+        assert!(signed.transaction.verify_sender(SignatureScheme::Ed25519).is_ok());
+    }
+}
@@ -0,0 +1,183 @@
+// tools/dev-tools/src/loadgen/stats.rs
+use blockchain_core::{Address, Nonce};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use super::SignedTransaction;
+
+/// Final state of one generated transaction
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    Confirmed(Duration),
+    Rejected(String),
+    TimedOut,
+}
+
+/// Confirmation latency percentiles
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+}
+
+impl LatencySummary {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let total: Duration = sorted.iter().sum();
+
+        Self {
+            p50: percentile(&sorted, 50.0),
+            p90: percentile(&sorted, 90.0),
+            p99: percentile(&sorted, 99.0),
+            max: sorted[sorted.len() - 1],
+            mean: total / sorted.len() as u32,
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+pub fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Aggregate results of a load test run
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    pub submitted: u64,
+    pub confirmed: u64,
+    pub rejected: u64,
+    pub timed_out: u64,
+    pub conflicts_injected: u64,
+    /// Conflicting transactions confirmed in place of the one they reused the nonce of
+    pub conflicts_confirmed: u64,
+    /// (sender, nonce) pairs more than one transaction confirmed under; must stay at zero
+    pub nonces_confirmed_twice: u64,
+    pub elapsed: Duration,
+    pub latencies: Vec<Duration>,
+    /// Confirmed transactions per (sender, nonce)
+    confirmations: HashMap<(Address, Nonce), u32>,
+}
+
+impl LoadReport {
+    pub fn record(&mut self, signed: &SignedTransaction, outcome: Outcome) {
+        self.submitted += 1;
+        if signed.conflict {
+            self.conflicts_injected += 1;
+        }
+
+        match outcome {
+            Outcome::Confirmed(latency) => {
+                self.confirmed += 1;
+                let tx = &signed.transaction;
+                let confirmations = self.confirmations.entry((tx.sender(), tx.nonce)).or_default();
+                *confirmations += 1;
+                if *confirmations == 2 {
+                    self.nonces_confirmed_twice += 1;
+                }
+
+                if signed.conflict {
+                    self.conflicts_confirmed += 1;
+                } else {
+                    self.latencies.push(latency);
+                }
+            }
+            Outcome::Rejected(_) => self.rejected += 1,
+            Outcome::TimedOut => self.timed_out += 1,
+        }
+    }
+
+    pub fn latency(&self) -> LatencySummary {
+        LatencySummary::from_samples(&self.latencies)
+    }
+
+    /// Confirmed transactions per second over the run
+    pub fn confirmed_tps(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => self.confirmed as f64 / secs,
+            _ => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let latency = self.latency();
+        writeln!(f, "duration:            {:.1}s", self.elapsed.as_secs_f64())?;
+        writeln!(f, "submitted:           {}", self.submitted)?;
+        writeln!(f, "confirmed:           {} ({:.1} tps)", self.confirmed, self.confirmed_tps())?;
+        writeln!(f, "rejected:            {}", self.rejected)?;
+        writeln!(f, "timed out:           {}", self.timed_out)?;
+        writeln!(f, "conflicts injected:  {}", self.conflicts_injected)?;
+        writeln!(f, "conflicts confirmed: {}", self.conflicts_confirmed)?;
+        writeln!(f, "double spends:       {}", self.nonces_confirmed_twice)?;
+        writeln!(
+            f,
+            "latency p50/p90/p99/max: {:?} / {:?} / {:?} / {:?} (mean {:?})",
+            latency.p50, latency.p90, latency.p99, latency.max, latency.mean
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loadgen::{LoadgenConfig, SenderPool};
+    use blockchain_core::SignatureScheme;
+    use clap::Parser;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let samples: Vec<Duration> = (1..=100).rev().map(ms).collect();
+        let summary = LatencySummary::from_samples(&samples);
+        assert_eq!(summary.p50, ms(50));
+        assert_eq!(summary.p90, ms(90));
+        assert_eq!(summary.p99, ms(99));
+        assert_eq!(summary.max, ms(100));
+        assert_eq!(LatencySummary::from_samples(&[]), LatencySummary::default());
+    }
+
+    #[test]
+    fn test_report_tracks_same_nonce_pairs() {
+        let config = LoadgenConfig::parse_from(["loadgen"]);
+        let pool = SenderPool::generate(1, SignatureScheme::Secp256k1);
+        let first = pool.next_transaction(&config, false).unwrap();
+        let conflicting = pool.next_transaction(&config, true).unwrap();
+        let next = pool.next_transaction(&config, false).unwrap();
+        let next_conflicting = pool.next_transaction(&config, true).unwrap();
+
+        let mut report = LoadReport::default();
+        report.record(&first, Outcome::Rejected("replacement underpriced".to_string()));
+        report.record(&conflicting, Outcome::Confirmed(ms(5)));
+        report.record(&next, Outcome::Confirmed(ms(10)));
+        report.record(&next_conflicting, Outcome::TimedOut);
+
+        assert_eq!(report.submitted, 4);
+        assert_eq!(report.confirmed, 2);
+        assert_eq!(report.conflicts_injected, 2);
+        assert_eq!(report.conflicts_confirmed, 1);
+        assert_eq!(report.nonces_confirmed_twice, 0);
+        // Conflicts are excluded from latency
+        assert_eq!(report.latencies, vec![ms(10)]);
+
+        // Both transactions of a pair confirming is a double spend
+        report.record(&next_conflicting, Outcome::Confirmed(ms(20)));
+        assert_eq!(report.nonces_confirmed_twice, 1);
+    }
+}
@@ -0,0 +1,376 @@
+// tools/dev-tools/src/sim.rs
+//! Deterministic multi-node simulation for reproducing bugs.
+//!
+//! One seed drives everything a scenario could otherwise pick up from the
+//! host: the shared `MockClock` every node reads, the RNG nodes draw keys and
+//! choices from, and the simulated transport's latency, drops and the order
+//! of messages due at the same instant. Nodes keep their state in memory and
+//! only talk and set timers through `SimContext`, so running a scenario twice
+//! with the same seed produces the same trace byte for byte. Set `SIM_SEED` to
+//! replay a failing run. `chain_node::ChainNode` is the node assembly to
+//! simulate; any other `SimNode` is a protocol sketch.
+use blockchain_core::MockClock;
+use chrono::{DateTime, TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Position of a node in the simulation
+pub type NodeIndex = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimConfig {
+    pub seed: u64,
+    /// Unix seconds the simulated clock starts at
+    pub start_time: i64,
+    pub min_latency_ms: u64,
+    pub max_latency_ms: u64,
+    /// Fraction of messages lost in transit (0.0 - 1.0)
+    pub drop_rate: f64,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            start_time: 1_700_000_000,
+            min_latency_ms: 10,
+            max_latency_ms: 200,
+            drop_rate: 0.0,
+        }
+    }
+}
+
+impl SimConfig {
+    /// Defaults with the seed taken from `SIM_SEED`, or a fresh random one to
+    /// be reported with any failure
+    pub fn from_env() -> Self {
+        let seed = std::env::var("SIM_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(rand::random);
+        Self { seed, ..Self::default() }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_latency_ms > self.max_latency_ms {
+            return Err("min_latency_ms must not exceed max_latency_ms".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.drop_rate) {
+            return Err("drop_rate must be between 0.0 and 1.0".to_string());
+        }
+        Utc.timestamp_opt(self.start_time, 0)
+            .single()
+            .map(|_| ())
+            .ok_or_else(|| format!("Invalid start_time: {}", self.start_time))
+    }
+}
+
+/// A node under simulation
+pub trait SimNode {
+    /// Called once per node, in index order, before any message is delivered
+    fn on_start(&mut self, _ctx: &mut SimContext<'_>) {}
+
+    fn on_message(&mut self, ctx: &mut SimContext<'_>, from: NodeIndex, payload: &[u8]);
+
+    /// Called when a timer set with `SimContext::set_timer` fires
+    fn on_timer(&mut self, _ctx: &mut SimContext<'_>) {}
+}
+
+/// What a node may use while handling an event
+pub struct SimContext<'a> {
+    node: NodeIndex,
+    node_count: usize,
+    clock: &'a MockClock,
+    rng: &'a mut StdRng,
+    outbox: Vec<(NodeIndex, Vec<u8>)>,
+    timers: Vec<u64>,
+}
+
+impl SimContext<'_> {
+    pub fn node(&self) -> NodeIndex {
+        self.node
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    pub fn clock(&self) -> &MockClock {
+        self.clock
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        blockchain_core::Clock::now(self.clock)
+    }
+
+    /// The simulation's seeded RNG; nodes must not use any other source
+    pub fn rng(&mut self) -> &mut StdRng {
+        self.rng
+    }
+
+    pub fn send(&mut self, to: NodeIndex, payload: Vec<u8>) {
+        self.outbox.push((to, payload));
+    }
+
+    /// Send `payload` to every other node
+    pub fn broadcast(&mut self, payload: &[u8]) {
+        for to in (0..self.node_count).filter(|to| *to != self.node) {
+            self.outbox.push((to, payload.to_vec()));
+        }
+    }
+
+    /// Call this node's `on_timer` once `after_ms` of simulated time has
+    /// passed. Timers are never dropped.
+    pub fn set_timer(&mut self, after_ms: u64) {
+        self.timers.push(after_ms);
+    }
+}
+
+/// Message in transit, ordered by delivery time and then by a seeded draw,
+/// so simultaneous arrivals are shuffled the same way on every replay
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct InFlight {
+    deliver_at_ms: u64,
+    tiebreak: u64,
+    /// Unique, so ordering never falls through to the payload
+    seq: u64,
+    timer: bool,
+    from: NodeIndex,
+    to: NodeIndex,
+    payload: Vec<u8>,
+}
+
+const TRACE_DELIVERED: u8 = 0;
+const TRACE_DROPPED: u8 = 1;
+const TRACE_TIMER: u8 = 2;
+
+pub struct Simulation<N> {
+    config: SimConfig,
+    clock: MockClock,
+    rng: StdRng,
+    nodes: Vec<N>,
+    queue: BinaryHeap<Reverse<InFlight>>,
+    now_ms: u64,
+    seq: u64,
+    trace: Vec<u8>,
+}
+
+impl<N: SimNode> Simulation<N> {
+    /// Build `count` nodes with `make_node`, which gets the shared clock and
+    /// seeded RNG so node keys and initial state follow the seed too
+    pub fn new(
+        config: SimConfig,
+        count: usize,
+        mut make_node: impl FnMut(NodeIndex, &MockClock, &mut StdRng) -> N,
+    ) -> Result<Self, String> {
+        config.validate()?;
+        let start = Utc
+            .timestamp_opt(config.start_time, 0)
+            .single()
+            .ok_or_else(|| format!("Invalid start_time: {}", config.start_time))?;
+        let clock = MockClock::new(start);
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let nodes = (0..count).map(|index| make_node(index, &clock, &mut rng)).collect();
+
+        Ok(Self {
+            config,
+            clock,
+            rng,
+            nodes,
+            queue: BinaryHeap::new(),
+            now_ms: 0,
+            seq: 0,
+            trace: Vec::new(),
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.config.seed
+    }
+
+    pub fn clock(&self) -> &MockClock {
+        &self.clock
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// Every delivered or dropped message so far, in order; equal traces mean
+    /// equal runs
+    pub fn trace(&self) -> &[u8] {
+        &self.trace
+    }
+
+    /// Milliseconds of simulated time since the start
+    pub fn elapsed_ms(&self) -> u64 {
+        self.now_ms
+    }
+
+    pub fn start(&mut self) {
+        for index in 0..self.nodes.len() {
+            self.dispatch(index, |node, ctx| node.on_start(ctx));
+        }
+    }
+
+    /// Deliver the next message or fire the next timer, advancing the clock
+    /// to its due time; `false` once nothing is pending
+    pub fn step(&mut self) -> bool {
+        let Some(Reverse(message)) = self.queue.pop() else {
+            return false;
+        };
+        self.clock.advance(chrono::Duration::milliseconds((message.deliver_at_ms - self.now_ms) as i64));
+        self.now_ms = message.deliver_at_ms;
+        self.record(if message.timer { TRACE_TIMER } else { TRACE_DELIVERED }, &message);
+
+        let InFlight { timer, from, to, payload, .. } = message;
+        if timer {
+            self.dispatch(to, |node, ctx| node.on_timer(ctx));
+        } else {
+            self.dispatch(to, |node, ctx| node.on_message(ctx, from, &payload));
+        }
+        true
+    }
+
+    /// Step until the network is quiet and no timer is set, or `max_steps`
+    /// events were handled, returning how many were
+    pub fn run(&mut self, max_steps: usize) -> usize {
+        let mut steps = 0;
+        while steps < max_steps && self.step() {
+            steps += 1;
+        }
+        steps
+    }
+
+    fn dispatch(&mut self, index: NodeIndex, handle: impl FnOnce(&mut N, &mut SimContext<'_>)) {
+        let mut ctx = SimContext {
+            node: index,
+            node_count: self.nodes.len(),
+            clock: &self.clock,
+            rng: &mut self.rng,
+            outbox: Vec::new(),
+            timers: Vec::new(),
+        };
+        handle(&mut self.nodes[index], &mut ctx);
+
+        let SimContext { outbox, timers, .. } = ctx;
+        for (to, payload) in outbox {
+            self.enqueue(index, to, payload);
+        }
+        for after_ms in timers {
+            self.schedule_timer(index, after_ms);
+        }
+    }
+
+    fn schedule_timer(&mut self, node: NodeIndex, after_ms: u64) {
+        let timer = InFlight {
+            deliver_at_ms: self.now_ms + after_ms,
+            tiebreak: self.rng.gen(),
+            seq: self.seq,
+            timer: true,
+            from: node,
+            to: node,
+            payload: Vec::new(),
+        };
+        self.seq += 1;
+        self.queue.push(Reverse(timer));
+    }
+
+    fn enqueue(&mut self, from: NodeIndex, to: NodeIndex, payload: Vec<u8>) {
+        let latency = self.rng.gen_range(self.config.min_latency_ms..=self.config.max_latency_ms);
+        let message = InFlight {
+            deliver_at_ms: self.now_ms + latency,
+            tiebreak: self.rng.gen(),
+            seq: self.seq,
+            timer: false,
+            from,
+            to,
+            payload,
+        };
+        self.seq += 1;
+
+        let dropped = self.config.drop_rate > 0.0 && self.rng.gen_bool(self.config.drop_rate);
+        if dropped || to >= self.nodes.len() {
+            self.record(TRACE_DROPPED, &message);
+        } else {
+            self.queue.push(Reverse(message));
+        }
+    }
+
+    fn record(&mut self, kind: u8, message: &InFlight) {
+        self.trace.push(kind);
+        self.trace.extend_from_slice(&self.now_ms.to_be_bytes());
+        self.trace.extend_from_slice(&(message.from as u32).to_be_bytes());
+        self.trace.extend_from_slice(&(message.to as u32).to_be_bytes());
+        self.trace.extend_from_slice(&(message.payload.len() as u32).to_be_bytes());
+        self.trace.extend_from_slice(&message.payload);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blockchain_core::{Address, AddressExt, KeyPair, SignatureScheme, Transaction, TxHash};
+    use std::collections::HashSet;
+
+    /// Signs one transfer at start and floods every transaction it first sees
+    struct FloodNode {
+        key: KeyPair,
+        seen: HashSet<TxHash>,
+    }
+
+    impl SimNode for FloodNode {
+        fn on_start(&mut self, ctx: &mut SimContext<'_>) {
+            let from = Address::from_public_key(&self.key.public_key()).unwrap();
+            let amount = ctx.rng().gen_range(1..1_000);
+            let mut tx = Transaction::new_transfer(from, [9; 20], amount, 0, 21_000, 1)
+                .and_then(|tx| tx.with_timestamp(ctx.now()))
+                .unwrap();
+            tx.sign(&self.key);
+            self.seen.insert(tx.hash);
+            ctx.broadcast(&serde_json::to_vec(&tx).unwrap());
+        }
+
+        fn on_message(&mut self, ctx: &mut SimContext<'_>, _from: NodeIndex, payload: &[u8]) {
+            let tx: Transaction = serde_json::from_slice(payload).unwrap();
+            if self.seen.insert(tx.hash) {
+                ctx.broadcast(payload);
+            }
+        }
+    }
+
+    fn run(seed: u64) -> Simulation<FloodNode> {
+        let config = SimConfig { seed, drop_rate: 0.1, ..Default::default() };
+        let mut sim = Simulation::new(config, 5, |_, _, rng| FloodNode {
+            key: KeyPair::from_secret_bytes(SignatureScheme::Ed25519, &rng.gen::<[u8; 32]>()).unwrap(),
+            seen: HashSet::new(),
+        })
+        .unwrap();
+        sim.start();
+        sim.run(10_000);
+        sim
+    }
+
+    #[test]
+    fn test_same_seed_replays_identically() {
+        let first = run(42);
+        let replay = run(42);
+        assert!(!first.trace().is_empty());
+        assert_eq!(first.trace(), replay.trace());
+        assert_eq!(first.elapsed_ms(), replay.elapsed_ms());
+        assert_eq!(
+            blockchain_core::Clock::now(first.clock()),
+            Utc.timestamp_opt(1_700_000_000, 0).unwrap() + chrono::Duration::milliseconds(first.elapsed_ms() as i64)
+        );
+
+        assert_ne!(run(43).trace(), first.trace());
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        assert!(SimConfig { min_latency_ms: 10, max_latency_ms: 5, ..Default::default() }.validate().is_err());
+        assert!(SimConfig { drop_rate: 1.5, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,402 @@
+// validation/engine/src/engine.rs
+//! Validating claimed batches.
+//!
+//! Each transaction is first checked on its own: it must still be pending,
+//! be well formed, be signed by its sender, be bound to this chain, fit in a
+//! block and pay at least `min_gas_price`. Those passing are then applied, by
+//! a `StateValidator` in batch order, to a ledger loaded with the
+//! stored state of every account they touch, so a nonce reused or skipped
+//! within the batch, or a sender spending more than it holds, fails there.
+//!
+//! A batch is `Validated` when every transaction passes and `Failed`
+//! otherwise; either way its result lists what failed and why, the gas each
+//! passing transaction will be charged, with a gas limit to suggest, and the
+//! balances the batch moves. A batch whose validation could not finish, e.g.
+//! because storage went away, goes back to `Pending` for the next pass.
+//!
+//! `estimate_gas` dry-runs a single transaction the same way, for senders
+//! choosing a gas limit.
+use anyhow::{bail, Result};
+use blockchain_core::gas_estimate::with_margin;
+use blockchain_core::{
+    intrinsic_gas, verify_transactions, Amount, BlockchainError, ChainSpec, FeeSpeed, GasOracleConfig, Transaction,
+    TxHash, VerifyConfig, DEFAULT_GAS_MARGIN_PERCENT,
+};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use storage_traits::{
+    FailedTransaction, GasEstimate, ValidationBatch, ValidationResult, ValidationStatus, ValidationStore,
+};
+
+use crate::state::StateValidator;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorConfig {
+    /// Recorded on every batch this engine claims
+    pub validator_id: String,
+    /// Batches claimed per pass
+    pub claim_limit: i32,
+    /// Lowest gas price a transaction may pay
+    pub min_gas_price: Amount,
+    pub interval: Duration,
+    /// How a batch's signatures are verified in parallel
+    pub verify: VerifyConfig,
+    /// Added to estimated gas for suggested gas limits, in percent
+    pub gas_margin_percent: u64,
+}
+
+impl Default for ValidatorConfig {
+    fn default() -> Self {
+        Self {
+            validator_id: "validator-1".to_string(),
+            claim_limit: 10,
+            min_gas_price: 1,
+            interval: Duration::from_secs(5),
+            verify: VerifyConfig::default(),
+            gas_margin_percent: DEFAULT_GAS_MARGIN_PERCENT,
+        }
+    }
+}
+
+impl ValidatorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.validator_id.is_empty() {
+            bail!("Validator id cannot be empty");
+        }
+        if self.claim_limit <= 0 {
+            bail!("Claim limit must be greater than 0");
+        }
+        if self.interval.is_zero() {
+            bail!("Validation interval must be greater than 0");
+        }
+        self.verify.validate()?;
+        Ok(())
+    }
+}
+
+pub struct ValidationEngine {
+    store: Arc<dyn ValidationStore>,
+    spec: ChainSpec,
+    config: ValidatorConfig,
+}
+
+impl ValidationEngine {
+    /// Engine validating against the rules and fee split of `spec`
+    pub fn new(store: Arc<dyn ValidationStore>, spec: ChainSpec, config: ValidatorConfig) -> Result<Self> {
+        config.validate()?;
+        spec.validate()?;
+        Ok(Self { store, spec, config })
+    }
+
+    pub fn config(&self) -> &ValidatorConfig {
+        &self.config
+    }
+
+    /// Claim and validate a round of batches, returning those completed
+    pub async fn validate_once(&self) -> Result<Vec<ValidationBatch>> {
+        let claimed = self.store.claim_batches(&self.config.validator_id, self.config.claim_limit).await?;
+        let mut completed = Vec::with_capacity(claimed.len());
+        for mut batch in claimed {
+            match self.validate_batch(&batch).await {
+                Ok(result) => {
+                    batch.complete_validation(result);
+                    self.store.update_batch(&batch).await?;
+                    tracing::debug!(
+                        queue_id = %batch.queue_id,
+                        lineage = %batch.batch_lineage_id,
+                        status = %batch.validation_status,
+                        "validated batch"
+                    );
+                    completed.push(batch);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        queue_id = %batch.queue_id,
+                        lineage = %batch.batch_lineage_id,
+                        error = %e,
+                        "validation did not finish, batch requeued"
+                    );
+                    batch.validation_status = ValidationStatus::Pending;
+                    batch.started_at = None;
+                    self.store.update_batch(&batch).await?;
+                }
+            }
+        }
+        Ok(completed)
+    }
+
+    /// Validate `batch`'s transactions without changing stored state; fails
+    /// only when storage does
+    pub async fn validate_batch(&self, batch: &ValidationBatch) -> Result<ValidationResult> {
+        let started = Instant::now();
+        let mut state = StateValidator::new(self.store.as_ref(), &self.spec.fees);
+        let mut validated = Vec::new();
+        let mut failed = Vec::new();
+        let mut gas_estimates = Vec::new();
+
+        let mut pending = Vec::with_capacity(batch.tx_hashes.len());
+        for tx_hash in &batch.tx_hashes {
+            pending.push((*tx_hash, self.store.pending_transaction(tx_hash).await?));
+        }
+        // One parallel pass over every signature; only when it fails is each
+        // transaction's checked again to find which
+        let loaded: Vec<Transaction> = pending.iter().filter_map(|(_, tx)| tx.clone()).collect();
+        let signatures_verified = verify_transactions(&loaded, &self.config.verify).is_ok();
+
+        for (tx_hash, tx) in pending {
+            let checked = Instant::now();
+            let Some(tx) = tx else {
+                failed.push(failure(tx_hash, "TX_NOT_FOUND", "Transaction is no longer pending".to_string(), None));
+                continue;
+            };
+            if let Err(failure) = self.check_transaction(&tx, signatures_verified) {
+                failed.push(failure);
+                continue;
+            }
+
+            match state.check(&tx).await? {
+                Ok(()) => {
+                    validated.push(tx.hash);
+                    gas_estimates.push(GasEstimate {
+                        tx_hash: tx.hash,
+                        // The whole gas limit is charged; there is no metering to refund unused gas
+                        estimated_gas: tx.gas_limit,
+                        suggested_gas_limit: self.suggested_gas_limit(intrinsic_gas(&tx)),
+                        gas_price_suggestion: tx.gas_price,
+                        execution_time_estimate_ms: checked.elapsed().as_millis() as u64,
+                    });
+                }
+                Err(e) => failed.push(execution_failure(&tx, &e)),
+            }
+        }
+
+        // What the batch's passing transactions pay at the median, never below the minimum
+        let paid = gas_estimates.iter().map(|estimate| estimate.gas_price_suggestion).collect();
+        let suggestion = GasOracleConfig::default().suggest(paid, self.config.min_gas_price).price(FeeSpeed::Standard);
+        for estimate in &mut gas_estimates {
+            estimate.gas_price_suggestion = suggestion;
+        }
+
+        let is_valid = failed.is_empty();
+        Ok(ValidationResult {
+            is_valid,
+            error_message: (!is_valid)
+                .then(|| format!("{} of {} transactions failed validation", failed.len(), batch.tx_hashes.len())),
+            validated_transactions: validated,
+            failed_transactions: failed,
+            gas_estimates,
+            balance_changes: state.balance_changes(),
+            validation_time_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Estimate the gas `tx` needs if sent now, from a dry run against the
+    /// stored state of the accounts it touches, with a gas limit to suggest
+    /// and a price of at least `min_gas_price`. Fails only when storage does;
+    /// the inner result fails when the transaction could not apply, e.g. on
+    /// a stale nonce or a sender unable to pay.
+    pub async fn estimate_gas(&self, tx: &Transaction) -> Result<std::result::Result<GasEstimate, BlockchainError>> {
+        let started = Instant::now();
+        let mut state = StateValidator::new(self.store.as_ref(), &self.spec.fees);
+        Ok(state.estimate_gas(tx).await?.map(|gas| GasEstimate {
+            tx_hash: tx.hash,
+            estimated_gas: gas,
+            suggested_gas_limit: self.suggested_gas_limit(gas),
+            gas_price_suggestion: tx.gas_price.max(self.config.min_gas_price),
+            execution_time_estimate_ms: started.elapsed().as_millis() as u64,
+        }))
+    }
+
+    /// `gas` plus the configured margin, within the block gas limit
+    fn suggested_gas_limit(&self, gas: u64) -> u64 {
+        with_margin(gas, self.config.gas_margin_percent, self.spec.params.block_gas_limit)
+    }
+
+    /// Checks needing nothing but the transaction and the chain's rules; the
+    /// signature is skipped when the batch's were verified together
+    fn check_transaction(
+        &self,
+        tx: &Transaction,
+        signature_verified: bool,
+    ) -> std::result::Result<(), FailedTransaction> {
+        let fail = |code: &str, message: String| failure(tx.hash, code, message, None);
+        if tx.is_coinbase() {
+            return Err(fail("INVALID_STRUCTURE", "Coinbase transactions are minted, not submitted".to_string()));
+        }
+        tx.validate_structure().map_err(|e| fail("INVALID_STRUCTURE", e.to_string()))?;
+
+        if !signature_verified {
+            let Some(scheme) = tx.signature_scheme() else {
+                let message = match tx.signature.len() {
+                    0 => "Transaction is not signed".to_string(),
+                    len => format!("Unrecognized signature of {} bytes", len),
+                };
+                return Err(fail("INVALID_SIGNATURE", message));
+            };
+            tx.verify_sender(scheme).map_err(|e| fail("INVALID_SIGNATURE", e.to_string()))?;
+        }
+
+        let params = &self.spec.params;
+        if tx.gas_limit > params.block_gas_limit {
+            return Err(failure(
+                tx.hash,
+                "GAS_LIMIT_TOO_HIGH",
+                format!("Gas limit {} exceeds block gas limit {}", tx.gas_limit, params.block_gas_limit),
+                Some(params.block_gas_limit),
+            ));
+        }
+        params.validate_transaction(tx).map_err(|e| fail("WRONG_CHAIN", e.to_string()))?;
+
+        if tx.gas_price < self.config.min_gas_price {
+            return Err(fail(
+                "GAS_PRICE_TOO_LOW",
+                format!("Gas price {} below the minimum {}", tx.gas_price, self.config.min_gas_price),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn failure(tx_hash: TxHash, code: &str, message: String, suggested_gas_limit: Option<u64>) -> FailedTransaction {
+    FailedTransaction { tx_hash, error_code: code.to_string(), error_message: message, suggested_gas_limit }
+}
+
+fn execution_failure(tx: &Transaction, error: &BlockchainError) -> FailedTransaction {
+    let code = match error {
+        BlockchainError::InvalidNonce { expected, actual } if actual < expected => "NONCE_TOO_LOW",
+        BlockchainError::InvalidNonce { .. } => "NONCE_GAP",
+        BlockchainError::InsufficientBalance { .. } => "INSUFFICIENT_BALANCE",
+        _ => "EXECUTION_FAILED",
+    };
+    failure(tx.hash, code, error.to_string(), None)
+}
+
+/// Validate claimed batches every `interval` of the engine's config
+pub fn spawn_validation_engine(engine: Arc<ValidationEngine>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(engine.config.interval);
+        loop {
+            ticker.tick().await;
+            match engine.validate_once().await {
+                Ok(batches) if !batches.is_empty() => {
+                    let failed = batches.iter().filter(|batch| batch.validation_status == ValidationStatus::Failed);
+                    tracing::info!(batches = batches.len(), failed = failed.count(), "validated batches");
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "validation engine pass failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use blockchain_core::params::TESTNET_CHAIN_ID;
+    use blockchain_core::{AccountState, Address, AddressExt, ChainParams, KeyPair, SignatureScheme};
+    use chrono::Utc;
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        batches: Mutex<Vec<ValidationBatch>>,
+        pending: Mutex<HashMap<TxHash, Transaction>>,
+        accounts: Mutex<HashMap<Address, AccountState>>,
+    }
+
+    #[async_trait]
+    impl ValidationStore for MemoryStore {
+        async fn claim_batches(&self, validator_id: &str, limit: i32) -> Result<Vec<ValidationBatch>> {
+            let mut batches = self.batches.lock();
+            let pending = batches.iter_mut().filter(|batch| batch.validation_status == ValidationStatus::Pending);
+            Ok(pending
+                .take(limit as usize)
+                .map(|batch| {
+                    batch.claim(validator_id, Utc::now());
+                    batch.clone()
+                })
+                .collect())
+        }
+
+        async fn pending_transaction(&self, tx_hash: &TxHash) -> Result<Option<Transaction>> {
+            Ok(self.pending.lock().get(tx_hash).cloned())
+        }
+
+        async fn account(&self, address: &Address) -> Result<AccountState> {
+            Ok(self.accounts.lock().get(address).copied().unwrap_or_default())
+        }
+
+        async fn update_batch(&self, batch: &ValidationBatch) -> Result<()> {
+            for stored in self.batches.lock().iter_mut().filter(|stored| stored.queue_id == batch.queue_id) {
+                *stored = batch.clone();
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validates_claimed_batches() {
+        let key = KeyPair::generate(SignatureScheme::Ed25519);
+        let alice = Address::from_public_key(&key.public_key()).unwrap();
+        let bob = [2; 20];
+        let transfer = |amount, nonce, signed| {
+            let mut tx = Transaction::new_transfer(alice, bob, amount, nonce, 21_000, 2)
+                .unwrap()
+                .with_chain_id(TESTNET_CHAIN_ID)
+                .unwrap();
+            if signed {
+                tx.sign(&key);
+            }
+            tx
+        };
+        // Costs 43_000 of the 100_000, leaving too little for the 92_000 of the third
+        let txs = [
+            transfer(1_000, 3, true),
+            transfer(2_000, 3, true),
+            transfer(50_000, 4, true),
+            transfer(10, 4, false),
+        ];
+
+        let store = Arc::new(MemoryStore::default());
+        store.accounts.lock().insert(alice, AccountState { balance: 100_000, nonce: 3 });
+        store.pending.lock().extend(txs.iter().map(|tx| (tx.hash, tx.clone())));
+        let mut hashes: Vec<TxHash> = txs.iter().map(|tx| tx.hash).collect();
+        hashes.push([7; 32]);
+        let mixed = ValidationBatch::new(hashes, String::new());
+        let clean = ValidationBatch::new(vec![txs[0].hash], String::new());
+        store.batches.lock().extend([mixed, clean]);
+
+        let spec = ChainSpec { params: ChainParams::testnet(), ..ChainSpec::default() };
+        let engine = ValidationEngine::new(store.clone(), spec, ValidatorConfig::default()).unwrap();
+        let completed = engine.validate_once().await.unwrap();
+        assert_eq!(completed.len(), 2);
+        assert!(engine.validate_once().await.unwrap().is_empty());
+
+        // Estimating dry-runs against stored state, where alice's next nonce is still 3
+        let estimate = engine.estimate_gas(&transfer(1_000, 3, false)).await.unwrap().unwrap();
+        assert_eq!((estimate.estimated_gas, estimate.suggested_gas_limit), (21_000, 25_200));
+        assert!(engine.estimate_gas(&transfer(1_000, 2, false)).await.unwrap().is_err());
+
+        let batches = store.batches.lock();
+        assert_eq!(batches[0].validation_status, ValidationStatus::Failed);
+        assert_eq!(batches[0].validator_id, "validator-1");
+        let result = batches[0].validation_result.as_ref().unwrap();
+        let codes: Vec<&str> = result.failed_transactions.iter().map(|failed| failed.error_code.as_str()).collect();
+        assert_eq!(codes, ["NONCE_TOO_LOW", "INSUFFICIENT_BALANCE", "INVALID_SIGNATURE", "TX_NOT_FOUND"]);
+        assert_eq!(result.validated_transactions, vec![txs[0].hash]);
+        assert_eq!(result.error_message.as_deref(), Some("4 of 5 transactions failed validation"));
+        assert_eq!((result.gas_estimates[0].estimated_gas, result.gas_estimates[0].gas_price_suggestion), (21_000, 2));
+        assert_eq!(result.gas_estimates[0].suggested_gas_limit, 25_200);
+
+        let change = |address| result.balance_changes.iter().find(|change| change.address == address).unwrap();
+        let sender = change(alice);
+        assert_eq!((sender.old_balance, sender.new_balance, sender.new_nonce), (100_000, 57_000, 4));
+        assert_eq!(change(bob).new_balance, 1_000);
+
+        // Validation never moves stored balances, so the same transaction passes again
+        assert_eq!(batches[1].validation_status, ValidationStatus::Validated);
+        assert_eq!(store.accounts.lock()[&alice].balance, 100_000);
+    }
+}
@@ -0,0 +1,15 @@
+// validation/engine/src/lib.rs
+//! Validation engine: claims pending `ValidationBatch` rows from
+//! `validation_queue`, checks each transaction's structure, signature and
+//! chain rules, applies the batch to the senders' stored accounts to catch
+//! nonce and balance failures, and writes the `ValidationResult` back with
+//! the batch's final status. Batches a crashed validator left processing
+//! are returned to the queue.
+pub mod engine;
+pub mod recovery;
+pub mod state;
+
+pub use engine::{spawn_validation_engine, ValidationEngine, ValidatorConfig};
+pub use recovery::{spawn_batch_recovery, BatchRecovery, RecoveryConfig, StuckBatchStore, StuckValidationBatches};
+pub use state::StateValidator;
+pub use storage_traits::ValidationStore;
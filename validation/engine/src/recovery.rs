@@ -0,0 +1,106 @@
+// validation/engine/src/recovery.rs
+//! Returning batches stranded by a crashed validator to the queue.
+//!
+//! A validator that dies or hangs mid-batch leaves it `Processing`, and
+//! validators only claim pending batches. Recovery puts back batches
+//! claimed longer than `stuck_after` ago and counts the recovery in the
+//! batch's `recovery_count`. The reset only applies while the batch is
+//! still under the claim that was found, so a slow validator finishing the
+//! batch meanwhile keeps its result. `stuck_after` should comfortably exceed
+//! the time a batch takes to validate.
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use storage_traits::{ValidationBatch, ValidationRecoveryStore};
+
+pub use batch_recovery::{spawn_batch_recovery, BatchRecovery, RecoveryConfig, StuckBatchStore};
+
+/// `validation_queue` as stuck-batch recovery sees it
+pub struct StuckValidationBatches(pub Arc<dyn ValidationRecoveryStore>);
+
+#[async_trait]
+impl StuckBatchStore for StuckValidationBatches {
+    type Batch = ValidationBatch;
+    const STAGE: &'static str = "validation";
+
+    /// Batches still `Processing` under claims started before `stuck_since`
+    async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<ValidationBatch>> {
+        self.0.stuck_batches(stuck_since, limit).await
+    }
+
+    /// Left alone unless still processing under the claim that was found
+    async fn recover_batch(&self, mut batch: ValidationBatch) -> Result<Option<ValidationBatch>> {
+        let claimed_at = batch.started_at;
+        batch.recover();
+        Ok(self.0.recover_batch(&batch, claimed_at).await?.then_some(batch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use storage_traits::ValidationStatus;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        batches: Mutex<Vec<ValidationBatch>>,
+    }
+
+    #[async_trait]
+    impl StuckBatchStore for MemoryStore {
+        type Batch = ValidationBatch;
+        const STAGE: &'static str = "validation";
+
+        async fn stuck_batches(&self, stuck_since: DateTime<Utc>, limit: i32) -> Result<Vec<ValidationBatch>> {
+            let batches = self.batches.lock();
+            Ok(batches
+                .iter()
+                .filter(|batch| batch.validation_status == ValidationStatus::Processing)
+                .filter(|batch| batch.started_at.is_some_and(|at| at < stuck_since))
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        async fn recover_batch(&self, mut batch: ValidationBatch) -> Result<Option<ValidationBatch>> {
+            let claimed_at = batch.started_at;
+            batch.recover();
+            let mut batches = self.batches.lock();
+            let stored = batches.iter_mut().find(|b| b.queue_id == batch.queue_id);
+            match stored {
+                Some(stored)
+                    if stored.validation_status == ValidationStatus::Processing && stored.started_at == claimed_at =>
+                {
+                    *stored = batch.clone();
+                    Ok(Some(batch))
+                }
+                _ => Ok(None),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_returns_stalled_claims_to_pending() {
+        let now = Utc::now();
+        let claimed = |minutes_ago| {
+            let mut batch = ValidationBatch::new(vec![[minutes_ago as u8; 32]], String::new());
+            batch.claim("validator-2", now - chrono::Duration::minutes(minutes_ago));
+            batch
+        };
+        let store = Arc::new(MemoryStore::default());
+        store.batches.lock().extend([claimed(30), claimed(2)]);
+        let recovery = BatchRecovery::new(store.clone(), RecoveryConfig::default()).unwrap();
+
+        let recovered = recovery.recover_once(now).await.unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].validation_status, ValidationStatus::Pending);
+        assert_eq!((recovered[0].started_at, recovered[0].recovery_count), (None, 1));
+        assert_eq!(store.batches.lock()[0].recovery_count, 1);
+
+        // Claimed again and stalled again, it is recovered a second time
+        store.batches.lock()[0].claim("validator-3", now - chrono::Duration::minutes(20));
+        assert_eq!(recovery.recover_once(now).await.unwrap()[0].recovery_count, 2);
+    }
+}
@@ -0,0 +1,146 @@
+// validation/engine/src/state.rs
+//! Checking transactions against stored account state.
+//!
+//! A `StateValidator` applies transactions in order to a ledger seeded
+//! lazily with the stored state of each account they touch. A transaction
+//! passes when its nonce is the sender's next one and the sender's balance
+//! covers the amount plus the maximum fee, `gas_limit * gas_price`;
+//! otherwise it fails with `InvalidNonce` or `InsufficientBalance` and
+//! leaves the ledger as it was. Passing transactions build on each other,
+//! so a nonce reused or skipped in a sequence, or a sender spending the
+//! same funds twice, fails on the later transaction. Stored state is only
+//! read, never written.
+use anyhow::Result;
+use blockchain_core::{AccountState, Address, BlockchainError, FeeDistribution, Ledger, Transaction};
+use std::collections::HashMap;
+use storage_traits::{BalanceChange, ValidationStore};
+
+pub struct StateValidator<'a> {
+    store: &'a dyn ValidationStore,
+    fees: &'a FeeDistribution,
+    ledger: Ledger,
+    /// State of every account loaded so far, as stored
+    stored: HashMap<Address, AccountState>,
+}
+
+impl<'a> StateValidator<'a> {
+    /// Validator charging fees as `fees` divides them
+    pub fn new(store: &'a dyn ValidationStore, fees: &'a FeeDistribution) -> Self {
+        Self { store, fees, ledger: Ledger::new(), stored: HashMap::new() }
+    }
+
+    /// Check `tx` after the transactions that passed before it. Fails only
+    /// when storage does; the inner result is the transaction's verdict.
+    pub async fn check(&mut self, tx: &Transaction) -> Result<std::result::Result<(), BlockchainError>> {
+        self.load(tx).await?;
+        let sender = self.ledger.account(&tx.sender());
+        Ok(check_account(tx, sender).and_then(|()| self.ledger.apply_transaction(tx, self.fees).map(|_| ())))
+    }
+
+    /// Gas `tx` needs if sent after the transactions that passed so far,
+    /// from a dry run that leaves them as they were. Fails only when
+    /// storage does; the inner result fails as `check` would.
+    pub async fn estimate_gas(&mut self, tx: &Transaction) -> Result<std::result::Result<u64, BlockchainError>> {
+        self.load(tx).await?;
+        Ok(self.ledger.estimate_gas(tx, self.fees))
+    }
+
+    /// Seed the ledger with the stored state of accounts `tx` touches
+    async fn load(&mut self, tx: &Transaction) -> Result<()> {
+        let touched = [Some(tx.sender()), tx.recipient(), self.fees.treasury];
+        for address in touched.into_iter().flatten() {
+            if !self.stored.contains_key(&address) {
+                let state = self.store.account(&address).await?;
+                self.ledger.set_account(address, state);
+                self.stored.insert(address, state);
+            }
+        }
+        Ok(())
+    }
+
+    /// Accounts the passing transactions changed, ordered by address
+    pub fn balance_changes(&self) -> Vec<BalanceChange> {
+        let mut changes: Vec<BalanceChange> = self
+            .stored
+            .iter()
+            .filter_map(|(address, old)| {
+                let new = self.ledger.account(address);
+                (new != *old).then_some(BalanceChange {
+                    address: *address,
+                    old_balance: old.balance,
+                    new_balance: new.balance,
+                    old_nonce: old.nonce,
+                    new_nonce: new.nonce,
+                })
+            })
+            .collect();
+        changes.sort_unstable_by_key(|change| change.address);
+        changes
+    }
+}
+
+/// Whether `sender`'s state lets it send `tx` next
+pub fn check_account(tx: &Transaction, sender: AccountState) -> std::result::Result<(), BlockchainError> {
+    if tx.nonce != sender.nonce {
+        return Err(BlockchainError::InvalidNonce { expected: sender.nonce, actual: tx.nonce });
+    }
+    let need = tx
+        .gas_limit
+        .checked_mul(tx.gas_price)
+        .and_then(|max_fee| max_fee.checked_add(tx.amount()))
+        .ok_or_else(|| BlockchainError::InvalidTransaction { reason: "Cost overflow".to_string() })?;
+    if sender.balance < need {
+        return Err(BlockchainError::InsufficientBalance { have: sender.balance, need });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use blockchain_core::TxHash;
+    use storage_traits::ValidationBatch;
+
+    struct Accounts(HashMap<Address, AccountState>);
+
+    #[async_trait]
+    impl ValidationStore for Accounts {
+        async fn claim_batches(&self, _validator_id: &str, _limit: i32) -> Result<Vec<ValidationBatch>> {
+            Ok(Vec::new())
+        }
+
+        async fn pending_transaction(&self, _tx_hash: &TxHash) -> Result<Option<Transaction>> {
+            Ok(None)
+        }
+
+        async fn account(&self, address: &Address) -> Result<AccountState> {
+            Ok(self.0.get(address).copied().unwrap_or_default())
+        }
+
+        async fn update_batch(&self, _batch: &ValidationBatch) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nonces_balances_and_changes() {
+        let (alice, bob) = ([1; 20], [2; 20]);
+        let store = Accounts(HashMap::from([(alice, AccountState { balance: 50_000, nonce: 7 })]));
+        let fees = FeeDistribution::default();
+        let mut validator = StateValidator::new(&store, &fees);
+        let transfer = |amount, nonce| Transaction::new_transfer(alice, bob, amount, nonce, 21_000, 1).unwrap();
+
+        assert!(validator.check(&transfer(1_000, 7)).await.unwrap().is_ok());
+        let reused = validator.check(&transfer(1_000, 7)).await.unwrap().unwrap_err();
+        assert!(matches!(reused, BlockchainError::InvalidNonce { expected: 8, actual: 7 }));
+        // 28_000 left, and the maximum fee alone is 21_000
+        let overspent = validator.check(&transfer(8_000, 8)).await.unwrap().unwrap_err();
+        assert!(matches!(overspent, BlockchainError::InsufficientBalance { have: 28_000, need: 29_000 }));
+
+        let changes = validator.balance_changes();
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].address, changes[0].new_balance, changes[0].new_nonce), (alice, 28_000, 8));
+        assert_eq!((changes[1].address, changes[1].old_balance, changes[1].new_balance), (bob, 0, 1_000));
+    }
+}